@@ -0,0 +1,101 @@
+/*
+ * C API of rpi-led-matrix's app loop, compositing and widgets.
+ *
+ * Link with -lrpi_led_matrix_capi. Functions returning int return 0 on success and -1 on
+ * failure, functions returning a pointer return NULL on failure. rlm_last_error() describes
+ * the last failure of the calling thread. Strings are UTF-8 and NUL-terminated, and stay
+ * owned by the caller.
+ */
+#ifndef RPI_LED_MATRIX_H
+#define RPI_LED_MATRIX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct rlm_app_builder rlm_app_builder;
+typedef struct rlm_app rlm_app;
+typedef struct rlm_control rlm_control;
+typedef struct rlm_canvas rlm_canvas;
+typedef struct rlm_font rlm_font;
+typedef struct rlm_surface rlm_surface;
+typedef struct rlm_ticker rlm_ticker;
+
+/* Draws a scene onto canvas, elapsed_ms milliseconds after it came on screen. */
+typedef void (*rlm_scene_fn)(rlm_canvas *canvas, uint64_t elapsed_ms, void *user_data);
+
+/* The last failure on this thread, valid until the next one. */
+const char *rlm_last_error(void);
+
+/* Building and running an app */
+rlm_app_builder *rlm_app_builder_new(void);
+void rlm_app_builder_free(rlm_app_builder *builder);
+int rlm_app_builder_panel(rlm_app_builder *builder, uint32_t rows, uint32_t cols,
+                          uint32_t chain_length, uint32_t parallel,
+                          const char *hardware_mapping);
+int rlm_app_builder_config(rlm_app_builder *builder, const char *path);
+int rlm_app_builder_frame_rate(rlm_app_builder *builder, uint32_t frames_per_second);
+int rlm_app_builder_scene(rlm_app_builder *builder, uint64_t duration_ms, rlm_scene_fn draw,
+                          void *user_data);
+int rlm_app_builder_control_socket(rlm_app_builder *builder, const char *address);
+/* Frees the builder, even if building fails. */
+rlm_app *rlm_app_build(rlm_app_builder *builder);
+void rlm_app_free(rlm_app *app);
+/* Blocks until the app quits, and frees it. */
+int rlm_app_run(rlm_app *app);
+
+/* Controlling a running app from any thread */
+rlm_control *rlm_app_control(const rlm_app *app);
+/* Commands like "brightness 50", "next", "cue <event>" or "quit". */
+int rlm_control_send(const rlm_control *control, const char *command);
+void rlm_control_free(rlm_control *control);
+
+/* Drawing on the canvas passed to scenes */
+void rlm_canvas_size(const rlm_canvas *canvas, int *width, int *height);
+void rlm_canvas_set_pixel(rlm_canvas *canvas, int x, int y, uint8_t red, uint8_t green,
+                          uint8_t blue);
+void rlm_canvas_fill(rlm_canvas *canvas, uint8_t red, uint8_t green, uint8_t blue);
+void rlm_canvas_clear(rlm_canvas *canvas);
+void rlm_canvas_draw_line(rlm_canvas *canvas, int x0, int y0, int x1, int y1, uint8_t red,
+                          uint8_t green, uint8_t blue);
+void rlm_canvas_draw_line_aa(rlm_canvas *canvas, float x0, float y0, float x1, float y1,
+                             uint8_t red, uint8_t green, uint8_t blue);
+void rlm_canvas_draw_circle(rlm_canvas *canvas, int x, int y, uint32_t radius, uint8_t red,
+                            uint8_t green, uint8_t blue);
+/* points holds count x and y pairs. */
+int rlm_canvas_fill_polygon(rlm_canvas *canvas, const int *points, size_t count, uint8_t red,
+                            uint8_t green, uint8_t blue);
+
+/* Text in BDF fonts, returning the width drawn or -1 */
+rlm_font *rlm_font_load(const char *path);
+void rlm_font_free(rlm_font *font);
+int rlm_canvas_draw_text(rlm_canvas *canvas, const rlm_font *font, const char *text, int x,
+                         int y, uint8_t red, uint8_t green, uint8_t blue);
+
+/* Surfaces with an alpha channel, composited onto the canvas */
+rlm_surface *rlm_surface_new(int width, int height);
+/* Only with the image feature. */
+rlm_surface *rlm_surface_open(const char *path);
+void rlm_surface_free(rlm_surface *surface);
+void rlm_surface_size(const rlm_surface *surface, int *width, int *height);
+void rlm_surface_set_pixel(rlm_surface *surface, int x, int y, uint8_t red, uint8_t green,
+                           uint8_t blue, uint8_t alpha);
+void rlm_canvas_draw_surface(rlm_canvas *canvas, const rlm_surface *surface, int x, int y);
+
+/* Widgets */
+int rlm_canvas_draw_seven_segment(rlm_canvas *canvas, const char *text, int x, int y,
+                                  int digit_width, int digit_height, uint8_t red,
+                                  uint8_t green, uint8_t blue);
+rlm_ticker *rlm_app_builder_ticker(rlm_app_builder *builder, uint64_t duration_ms,
+                                   const char *font_path);
+int rlm_ticker_set_items(const rlm_ticker *ticker, const char *const *items, size_t count);
+void rlm_ticker_free(rlm_ticker *ticker);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
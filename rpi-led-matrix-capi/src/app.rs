@@ -0,0 +1,262 @@
+//! Building and running a [`MatrixApp`], and controlling it from other threads.
+use libc::{c_char, c_int, c_void};
+use std::path::Path;
+use std::time::Duration;
+
+use rpi_led_matrix::{
+    AppCommand, AppControl, LedCanvas, LedMatrixOptions, LedRuntimeOptions, MatrixApp,
+    MatrixAppBuilder, Scene,
+};
+
+use crate::{boxed, catch, free, status, to_str};
+
+/// Draws a scene onto `canvas`, `elapsed_ms` milliseconds after it came on screen.
+pub type SceneFn = extern "C" fn(canvas: *mut LedCanvas, elapsed_ms: u64, user_data: *mut c_void);
+
+/// A scene drawn by a C function
+struct CScene {
+    draw: SceneFn,
+    user_data: *mut c_void,
+}
+
+// scenes are only drawn on the thread running the app, which C callers know their data is
+// used on
+unsafe impl Send for CScene {}
+
+impl Scene for CScene {
+    fn render(&mut self, canvas: &mut LedCanvas, elapsed: Duration) {
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        (self.draw)(canvas, elapsed_ms, self.user_data);
+    }
+}
+
+/// Replaces the builder behind `builder` by `change` applied to it.
+///
+/// # Safety
+/// `builder` must be `NULL` or come from [`rlm_app_builder_new`] and not be built or freed.
+pub(crate) unsafe fn change(
+    builder: *mut MatrixAppBuilder,
+    change: impl FnOnce(MatrixAppBuilder) -> MatrixAppBuilder,
+) -> Result<(), &'static str> {
+    let builder = builder.as_mut().ok_or("App builder is NULL")?;
+    let unchanged = std::mem::replace(builder, MatrixApp::builder());
+    *builder = change(unchanged);
+    Ok(())
+}
+
+/// Starts building an app running at 30 frames per second on a matrix with the default
+/// options. Free it with [`rlm_app_build`] or [`rlm_app_builder_free`].
+#[no_mangle]
+pub extern "C" fn rlm_app_builder_new() -> *mut MatrixAppBuilder {
+    catch(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(MatrixApp::builder()))
+    })
+}
+
+/// Frees a builder that wasn't built.
+///
+/// # Safety
+/// `builder` must be `NULL` or come from [`rlm_app_builder_new`] and not be built or freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_free(builder: *mut MatrixAppBuilder) {
+    catch((), || {
+        free(builder);
+    })
+}
+
+/// Sets the size of the panels and how they're connected, like the `--led-rows`,
+/// `--led-cols`, `--led-chain`, `--led-parallel` and `--led-gpio-mapping` flags of the C++
+/// library. `hardware_mapping` may be `NULL` for the default wiring.
+///
+/// # Safety
+/// `builder` must come from [`rlm_app_builder_new`], `hardware_mapping` must be `NULL` or a
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_panel(
+    builder: *mut MatrixAppBuilder,
+    rows: u32,
+    cols: u32,
+    chain_length: u32,
+    parallel: u32,
+    hardware_mapping: *const c_char,
+) -> c_int {
+    catch(-1, || {
+        let mapping = if hardware_mapping.is_null() {
+            Ok(None)
+        } else {
+            to_str(hardware_mapping).map(Some)
+        };
+        status(mapping.and_then(|mapping| {
+            let mut options = LedMatrixOptions::default();
+            options.set_rows(rows);
+            options.set_cols(cols);
+            options.set_chain_length(chain_length);
+            options.set_parallel(parallel);
+            if let Some(mapping) = mapping {
+                options.set_hardware_mapping(mapping);
+            }
+            change(builder, |builder| {
+                builder.options(options, LedRuntimeOptions::default())
+            })
+        }))
+    })
+}
+
+/// Reads the panel options from a config file, if it exists, see
+/// [`MatrixAppBuilder::config`].
+///
+/// # Safety
+/// `builder` must come from [`rlm_app_builder_new`], `path` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_config(
+    builder: *mut MatrixAppBuilder,
+    path: *const c_char,
+) -> c_int {
+    catch(-1, || {
+        status(
+            to_str(path)
+                .and_then(|path| change(builder, |builder| builder.config(Path::new(path)))),
+        )
+    })
+}
+
+/// Sets the frames drawn per second, 30 by default.
+///
+/// # Safety
+/// `builder` must come from [`rlm_app_builder_new`].
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_frame_rate(
+    builder: *mut MatrixAppBuilder,
+    frames_per_second: u32,
+) -> c_int {
+    catch(-1, || {
+        status(change(builder, |builder| {
+            builder.frame_rate(frames_per_second)
+        }))
+    })
+}
+
+/// Adds a scene shown for `duration_ms` milliseconds per rotation, drawn by calling `draw`
+/// with `user_data` every frame on the thread running the app.
+///
+/// # Safety
+/// `builder` must come from [`rlm_app_builder_new`], `user_data` must stay valid while the
+/// app exists.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_scene(
+    builder: *mut MatrixAppBuilder,
+    duration_ms: u64,
+    draw: Option<SceneFn>,
+    user_data: *mut c_void,
+) -> c_int {
+    catch(-1, || {
+        let Some(draw) = draw else {
+            return status(Err("Scene function is NULL"));
+        };
+        let scene = CScene { draw, user_data };
+        let duration = Duration::from_millis(duration_ms);
+        status(change(builder, |builder| builder.scene(duration, scene)))
+    })
+}
+
+/// Listens for commands on a TCP address like `127.0.0.1:7000`, see
+/// [`MatrixAppBuilder::control_socket`].
+///
+/// # Safety
+/// `builder` must come from [`rlm_app_builder_new`], `address` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_control_socket(
+    builder: *mut MatrixAppBuilder,
+    address: *const c_char,
+) -> c_int {
+    catch(-1, || {
+        status(
+            to_str(address)
+                .and_then(|address| change(builder, |builder| builder.control_socket(address))),
+        )
+    })
+}
+
+/// Creates the matrix and the app, freeing the builder even if that fails.
+///
+/// # Safety
+/// `builder` must be `NULL` or come from [`rlm_app_builder_new`] and not be built or freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_build(builder: *mut MatrixAppBuilder) -> *mut MatrixApp {
+    catch(std::ptr::null_mut(), || {
+        if builder.is_null() {
+            return boxed(Err("App builder is NULL"));
+        }
+        boxed(Box::from_raw(builder).build())
+    })
+}
+
+/// Frees an app that wasn't run.
+///
+/// # Safety
+/// `app` must be `NULL` or come from [`rlm_app_build`] and not be run or freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_free(app: *mut MatrixApp) {
+    catch((), || {
+        free(app);
+    })
+}
+
+/// Returns a handle sending commands to the app from any thread, free it with
+/// [`rlm_control_free`].
+///
+/// # Safety
+/// `app` must come from [`rlm_app_build`] and not be run or freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_control(app: *const MatrixApp) -> *mut AppControl {
+    catch(std::ptr::null_mut(), || {
+        boxed(app.as_ref().map(MatrixApp::control).ok_or("App is NULL"))
+    })
+}
+
+/// Runs the app until it's told to quit, `SIGINT` or `SIGTERM`, and frees it.
+///
+/// # Safety
+/// `app` must be `NULL` or come from [`rlm_app_build`] and not be run or freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_run(app: *mut MatrixApp) -> c_int {
+    catch(-1, || {
+        if app.is_null() {
+            return status(Err("App is NULL"));
+        }
+        status(Box::from_raw(app).run())
+    })
+}
+
+/// Sends a command line to the app, like the control socket accepts, see
+/// [`AppCommand::parse`].
+///
+/// # Safety
+/// `control` must come from [`rlm_app_control`], `command` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_control_send(
+    control: *const AppControl,
+    command: *const c_char,
+) -> c_int {
+    catch(-1, || {
+        let Some(control) = control.as_ref() else {
+            return status(Err("Control is NULL"));
+        };
+        status(
+            to_str(command)
+                .and_then(AppCommand::parse)
+                .map(|command| control.send(command)),
+        )
+    })
+}
+
+/// Frees a control handle.
+///
+/// # Safety
+/// `control` must be `NULL` or come from [`rlm_app_control`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_control_free(control: *mut AppControl) {
+    catch((), || {
+        free(control);
+    })
+}
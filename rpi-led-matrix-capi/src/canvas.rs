@@ -0,0 +1,238 @@
+//! Drawing on the canvas passed to scenes, and loading fonts for it.
+//!
+//! Drawing functions ignore a `NULL` canvas and pixels off the canvas, like the C++ library.
+use libc::{c_char, c_int, size_t};
+use std::path::Path;
+
+use rpi_led_matrix::{LedCanvas, LedColor, LedFont, TextDrawOptions};
+
+use crate::{boxed, catch, free, status, to_str};
+
+/// Gets the width and height of the canvas in pixels, as seen through its transform.
+///
+/// # Safety
+/// `canvas` must be a canvas passed to a scene, `width` and `height` must be `NULL` or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_size(
+    canvas: *const LedCanvas,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    catch((), || {
+        let (w, h) = canvas.as_ref().map_or((0, 0), LedCanvas::canvas_size);
+        if let Some(width) = width.as_mut() {
+            *width = w;
+        }
+        if let Some(height) = height.as_mut() {
+            *height = h;
+        }
+    })
+}
+
+/// Sets the pixel at (`x`, `y`).
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_set_pixel(
+    canvas: *mut LedCanvas,
+    x: c_int,
+    y: c_int,
+    red: u8,
+    green: u8,
+    blue: u8,
+) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.set(x, y, &LedColor { red, green, blue });
+        }
+    })
+}
+
+/// Fills the canvas, or its clip rectangle, with a color.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_fill(canvas: *mut LedCanvas, red: u8, green: u8, blue: u8) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.fill(&LedColor { red, green, blue });
+        }
+    })
+}
+
+/// Turns all pixels of the canvas, or its clip rectangle, off.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_clear(canvas: *mut LedCanvas) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.clear();
+        }
+    })
+}
+
+/// Draws a one pixel wide line.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_line(
+    canvas: *mut LedCanvas,
+    x0: c_int,
+    y0: c_int,
+    x1: c_int,
+    y1: c_int,
+    red: u8,
+    green: u8,
+    blue: u8,
+) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.draw_line(x0, y0, x1, y1, &LedColor { red, green, blue });
+        }
+    })
+}
+
+/// Draws an anti-aliased line, blending it into the pixels below.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_line_aa(
+    canvas: *mut LedCanvas,
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    red: u8,
+    green: u8,
+    blue: u8,
+) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.draw_line_aa(x0, y0, x1, y1, &LedColor { red, green, blue });
+        }
+    })
+}
+
+/// Draws a one pixel wide circle.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_circle(
+    canvas: *mut LedCanvas,
+    x: c_int,
+    y: c_int,
+    radius: u32,
+    red: u8,
+    green: u8,
+    blue: u8,
+) {
+    catch((), || {
+        if let Some(canvas) = canvas.as_mut() {
+            canvas.draw_circle(x, y, radius, &LedColor { red, green, blue });
+        }
+    })
+}
+
+/// Fills the polygon through `count` points, given as x and y pairs in `points`.
+///
+/// Fails if `points` is `NULL` or `count` is too large to address.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene, `points` must hold `2 * count`
+/// integers.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_fill_polygon(
+    canvas: *mut LedCanvas,
+    points: *const c_int,
+    count: size_t,
+    red: u8,
+    green: u8,
+    blue: u8,
+) -> c_int {
+    catch(-1, || {
+        let Some(canvas) = canvas.as_mut() else {
+            return 0;
+        };
+        let length = count
+            .checked_mul(2)
+            .filter(|&length| length <= isize::MAX as usize / std::mem::size_of::<c_int>());
+        let Some(length) = length else {
+            return status(Err("Too many points"));
+        };
+        if points.is_null() && length > 0 {
+            return status(Err("Points are NULL"));
+        }
+        let points: Vec<_> = if length == 0 {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(points, length)
+                .chunks_exact(2)
+                .map(|point| (point[0], point[1]))
+                .collect()
+        };
+        canvas.fill_polygon(&points, &LedColor { red, green, blue });
+        0
+    })
+}
+
+/// Loads a font from a BDF file, free it with [`rlm_font_free`].
+///
+/// # Safety
+/// `path` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_font_load(path: *const c_char) -> *mut LedFont {
+    catch(std::ptr::null_mut(), || {
+        boxed(to_str(path).and_then(|path| LedFont::new(Path::new(path))))
+    })
+}
+
+/// Frees a font.
+///
+/// # Safety
+/// `font` must be `NULL` or come from [`rlm_font_load`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_font_free(font: *mut LedFont) {
+    catch((), || {
+        free(font);
+    })
+}
+
+/// Draws `text` with its baseline starting at (`x`, `y`), returning its width in pixels, or
+/// -1 if it isn't drawn.
+///
+/// # Safety
+/// `canvas` must be a canvas passed to a scene, `font` must come from [`rlm_font_load`],
+/// `text` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_text(
+    canvas: *mut LedCanvas,
+    font: *const LedFont,
+    text: *const c_char,
+    x: c_int,
+    y: c_int,
+    red: u8,
+    green: u8,
+    blue: u8,
+) -> c_int {
+    catch(-1, || {
+        let (Some(canvas), Some(font)) = (canvas.as_mut(), font.as_ref()) else {
+            return status(Err("Canvas or font is NULL"));
+        };
+        match to_str(text) {
+            Ok(text) => {
+                let color = LedColor { red, green, blue };
+                let options = TextDrawOptions::new().position(x, y).color(&color);
+                canvas.draw_text(font, text, &options)
+            }
+            Err(error) => status(Err(error)),
+        }
+    })
+}
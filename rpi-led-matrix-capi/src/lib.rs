@@ -0,0 +1,184 @@
+//! C API of [rpi-led-matrix](https://docs.rs/rpi-led-matrix/), so C programs, and Python
+//! through `ctypes` or `cffi`, can use its app loop, compositing and widgets.
+//!
+//! Built as `librpi_led_matrix_capi.so` and `librpi_led_matrix_capi.a`, declared in
+//! `include/rpi_led_matrix.h`. All functions are prefixed `rlm_`, the types behind the
+//! pointers are opaque to C.
+//!
+//! ```c
+//! #include "rpi_led_matrix.h"
+//!
+//! static void draw(rlm_canvas *canvas, uint64_t elapsed_ms, void *user_data) {
+//!     int width, height;
+//!     rlm_canvas_size(canvas, &width, &height);
+//!     rlm_canvas_fill(canvas, 0, 0, 64);
+//!     rlm_canvas_draw_line(canvas, 0, 0, width - 1, height - 1, 255, 255, 255);
+//! }
+//!
+//! int main(void) {
+//!     rlm_app_builder *builder = rlm_app_builder_new();
+//!     rlm_app_builder_config(builder, "/etc/sign/matrix.conf");
+//!     rlm_app_builder_scene(builder, 10000, draw, NULL);
+//!     rlm_app *app = rlm_app_build(builder);
+//!     if (app == NULL || rlm_app_run(app) != 0) {
+//!         fprintf(stderr, "%s\n", rlm_last_error());
+//!         return 1;
+//!     }
+//!     return 0;
+//! }
+//! ```
+//!
+//! # Conventions
+//!
+//! Functions returning `int` return 0 on success and -1 on failure, functions returning a
+//! pointer return `NULL` on failure, also when the Rust code panics. [`rlm_last_error`]
+//! describes the last failure of the calling thread. Strings are UTF-8 and NUL-terminated, and stay owned by the caller.
+//! Pointers passed in must come from the matching `rlm_` function and not be freed yet.
+//!
+//! # Features
+//!
+//! ## `image`
+//!
+//! Enables [`rlm_surface_open`](surface::rlm_surface_open), loading surfaces from image files.
+//!
+//! ## `c-stubs`
+//!
+//! Passthrough argument to [`rpi-led-matrix`](rpi_led_matrix), to build without the C++
+//! library.
+use libc::c_char;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+
+#[deny(missing_docs)]
+pub mod app;
+#[deny(missing_docs)]
+pub mod canvas;
+#[deny(missing_docs)]
+pub mod surface;
+#[deny(missing_docs)]
+pub mod widgets;
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Remembers `error` as the last error of this thread.
+fn set_error(error: &'static str) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(error).unwrap_or_default());
+}
+
+/// Runs the body of a function called from C, so a panic doesn't unwind into the caller.
+/// A panic is remembered as the last error, and `failed` returned instead.
+fn catch<T>(failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| {
+        set_error("Panicked inside rpi-led-matrix");
+        failed
+    })
+}
+
+/// Returns 0 for `Ok`, remembering the error and returning -1 otherwise.
+fn status(result: Result<(), &'static str>) -> libc::c_int {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            set_error(error);
+            -1
+        }
+    }
+}
+
+/// Boxes the value of `Ok` for C, remembering the error and returning `NULL` otherwise.
+fn boxed<T>(result: Result<T, &'static str>) -> *mut T {
+    match result {
+        Ok(value) => Box::into_raw(Box::new(value)),
+        Err(error) => {
+            set_error(error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Borrows a string passed from C.
+///
+/// # Safety
+/// `text` must be `NULL` or point to a NUL-terminated string outliving `'a`.
+unsafe fn to_str<'a>(text: *const c_char) -> Result<&'a str, &'static str> {
+    if text.is_null() {
+        return Err("String is NULL");
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|_| "String isn't UTF-8")
+}
+
+/// Frees a value boxed for C, ignoring `NULL`.
+///
+/// # Safety
+/// `value` must be `NULL` or come from [`boxed`] and not be freed yet.
+unsafe fn free<T>(value: *mut T) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// Describes the last failure of a function called on this thread, or returns an empty
+/// string. The string stays valid until the next failure on this thread.
+#[no_mangle]
+pub extern "C" fn rlm_last_error() -> *const c_char {
+    catch(c"".as_ptr(), || {
+        LAST_ERROR.with(|last| last.borrow().as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::app::*;
+    use super::canvas::*;
+    use super::surface::*;
+    use super::*;
+
+    #[test]
+    fn errors() {
+        let error = || {
+            unsafe { CStr::from_ptr(rlm_last_error()) }
+                .to_str()
+                .unwrap()
+        };
+        assert_eq!(error(), "");
+        let address = CString::new("no address").unwrap();
+        unsafe {
+            let builder = rlm_app_builder_new();
+            rlm_app_builder_frame_rate(builder, 60);
+            assert_eq!(
+                rlm_app_builder_control_socket(builder, std::ptr::null()),
+                -1
+            );
+            assert_eq!(error(), "String is NULL");
+            assert_eq!(rlm_app_builder_control_socket(builder, address.as_ptr()), 0);
+            assert!(rlm_app_build(builder).is_null());
+            assert_eq!(error(), "Couldn't bind control socket");
+
+            let surface = rlm_surface_new(2, 2);
+            assert!(!surface.is_null());
+            rlm_surface_set_pixel(surface, 1, 1, 255, 0, 0, 128);
+            rlm_surface_free(surface);
+
+            let mut canvas = rpi_led_matrix::LedCanvas::headless(4, 4);
+            let points = [0, 0, 3, 0, 3, 3];
+            assert_eq!(
+                rlm_canvas_fill_polygon(&mut canvas, points.as_ptr(), 3, 0, 0, 255),
+                0
+            );
+            assert_eq!(canvas.get(2, 1).map(|color| color.blue), Some(255));
+            let count = usize::MAX;
+            assert_eq!(
+                rlm_canvas_fill_polygon(&mut canvas, points.as_ptr(), count, 0, 0, 0),
+                -1
+            );
+            assert_eq!(error(), "Too many points");
+        }
+        assert_eq!(catch(-1, || panic!("drawing failed")), -1);
+        assert_eq!(error(), "Panicked inside rpi-led-matrix");
+    }
+}
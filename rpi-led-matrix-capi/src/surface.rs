@@ -0,0 +1,112 @@
+//! Surfaces with an alpha channel, composited onto the canvas.
+#[cfg(feature = "image")]
+use libc::c_char;
+use libc::c_int;
+
+use rpi_led_matrix::{LedCanvas, LedColorA, Surface};
+
+#[cfg(feature = "image")]
+use crate::{boxed, to_str};
+use crate::{catch, free};
+
+/// Creates a transparent surface of the given size, free it with [`rlm_surface_free`].
+#[no_mangle]
+pub extern "C" fn rlm_surface_new(width: c_int, height: c_int) -> *mut Surface {
+    catch(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(Surface::new(width, height)))
+    })
+}
+
+/// Loads an image file with its alpha channel, see [`Surface::open`].
+///
+/// # Safety
+/// `path` must be a string.
+#[cfg(feature = "image")]
+#[no_mangle]
+pub unsafe extern "C" fn rlm_surface_open(path: *const c_char) -> *mut Surface {
+    catch(std::ptr::null_mut(), || {
+        boxed(to_str(path).and_then(|path| Surface::open(std::path::Path::new(path))))
+    })
+}
+
+/// Frees a surface.
+///
+/// # Safety
+/// `surface` must be `NULL` or come from [`rlm_surface_new`] or `rlm_surface_open` and not be
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_surface_free(surface: *mut Surface) {
+    catch((), || {
+        free(surface);
+    })
+}
+
+/// Gets the width and height of the surface in pixels.
+///
+/// # Safety
+/// `surface` must come from [`rlm_surface_new`] or `rlm_surface_open`, `width` and `height`
+/// must be `NULL` or writable.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_surface_size(
+    surface: *const Surface,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    catch((), || {
+        let (w, h) = surface.as_ref().map_or((0, 0), Surface::size);
+        if let Some(width) = width.as_mut() {
+            *width = w;
+        }
+        if let Some(height) = height.as_mut() {
+            *height = h;
+        }
+    })
+}
+
+/// Sets the pixel at (`x`, `y`) of the surface, `alpha` from 0 for transparent to 255 for
+/// opaque.
+///
+/// # Safety
+/// `surface` must be `NULL` or come from [`rlm_surface_new`] or `rlm_surface_open`.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_surface_set_pixel(
+    surface: *mut Surface,
+    x: c_int,
+    y: c_int,
+    red: u8,
+    green: u8,
+    blue: u8,
+    alpha: u8,
+) {
+    catch((), || {
+        if let Some(surface) = surface.as_mut() {
+            let color = LedColorA {
+                red,
+                green,
+                blue,
+                alpha,
+            };
+            surface.set(x, y, color);
+        }
+    })
+}
+
+/// Draws `surface` onto the canvas with its top left corner at (`x`, `y`), blending it over
+/// what's on the canvas by its alpha.
+///
+/// # Safety
+/// `canvas` must be `NULL` or a canvas passed to a scene, `surface` must be `NULL` or come
+/// from [`rlm_surface_new`] or `rlm_surface_open`.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_surface(
+    canvas: *mut LedCanvas,
+    surface: *const Surface,
+    x: c_int,
+    y: c_int,
+) {
+    catch((), || {
+        if let (Some(canvas), Some(surface)) = (canvas.as_mut(), surface.as_ref()) {
+            canvas.draw_surface(surface, x, y);
+        }
+    })
+}
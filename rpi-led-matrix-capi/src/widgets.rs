@@ -0,0 +1,114 @@
+//! Ready-made widgets: seven segment digits and a scrolling ticker fed from C.
+use libc::{c_char, c_int, size_t};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rpi_led_matrix::widgets::ticker::{Ticker, TickerHandle, TickerItem};
+use rpi_led_matrix::widgets::{SevenSegment, SharedTheme, Theme};
+use rpi_led_matrix::{LedCanvas, LedColor, LedFont, MatrixAppBuilder};
+
+use crate::{app, boxed, catch, free, status, to_str};
+
+/// Draws `text` as seven segment digits of the given size with their top left corner at
+/// (`x`, `y`), returning the width drawn, or -1 if `text` isn't a string. Characters
+/// without segments leave a gap.
+///
+/// # Safety
+/// `canvas` must be a canvas passed to a scene, `text` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_canvas_draw_seven_segment(
+    canvas: *mut LedCanvas,
+    text: *const c_char,
+    x: c_int,
+    y: c_int,
+    digit_width: c_int,
+    digit_height: c_int,
+    red: u8,
+    green: u8,
+    blue: u8,
+) -> c_int {
+    catch(-1, || {
+        let Some(canvas) = canvas.as_mut() else {
+            return status(Err("Canvas is NULL"));
+        };
+        match to_str(text) {
+            Ok(text) => SevenSegment::new(digit_width, digit_height)
+                .color(LedColor { red, green, blue })
+                .draw(canvas, x, y, text),
+            Err(error) => status(Err(error)),
+        }
+    })
+}
+
+/// Adds a ticker scrolling its items in the font of a BDF file as a scene shown for
+/// `duration_ms` milliseconds per rotation, in the colors of the day theme.
+///
+/// Returns a handle to set the items from any thread with [`rlm_ticker_set_items`], free it
+/// with [`rlm_ticker_free`].
+///
+/// # Safety
+/// `builder` must come from `rlm_app_builder_new`, `font_path` must be a string.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_app_builder_ticker(
+    builder: *mut MatrixAppBuilder,
+    duration_ms: u64,
+    font_path: *const c_char,
+) -> *mut TickerHandle {
+    catch(std::ptr::null_mut(), || {
+        boxed(
+            to_str(font_path)
+                .and_then(|path| LedFont::new(Path::new(path)))
+                .and_then(|font| {
+                    let theme = Theme::day().with_font(Theme::DEFAULT_FONT, Arc::new(font));
+                    let ticker = Ticker::new(SharedTheme::new(theme));
+                    let handle = ticker.handle();
+                    let duration = Duration::from_millis(duration_ms);
+                    app::change(builder, |builder| builder.scene(duration, ticker))?;
+                    Ok(handle)
+                }),
+        )
+    })
+}
+
+/// Replaces the items of the ticker by `count` strings, shown once the current loop has
+/// scrolled by.
+///
+/// # Safety
+/// `ticker` must come from [`rlm_app_builder_ticker`], `items` must hold `count` strings.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_ticker_set_items(
+    ticker: *const TickerHandle,
+    items: *const *const c_char,
+    count: size_t,
+) -> c_int {
+    catch(-1, || {
+        let Some(ticker) = ticker.as_ref() else {
+            return status(Err("Ticker is NULL"));
+        };
+        if items.is_null() && count > 0 {
+            return status(Err("Items are NULL"));
+        }
+        let items = if count == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(items, count)
+        };
+        let items: Result<Vec<_>, _> = items
+            .iter()
+            .map(|&item| to_str(item).map(TickerItem::new))
+            .collect();
+        status(items.map(|items| ticker.set_items(items)))
+    })
+}
+
+/// Frees a ticker handle, the ticker keeps showing its items.
+///
+/// # Safety
+/// `ticker` must be `NULL` or come from [`rlm_app_builder_ticker`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_ticker_free(ticker: *mut TickerHandle) {
+    catch((), || {
+        free(ticker);
+    })
+}
@@ -0,0 +1,334 @@
+use alloc::vec::Vec;
+
+use crate::raster;
+use crate::{CoordinateSpace, LedColor, Rect};
+
+/// A surface to draw on, implemented by the canvases and frames of `rpi-led-matrix`.
+///
+/// Implementors only provide reading and writing single pixels, all shapes are drawn on top
+/// of that. Effects and pixel based widgets accept any canvas, so they can draw onto other
+/// backends and buffers, like the frame buffer of a microcontroller driving LED strips.
+///
+/// ```
+/// use rpi_led_matrix_core::{Canvas, LedColor};
+/// struct Strip([LedColor; 64]);
+/// impl Canvas for Strip {
+///     fn canvas_size(&self) -> (i32, i32) {
+///         (8, 8)
+///     }
+///     fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+///         let inside = (0..8).contains(&x) && (0..8).contains(&y);
+///         inside.then(|| self.0[(y * 8 + x) as usize])
+///     }
+///     fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+///         if (0..8).contains(&x) && (0..8).contains(&y) {
+///             self.0[(y * 8 + x) as usize] = *color;
+///         }
+///     }
+/// }
+/// let black = LedColor { red: 0, green: 0, blue: 0 };
+/// let mut strip = Strip([black; 64]);
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// strip.draw_polygon(&[(0, 0), (7, 0), (7, 7), (0, 7)], &red);
+/// assert_eq!(strip.0[7].red, 255);
+/// assert_eq!(strip.0[9], black);
+/// ```
+pub trait Canvas {
+    /// The width and height in pixels
+    fn canvas_size(&self) -> (i32, i32);
+
+    /// The color of the pixel at the given coordinate, if it is on the canvas
+    fn get(&self, x: i32, y: i32) -> Option<LedColor>;
+
+    /// Sets the pixel at the given coordinate, ignoring pixels off the canvas.
+    fn set(&mut self, x: i32, y: i32, color: &LedColor);
+
+    /// Sets many pixels in one batch.
+    fn set_iter(&mut self, pixels: impl IntoIterator<Item = (i32, i32, LedColor)>) {
+        for (x, y, color) in pixels {
+            self.set(x, y, &color);
+        }
+    }
+
+    /// Computes the color of every pixel with `shader`.
+    fn shade(&mut self, mut shader: impl FnMut(i32, i32) -> LedColor) {
+        let (width, height) = self.canvas_size();
+        for y in 0..height {
+            for x in 0..width {
+                self.set(x, y, &shader(x, y));
+            }
+        }
+    }
+
+    /// Turns all pixels off.
+    fn clear(&mut self) {
+        self.fill(&LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        });
+    }
+
+    /// Sets all pixels to the given color.
+    fn fill(&mut self, color: &LedColor) {
+        self.shade(|_, _| *color);
+    }
+
+    /// The region at (`x`, `y`) of the given size as a canvas of its own, with its origin in
+    /// the top left corner of the region. The region is cut off at the edges of this canvas.
+    fn view(&mut self, x: i32, y: i32, width: i32, height: i32) -> View<'_, Self> {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        let width = width.min(canvas_width - x).max(0);
+        let height = height.min(canvas_height - y).max(0);
+        View {
+            canvas: self,
+            region: Rect::new(x, y, width, height),
+        }
+    }
+
+    /// Draws in the logical units of `space` instead of pixels, e.g. with y growing upwards.
+    fn plot(&mut self, space: CoordinateSpace) -> Plot<'_, Self> {
+        Plot {
+            canvas: self,
+            space,
+        }
+    }
+
+    /// Draws a straight, one pixel wide line.
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor) {
+        raster::line(x0, y0, x1, y1, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws a line of the given width in pixels, with round ends.
+    fn draw_line_thick(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        width: u32,
+        color: &LedColor,
+    ) {
+        raster::thick_line(x0, y0, x1, y1, width, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws connected lines through all `points`, with the given width in pixels.
+    fn draw_polyline(&mut self, points: &[(i32, i32)], width: u32, color: &LedColor) {
+        if let [(x, y)] = *points {
+            self.draw_line_thick(x, y, x, y, width, color);
+        }
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            self.draw_line_thick(x0, y0, x1, y1, width, color);
+        }
+    }
+
+    /// Draws a one pixel wide arc of a circle between two angles in degrees, clockwise from
+    /// 3 o'clock.
+    fn draw_arc(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        start_angle: f32,
+        end_angle: f32,
+        color: &LedColor,
+    ) {
+        raster::arc(x, y, radius as i32, start_angle, end_angle, |px, py| {
+            self.set(px, py, color);
+        });
+    }
+
+    /// Draws the one pixel wide outline of the polygon through `points`.
+    fn draw_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        raster::polygon(points, |x, y| self.set(x, y, color));
+    }
+
+    /// Fills the polygon through `points`, including its outline.
+    fn fill_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        raster::fill_polygon(points, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws a one pixel wide circle.
+    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
+        raster::circle(x, y, radius as i32, |px, py| self.set(px, py, color));
+    }
+
+    /// Draws an anti-aliased line, blending it into the pixels below.
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        raster::line_aa(x0, y0, x1, y1, |x, y, coverage| {
+            if let Some(background) = self.get(x, y) {
+                self.set(x, y, &background.lerp(color, coverage));
+            }
+        });
+    }
+
+    /// Draws an anti-aliased circle, blending it into the pixels below.
+    fn draw_circle_aa(&mut self, x: f32, y: f32, radius: f32, color: &LedColor) {
+        raster::circle_aa(x, y, radius, |px, py, coverage| {
+            if let Some(background) = self.get(px, py) {
+                self.set(px, py, &background.lerp(color, coverage));
+            }
+        });
+    }
+}
+
+/// A region of another canvas, drawing in coordinates relative to its top left corner and
+/// clipping everything outside of it. Created by [`Canvas::view`].
+///
+/// ```
+/// use rpi_led_matrix_core::{Canvas, LedColor};
+/// # struct Strip([LedColor; 16]);
+/// # impl Canvas for Strip {
+/// #     fn canvas_size(&self) -> (i32, i32) {
+/// #         (16, 1)
+/// #     }
+/// #     fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+/// #         ((0..16).contains(&x) && y == 0).then(|| self.0[x as usize])
+/// #     }
+/// #     fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+/// #         if (0..16).contains(&x) && y == 0 {
+/// #             self.0[x as usize] = *color;
+/// #         }
+/// #     }
+/// # }
+/// let black = LedColor { red: 0, green: 0, blue: 0 };
+/// let mut strip = Strip([black; 16]);
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// strip.view(4, 0, 8, 1).fill(&red);
+/// assert_eq!(strip.0.iter().filter(|&&pixel| pixel == red).count(), 8);
+/// assert_eq!(strip.0[3], black);
+/// ```
+#[derive(Debug)]
+pub struct View<'a, C: ?Sized> {
+    canvas: &'a mut C,
+    region: Rect,
+}
+
+impl<C: Canvas + ?Sized> Canvas for View<'_, C> {
+    fn canvas_size(&self) -> (i32, i32) {
+        (self.region.width, self.region.height)
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        let (left, top) = (self.region.x, self.region.y);
+        let inside = self.region.contains(left + x, top + y);
+        inside.then(|| self.canvas.get(left + x, top + y)).flatten()
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        let (left, top) = (self.region.x, self.region.y);
+        if self.region.contains(left + x, top + y) {
+            self.canvas.set(left + x, top + y, color);
+        }
+    }
+
+    fn set_iter(&mut self, pixels: impl IntoIterator<Item = (i32, i32, LedColor)>) {
+        let region = self.region;
+        self.canvas.set_iter(
+            pixels
+                .into_iter()
+                .map(|(x, y, color)| (region.x + x, region.y + y, color))
+                .filter(|&(x, y, _)| region.contains(x, y)),
+        );
+    }
+}
+
+/// A canvas drawn on in the units of a [`CoordinateSpace`], mapping every coordinate onto the
+/// nearest pixel. Created by [`Canvas::plot`].
+///
+/// Widths stay in pixels. The mapped pixels go through the canvas as any other draw call, so
+/// its views, clipping and transform apply.
+///
+/// ```
+/// use rpi_led_matrix_core::{Canvas, CoordinateSpace, LedColor, Rect};
+/// # struct Panel([[LedColor; 65]; 33]);
+/// # impl Canvas for Panel {
+/// #     fn canvas_size(&self) -> (i32, i32) {
+/// #         (65, 33)
+/// #     }
+/// #     fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+/// #         self.0.get(y as usize)?.get(x as usize).copied()
+/// #     }
+/// #     fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+/// #         let row = self.0.get_mut(y as usize);
+/// #         if let Some(pixel) = row.and_then(|row| row.get_mut(x as usize)) {
+/// #             *pixel = *color;
+/// #         }
+/// #     }
+/// # }
+/// let black = LedColor { red: 0, green: 0, blue: 0 };
+/// let mut panel = Panel([[black; 65]; 33]);
+/// let space = CoordinateSpace::new(Rect::new(0, 0, 65, 33), (0.0, 6.4), (-1.0, 1.0));
+/// let sine: Vec<_> = (0..=64).map(|i| i as f32 / 10.0).map(|x| (x, x.sin())).collect();
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// panel.plot(space).draw_polyline(&sine, 1, &red);
+/// assert_eq!(panel.0[16][0], red);
+/// assert_eq!(panel.0[0][16], red);
+/// ```
+#[derive(Debug)]
+pub struct Plot<'a, C: ?Sized> {
+    canvas: &'a mut C,
+    space: CoordinateSpace,
+}
+
+impl<C: Canvas + ?Sized> Plot<'_, C> {
+    /// The space coordinates are given in
+    #[must_use]
+    pub const fn space(&self) -> CoordinateSpace {
+        self.space
+    }
+
+    /// The color of the pixel at the logical coordinate, if it is on the canvas
+    #[must_use]
+    pub fn get(&self, x: f32, y: f32) -> Option<LedColor> {
+        let (x, y) = self.space.map(x, y);
+        self.canvas.get(x, y)
+    }
+
+    /// Sets the pixel at the logical coordinate.
+    pub fn set(&mut self, x: f32, y: f32, color: &LedColor) {
+        let (x, y) = self.space.map(x, y);
+        self.canvas.set(x, y, color);
+    }
+
+    /// Computes the color of every pixel of the space's region with `shader`, called with
+    /// the logical coordinate of the pixel's center.
+    pub fn shade(&mut self, mut shader: impl FnMut(f32, f32) -> LedColor) {
+        let region = self.space.region();
+        for y in region.y..region.y + region.height {
+            for x in region.x..region.x + region.width {
+                let (unit_x, unit_y) = self.space.unmap(x, y);
+                self.canvas.set(x, y, &shader(unit_x, unit_y));
+            }
+        }
+    }
+
+    /// Draws a straight, one pixel wide line.
+    pub fn draw_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        let ((x0, y0), (x1, y1)) = (self.space.map(x0, y0), self.space.map(x1, y1));
+        self.canvas.draw_line(x0, y0, x1, y1, color);
+    }
+
+    /// Draws connected lines through all `points`, with the given width in pixels.
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)], width: u32, color: &LedColor) {
+        let points = self.map_all(points);
+        self.canvas.draw_polyline(&points, width, color);
+    }
+
+    /// Draws the one pixel wide outline of the polygon through `points`.
+    pub fn draw_polygon(&mut self, points: &[(f32, f32)], color: &LedColor) {
+        let points = self.map_all(points);
+        self.canvas.draw_polygon(&points, color);
+    }
+
+    /// Fills the polygon through `points`, including its outline.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], color: &LedColor) {
+        let points = self.map_all(points);
+        self.canvas.fill_polygon(&points, color);
+    }
+
+    fn map_all(&self, points: &[(f32, f32)]) -> Vec<(i32, i32)> {
+        points.iter().map(|&(x, y)| self.space.map(x, y)).collect()
+    }
+}
@@ -0,0 +1,463 @@
+//! Animated full-canvas effects for demos, burn-in tests and screensavers.
+//!
+//! Every effect keeps its own state and draws the next frame over the whole canvas, or the
+//! active viewport, with `step`.
+//!
+//! ```
+//! use rpi_led_matrix_core::effects::Plasma;
+//! use rpi_led_matrix_core::{Canvas, LedColor};
+//! struct Strip([LedColor; 16]);
+//! impl Canvas for Strip {
+//!     fn canvas_size(&self) -> (i32, i32) {
+//!         (16, 1)
+//!     }
+//!     fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+//!         (y == 0 && (0..16).contains(&x)).then(|| self.0[x as usize])
+//!     }
+//!     fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+//!         if y == 0 && (0..16).contains(&x) {
+//!             self.0[x as usize] = *color;
+//!         }
+//!     }
+//! }
+//! let mut strip = Strip([LedColor { red: 0, green: 0, blue: 0 }; 16]);
+//! let mut plasma = Plasma::new();
+//! plasma.step(&mut strip);
+//! ```
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::TAU;
+
+use crate::float::Float;
+use crate::{Canvas, LedColor};
+
+/// A small xorshift generator, random enough for visuals.
+#[derive(Clone, Debug)]
+struct Rng(u32);
+
+impl Rng {
+    const fn new(seed: u32) -> Self {
+        // xorshift never leaves zero
+        Self(if seed == 0 { 0x9E37_79B9 } else { seed })
+    }
+
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// A number in \[0, `bound`)
+    fn below(&mut self, bound: u32) -> u32 {
+        self.next() % bound.max(1)
+    }
+
+    /// A number in \[0, 1)
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+/// The fully saturated color at `hue` around the color wheel, from 0 to 1.
+fn wheel(hue: f32) -> LedColor {
+    let channel = |peak: f32| {
+        let distance = (hue - peak).rem_euclid(1.0);
+        let distance = distance.min(1.0 - distance);
+        ((2.0 - 6.0 * distance).clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    LedColor {
+        red: channel(0.0),
+        green: channel(1.0 / 3.0),
+        blue: channel(2.0 / 3.0),
+    }
+}
+
+/// Overlapping sine waves cycling through the colors.
+#[derive(Clone, Debug)]
+pub struct Plasma {
+    time: f32,
+    speed: f32,
+}
+
+impl Plasma {
+    /// Creates the plasma
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            time: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Sets how fast the plasma moves, 1 by default
+    #[must_use]
+    pub const fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Draws the next frame
+    pub fn step(&mut self, canvas: &mut impl Canvas) {
+        self.time += 0.05 * self.speed;
+        let time = self.time;
+        let (width, height) = canvas.canvas_size();
+        let scale = 8.0 / width.min(height).max(1) as f32;
+        canvas.shade(|x, y| {
+            let (x, y) = (x as f32 * scale, y as f32 * scale);
+            let value = (x + time).sin()
+                + (y * 0.7 - time).sin()
+                + ((x + y + time) * 0.5).sin()
+                + ((x * x + y * y).sqrt() - time).sin();
+            wheel(value / 8.0 + time * 0.05)
+        });
+    }
+}
+
+impl Default for Plasma {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Flames rising from the bottom edge.
+#[derive(Clone, Debug)]
+pub struct Fire {
+    /// Heat of every pixel, row by row
+    heat: Vec<u8>,
+    size: (i32, i32),
+    cooling: u8,
+    rng: Rng,
+}
+
+impl Fire {
+    /// Creates the fire
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            heat: Vec::new(),
+            size: (0, 0),
+            cooling: 3,
+            rng: Rng::new(1),
+        }
+    }
+
+    /// Sets how much heat is lost per pixel risen, so higher values give lower flames
+    #[must_use]
+    pub const fn cooling(mut self, cooling: u8) -> Self {
+        self.cooling = cooling;
+        self
+    }
+
+    /// Lets the heat rise by a pixel on a canvas of `size`
+    fn update(&mut self, size: (i32, i32)) {
+        let (width, height) = size;
+        if self.size != size {
+            self.size = size;
+            self.heat = vec![0; (width.max(0) * height.max(0)) as usize];
+        }
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let index = |x: i32, y: i32| (y * width + x) as usize;
+        for x in 0..width {
+            self.heat[index(x, height - 1)] = 200 + self.rng.below(56) as u8;
+        }
+        for y in 0..height - 1 {
+            for x in 0..width {
+                let drift = self.rng.below(3) as i32 - 1;
+                let below = self.heat[index((x + drift).clamp(0, width - 1), y + 1)];
+                let loss = self.rng.below(u32::from(self.cooling) * 2 + 1) as u8;
+                self.heat[index(x, y)] = below.saturating_sub(loss);
+            }
+        }
+    }
+
+    /// The color of glowing `heat`, from black over red and yellow to white
+    fn color(heat: u8) -> LedColor {
+        let heat = u16::from(heat) * 3;
+        let ramp = |start: u16| (heat.saturating_sub(start).min(255)) as u8;
+        LedColor {
+            red: ramp(0),
+            green: ramp(255),
+            blue: ramp(510),
+        }
+    }
+
+    /// Draws the next frame
+    pub fn step(&mut self, canvas: &mut impl Canvas) {
+        let size = canvas.canvas_size();
+        self.update(size);
+        let heat = &self.heat;
+        canvas.shade(|x, y| Self::color(heat[(y * size.0 + x) as usize]));
+    }
+}
+
+impl Default for Fire {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Falling streaks of glyph-like pixels, as seen in a well-known movie.
+#[derive(Clone, Debug)]
+pub struct MatrixRain {
+    /// Brightness of every pixel, row by row
+    glow: Vec<u8>,
+    /// Position and speed of the drop in every column, negative while waiting
+    drops: Vec<(f32, f32)>,
+    size: (i32, i32),
+    color: LedColor,
+    rng: Rng,
+}
+
+impl MatrixRain {
+    /// Creates green rain
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            glow: Vec::new(),
+            drops: Vec::new(),
+            size: (0, 0),
+            color: LedColor {
+                red: 0,
+                green: 255,
+                blue: 64,
+            },
+            rng: Rng::new(2),
+        }
+    }
+
+    /// Sets the color of the trails, heads are drawn white
+    #[must_use]
+    pub const fn color(mut self, color: LedColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// A drop starting above the top edge after a random pause
+    fn new_drop(rng: &mut Rng, height: i32) -> (f32, f32) {
+        (
+            -(rng.below(height.max(1) as u32) as f32),
+            0.3 + rng.unit() * 0.7,
+        )
+    }
+
+    /// Fades the trails and moves the drops on a canvas of `size`
+    fn update(&mut self, size: (i32, i32)) {
+        let (width, height) = size;
+        if self.size != size {
+            self.size = size;
+            self.glow = vec![0; (width.max(0) * height.max(0)) as usize];
+            let rng = &mut self.rng;
+            self.drops = (0..width).map(|_| Self::new_drop(rng, height)).collect();
+        }
+        for glow in &mut self.glow {
+            *glow = (u16::from(*glow) * 7 / 8) as u8;
+        }
+        for (x, drop) in (0..).zip(&mut self.drops) {
+            drop.0 += drop.1;
+            let y = drop.0 as i32;
+            if y >= height {
+                *drop = Self::new_drop(&mut self.rng, height);
+            } else if drop.0 >= 0.0 {
+                self.glow[(y * width + x) as usize] = 255;
+            }
+        }
+    }
+
+    /// Draws the next frame
+    pub fn step(&mut self, canvas: &mut impl Canvas) {
+        let size = canvas.canvas_size();
+        self.update(size);
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let (glow, color) = (&self.glow, self.color);
+        canvas.shade(|x, y| match glow[(y * size.0 + x) as usize] {
+            255 => white,
+            level => black.lerp(&color, f32::from(level) / 255.0),
+        });
+    }
+}
+
+impl Default for MatrixRain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stars flying towards the viewer from the center.
+#[derive(Clone, Debug)]
+pub struct Starfield {
+    /// Position of every star, x and y from -1 to 1, and depth from 0 to 1
+    stars: Vec<(f32, f32, f32)>,
+    speed: f32,
+    rng: Rng,
+}
+
+impl Starfield {
+    /// Creates a field of `count` stars
+    #[must_use]
+    pub fn new(count: usize) -> Self {
+        let mut rng = Rng::new(3);
+        let stars = (0..count).map(|_| Self::new_star(&mut rng, true)).collect();
+        Self {
+            stars,
+            speed: 1.0,
+            rng,
+        }
+    }
+
+    /// Sets how fast the stars fly, 1 by default
+    #[must_use]
+    pub const fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// A star at a random position, at any depth or the farthest one
+    fn new_star(rng: &mut Rng, anywhere: bool) -> (f32, f32, f32) {
+        let depth = if anywhere { rng.unit().max(0.05) } else { 1.0 };
+        (rng.unit() * 2.0 - 1.0, rng.unit() * 2.0 - 1.0, depth)
+    }
+
+    /// Moves the stars closer, replacing the ones that passed by
+    fn update(&mut self) {
+        for star in &mut self.stars {
+            star.2 -= 0.01 * self.speed;
+            let visible = star.2 > 0.0 && star.0.abs() < star.2 && star.1.abs() < star.2;
+            if !visible {
+                *star = Self::new_star(&mut self.rng, false);
+            }
+        }
+    }
+
+    /// Draws the next frame
+    pub fn step(&mut self, canvas: &mut impl Canvas) {
+        self.update();
+        let (width, height) = canvas.canvas_size();
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+        canvas.clear();
+        canvas.set_iter(self.stars.iter().map(|&(x, y, depth)| {
+            let level = ((1.0 - depth) * 255.0) as u8;
+            (
+                (center_x + x / depth * center_x) as i32,
+                (center_y + y / depth * center_y) as i32,
+                LedColor {
+                    red: level,
+                    green: level,
+                    blue: level,
+                },
+            )
+        }));
+    }
+}
+
+impl Default for Starfield {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+/// The color wheel turning around the center, useful to check colors and every pixel.
+#[derive(Clone, Debug)]
+pub struct ColorWheel {
+    angle: f32,
+    speed: f32,
+}
+
+impl ColorWheel {
+    /// Creates the color wheel
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            angle: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Sets how fast the wheel turns, 1 by default for a turn every 200 frames
+    #[must_use]
+    pub const fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Draws the next frame
+    pub fn step(&mut self, canvas: &mut impl Canvas) {
+        self.angle = (self.angle + 0.005 * self.speed).rem_euclid(1.0);
+        let angle = self.angle;
+        let (width, height) = canvas.canvas_size();
+        let (center_x, center_y) = ((width - 1) as f32 / 2.0, (height - 1) as f32 / 2.0);
+        canvas.shade(|x, y| {
+            let direction = (y as f32 - center_y).atan2(x as f32 - center_x) / TAU;
+            wheel(direction + angle)
+        });
+    }
+}
+
+impl Default for ColorWheel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors() {
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        assert_eq!(wheel(0.0), red);
+        assert_eq!(wheel(1.0), red);
+        assert_eq!(wheel(1.0 / 3.0).green, 255);
+        assert_eq!(wheel(2.0 / 3.0).blue, 255);
+        assert_eq!(Fire::color(0), LedColor { red: 0, ..red });
+        assert_eq!(Fire::color(255).blue, 255);
+        assert!(Fire::color(100).red > Fire::color(100).green);
+    }
+
+    #[test]
+    fn state() {
+        let mut fire = Fire::new();
+        for _ in 0..40 {
+            fire.update((8, 16));
+        }
+        let row = |y: usize| {
+            fire.heat[y * 8..y * 8 + 8]
+                .iter()
+                .map(|&h| u32::from(h))
+                .sum::<u32>()
+        };
+        assert!(row(15) > row(8) && row(8) >= row(0));
+
+        let mut rain = MatrixRain::new();
+        for _ in 0..100 {
+            rain.update((8, 16));
+        }
+        assert!(rain.glow.contains(&255));
+        assert!(rain.glow.iter().any(|&glow| glow > 0 && glow < 255));
+
+        let mut stars = Starfield::new(10);
+        for _ in 0..200 {
+            stars.update();
+        }
+        assert!(stars
+            .stars
+            .iter()
+            .all(|&(x, y, depth)| depth > 0.0 && x.abs() <= 1.0 && y.abs() <= 1.0));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::float::Float;
+
+/// An axis-aligned rectangle in canvas coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// the left edge
+    pub x: i32,
+    /// the top edge
+    pub y: i32,
+    /// the width, nothing is inside if not positive
+    pub width: i32,
+    /// the height, nothing is inside if not positive
+    pub height: i32,
+}
+
+impl Rect {
+    /// Creates a rectangle with its top left corner at (`x`, `y`).
+    #[must_use]
+    pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns true if the rectangle contains no pixels
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// Returns true if the pixel at (`x`, `y`) lies inside the rectangle
+    #[must_use]
+    pub const fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Returns the area covered by both rectangles, which may be empty.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Self::new(x, y, (right - x).max(0), (bottom - y).max(0))
+    }
+}
+
+/// A logical coordinate system mapped onto a region of a canvas, e.g. for plotting math.
+///
+/// The x range maps from the left to the right column of the region, the y range from its
+/// bottom to its top row, so y grows upwards unless the range is given reversed. Draw in
+/// these units with [`Canvas::plot`](crate::Canvas::plot), the mapped coordinates are local
+/// to the canvas, so viewports, clipping and the [`Transform`](crate::Transform) of a canvas
+/// apply to them as to all other draw calls.
+///
+/// ```
+/// use rpi_led_matrix_core::{CoordinateSpace, Rect};
+/// let region = Rect::new(0, 0, 65, 33);
+/// let space = CoordinateSpace::new(region, (0.0, std::f32::consts::TAU), (-1.0, 1.0));
+/// let sine: Vec<_> = (0..=100)
+///     .map(|i| i as f32 / 100.0 * std::f32::consts::TAU)
+///     .map(|x| space.map(x, x.sin()))
+///     .collect();
+/// assert_eq!(sine[0], (0, 16));
+/// assert_eq!(sine[25], (16, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoordinateSpace {
+    region: Rect,
+    x: (f32, f32),
+    y: (f32, f32),
+}
+
+impl CoordinateSpace {
+    /// Maps `x` and `y` ranges onto the pixel centers of `region`.
+    #[must_use]
+    pub const fn new(region: Rect, x: (f32, f32), y: (f32, f32)) -> Self {
+        Self { region, x, y }
+    }
+
+    /// Pixel units with the origin at the bottom left and y growing upwards
+    #[must_use]
+    pub fn bottom_left((width, height): (i32, i32)) -> Self {
+        Self::new(
+            Rect::new(0, 0, width, height),
+            (0.0, (width - 1) as f32),
+            (0.0, (height - 1) as f32),
+        )
+    }
+
+    /// Pixel units with the origin in the center and y growing upwards
+    ///
+    /// For even sizes the origin is the pixel right of and below the center.
+    #[must_use]
+    pub fn centered((width, height): (i32, i32)) -> Self {
+        let (left, bottom) = (-(width / 2), -((height - 1) / 2));
+        Self::new(
+            Rect::new(0, 0, width, height),
+            (left as f32, (left + width - 1) as f32),
+            (bottom as f32, (bottom + height - 1) as f32),
+        )
+    }
+
+    /// The region of the canvas the space is mapped onto
+    #[must_use]
+    pub const fn region(&self) -> Rect {
+        self.region
+    }
+
+    /// Pixels per unit along x and y, negative if the axis runs against the canvas' one
+    #[must_use]
+    pub fn scale(&self) -> (f32, f32) {
+        let span = |(start, end): (f32, f32), pixels: i32| {
+            let units = end - start;
+            if units == 0.0 {
+                0.0
+            } else {
+                (pixels - 1).max(0) as f32 / units
+            }
+        };
+        (
+            span(self.x, self.region.width),
+            -span(self.y, self.region.height),
+        )
+    }
+
+    /// Maps a logical coordinate onto the nearest canvas pixel.
+    #[must_use]
+    pub fn map(&self, x: f32, y: f32) -> (i32, i32) {
+        let (scale_x, scale_y) = self.scale();
+        let bottom = self.region.y + self.region.height - 1;
+        (
+            self.region.x + ((x - self.x.0) * scale_x).round() as i32,
+            bottom + ((y - self.y.0) * scale_y).round() as i32,
+        )
+    }
+
+    /// Maps a canvas pixel back onto the logical coordinate of its center.
+    #[must_use]
+    pub fn unmap(&self, x: i32, y: i32) -> (f32, f32) {
+        let (scale_x, scale_y) = self.scale();
+        let bottom = self.region.y + self.region.height - 1;
+        let unit = |offset: i32, scale: f32, start: f32| {
+            if scale == 0.0 {
+                start
+            } else {
+                start + offset as f32 / scale
+            }
+        };
+        (
+            unit(x - self.region.x, scale_x, self.x.0),
+            unit(y - bottom, scale_y, self.y.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        let rect = Rect::new(2, 3, 4, 5);
+        assert!(rect.contains(2, 3));
+        assert!(rect.contains(5, 7));
+        assert!(!rect.contains(6, 7));
+        assert!(!rect.contains(5, 8));
+        assert!(!Rect::new(0, 0, 0, 5).contains(0, 0));
+    }
+
+    #[test]
+    fn intersection() {
+        let rect = Rect::new(0, 0, 10, 10);
+        assert_eq!(
+            rect.intersection(&Rect::new(5, -5, 10, 10)),
+            Rect::new(5, 0, 5, 5)
+        );
+        assert!(rect.intersection(&Rect::new(20, 20, 5, 5)).is_empty());
+    }
+
+    #[test]
+    fn coordinate_spaces() {
+        let space = CoordinateSpace::bottom_left((64, 32));
+        assert_eq!(space.map(0.0, 0.0), (0, 31));
+        assert_eq!(space.map(63.0, 31.0), (63, 0));
+        assert_eq!(space.unmap(10, 31), (10.0, 0.0));
+
+        let space = CoordinateSpace::centered((64, 32));
+        assert_eq!(space.map(0.0, 0.0), (32, 16));
+        assert_eq!(space.map(-32.0, 15.0), (0, 1));
+
+        let space = CoordinateSpace::new(Rect::new(10, 0, 11, 5), (0.0, 1.0), (1.0, -1.0));
+        assert_eq!(space.scale(), (10.0, 2.0));
+        assert_eq!(space.map(0.5, -1.0), (15, 0));
+        assert_eq!(space.unmap(20, 4), (1.0, 1.0));
+    }
+}
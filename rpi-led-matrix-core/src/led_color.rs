@@ -0,0 +1,243 @@
+use crate::float::Float;
+
+#[cfg(feature = "embeddedgraphics")]
+use embedded_graphics_core::pixelcolor::{
+    raw::RawU24, Bgr555, Bgr565, Bgr888, BinaryColor, Gray2, Gray4, Gray8, GrayColor, PixelColor,
+    Rgb555, Rgb565, Rgb888, RgbColor,
+};
+
+/// The Rust handle representing a color you'd like to display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LedColor {
+    /// the red channel value
+    pub red: u8,
+    /// the green channel value
+    pub green: u8,
+    /// the blue channel value
+    pub blue: u8,
+}
+
+impl LedColor {
+    /// Linearly interpolates between this color and `other`, `t` being clamped to \[0,1\].
+    ///
+    /// ```
+    /// use rpi_led_matrix_core::LedColor;
+    /// let black = LedColor { red: 0, green: 0, blue: 0 };
+    /// let white = LedColor { red: 255, green: 255, blue: 255 };
+    /// assert_eq!(black.lerp(&white, 0.5), LedColor { red: 128, green: 128, blue: 128 });
+    /// ```
+    #[must_use]
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+        Self {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+        }
+    }
+}
+
+/// A color with an alpha channel, for content that's partly transparent until it's
+/// flattened onto the canvas, like in a `Surface` of `rpi-led-matrix`.
+///
+/// Colors aren't premultiplied: `alpha` only says how much the color covers what's below.
+///
+/// ```
+/// use rpi_led_matrix_core::{LedColor, LedColorA};
+/// let black = LedColor { red: 0, green: 0, blue: 0 };
+/// let half_white = LedColorA { red: 255, green: 255, blue: 255, alpha: 128 };
+/// assert_eq!(half_white.flatten(&black), LedColor { red: 128, green: 128, blue: 128 });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LedColorA {
+    /// the red channel value
+    pub red: u8,
+    /// the green channel value
+    pub green: u8,
+    /// the blue channel value
+    pub blue: u8,
+    /// the opacity, from 0 for transparent to 255 for opaque
+    pub alpha: u8,
+}
+
+impl LedColorA {
+    /// A fully transparent color
+    pub const TRANSPARENT: Self = Self {
+        red: 0,
+        green: 0,
+        blue: 0,
+        alpha: 0,
+    };
+
+    /// Creates `color` with the given opacity.
+    #[must_use]
+    pub const fn new(color: LedColor, alpha: u8) -> Self {
+        Self {
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+            alpha,
+        }
+    }
+
+    /// The color without its alpha channel
+    #[must_use]
+    pub const fn color(&self) -> LedColor {
+        LedColor {
+            red: self.red,
+            green: self.green,
+            blue: self.blue,
+        }
+    }
+
+    /// Draws this color over the opaque color `below`, returning the result.
+    #[must_use]
+    pub fn flatten(&self, below: &LedColor) -> LedColor {
+        below.lerp(&self.color(), f32::from(self.alpha) / 255.0)
+    }
+
+    /// Draws this color over `below`, which may be transparent as well, returning the result.
+    #[must_use]
+    pub fn blend(&self, below: &Self) -> Self {
+        let (top, under) = (
+            f32::from(self.alpha) / 255.0,
+            f32::from(below.alpha) / 255.0,
+        );
+        let alpha = top + under * (1.0 - top);
+        if alpha <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+        let mix = |a: u8, b: u8| {
+            ((f32::from(a) * top + f32::from(b) * under * (1.0 - top)) / alpha).round() as u8
+        };
+        Self {
+            red: mix(self.red, below.red),
+            green: mix(self.green, below.green),
+            blue: mix(self.blue, below.blue),
+            alpha: (alpha * 255.0).round() as u8,
+        }
+    }
+}
+
+impl From<LedColor> for LedColorA {
+    fn from(color: LedColor) -> Self {
+        Self::new(color, 255)
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl PixelColor for LedColor {
+    type Raw = RawU24;
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Bgr555> for LedColor {
+    fn from(p: Bgr555) -> Self {
+        Self {
+            red: p.r() << 3,
+            green: p.g() << 3,
+            blue: p.b() << 3,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Bgr565> for LedColor {
+    fn from(p: Bgr565) -> Self {
+        Self {
+            red: p.r() << 3,
+            green: p.g() << 2,
+            blue: p.b() << 3,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Bgr888> for LedColor {
+    fn from(p: Bgr888) -> Self {
+        Self {
+            red: p.r(),
+            green: p.g(),
+            blue: p.b(),
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Gray2> for LedColor {
+    fn from(p: Gray2) -> Self {
+        Self {
+            red: p.luma() << 6,
+            green: p.luma() << 6,
+            blue: p.luma() << 6,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Gray4> for LedColor {
+    fn from(p: Gray4) -> Self {
+        Self {
+            red: p.luma() << 4,
+            green: p.luma() << 4,
+            blue: p.luma() << 4,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Gray8> for LedColor {
+    fn from(p: Gray8) -> Self {
+        Self {
+            red: p.luma(),
+            green: p.luma(),
+            blue: p.luma(),
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Rgb555> for LedColor {
+    fn from(p: Rgb555) -> Self {
+        Self {
+            red: p.r() << 3,
+            green: p.g() << 3,
+            blue: p.b() << 3,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Rgb565> for LedColor {
+    fn from(p: Rgb565) -> Self {
+        Self {
+            red: p.r() << 3,
+            green: p.g() << 2,
+            blue: p.b() << 3,
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<Rgb888> for LedColor {
+    fn from(p: Rgb888) -> Self {
+        Self {
+            red: p.r(),
+            green: p.g(),
+            blue: p.b(),
+        }
+    }
+}
+
+#[cfg(feature = "embeddedgraphics")]
+impl From<BinaryColor> for LedColor {
+    fn from(p: BinaryColor) -> Self {
+        let value = if p == BinaryColor::On { u8::MAX } else { 0 };
+        Self {
+            red: value,
+            green: value,
+            blue: value,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Colors, geometry and drawing of [rpi-led-matrix](https://docs.rs/rpi-led-matrix/), without
+//! the Raspberry Pi.
+//!
+//! Everything here is `no_std` and only needs `alloc`, so the [`effects`] and [`widgets`]
+//! drawing on a [`Canvas`] run on microcontroller LED projects as well. Implement [`Canvas`]
+//! for the frame buffer of your LEDs, and all shapes of the trait, effects and widgets draw
+//! onto it. `rpi-led-matrix` re-exports all of this and implements [`Canvas`] for its
+//! canvases.
+//!
+//! # Features
+//!
+//! ## `embeddedgraphics`
+//!
+//! Pulls in [`embedded-graphics-core`](embedded_graphics_core) and implements
+//! [`PixelColor`](embedded_graphics_core::pixelcolor::PixelColor) for [`LedColor`], with
+//! conversions from the colors of `embedded-graphics`.
+#![no_std]
+// tests link std, whose float methods take the place of the ones from libm
+#![cfg_attr(test, allow(dead_code, unused_imports))]
+
+extern crate alloc;
+
+#[deny(missing_docs)]
+mod draw;
+#[deny(missing_docs)]
+pub mod effects;
+mod float;
+#[deny(missing_docs)]
+mod geometry;
+#[deny(missing_docs)]
+mod led_color;
+#[deny(missing_docs)]
+pub mod raster;
+#[deny(missing_docs)]
+mod transform;
+pub mod widgets;
+
+#[doc(inline)]
+pub use draw::{Canvas, Plot, View};
+#[doc(inline)]
+pub use geometry::{CoordinateSpace, Rect};
+#[doc(inline)]
+pub use led_color::{LedColor, LedColorA};
+#[doc(inline)]
+pub use transform::{Rotation, Transform};
@@ -0,0 +1,494 @@
+//! Rasterization of primitives into pixel coordinates, shared by the drawing methods.
+//!
+//! Every function passes the pixels to a `plot` callback instead of drawing them, so shapes can
+//! be collected, clipped or transformed before they reach a canvas.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::float::Float;
+
+/// Plots a one pixel wide line, using the same fixed point algorithm as the C++ library.
+pub fn line(x0: i32, y0: i32, x1: i32, y1: i32, mut plot: impl FnMut(i32, i32)) {
+    const SHIFT: i32 = 16;
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    if dx.abs() > dy.abs() {
+        let ((x0, y0), (x1, _)) = if x1 < x0 {
+            ((x1, y1), (x0, y0))
+        } else {
+            ((x0, y0), (x1, y1))
+        };
+        let gradient = (i64::from(dy) << SHIFT) / i64::from(dx);
+        let mut y = (1_i64 << (SHIFT - 1)) + (i64::from(y0) << SHIFT);
+        for x in x0..=x1 {
+            plot(x, (y >> SHIFT) as i32);
+            y += gradient;
+        }
+    } else if dy != 0 {
+        let ((x0, y0), (_, y1)) = if y1 < y0 {
+            ((x1, y1), (x0, y0))
+        } else {
+            ((x0, y0), (x1, y1))
+        };
+        let gradient = (i64::from(dx) << SHIFT) / i64::from(dy);
+        let mut x = (1_i64 << (SHIFT - 1)) + (i64::from(x0) << SHIFT);
+        for y in y0..=y1 {
+            plot((x >> SHIFT) as i32, y);
+            x += gradient;
+        }
+    } else {
+        plot(x0, y0);
+    }
+}
+
+/// Plots a one pixel wide circle outline using the midpoint algorithm, like the C++ library.
+pub fn circle(x0: i32, y0: i32, radius: i32, mut plot: impl FnMut(i32, i32)) {
+    let (mut x, mut y) = (radius, 0);
+    let mut error = 1 - x;
+    while y <= x {
+        plot(x0 + x, y0 + y);
+        plot(x0 + y, y0 + x);
+        plot(x0 - x, y0 + y);
+        plot(x0 - y, y0 + x);
+        plot(x0 - x, y0 - y);
+        plot(x0 - y, y0 - x);
+        plot(x0 + x, y0 - y);
+        plot(x0 + y, y0 - x);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x + 1);
+        }
+    }
+}
+
+/// Plots a line of the given width with round caps, so joined segments leave no gaps.
+///
+/// Pixels are plotted if their center lies within `width / 2` of the segment. Even widths are
+/// shifted by half a pixel towards positive coordinates to stay on the pixel grid.
+pub fn thick_line(x0: i32, y0: i32, x1: i32, y1: i32, width: u32, mut plot: impl FnMut(i32, i32)) {
+    if width <= 1 {
+        if width == 1 {
+            line(x0, y0, x1, y1, plot);
+        }
+        return;
+    }
+    let radius = width as f32 / 2.0;
+    let shift = if width.is_multiple_of(2) { 0.5 } else { 0.0 };
+    let (ax, ay) = (x0 as f32 + shift, y0 as f32 + shift);
+    let (dx, dy) = ((x1 - x0) as f32, (y1 - y0) as f32);
+    let length_squared = dx * dx + dy * dy;
+
+    let reach = radius.ceil() as i32;
+    for y in y0.min(y1) - reach..=y0.max(y1) + reach {
+        for x in x0.min(x1) - reach..=x0.max(x1) + reach {
+            let (px, py) = (x as f32 - ax, y as f32 - ay);
+            // position of the closest point on the segment, 0 at the start and 1 at the end
+            let t = if length_squared == 0.0 {
+                0.0
+            } else {
+                ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0)
+            };
+            if (px - t * dx).hypot(py - t * dy) <= radius {
+                plot(x, y);
+            }
+        }
+    }
+}
+
+/// Plots the part of a circle outline between two angles in degrees, clockwise from 3 o'clock.
+///
+/// The arc always runs clockwise from `start_angle` to `end_angle`, so 270° to 90° is the
+/// right half. Angles a whole turn or more apart give the full circle.
+pub fn arc(
+    x0: i32,
+    y0: i32,
+    radius: i32,
+    start_angle: f32,
+    end_angle: f32,
+    mut plot: impl FnMut(i32, i32),
+) {
+    if (end_angle - start_angle).abs() >= 360.0 {
+        return circle(x0, y0, radius, plot);
+    }
+    let sweep = (end_angle - start_angle).rem_euclid(360.0);
+    circle(x0, y0, radius, |x, y| {
+        let angle = ((y - y0) as f32).atan2((x - x0) as f32).to_degrees();
+        if (angle - start_angle).rem_euclid(360.0) <= sweep {
+            plot(x, y);
+        }
+    });
+}
+
+/// Plots a Bézier curve of any degree through its control points, as connected lines.
+pub fn bezier(points: &[(i32, i32)], mut plot: impl FnMut(i32, i32)) {
+    let Some(&first) = points.first() else {
+        return;
+    };
+    // the curve is never longer than its control polygon
+    let length: f32 = points
+        .windows(2)
+        .map(|w| ((w[1].0 - w[0].0) as f32).hypot((w[1].1 - w[0].1) as f32))
+        .sum();
+    let steps = (length / 2.0).ceil().max(1.0) as usize;
+
+    let mut scratch = Vec::with_capacity(points.len());
+    let mut previous = first;
+    for step in 1..=steps {
+        let t = step as f32 / steps as f32;
+        // de Casteljau
+        scratch.clear();
+        scratch.extend(points.iter().map(|&(x, y)| (x as f32, y as f32)));
+        for degree in (1..points.len()).rev() {
+            for i in 0..degree {
+                scratch[i].0 += (scratch[i + 1].0 - scratch[i].0) * t;
+                scratch[i].1 += (scratch[i + 1].1 - scratch[i].1) * t;
+            }
+        }
+        let point = (scratch[0].0.round() as i32, scratch[0].1.round() as i32);
+        line(previous.0, previous.1, point.0, point.1, &mut plot);
+        previous = point;
+    }
+}
+
+/// Plots the closed outline through all `points`.
+pub fn polygon(points: &[(i32, i32)], mut plot: impl FnMut(i32, i32)) {
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % points.len()];
+        line(x0, y0, x1, y1, &mut plot);
+    }
+}
+
+/// Plots the area enclosed by `points` with an even-odd scanline fill, including its outline.
+pub fn fill_polygon(points: &[(i32, i32)], mut plot: impl FnMut(i32, i32)) {
+    let (Some(top), Some(bottom)) = (
+        points.iter().map(|p| p.1).min(),
+        points.iter().map(|p| p.1).max(),
+    ) else {
+        return;
+    };
+    let mut crossings = Vec::new();
+    for y in top..=bottom {
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            // half-open so vertices shared by two edges are only counted once
+            if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                let t = (y - y0) as f32 / (y1 - y0) as f32;
+                crossings.push(x0 as f32 + t * (x1 - x0) as f32);
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+        for span in crossings.chunks_exact(2) {
+            for x in span[0].ceil() as i32..=span[1].floor() as i32 {
+                plot(x, y);
+            }
+        }
+    }
+    polygon(points, plot);
+}
+
+/// Plots an anti-aliased line with Xiaolin Wu's algorithm, passing the coverage of each pixel.
+pub fn line_aa(
+    mut x0: f32,
+    mut y0: f32,
+    mut x1: f32,
+    mut y1: f32,
+    mut plot: impl FnMut(i32, i32, f32),
+) {
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        core::mem::swap(&mut x0, &mut y0);
+        core::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        core::mem::swap(&mut x0, &mut x1);
+        core::mem::swap(&mut y0, &mut y1);
+    }
+    let mut plot = |x: i32, y: i32, coverage: f32| {
+        if coverage > 0.0 {
+            if steep {
+                plot(y, x, coverage);
+            } else {
+                plot(x, y, coverage);
+            }
+        }
+    };
+    let gradient = if x1 - x0 == 0.0 {
+        1.0
+    } else {
+        (y1 - y0) / (x1 - x0)
+    };
+
+    // the end points cover their pixels only partially in the main direction
+    let mut end_point = |x: f32, y: f32, gap: f32| -> i32 {
+        let x_end = x.round();
+        let y_end = y + gradient * (x_end - x);
+        let (x_pixel, y_pixel) = (x_end as i32, y_end.floor() as i32);
+        plot(x_pixel, y_pixel, (1.0 - y_end.fract_positive()) * gap);
+        plot(x_pixel, y_pixel + 1, y_end.fract_positive() * gap);
+        x_pixel
+    };
+    let x_start = end_point(x0, y0, 1.0 - (x0 + 0.5).fract_positive());
+    let x_end = end_point(x1, y1, (x1 + 0.5).fract_positive());
+
+    let mut y = y0 + gradient * (x0.round() - x0) + gradient;
+    for x in x_start + 1..x_end {
+        plot(x, y.floor() as i32, 1.0 - y.fract_positive());
+        plot(x, y.floor() as i32 + 1, y.fract_positive());
+        y += gradient;
+    }
+}
+
+/// Plots an anti-aliased, one pixel wide circle, passing the coverage of each pixel.
+pub fn circle_aa(x0: f32, y0: f32, radius: f32, mut plot: impl FnMut(i32, i32, f32)) {
+    let reach = radius.abs() + 1.0;
+    for y in (y0 - reach).floor() as i32..=(y0 + reach).ceil() as i32 {
+        for x in (x0 - reach).floor() as i32..=(x0 + reach).ceil() as i32 {
+            let distance = (x as f32 - x0).hypot(y as f32 - y0);
+            let coverage = 1.0 - (distance - radius).abs();
+            if coverage > 0.0 {
+                plot(x, y, coverage.min(1.0));
+            }
+        }
+    }
+}
+
+/// Plots `points` rotated clockwise around `origin` by `degrees`, y pointing down.
+///
+/// Quarter turns map every point exactly. Other angles sample the rotated bounding box
+/// backwards onto the points, so rotated shapes get no holes.
+pub fn rotate(
+    points: &[(i32, i32)],
+    (x0, y0): (i32, i32),
+    degrees: f32,
+    mut plot: impl FnMut(i32, i32),
+) {
+    let degrees = degrees.rem_euclid(360.0);
+    if degrees % 90.0 == 0.0 {
+        for &(x, y) in points {
+            let (dx, dy) = (x - x0, y - y0);
+            let (rx, ry) = match degrees as i32 {
+                90 => (-dy, dx),
+                180 => (-dx, -dy),
+                270 => (dy, -dx),
+                _ => (dx, dy),
+            };
+            plot(x0 + rx, y0 + ry);
+        }
+        return;
+    }
+    let Some(&first) = points.first() else {
+        return;
+    };
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (mut min, mut max) = (first, first);
+    for &(x, y) in points {
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+    let corners = [min, (max.0, min.1), (min.0, max.1), max].map(|(x, y)| {
+        let (dx, dy) = ((x - x0) as f32, (y - y0) as f32);
+        (dx * cos - dy * sin, dx * sin + dy * cos)
+    });
+    let bound = |pick: fn(f32, f32) -> f32, axis: fn(&(f32, f32)) -> f32| {
+        corners.iter().map(axis).reduce(pick).unwrap_or_default()
+    };
+    let (left, right) = (bound(f32::min, |c| c.0), bound(f32::max, |c| c.0));
+    let (top, bottom) = (bound(f32::min, |c| c.1), bound(f32::max, |c| c.1));
+    let points: BTreeSet<_> = points.iter().copied().collect();
+    for ry in top.floor() as i32..=bottom.ceil() as i32 {
+        for rx in left.floor() as i32..=right.ceil() as i32 {
+            let (dx, dy) = (rx as f32, ry as f32);
+            let source = (
+                x0 + (dx * cos + dy * sin).round() as i32,
+                y0 + (dy * cos - dx * sin).round() as i32,
+            );
+            if points.contains(&source) {
+                plot(x0 + rx, y0 + ry);
+            }
+        }
+    }
+}
+
+/// Fractional part that is positive for negative numbers as well.
+trait FractPositive {
+    fn fract_positive(self) -> Self;
+}
+
+impl FractPositive for f32 {
+    fn fract_positive(self) -> Self {
+        self - self.floor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(draw: impl FnOnce(&mut dyn FnMut(i32, i32))) -> Vec<(i32, i32)> {
+        let mut points = Vec::new();
+        draw(&mut |x, y| points.push((x, y)));
+        points
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            collect(|p| line(0, 0, 3, 0, p)),
+            [(0, 0), (1, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(collect(|p| line(0, 3, 0, 1, p)), [(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(
+            collect(|p| line(4, 2, 0, 0, p)),
+            [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+        assert_eq!(collect(|p| line(1, 1, 1, 1, p)), [(1, 1)]);
+    }
+
+    #[test]
+    fn thick_lines() {
+        assert_eq!(
+            collect(|p| thick_line(0, 0, 3, 0, 1, p)),
+            collect(|p| line(0, 0, 3, 0, p))
+        );
+        assert!(collect(|p| thick_line(0, 0, 3, 0, 0, p)).is_empty());
+
+        let points = collect(|p| thick_line(0, 0, 4, 0, 3, p));
+        assert!(points.iter().all(|&(_, y)| (-1..=1).contains(&y)));
+        assert!((0..=4).all(|x| (-1..=1).all(|y| points.contains(&(x, y)))));
+        // round caps
+        assert!(points.contains(&(-1, 0)) && !points.contains(&(-2, 0)));
+
+        let points = collect(|p| thick_line(0, 0, 0, 4, 2, p));
+        assert!(points.iter().all(|&(x, _)| x == 0 || x == 1));
+        assert!((0..=4).all(|y| points.contains(&(0, y)) && points.contains(&(1, y))));
+    }
+
+    #[test]
+    fn arcs() {
+        let mut full = collect(|p| circle(0, 0, 5, p));
+        let mut arc_full = collect(|p| arc(0, 0, 5, 0.0, 360.0, p));
+        full.sort_unstable();
+        arc_full.sort_unstable();
+        assert_eq!(full, arc_full);
+
+        // lower right quadrant, y pointing down
+        let quarter = collect(|p| arc(0, 0, 5, 0.0, 90.0, p));
+        assert!(quarter.contains(&(5, 0)) && quarter.contains(&(0, 5)));
+        assert!(quarter.iter().all(|&(x, y)| x >= 0 && y >= 0));
+
+        // wrapping past 360°
+        let wrapped = collect(|p| arc(0, 0, 5, 270.0, 450.0, p));
+        assert!(wrapped.contains(&(0, -5)) && wrapped.contains(&(0, 5)));
+        assert!(wrapped.iter().all(|&(x, _)| x >= 0));
+        let mut through_zero = collect(|p| arc(0, 0, 5, 270.0, 90.0, p));
+        through_zero.sort_unstable();
+        let mut wrapped = wrapped;
+        wrapped.sort_unstable();
+        assert_eq!(through_zero, wrapped);
+        let mut backwards = collect(|p| arc(0, 0, 5, 360.0, 0.0, p));
+        backwards.sort_unstable();
+        assert_eq!(backwards, full);
+    }
+
+    #[test]
+    fn rotations() {
+        let points = [(1, 0), (2, 0), (2, 1)];
+        assert_eq!(collect(|p| rotate(&points, (0, 0), 0.0, p)), points);
+        assert_eq!(
+            collect(|p| rotate(&points, (0, 0), 90.0, p)),
+            [(0, 1), (0, 2), (-1, 2)]
+        );
+        assert_eq!(
+            collect(|p| rotate(&points, (1, 1), -90.0, p)),
+            [(0, 1), (0, 0), (1, 0)]
+        );
+
+        // a bar rotated by 45° stays connected along the diagonal
+        let bar: Vec<_> = (0..6).map(|x| (x, 0)).collect();
+        let rotated = collect(|p| rotate(&bar, (0, 0), 45.0, p));
+        assert!(rotated.contains(&(0, 0)) && rotated.contains(&(3, 3)));
+        assert!((0..=3).all(|i| rotated.contains(&(i, i))));
+        assert!(rotated.iter().all(|&(x, y)| (x - y).abs() <= 1));
+    }
+
+    #[test]
+    fn beziers() {
+        let straight = collect(|p| bezier(&[(0, 0), (2, 0), (4, 0)], p));
+        assert!(straight.iter().all(|&(_, y)| y == 0));
+        assert!(straight.contains(&(0, 0)) && straight.contains(&(4, 0)));
+
+        let curve = collect(|p| bezier(&[(0, 0), (0, 8), (8, 8), (8, 0)], p));
+        assert!(curve.contains(&(0, 0)) && curve.contains(&(8, 0)));
+        assert!(curve.contains(&(4, 6)));
+        assert!(curve.iter().all(|&(_, y)| y <= 6));
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [(0, 0), (3, 0), (3, 3), (0, 3)];
+        let mut outline = collect(|p| polygon(&square, p));
+        outline.sort_unstable();
+        outline.dedup();
+        assert_eq!(outline.len(), 12);
+        assert!(!outline.contains(&(1, 1)));
+
+        let mut filled = collect(|p| fill_polygon(&square, p));
+        filled.sort_unstable();
+        filled.dedup();
+        assert_eq!(filled.len(), 16);
+
+        // concave arrow head
+        let arrow = [(0, 0), (4, 2), (0, 4), (2, 2)];
+        let filled = collect(|p| fill_polygon(&arrow, p));
+        assert!(filled.contains(&(3, 2)));
+        assert!(!filled.contains(&(1, 2)));
+        assert!(collect(|p| fill_polygon(&[], p)).is_empty());
+    }
+
+    #[test]
+    fn lines_aa() {
+        let mut points = Vec::new();
+        line_aa(0.0, 0.0, 4.0, 0.0, |x, y, c| points.push((x, y, c)));
+        assert_eq!(
+            points,
+            [
+                (0, 0, 0.5),
+                (4, 0, 0.5),
+                (1, 0, 1.0),
+                (2, 0, 1.0),
+                (3, 0, 1.0)
+            ]
+        );
+
+        let mut points = Vec::new();
+        line_aa(0.0, 0.0, 0.0, 2.0, |x, y, c| points.push((x, y, c)));
+        assert!(points.iter().all(|&(x, _, _)| x == 0));
+        assert!(points.contains(&(0, 1, 1.0)));
+
+        let mut total = 0.0;
+        line_aa(0.0, 0.0, 10.0, 5.0, |_, _, c| total += c);
+        assert!((total - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn circles_aa() {
+        let mut points = Vec::new();
+        circle_aa(5.0, 5.0, 3.0, |x, y, c| points.push((x, y, c)));
+        assert!(points.contains(&(8, 5, 1.0)));
+        assert!(!points.iter().any(|&(x, y, _)| (x, y) == (5, 5)));
+    }
+
+    #[test]
+    fn circles() {
+        let points = collect(|p| circle(10, 10, 3, p));
+        assert!(points.contains(&(13, 10)));
+        assert!(points.contains(&(10, 7)));
+        assert!(points
+            .iter()
+            .all(|&(x, y)| (x - 10).abs() <= 3 && (y - 10).abs() <= 3));
+    }
+}
@@ -0,0 +1,139 @@
+/// Clockwise rotation applied when drawing onto a canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// Draw as is
+    #[default]
+    None,
+    /// Rotate everything by 90° clockwise
+    Rotate90,
+    /// Rotate everything by 180°
+    Rotate180,
+    /// Rotate everything by 270° clockwise
+    Rotate270,
+}
+
+/// Draw-time transform of a canvas, like the `LedCanvas` of `rpi-led-matrix`.
+///
+/// Useful for panels mounted sideways or upside down. Mirroring is applied in the
+/// canvas' own coordinates, before the result is rotated onto the panel.
+///
+/// ```
+/// use rpi_led_matrix_core::{Rotation, Transform};
+/// let transform = Transform::new().rotation(Rotation::Rotate90).mirror_horizontal(true);
+/// assert_eq!(transform.logical_size((64, 32)), (32, 64));
+/// assert_eq!(transform.apply(0, 0, (64, 32)), (63, 31));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Transform {
+    rotation: Rotation,
+    mirror_horizontal: bool,
+    mirror_vertical: bool,
+}
+
+impl Transform {
+    /// Creates the identity transform
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rotation: Rotation::None,
+            mirror_horizontal: false,
+            mirror_vertical: false,
+        }
+    }
+
+    /// Sets the clockwise rotation
+    #[must_use]
+    pub const fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets if the x-axis is mirrored (left becomes right)
+    #[must_use]
+    pub const fn mirror_horizontal(mut self, enable: bool) -> Self {
+        self.mirror_horizontal = enable;
+        self
+    }
+
+    /// Sets if the y-axis is mirrored (top becomes bottom)
+    #[must_use]
+    pub const fn mirror_vertical(mut self, enable: bool) -> Self {
+        self.mirror_vertical = enable;
+        self
+    }
+
+    /// Returns true if the transform leaves all coordinates untouched
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::new()
+    }
+
+    /// Size of the canvas as seen through this transform, given the size of the panel.
+    #[must_use]
+    pub const fn logical_size(&self, device_size: (i32, i32)) -> (i32, i32) {
+        match self.rotation {
+            Rotation::None | Rotation::Rotate180 => device_size,
+            Rotation::Rotate90 | Rotation::Rotate270 => (device_size.1, device_size.0),
+        }
+    }
+
+    /// Maps a coordinate of the transformed canvas onto the panel.
+    #[must_use]
+    pub const fn apply(&self, x: i32, y: i32, device_size: (i32, i32)) -> (i32, i32) {
+        let (width, height) = device_size;
+        let (logical_width, logical_height) = self.logical_size(device_size);
+        let x = if self.mirror_horizontal {
+            logical_width - 1 - x
+        } else {
+            x
+        };
+        let y = if self.mirror_vertical {
+            logical_height - 1 - y
+        } else {
+            y
+        };
+        match self.rotation {
+            Rotation::None => (x, y),
+            Rotation::Rotate90 => (width - 1 - y, x),
+            Rotation::Rotate180 => (width - 1 - x, height - 1 - y),
+            Rotation::Rotate270 => (y, height - 1 - x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: (i32, i32) = (64, 32);
+
+    #[test]
+    fn identity() {
+        let transform = Transform::new();
+        assert!(transform.is_identity());
+        assert_eq!(transform.logical_size(SIZE), SIZE);
+        assert_eq!(transform.apply(3, 5, SIZE), (3, 5));
+    }
+
+    #[test]
+    fn rotations() {
+        let rotate = |rotation| Transform::new().rotation(rotation);
+        assert_eq!(rotate(Rotation::Rotate90).logical_size(SIZE), (32, 64));
+        assert_eq!(rotate(Rotation::Rotate90).apply(0, 0, SIZE), (63, 0));
+        assert_eq!(rotate(Rotation::Rotate180).apply(0, 0, SIZE), (63, 31));
+        assert_eq!(rotate(Rotation::Rotate270).apply(0, 0, SIZE), (0, 31));
+        assert_eq!(rotate(Rotation::Rotate270).apply(31, 63, SIZE), (63, 0));
+    }
+
+    #[test]
+    fn mirrors() {
+        let transform = Transform::new().mirror_horizontal(true);
+        assert_eq!(transform.apply(0, 5, SIZE), (63, 5));
+        let transform = Transform::new().mirror_vertical(true);
+        assert_eq!(transform.apply(3, 0, SIZE), (3, 31));
+        let transform = Transform::new()
+            .rotation(Rotation::Rotate90)
+            .mirror_horizontal(true);
+        assert_eq!(transform.apply(0, 0, SIZE), (63, 31));
+    }
+}
@@ -0,0 +1,218 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::float::Float;
+use crate::{Canvas, LedColor};
+
+const WHITE: LedColor = LedColor {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+
+/// Columns of live values with peak-hold markers, e.g. for audio spectrums or metrics.
+///
+/// Bars are drawn bottom up, centered horizontally, in one color or colored by height along a
+/// gradient. The peak of every bar is held for a while and then falls, drawn as a marker row.
+///
+/// ```
+/// use rpi_led_matrix_core::widgets::BarGraph;
+/// use rpi_led_matrix_core::LedColor;
+/// let green = LedColor { red: 0, green: 255, blue: 0 };
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// // drawn with `spectrum.draw(&mut canvas, &bands, elapsed)` every frame
+/// let spectrum = BarGraph::new().bar_width(3).gap(1).gradient(&[green, green, red]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BarGraph {
+    range: (f32, f32),
+    bar_width: i32,
+    gap: i32,
+    color: LedColor,
+    peak_color: LedColor,
+    gradient: Vec<LedColor>,
+    hold: Duration,
+    fall: f32,
+    /// Peak of every bar as fraction of the range, with the time it has been held
+    peaks: Vec<(f32, Duration)>,
+    last_elapsed: Duration,
+}
+
+impl BarGraph {
+    /// Creates a graph of values from 0 to 1 in white, with bars 2 pixels wide and 1 pixel
+    /// apart, and peaks held for half a second before falling by the whole height in a second.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            range: (0.0, 1.0),
+            bar_width: 2,
+            gap: 1,
+            color: WHITE,
+            peak_color: WHITE,
+            gradient: Vec::new(),
+            hold: Duration::from_millis(500),
+            fall: 1.0,
+            peaks: Vec::new(),
+            last_elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Sets the values of an empty and a full bar
+    #[must_use]
+    pub const fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = (min, max);
+        self
+    }
+
+    /// Sets the width of the bars in pixels
+    #[must_use]
+    pub const fn bar_width(mut self, width: i32) -> Self {
+        self.bar_width = width;
+        self
+    }
+
+    /// Sets the pixels between bars
+    #[must_use]
+    pub const fn gap(mut self, gap: i32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets the color of the bars without a gradient
+    #[must_use]
+    pub const fn color(mut self, color: LedColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the color of the peak markers
+    #[must_use]
+    pub const fn peak_color(mut self, color: LedColor) -> Self {
+        self.peak_color = color;
+        self
+    }
+
+    /// Colors the bars by height, blending evenly spaced colors from the bottom to the top.
+    /// Without colors, bars are drawn in their single color
+    #[must_use]
+    pub fn gradient(mut self, colors: &[LedColor]) -> Self {
+        self.gradient = colors.to_vec();
+        self
+    }
+
+    /// Sets how long peaks are held, and how fast they fall after, in heights per second.
+    /// A fall speed of 0 disables the peak markers
+    #[must_use]
+    pub const fn peak_hold(mut self, hold: Duration, fall: f32) -> Self {
+        self.hold = hold;
+        self.fall = fall;
+        self
+    }
+
+    /// The color at `fraction` of the height, along the gradient
+    fn color_at(&self, fraction: f32) -> LedColor {
+        match self.gradient.as_slice() {
+            [] => self.color,
+            [color] => *color,
+            colors => {
+                let position = fraction.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+                let index = (position as usize).min(colors.len() - 2);
+                colors[index].lerp(&colors[index + 1], position - index as f32)
+            }
+        }
+    }
+
+    /// Updates the peaks with the values, as fractions of the range, `delta` after the last
+    /// update.
+    fn update_peaks(&mut self, fractions: &[f32], delta: Duration) {
+        self.peaks.resize(fractions.len(), (0.0, Duration::ZERO));
+        for (peak, &fraction) in self.peaks.iter_mut().zip(fractions) {
+            let (level, held) = peak;
+            *held += delta;
+            if *held > self.hold {
+                let falling = (*held - self.hold).min(delta);
+                *level -= falling.as_secs_f32() * self.fall;
+            }
+            if fraction >= *level {
+                *peak = (fraction, Duration::ZERO);
+            }
+        }
+    }
+
+    /// Draws `values` as bars over the whole canvas.
+    ///
+    /// `elapsed` is the time the graph has been on screen, which moves the peak markers.
+    pub fn draw(&mut self, canvas: &mut impl Canvas, values: &[f32], elapsed: Duration) {
+        let (width, height) = canvas.canvas_size();
+        let (min, max) = self.range;
+        let fractions: Vec<f32> = values
+            .iter()
+            .map(|v| {
+                if max > min {
+                    ((v - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        // a graph shown again starts counting from zero
+        let delta = elapsed.checked_sub(self.last_elapsed).unwrap_or(elapsed);
+        self.last_elapsed = elapsed;
+        self.update_peaks(&fractions, delta);
+
+        let marker = self.peak_color;
+        let count = fractions.len() as i32;
+        let total = count * (self.bar_width + self.gap) - self.gap;
+        let left = (width - total) / 2;
+        let row = |fraction: f32| ((fraction * height as f32).round() as i32).min(height);
+        for (i, (&fraction, &(peak, _))) in (0..).zip(fractions.iter().zip(&self.peaks)) {
+            let x = left + i * (self.bar_width + self.gap);
+            let bar = row(fraction);
+            canvas.set_iter((0..bar).flat_map(|level| {
+                let color = self.color_at((level as f32 + 0.5) / height as f32);
+                (x..x + self.bar_width).map(move |px| (px, height - 1 - level, color))
+            }));
+            let peak = row(peak);
+            if self.fall > 0.0 && peak > 0 {
+                let y = height - peak;
+                canvas.set_iter((x..x + self.bar_width).map(|px| (px, y, marker)));
+            }
+        }
+    }
+}
+
+impl Default for BarGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_graph() {
+        let mut graph = BarGraph::new().peak_hold(Duration::from_millis(100), 2.0);
+        let ms = Duration::from_millis;
+        graph.update_peaks(&[0.8, 0.2], ms(0));
+        graph.update_peaks(&[0.1, 0.5], ms(50));
+        assert_eq!(graph.peaks, [(0.8, ms(50)), (0.5, ms(0))]);
+        // held for 100ms, then falling 2 heights per second
+        graph.update_peaks(&[0.1, 0.1], ms(100));
+        assert!((graph.peaks[0].0 - 0.7).abs() < 1e-6);
+        graph.update_peaks(&[0.1, 0.1], ms(1000));
+        assert_eq!(graph.peaks[0], (0.1, ms(0)));
+
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        assert_eq!(graph.color_at(0.5), WHITE);
+        let graph = graph.gradient(&[black, WHITE, black]);
+        assert_eq!(graph.color_at(0.0), black);
+        assert_eq!(graph.color_at(0.5), WHITE);
+        assert_eq!(graph.color_at(1.0), black);
+    }
+}
@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+
+use crate::{Canvas, LedColor};
+
+/// A small single-color bitmap, e.g. a status symbol next to a value.
+///
+/// ```
+/// use rpi_led_matrix_core::widgets::Icon;
+/// let arrow = Icon::from_rows(&["..#..", ".###.", "#####"]).unwrap();
+/// assert_eq!(arrow.size(), (5, 3));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Icon {
+    width: i32,
+    height: i32,
+    /// Row-major, one entry per pixel
+    pixels: Vec<bool>,
+}
+
+impl Icon {
+    /// Creates an icon from rows of equal length, where `#` marks a set pixel.
+    ///
+    /// # Errors
+    /// If the rows differ in length.
+    pub fn from_rows(rows: &[&str]) -> Result<Self, &'static str> {
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if rows.iter().any(|row| row.chars().count() != width) {
+            return Err("Icon rows differ in length");
+        }
+        Ok(Self {
+            width: width as i32,
+            height: rows.len() as i32,
+            pixels: rows
+                .iter()
+                .flat_map(|row| row.chars().map(|c| c == '#'))
+                .collect(),
+        })
+    }
+
+    fn builtin(rows: &[&str]) -> Self {
+        Self::from_rows(rows).expect("Built-in icons are rectangular")
+    }
+
+    /// A light bulb
+    #[must_use]
+    pub fn bulb() -> Self {
+        Self::builtin(&[".###.", "#...#", "#...#", ".#.#.", ".###.", "..#.."])
+    }
+
+    /// A power symbol
+    #[must_use]
+    pub fn power() -> Self {
+        Self::builtin(&["..#..", "#.#.#", "#.#.#", "#...#", ".###."])
+    }
+
+    /// A thermometer
+    #[must_use]
+    pub fn thermometer() -> Self {
+        Self::builtin(&[".#.", "#.#", "#.#", "#.#", "###", "###"])
+    }
+
+    /// A door
+    #[must_use]
+    pub fn door() -> Self {
+        Self::builtin(&["####", "#..#", "#..#", "#.##", "#..#", "####"])
+    }
+
+    /// Width and height in pixels
+    #[must_use]
+    pub const fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Draws the icon with its top left corner at the given coordinate.
+    pub fn draw(&self, canvas: &mut impl Canvas, x: i32, y: i32, color: &LedColor) {
+        for (dx, dy) in self.pixels() {
+            canvas.set(x + dx, y + dy, color);
+        }
+    }
+
+    /// Iterates over the coordinates of the set pixels, relative to the top left corner
+    pub fn pixels(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (0..)
+            .zip(&self.pixels)
+            .filter(|(_, &set)| set)
+            .map(|(i, _)| (i % self.width, i / self.width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_rows() {
+        let icon = Icon::from_rows(&["#.", ".#"]).unwrap();
+        assert_eq!(icon.pixels().collect::<Vec<_>>(), [(0, 0), (1, 1)]);
+        assert!(Icon::from_rows(&["#.", "#"]).is_err());
+        assert_eq!(Icon::from_rows(&[]).unwrap().size(), (0, 0));
+        assert_eq!(Icon::thermometer().size(), (3, 6));
+    }
+}
@@ -0,0 +1,14 @@
+//! Widgets drawing on any [`Canvas`](crate::Canvas), without fonts.
+#[deny(missing_docs)]
+mod bar_graph;
+#[deny(missing_docs)]
+mod icon;
+#[deny(missing_docs)]
+mod seven_segment;
+
+#[doc(inline)]
+pub use bar_graph::BarGraph;
+#[doc(inline)]
+pub use icon::Icon;
+#[doc(inline)]
+pub use seven_segment::SevenSegment;
@@ -0,0 +1,214 @@
+use alloc::vec::Vec;
+
+use crate::{Canvas, LedColor, Rect};
+
+/// Draws classic segmented digits of any size, e.g. for clocks and scoreboards.
+///
+/// Besides digits, the hexadecimal letters, `-`, `_` and space are drawn as seven segments,
+/// while `:` and `.` are drawn as narrow dots. Other characters are skipped. Unlit segments
+/// are drawn in the off color, if one is set, to mimic a real display.
+///
+/// ```
+/// use rpi_led_matrix_core::widgets::SevenSegment;
+/// use rpi_led_matrix_core::LedColor;
+/// let digits = SevenSegment::new(12, 24)
+///     .thickness(3)
+///     .color(LedColor { red: 255, green: 0, blue: 0 })
+///     .off_color(Some(LedColor { red: 24, green: 0, blue: 0 }));
+/// // centered on a panel 64 pixels wide with `digits.draw(&mut canvas, x, 4, "12:34")`
+/// let x = (64 - digits.measure("12:34")) / 2;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SevenSegment {
+    width: i32,
+    height: i32,
+    thickness: i32,
+    spacing: i32,
+    color: LedColor,
+    off_color: Option<LedColor>,
+}
+
+impl SevenSegment {
+    /// Creates digits of the given size, drawn in white with segments a fifth of the width
+    /// thick and as far apart.
+    #[must_use]
+    pub fn new(width: i32, height: i32) -> Self {
+        let thickness = (width / 5).max(1);
+        Self {
+            width,
+            height,
+            thickness,
+            spacing: thickness,
+            color: LedColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+            off_color: None,
+        }
+    }
+
+    /// Sets the thickness of the segments
+    #[must_use]
+    pub const fn thickness(mut self, thickness: i32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Sets the pixels between characters
+    #[must_use]
+    pub const fn spacing(mut self, spacing: i32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the color of lit segments
+    #[must_use]
+    pub const fn color(mut self, color: LedColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the color of unlit segments, or `None` to leave them undrawn
+    #[must_use]
+    pub const fn off_color(mut self, off_color: Option<LedColor>) -> Self {
+        self.off_color = off_color;
+        self
+    }
+
+    /// The lit segments of a character as bits, from segment a (top, bit 0) clockwise to f
+    /// (top left, bit 5) and g (middle, bit 6).
+    #[must_use]
+    pub const fn segments(c: char) -> Option<u8> {
+        Some(match c.to_ascii_uppercase() {
+            '0' | 'O' => 0b011_1111,
+            '1' => 0b000_0110,
+            '2' => 0b101_1011,
+            '3' => 0b100_1111,
+            '4' => 0b110_0110,
+            '5' | 'S' => 0b110_1101,
+            '6' => 0b111_1101,
+            '7' => 0b000_0111,
+            '8' => 0b111_1111,
+            '9' => 0b110_1111,
+            'A' => 0b111_0111,
+            'B' => 0b111_1100,
+            'C' => 0b011_1001,
+            'D' => 0b101_1110,
+            'E' => 0b111_1001,
+            'F' => 0b111_0001,
+            '-' => 0b100_0000,
+            '_' => 0b000_1000,
+            ' ' => 0,
+            _ => return None,
+        })
+    }
+
+    /// Horizontal distance the pen advances after drawing `c`.
+    fn advance(&self, c: char) -> i32 {
+        match c {
+            ':' | '.' => self.thickness + self.spacing,
+            c if Self::segments(c).is_some() => self.width + self.spacing,
+            _ => 0,
+        }
+    }
+
+    /// The width of `text`, including the spacing after the last character.
+    #[must_use]
+    pub fn measure(&self, text: &str) -> i32 {
+        text.chars().map(|c| self.advance(c)).sum()
+    }
+
+    /// The rectangles of segments a to g of a digit with its top left corner at (`x`, `y`).
+    fn segment_rects(&self, x: i32, y: i32) -> [Rect; 7] {
+        let Self {
+            width: w,
+            height: h,
+            thickness: t,
+            ..
+        } = *self;
+        // the middle segment's top, with the halves of the digit as equal as possible
+        let middle = y + (h - t) / 2;
+        let upper = middle - (y + t);
+        let lower = y + h - t - (middle + t);
+        [
+            Rect::new(x + t, y, w - 2 * t, t),
+            Rect::new(x + w - t, y + t, t, upper),
+            Rect::new(x + w - t, middle + t, t, lower),
+            Rect::new(x + t, y + h - t, w - 2 * t, t),
+            Rect::new(x, middle + t, t, lower),
+            Rect::new(x, y + t, t, upper),
+            Rect::new(x + t, middle, w - 2 * t, t),
+        ]
+    }
+
+    /// Draws `c` with its top left corner at (`x`, `y`), returning its advance width.
+    pub fn draw_char(&self, canvas: &mut impl Canvas, x: i32, y: i32, c: char) -> i32 {
+        let t = self.thickness;
+        let mut lit = Vec::new();
+        let mut unlit = Vec::new();
+        match c {
+            // dots in line with the gaps around the middle segment, or at the bottom
+            ':' => {
+                let middle = y + (self.height - t) / 2;
+                lit.push(Rect::new(x, (y + middle) / 2, t, t));
+                lit.push(Rect::new(x, (middle + y + self.height - t) / 2, t, t));
+            }
+            '.' => lit.push(Rect::new(x, y + self.height - t, t, t)),
+            c => {
+                let Some(segments) = Self::segments(c) else {
+                    return 0;
+                };
+                for (i, rect) in self.segment_rects(x, y).into_iter().enumerate() {
+                    if segments & (1 << i) != 0 {
+                        lit.push(rect);
+                    } else {
+                        unlit.push(rect);
+                    }
+                }
+            }
+        }
+        let mut fill = |rects: Vec<Rect>, color: LedColor| {
+            canvas.set_iter(rects.into_iter().flat_map(|rect| {
+                (rect.y..rect.y + rect.height)
+                    .flat_map(move |py| (rect.x..rect.x + rect.width).map(move |px| (px, py)))
+                    .map(move |(px, py)| (px, py, color))
+            }));
+        };
+        if let Some(off) = self.off_color {
+            fill(unlit, off);
+        }
+        fill(lit, self.color);
+        self.advance(c)
+    }
+
+    /// Draws `text` with the top left corner of its first character at (`x`, `y`),
+    /// returning its width.
+    pub fn draw(&self, canvas: &mut impl Canvas, x: i32, y: i32, text: &str) -> i32 {
+        text.chars()
+            .fold(0, |pen, c| pen + self.draw_char(canvas, x + pen, y, c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments() {
+        assert_eq!(SevenSegment::segments('8'), Some(0x7F));
+        assert_eq!(SevenSegment::segments('1'), Some(0b110));
+        assert_eq!(SevenSegment::segments('b'), SevenSegment::segments('B'));
+        assert_eq!(SevenSegment::segments('x'), None);
+
+        let digits = SevenSegment::new(10, 21).thickness(3).spacing(2);
+        assert_eq!(digits.measure("12:3x"), 3 * 12 + 5);
+        let rects = digits.segment_rects(0, 0);
+        // a, g and d stacked with equal gaps in between
+        assert_eq!(rects[0], Rect::new(3, 0, 4, 3));
+        assert_eq!(rects[6], Rect::new(3, 9, 4, 3));
+        assert_eq!(rects[3], Rect::new(3, 18, 4, 3));
+        assert_eq!((rects[1].height, rects[2].height), (6, 6));
+        assert_eq!(rects[5], Rect::new(0, 3, 3, 6));
+    }
+}
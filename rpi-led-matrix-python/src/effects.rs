@@ -0,0 +1,100 @@
+//! The animated full-canvas effects, each drawing its next frame with `step`.
+use pyo3::prelude::*;
+
+use crate::matrix::Canvas;
+use crate::{color, Rgb};
+
+macro_rules! effects {
+    ($($name:ident),*) => {
+        $(
+            #[pymethods]
+            impl $name {
+                /// Draws the next frame over the whole canvas.
+                fn step(&mut self, canvas: &mut Canvas) -> PyResult<()> {
+                    self.0.step(canvas.inner()?);
+                    Ok(())
+                }
+            }
+        )*
+
+        pub(crate) fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+            $(module.add_class::<$name>()?;)*
+            Ok(())
+        }
+    };
+}
+
+effects!(Plasma, Fire, MatrixRain, Starfield, ColorWheel);
+
+/// Flowing plasma colors.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Plasma(rlm::effects::Plasma);
+
+#[pymethods]
+impl Plasma {
+    #[new]
+    #[pyo3(signature = (speed = 1.0))]
+    fn new(speed: f32) -> Self {
+        Self(rlm::effects::Plasma::new().speed(speed))
+    }
+}
+
+/// Flames rising from the bottom edge.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Fire(rlm::effects::Fire);
+
+#[pymethods]
+impl Fire {
+    #[new]
+    #[pyo3(signature = (cooling = None))]
+    fn new(cooling: Option<u8>) -> Self {
+        let fire = rlm::effects::Fire::new();
+        Self(match cooling {
+            Some(cooling) => fire.cooling(cooling),
+            None => fire,
+        })
+    }
+}
+
+/// Glyph trails falling down.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct MatrixRain(rlm::effects::MatrixRain);
+
+#[pymethods]
+impl MatrixRain {
+    #[new]
+    #[pyo3(signature = (rgb = None))]
+    fn new(rgb: Option<Rgb>) -> Self {
+        let rain = rlm::effects::MatrixRain::new();
+        Self(match rgb {
+            Some(rgb) => rain.color(color(rgb)),
+            None => rain,
+        })
+    }
+}
+
+/// `count` stars flying towards the viewer.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Starfield(rlm::effects::Starfield);
+
+#[pymethods]
+impl Starfield {
+    #[new]
+    #[pyo3(signature = (count = 100, speed = 1.0))]
+    fn new(count: usize, speed: f32) -> Self {
+        Self(rlm::effects::Starfield::new(count).speed(speed))
+    }
+}
+
+/// Hues rotating around the center.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct ColorWheel(rlm::effects::ColorWheel);
+
+#[pymethods]
+impl ColorWheel {
+    #[new]
+    #[pyo3(signature = (speed = 1.0))]
+    fn new(speed: f32) -> Self {
+        Self(rlm::effects::ColorWheel::new().speed(speed))
+    }
+}
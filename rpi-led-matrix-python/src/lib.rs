@@ -0,0 +1,47 @@
+//! Python bindings of [rpi-led-matrix](https://docs.rs/rpi-led-matrix/), built with `maturin`
+//! into the `rpi_led_matrix` module.
+//!
+//! Colors are `(red, green, blue)` tuples, points `(x, y)` tuples, and errors of the crate
+//! raise `RuntimeError`.
+//!
+//! ```python
+//! import rpi_led_matrix as rlm
+//! matrix = rlm.LedMatrix(rows=32, cols=64)
+//! font = rlm.Font("fonts/6x10.bdf")
+//! fire = rlm.Fire()
+//! canvas = matrix.offscreen_canvas()
+//! while True:
+//!     fire.step(canvas)
+//!     canvas.draw_text(font, "Hello", 2, 10, (255, 255, 255))
+//!     canvas = matrix.swap(canvas)
+//! ```
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use rlm::LedColor;
+
+mod effects;
+mod matrix;
+mod widgets;
+
+/// A color passed from Python
+type Rgb = (u8, u8, u8);
+
+const fn color((red, green, blue): Rgb) -> LedColor {
+    LedColor { red, green, blue }
+}
+
+/// Raises an error of the crate in Python.
+fn error(error: &'static str) -> PyErr {
+    PyRuntimeError::new_err(error)
+}
+
+#[pymodule]
+fn rpi_led_matrix(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<matrix::Matrix>()?;
+    module.add_class::<matrix::Canvas>()?;
+    module.add_class::<matrix::Font>()?;
+    effects::register(module)?;
+    module.add_class::<widgets::SevenSegment>()?;
+    module.add_class::<widgets::Ticker>()?;
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! The matrix, its canvases and fonts.
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rlm::{
+    Direction, LedCanvas, LedFont, LedMatrix, LedMatrixOptions, TextDrawOptions, Transition,
+    TransitionKind,
+};
+
+use crate::{color, error, Rgb};
+
+/// The LED panels, created with the most common options of the C++ library.
+#[pyclass(name = "LedMatrix", module = "rpi_led_matrix", unsendable)]
+pub struct Matrix {
+    matrix: LedMatrix,
+}
+
+#[pymethods]
+impl Matrix {
+    #[new]
+    #[pyo3(signature = (
+        rows = 32,
+        cols = 32,
+        chain_length = 1,
+        parallel = 1,
+        hardware_mapping = None,
+        brightness = 100,
+        pwm_bits = 11,
+    ))]
+    fn new(
+        rows: u32,
+        cols: u32,
+        chain_length: u32,
+        parallel: u32,
+        hardware_mapping: Option<&str>,
+        brightness: u8,
+        pwm_bits: u8,
+    ) -> PyResult<Self> {
+        let mut options = LedMatrixOptions::new();
+        options.set_rows(rows);
+        options.set_cols(cols);
+        options.set_chain_length(chain_length);
+        options.set_parallel(parallel);
+        if let Some(mapping) = hardware_mapping {
+            options.set_hardware_mapping(mapping);
+        }
+        options.set_brightness(brightness).map_err(error)?;
+        options.set_pwm_bits(pwm_bits).map_err(error)?;
+        let matrix = LedMatrix::new(Some(options), None).map_err(error)?;
+        Ok(Self { matrix })
+    }
+
+    /// The canvas shown on the panels right now
+    fn canvas(&self) -> Canvas {
+        Canvas::new(self.matrix.canvas())
+    }
+
+    /// A canvas to draw the next frame on, shown by `swap`
+    fn offscreen_canvas(&self) -> Canvas {
+        Canvas::new(self.matrix.offscreen_canvas())
+    }
+
+    /// Shows `canvas` at the next vertical sync and returns the canvas to draw on next.
+    /// `canvas` can't be drawn on anymore.
+    fn swap(&self, canvas: &mut Canvas) -> PyResult<Canvas> {
+        let shown = canvas.canvas.take().ok_or_else(|| error(SWAPPED))?;
+        Ok(Canvas::new(self.matrix.swap(shown)))
+    }
+
+    /// The brightness in percent
+    #[getter]
+    fn brightness(&self) -> u8 {
+        self.matrix.brightness()
+    }
+
+    #[setter]
+    fn set_brightness(&self, brightness: u8) -> PyResult<()> {
+        self.matrix.set_brightness(brightness).map_err(error)
+    }
+
+    /// Plays a transition from the frame shown to the next ones over `frames` swaps: `slide`
+    /// or `wipe` towards `direction`, `fade` or `dissolve`.
+    #[pyo3(signature = (kind, frames, direction = "left"))]
+    fn start_transition(&self, kind: &str, frames: u32, direction: &str) -> PyResult<()> {
+        let direction = match direction {
+            "left" => Direction::Left,
+            "right" => Direction::Right,
+            "up" => Direction::Up,
+            "down" => Direction::Down,
+            _ => return Err(error("Unknown direction")),
+        };
+        let kind = match kind {
+            "slide" => TransitionKind::Slide(direction),
+            "wipe" => TransitionKind::Wipe(direction),
+            "fade" => TransitionKind::Fade,
+            "dissolve" => TransitionKind::Dissolve,
+            _ => return Err(error("Unknown transition")),
+        };
+        self.matrix.start_transition(Transition::new(kind, frames));
+        Ok(())
+    }
+}
+
+const SWAPPED: &str = "Canvas was swapped";
+
+/// A canvas to draw on, from `LedMatrix.offscreen_canvas` or `LedMatrix.swap`.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Canvas {
+    /// Taken when swapped
+    canvas: Option<LedCanvas>,
+}
+
+impl Canvas {
+    const fn new(canvas: LedCanvas) -> Self {
+        Self {
+            canvas: Some(canvas),
+        }
+    }
+
+    /// The canvas, if it wasn't swapped yet
+    pub(crate) fn inner(&mut self) -> PyResult<&mut LedCanvas> {
+        self.canvas.as_mut().ok_or_else(|| error(SWAPPED))
+    }
+}
+
+#[pymethods]
+impl Canvas {
+    /// The width and height in pixels
+    #[getter]
+    fn size(&mut self) -> PyResult<(i32, i32)> {
+        Ok(self.inner()?.canvas_size())
+    }
+
+    /// The color of the pixel at (`x`, `y`), or `None` off the canvas
+    fn get_pixel(&mut self, x: i32, y: i32) -> PyResult<Option<Rgb>> {
+        let color = self.inner()?.get(x, y);
+        Ok(color.map(|c| (c.red, c.green, c.blue)))
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.set(x, y, &color(rgb));
+        Ok(())
+    }
+
+    fn fill(&mut self, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.fill(&color(rgb));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> PyResult<()> {
+        self.inner()?.clear();
+        Ok(())
+    }
+
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.draw_line(x0, y0, x1, y1, &color(rgb));
+        Ok(())
+    }
+
+    /// Draws an anti-aliased line, blending it into the pixels below.
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.draw_line_aa(x0, y0, x1, y1, &color(rgb));
+        Ok(())
+    }
+
+    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.draw_circle(x, y, radius, &color(rgb));
+        Ok(())
+    }
+
+    /// Draws connected lines through all points, `width` pixels wide.
+    #[pyo3(signature = (points, rgb, width = 1))]
+    fn draw_polyline(&mut self, points: Vec<(i32, i32)>, rgb: Rgb, width: u32) -> PyResult<()> {
+        self.inner()?.draw_polyline(&points, width, &color(rgb));
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: Vec<(i32, i32)>, rgb: Rgb) -> PyResult<()> {
+        self.inner()?.fill_polygon(&points, &color(rgb));
+        Ok(())
+    }
+
+    /// Draws `text` with its baseline starting at (`x`, `y`), returning its width.
+    fn draw_text(&mut self, font: &Font, text: &str, x: i32, y: i32, rgb: Rgb) -> PyResult<i32> {
+        let color = color(rgb);
+        let options = TextDrawOptions::new().position(x, y).color(&color);
+        Ok(self.inner()?.draw_text(&font.font, text, &options))
+    }
+}
+
+/// A BDF font.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Font {
+    pub(crate) font: Arc<LedFont>,
+}
+
+#[pymethods]
+impl Font {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let font = LedFont::new(&path).map_err(error)?;
+        Ok(Self {
+            font: Arc::new(font),
+        })
+    }
+}
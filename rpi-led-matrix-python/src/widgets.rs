@@ -0,0 +1,67 @@
+//! Widgets drawn onto a canvas.
+use pyo3::prelude::*;
+use std::time::Duration;
+
+use rlm::widgets::ticker::{self, TickerHandle, TickerItem};
+use rlm::widgets::{self as widgets, SharedTheme, Theme};
+use rlm::Scene;
+
+use crate::matrix::{Canvas, Font};
+use crate::{color, Rgb};
+
+/// Digits drawn as the segments of a seven-segment display, each `width` by `height` pixels.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct SevenSegment(widgets::SevenSegment);
+
+#[pymethods]
+impl SevenSegment {
+    #[new]
+    #[pyo3(signature = (width, height, rgb = (255, 0, 0)))]
+    fn new(width: i32, height: i32, rgb: Rgb) -> Self {
+        Self(widgets::SevenSegment::new(width, height).color(color(rgb)))
+    }
+
+    /// Draws `text` with its top left corner at (`x`, `y`), returning its width.
+    fn draw(&self, canvas: &mut Canvas, x: i32, y: i32, text: &str) -> PyResult<i32> {
+        Ok(self.0.draw(canvas.inner()?, x, y, text))
+    }
+
+    /// The width `text` would be drawn with
+    fn measure(&self, text: &str) -> i32 {
+        self.0.measure(text)
+    }
+}
+
+/// Text items scrolling from right to left, `speed` pixels per second.
+#[pyclass(module = "rpi_led_matrix", unsendable)]
+pub struct Ticker {
+    ticker: ticker::Ticker,
+    handle: TickerHandle,
+}
+
+#[pymethods]
+impl Ticker {
+    #[new]
+    #[pyo3(signature = (font, speed = 20.0, separator = " | "))]
+    fn new(font: &Font, speed: f32, separator: &str) -> Self {
+        let theme = Theme::day().with_font(Theme::DEFAULT_FONT, font.font.clone());
+        let ticker = ticker::Ticker::new(SharedTheme::new(theme))
+            .speed(speed)
+            .separator(separator);
+        let handle = ticker.handle();
+        Self { ticker, handle }
+    }
+
+    /// Replaces the items once the ones shown have scrolled by.
+    fn set_items(&self, items: Vec<String>) {
+        let items = items.iter().map(|text| TickerItem::new(text)).collect();
+        self.handle.set_items(items);
+    }
+
+    /// Draws the ticker `elapsed_seconds` after it started scrolling.
+    fn render(&mut self, canvas: &mut Canvas, elapsed_seconds: f64) -> PyResult<()> {
+        let elapsed = Duration::from_secs_f64(elapsed_seconds.max(0.0));
+        self.ticker.render(canvas.inner()?, elapsed);
+        Ok(())
+    }
+}
@@ -4,58 +4,73 @@
 use crate::*;
 use libc::{c_char, c_int};
 
-#[no_mangle]
-extern "C" fn led_matrix_create_from_options_and_rt_options(
-    _opts: *mut CLedMatrixOptions,
-    _rt_opts: *mut CLedRuntimeOptions,
-) -> *mut CLedMatrix {
-    std::ptr::null_mut()
-}
-
-#[no_mangle]
-extern "C" fn led_matrix_delete(_matrix: *mut CLedMatrix) {}
-
-#[no_mangle]
-extern "C" fn led_matrix_get_canvas(_matrix: *mut CLedMatrix) -> *mut CLedCanvas {
-    std::ptr::null_mut()
-}
-
-#[no_mangle]
-extern "C" fn led_canvas_get_size(
-    _canvas: *const CLedCanvas,
-    _width: *mut c_int,
-    _height: *mut c_int,
-) {
-}
-
-#[no_mangle]
-extern "C" fn led_canvas_set_pixel(
-    _canvas: *mut CLedCanvas,
-    _x: c_int,
-    _y: c_int,
-    _r: u8,
-    _g: u8,
-    _b: u8,
-) {
-}
-
-#[no_mangle]
-extern "C" fn led_canvas_clear(_canvas: *mut CLedCanvas) {}
-
-#[no_mangle]
-extern "C" fn led_canvas_fill(_canvas: *mut CLedCanvas, _r: u8, _g: u8, _b: u8) {}
-
-#[no_mangle]
-extern "C" fn led_matrix_create_offscreen_canvas(_matrix: *mut CLedMatrix) -> *mut CLedCanvas {
-    std::ptr::null_mut()
-}
-
-#[no_mangle]
-extern "C" fn led_matrix_swap_on_vsync(
-    _matrix: *mut CLedMatrix,
-    _canvas: *mut CLedCanvas,
-) -> *mut CLedCanvas {
-    std::ptr::null_mut()
+/// Replaced by the simulator when it's enabled
+#[cfg(not(any(feature = "simulator-terminal", feature = "simulator-window")))]
+mod matrix {
+    use crate::*;
+    use libc::c_int;
+
+    #[no_mangle]
+    extern "C" fn led_matrix_create_from_options_and_rt_options(
+        _opts: *mut CLedMatrixOptions,
+        _rt_opts: *mut CLedRuntimeOptions,
+    ) -> *mut CLedMatrix {
+        std::ptr::null_mut()
+    }
+
+    #[no_mangle]
+    extern "C" fn led_matrix_delete(_matrix: *mut CLedMatrix) {}
+
+    #[no_mangle]
+    extern "C" fn led_matrix_get_canvas(_matrix: *mut CLedMatrix) -> *mut CLedCanvas {
+        std::ptr::null_mut()
+    }
+
+    #[no_mangle]
+    extern "C" fn led_matrix_get_brightness(_matrix: *mut CLedMatrix) -> u8 {
+        0
+    }
+
+    #[no_mangle]
+    extern "C" fn led_matrix_set_brightness(_matrix: *mut CLedMatrix, _brightness: u8) {}
+
+    #[no_mangle]
+    extern "C" fn led_canvas_get_size(
+        _canvas: *const CLedCanvas,
+        _width: *mut c_int,
+        _height: *mut c_int,
+    ) {
+    }
+
+    #[no_mangle]
+    extern "C" fn led_canvas_set_pixel(
+        _canvas: *mut CLedCanvas,
+        _x: c_int,
+        _y: c_int,
+        _r: u8,
+        _g: u8,
+        _b: u8,
+    ) {
+    }
+
+    #[no_mangle]
+    extern "C" fn led_canvas_clear(_canvas: *mut CLedCanvas) {}
+
+    #[no_mangle]
+    extern "C" fn led_canvas_fill(_canvas: *mut CLedCanvas, _r: u8, _g: u8, _b: u8) {}
+
+    #[no_mangle]
+    extern "C" fn led_matrix_create_offscreen_canvas(_matrix: *mut CLedMatrix) -> *mut CLedCanvas {
+        std::ptr::null_mut()
+    }
+
+    #[no_mangle]
+    extern "C" fn led_matrix_swap_on_vsync(
+        _matrix: *mut CLedMatrix,
+        _canvas: *mut CLedCanvas,
+    ) -> *mut CLedCanvas {
+        std::ptr::null_mut()
+    }
 }
 
 #[no_mangle]
@@ -7,6 +7,20 @@
 //! Instead of linking to the C++ library, we make stub C functions ourselves with the same
 //! signature to enable limited testing on non-raspberry pi computers.
 //!
+//! ## `simulator-terminal`
+//!
+//! Builds on `c-stubs`, but implements the matrix and its canvases in Rust, showing the panel
+//! in the terminal as 24-bit colored half blocks, two pixels per character. A refresh thread
+//! redraws the screen whenever the active canvas changes, so programs run unmodified on a
+//! computer without a panel. The panel is `cols * chain_length` pixels wide and
+//! `rows * parallel` pixels tall, pixel mappers are ignored.
+//!
+//! ## `simulator-window`
+//!
+//! Like `simulator-terminal`, but shows the panel in a desktop window, with every pixel drawn
+//! as a separate square like an LED. Closing the window exits the program. Without a display
+//! to open the window on, the panel is shown in the terminal instead.
+//!
 //! ## `stdcpp-static-link`
 //!
 //! By default, we link dynamically to `libstdc++` as the underlying C++ library requires access
@@ -23,6 +37,8 @@ use libc::{c_char, c_int};
 
 #[cfg(feature = "c-stubs")]
 pub mod c_stubs;
+#[cfg(any(feature = "simulator-terminal", feature = "simulator-window"))]
+pub mod simulator;
 
 /// The C handle for `LedMatrix`.
 pub enum CLedMatrix {}
@@ -78,6 +94,8 @@ extern "C" {
     ) -> *mut CLedMatrix;
     pub fn led_matrix_delete(matrix: *mut CLedMatrix);
     pub fn led_matrix_get_canvas(matrix: *mut CLedMatrix) -> *mut CLedCanvas;
+    pub fn led_matrix_get_brightness(matrix: *mut CLedMatrix) -> u8;
+    pub fn led_matrix_set_brightness(matrix: *mut CLedMatrix, brightness: u8);
     pub fn led_canvas_get_size(canvas: *const CLedCanvas, width: *mut c_int, height: *mut c_int);
     pub fn led_canvas_set_pixel(canvas: *mut CLedCanvas, x: c_int, y: c_int, r: u8, g: u8, b: u8);
     pub fn led_canvas_clear(canvas: *mut CLedCanvas);
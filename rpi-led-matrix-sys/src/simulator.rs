@@ -0,0 +1,351 @@
+//! rust implementation of the matrix and canvas part of the C++ library, showing the panel in
+//! a window or the terminal instead of driving the GPIO pins
+#![allow(clippy::wildcard_imports)]
+use crate::*;
+use libc::c_int;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often the refresh thread checks for changes to show
+const REFRESH_INTERVAL: Duration = Duration::from_millis(16);
+
+struct Canvas {
+    width: c_int,
+    height: c_int,
+    pixels: Mutex<Vec<[u8; 3]>>,
+    /// Set when the pixels changed since they were last shown
+    dirty: AtomicBool,
+}
+
+impl Canvas {
+    fn new(width: c_int, height: c_int) -> Box<Self> {
+        Box::new(Self {
+            width,
+            height,
+            pixels: Mutex::new(vec![[0; 3]; (width * height) as usize]),
+            dirty: AtomicBool::new(true),
+        })
+    }
+
+    fn pixels(&self) -> MutexGuard<'_, Vec<[u8; 3]>> {
+        self.dirty.store(true, Ordering::Relaxed);
+        self.pixels
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// State shared with the refresh thread
+struct Display {
+    active: AtomicPtr<Canvas>,
+    brightness: AtomicU8,
+    running: AtomicBool,
+}
+
+struct Simulator {
+    display: Arc<Display>,
+    /// Every canvas of the matrix, freed together with it. Boxed so the handles stay valid
+    #[allow(clippy::vec_box)]
+    canvases: Mutex<Vec<Box<Canvas>>>,
+    refresh: Option<JoinHandle<()>>,
+}
+
+/// Dims `color` to `brightness` percent.
+fn dim(color: [u8; 3], brightness: u8) -> [u8; 3] {
+    color.map(|channel| (u16::from(channel) * u16::from(brightness.min(100)) / 100) as u8)
+}
+
+/// Where the refresh thread shows the active canvas
+trait Screen {
+    /// Shows `canvas`, whose pixels or brightness `changed` since the last call. Returns false
+    /// once the screen was closed.
+    fn show(&mut self, canvas: &Canvas, brightness: u8, changed: bool) -> bool;
+}
+
+/// The terminal, showing two pixel rows per line of text as half blocks
+struct Terminal(std::io::Stdout);
+
+impl Terminal {
+    fn new() -> Self {
+        let mut stdout = std::io::stdout();
+        let _ = write!(stdout, "\x1b[2J\x1b[?25l");
+        Self(stdout)
+    }
+}
+
+impl Screen for Terminal {
+    fn show(&mut self, canvas: &Canvas, brightness: u8, changed: bool) -> bool {
+        if changed {
+            let _ = self.0.write_all(render(canvas, brightness).as_bytes());
+            let _ = self.0.flush();
+        }
+        true
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = write!(self.0, "\x1b[0m\x1b[?25h");
+        let _ = self.0.flush();
+    }
+}
+
+/// Formats `canvas` as rows of half blocks, two pixel rows per line of text.
+fn render(canvas: &Canvas, brightness: u8) -> String {
+    let pixels = canvas
+        .pixels
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut text = String::from("\x1b[H");
+    for y in (0..canvas.height).step_by(2) {
+        for x in 0..canvas.width {
+            let [r, g, b] = dim(pixels[(y * canvas.width + x) as usize], brightness);
+            let _ = write!(text, "\x1b[38;2;{r};{g};{b}m");
+            if y + 1 < canvas.height {
+                let [r, g, b] = dim(pixels[((y + 1) * canvas.width + x) as usize], brightness);
+                let _ = write!(text, "\x1b[48;2;{r};{g};{b}m");
+            }
+            text.push('▀');
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+#[cfg(feature = "simulator-window")]
+mod window {
+    use super::{dim, Canvas, Screen};
+
+    /// Size of a pixel in the window, including the gap to the next one
+    const LED_SIZE: usize = 8;
+
+    /// A desktop window, showing pixels as separate squares like the LEDs of a panel
+    pub(super) struct Window {
+        window: minifb::Window,
+        buffer: Vec<u32>,
+    }
+
+    impl Window {
+        pub(super) fn new(width: usize, height: usize) -> Option<Self> {
+            let (width, height) = (width * LED_SIZE, height * LED_SIZE);
+            let options = minifb::WindowOptions::default();
+            let window = minifb::Window::new("LED matrix", width, height, options).ok()?;
+            Some(Self {
+                window,
+                buffer: vec![0; width * height],
+            })
+        }
+    }
+
+    impl Screen for Window {
+        fn show(&mut self, canvas: &Canvas, brightness: u8, changed: bool) -> bool {
+            if !self.window.is_open() {
+                return false;
+            }
+            if !changed {
+                self.window.update();
+                return true;
+            }
+            let width = canvas.width as usize;
+            let pixels = canvas
+                .pixels
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            for (index, value) in self.buffer.iter_mut().enumerate() {
+                let (x, y) = (index % (width * LED_SIZE), index / (width * LED_SIZE));
+                let gap = x % LED_SIZE == LED_SIZE - 1 || y % LED_SIZE == LED_SIZE - 1;
+                *value = if gap {
+                    0
+                } else {
+                    let pixel = pixels[y / LED_SIZE * width + x / LED_SIZE];
+                    let [r, g, b] = dim(pixel, brightness).map(u32::from);
+                    r << 16 | g << 8 | b
+                };
+            }
+            drop(pixels);
+            let height = canvas.height as usize;
+            let size = (width * LED_SIZE, height * LED_SIZE);
+            self.window
+                .update_with_buffer(&self.buffer, size.0, size.1)
+                .is_ok()
+        }
+    }
+}
+
+/// Opens the window if there's one to open, or the terminal.
+fn open_screen(_width: c_int, _height: c_int) -> Box<dyn Screen> {
+    #[cfg(feature = "simulator-window")]
+    if let Some(window) = window::Window::new(_width as usize, _height as usize) {
+        return Box::new(window);
+    }
+    Box::new(Terminal::new())
+}
+
+fn refresh(display: &Display, mut screen: Box<dyn Screen>) {
+    let mut shown = (std::ptr::null_mut(), 0);
+    while display.running.load(Ordering::Relaxed) {
+        let active = display.active.load(Ordering::Acquire);
+        let brightness = display.brightness.load(Ordering::Relaxed);
+        // SAFETY: canvases live until the refresh thread is joined
+        let canvas = unsafe { &*active };
+        let changed = canvas.dirty.swap(false, Ordering::Relaxed) || shown != (active, brightness);
+        shown = (active, brightness);
+        if !screen.show(canvas, brightness, changed) {
+            // like closing the window of any other program
+            drop(screen);
+            std::process::exit(0);
+        }
+        std::thread::sleep(REFRESH_INTERVAL);
+    }
+}
+
+unsafe fn simulator<'a>(matrix: *mut CLedMatrix) -> &'a Simulator {
+    &*matrix.cast::<Simulator>()
+}
+
+unsafe fn canvas<'a>(canvas: *const CLedCanvas) -> &'a Canvas {
+    &*canvas.cast::<Canvas>()
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_create_from_options_and_rt_options(
+    opts: *mut CLedMatrixOptions,
+    _rt_opts: *mut CLedRuntimeOptions,
+) -> *mut CLedMatrix {
+    let opts = &*opts;
+    let width = opts.cols * opts.chain_length;
+    let height = opts.rows * opts.parallel;
+    if width <= 0 || height <= 0 {
+        return std::ptr::null_mut();
+    }
+    let mut active = Canvas::new(width, height);
+    let display = Arc::new(Display {
+        active: AtomicPtr::new(&mut *active),
+        brightness: AtomicU8::new(opts.brightness.clamp(0, 100) as u8),
+        running: AtomicBool::new(true),
+    });
+    let shared = Arc::clone(&display);
+    let refresh = std::thread::spawn(move || refresh(&shared, open_screen(width, height)));
+    let simulator = Simulator {
+        display,
+        canvases: Mutex::new(vec![active]),
+        refresh: Some(refresh),
+    };
+    Box::into_raw(Box::new(simulator)).cast()
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_delete(matrix: *mut CLedMatrix) {
+    if matrix.is_null() {
+        return;
+    }
+    let mut simulator = Box::from_raw(matrix.cast::<Simulator>());
+    simulator.display.running.store(false, Ordering::Relaxed);
+    if let Some(refresh) = simulator.refresh.take() {
+        let _ = refresh.join();
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_get_canvas(matrix: *mut CLedMatrix) -> *mut CLedCanvas {
+    if matrix.is_null() {
+        return std::ptr::null_mut();
+    }
+    simulator(matrix)
+        .display
+        .active
+        .load(Ordering::Acquire)
+        .cast()
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_get_brightness(matrix: *mut CLedMatrix) -> u8 {
+    simulator(matrix).display.brightness.load(Ordering::Relaxed)
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_set_brightness(matrix: *mut CLedMatrix, brightness: u8) {
+    let display = &simulator(matrix).display;
+    display
+        .brightness
+        .store(brightness.min(100), Ordering::Relaxed);
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_canvas_get_size(
+    canvas_: *const CLedCanvas,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    let canvas = canvas(canvas_);
+    *width = canvas.width;
+    *height = canvas.height;
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_canvas_set_pixel(
+    canvas_: *mut CLedCanvas,
+    x: c_int,
+    y: c_int,
+    r: u8,
+    g: u8,
+    b: u8,
+) {
+    let canvas = canvas(canvas_);
+    if (0..canvas.width).contains(&x) && (0..canvas.height).contains(&y) {
+        canvas.pixels()[(y * canvas.width + x) as usize] = [r, g, b];
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_canvas_clear(canvas_: *mut CLedCanvas) {
+    led_canvas_fill(canvas_, 0, 0, 0);
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_canvas_fill(canvas_: *mut CLedCanvas, r: u8, g: u8, b: u8) {
+    if !canvas_.is_null() {
+        canvas(canvas_).pixels().fill([r, g, b]);
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_create_offscreen_canvas(
+    matrix: *mut CLedMatrix,
+) -> *mut CLedCanvas {
+    let simulator = simulator(matrix);
+    let mut canvases = simulator
+        .canvases
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut canvas = Canvas::new(canvases[0].width, canvases[0].height);
+    let handle: *mut Canvas = &mut *canvas;
+    canvases.push(canvas);
+    handle.cast()
+}
+
+#[no_mangle]
+unsafe extern "C" fn led_matrix_swap_on_vsync(
+    matrix: *mut CLedMatrix,
+    canvas: *mut CLedCanvas,
+) -> *mut CLedCanvas {
+    let display = &simulator(matrix).display;
+    display.active.swap(canvas.cast(), Ordering::AcqRel).cast()
+}
+
+/// Copies the pixels of a canvas row by row, e.g. for tests checking what reached the panel.
+///
+/// # Safety
+/// `canvas_` must be a canvas of a matrix that wasn't deleted yet.
+#[must_use]
+pub unsafe fn pixels(canvas_: *const CLedCanvas) -> Vec<[u8; 3]> {
+    canvas(canvas_)
+        .pixels
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
@@ -0,0 +1,25 @@
+/// Example subsetting a BDF font to the characters of a text, e.g. to embed a CJK font.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::LedFont;
+use std::path::Path;
+
+fn main() {
+    let matches = App::new("BDF Subset Example")
+        .about("writes a BDF font holding only the characters of the given text")
+        .version(crate_version!())
+        .arg(arg!(<INPUT> "BDF font to subset"))
+        .arg(arg!(<OUTPUT> "where to write the subset"))
+        .arg(arg!(--text <TEXT> "characters to keep").required_unless_present("text-file"))
+        .arg(arg!(--"text-file" <FILE> "file holding the characters to keep").required(false))
+        .get_matches();
+
+    let text = match matches.value_of("text-file") {
+        Some(path) => std::fs::read_to_string(path).expect("Couldn't read text file"),
+        None => matches.value_of("text").unwrap_or_default().to_string(),
+    };
+    let font = LedFont::new(Path::new(matches.value_of("INPUT").unwrap())).unwrap();
+    let subset = font.subset(text.chars());
+    subset
+        .save(Path::new(matches.value_of("OUTPUT").unwrap()))
+        .unwrap();
+}
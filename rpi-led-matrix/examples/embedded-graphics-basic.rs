@@ -38,13 +38,10 @@ fn main() {
     // Draw a 3px wide outline around the matrix.
     // let display_size = canvas.size();
     let (width, height) = canvas.canvas_size();
-    Rectangle::with_corners(
-        Point::zero(),
-        Point::new(width as i32 - 1, height as i32 - 1),
-    )
-    .into_styled(thin_stroke)
-    .draw(&mut canvas)
-    .unwrap();
+    Rectangle::with_corners(Point::zero(), Point::new(width - 1, height - 1))
+        .into_styled(thin_stroke)
+        .draw(&mut canvas)
+        .unwrap();
 
     // Draw a triangle.
     Triangle::new(
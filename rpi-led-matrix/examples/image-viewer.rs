@@ -0,0 +1,40 @@
+/// Example showing an image, optionally equalized for dim panels.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::{args, LedImage, LedMatrix};
+
+const DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Image Viewer Example")
+            .about("shows an image scaled to the matrix")
+            .version(crate_version!())
+            .arg(arg!(<IMAGE> "image file to show"))
+            .arg(
+                arg!(--equalize <LEVEL> "equalize the image, lifting everything not black to at least LEVEL")
+                    .required(false),
+            ),
+    );
+    let matches = app.get_matches();
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+    let mut canvas = matrix.offscreen_canvas();
+
+    let path = std::path::Path::new(matches.value_of("IMAGE").unwrap());
+    let mut image = LedImage::open(path).unwrap().fit(canvas.canvas_size());
+    if let Some(level) = matches.value_of("equalize") {
+        image = image.equalize(level.parse().expect("Invalid value given for equalize"));
+    }
+
+    let (width, height) = canvas.canvas_size();
+    canvas.clear();
+    canvas.draw_image(
+        &image,
+        (width - image.width() as i32) / 2,
+        (height - image.height() as i32) / 2,
+    );
+    let _ = matrix.swap(canvas);
+
+    std::thread::sleep(DELAY);
+}
@@ -0,0 +1,26 @@
+/// Example showing frames mirrored from a desktop by the `mirror-sender` example.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::{args, LedMatrix, MirrorReceiver};
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Mirror Receiver Example")
+            .about("shows frames streamed by the mirror-sender example")
+            .version(crate_version!())
+            .arg(arg!(--listen <ADDRESS> "address to listen on").default_value("0.0.0.0:7070")),
+    );
+    let matches = app.get_matches();
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+    let mut canvas = matrix.offscreen_canvas();
+    let mut receiver = MirrorReceiver::bind(matches.value_of("listen").unwrap()).unwrap();
+
+    loop {
+        if receiver.receive().unwrap() {
+            canvas.clear();
+            receiver.draw(&mut canvas);
+            canvas = matrix.swap(canvas);
+        }
+    }
+}
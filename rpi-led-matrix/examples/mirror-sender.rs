@@ -0,0 +1,60 @@
+/// Example streaming a screen region to the `mirror-receiver` example.
+///
+/// Raw RGB frames are read from stdin, e.g. captured with ffmpeg on an X11 desktop:
+///
+/// ```text
+/// ffmpeg -f x11grab -framerate 30 -video_size 640x320 -i :0.0+100,200 \
+///     -f rawvideo -pix_fmt rgb24 - | mirror-sender --input 640x320 --output 64x32 matrix:7070
+/// ```
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::MirrorEncoder;
+use std::io::Read;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+fn size(value: &str) -> (u32, u32) {
+    let (width, height) = value
+        .split_once('x')
+        .expect("Sizes are given as WIDTHxHEIGHT");
+    (
+        width.parse().expect("Invalid width"),
+        height.parse().expect("Invalid height"),
+    )
+}
+
+fn main() {
+    let matches = App::new("Mirror Sender Example")
+        .about("streams raw RGB frames from stdin to the mirror-receiver example")
+        .version(crate_version!())
+        .arg(arg!(<TARGET> "address of the receiver"))
+        .arg(arg!(--input <SIZE> "size of the frames on stdin, e.g. 640x320"))
+        .arg(arg!(--output <SIZE> "size of the matrix, e.g. 64x32"))
+        .arg(arg!(--fps <FPS> "maximum frames sent per second").default_value("30"))
+        .get_matches();
+
+    let input = size(matches.value_of("input").unwrap());
+    let (width, height) = size(matches.value_of("output").unwrap());
+    let fps: f64 = matches
+        .value_of("fps")
+        .unwrap()
+        .parse()
+        .expect("Invalid fps");
+    let frame_time = Duration::from_secs_f64(1.0 / fps);
+
+    let mut stream = TcpStream::connect(matches.value_of("TARGET").unwrap()).unwrap();
+    stream.set_nodelay(true).unwrap();
+    let mut encoder = MirrorEncoder::new(width as u16, height as u16);
+    let mut frame = vec![0; input.0 as usize * input.1 as usize * 3];
+    let mut stdin = std::io::stdin().lock();
+    let mut next = Instant::now();
+
+    // frames arriving faster than the frame rate are dropped
+    while stdin.read_exact(&mut frame).is_ok() {
+        let now = Instant::now();
+        if now < next {
+            continue;
+        }
+        next = now + frame_time;
+        encoder.send(&mut stream, &frame, input).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+/// Pong against the computer, the left paddle moved with the w and s keys in the terminal.
+/// Quit with q.
+use clap::{crate_version, App};
+use rpi_led_matrix::{args, Game, GameLoop, LedCanvas, LedColor, LedMatrix};
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+const PADDLE: i32 = 6;
+const WHITE: LedColor = LedColor {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+
+/// Switches the terminal to unbuffered input without echo, returning the previous settings.
+fn raw_terminal() -> libc::termios {
+    unsafe {
+        let mut settings = std::mem::zeroed::<libc::termios>();
+        libc::tcgetattr(libc::STDIN_FILENO, &mut settings);
+        let previous = settings;
+        settings.c_lflag &= !(libc::ICANON | libc::ECHO);
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &settings);
+        previous
+    }
+}
+
+/// Reads key presses from stdin on a background thread.
+fn keys() -> Receiver<u8> {
+    let (send, receive) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut key = [0];
+        while std::io::stdin().read_exact(&mut key).is_ok() && send.send(key[0]).is_ok() {}
+    });
+    receive
+}
+
+struct Pong {
+    keys: Receiver<u8>,
+    size: (f32, f32),
+    /// Top of the player's and the computer's paddle
+    paddles: [f32; 2],
+    ball: (f32, f32),
+    /// Ball speed in pixels per second
+    velocity: (f32, f32),
+    score: [u32; 2],
+    quit: bool,
+}
+
+impl Pong {
+    fn new((width, height): (i32, i32)) -> Self {
+        let size = (width as f32, height as f32);
+        Self {
+            keys: keys(),
+            size,
+            paddles: [(size.1 - PADDLE as f32) / 2.0; 2],
+            ball: (size.0 / 2.0, size.1 / 2.0),
+            velocity: (20.0, 12.0),
+            score: [0; 2],
+            quit: false,
+        }
+    }
+
+    /// Serves the ball from the center towards the player who lost the point
+    fn serve(&mut self, towards: f32) {
+        self.ball = (self.size.0 / 2.0, self.size.1 / 2.0);
+        self.velocity = (20.0 * towards, self.velocity.1);
+    }
+}
+
+impl Game for Pong {
+    fn poll_input(&mut self) {
+        while let Ok(key) = self.keys.try_recv() {
+            match key {
+                b'w' => self.paddles[0] -= 2.0,
+                b's' => self.paddles[0] += 2.0,
+                b'q' => self.quit = true,
+                _ => {}
+            }
+        }
+        self.paddles[0] = self.paddles[0].clamp(0.0, self.size.1 - PADDLE as f32);
+    }
+
+    fn update(&mut self, step: Duration) {
+        let seconds = step.as_secs_f32();
+        // the computer follows the ball, a little slower than it can move
+        let target = self.ball.1 - PADDLE as f32 / 2.0;
+        let reach = 10.0 * seconds;
+        self.paddles[1] += (target - self.paddles[1]).clamp(-reach, reach);
+        self.paddles[1] = self.paddles[1].clamp(0.0, self.size.1 - PADDLE as f32);
+
+        self.ball.0 += self.velocity.0 * seconds;
+        self.ball.1 += self.velocity.1 * seconds;
+        if self.ball.1 < 0.0 || self.ball.1 >= self.size.1 - 1.0 {
+            self.velocity.1 = -self.velocity.1;
+            self.ball.1 = self.ball.1.clamp(0.0, self.size.1 - 1.0);
+        }
+        let hits = |paddle: f32, y: f32| (paddle - 1.0..paddle + PADDLE as f32).contains(&y);
+        if self.ball.0 < 1.0 && self.velocity.0 < 0.0 {
+            if hits(self.paddles[0], self.ball.1) {
+                self.velocity.0 = -self.velocity.0 * 1.05;
+            } else {
+                self.score[1] += 1;
+                self.serve(1.0);
+            }
+        }
+        if self.ball.0 >= self.size.0 - 2.0 && self.velocity.0 > 0.0 {
+            if hits(self.paddles[1], self.ball.1) {
+                self.velocity.0 = -self.velocity.0 * 1.05;
+            } else {
+                self.score[0] += 1;
+                self.serve(-1.0);
+            }
+        }
+    }
+
+    fn render(&mut self, canvas: &mut LedCanvas, alpha: f32) {
+        canvas.clear();
+        let right = self.size.0 as i32 - 1;
+        for (x, paddle) in [(0, self.paddles[0]), (right, self.paddles[1])] {
+            canvas.draw_line(x, paddle as i32, x, paddle as i32 + PADDLE - 1, &WHITE);
+        }
+        // the ball where it is in between updates
+        let step = 1.0 / 60.0 * alpha;
+        canvas.set(
+            (self.ball.0 + self.velocity.0 * step) as i32,
+            (self.ball.1 + self.velocity.1 * step) as i32,
+            &WHITE,
+        );
+    }
+
+    fn is_over(&self) -> bool {
+        self.quit || self.score.contains(&10)
+    }
+}
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Pong Example")
+            .about("plays pong against the computer, moved with w and s")
+            .version(crate_version!()),
+    );
+    let matches = app.get_matches();
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+
+    let terminal = raw_terminal();
+    let mut pong = Pong::new(matrix.canvas().canvas_size());
+    GameLoop::new(60).run(&matrix, &mut pong);
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &terminal) };
+    println!("{} : {}", pong.score[0], pong.score[1]);
+}
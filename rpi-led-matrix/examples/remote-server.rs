@@ -0,0 +1,28 @@
+/// Example showing frames drawn on a `RemoteMatrix` on another machine.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::{args, LedMatrix, MirrorReceiver};
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Remote Server Example")
+            .about("shows frames drawn on a RemoteMatrix over the network")
+            .version(crate_version!())
+            .arg(arg!(--listen <ADDRESS> "address to listen on").default_value("0.0.0.0:7071")),
+    );
+    let matches = app.get_matches();
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+    let mut canvas = matrix.offscreen_canvas();
+    let (width, height) = canvas.canvas_size();
+    let mut receiver = MirrorReceiver::bind(matches.value_of("listen").unwrap())
+        .unwrap()
+        .announce(width as u16, height as u16);
+
+    loop {
+        if receiver.receive().unwrap() {
+            receiver.draw(&mut canvas);
+            canvas = matrix.swap(canvas);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+/// Snake on the matrix, steered with the w, a, s and d keys in the terminal. Quit with q.
+use clap::{crate_version, App};
+use rpi_led_matrix::{args, Game, GameLoop, LedCanvas, LedColor, LedMatrix};
+use std::collections::VecDeque;
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Switches the terminal to unbuffered input without echo, returning the previous settings.
+fn raw_terminal() -> libc::termios {
+    unsafe {
+        let mut settings = std::mem::zeroed::<libc::termios>();
+        libc::tcgetattr(libc::STDIN_FILENO, &mut settings);
+        let previous = settings;
+        settings.c_lflag &= !(libc::ICANON | libc::ECHO);
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &settings);
+        previous
+    }
+}
+
+/// Reads key presses from stdin on a background thread.
+fn keys() -> Receiver<u8> {
+    let (send, receive) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut key = [0];
+        while std::io::stdin().read_exact(&mut key).is_ok() && send.send(key[0]).is_ok() {}
+    });
+    receive
+}
+
+struct Snake {
+    keys: Receiver<u8>,
+    size: (i32, i32),
+    body: VecDeque<(i32, i32)>,
+    direction: (i32, i32),
+    food: (i32, i32),
+    seed: u32,
+    ticks: u32,
+    over: bool,
+}
+
+impl Snake {
+    fn new(size: (i32, i32)) -> Self {
+        let mut snake = Self {
+            keys: keys(),
+            size,
+            body: VecDeque::from([(size.0 / 2, size.1 / 2)]),
+            direction: (1, 0),
+            food: (0, 0),
+            seed: 0x2545_F491,
+            ticks: 0,
+            over: false,
+        };
+        snake.place_food();
+        snake
+    }
+
+    fn place_food(&mut self) {
+        loop {
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            let food = (
+                (self.seed % self.size.0 as u32) as i32,
+                (self.seed / 7 % self.size.1 as u32) as i32,
+            );
+            if !self.body.contains(&food) {
+                self.food = food;
+                return;
+            }
+        }
+    }
+}
+
+impl Game for Snake {
+    fn poll_input(&mut self) {
+        while let Ok(key) = self.keys.try_recv() {
+            let direction = match key {
+                b'w' => (0, -1),
+                b'a' => (-1, 0),
+                b's' => (0, 1),
+                b'd' => (1, 0),
+                b'q' => {
+                    self.over = true;
+                    continue;
+                }
+                _ => continue,
+            };
+            // no turning back into the body
+            if direction != (-self.direction.0, -self.direction.1) {
+                self.direction = direction;
+            }
+        }
+    }
+
+    fn update(&mut self, _step: Duration) {
+        // the snake moves every fifth update, getting faster as it grows
+        self.ticks += 1;
+        if self.ticks < 5u32.saturating_sub(self.body.len() as u32 / 10).max(1) {
+            return;
+        }
+        self.ticks = 0;
+        let (x, y) = self.body[0];
+        let head = (
+            (x + self.direction.0).rem_euclid(self.size.0),
+            (y + self.direction.1).rem_euclid(self.size.1),
+        );
+        if self.body.contains(&head) {
+            self.over = true;
+            return;
+        }
+        self.body.push_front(head);
+        if head == self.food {
+            self.place_food();
+        } else {
+            self.body.pop_back();
+        }
+    }
+
+    fn render(&mut self, canvas: &mut LedCanvas, _alpha: f32) {
+        canvas.clear();
+        let green = LedColor {
+            red: 0,
+            green: 255,
+            blue: 0,
+        };
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        for &(x, y) in &self.body {
+            canvas.set(x, y, &green);
+        }
+        canvas.set(self.food.0, self.food.1, &red);
+    }
+
+    fn is_over(&self) -> bool {
+        self.over
+    }
+}
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Snake Example")
+            .about("plays snake, steered with w, a, s and d")
+            .version(crate_version!()),
+    );
+    let matches = app.get_matches();
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+
+    let terminal = raw_terminal();
+    let mut snake = Snake::new(matrix.canvas().canvas_size());
+    GameLoop::new(30).run(&matrix, &mut snake);
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &terminal) };
+    println!("Game over, length {}", snake.body.len());
+}
@@ -0,0 +1,46 @@
+/// Example showing the spectrum of a microphone or the system's audio as bars.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::widgets::charts::BarGraph;
+use rpi_led_matrix::widgets::SharedTheme;
+use rpi_led_matrix::{args, AudioInput, LedColor, LedMatrix};
+use std::time::Instant;
+
+fn main() {
+    let app = args::add_matrix_args(
+        App::new("Audio Visualizer Example")
+            .about("shows the spectrum of audio input as bars")
+            .version(crate_version!())
+            .arg(
+                arg!(--device <NAME> "audio input to capture, e.g. a monitor of the output")
+                    .required(false),
+            )
+            .arg(arg!(--list "lists the audio inputs and exits")),
+    );
+    let matches = app.get_matches();
+    if matches.is_present("list") {
+        for device in AudioInput::devices() {
+            println!("{device}");
+        }
+        return;
+    }
+    let (options, rt_options) = args::matrix_options_from_args(&matches);
+    let matrix = LedMatrix::new(Some(options), Some(rt_options)).unwrap();
+    let mut canvas = matrix.offscreen_canvas();
+
+    let input = AudioInput::open(matches.value_of("device")).unwrap();
+    let color = |red, green, blue| LedColor { red, green, blue };
+    let mut graph = BarGraph::new(SharedTheme::default()).gradient(&[
+        color(0, 0, 255),
+        color(0, 255, 0),
+        color(255, 255, 0),
+        color(255, 0, 0),
+    ]);
+    // bars of 2 pixels with a gap of 1 across the whole width
+    let count = (canvas.canvas_size().0 as usize + 1) / 3;
+    let start = Instant::now();
+    loop {
+        canvas.clear();
+        graph.draw(&mut canvas, &input.bands(count), start.elapsed());
+        canvas = matrix.swap(canvas);
+    }
+}
@@ -0,0 +1,51 @@
+/// Walks through the panel setup with the keyboard and writes the result to a config file.
+use clap::{arg, crate_version, App};
+use rpi_led_matrix::wizard::{Answer, PanelConfig, Wizard};
+use std::io::BufRead;
+use std::path::Path;
+
+fn main() {
+    let matches = App::new("Setup Wizard")
+        .about("finds the settings of your panel by showing test patterns")
+        .version(crate_version!())
+        .arg(
+            arg!(--config <FILE> "Config file to start from and write to")
+                .default_value("matrix.conf")
+                .required(false),
+        )
+        .get_matches();
+    let path = Path::new(matches.value_of("config").unwrap());
+    let config = PanelConfig::load(path).unwrap_or_default();
+
+    println!("For every setting, check the test pattern on the panel: a white border around");
+    println!("the whole panel, a yellow diagonal from the top left, and red, green, blue and");
+    println!("white squares in the top left, top right, bottom left and bottom right corners.");
+    println!("Answer with enter to keep a setting, n for the next, p for the previous, q to quit.");
+
+    let mut lines = std::io::stdin().lock().lines();
+    let result = Wizard::new(config).run(|prompt| {
+        println!("{prompt}");
+        match lines.next().and_then(Result::ok).as_deref().map(str::trim) {
+            Some("") => Answer::Accept,
+            Some("p") => Answer::Previous,
+            Some("q") | None => Answer::Quit,
+            Some(_) => Answer::Next,
+        }
+    });
+    match result {
+        Ok(config) => {
+            config.save(path).unwrap();
+            let args = config.to_args();
+            if args.is_empty() {
+                println!("Wrote {}, the defaults fit your panel", path.display());
+            } else {
+                println!(
+                    "Wrote {}, pass these arguments to the other examples:",
+                    path.display()
+                );
+                println!("{}", args.join(" "));
+            }
+        }
+        Err(error) => eprintln!("{error}"),
+    }
+}
@@ -0,0 +1,492 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{LedColor, LedMatrix, Levels};
+
+/// Magic bytes starting a file saved by [`WearTracker::save`]
+const WEAR_MAGIC: &[u8; 8] = b"LEDWEAR1";
+/// Longest time between two frames counted as on-time, so pauses in drawing don't count
+const MAX_FRAME_TIME: Duration = Duration::from_secs(1);
+
+/// A per-pixel brightness map evening out panels where some regions dimmed with age, set with
+/// [`LedMatrix::set_compensation`].
+///
+/// LEDs can't be driven brighter than full, so every pixel is dimmed to the level of the most
+/// worn one: a gain of 1 leaves a pixel as it is, 0.8 shows it at 80 %. The map can come from
+/// a measurement, e.g. a photo of the panel showing white, or be estimated by a
+/// [`WearTracker`].
+///
+/// ```no_run
+/// use rpi_led_matrix::{Compensation, LedMatrix};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let compensation = Compensation::load(Path::new("/etc/led-matrix/aging.pgm")).unwrap();
+/// matrix.set_compensation(Some(compensation));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Compensation {
+    size: (i32, i32),
+    gains: Vec<f32>,
+}
+
+impl Compensation {
+    /// Creates a map for a panel of `size` pixels from the `gains` of its pixels row by row,
+    /// each from 0 to 1.
+    ///
+    /// # Errors
+    /// If there isn't a gain for every pixel.
+    pub fn new(size: (i32, i32), gains: Vec<f32>) -> Result<Self, &'static str> {
+        if gains.len() != (size.0 * size.1).max(0) as usize {
+            return Err("Compensation doesn't match the panel size");
+        }
+        let gains = gains.into_iter().map(|gain| gain.clamp(0.0, 1.0)).collect();
+        Ok(Self { size, gains })
+    }
+
+    /// Creates a map from the measured brightness of every pixel showing the same color, in
+    /// any unit, dimming all pixels to the darkest one.
+    ///
+    /// # Errors
+    /// If there isn't a measurement for every pixel.
+    pub fn from_measurement(size: (i32, i32), levels: &[f32]) -> Result<Self, &'static str> {
+        let darkest = levels
+            .iter()
+            .copied()
+            .filter(|level| *level > 0.0)
+            .fold(f32::INFINITY, f32::min);
+        let gains = levels
+            .iter()
+            .map(|&level| if level > 0.0 { darkest / level } else { 1.0 })
+            .collect();
+        Self::new(size, gains)
+    }
+
+    /// Loads a map saved with [`save`](Self::save), an 8 bit binary PGM image with white as a
+    /// gain of 1, which image editors can open and touch up.
+    ///
+    /// # Errors
+    /// If the file can't be read or isn't a binary PGM image.
+    pub fn load(path: &Path) -> Result<Self, &'static str> {
+        let data = fs::read(path).map_err(|_| "Couldn't read compensation map")?;
+        let mut fields = Vec::new();
+        let mut position = 0;
+        // magic, width, height and maximum, separated by whitespace
+        while fields.len() < 4 {
+            while data.get(position).is_some_and(u8::is_ascii_whitespace) {
+                position += 1;
+            }
+            if data.get(position) == Some(&b'#') {
+                while data.get(position).is_some_and(|&b| b != b'\n') {
+                    position += 1;
+                }
+                continue;
+            }
+            let start = position;
+            while data.get(position).is_some_and(|b| !b.is_ascii_whitespace()) {
+                position += 1;
+            }
+            if start == position {
+                return Err("Truncated compensation map");
+            }
+            fields.push(&data[start..position]);
+        }
+        let number = |field: &[u8]| {
+            std::str::from_utf8(field)
+                .ok()
+                .and_then(|field| field.parse::<i32>().ok())
+                .ok_or("Invalid compensation map")
+        };
+        if fields[0] != b"P5" || number(fields[3])? != 255 {
+            return Err("Compensation map isn't an 8 bit PGM image");
+        }
+        let size = (number(fields[1])?, number(fields[2])?);
+        let pixels = (size.0 * size.1).max(0) as usize;
+        let levels = data
+            .get(position + 1..position + 1 + pixels)
+            .ok_or("Truncated compensation map")?;
+        Self::new(
+            size,
+            levels
+                .iter()
+                .map(|&level| f32::from(level) / 255.0)
+                .collect(),
+        )
+    }
+
+    /// Saves the map as an 8 bit binary PGM image, see [`load`](Self::load).
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        let mut data = format!("P5\n{} {}\n255\n", self.size.0, self.size.1).into_bytes();
+        data.extend(self.gains.iter().map(|gain| (gain * 255.0).round() as u8));
+        fs::write(path, data).map_err(|_| "Couldn't write compensation map")
+    }
+
+    /// The size of the panel the map is for
+    #[must_use]
+    pub const fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// The gain of the pixel at (`x`, `y`), or `None` outside the panel
+    #[must_use]
+    pub fn gain(&self, x: i32, y: i32) -> Option<f32> {
+        let (width, height) = self.size;
+        ((0..width).contains(&x) && (0..height).contains(&y))
+            .then(|| self.gains[(y * width + x) as usize])
+    }
+
+    /// Dims `color` of the pixel at `index` by its gain.
+    pub(crate) fn apply(&self, index: usize, color: LedColor) -> LedColor {
+        let Some(&gain) = self.gains.get(index) else {
+            return color;
+        };
+        let scale = |channel: u8| (f32::from(channel) * gain).round() as u8;
+        LedColor {
+            red: scale(color.red),
+            green: scale(color.green),
+            blue: scale(color.blue),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Wear {
+    size: (i32, i32),
+    /// Width and height of the square of pixels counted together
+    factor: i32,
+    /// Hours every square of pixels was on on average, weighted by brightness
+    hours: Vec<f64>,
+    last: Option<Instant>,
+    /// Where and how often to save, with the time of the last save
+    autosave: Option<(PathBuf, Duration, Option<Instant>)>,
+}
+
+impl Wear {
+    fn new(size: (i32, i32), factor: i32) -> Self {
+        let factor = factor.max(1);
+        let (width, height) = Self::grid(size, factor);
+        Self {
+            size,
+            factor,
+            hours: vec![0.0; (width * height).max(0) as usize],
+            last: None,
+            autosave: None,
+        }
+    }
+
+    /// The size of the grid of squares counted
+    const fn grid(size: (i32, i32), factor: i32) -> (i32, i32) {
+        (
+            (size.0 + factor - 1) / factor,
+            (size.1 + factor - 1) / factor,
+        )
+    }
+
+    /// The index of the square the pixel at (`x`, `y`) is counted in
+    fn cell(&self, x: i32, y: i32) -> usize {
+        let width = Self::grid(self.size, self.factor).0;
+        (y / self.factor * width + x / self.factor) as usize
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut data = WEAR_MAGIC.to_vec();
+        for value in [self.size.0, self.size.1, self.factor] {
+            data.extend(value.to_le_bytes());
+        }
+        for hours in &self.hours {
+            data.extend(hours.to_le_bytes());
+        }
+        data
+    }
+}
+
+/// Tracks the cumulative on-time of every pixel, weighted by brightness, as the data source
+/// for a [`Compensation`] for wear from static content like logos and clocks, and for burn-in
+/// analysis with a heatmap.
+///
+/// Attach it to the matrix and save it regularly, e.g. with [`autosave`](Self::autosave), to
+/// keep counting across restarts. Large panels can be tracked in squares of pixels with
+/// [`downsample`](Self::downsample), to keep the counting and the files small.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, WearTracker};
+/// use std::path::Path;
+/// use std::time::Duration;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let path = Path::new("/var/lib/led-matrix/wear");
+/// let size = matrix.canvas().canvas_size();
+/// let wear = WearTracker::load(path, size)
+///     .unwrap_or_else(|_| WearTracker::new(size).downsample(2))
+///     .autosave(path, Duration::from_secs(3600));
+/// wear.attach(&matrix);
+/// // a fifth of the brightness lost after 10 000 hours at full white
+/// matrix.set_compensation(Some(wear.compensation(0.02)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct WearTracker {
+    wear: Arc<Mutex<Wear>>,
+}
+
+impl WearTracker {
+    /// Creates a tracker for a panel of `size` pixels that haven't been on yet.
+    #[must_use]
+    pub fn new(size: (i32, i32)) -> Self {
+        Self {
+            wear: Arc::new(Mutex::new(Wear::new(size, 1))),
+        }
+    }
+
+    fn wear(&self) -> std::sync::MutexGuard<'_, Wear> {
+        self.wear
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Counts squares of `factor` by `factor` pixels together, starting over from no on-time.
+    #[must_use]
+    pub fn downsample(self, factor: i32) -> Self {
+        let mut wear = self.wear();
+        let autosave = wear.autosave.take();
+        *wear = Wear {
+            autosave,
+            ..Wear::new(wear.size, factor)
+        };
+        drop(wear);
+        self
+    }
+
+    /// Saves the on-time to `path` every `interval` while counting.
+    #[must_use]
+    pub fn autosave(self, path: impl Into<PathBuf>, interval: Duration) -> Self {
+        self.wear().autosave = Some((path.into(), interval, None));
+        self
+    }
+
+    /// Loads the on-time saved with [`save`](Self::save) for a panel of `size` pixels.
+    ///
+    /// # Errors
+    /// If the file can't be read, or was saved for another panel size.
+    pub fn load(path: &Path, size: (i32, i32)) -> Result<Self, &'static str> {
+        let data = fs::read(path).map_err(|_| "Couldn't read wear file")?;
+        let header = data
+            .get(..20)
+            .filter(|header| header[..8] == WEAR_MAGIC[..])
+            .ok_or("Not a wear file")?;
+        let field =
+            |at: usize| i32::from_le_bytes(header[at..at + 4].try_into().unwrap_or_default());
+        if (field(8), field(12)) != size {
+            return Err("Wear file is for another panel size");
+        }
+        let mut wear = Wear::new(size, field(16));
+        let hours: Vec<_> = data[20..]
+            .chunks_exact(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+            .collect();
+        if hours.len() != wear.hours.len() {
+            return Err("Truncated wear file");
+        }
+        wear.hours = hours;
+        Ok(Self {
+            wear: Arc::new(Mutex::new(wear)),
+        })
+    }
+
+    /// Saves the on-time of every pixel.
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        let data = self.wear().encode();
+        fs::write(path, data).map_err(|_| "Couldn't write wear file")
+    }
+
+    /// Counts the on-time of every frame swapped onto `matrix`.
+    pub fn attach(&self, matrix: &LedMatrix) {
+        let tracker = self.clone();
+        matrix.add_pre_swap(move |frame| {
+            tracker.count(frame.pixels(), Instant::now());
+        });
+    }
+
+    /// Counts the time since the last frame for the pixels of the frame now shown.
+    fn count(&self, pixels: &[LedColor], now: Instant) {
+        let mut wear = self.wear();
+        let Some(last) = wear.last.replace(now) else {
+            return;
+        };
+        let hours = now
+            .saturating_duration_since(last)
+            .min(MAX_FRAME_TIME)
+            .as_secs_f64()
+            / 3600.0;
+        let ((width, height), factor) = (wear.size, wear.factor);
+        for (i, color) in (0..width * height).zip(pixels) {
+            let (x, y) = (i % width, i / width);
+            // squares at the right and bottom edges may be cut off
+            let area =
+                factor.min(width - x / factor * factor) * factor.min(height - y / factor * factor);
+            let level = u32::from(color.red) + u32::from(color.green) + u32::from(color.blue);
+            let cell = wear.cell(x, y);
+            wear.hours[cell] += hours * f64::from(level) / (3.0 * 255.0) / f64::from(area);
+        }
+        let Some((path, interval, saved)) = &mut wear.autosave else {
+            return;
+        };
+        let saved = *saved.get_or_insert(now);
+        if now.saturating_duration_since(saved) >= *interval {
+            let path = path.clone();
+            if let Some((.., saved)) = &mut wear.autosave {
+                *saved = Some(now);
+            }
+            let _ = fs::write(path, wear.encode());
+        }
+    }
+
+    /// The hours the pixel at (`x`, `y`) was on at full white, or `None` outside the panel
+    #[must_use]
+    pub fn hours(&self, x: i32, y: i32) -> Option<f64> {
+        let wear = self.wear();
+        let (width, height) = wear.size;
+        ((0..width).contains(&x) && (0..height).contains(&y)).then(|| wear.hours[wear.cell(x, y)])
+    }
+
+    /// The on-time as levels from 0 to 255 relative to the most worn square, one per square
+    /// counted, e.g. to draw as a heatmap with [`Palette::heat`](crate::Palette::heat).
+    #[must_use]
+    pub fn levels(&self) -> Levels {
+        let wear = self.wear();
+        let (width, height) = Wear::grid(wear.size, wear.factor);
+        let most = wear.hours.iter().copied().fold(0.0, f64::max);
+        let mut levels = Levels::new(width, height);
+        for (i, hours) in (0..).zip(&wear.hours) {
+            let level = if most > 0.0 {
+                hours / most * 255.0
+            } else {
+                0.0
+            };
+            levels.set(i % width, i / width, level.round() as u8);
+        }
+        levels
+    }
+
+    /// The on-time as a heatmap image, from black for unused to white for the most worn
+    /// square, one pixel per square counted.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn heatmap(&self) -> crate::LedImage {
+        let levels = self.levels();
+        let palette = crate::Palette::heat();
+        let (width, height) = levels.size();
+        let pixels: Vec<_> = levels
+            .levels()
+            .iter()
+            .map(|&level| palette.get(level))
+            .collect();
+        crate::LedImage::from_pixels(width as u32, height as u32, &pixels)
+            .expect("the heatmap has a pixel per level")
+    }
+
+    /// Estimates a compensation for pixels losing `loss_per_khour` of their brightness for
+    /// every thousand hours at full white, like 0.02 for 2 %.
+    #[must_use]
+    pub fn compensation(&self, loss_per_khour: f32) -> Compensation {
+        let wear = self.wear();
+        let (width, height) = wear.size;
+        let levels: Vec<_> = (0..width * height)
+            .map(|i| {
+                let hours = wear.hours[wear.cell(i % width, i / width)];
+                (1.0 - f64::from(loss_per_khour) * hours / 1000.0).max(0.05) as f32
+            })
+            .collect();
+        Compensation::from_measurement(wear.size, &levels).unwrap_or_else(|_| Compensation {
+            size: wear.size,
+            gains: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compensation() {
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let wear = WearTracker::new((2, 1)).downsample(1);
+        let mut now = Instant::now();
+        wear.count(&[white, black], now);
+        for frame in 0..100 {
+            // pauses only count up to a second
+            now += Duration::from_secs(if frame == 50 { 3600 } else { 1 });
+            wear.count(&[white, black], now);
+        }
+        let hours = wear.hours(0, 0).unwrap();
+        assert!((hours - 100.0 / 3600.0).abs() < 1e-9, "{hours}");
+        assert_eq!(wear.hours(1, 0), Some(0.0));
+
+        let path = std::env::temp_dir().join(format!("led-wear-{}", std::process::id()));
+        wear.save(&path).unwrap();
+        let loaded = WearTracker::load(&path, (2, 1)).unwrap();
+        assert_eq!(loaded.hours(0, 0), Some(hours));
+        assert!(WearTracker::load(&path, (1, 2)).is_err());
+
+        // 20 % lost at the first pixel, so the other one is dimmed to match
+        let compensation = loaded.compensation(7200.0);
+        assert_eq!(compensation.gain(0, 0), Some(1.0));
+        let gain = compensation.gain(1, 0).unwrap();
+        assert!((gain - 0.8).abs() < 1e-4, "{gain}");
+        assert_eq!(
+            compensation.apply(1, white),
+            LedColor {
+                red: 204,
+                green: 204,
+                blue: 204
+            }
+        );
+
+        let path = path.with_extension("pgm");
+        compensation.save(&path).unwrap();
+        let loaded = Compensation::load(&path).unwrap();
+        assert_eq!(loaded.size(), (2, 1));
+        assert_eq!(loaded.gain(1, 0), Some(204.0 / 255.0));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("")).unwrap();
+        assert!(Compensation::new((2, 2), vec![1.0; 3]).is_err());
+
+        // squares of 2 by 2 pixels, the right ones cut off, saved every 10 seconds
+        let path = std::env::temp_dir().join(format!("led-wear-grid-{}", std::process::id()));
+        let wear = WearTracker::new((3, 2))
+            .autosave(&path, Duration::from_secs(10))
+            .downsample(2);
+        let mut pixels = [black; 6];
+        pixels[0] = white;
+        pixels[2] = white;
+        let mut now = Instant::now();
+        for _ in 0..=12 {
+            wear.count(&pixels, now);
+            now += Duration::from_secs(1);
+        }
+        let seconds = |tracker: &WearTracker, x, y| tracker.hours(x, y).unwrap() * 3600.0;
+        // saved ten seconds after the first second counted
+        let saved = WearTracker::load(&path, (3, 2)).unwrap();
+        assert!((seconds(&saved, 0, 0) - 11.0 / 4.0).abs() < 1e-9);
+        assert!((seconds(&wear, 1, 1) - 12.0 / 4.0).abs() < 1e-9);
+        assert!((seconds(&wear, 2, 0) - 12.0 / 2.0).abs() < 1e-9);
+        let levels = wear.levels();
+        assert_eq!((levels.size(), levels.levels()), ((2, 1), &[128, 255][..]));
+        fs::remove_file(path).unwrap();
+    }
+}
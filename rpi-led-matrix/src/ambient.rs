@@ -0,0 +1,281 @@
+use std::time::{Duration, Instant};
+
+use crate::LedColor;
+
+/// A network controlled light following the colors of the panel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AmbientLight {
+    /// A [WLED](https://kno.wled.ge/) controller, set via its JSON API
+    Wled {
+        /// Base URL of the controller, e.g. `http://wled.local`
+        url: String,
+    },
+    /// A Philips Hue light, set via the bridge API
+    Hue {
+        /// Base URL of the bridge, e.g. `http://192.168.1.2`
+        bridge: String,
+        /// User name registered with the bridge
+        username: String,
+        /// ID of the light on the bridge
+        light: u32,
+    },
+}
+
+impl AmbientLight {
+    /// Method, URL and JSON body of the request setting the light to `color`
+    fn request(&self, color: LedColor) -> (&'static str, String, String) {
+        let LedColor { red, green, blue } = color;
+        let on = (red, green, blue) != (0, 0, 0);
+        match self {
+            Self::Wled { url } => (
+                "POST",
+                format!("{}/json/state", url.trim_end_matches('/')),
+                format!(r#"{{"on":{on},"seg":[{{"col":[[{red},{green},{blue}]]}}]}}"#),
+            ),
+            Self::Hue {
+                bridge,
+                username,
+                light,
+            } => {
+                let ((x, y), brightness) = hue_xy(color);
+                let url = format!(
+                    "{}/api/{username}/lights/{light}/state",
+                    bridge.trim_end_matches('/')
+                );
+                let body = if on {
+                    format!(r#"{{"on":true,"xy":[{x:.4},{y:.4}],"bri":{brightness}}}"#)
+                } else {
+                    r#"{"on":false}"#.to_string()
+                };
+                ("PUT", url, body)
+            }
+        }
+    }
+}
+
+/// Converts a color to the CIE xy chromaticity and brightness used by Hue lights.
+fn hue_xy(color: LedColor) -> ((f32, f32), u8) {
+    let linear = |channel: u8| {
+        let value = f32::from(channel) / 255.0;
+        if value > 0.04045 {
+            ((value + 0.055) / 1.055).powf(2.4)
+        } else {
+            value / 12.92
+        }
+    };
+    let (r, g, b) = (linear(color.red), linear(color.green), linear(color.blue));
+    let x = r * 0.664_511 + g * 0.154_324 + b * 0.162_028;
+    let y = r * 0.283_881 + g * 0.668_433 + b * 0.047_685;
+    let z = r * 0.000_088 + g * 0.072_310 + b * 0.986_039;
+    let sum = x + y + z;
+    if sum <= 0.0 {
+        return ((0.0, 0.0), 0);
+    }
+    (
+        (x / sum, y / sum),
+        (y * 254.0).round().clamp(1.0, 254.0) as u8,
+    )
+}
+
+/// Sends the colors of the panel, usually its [average color], to ambient lights.
+///
+/// Updates are rate limited and skipped while the color changes by less than a threshold,
+/// so the lights and their bridges aren't flooded with requests every frame.
+///
+/// ```no_run
+/// use rpi_led_matrix::{AmbientSync, LedMatrix};
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut ambient = AmbientSync::new()
+///     .wled("http://wled.local")
+///     .hue("http://192.168.1.2", "user", 3);
+/// loop {
+///     // draw a frame ...
+///     ambient.update(canvas.average_color(), Instant::now()).ok();
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+///
+/// [average color]: crate::LedCanvas::average_color
+pub struct AmbientSync {
+    lights: Vec<AmbientLight>,
+    interval: Duration,
+    threshold: u8,
+    timeout: Duration,
+    last: Option<(Instant, LedColor)>,
+}
+
+impl AmbientSync {
+    /// Creates a sync without lights, sending at most twice a second and only when a channel
+    /// changed by at least 8.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            lights: Vec::new(),
+            interval: Duration::from_millis(500),
+            threshold: 8,
+            timeout: Duration::from_secs(2),
+            last: None,
+        }
+    }
+
+    /// Adds a light
+    #[must_use]
+    pub fn light(mut self, light: AmbientLight) -> Self {
+        self.lights.push(light);
+        self
+    }
+
+    /// Adds a WLED controller at `url`
+    #[must_use]
+    pub fn wled(self, url: &str) -> Self {
+        self.light(AmbientLight::Wled {
+            url: url.to_string(),
+        })
+    }
+
+    /// Adds the Hue light with the ID `light` on the bridge at `bridge`
+    #[must_use]
+    pub fn hue(self, bridge: &str, username: &str, light: u32) -> Self {
+        self.light(AmbientLight::Hue {
+            bridge: bridge.to_string(),
+            username: username.to_string(),
+            light,
+        })
+    }
+
+    /// Sets the minimum time between updates
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how much a channel has to change before the lights are updated
+    #[must_use]
+    pub const fn threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the timeout of every request
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns true if `color` differs enough from the last sent one and the interval passed.
+    fn due(&self, color: LedColor, now: Instant) -> bool {
+        self.last.is_none_or(|(sent_at, sent)| {
+            let change = [
+                sent.red.abs_diff(color.red),
+                sent.green.abs_diff(color.green),
+                sent.blue.abs_diff(color.blue),
+            ];
+            now.saturating_duration_since(sent_at) >= self.interval
+                && change.iter().any(|&c| c >= self.threshold.max(1))
+        })
+    }
+
+    /// Sends `color` to all lights if it's due, returning true if it was sent.
+    ///
+    /// # Errors
+    /// If any light couldn't be updated. The remaining lights are still updated.
+    pub fn update(&mut self, color: LedColor, now: Instant) -> Result<bool, &'static str> {
+        if !self.due(color, now) {
+            return Ok(false);
+        }
+        self.last = Some((now, color));
+        self.send(color)?;
+        Ok(true)
+    }
+
+    /// Sends `color` to all lights right away.
+    ///
+    /// # Errors
+    /// If any light couldn't be updated. The remaining lights are still updated.
+    pub fn send(&self, color: LedColor) -> Result<(), &'static str> {
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let mut result = Ok(());
+        for light in &self.lights {
+            let (method, url, body) = light.request(color);
+            let sent = agent
+                .request(method, &url)
+                .set("Content-Type", "application/json")
+                .send_string(&body);
+            if sent.is_err() {
+                result = Err("Couldn't update ambient light");
+            }
+        }
+        result
+    }
+}
+
+impl Default for AmbientSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests() {
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        let wled = AmbientLight::Wled {
+            url: "http://wled.local/".to_string(),
+        };
+        assert_eq!(
+            wled.request(red),
+            (
+                "POST",
+                "http://wled.local/json/state".to_string(),
+                r#"{"on":true,"seg":[{"col":[[255,0,0]]}]}"#.to_string()
+            )
+        );
+        let hue = AmbientLight::Hue {
+            bridge: "http://bridge".to_string(),
+            username: "user".to_string(),
+            light: 3,
+        };
+        let (method, url, body) = hue.request(red);
+        assert_eq!(
+            (method, url.as_str()),
+            ("PUT", "http://bridge/api/user/lights/3/state")
+        );
+        assert_eq!(body, r#"{"on":true,"xy":[0.7006,0.2993],"bri":72}"#);
+        assert_eq!(
+            hue.request(LedColor {
+                red: 0,
+                green: 0,
+                blue: 0
+            })
+            .2,
+            r#"{"on":false}"#
+        );
+    }
+
+    #[test]
+    fn rate_limit() {
+        let start = Instant::now();
+        let gray = |level| LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        };
+        let mut sync = AmbientSync::new();
+        assert!(sync.due(gray(100), start));
+        sync.last = Some((start, gray(100)));
+        assert!(!sync.due(gray(200), start + Duration::from_millis(100)));
+        assert!(!sync.due(gray(104), start + Duration::from_secs(1)));
+        assert!(sync.due(gray(108), start + Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,599 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::http_server;
+use crate::wizard::PanelConfig;
+#[cfg(feature = "osc")]
+use crate::OscRoutes;
+use crate::{
+    CueList, LedCanvas, LedMatrix, LedMatrixOptions, LedRuntimeOptions, Priority, Scene, SceneId,
+    SceneManager, StatsLog, TimecodeClock,
+};
+#[cfg(feature = "midi")]
+use crate::{MidiInput, MidiRoutes};
+
+/// Set by the signal handler when the process was asked to stop
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// The control connections answered at once, further ones are closed right away
+const MAX_CONTROL_CLIENTS: usize = 8;
+/// The longest line of the control socket, longer ones close the connection
+const MAX_LINE: u64 = 1024;
+
+extern "C" fn request_stop(_signal: libc::c_int) {
+    STOP_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+type Hook = Box<dyn FnMut(&AppControl)>;
+type CommandHook = Box<dyn FnMut(&str, &mut SceneManager) -> Result<(), &'static str>>;
+
+/// A command changing a running [`MatrixApp`], sent through its [`AppControl`].
+pub enum AppCommand {
+    /// Sets the brightness in percent
+    Brightness(u8),
+    /// Moves on to the next scene of the rotation
+    NextScene,
+    /// Continues the rotation with a scene, see [`SceneManager::switch_to`]
+    Scene(SceneId),
+    /// Shows a scene instead of the rotation, for a while or until the app ends
+    Show(Priority, Option<Duration>, Box<dyn Scene + Send>),
+    /// Triggers an event of the [`cues`](MatrixAppBuilder::cues)
+    Cue(String),
+    /// Any other command, passed to the [`on_command`](MatrixAppBuilder::on_command) hook
+    Custom(String),
+    /// Clears the panel and ends [`MatrixApp::run`]
+    Quit,
+}
+
+impl AppCommand {
+    /// Parses a line of the control socket: `brightness <percent>`, `next`, `cue <event>`,
+    /// `quit`, or a custom command.
+    ///
+    /// # Errors
+    /// If the line is empty, or the brightness isn't a number.
+    pub fn parse(line: &str) -> Result<Self, &'static str> {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "" => Err("Empty command"),
+            "brightness" => argument
+                .trim()
+                .parse()
+                .map(Self::Brightness)
+                .map_err(|_| "Invalid brightness"),
+            "next" => Ok(Self::NextScene),
+            "cue" if !argument.trim().is_empty() => Ok(Self::Cue(argument.trim().to_string())),
+            "quit" => Ok(Self::Quit),
+            _ => Ok(Self::Custom(line.to_string())),
+        }
+    }
+}
+
+/// Sends [`AppCommand`]s to a running [`MatrixApp`] from any thread, e.g. one reading
+/// buttons or a remote control.
+#[derive(Clone)]
+pub struct AppControl {
+    commands: Sender<AppCommand>,
+}
+
+impl AppControl {
+    /// Sends `command` to the app, handled before the next frame. Commands sent after the
+    /// app ended are dropped.
+    pub fn send(&self, command: AppCommand) {
+        let _ = self.commands.send(command);
+    }
+}
+
+/// A complete sign application: a matrix showing a [`SceneManager`] rotation, controlled
+/// through [`AppControl`] handles and an optional control socket, that clears the panel on
+/// exit.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedCanvas, LedColor, MatrixApp};
+/// use std::path::Path;
+/// use std::time::Duration;
+/// MatrixApp::builder()
+///     .config(Path::new("/etc/sign/matrix.conf"))
+///     .control_socket("127.0.0.1:7000")
+///     .scene(Duration::from_secs(10), |canvas: &mut LedCanvas, _: Duration| {
+///         canvas.fill(&LedColor { red: 0, green: 0, blue: 64 });
+///     })
+///     .build()
+///     .unwrap()
+///     .run()
+///     .unwrap();
+/// ```
+///
+/// The control socket accepts one command per line, see [`AppCommand::parse`], and answers
+/// every line with `ok` or the error, from up to 8 connections at once. The app ends on a `quit`
+/// command, `SIGINT` or `SIGTERM`. The signal handlers are only installed while the app runs,
+/// those set before are restored after.
+pub struct MatrixApp {
+    matrix: LedMatrix,
+    scenes: SceneManager,
+    cues: CueList,
+    timecode: Option<Arc<Mutex<TimecodeClock>>>,
+    frame_time: Duration,
+    control: AppControl,
+    commands: Receiver<AppCommand>,
+    listener: Option<TcpListener>,
+    #[cfg(feature = "osc")]
+    osc: Option<(std::net::UdpSocket, OscRoutes)>,
+    #[cfg(feature = "midi")]
+    midi: Option<(MidiInput, MidiRoutes)>,
+    hooks: Vec<Hook>,
+    on_command: Option<CommandHook>,
+    stats_log: Option<StatsLog>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::Metrics>,
+    shutdown_timeout: Duration,
+}
+
+/// Builds a [`MatrixApp`], created by [`MatrixApp::builder`].
+pub struct MatrixAppBuilder {
+    options: Option<(LedMatrixOptions, LedRuntimeOptions)>,
+    config: Option<Result<PanelConfig, &'static str>>,
+    frame_rate: u32,
+    scenes: SceneManager,
+    cues: CueList,
+    timecode: Option<Arc<Mutex<TimecodeClock>>>,
+    control_socket: Option<String>,
+    #[cfg(feature = "osc")]
+    osc: Option<(String, OscRoutes)>,
+    #[cfg(feature = "midi")]
+    midi: Option<(String, MidiRoutes)>,
+    #[cfg(feature = "preview")]
+    preview: Option<String>,
+    hooks: Vec<Hook>,
+    on_command: Option<CommandHook>,
+    stats_log: Option<StatsLog>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::Metrics>,
+    shutdown_timeout: Duration,
+}
+
+impl MatrixAppBuilder {
+    /// Sets the options of the matrix, instead of the defaults or a config file
+    #[must_use]
+    pub fn options(mut self, options: LedMatrixOptions, runtime: LedRuntimeOptions) -> Self {
+        self.options = Some((options, runtime));
+        self
+    }
+
+    /// Reads the options of the matrix from a config file written by the
+    /// [`Wizard`](crate::wizard::Wizard), if it exists. A config file that can't be parsed
+    /// fails [`build`](Self::build).
+    #[must_use]
+    pub fn config(mut self, path: &Path) -> Self {
+        if path.exists() {
+            self.config = Some(PanelConfig::load(path));
+        }
+        self
+    }
+
+    /// Sets the frames drawn per second, 30 by default
+    #[must_use]
+    pub const fn frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.frame_rate = frames_per_second;
+        self
+    }
+
+    /// Adds a scene to the rotation, shown for `duration` each time, see
+    /// [`SceneManager::add`].
+    #[must_use]
+    pub fn scene(mut self, duration: Duration, scene: impl Scene + Send + 'static) -> Self {
+        self.scenes.add(duration, scene);
+        self
+    }
+
+    /// Uses a scene manager set up beforehand, e.g. with rules, replacing added scenes
+    #[must_use]
+    pub fn scenes(mut self, scenes: SceneManager) -> Self {
+        self.scenes = scenes;
+        self
+    }
+
+    /// Fires `cues` in the render loop, the show starting with the first frame. Events are
+    /// triggered with [`AppCommand::Cue`].
+    #[must_use]
+    pub fn cues(mut self, cues: CueList) -> Self {
+        self.cues = cues;
+        self
+    }
+
+    /// Makes the [`cues`](Self::cues) chase `clock` instead of running from the first frame,
+    /// so they line up with the show control system sending the timecode. The cues hold
+    /// while no timecode arrives. Feed the clock from a decoder, or with
+    /// [`MidiRoutes::timecode`](crate::MidiRoutes::timecode).
+    #[must_use]
+    pub fn timecode(mut self, clock: &Arc<Mutex<TimecodeClock>>) -> Self {
+        self.timecode = Some(Arc::clone(clock));
+        self
+    }
+
+    /// Listens for commands on a TCP address, like `127.0.0.1:7000`
+    #[must_use]
+    pub fn control_socket(mut self, address: &str) -> Self {
+        self.control_socket = Some(address.to_string());
+        self
+    }
+
+    /// Receives Open Sound Control messages on a UDP address, like `0.0.0.0:9000`, handled
+    /// by `routes`.
+    #[cfg(feature = "osc")]
+    #[must_use]
+    pub fn osc(mut self, address: &str, routes: OscRoutes) -> Self {
+        self.osc = Some((address.to_string(), routes));
+        self
+    }
+
+    /// Opens an ALSA sequencer port named `name` for MIDI controllers, handled by `routes`.
+    #[cfg(feature = "midi")]
+    #[must_use]
+    pub fn midi(mut self, name: &str, routes: MidiRoutes) -> Self {
+        self.midi = Some((name.to_string(), routes));
+        self
+    }
+
+    /// Streams the panel to browsers on a TCP address, like `0.0.0.0:8080`, see
+    /// [`PreviewServer`](crate::PreviewServer).
+    #[cfg(feature = "preview")]
+    #[must_use]
+    pub fn preview(mut self, address: &str) -> Self {
+        self.preview = Some(address.to_string());
+        self
+    }
+
+    /// Calls `hook` before every frame, e.g. to poll input devices and send commands.
+    #[must_use]
+    pub fn poll(mut self, hook: impl FnMut(&AppControl) + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Handles [`AppCommand::Custom`] commands. The error is answered on the control socket.
+    #[must_use]
+    pub fn on_command(
+        mut self,
+        hook: impl FnMut(&str, &mut SceneManager) -> Result<(), &'static str> + 'static,
+    ) -> Self {
+        self.on_command = Some(Box::new(hook));
+        self
+    }
+
+    /// Logs runtime statistics of the frames drawn to `stats`, at the frame rate of the app.
+    /// A log that can't be written doesn't stop the app.
+    #[must_use]
+    pub fn stats_log(mut self, stats: StatsLog) -> Self {
+        self.stats_log = Some(stats);
+        self
+    }
+
+    /// Counts the frames of the app and times their rendering in `metrics`, at the frame rate
+    /// of the app.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics(mut self, metrics: &crate::Metrics) -> Self {
+        self.metrics = Some(metrics.clone());
+        self
+    }
+
+    /// Sets how long clearing the panel may take on exit, 1 second by default
+    #[must_use]
+    pub const fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Creates the matrix and binds the control socket.
+    ///
+    /// # Errors
+    /// If the config file can't be parsed, the matrix can't be created, a socket can't be
+    /// bound or the MIDI port can't be opened.
+    pub fn build(self) -> Result<MatrixApp, &'static str> {
+        let config = self.config.transpose()?;
+        let (options, runtime) = match (self.options, config) {
+            (Some((options, runtime)), _) => (Some(options), Some(runtime)),
+            (None, Some(config)) => {
+                let (options, runtime) = config.options();
+                (Some(options), Some(runtime))
+            }
+            (None, None) => (None, None),
+        };
+        let listener = self
+            .control_socket
+            .map(|address| {
+                TcpListener::bind(address.as_str()).map_err(|_| "Couldn't bind control socket")
+            })
+            .transpose()?;
+        #[cfg(feature = "osc")]
+        let osc = self
+            .osc
+            .map(|(address, routes)| {
+                std::net::UdpSocket::bind(address.as_str())
+                    .map(|socket| (socket, routes))
+                    .map_err(|_| "Couldn't bind OSC socket")
+            })
+            .transpose()?;
+        #[cfg(feature = "midi")]
+        let midi = self
+            .midi
+            .map(|(name, routes)| MidiInput::open(&name).map(|input| (input, routes)))
+            .transpose()?;
+        let matrix = LedMatrix::new(options, runtime)?;
+        #[cfg(feature = "preview")]
+        if let Some(address) = self.preview {
+            crate::PreviewServer::start(&matrix, address.as_str())?;
+        }
+        #[cfg(feature = "metrics")]
+        let metrics = self.metrics.map(|metrics| {
+            let metrics = metrics.frame_rate(self.frame_rate);
+            metrics.attach(&matrix);
+            metrics
+        });
+        let (sender, commands) = mpsc::channel();
+        Ok(MatrixApp {
+            matrix,
+            scenes: self.scenes,
+            cues: self.cues,
+            timecode: self.timecode,
+            frame_time: Duration::from_secs(1) / self.frame_rate.max(1),
+            control: AppControl { commands: sender },
+            commands,
+            listener,
+            #[cfg(feature = "osc")]
+            osc,
+            #[cfg(feature = "midi")]
+            midi,
+            hooks: self.hooks,
+            on_command: self.on_command,
+            stats_log: self
+                .stats_log
+                .map(|stats| stats.frame_rate(self.frame_rate)),
+            #[cfg(feature = "metrics")]
+            metrics,
+            shutdown_timeout: self.shutdown_timeout,
+        })
+    }
+}
+
+impl MatrixApp {
+    /// Starts building an app running at 30 frames per second on a matrix with the default
+    /// options.
+    #[must_use]
+    pub fn builder() -> MatrixAppBuilder {
+        MatrixAppBuilder {
+            options: None,
+            config: None,
+            frame_rate: 30,
+            scenes: SceneManager::new(),
+            cues: CueList::new(),
+            timecode: None,
+            control_socket: None,
+            #[cfg(feature = "osc")]
+            osc: None,
+            #[cfg(feature = "midi")]
+            midi: None,
+            #[cfg(feature = "preview")]
+            preview: None,
+            hooks: Vec::new(),
+            on_command: None,
+            stats_log: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            shutdown_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// A handle to send commands to the app
+    #[must_use]
+    pub fn control(&self) -> AppControl {
+        self.control.clone()
+    }
+
+    /// The matrix the app runs on, e.g. to add a watermark
+    #[must_use]
+    pub const fn matrix(&self) -> &LedMatrix {
+        &self.matrix
+    }
+
+    /// The scenes of the app, to add more after building it
+    pub fn scenes_mut(&mut self) -> &mut SceneManager {
+        &mut self.scenes
+    }
+
+    /// Handles `command`, returning false once the app should end.
+    fn handle(&mut self, command: AppCommand) -> Result<bool, &'static str> {
+        match command {
+            AppCommand::Brightness(brightness) => self.matrix.set_brightness(brightness)?,
+            AppCommand::NextScene => self.scenes.skip(),
+            AppCommand::Scene(id) => {
+                if !self.scenes.switch_to(id) {
+                    return Err("Unknown scene");
+                }
+            }
+            AppCommand::Show(priority, duration, mut scene) => {
+                let render = move |canvas: &mut LedCanvas, elapsed| scene.render(canvas, elapsed);
+                self.scenes.preempt(priority, duration, render);
+            }
+            AppCommand::Cue(event) => self.cues.trigger(&event),
+            AppCommand::Custom(command) => match &mut self.on_command {
+                Some(hook) => hook(&command, &mut self.scenes)?,
+                None => return Err("Unknown command"),
+            },
+            AppCommand::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Runs the app until it's asked to quit, then clears the panel.
+    ///
+    /// # Errors
+    /// If the panel couldn't be cleared in time on exit.
+    pub fn run(self) -> Result<(), &'static str> {
+        STOP_REQUESTED.store(false, Ordering::Relaxed);
+        let previous = unsafe {
+            let handler = request_stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            (
+                libc::signal(libc::SIGINT, handler),
+                libc::signal(libc::SIGTERM, handler),
+            )
+        };
+        let result = self.show();
+        unsafe {
+            libc::signal(libc::SIGINT, previous.0);
+            libc::signal(libc::SIGTERM, previous.1);
+        }
+        result
+    }
+
+    /// Shows the scenes until the app is asked to quit, then clears the panel.
+    fn show(mut self) -> Result<(), &'static str> {
+        let (replies, answers) = mpsc::channel::<(String, Sender<String>)>();
+        if let Some(listener) = self.listener.take() {
+            std::thread::spawn(move || serve(&listener, &replies));
+        }
+        #[cfg(feature = "osc")]
+        if let Some((socket, routes)) = self.osc.take() {
+            let control = self.control();
+            std::thread::spawn(move || routes.serve(&socket, &control));
+        }
+        #[cfg(feature = "midi")]
+        if let Some((input, routes)) = self.midi.take() {
+            let control = self.control();
+            std::thread::spawn(move || input.serve(routes, &control));
+        }
+        let mut canvas = self.matrix.offscreen_canvas();
+        'frames: while !STOP_REQUESTED.load(Ordering::Relaxed) {
+            let start = Instant::now();
+            for hook in &mut self.hooks {
+                hook(&self.control);
+            }
+            while let Ok((line, reply)) = answers.try_recv() {
+                let result = AppCommand::parse(&line).and_then(|command| self.handle(command));
+                let _ = reply.send(result.map_or_else(String::from, |_| "ok".to_string()));
+                if result == Ok(false) {
+                    break 'frames;
+                }
+            }
+            while let Ok(command) = self.commands.try_recv() {
+                if self.handle(command) == Ok(false) {
+                    break 'frames;
+                }
+            }
+            let actions = match &self.timecode {
+                Some(clock) => clock
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .position(start)
+                    .map_or_else(Vec::new, |position| self.cues.chase(position, start)),
+                None => self.cues.due(start),
+            };
+            for action in actions {
+                action.apply(&self.matrix, &mut self.scenes);
+            }
+            canvas.clear();
+            self.scenes.render(&mut canvas, start);
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &self.metrics {
+                metrics.record_render(start.elapsed());
+            }
+            canvas = self.matrix.swap(canvas);
+            if let Some(stats) = &mut self.stats_log {
+                let _ = stats.record(&self.matrix, start);
+            }
+            std::thread::sleep(self.frame_time.saturating_sub(start.elapsed()));
+        }
+        self.matrix.shutdown(self.shutdown_timeout)
+    }
+}
+
+/// Accepts control connections, passing every line with a channel for the answer to the app.
+fn serve(listener: &TcpListener, lines: &Sender<(String, Sender<String>)>) {
+    let lines = lines.clone();
+    http_server::serve(listener, MAX_CONTROL_CLIENTS, move |stream| {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        let mut reader = BufReader::new(stream);
+        loop {
+            let mut line = String::new();
+            match (&mut reader).take(MAX_LINE).read_line(&mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(length) if length as u64 == MAX_LINE && !line.ends_with('\n') => {
+                    let _ = writeln!(writer, "Line too long");
+                    return;
+                }
+                Ok(_) => {}
+            }
+            let line = line.trim_end_matches(['\r', '\n']).to_string();
+            let (reply, answer) = mpsc::channel();
+            if lines.send((line, reply)).is_err() {
+                return;
+            }
+            let Ok(answer) = answer.recv() else {
+                return;
+            };
+            if writeln!(writer, "{answer}").is_err() {
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        assert!(matches!(
+            AppCommand::parse(" brightness 40\n"),
+            Ok(AppCommand::Brightness(40))
+        ));
+        assert!(AppCommand::parse("brightness high").is_err());
+        assert!(matches!(
+            AppCommand::parse("next"),
+            Ok(AppCommand::NextScene)
+        ));
+        assert!(matches!(AppCommand::parse("quit"), Ok(AppCommand::Quit)));
+        assert!(matches!(
+            AppCommand::parse("cue drop"),
+            Ok(AppCommand::Cue(event)) if event == "drop"
+        ));
+        assert!(matches!(
+            AppCommand::parse("message hello world"),
+            Ok(AppCommand::Custom(line)) if line == "message hello world"
+        ));
+        assert!(AppCommand::parse("  ").is_err());
+    }
+
+    #[test]
+    fn control_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (lines, received) = mpsc::channel();
+        std::thread::spawn(move || serve(&listener, &lines));
+
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        stream.write_all(b"next\r\n").unwrap();
+        let (line, reply): (String, Sender<String>) = received.recv().unwrap();
+        assert_eq!(line, "next");
+        reply.send("ok".to_string()).unwrap();
+        let mut answer = String::new();
+        reader.read_line(&mut answer).unwrap();
+        assert_eq!(answer, "ok\n");
+
+        stream.write_all(&[b'a'; 2000]).unwrap();
+        let mut answer = String::new();
+        reader.read_line(&mut answer).unwrap();
+        assert_eq!(answer, "Line too long\n");
+        assert!(received.try_recv().is_err());
+    }
+}
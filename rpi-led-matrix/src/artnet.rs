@@ -0,0 +1,333 @@
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, DmxLayout, LedColor, LedMatrix};
+
+/// The port Art-Net is sent to
+pub const ARTNET_PORT: u16 = 6454;
+
+/// Starts every Art-Net packet
+const ID: &[u8; 8] = b"Art-Net\0";
+const OP_POLL: u16 = 0x2000;
+const OP_POLL_REPLY: u16 = 0x2100;
+const OP_DMX: u16 = 0x5000;
+const OP_SYNC: u16 = 0x5200;
+
+/// Length of the header of an `ArtDmx` packet, before the channels
+const DMX_HEADER: usize = 18;
+/// Length of an `ArtPollReply` packet
+const POLL_REPLY: usize = 239;
+/// How long after the last `ArtSync` data is shown as it arrives again, as in the spec
+const SYNC_TIMEOUT: Duration = Duration::from_secs(4);
+/// Largest packet read from the socket, an `ArtDmx` packet is at most 530 bytes
+const MAX_PACKET: usize = 2048;
+
+/// The color of pixels without data
+const BLACK: LedColor = LedColor {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+/// Receives DMX universes over Art-Net, so lighting consoles can patch and drive the matrix
+/// as a fixture.
+///
+/// The receiver answers `ArtPoll` discovery with a port for every universe, and holds data
+/// until an `ArtSync` once a console sends them, to show whole frames without tearing.
+///
+/// ```no_run
+/// use rpi_led_matrix::{ArtNet, DmxLayout, LedMatrix};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let layout = DmxLayout::new(matrix.canvas().canvas_size()).universe(16);
+/// ArtNet::bind("0.0.0.0:6454", layout)
+///     .unwrap()
+///     .name("Stage left")
+///     .run(&matrix)
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ArtNet {
+    socket: UdpSocket,
+    layout: DmxLayout,
+    name: String,
+    timeout: Duration,
+    pixels: Vec<LedColor>,
+    /// Whether data arrived since the last `ArtSync`
+    changed: bool,
+    synced: Option<Instant>,
+    buffer: Vec<u8>,
+}
+
+impl ArtNet {
+    /// Listens for Art-Net on `address`, usually port [`ARTNET_PORT`] on all interfaces, with
+    /// the universes of `layout`.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn bind(address: impl ToSocketAddrs, layout: DmxLayout) -> Result<Self, &'static str> {
+        let socket = UdpSocket::bind(address).map_err(|_| "Couldn't bind Art-Net address")?;
+        let (width, height) = layout.size();
+        Ok(Self {
+            socket,
+            layout,
+            name: String::from("LED matrix"),
+            timeout: Duration::from_millis(100),
+            pixels: vec![BLACK; (width * height).max(0) as usize],
+            changed: false,
+            synced: None,
+            buffer: vec![0; MAX_PACKET],
+        })
+    }
+
+    /// Sets the name shown by consoles when they discover the matrix, "LED matrix" by default
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Sets how long [`receive`](Self::receive) waits for a packet
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits up to the timeout for packets and handles all that arrived, returning true if a
+    /// new frame is ready to draw.
+    ///
+    /// Packets that aren't valid are ignored.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn receive(&mut self) -> Result<bool, &'static str> {
+        let now = Instant::now();
+        let mut ready = false;
+        let timeout = Some(self.timeout.max(Duration::from_millis(1)));
+        let _ = self.socket.set_read_timeout(timeout);
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((length, from)) => {
+                    let packet = std::mem::take(&mut self.buffer);
+                    ready |= self.handle(&packet[..length], from, now);
+                    self.buffer = packet;
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    break;
+                }
+                Err(_) => return Err("Couldn't receive Art-Net packet"),
+            }
+            // take the packets already waiting, without waiting for more
+            let _ = self.socket.set_read_timeout(Some(Duration::from_micros(1)));
+        }
+        Ok(ready)
+    }
+
+    /// Handles a packet from `from`, returning true if a frame is ready.
+    fn handle(&mut self, data: &[u8], from: SocketAddr, now: Instant) -> bool {
+        if data.len() < 10 || data[..8] != ID[..] {
+            return false;
+        }
+        match u16::from_le_bytes([data[8], data[9]]) {
+            OP_DMX => {
+                if !self.dmx(data) {
+                    return false;
+                }
+                let synced = self
+                    .synced
+                    .is_some_and(|synced| now.saturating_duration_since(synced) <= SYNC_TIMEOUT);
+                self.changed |= synced;
+                !synced
+            }
+            OP_SYNC => {
+                self.synced = Some(now);
+                std::mem::take(&mut self.changed)
+            }
+            OP_POLL => {
+                self.reply(from);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Copies the channels of an `ArtDmx` packet to the pixels, returning true if it's one
+    /// of the universes of the layout.
+    fn dmx(&mut self, data: &[u8]) -> bool {
+        let Some(header) = data.get(..DMX_HEADER) else {
+            return false;
+        };
+        let address = u16::from_le_bytes([header[14], header[15]]) & 0x7fff;
+        let length = usize::from(u16::from_be_bytes([header[16], header[17]]));
+        let channels = &data[DMX_HEADER..];
+        let channels = &channels[..length.min(channels.len())];
+        self.layout.apply(address, channels, &mut self.pixels)
+    }
+
+    /// Answers an `ArtPoll` from `from` with a reply for every four universes.
+    fn reply(&self, from: SocketAddr) {
+        let ip = match self.socket.local_addr().map(|address| address.ip()) {
+            Ok(IpAddr::V4(ip)) if !ip.is_unspecified() => ip,
+            // the address of the interface the poll came in on
+            _ => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+                .and_then(|probe| probe.connect(from).and_then(|()| probe.local_addr()))
+                .ok()
+                .and_then(|address| match address.ip() {
+                    IpAddr::V4(ip) => Some(ip),
+                    IpAddr::V6(_) => None,
+                })
+                .unwrap_or(Ipv4Addr::UNSPECIFIED),
+        };
+        let mut groups: Vec<Vec<u16>> = Vec::new();
+        for address in self.layout.universes() {
+            if address > 0x7fff {
+                break;
+            }
+            // the ports of a reply share their net and sub-net
+            match groups.last_mut() {
+                Some(group) if group.len() < 4 && group[0] >> 4 == address >> 4 => {
+                    group.push(address);
+                }
+                _ => groups.push(vec![address]),
+            }
+        }
+        let (width, height) = self.layout.size();
+        let long_name = format!("rpi-led-matrix {width}x{height}");
+        for (index, ports) in (1..).zip(&groups) {
+            let packet = poll_reply(ip, &self.name, &long_name, ports, index);
+            let _ = self.socket.send_to(&packet, from);
+        }
+    }
+
+    /// Draws the received pixels.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        let width = self.layout.size().0.max(1);
+        for (i, color) in (0..).zip(&self.pixels) {
+            canvas.set(i % width, i / width, color);
+        }
+    }
+
+    /// Drives `matrix` with the received universes until the socket fails.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn run(mut self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        let mut canvas = matrix.offscreen_canvas();
+        loop {
+            if self.receive()? {
+                self.draw(&mut canvas);
+                canvas = matrix.swap(canvas);
+            }
+        }
+    }
+}
+
+/// Builds an `ArtPollReply` announcing output `ports` of the same net and sub-net, as the
+/// `bind_index`th reply of the node.
+fn poll_reply(ip: Ipv4Addr, name: &str, long_name: &str, ports: &[u16], bind_index: u8) -> Vec<u8> {
+    let mut packet = vec![0; POLL_REPLY];
+    packet[..8].copy_from_slice(ID);
+    packet[8..10].copy_from_slice(&OP_POLL_REPLY.to_le_bytes());
+    packet[10..14].copy_from_slice(&ip.octets());
+    packet[14..16].copy_from_slice(&ARTNET_PORT.to_le_bytes());
+    let address = ports.first().copied().unwrap_or_default();
+    packet[18] = (address >> 8) as u8;
+    packet[19] = (address >> 4 & 15) as u8;
+    // no registered manufacturer
+    packet[20..22].copy_from_slice(&0x00ff_u16.to_be_bytes());
+    for (field, text) in [(26..43, name), (44..107, long_name)] {
+        let length = text.len().min(field.len());
+        packet[field.start..field.start + length].copy_from_slice(&text.as_bytes()[..length]);
+    }
+    packet[173] = ports.len() as u8;
+    for (i, address) in ports.iter().enumerate() {
+        // outputs DMX from Art-Net
+        packet[174 + i] = 0x80;
+        packet[182 + i] = 0x80;
+        packet[190 + i] = (address & 15) as u8;
+    }
+    packet[207..211].copy_from_slice(&ip.octets());
+    packet[211] = bind_index;
+    // supports 15 bit port-addresses
+    packet[212] = 0x08;
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Frame;
+
+    fn dmx(address: u16, channels: &[u8]) -> Vec<u8> {
+        let mut packet = ID.to_vec();
+        packet.extend(OP_DMX.to_le_bytes());
+        packet.extend([0, 14, 0, 0]);
+        packet.extend(address.to_le_bytes());
+        packet.extend((channels.len() as u16).to_be_bytes());
+        packet.extend(channels);
+        packet
+    }
+
+    #[test]
+    fn receiving() {
+        let layout = DmxLayout::new((3, 2))
+            .universe(0x0f)
+            .start_channel(4)
+            .pixels_per_universe(4)
+            .serpentine(true);
+
+        let mut receiver = ArtNet::bind("127.0.0.1:0", layout)
+            .unwrap()
+            .timeout(Duration::from_millis(200));
+        let from = SocketAddr::from(([127, 0, 0, 1], ARTNET_PORT));
+        let now = Instant::now();
+        let first = [[0; 3], [1; 3], [2; 3], [3; 3], [4; 3]].concat();
+        assert!(receiver.handle(&dmx(0x0f, &first), from, now));
+        assert!(!receiver.handle(&dmx(0x11, &[9; 12]), from, now));
+        let mut sync = ID.to_vec();
+        sync.extend(OP_SYNC.to_le_bytes());
+        sync.extend([0, 14, 0, 0]);
+        assert!(!receiver.handle(&sync, from, now));
+        assert!(!receiver.handle(&dmx(0x10, &[9; 12]), from, now));
+        assert!(receiver.handle(&sync, from, now));
+
+        let mut pixels = vec![BLACK; 6];
+        receiver.draw(&mut Frame::new(&mut pixels, (3, 2)));
+        let gray = |level| LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        };
+        assert_eq!(
+            pixels,
+            [gray(1), gray(2), gray(3), gray(9), gray(9), gray(4)]
+        );
+
+        let reply = poll_reply(Ipv4Addr::LOCALHOST, "Stage", "Long", &[0x0f], 1);
+        assert_eq!(reply.len(), POLL_REPLY);
+        assert_eq!(&reply[26..31], b"Stage");
+        assert_eq!(
+            (reply[18], reply[19], reply[173], reply[190]),
+            (0, 0, 1, 15)
+        );
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = receiver.socket.local_addr().unwrap();
+        let mut poll = ID.to_vec();
+        poll.extend(OP_POLL.to_le_bytes());
+        poll.extend([0, 14, 0, 0]);
+        client.send_to(&poll, address).unwrap();
+        assert!(!receiver.receive().unwrap());
+        client
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut buffer = [0; MAX_PACKET];
+        // universes 0x0f and 0x10 are in different sub-nets
+        for port in [0x0f, 0x10] {
+            let length = client.recv(&mut buffer).unwrap();
+            assert_eq!(length, POLL_REPLY);
+            assert_eq!(buffer[19], port >> 4);
+            assert_eq!(buffer[190], port & 15);
+        }
+    }
+}
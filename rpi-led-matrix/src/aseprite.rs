@@ -0,0 +1,632 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{LedColorA, Surface};
+
+/// The order the frames of a [`Tag`] play in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayDirection {
+    /// First to last
+    #[default]
+    Forward,
+    /// Last to first
+    Reverse,
+    /// First to last and back
+    PingPong,
+    /// Last to first and back
+    PingPongReverse,
+}
+
+/// A named range of frames of an [`Animation`], as tagged in Aseprite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tag {
+    name: String,
+    frames: (usize, usize),
+    direction: PlayDirection,
+    repeat: u16,
+}
+
+impl Tag {
+    /// The name of the tag
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The first and last frame of the tag, both included
+    #[must_use]
+    pub const fn frames(&self) -> (usize, usize) {
+        self.frames
+    }
+
+    /// The order the frames play in
+    #[must_use]
+    pub const fn direction(&self) -> PlayDirection {
+        self.direction
+    }
+
+    /// How often the frames play before stopping on the last one, 0 to loop forever
+    #[must_use]
+    pub const fn repeat(&self) -> u16 {
+        self.repeat
+    }
+
+    /// The frames in the order they play in, once
+    fn sequence(&self) -> Vec<usize> {
+        let (first, last) = self.frames;
+        let forward: Vec<usize> = (first..=last).collect();
+        let back = || {
+            forward
+                .iter()
+                .rev()
+                .skip(1)
+                .take(forward.len().saturating_sub(2))
+        };
+        match self.direction {
+            PlayDirection::Forward => forward.clone(),
+            PlayDirection::Reverse => forward.iter().rev().copied().collect(),
+            PlayDirection::PingPong => forward.iter().chain(back()).copied().collect(),
+            PlayDirection::PingPongReverse => {
+                let mut sequence: Vec<usize> = forward.iter().chain(back()).copied().collect();
+                sequence.reverse();
+                sequence
+            }
+        }
+    }
+}
+
+/// Frames, frame durations and tags of a sprite made with Aseprite, loaded from an
+/// `.aseprite` or `.ase` file.
+///
+/// The visible layers of every frame are flattened into a [`Surface`], keeping its
+/// transparency. Tagged animations are played by asking for the frame at the time they've
+/// been running, e.g. the elapsed time passed to [`Scene::render`](crate::Scene::render).
+///
+/// ```no_run
+/// use rpi_led_matrix::{Animation, LedMatrix};
+/// use std::path::Path;
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let cat = Animation::open(Path::new("cat.aseprite")).unwrap();
+/// let start = Instant::now();
+/// loop {
+///     canvas.clear();
+///     if let Some(frame) = cat.frame_at(Some("walk"), start.elapsed()) {
+///         canvas.draw_surface(frame, 4, 8);
+///     }
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Animation {
+    frames: Vec<(Surface, Duration)>,
+    tags: Vec<Tag>,
+}
+
+/// A layer as far as flattening is concerned
+struct Layer {
+    visible: bool,
+    opacity: u8,
+    /// Image layers are flattened, groups and tilemaps aren't
+    image: bool,
+}
+
+/// An image placed on a layer in a frame
+#[derive(Clone)]
+struct Cel {
+    layer: usize,
+    position: (i32, i32),
+    opacity: u8,
+    size: (i32, i32),
+    pixels: Vec<LedColorA>,
+}
+
+/// Reads the little-endian values of the file format.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], &'static str> {
+        if count > self.bytes.len() {
+            return Err("Aseprite file is truncated");
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, &'static str> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, &'static str> {
+        Ok(self.word()? as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, &'static str> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        let length = self.word()?;
+        Ok(String::from_utf8_lossy(self.take(usize::from(length))?).into_owned())
+    }
+}
+
+impl Animation {
+    /// Loads an `.aseprite` or `.ase` file.
+    ///
+    /// # Errors
+    /// If the file can't be read or isn't a valid Aseprite file.
+    pub fn open(path: &Path) -> Result<Self, &'static str> {
+        let bytes = std::fs::read(path).map_err(|_| "Couldn't read Aseprite file")?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parses the contents of an Aseprite file.
+    ///
+    /// RGBA, grayscale and indexed sprites are supported. Layers are blended normally,
+    /// whatever their blend mode.
+    ///
+    /// # Errors
+    /// If the data isn't a valid Aseprite file, has a color depth other than 8, 16 or 32 bits
+    /// or a cel larger than the sprite.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes };
+        let header = reader.take(128)?;
+        let mut header = Reader { bytes: header };
+        header.dword()?;
+        if header.word()? != 0xA5E0 {
+            return Err("Not an Aseprite file");
+        }
+        let frame_count = header.word()?;
+        let size = (i32::from(header.word()?), i32::from(header.word()?));
+        let depth = header.word()?;
+        if !matches!(depth, 8 | 16 | 32) {
+            return Err("Unsupported Aseprite color depth");
+        }
+        let layer_opacity = header.dword()? & 1 != 0;
+        header.take(10)?;
+        let transparent = header.byte()?;
+
+        let mut layers: Vec<Layer> = Vec::new();
+        // visibility of the groups the next layer can be in, by child level
+        let mut groups: Vec<bool> = Vec::new();
+        let mut palette = vec![LedColorA::TRANSPARENT; 256];
+        let mut tags = Vec::new();
+        let mut durations = Vec::new();
+        let mut cels: Vec<Vec<Cel>> = Vec::new();
+        for _ in 0..frame_count {
+            let length = reader.dword()? as usize;
+            let mut frame = Reader {
+                bytes: reader.take(length.saturating_sub(4))?,
+            };
+            if frame.word()? != 0xF1FA {
+                return Err("Aseprite frame is corrupt");
+            }
+            let old_chunks = frame.word()?;
+            durations.push(Duration::from_millis(u64::from(frame.word()?)));
+            frame.take(2)?;
+            let chunks = match frame.dword()? {
+                0 => u32::from(old_chunks),
+                chunks => chunks,
+            };
+            let mut frame_cels = Vec::new();
+            for _ in 0..chunks {
+                let length = frame.dword()? as usize;
+                let kind = frame.word()?;
+                let mut chunk = Reader {
+                    bytes: frame.take(length.saturating_sub(6))?,
+                };
+                match kind {
+                    0x0004 => read_old_palette(&mut chunk, &mut palette)?,
+                    0x2004 => {
+                        let flags = chunk.word()?;
+                        let layer_type = chunk.word()?;
+                        let level = usize::from(chunk.word()?);
+                        chunk.take(6)?;
+                        let opacity = chunk.byte()?;
+                        groups.truncate(level);
+                        let visible = flags & 1 != 0 && groups.iter().all(|&shown| shown);
+                        groups.push(visible);
+                        layers.push(Layer {
+                            visible,
+                            opacity: if layer_opacity { opacity } else { 255 },
+                            image: layer_type == 0,
+                        });
+                    }
+                    0x2005 => {
+                        let pixel = |data: &[u8]| match depth {
+                            32 => LedColorA {
+                                red: data[0],
+                                green: data[1],
+                                blue: data[2],
+                                alpha: data[3],
+                            },
+                            16 => LedColorA {
+                                red: data[0],
+                                green: data[0],
+                                blue: data[0],
+                                alpha: data[1],
+                            },
+                            _ if data[0] == transparent => LedColorA::TRANSPARENT,
+                            _ => palette[usize::from(data[0])],
+                        };
+                        let cel = read_cel(&mut chunk, depth, size, &cels, pixel)?;
+                        frame_cels.extend(cel);
+                    }
+                    0x2018 => tags = read_tags(&mut chunk)?,
+                    0x2019 => read_palette(&mut chunk, &mut palette)?,
+                    _ => {}
+                }
+            }
+            cels.push(frame_cels);
+        }
+
+        let frames = cels
+            .into_iter()
+            .zip(durations)
+            .map(|(mut frame_cels, duration)| {
+                let mut surface = Surface::new(size.0, size.1);
+                frame_cels.sort_by_key(|cel| cel.layer);
+                for cel in frame_cels {
+                    let Some(layer) = layers.get(cel.layer) else {
+                        continue;
+                    };
+                    if !layer.visible || !layer.image {
+                        continue;
+                    }
+                    let opacity = u32::from(cel.opacity) * u32::from(layer.opacity);
+                    for (index, color) in (0..).zip(cel.pixels) {
+                        let alpha = (u32::from(color.alpha) * opacity / (255 * 255)) as u8;
+                        surface.blend(
+                            cel.position.0 + index % cel.size.0,
+                            cel.position.1 + index / cel.size.0,
+                            LedColorA { alpha, ..color },
+                        );
+                    }
+                }
+                (surface, duration)
+            })
+            .collect();
+        Ok(Self { frames, tags })
+    }
+
+    /// The width and height of the frames
+    #[must_use]
+    pub fn size(&self) -> (i32, i32) {
+        self.frames
+            .first()
+            .map_or((0, 0), |(frame, _)| frame.size())
+    }
+
+    /// The number of frames
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The frame at `index`, with all visible layers flattened.
+    #[must_use]
+    pub fn frame(&self, index: usize) -> Option<&Surface> {
+        self.frames.get(index).map(|(frame, _)| frame)
+    }
+
+    /// How long the frame at `index` is shown.
+    #[must_use]
+    pub fn duration(&self, index: usize) -> Option<Duration> {
+        self.frames.get(index).map(|(_, duration)| *duration)
+    }
+
+    /// All tags, in the order they were defined
+    #[must_use]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// The tag named `name`
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// The index of the frame shown `elapsed` after starting the animation tagged `tag`, or
+    /// all frames in order with `None`, or `None` if there's no such tag or frame.
+    ///
+    /// Tags loop unless they repeat a set number of times, after which their last frame
+    /// stays.
+    #[must_use]
+    pub fn frame_index_at(&self, tag: Option<&str>, elapsed: Duration) -> Option<usize> {
+        let (sequence, repeat) = match tag {
+            Some(name) => {
+                let tag = self.tag(name)?;
+                (tag.sequence(), u32::from(tag.repeat))
+            }
+            None => ((0..self.frames.len()).collect(), 0),
+        };
+        let durations: Vec<Duration> = sequence
+            .iter()
+            .map(|&index| self.duration(index).unwrap_or_default())
+            .collect();
+        let total: Duration = durations.iter().sum();
+        if total.is_zero() || (repeat > 0 && elapsed >= total * repeat) {
+            return sequence.last().copied();
+        }
+        let mut time = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+        for (index, duration) in sequence.into_iter().zip(durations) {
+            if time < duration {
+                return Some(index);
+            }
+            time -= duration;
+        }
+        None
+    }
+
+    /// The frame shown `elapsed` after starting the animation tagged `tag`, see
+    /// [`frame_index_at`](Self::frame_index_at).
+    #[must_use]
+    pub fn frame_at(&self, tag: Option<&str>, elapsed: Duration) -> Option<&Surface> {
+        self.frame(self.frame_index_at(tag, elapsed)?)
+    }
+}
+
+/// Reads a cel chunk of a sprite of `canvas` size, returning `None` for tilemaps and links to
+/// missing cels.
+fn read_cel(
+    chunk: &mut Reader,
+    depth: u16,
+    canvas: (i32, i32),
+    previous: &[Vec<Cel>],
+    pixel: impl Fn(&[u8]) -> LedColorA,
+) -> Result<Option<Cel>, &'static str> {
+    let layer = usize::from(chunk.word()?);
+    let position = (i32::from(chunk.short()?), i32::from(chunk.short()?));
+    let opacity = chunk.byte()?;
+    let kind = chunk.word()?;
+    chunk.take(7)?;
+    if kind == 1 {
+        let linked = previous.get(usize::from(chunk.word()?));
+        let cel = linked.and_then(|cels| cels.iter().find(|cel| cel.layer == layer));
+        return Ok(cel.map(|cel| Cel {
+            position,
+            opacity,
+            ..cel.clone()
+        }));
+    }
+    if kind != 0 && kind != 2 {
+        return Ok(None);
+    }
+    let size = (i32::from(chunk.word()?), i32::from(chunk.word()?));
+    if size.0 > canvas.0 || size.1 > canvas.1 {
+        return Err("Aseprite cel is larger than the sprite");
+    }
+    let bytes_per_pixel = usize::from(depth / 8);
+    let length = (size.0 as usize)
+        .checked_mul(size.1 as usize)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .ok_or("Aseprite cel is too large")?;
+    let data = if kind == 2 {
+        // only as much as the cel holds, whatever the data decompresses to
+        let mut data = Vec::new();
+        flate2::read::ZlibDecoder::new(chunk.bytes)
+            .take(length as u64)
+            .read_to_end(&mut data)
+            .map_err(|_| "Aseprite cel is corrupt")?;
+        data
+    } else {
+        chunk.take(length)?.to_vec()
+    };
+    if data.len() < length {
+        return Err("Aseprite cel is truncated");
+    }
+    let pixels = data[..length]
+        .chunks_exact(bytes_per_pixel)
+        .map(pixel)
+        .collect();
+    Ok(Some(Cel {
+        layer,
+        position,
+        opacity,
+        size,
+        pixels,
+    }))
+}
+
+fn read_tags(chunk: &mut Reader) -> Result<Vec<Tag>, &'static str> {
+    let count = chunk.word()?;
+    chunk.take(8)?;
+    (0..count)
+        .map(|_| {
+            let frames = (usize::from(chunk.word()?), usize::from(chunk.word()?));
+            let direction = match chunk.byte()? {
+                1 => PlayDirection::Reverse,
+                2 => PlayDirection::PingPong,
+                3 => PlayDirection::PingPongReverse,
+                _ => PlayDirection::Forward,
+            };
+            let repeat = chunk.word()?;
+            chunk.take(10)?;
+            Ok(Tag {
+                name: chunk.string()?,
+                frames,
+                direction,
+                repeat,
+            })
+        })
+        .collect()
+}
+
+fn read_palette(chunk: &mut Reader, palette: &mut [LedColorA]) -> Result<(), &'static str> {
+    chunk.dword()?;
+    let first = chunk.dword()? as usize;
+    let last = chunk.dword()? as usize;
+    chunk.take(8)?;
+    for index in first..=last {
+        let flags = chunk.word()?;
+        let [red, green, blue, alpha] =
+            [chunk.byte()?, chunk.byte()?, chunk.byte()?, chunk.byte()?];
+        if flags & 1 != 0 {
+            chunk.string()?;
+        }
+        if let Some(entry) = palette.get_mut(index) {
+            *entry = LedColorA {
+                red,
+                green,
+                blue,
+                alpha,
+            };
+        }
+    }
+    Ok(())
+}
+
+fn read_old_palette(chunk: &mut Reader, palette: &mut [LedColorA]) -> Result<(), &'static str> {
+    let mut index = 0;
+    for _ in 0..chunk.word()? {
+        index += usize::from(chunk.byte()?);
+        let count = match chunk.byte()? {
+            0 => 256,
+            count => usize::from(count),
+        };
+        for _ in 0..count {
+            let rgb = chunk.take(3)?;
+            if let Some(entry) = palette.get_mut(index) {
+                *entry = LedColorA {
+                    red: rgb[0],
+                    green: rgb[1],
+                    blue: rgb[2],
+                    alpha: 255,
+                };
+            }
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn chunk(kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32 + 6).to_le_bytes().to_vec();
+        chunk.extend(kind.to_le_bytes());
+        chunk.extend(data);
+        chunk
+    }
+
+    fn frame(duration: u16, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let data = chunks.concat();
+        let mut frame = (data.len() as u32 + 16).to_le_bytes().to_vec();
+        frame.extend(0xF1FA_u16.to_le_bytes());
+        frame.extend((chunks.len() as u16).to_le_bytes());
+        frame.extend(duration.to_le_bytes());
+        frame.extend([0; 2]);
+        frame.extend((chunks.len() as u32).to_le_bytes());
+        frame.extend(data);
+        frame
+    }
+
+    /// Cel data of the given type placed at (x, y) on layer 0
+    fn cel(x: i16, y: i16, kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut cel = [0_u16.to_le_bytes(), x.to_le_bytes(), y.to_le_bytes()].concat();
+        cel.push(255);
+        cel.extend(kind.to_le_bytes());
+        cel.extend([0; 7]);
+        cel.extend(data);
+        chunk(0x2005, &cel)
+    }
+
+    #[test]
+    fn parse() {
+        let mut header = vec![0; 128];
+        header[4..6].copy_from_slice(&0xA5E0_u16.to_le_bytes());
+        header[6] = 3;
+        header[8] = 2;
+        header[10] = 2;
+        header[12] = 32;
+        header[14] = 1;
+
+        let mut layer = [1_u16, 0, 0, 0, 0, 0].map(u16::to_le_bytes).concat();
+        layer.extend([255, 0, 0, 0, 2, 0]);
+        layer.extend(b"bg");
+        let mut tags = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        tags.extend([0, 0, 2, 0, 2, 0, 0]);
+        tags.extend([0; 10]);
+        tags.extend([5, 0]);
+        tags.extend(b"blink");
+
+        let raw = [1, 0, 1, 0, 255, 0, 0, 255];
+        let mut compressed = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+        compressed
+            .write_all(&[0, 0, 255, 255, 0, 0, 255, 128])
+            .unwrap();
+        let mut packed = vec![2, 0, 1, 0];
+        packed.extend(compressed.finish().unwrap());
+
+        let file = [
+            header,
+            frame(
+                100,
+                &[
+                    chunk(0x2004, &layer),
+                    cel(1, 0, 0, &raw),
+                    chunk(0x2018, &tags),
+                ],
+            ),
+            frame(50, &[cel(0, 1, 2, &packed)]),
+            frame(200, &[cel(0, 0, 1, &[0, 0])]),
+        ]
+        .concat();
+        let animation = Animation::from_bytes(&file).unwrap();
+        assert_eq!((animation.size(), animation.frame_count()), ((2, 2), 3));
+        let red = animation.frame(0).unwrap().get(1, 0).unwrap();
+        assert_eq!((red.red, red.alpha), (255, 255));
+        let blue = animation.frame(1).unwrap().get(1, 1).unwrap();
+        assert_eq!((blue.blue, blue.alpha), (255, 128));
+        // linked cels are placed anew
+        assert_eq!(animation.frame(2).unwrap().get(0, 0), Some(red));
+
+        let tag = animation.tag("blink").unwrap();
+        assert_eq!(
+            (tag.frames(), tag.direction()),
+            ((0, 2), PlayDirection::PingPong)
+        );
+        let at = |tag, millis| animation.frame_index_at(tag, Duration::from_millis(millis));
+        assert_eq!(at(None, 120), Some(1));
+        // 0, 1, 2 and back to 1 take 400 ms
+        assert_eq!(at(Some("blink"), 370), Some(1));
+        assert_eq!(at(Some("blink"), 410), Some(0));
+        assert_eq!(at(Some("run"), 0), None);
+        assert!(Animation::from_bytes(&file[..200]).is_err());
+
+        // a cel claiming 65535 by 65535 pixels in the 2 by 2 sprite
+        let mut corrupt = vec![0xff, 0xff, 0xff, 0xff];
+        corrupt.extend(&packed[4..]);
+        let corrupt = [&file[..128], &frame(100, &[cel(0, 0, 2, &corrupt)])].concat();
+        assert_eq!(
+            Animation::from_bytes(&corrupt).err(),
+            Some("Aseprite cel is larger than the sprite")
+        );
+
+        for depth in [0, 4, 24] {
+            let mut header = file[..128].to_vec();
+            header[12] = depth;
+            let file = [&header, &file[128..]].concat();
+            assert_eq!(
+                Animation::from_bytes(&file).err(),
+                Some("Unsupported Aseprite color depth")
+            );
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use crate::LedMatrix;
+#[cfg(feature = "image")]
+use crate::{LedImage, Surface};
+
+/// Picks the height of the best variant for a panel `height` pixels tall: the tallest one
+/// that fits, or the smallest one if none does.
+fn best_height(heights: impl Iterator<Item = i32> + Clone, height: i32) -> Option<i32> {
+    heights
+        .clone()
+        .filter(|&variant| variant <= height)
+        .max()
+        .or_else(|| heights.min())
+}
+
+/// Versions of an asset made for panels of different heights, e.g. an [`Icon`] drawn larger
+/// for taller panels.
+///
+/// ```
+/// use rpi_led_matrix::widgets::Icon;
+/// use rpi_led_matrix::Variants;
+/// let small = Icon::from_rows(&["#.#", ".#.", "#.#"]).unwrap();
+/// let large = Icon::from_rows(&["#..#", ".##.", ".##.", "#..#"]).unwrap();
+/// let close = Variants::new().with(32, small).with(64, large);
+/// assert_eq!(close.best(48).unwrap().size(), (3, 3));
+/// assert_eq!(close.best(128).unwrap().size(), (4, 4));
+/// ```
+///
+/// [`Icon`]: crate::widgets::Icon
+#[derive(Clone, Debug)]
+pub struct Variants<T> {
+    variants: Vec<(i32, T)>,
+}
+
+impl<T> Default for Variants<T> {
+    fn default() -> Self {
+        Self {
+            variants: Vec::new(),
+        }
+    }
+}
+
+impl<T> Variants<T> {
+    /// Creates an empty set of variants.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the variant made for panels `height` pixels tall
+    #[must_use]
+    pub fn with(mut self, height: i32, asset: T) -> Self {
+        self.variants.push((height, asset));
+        self
+    }
+
+    /// The variant for a panel `height` pixels tall: the one made for the tallest panel that
+    /// isn't taller, or for the smallest panel if all are taller.
+    #[must_use]
+    pub fn best(&self, height: i32) -> Option<&T> {
+        let best = best_height(self.variants.iter().map(|(variant, _)| *variant), height)?;
+        self.variants
+            .iter()
+            .find(|(variant, _)| *variant == best)
+            .map(|(_, asset)| asset)
+    }
+
+    /// The variant for the panel of `matrix`, see [`best`](Self::best).
+    #[must_use]
+    pub fn for_matrix(&self, matrix: &LedMatrix) -> Option<&T> {
+        self.best(matrix.canvas().canvas_size().1)
+    }
+}
+
+/// Loads asset files from a directory, picking the variant made for the panel height.
+///
+/// Variants are files named after the asset with the panel height they're made for, like
+/// `logo@32.png`, `logo@64.png` and `logo@128.png` for `logo.png`. The best one is chosen
+/// like [`Variants::best`] does, and an asset without variants is loaded as it is named.
+///
+/// ```no_run
+/// use rpi_led_matrix::{AssetLoader, LedMatrix};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let assets = AssetLoader::for_matrix(Path::new("/opt/signage/assets"), &matrix);
+/// let logo = assets.path("logo.png");
+/// ```
+#[derive(Clone, Debug)]
+pub struct AssetLoader {
+    directory: PathBuf,
+    height: i32,
+}
+
+impl AssetLoader {
+    /// Creates a loader of the assets in `directory` for a panel `height` pixels tall.
+    #[must_use]
+    pub fn new(directory: &Path, height: i32) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            height,
+        }
+    }
+
+    /// Creates a loader of the assets in `directory` for the panel of `matrix`.
+    #[must_use]
+    pub fn for_matrix(directory: &Path, matrix: &LedMatrix) -> Self {
+        Self::new(directory, matrix.canvas().canvas_size().1)
+    }
+
+    /// The path of the best variant of the asset `name`, or `None` if there's neither a
+    /// variant nor a file of that name.
+    #[must_use]
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) => (stem, format!(".{extension}")),
+            None => (name, String::new()),
+        };
+        let prefix = format!("{stem}@");
+        let heights: Vec<i32> = std::fs::read_dir(&self.directory)
+            .ok()?
+            .filter_map(|entry| {
+                let file = entry.ok()?.file_name().into_string().ok()?;
+                let height = file.strip_prefix(&prefix)?.strip_suffix(&extension)?;
+                height.parse().ok()
+            })
+            .collect();
+        match best_height(heights.iter().copied(), self.height) {
+            Some(height) => Some(self.directory.join(format!("{prefix}{height}{extension}"))),
+            None => Some(self.directory.join(name)).filter(|path| path.is_file()),
+        }
+    }
+
+    /// Loads the best variant of the image `name`.
+    ///
+    /// # Errors
+    /// If there's no such asset, or it can't be decoded.
+    #[cfg(feature = "image")]
+    pub fn image(&self, name: &str) -> Result<LedImage, &'static str> {
+        LedImage::open(&self.path(name).ok_or("Asset not found")?)
+    }
+
+    /// Loads the best variant of the image `name` with its transparency.
+    ///
+    /// # Errors
+    /// If there's no such asset, or it can't be decoded.
+    #[cfg(feature = "image")]
+    pub fn surface(&self, name: &str) -> Result<Surface, &'static str> {
+        Surface::open(&self.path(name).ok_or("Asset not found")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants() {
+        let variants = Variants::new().with(64, "medium").with(32, "small");
+        assert_eq!(variants.best(32), Some(&"small"));
+        assert_eq!(variants.best(96), Some(&"medium"));
+        assert_eq!(variants.best(16), Some(&"small"));
+        assert_eq!(Variants::<()>::new().best(32), None);
+
+        let directory = std::env::temp_dir().join(format!("led-assets-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for file in [
+            "logo@32.png",
+            "logo@64.png",
+            "logo@64.gif",
+            "icon.png",
+            "README",
+        ] {
+            std::fs::write(directory.join(file), []).unwrap();
+        }
+        let assets = AssetLoader::new(&directory, 48);
+        assert_eq!(assets.path("logo.png"), Some(directory.join("logo@32.png")));
+        assert_eq!(assets.path("logo.gif"), Some(directory.join("logo@64.gif")));
+        assert_eq!(assets.path("icon.png"), Some(directory.join("icon.png")));
+        assert_eq!(assets.path("README"), Some(directory.join("README")));
+        assert_eq!(assets.path("missing.png"), None);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+
+use crate::Spectrum;
+
+/// Captures audio from a microphone or the system's audio into a [`Spectrum`].
+///
+/// Capture runs on a thread of the audio backend for as long as the input is kept. To show
+/// what's playing on the system rather than a microphone, open the monitor device of the
+/// output, e.g. `Monitor of Built-in Audio` with PulseAudio or PipeWire.
+///
+/// ```no_run
+/// use rpi_led_matrix::widgets::charts::BarGraph;
+/// use rpi_led_matrix::widgets::SharedTheme;
+/// use rpi_led_matrix::{AudioInput, LedMatrix};
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let input = AudioInput::open(None).unwrap();
+/// let mut graph = BarGraph::new(SharedTheme::default());
+/// let start = Instant::now();
+/// loop {
+///     canvas.clear();
+///     graph.draw(&mut canvas, &input.bands(16), start.elapsed());
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+pub struct AudioInput {
+    spectrum: Arc<Mutex<Spectrum>>,
+    _stream: cpal::Stream,
+}
+
+impl AudioInput {
+    /// Starts capturing from the default input device, or the first one whose name contains
+    /// `device`.
+    ///
+    /// # Errors
+    /// If there's no such device, or it can't be opened.
+    pub fn open(device: Option<&str>) -> Result<Self, &'static str> {
+        let host = cpal::default_host();
+        let device = match device {
+            Some(name) => host
+                .input_devices()
+                .map_err(|_| "Couldn't list audio devices")?
+                .find(|device| device.name().is_ok_and(|found| found.contains(name))),
+            None => host.default_input_device(),
+        }
+        .ok_or("Audio input device not found")?;
+        let supported = device
+            .default_input_config()
+            .map_err(|_| "Couldn't query the audio input format")?;
+        let config = supported.config();
+        // about 40 ms of audio, enough to resolve the lowest bass notes
+        let size = config.sample_rate.0 as usize / 25;
+        let spectrum = Arc::new(Mutex::new(Spectrum::new(size, config.sample_rate.0)));
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => capture::<f32>(&device, &config, &spectrum),
+            SampleFormat::I16 => capture::<i16>(&device, &config, &spectrum),
+            SampleFormat::U16 => capture::<u16>(&device, &config, &spectrum),
+            SampleFormat::I32 => capture::<i32>(&device, &config, &spectrum),
+            _ => return Err("Unsupported audio sample format"),
+        }
+        .map_err(|_| "Couldn't open the audio input")?;
+        stream
+            .play()
+            .map_err(|_| "Couldn't start the audio input")?;
+        Ok(Self {
+            spectrum,
+            _stream: stream,
+        })
+    }
+
+    /// The names of all input devices, to pass to [`open`](Self::open).
+    #[must_use]
+    pub fn devices() -> Vec<String> {
+        cpal::default_host()
+            .input_devices()
+            .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// A copy of the spectrum of the latest audio, e.g. to change its frequency range.
+    #[must_use]
+    pub fn spectrum(&self) -> Spectrum {
+        self.spectrum
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The latest audio in `count` bands, see [`Spectrum::bands`].
+    #[must_use]
+    pub fn bands(&self, count: usize) -> Vec<f32> {
+        self.spectrum
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .bands(count)
+    }
+}
+
+/// Opens a stream pushing samples of type `T`, mixed down to mono, into `spectrum`.
+fn capture<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    spectrum: &Arc<Mutex<Spectrum>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = usize::from(config.channels.max(1));
+    let spectrum = Arc::clone(spectrum);
+    let size = spectrum
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .size();
+    // the spectrum only keeps its size in samples, so the buffer never grows past that and
+    // the audio thread doesn't allocate
+    let mut mono = Vec::with_capacity(size);
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let frames = data.chunks(channels);
+            let skipped = frames.len().saturating_sub(size);
+            mono.clear();
+            mono.extend(frames.skip(skipped).map(|frame| {
+                frame
+                    .iter()
+                    .map(|&sample| f32::from_sample(sample))
+                    .sum::<f32>()
+                    / channels as f32
+            }));
+            spectrum
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(&mono);
+        },
+        // the stream stops delivering samples, leaving the last spectrum in place
+        |_| {},
+        None,
+    )
+}
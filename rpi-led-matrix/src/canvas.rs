@@ -1,8 +1,13 @@
 use libc::c_int;
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
 
 use crate::ffi;
-use crate::{LedColor, LedFont};
+use crate::font::Glyph;
+use crate::race;
+use crate::raster;
+use crate::unicode;
+use crate::{Canvas, LedColor, LedFont, Rect, Transform};
 
 /// The Rust handle for the matrix canvas to draw on.
 ///
@@ -14,6 +19,51 @@ use crate::{LedColor, LedFont};
 /// ```
 pub struct LedCanvas {
     pub(crate) handle: *mut ffi::CLedCanvas,
+    pub(crate) device_size: (i32, i32),
+    transform: Transform,
+    /// Area of the active [`SubCanvas`], in coordinates of the whole canvas
+    viewport: Option<Rect>,
+    /// Clip rectangle, in coordinates of the whole canvas
+    clip: Option<Rect>,
+    /// Copy of the pixels drawn onto the panel, in panel coordinates
+    pub(crate) shadow: Vec<LedColor>,
+    bounds_mode: BoundsMode,
+    /// Set when a pixel was drawn out of bounds in [`BoundsMode::Error`]
+    out_of_bounds: bool,
+    /// Set while drawing the labels of [`draw_texts`](Self::draw_texts)
+    batch: Option<TextBatch>,
+}
+
+/// What happens to pixels drawn outside of a [`LedCanvas`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BoundsMode {
+    /// Pixels out of bounds are dropped
+    #[default]
+    Clip,
+    /// Coordinates wrap around at the edges, as if the canvas was a torus
+    Wrap,
+    /// Pixels out of bounds are dropped and reported by [`LedCanvas::check_bounds`]
+    Error,
+}
+
+/// A rectangular region of a [`LedCanvas`] with its own origin and bounds.
+///
+/// Created by [`LedCanvas::viewport`]. Dereferences to the parent canvas, so all drawing
+/// methods are available and work in coordinates local to the region, with everything outside
+/// of it clipped. Dropping the sub-canvas restores the parent's state.
+///
+/// ```
+/// use rpi_led_matrix::{LedColor, LedMatrix};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut sub = canvas.viewport(16, 0, 16, 16);
+/// sub.fill(&LedColor { red: 0, green: 0, blue: 255 });
+/// sub.draw_line(0, 0, 15, 15, &LedColor { red: 255, green: 255, blue: 255 });
+/// ```
+pub struct SubCanvas<'a> {
+    canvas: &'a mut LedCanvas,
+    parent_viewport: Option<Rect>,
+    parent_clip: Option<Rect>,
 }
 
 /// Layout options for rendering text on the canvas
@@ -28,6 +78,40 @@ pub enum TextLayout {
         /// Maximum line width
         line_width: i32
     }, 
+    /// Draw text in columns from top to bottom, the columns running from right to left, as
+    /// in vertical Chinese and Japanese text.
+    ///
+    /// The position is the right edge of the first column and the baseline of its first
+    /// character. Characters are centered within columns as wide as the widest character, and
+    /// a new column starts after a newline or when the column height is reached. The leading
+    /// is added between columns.
+    VerticalColumns {
+        /// Maximum column height
+        column_height: i32,
+    },
+    /// Draw text wrapped greedily at whitespace, filling each line as far as possible.
+    ///
+    /// Unlike [`Wrapped`](Self::Wrapped), lines only ever break at whitespace, unless words
+    /// are hyphenated. The lines are returned by [`LedFont::wrap_words`].
+    WrappedWords {
+        /// Maximum line width
+        line_width: i32,
+        /// Break words not fitting the rest of a line with a hyphen, keeping at least two
+        /// characters on either side
+        hyphenate: bool,
+    },
+}
+
+/// Horizontal alignment of text relative to its position
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Text starts at the position
+    #[default]
+    Left,
+    /// Text is centered on the position
+    Center,
+    /// Text ends at the position
+    Right,
 }
 
 /// Options for rendering text on the canvas
@@ -36,10 +120,40 @@ pub struct TextDrawOptions<'a> {
     y: i32,
     color: &'a LedColor,
     layout: TextLayout,
+    align: Align,
     kerning_offset: i32,
     leading: i32,
+    outline: Option<(&'a LedColor, i32)>,
+    shadow: Option<(i32, i32, &'a LedColor)>,
+    brightness: f32,
+    reveal: usize,
+    rotation: f32,
+}
+
+/// One pass over the glyphs of a text: the fill, the outline or the shadow.
+struct TextPass {
+    offset: (i32, i32),
+    /// Radius every glyph pixel is grown by
+    spread: i32,
+    color: LedColor,
+    /// Characters left to draw before the rest is only laid out
+    remaining: usize,
+    /// Clockwise rotation in degrees around the position of the text
+    rotation: f32,
+    origin: (i32, i32),
+}
+
+/// State shared by the labels drawn in one [`LedCanvas::draw_texts`] batch.
+struct TextBatch {
+    /// Panel pixels written so far, sent to the panel once at the end
+    dirty: Vec<bool>,
+    /// Pixels of every glyph grown by a spread, relative to its origin
+    glyphs: GlyphCache,
 }
 
+/// Glyph pixels by character and spread
+type GlyphCache = HashMap<(char, i32), Vec<(i32, i32)>>;
+
 /// Implements both the [`Send`] and [`Sync`] traits for [`LedCanvas`].
 ///
 /// The underlying handle referenced by this FFI is [heap-allocated],
@@ -51,124 +165,883 @@ unsafe impl Send for LedCanvas {}
 unsafe impl Sync for LedCanvas {}
 
 impl LedCanvas {
-    /// Retrieves the width & height of the canvas
-    #[must_use]
-    pub fn canvas_size(&self) -> (i32, i32) {
+    pub(crate) fn new(handle: *mut ffi::CLedCanvas) -> Self {
         let (mut width, mut height): (c_int, c_int) = (0, 0);
         unsafe {
             ffi::led_canvas_get_size(
-                self.handle,
+                handle,
                 std::ptr::addr_of_mut!(width),
                 std::ptr::addr_of_mut!(height),
             );
         }
-        (width as i32, height as i32)
+        Self::with_size(handle, (width, height))
+    }
+
+    fn with_size(handle: *mut ffi::CLedCanvas, device_size: (i32, i32)) -> Self {
+        Self {
+            handle,
+            device_size,
+            transform: Transform::new(),
+            viewport: None,
+            clip: None,
+            shadow: Self::blank_shadow(device_size),
+            bounds_mode: BoundsMode::Clip,
+            out_of_bounds: false,
+            batch: None,
+        }
+    }
+
+    /// Creates a canvas of `width` by `height` pixels that only exists in memory, without a
+    /// matrix.
+    ///
+    /// Everything is drawn the same way as on a canvas of a matrix, so drawing code can be
+    /// tested without a Raspberry Pi, by reading pixels back with [`get`](Self::get) or
+    /// comparing the canvas to a golden image with `assert_png`. A headless canvas can't be
+    /// swapped onto a matrix.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedCanvas, LedColor};
+    /// let mut canvas = LedCanvas::headless(8, 8);
+    /// let red = LedColor { red: 255, green: 0, blue: 0 };
+    /// canvas.draw_line(0, 0, 7, 7, &red);
+    /// assert_eq!(canvas.get(3, 3), Some(red));
+    /// assert_eq!(canvas.get(3, 4).map(|color| color.red), Some(0));
+    /// ```
+    #[must_use]
+    pub fn headless(width: i32, height: i32) -> Self {
+        Self::with_size(std::ptr::null_mut(), (width.max(0), height.max(0)))
+    }
+
+    pub(crate) fn blank_shadow(device_size: (i32, i32)) -> Vec<LedColor> {
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        vec![black; (device_size.0.max(0) * device_size.1.max(0)) as usize]
+    }
+
+    /// Retrieves the width & height of the canvas, as seen through the current [`Transform`].
+    ///
+    /// For a [`SubCanvas`] this is the size of its region.
+    #[must_use]
+    pub const fn canvas_size(&self) -> (i32, i32) {
+        match self.viewport {
+            Some(viewport) => (viewport.width, viewport.height),
+            None => self.transform.logical_size(self.device_size),
+        }
+    }
+
+    /// Offset of local coordinates to coordinates of the whole canvas
+    const fn origin(&self) -> (i32, i32) {
+        match self.viewport {
+            Some(viewport) => (viewport.x, viewport.y),
+            None => (0, 0),
+        }
+    }
+
+    /// Returns a handle to draw into the given region in local coordinates.
+    ///
+    /// The region is confined to the bounds of this canvas, so viewports can be nested.
+    pub fn viewport(&mut self, x: i32, y: i32, width: i32, height: i32) -> SubCanvas<'_> {
+        let (origin_x, origin_y) = self.origin();
+        let (parent_width, parent_height) = self.canvas_size();
+        let region = Rect::new(origin_x + x, origin_y + y, width, height).intersection(&Rect::new(
+            origin_x,
+            origin_y,
+            parent_width,
+            parent_height,
+        ));
+        // keep the origin where it was asked for, only cut off the size at the parent's end
+        let viewport = Rect::new(
+            origin_x + x,
+            origin_y + y,
+            width.min(parent_width - x).max(0),
+            height.min(parent_height - y).max(0),
+        );
+        let parent_viewport = self.viewport.replace(viewport);
+        let parent_clip = self.clip;
+        self.clip = Some(parent_clip.map_or(region, |clip| clip.intersection(&region)));
+        SubCanvas {
+            canvas: self,
+            parent_viewport,
+            parent_clip,
+        }
+    }
+
+    /// Sets the transform applied to all subsequent draw calls.
+    ///
+    /// The transform is kept when the canvas is swapped with
+    /// [`LedMatrix::swap`](crate::LedMatrix::swap).
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    /// Retrieves the transform applied to draw calls.
+    #[must_use]
+    pub const fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Confines all subsequent draw calls, including [`fill`](Self::fill) and text, to the
+    /// given rectangle.
+    ///
+    /// Like the transform, the clip rectangle is kept when the canvas is swapped.
+    pub fn set_clip(&mut self, clip: Rect) {
+        let (x, y) = self.origin();
+        self.clip = Some(Rect::new(clip.x + x, clip.y + y, clip.width, clip.height));
+    }
+
+    /// Removes the clip rectangle, allowing draw calls to reach the whole canvas again.
+    pub fn clear_clip(&mut self) {
+        self.clip = None;
+    }
+
+    /// Retrieves the clip rectangle, if any.
+    #[must_use]
+    pub const fn clip(&self) -> Option<Rect> {
+        let (x, y) = self.origin();
+        match self.clip {
+            Some(clip) => Some(Rect::new(clip.x - x, clip.y - y, clip.width, clip.height)),
+            None => None,
+        }
+    }
+
+    /// Sets what happens to pixels drawn outside of the canvas, or the [`SubCanvas`] region.
+    ///
+    /// With [`BoundsMode::Wrap`] reading with [`get`](Self::get) wraps around as well. Like the
+    /// transform, the mode is kept when the canvas is swapped.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{BoundsMode, LedColor, LedMatrix};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// canvas.set_bounds_mode(BoundsMode::Wrap);
+    /// let (width, _) = canvas.canvas_size();
+    /// let red = LedColor { red: 255, green: 0, blue: 0 };
+    /// canvas.set(width, 0, &red);
+    /// assert_eq!(canvas.get(0, 0), Some(red));
+    /// ```
+    pub fn set_bounds_mode(&mut self, mode: BoundsMode) {
+        self.bounds_mode = mode;
+        self.out_of_bounds = false;
+    }
+
+    /// Retrieves what happens to pixels drawn outside of the canvas.
+    #[must_use]
+    pub const fn bounds_mode(&self) -> BoundsMode {
+        self.bounds_mode
+    }
+
+    /// Reports if pixels were drawn out of bounds in [`BoundsMode::Error`] since the last
+    /// check.
+    ///
+    /// # Errors
+    /// If a pixel was drawn out of bounds since the last check.
+    pub fn check_bounds(&mut self) -> Result<(), &'static str> {
+        if std::mem::take(&mut self.out_of_bounds) {
+            Err("Pixels were drawn out of bounds")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Flags a pixel that couldn't be drawn if it was out of bounds in [`BoundsMode::Error`].
+    fn report_bounds(&mut self, x: i32, y: i32) {
+        let (width, height) = self.canvas_size();
+        let outside = x < 0 || y < 0 || x >= width || y >= height;
+        self.out_of_bounds |= outside && self.bounds_mode == BoundsMode::Error;
+    }
+
+    /// Maps a local coordinate onto the panel, if it isn't clipped.
+    fn to_device(&self, x: i32, y: i32, clipped: bool) -> Option<(i32, i32)> {
+        let (x, y) = match (self.bounds_mode, self.canvas_size()) {
+            (BoundsMode::Wrap, (width, height)) if width > 0 && height > 0 => {
+                (x.rem_euclid(width), y.rem_euclid(height))
+            }
+            _ => (x, y),
+        };
+        let (origin_x, origin_y) = self.origin();
+        let (x, y) = (x + origin_x, y + origin_y);
+        if let Some(viewport) = self.viewport {
+            if !viewport.contains(x, y) {
+                return None;
+            }
+        }
+        if let (true, Some(clip)) = (clipped, self.clip) {
+            if !clip.contains(x, y) {
+                return None;
+            }
+        }
+        let (x, y) = self.transform.apply(x, y, self.device_size);
+        let (width, height) = self.device_size;
+        (x >= 0 && y >= 0 && x < width && y < height).then_some((x, y))
     }
 
     /// Sets the pixel at the given coordinate to the given color.
     pub fn set(&mut self, x: i32, y: i32, color: &LedColor) {
-        unsafe {
-            ffi::led_canvas_set_pixel(
-                self.handle,
-                x as c_int,
-                y as c_int,
-                color.red,
-                color.green,
-                color.blue,
-            );
+        let Some((device_x, device_y)) = self.to_device(x, y, true) else {
+            self.report_bounds(x, y);
+            return;
+        };
+        self.shadow[(device_y * self.device_size.0 + device_x) as usize] = *color;
+        self.flush(std::iter::once((device_x, device_y)));
+    }
+
+    /// Sets many pixels in one batch, skipping the ones outside the canvas or clip rectangle.
+    ///
+    /// A middle ground between [`set`](Self::set) and [`shade`](Self::shade) for sparse
+    /// content like starfields or particles.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedMatrix};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// let white = LedColor { red: 255, green: 255, blue: 255 };
+    /// let stars = [(3, 5), (17, 2), (40, 20)];
+    /// canvas.set_iter(stars.iter().map(|&(x, y)| (x, y, white)));
+    /// ```
+    pub fn set_iter(&mut self, pixels: impl IntoIterator<Item = (i32, i32, LedColor)>) {
+        let pixels = pixels.into_iter();
+        let mut written = Vec::with_capacity(pixels.size_hint().0);
+        for (x, y, color) in pixels {
+            if let Some((x, y)) = self.to_device(x, y, true) {
+                self.shadow[(y * self.device_size.0 + x) as usize] = color;
+                written.push((x, y));
+            } else {
+                self.report_bounds(x, y);
+            }
+        }
+        self.flush(written);
+    }
+
+    /// Sends the given pixels, in panel coordinates, from the shadow copy to the panel.
+    ///
+    /// Together with [`present`](Self::present) the only places pixels cross the FFI boundary.
+    fn flush(&mut self, pixels: impl IntoIterator<Item = (i32, i32)>) {
+        let width = self.device_size.0;
+        if let Some(batch) = &mut self.batch {
+            for (x, y) in pixels {
+                batch.dirty[(y * width + x) as usize] = true;
+            }
+            return;
+        }
+        if self.handle.is_null() {
+            return;
+        }
+        let _writing = race::writing(self.handle);
+        for (x, y) in pixels {
+            let color = self.shadow[(y * self.device_size.0 + x) as usize];
+            unsafe {
+                ffi::led_canvas_set_pixel(
+                    self.handle,
+                    x as c_int,
+                    y as c_int,
+                    color.red,
+                    color.green,
+                    color.blue,
+                );
+            }
         }
     }
 
-    /// Clears the canvas.
+    /// Sends every pixel to the panel, passing its index and color in the shadow copy through
+    /// `map` first. The shadow copy itself is left untouched.
+    pub(crate) fn present(&mut self, mut map: impl FnMut(usize, LedColor) -> LedColor) {
+        if self.handle.is_null() {
+            return;
+        }
+        let _writing = race::writing(self.handle);
+        let width = self.device_size.0;
+        for (index, color) in self.shadow.iter().enumerate() {
+            let color = map(index, *color);
+            let (x, y) = (index as i32 % width, index as i32 / width);
+            unsafe {
+                ffi::led_canvas_set_pixel(
+                    self.handle,
+                    x as c_int,
+                    y as c_int,
+                    color.red,
+                    color.green,
+                    color.blue,
+                );
+            }
+        }
+    }
+
+    /// Computes the color of every pixel with `shader` and writes them in one batch.
+    ///
+    /// The shader is called with local coordinates, only for pixels inside the clip rectangle.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedMatrix};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// canvas.shade(|x, y| LedColor { red: (x * 4) as u8, green: (y * 8) as u8, blue: 64 });
+    /// ```
+    pub fn shade<F>(&mut self, mut shader: F)
+    where
+        F: FnMut(i32, i32) -> LedColor,
+    {
+        let (width, height) = self.canvas_size();
+        let mut written = Vec::with_capacity((width.max(0) * height.max(0)) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                if let Some((device_x, device_y)) = self.to_device(x, y, true) {
+                    self.shadow[(device_y * self.device_size.0 + device_x) as usize] = shader(x, y);
+                    written.push((device_x, device_y));
+                }
+            }
+        }
+        self.flush(written);
+    }
+
+    /// Retrieves the color of the pixel at the given coordinate, if it is on the canvas.
+    ///
+    /// Colors are read from a copy kept on the Rust side, which tracks everything drawn through
+    /// this canvas. The clip rectangle doesn't affect reading.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.to_device(x, y, false)
+            .map(|(x, y)| self.shadow[(y * self.device_size.0 + x) as usize])
+    }
+
+    /// Returns the average color of the whole canvas, e.g. to match ambient lights to it.
+    #[must_use]
+    pub fn average_color(&self) -> LedColor {
+        let count = self.shadow.len().max(1) as u64;
+        let sums = self.shadow.iter().fold([0_u64; 3], |[r, g, b], color| {
+            [
+                r + u64::from(color.red),
+                g + u64::from(color.green),
+                b + u64::from(color.blue),
+            ]
+        });
+        let [red, green, blue] = sums.map(|sum| (sum / count) as u8);
+        LedColor { red, green, blue }
+    }
+
+    /// Clears the canvas, or the clip rectangle if one is set.
     pub fn clear(&mut self) {
-        unsafe {
-            ffi::led_canvas_clear(self.handle);
+        if self.clip.is_some() || self.viewport.is_some() {
+            self.fill_clip(&LedColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+            });
+        } else {
+            self.shadow = Self::blank_shadow(self.device_size);
+            if !self.handle.is_null() {
+                let _writing = race::writing(self.handle);
+                unsafe {
+                    ffi::led_canvas_clear(self.handle);
+                }
+            }
         }
     }
 
-    /// Fills the canvas with the given color.
+    /// Fills the canvas, or the clip rectangle if one is set, with the given color.
     pub fn fill(&mut self, color: &LedColor) {
-        unsafe {
-            ffi::led_canvas_fill(self.handle, color.red, color.green, color.blue);
+        if self.clip.is_some() || self.viewport.is_some() {
+            self.fill_clip(color);
+        } else {
+            self.shadow.fill(*color);
+            if !self.handle.is_null() {
+                let _writing = race::writing(self.handle);
+                unsafe {
+                    ffi::led_canvas_fill(self.handle, color.red, color.green, color.blue);
+                }
+            }
+        }
+    }
+
+    fn fill_clip(&mut self, color: &LedColor) {
+        let (width, height) = self.canvas_size();
+        let mut area = Rect::new(0, 0, width, height);
+        if let Some(clip) = self.clip() {
+            area = area.intersection(&clip);
+        }
+        for y in area.y..area.y + area.height {
+            for x in area.x..area.x + area.width {
+                self.set(x, y, color);
+            }
         }
     }
 
-    /// Draws a straight, one pixel wide line using the C++ library.
+    /// Draws a straight, one pixel wide line.
     ///
     /// Consider using embedded-graphics for more drawing features.
     pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor) {
-        unsafe {
-            ffi::draw_line(
-                self.handle,
-                x0,
-                y0,
-                x1,
-                y1,
-                color.red,
-                color.green,
-                color.blue,
-            );
+        raster::line(x0, y0, x1, y1, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws a line of the given width in pixels, with round ends.
+    pub fn draw_line_thick(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        width: u32,
+        color: &LedColor,
+    ) {
+        raster::thick_line(x0, y0, x1, y1, width, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws connected lines through all `points`, with the given width in pixels.
+    ///
+    /// A single point is drawn as a dot of the given width.
+    pub fn draw_polyline(&mut self, points: &[(i32, i32)], width: u32, color: &LedColor) {
+        if let [(x, y)] = *points {
+            self.draw_line_thick(x, y, x, y, width, color);
+        }
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            self.draw_line_thick(x0, y0, x1, y1, width, color);
         }
     }
 
-    /// Draws a one pixel wide circle using the C++ library.
+    /// Draws a one pixel wide arc of a circle.
+    ///
+    /// Angles are in degrees, measured clockwise from 3 o'clock. The arc runs clockwise from
+    /// `start_angle` to `end_angle`; a sweep of 360° or more draws the full circle.
+    pub fn draw_arc(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        start_angle: f32,
+        end_angle: f32,
+        color: &LedColor,
+    ) {
+        raster::arc(x, y, radius as i32, start_angle, end_angle, |px, py| {
+            self.set(px, py, color);
+        });
+    }
+
+    /// Draws a one pixel wide quadratic Bézier curve from `p0` to `p2`, bent towards `p1`.
+    pub fn draw_quadratic_bezier(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        color: &LedColor,
+    ) {
+        raster::bezier(&[p0, p1, p2], |x, y| self.set(x, y, color));
+    }
+
+    /// Draws a one pixel wide cubic Bézier curve from `p0` to `p3`, bent towards `p1` and `p2`.
+    pub fn draw_cubic_bezier(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        p3: (i32, i32),
+        color: &LedColor,
+    ) {
+        raster::bezier(&[p0, p1, p2, p3], |x, y| self.set(x, y, color));
+    }
+
+    /// Draws the one pixel wide outline of the polygon through `points`, closing it back to
+    /// the first point.
+    pub fn draw_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        raster::polygon(points, |x, y| self.set(x, y, color));
+    }
+
+    /// Fills the polygon through `points`, including its outline.
+    ///
+    /// Self-intersecting polygons are filled with the even-odd rule.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        raster::fill_polygon(points, |x, y| self.set(x, y, color));
+    }
+
+    /// Draws a one pixel wide circle.
     ///
     /// Consider using embedded-graphics for more drawing features.
     pub fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
-        unsafe {
-            ffi::draw_circle(
-                self.handle,
-                x as c_int,
-                y as c_int,
-                radius as c_int,
-                color.red,
-                color.green,
-                color.blue,
-            );
+        raster::circle(x, y, radius as i32, |px, py| self.set(px, py, color));
+    }
+
+    /// Blends `color` into the pixel at the given coordinate by `coverage` in \[0,1\].
+    fn blend(&mut self, x: i32, y: i32, color: &LedColor, coverage: f32) {
+        if let Some(background) = self.get(x, y) {
+            self.set(x, y, &background.lerp(color, coverage));
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    /// Renders text using the C++ library.
+    /// Draws an anti-aliased, one pixel wide line between sub-pixel positions.
+    ///
+    /// Pixel centers lie on integer coordinates. Edge pixels are blended with what was drawn
+    /// before, see [`get`](Self::get).
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        raster::line_aa(x0, y0, x1, y1, |x, y, coverage| {
+            self.blend(x, y, color, coverage);
+        });
+    }
+
+    /// Draws an anti-aliased, one pixel wide circle around a sub-pixel position.
     ///
-    /// # Panics
-    /// If the given `text` fails to convert to a `CString`. This can
-    /// occur when there is a null character mid way in the string.
+    /// Edge pixels are blended with what was drawn before, see [`get`](Self::get).
+    pub fn draw_circle_aa(&mut self, x: f32, y: f32, radius: f32, color: &LedColor) {
+        raster::circle_aa(x, y, radius, |px, py, coverage| {
+            self.blend(px, py, color, coverage);
+        });
+    }
+
+    /// Renders text with the given font, returning the distance the pen advanced.
+    ///
+    /// `options.position` is the start of the baseline. For [`TextLayout::Vertical`] and the
+    /// wrapped layouts the vertical advance is returned instead of the horizontal one, for
+    /// [`TextLayout::VerticalColumns`] the width of all columns.
+    ///
+    /// The shadow is drawn first, then the outline, so neither covers the text itself.
     pub fn draw_text(&mut self, font: &LedFont, text: &str, options: &TextDrawOptions) -> i32 {
-        let text = CString::new(text).expect("given string failed to convert into a CString");
-        let x = options.x as c_int;
-        let y = options.y as c_int;
-        let r = options.color.red;
-        let g = options.color.green;
-        let b = options.color.blue;
-        let text = text.as_ptr();
-        let kerning_offset = options.kerning_offset as c_int;
-        let leading = options.leading as c_int;
+        // a batch lays out every glyph once for all of its labels
+        let mut glyphs = self
+            .batch
+            .as_mut()
+            .map(|batch| std::mem::take(&mut batch.glyphs));
+        let advance = TextRenderer {
+            canvas: &mut *self,
+            glyphs: glyphs.as_mut(),
+        }
+        .draw_text(font, text, options);
+        if let (Some(batch), Some(glyphs)) = (&mut self.batch, glyphs) {
+            batch.glyphs = glyphs;
+        }
+        advance
+    }
+
+    /// Draws many labels in the same font as one batch, returning the advance of each as
+    /// [`draw_text`](Self::draw_text) would.
+    ///
+    /// Glyphs are looked up and laid out once for all labels, and every pixel is sent to the
+    /// panel once at the end, however many labels, outlines and shadows cover it. A dashboard
+    /// drawing lots of small labels every frame spends much less time in the C library this way.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedFont, LedMatrix, TextDrawOptions};
+    /// use std::path::Path;
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/4x6.bdf")).unwrap();
+    /// let white = LedColor { red: 255, green: 255, blue: 255 };
+    /// let label = |text, y| (text, TextDrawOptions::new().position(0, y).color(&white));
+    /// let labels = [label("CPU", 6), label("RAM", 13), label("NET", 20)];
+    /// let advances = canvas.draw_texts(&font, &labels);
+    /// assert_eq!(advances.len(), 3);
+    /// ```
+    pub fn draw_texts(&mut self, font: &LedFont, labels: &[(&str, TextDrawOptions)]) -> Vec<i32> {
+        let (width, height) = self.device_size;
+        self.batch = Some(TextBatch {
+            dirty: vec![false; (width.max(0) * height.max(0)) as usize],
+            glyphs: HashMap::new(),
+        });
+        let advances = labels
+            .iter()
+            .map(|(text, options)| self.draw_text(font, text, options))
+            .collect();
+        if let Some(batch) = self.batch.take() {
+            let dirty = (0..).zip(batch.dirty).filter(|(_, dirty)| *dirty);
+            let pixels: Vec<_> = dirty
+                .map(|(index, _)| (index % width, index / width))
+                .collect();
+            self.flush(pixels);
+        }
+        advances
+    }
+
+    /// Draws lines of text top to bottom within `region`, returning how many lines fit.
+    ///
+    /// The first line's ascent starts at the top of the region and every further line
+    /// follows `line_spacing` pixels below the descent of the one before, using the font's
+    /// [`baseline`](LedFont::baseline) and [`descent`](LedFont::descent). Each line is aligned
+    /// within the width of the region. Lines that don't fit the region completely aren't
+    /// drawn, and nothing is drawn outside of it.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{Align, LedColor, LedFont, LedMatrix, Rect};
+    /// use std::path::Path;
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/5x7.bdf")).unwrap();
+    /// let white = LedColor { red: 255, green: 255, blue: 255 };
+    /// let lines = ["Platform 3", "12:04 Berlin", "12:10 Hamburg"];
+    /// let region = Rect::new(0, 2, 64, 30);
+    /// let shown = canvas.draw_text_lines(&font, &lines, region, 1, Align::Center, &white);
+    /// assert_eq!(shown, 3);
+    /// ```
+    pub fn draw_text_lines(
+        &mut self,
+        font: &LedFont,
+        lines: &[&str],
+        region: Rect,
+        line_spacing: i32,
+        align: Align,
+        color: &LedColor,
+    ) -> usize {
+        let line_height = font.baseline() + font.descent();
+        let fitting = (region.height + line_spacing) / (line_height + line_spacing).max(1);
+        let count = lines.len().min(fitting.max(0) as usize);
+        let anchor = match align {
+            Align::Left => 0,
+            Align::Center => region.width / 2,
+            Align::Right => region.width,
+        };
+        let mut canvas = self.viewport(region.x, region.y, region.width, region.height);
+        let mut baseline = font.baseline();
+        for line in &lines[..count] {
+            let options = TextDrawOptions::new()
+                .position(anchor, baseline)
+                .align(align)
+                .color(color);
+            canvas.draw_text(font, line, &options);
+            baseline += line_height + line_spacing;
+        }
+        count
+    }
+}
+
+/// Draws text onto any canvas, for [`LedCanvas::draw_text`] and [`TextCanvas`].
+pub(crate) struct TextRenderer<'a, C: ?Sized> {
+    pub(crate) canvas: &'a mut C,
+    /// Shared by the labels of a batch, laid out anew otherwise
+    pub(crate) glyphs: Option<&'a mut GlyphCache>,
+}
+
+impl<C: Canvas + ?Sized> TextRenderer<'_, C> {
+    /// Draws the shadow, the outline and the text, returning the distance the pen advanced.
+    pub(crate) fn draw_text(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        options: &TextDrawOptions,
+    ) -> i32 {
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let pass = |offset, spread, color: &LedColor| TextPass {
+            offset,
+            spread,
+            color: black.lerp(color, options.brightness),
+            remaining: options.reveal,
+            rotation: options.rotation,
+            origin: (options.x, options.y),
+        };
+        let outline_width = options.outline.map_or(0, |(_, width)| width);
+        if let Some((dx, dy, color)) = options.shadow {
+            self.draw_text_pass(
+                font,
+                text,
+                options,
+                &mut pass((dx, dy), outline_width, color),
+            );
+        }
+        if let Some((color, width)) = options.outline {
+            self.draw_text_pass(font, text, options, &mut pass((0, 0), width, color));
+        }
+        self.draw_text_pass(font, text, options, &mut pass((0, 0), 0, options.color))
+    }
 
+    /// Lays out and draws the glyphs of a text once, returning the distance the pen advanced.
+    fn draw_text_pass(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        options: &TextDrawOptions,
+        pass: &mut TextPass,
+    ) -> i32 {
         match options.layout {
             TextLayout::Horizontal => {
-                unsafe {
-                    ffi::draw_text(
-                        self.handle, font.handle, x, y, r, g, b, text, kerning_offset
-                    ) as i32
-                }
+                let width = font.advance(text, options.kerning_offset);
+                let x = options.align.start(options.x, width);
+                self.draw_text_line(font, text, x, options.y, options, pass)
             }
             TextLayout::Vertical => {
-                unsafe {
-                    ffi::vertical_draw_text(
-                        self.handle, font.handle, x, y, r, g, b, text, kerning_offset
-                    ) as i32
+                let line_height = font.height().unwrap_or_default() + options.kerning_offset;
+                let mut y = options.y;
+                for c in text.chars() {
+                    let width = font.glyph(c).map_or(0, |g| g.device_width);
+                    let x = options.align.start(options.x, width);
+                    self.draw_glyph(font, c, x, y, pass);
+                    y += line_height;
                 }
+                y - options.y
             }
             TextLayout::Wrapped { line_width } => {
-                unsafe {
-                    ffi::draw_text_wrapped(
-                        self.handle, font.handle, x, y, line_width ,r, g, b, text, kerning_offset, leading
-                    ) as i32
+                let lines = font.wrap(text, line_width, options.kerning_offset);
+                self.draw_wrapped(font, &lines, line_width, options, pass)
+            }
+            TextLayout::WrappedWords {
+                line_width,
+                hyphenate,
+            } => {
+                let lines = font.wrap_words(text, line_width, options.kerning_offset, hyphenate);
+                self.draw_wrapped(font, &lines, line_width, options, pass)
+            }
+            TextLayout::VerticalColumns { column_height } => {
+                let line_height = font.height().unwrap_or_default() + options.kerning_offset;
+                let rows = (column_height / line_height.max(1)).max(1);
+                let widths = text.chars().map(|c| font.char_width(c));
+                let column_width = widths.max().unwrap_or_default();
+                let mut right = options.x;
+                let mut row = 0;
+                for c in text.chars() {
+                    if c == '\n' || row == rows {
+                        right -= column_width + options.leading;
+                        row = 0;
+                    }
+                    if c == '\n' {
+                        continue;
+                    }
+                    let x = right - (column_width + font.char_width(c)) / 2;
+                    self.draw_glyph(font, c, x, options.y + row * line_height, pass);
+                    row += 1;
                 }
+                options.x - right + column_width
             }
         }
     }
+
+    /// Draws wrapped lines of text, returning their vertical advance.
+    fn draw_wrapped(
+        &mut self,
+        font: &LedFont,
+        lines: &[String],
+        line_width: i32,
+        options: &TextDrawOptions,
+        pass: &mut TextPass,
+    ) -> i32 {
+        let line_height = font.height().unwrap_or_default() + options.leading;
+        let mut y = options.y;
+        // aligning the right edge of the box aligns lines within it
+        let anchor = match options.align {
+            Align::Left => options.x,
+            Align::Center => options.x + line_width / 2,
+            Align::Right => options.x + line_width,
+        };
+        for line in lines {
+            let width = font.advance(line, options.kerning_offset);
+            let x = options.align.start(anchor, width);
+            self.draw_text_line(font, line, x, y, options, pass);
+            y += line_height;
+        }
+        y - options.y
+    }
+
+    /// Draws a single line of text, returning its advance width.
+    fn draw_text_line(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        x: i32,
+        y: i32,
+        options: &TextDrawOptions,
+        pass: &mut TextPass,
+    ) -> i32 {
+        let mut pen = x;
+        for cluster in unicode::display_clusters(text) {
+            // combining marks are drawn on top of the character before them
+            let mut advance = 0;
+            for (i, c) in cluster.into_iter().enumerate() {
+                let width = self.draw_glyph(font, c, pen, y, pass);
+                if i == 0 {
+                    advance = width;
+                }
+            }
+            pen += advance + options.kerning_offset;
+        }
+        pen - x
+    }
+
+    /// Draws a glyph with its baseline origin at (`x`, `y`), returning its advance width.
+    fn draw_glyph(&mut self, font: &LedFont, c: char, x: i32, y: i32, pass: &mut TextPass) -> i32 {
+        font.glyph(c).map_or(0, |glyph| {
+            if pass.remaining == 0 {
+                return glyph.device_width;
+            }
+            pass.remaining -= 1;
+            let place = |&(dx, dy): &(i32, i32)| (x + dx, y + dy);
+            let mut pixels: Vec<(i32, i32)> = match &mut self.glyphs {
+                Some(glyphs) => glyphs
+                    .entry((c, pass.spread))
+                    .or_insert_with(|| glyph_offsets(glyph, pass.spread))
+                    .iter()
+                    .map(place)
+                    .collect(),
+                None => glyph_offsets(glyph, pass.spread)
+                    .iter()
+                    .map(place)
+                    .collect(),
+            };
+            if pass.rotation != 0.0 {
+                let mut rotated = Vec::with_capacity(pixels.len());
+                raster::rotate(&pixels, pass.origin, pass.rotation, |x, y| {
+                    rotated.push((x, y))
+                });
+                pixels = rotated;
+            }
+            let ((dx, dy), color) = (pass.offset, pass.color);
+            self.canvas
+                .set_iter(pixels.into_iter().map(|(x, y)| (x + dx, y + dy, color)));
+            glyph.device_width
+        })
+    }
+}
+
+impl Deref for SubCanvas<'_> {
+    type Target = LedCanvas;
+
+    fn deref(&self) -> &LedCanvas {
+        self.canvas
+    }
+}
+
+impl DerefMut for SubCanvas<'_> {
+    fn deref_mut(&mut self) -> &mut LedCanvas {
+        self.canvas
+    }
+}
+
+impl Drop for SubCanvas<'_> {
+    fn drop(&mut self) {
+        self.canvas.viewport = self.parent_viewport;
+        self.canvas.clip = self.parent_clip;
+    }
+}
+
+/// Pixels of `glyph` grown by `spread`, relative to its origin, each listed once.
+fn glyph_offsets(glyph: &Glyph, spread: i32) -> Vec<(i32, i32)> {
+    let spread = spread_offsets(spread);
+    let mut pixels: Vec<(i32, i32)> = glyph
+        .pixels()
+        .flat_map(|(dx, dy)| spread.iter().map(move |(sx, sy)| (dx + sx, dy + sy)))
+        .collect();
+    pixels.sort_unstable();
+    pixels.dedup();
+    pixels
+}
+
+/// Offsets within a disc of the given radius, grown slightly to round off small radii.
+fn spread_offsets(radius: i32) -> Vec<(i32, i32)> {
+    let radius = radius.max(0);
+    (-radius..=radius)
+        .flat_map(|y| (-radius..=radius).map(move |x| (x, y)))
+        .filter(|(x, y)| x * x + y * y <= radius * radius + radius)
+        .collect()
+}
+
+impl Align {
+    /// Left edge of text `width` pixels wide, aligned to `anchor`
+    const fn start(self, anchor: i32, width: i32) -> i32 {
+        match self {
+            Self::Left => anchor,
+            Self::Center => anchor - width / 2,
+            Self::Right => anchor - width,
+        }
+    }
 }
 
 impl<'a> TextDrawOptions<'a> {
@@ -183,8 +1056,14 @@ impl<'a> TextDrawOptions<'a> {
                 blue: 255,
             },
             layout: TextLayout::Horizontal,
+            align: Align::Left,
             kerning_offset: 0,
             leading: 0,
+            outline: None,
+            shadow: None,
+            brightness: 1.0,
+            reveal: usize::MAX,
+            rotation: 0.0,
         }
     }
 
@@ -207,6 +1086,15 @@ impl<'a> TextDrawOptions<'a> {
         self
     }
 
+    /// Sets how the text is aligned horizontally to the position.
+    ///
+    /// For [`TextLayout::Wrapped`] the lines are aligned within the line width instead, and
+    /// for [`TextLayout::Vertical`] every character is aligned on its own.
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
     /// Sets the value for additional horizontal spacing between characters
     pub fn kerning_offset(mut self, offset: i32) -> Self {
         self.kerning_offset = offset;
@@ -218,6 +1106,51 @@ impl<'a> TextDrawOptions<'a> {
         self.leading = leading;
         self
     }
+
+    /// Surrounds the text with an outline `width` pixels wide, keeping it readable on busy
+    /// backgrounds
+    pub fn outline(mut self, color: &'a LedColor, width: i32) -> Self {
+        self.outline = Some((color, width));
+        self
+    }
+
+    /// Draws a shadow of the text, including its outline, offset by (`dx`, `dy`)
+    pub fn shadow(mut self, dx: i32, dy: i32, color: &'a LedColor) -> Self {
+        self.shadow = Some((dx, dy, color));
+        self
+    }
+
+    /// Scales all colors of the text towards black, from 0 (black) to 1 (unchanged)
+    pub fn brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Draws only the first `count` characters, laid out and aligned as the whole text
+    pub fn reveal(mut self, count: usize) -> Self {
+        self.reveal = count;
+        self
+    }
+
+    /// Rotates the text clockwise by `degrees` around its position, e.g. by -90 to run it up
+    /// the side of the canvas.
+    ///
+    /// Quarter turns are exact. Other angles are sampled per pixel, which can look jagged on
+    /// small fonts. Shadows keep their offset on the canvas.
+    pub fn rotation(mut self, degrees: f32) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// The brightness set by [`brightness`](Self::brightness)
+    pub(crate) const fn current_brightness(&self) -> f32 {
+        self.brightness
+    }
+
+    /// The count set by [`reveal`](Self::reveal)
+    pub(crate) const fn current_reveal(&self) -> usize {
+        self.reveal
+    }
 }
 
 impl Default for TextDrawOptions<'_> {
@@ -253,6 +1186,30 @@ mod tests {
         assert_eq!(canvas.canvas_size(), (64, 32));
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn viewport() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.canvas();
+        canvas.set_clip(Rect::new(0, 0, 20, 32));
+        {
+            let mut sub = canvas.viewport(16, 8, 16, 16);
+            assert_eq!(sub.canvas_size(), (16, 16));
+            assert_eq!(sub.clip(), Some(Rect::new(0, 0, 4, 16)));
+            {
+                let nested = sub.viewport(8, 8, 16, 16);
+                assert_eq!(nested.canvas_size(), (8, 8));
+            }
+            sub.fill(&LedColor {
+                red: 0,
+                green: 0,
+                blue: 127,
+            });
+        }
+        assert_eq!(canvas.canvas_size(), (64, 32));
+        assert_eq!(canvas.clip(), Some(Rect::new(0, 0, 20, 32)));
+    }
+
     #[test]
     #[serial_test::serial]
     fn draw_line() {
@@ -320,6 +1277,183 @@ mod tests {
         }
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn shade() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let (width, height) = canvas.canvas_size();
+        let frames = 100;
+        let sleep_duration = time::Duration::new(3, 0) / frames;
+
+        for t in 0..frames {
+            let t = f64::from(t) / 10.;
+            canvas.shade(|x, y| {
+                let (x, y) = (f64::from(x) / 8., f64::from(y) / 8.);
+                let v = (x + t).sin() + (y + t).cos() + (x + y + t).sin();
+                LedColor {
+                    red: ((v * PI).sin() * 127. + 128.) as u8,
+                    green: ((v * PI).cos() * 127. + 128.) as u8,
+                    blue: 64,
+                }
+            });
+            canvas = matrix.swap(canvas);
+            thread::sleep(sleep_duration);
+        }
+        assert!(canvas.get(width - 1, height - 1).is_some());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn set_iter() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let (width, height) = canvas.canvas_size();
+        let color = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        canvas.set_iter([(0, 0, color), (-1, 0, color), (width, height, color)]);
+        assert_eq!(canvas.get(0, 0), Some(color));
+        assert_eq!(canvas.get(width, height), None);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn draw_texts() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let font = LedFont::new(std::path::Path::new("/usr/share/fonts/misc/4x6.bdf")).unwrap();
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let labels = [
+            ("12", TextDrawOptions::new().position(0, 6).color(&white)),
+            (
+                "34",
+                TextDrawOptions::new()
+                    .position(0, 13)
+                    .color(&white)
+                    .outline(&white, 1),
+            ),
+        ];
+        let advances = canvas.draw_texts(&font, &labels);
+        let mut single = matrix.offscreen_canvas();
+        for (text, options) in &labels {
+            single.draw_text(&font, text, options);
+        }
+        assert_eq!(advances, [8, 8]);
+        assert_eq!(canvas.shadow, single.shadow);
+        assert!(canvas.batch.is_none());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn bounds_mode() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let (width, height) = canvas.canvas_size();
+        let color = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        canvas.set_bounds_mode(BoundsMode::Wrap);
+        canvas.set(-1, height, &color);
+        assert_eq!(canvas.get(width - 1, 0), Some(color));
+        assert_eq!(canvas.get(-1, 0), Some(color));
+
+        canvas.set_bounds_mode(BoundsMode::Error);
+        canvas.set(0, 0, &color);
+        assert_eq!(canvas.check_bounds(), Ok(()));
+        canvas.draw_circle(0, 0, 3, &color);
+        assert!(canvas.check_bounds().is_err());
+        assert_eq!(canvas.check_bounds(), Ok(()));
+    }
+
+    #[test]
+    fn align() {
+        assert_eq!(Align::Left.start(10, 7), 10);
+        assert_eq!(Align::Center.start(10, 7), 7);
+        assert_eq!(Align::Right.start(10, 7), 3);
+    }
+
+    #[test]
+    fn headless() {
+        let mut canvas = LedCanvas::headless(8, 8);
+        assert_eq!(canvas.canvas_size(), (8, 8));
+        let font = LedFont::new_from_buffer(crate::font::test::TINY_BDF.as_bytes()).unwrap();
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let options = TextDrawOptions::new().position(0, 6).color(&white);
+        assert_eq!(canvas.draw_text(&font, "A", &options), 4);
+        let lit = canvas
+            .shadow
+            .iter()
+            .filter(|&&color| color == white)
+            .count();
+        assert_eq!(lit, 10);
+        canvas.clear();
+        assert_eq!(canvas.average_color().red, 0);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn vertical_columns() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let font = LedFont::new_from_buffer(crate::font::test::TINY_BDF.as_bytes()).unwrap();
+        let layout = TextLayout::VerticalColumns { column_height: 12 };
+        let options = TextDrawOptions::new()
+            .position(20, 5)
+            .layout(layout)
+            .leading(1);
+        // two columns of two characters and one of one, each 4 wide and 1 apart
+        assert_eq!(canvas.draw_text(&font, "AAAAA", &options), 14);
+        assert_eq!(canvas.draw_text(&font, "A\nA", &options), 9);
+        assert!(canvas.get(17, 0).is_some_and(|c| c.red > 0));
+        assert!(canvas.get(17, 6).is_some_and(|c| c.red > 0));
+        assert!(canvas.get(7, 0).is_some_and(|c| c.red > 0));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn text_lines() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let font = LedFont::new_from_buffer(crate::font::test::TINY_BDF.as_bytes()).unwrap();
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        // lines are 6 high, so two fit into 13 pixels with a spacing of 1, but not three
+        let region = Rect::new(10, 10, 20, 13);
+        let lines = ["A", "A", "A"];
+        let shown = canvas.draw_text_lines(&font, &lines, region, 1, Align::Right, &white);
+        assert_eq!(shown, 2);
+        // the tip of 'A' at the top of each line, right aligned within the region
+        assert!(canvas.get(27, 10).is_some_and(|c| c.red > 0));
+        assert!(canvas.get(27, 17).is_some_and(|c| c.red > 0));
+        assert!(canvas.get(27, 24).is_some_and(|c| c.red == 0));
+    }
+
+    #[test]
+    fn spread() {
+        assert_eq!(spread_offsets(0), [(0, 0)]);
+        assert_eq!(spread_offsets(1).len(), 9);
+        // the corners of the square are cut off from radius 2 on
+        let offsets = spread_offsets(2);
+        assert_eq!(offsets.len(), 21);
+        assert!(!offsets.contains(&(2, 2)));
+    }
+
     #[test]
     #[serial_test::serial]
     fn canvas_swap() {
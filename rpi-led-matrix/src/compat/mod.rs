@@ -0,0 +1,3 @@
+//! APIs shaped like other matrix libraries, to ease porting programs written against them.
+#[deny(missing_docs)]
+pub mod python_like;
@@ -0,0 +1,267 @@
+//! Methods named like the [Python bindings] of the C++ library, so ported programs keep
+//! their structure and names.
+//!
+//! The Python `RGBMatrix`, frame canvases and the `graphics` module map onto [`RGBMatrix`],
+//! [`FrameCanvas`] and [`graphics`]. Attributes like `matrix.width` become methods, and the
+//! options are set with the setters of [`RGBMatrixOptions`].
+//!
+//! ```
+//! use rpi_led_matrix::compat::python_like::{graphics, Canvas, RGBMatrix, RGBMatrixOptions};
+//! let mut options = RGBMatrixOptions::new();
+//! options.set_rows(32);
+//! let mut matrix = RGBMatrix::new(options).unwrap();
+//! let mut offscreen_canvas = matrix.CreateFrameCanvas();
+//! let mut font = graphics::Font::new();
+//! font.LoadFont("/usr/share/fonts/misc/7x13.bdf").unwrap();
+//! let text_color = graphics::Color(255, 255, 0);
+//! for pos in (0..offscreen_canvas.width()).rev() {
+//!     offscreen_canvas.Clear();
+//!     graphics::DrawText(&mut offscreen_canvas, &font, pos, 10, text_color, "Hello");
+//!     offscreen_canvas = matrix.SwapOnVSync(offscreen_canvas);
+//! }
+//! ```
+//!
+//! [Python bindings]: https://github.com/hzeller/rpi-rgb-led-matrix/tree/master/bindings/python
+#![allow(non_snake_case)]
+#[cfg(feature = "image")]
+use crate::LedImage;
+use crate::{LedCanvas, LedMatrix, LedMatrixOptions, LedRuntimeOptions};
+
+/// The options of an [`RGBMatrix`], like `RGBMatrixOptions` in Python
+pub type RGBMatrixOptions = LedMatrixOptions;
+
+/// A canvas that's drawn on offscreen and swapped in, like `FrameCanvas` in Python
+pub type FrameCanvas = LedCanvas;
+
+/// Drawing methods shared by [`RGBMatrix`] and [`FrameCanvas`].
+pub trait Canvas {
+    /// The canvas drawn on
+    fn led_canvas(&self) -> &LedCanvas;
+
+    /// The canvas drawn on, mutably
+    fn led_canvas_mut(&mut self) -> &mut LedCanvas;
+
+    /// The width in pixels
+    fn width(&self) -> i32 {
+        self.led_canvas().canvas_size().0
+    }
+
+    /// The height in pixels
+    fn height(&self) -> i32 {
+        self.led_canvas().canvas_size().1
+    }
+
+    /// Sets the pixel at (`x`, `y`).
+    fn SetPixel(&mut self, x: i32, y: i32, red: u8, green: u8, blue: u8) {
+        self.led_canvas_mut()
+            .set(x, y, &graphics::Color(red, green, blue));
+    }
+
+    /// Turns all pixels off.
+    fn Clear(&mut self) {
+        self.led_canvas_mut().clear();
+    }
+
+    /// Sets all pixels to one color.
+    fn Fill(&mut self, red: u8, green: u8, blue: u8) {
+        self.led_canvas_mut()
+            .fill(&graphics::Color(red, green, blue));
+    }
+
+    /// Draws `image` with its top left corner at (`offset_x`, `offset_y`).
+    #[cfg(feature = "image")]
+    fn SetImage(&mut self, image: &LedImage, offset_x: i32, offset_y: i32) {
+        self.led_canvas_mut().draw_image(image, offset_x, offset_y);
+    }
+}
+
+impl Canvas for LedCanvas {
+    fn led_canvas(&self) -> &LedCanvas {
+        self
+    }
+
+    fn led_canvas_mut(&mut self) -> &mut LedCanvas {
+        self
+    }
+}
+
+/// The matrix, like `RGBMatrix` in Python.
+///
+/// Drawing on it directly draws on the canvas that's currently shown.
+pub struct RGBMatrix {
+    matrix: LedMatrix,
+    canvas: LedCanvas,
+}
+
+impl RGBMatrix {
+    /// Creates the matrix with `options` and the default runtime options.
+    ///
+    /// # Errors
+    /// If the matrix can't be created, see [`LedMatrix::new`].
+    pub fn new(options: RGBMatrixOptions) -> Result<Self, &'static str> {
+        Self::with_runtime_options(options, LedRuntimeOptions::new())
+    }
+
+    /// Creates the matrix with `options` and `runtime_options`, which Python merges into
+    /// the options.
+    ///
+    /// # Errors
+    /// If the matrix can't be created, see [`LedMatrix::new`].
+    pub fn with_runtime_options(
+        options: RGBMatrixOptions,
+        runtime_options: LedRuntimeOptions,
+    ) -> Result<Self, &'static str> {
+        let matrix = LedMatrix::new(Some(options), Some(runtime_options))?;
+        let canvas = matrix.canvas();
+        Ok(Self { matrix, canvas })
+    }
+
+    /// The underlying matrix, for everything this layer doesn't cover
+    #[must_use]
+    pub const fn matrix(&self) -> &LedMatrix {
+        &self.matrix
+    }
+
+    /// The brightness in percent
+    #[must_use]
+    pub fn brightness(&self) -> u8 {
+        self.matrix.brightness()
+    }
+
+    /// Sets the brightness in percent.
+    ///
+    /// # Errors
+    /// If `brightness` is more than 100.
+    pub fn set_brightness(&self, brightness: u8) -> Result<(), &'static str> {
+        self.matrix.set_brightness(brightness)
+    }
+
+    /// Creates a canvas to draw the next frame on.
+    #[must_use]
+    pub fn CreateFrameCanvas(&self) -> FrameCanvas {
+        self.matrix.offscreen_canvas()
+    }
+
+    /// Shows `canvas` at the next vertical sync and returns the canvas shown before, to draw
+    /// the frame after on.
+    pub fn SwapOnVSync(&mut self, canvas: FrameCanvas) -> FrameCanvas {
+        let previous = self.matrix.swap(canvas);
+        self.canvas = self.matrix.canvas();
+        previous
+    }
+}
+
+impl Canvas for RGBMatrix {
+    fn led_canvas(&self) -> &LedCanvas {
+        &self.canvas
+    }
+
+    fn led_canvas_mut(&mut self) -> &mut LedCanvas {
+        &mut self.canvas
+    }
+}
+
+/// Colors, fonts and shapes, like the `graphics` module in Python
+pub mod graphics {
+    use std::path::Path;
+
+    use super::Canvas;
+    use crate::{LedColor, LedFont, TextDrawOptions};
+
+    /// The color of `red`, `green` and `blue`
+    #[must_use]
+    pub const fn Color(red: u8, green: u8, blue: u8) -> LedColor {
+        LedColor { red, green, blue }
+    }
+
+    /// A BDF font, empty until loaded
+    #[derive(Clone, Debug, Default)]
+    pub struct Font {
+        font: Option<LedFont>,
+    }
+
+    impl Font {
+        /// Creates an empty font.
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Loads the BDF font file at `path`.
+        ///
+        /// # Errors
+        /// If the file can't be read or isn't a valid BDF font.
+        pub fn LoadFont(&mut self, path: &str) -> Result<(), &'static str> {
+            self.font = Some(LedFont::new(Path::new(path))?);
+            Ok(())
+        }
+
+        /// The height of a line, or -1 if no font is loaded
+        #[must_use]
+        pub fn height(&self) -> i32 {
+            self.font
+                .as_ref()
+                .and_then(|font| font.height().ok())
+                .unwrap_or(-1)
+        }
+
+        /// The distance from the top of a line to the baseline
+        #[must_use]
+        pub fn baseline(&self) -> i32 {
+            self.font.as_ref().map_or(0, LedFont::baseline)
+        }
+
+        /// The distance the pen advances after drawing `c`
+        #[must_use]
+        pub fn CharacterWidth(&self, c: char) -> i32 {
+            self.font.as_ref().map_or(0, |font| font.char_width(c))
+        }
+    }
+
+    /// Draws `text` starting at the baseline position (`x`, `y`), returning its width. An
+    /// empty font draws nothing.
+    pub fn DrawText(
+        canvas: &mut impl Canvas,
+        font: &Font,
+        x: i32,
+        y: i32,
+        color: LedColor,
+        text: &str,
+    ) -> i32 {
+        let Some(font) = &font.font else {
+            return 0;
+        };
+        let options = TextDrawOptions::new().position(x, y).color(&color);
+        canvas.led_canvas_mut().draw_text(font, text, &options)
+    }
+
+    /// Draws a circle around (`x`, `y`).
+    pub fn DrawCircle(canvas: &mut impl Canvas, x: i32, y: i32, radius: u32, color: LedColor) {
+        canvas.led_canvas_mut().draw_circle(x, y, radius, &color);
+    }
+
+    /// Draws a line from (`x0`, `y0`) to (`x1`, `y1`).
+    pub fn DrawLine(canvas: &mut impl Canvas, x0: i32, y0: i32, x1: i32, y1: i32, color: LedColor) {
+        canvas.led_canvas_mut().draw_line(x0, y0, x1, y1, &color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::graphics::{self, Font};
+
+    #[test]
+    fn fonts() {
+        let mut font = Font::new();
+        assert_eq!((font.height(), font.CharacterWidth('A')), (-1, 0));
+        assert!(font.LoadFont("/nonexistent/font.bdf").is_err());
+        let path = std::env::temp_dir().join(format!("python-like-{}.bdf", std::process::id()));
+        std::fs::write(&path, crate::font::test::TINY_BDF).unwrap();
+        font.LoadFont(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((font.height(), font.baseline()), (6, 5));
+        assert_eq!(font.CharacterWidth('A'), 4);
+        let color = graphics::Color(1, 2, 3);
+        assert_eq!((color.red, color.green, color.blue), (1, 2, 3));
+    }
+}
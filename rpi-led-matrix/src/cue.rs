@@ -0,0 +1,225 @@
+use std::time::{Duration, Instant};
+
+use crate::{LedCanvas, LedColor, LedMatrix, Priority, SceneId, SceneManager};
+
+/// How far an external clock may be off before [`CueList::chase`] follows it, two frames at
+/// 25 frames per second
+const CHASE_TOLERANCE: Duration = Duration::from_millis(80);
+
+/// What a [`CueList`] does when a cue fires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CueAction {
+    /// Continues the rotation with a scene, see [`SceneManager::switch_to`]
+    Scene(SceneId),
+    /// Fills the panel with a color for a while, in front of the rotation
+    Flash(LedColor, Duration),
+    /// Sets the brightness in percent
+    Brightness(u8),
+}
+
+impl CueAction {
+    /// Carries the action out on `matrix` and its `scenes`.
+    pub fn apply(&self, matrix: &LedMatrix, scenes: &mut SceneManager) {
+        match *self {
+            Self::Scene(id) => {
+                scenes.switch_to(id);
+            }
+            Self::Flash(color, duration) => {
+                let flash = move |canvas: &mut LedCanvas, _: Duration| canvas.fill(&color);
+                scenes.preempt(Priority::Alert, Some(duration), flash);
+            }
+            Self::Brightness(brightness) => {
+                let _ = matrix.set_brightness(brightness);
+            }
+        }
+    }
+}
+
+/// Actions fired at set times of a show or on named events, for shows synchronized with
+/// music or lighting.
+///
+/// The list is checked once per frame with the time of that frame, see [`due`](Self::due).
+/// A timed cue fires on the first frame at or after its time, so it's never early and at
+/// most a frame late, and an event fires on the frame after it was triggered. Cues at the
+/// same time fire in the order they were added.
+///
+/// ```no_run
+/// use rpi_led_matrix::{CueAction, CueList, LedColor, LedMatrix, SceneManager};
+/// use std::time::{Duration, Instant};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut scenes = SceneManager::new();
+/// let white = LedColor { red: 255, green: 255, blue: 255 };
+/// let mut cues = CueList::new()
+///     .at(Duration::from_millis(1500), CueAction::Flash(white, Duration::from_millis(100)))
+///     .at(Duration::from_secs(30), CueAction::Brightness(40))
+///     .on("drop", CueAction::Brightness(100));
+/// let mut canvas = matrix.offscreen_canvas();
+/// loop {
+///     let now = Instant::now();
+///     for action in cues.due(now) {
+///         action.apply(&matrix, &mut scenes);
+///     }
+///     scenes.render(&mut canvas, now);
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CueList<A = CueAction> {
+    /// Timed cues, sorted by time
+    timed: Vec<(Duration, A)>,
+    /// Index of the next timed cue to fire
+    next: usize,
+    events: Vec<(String, A)>,
+    /// Events triggered since the last frame
+    triggered: Vec<String>,
+    start: Option<Instant>,
+    length: Option<Duration>,
+}
+
+impl<A> Default for CueList<A> {
+    fn default() -> Self {
+        Self {
+            timed: Vec::new(),
+            next: 0,
+            events: Vec::new(),
+            triggered: Vec::new(),
+            start: None,
+            length: None,
+        }
+    }
+}
+
+impl<A: Clone> CueList<A> {
+    /// Creates an empty cue list, starting on the first frame it's checked.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a cue firing `action` at `time` into the show
+    #[must_use]
+    pub fn at(mut self, time: Duration, action: A) -> Self {
+        let index = self.timed.partition_point(|(cue, _)| *cue <= time);
+        self.timed.insert(index, (time, action));
+        self
+    }
+
+    /// Adds a cue firing `action` whenever `event` is [`trigger`](Self::trigger)ed
+    #[must_use]
+    pub fn on(mut self, event: &str, action: A) -> Self {
+        self.events.push((event.to_string(), action));
+        self
+    }
+
+    /// Starts the show over after `length`, firing the timed cues again.
+    #[must_use]
+    pub fn looping(mut self, length: Duration) -> Self {
+        self.length = Some(length).filter(|length| !length.is_zero());
+        self
+    }
+
+    /// Fires the cues of `event` on the next frame.
+    pub fn trigger(&mut self, event: &str) {
+        self.triggered.push(event.to_string());
+    }
+
+    /// Moves the show to `position` at `now`, e.g. the playback position reported by a
+    /// music player. Cues from `position` on fire again.
+    pub fn sync(&mut self, position: Duration, now: Instant) {
+        self.start = Some(now.checked_sub(position).unwrap_or(now));
+        self.next = self.timed.partition_point(|(time, _)| *time < position);
+    }
+
+    /// The actions due at `position` of an external clock, like a
+    /// [`TimecodeClock`](crate::TimecodeClock), in the frame shown at `now`. Use it instead of
+    /// [`due`](Self::due) to chase timecode.
+    ///
+    /// The show [`sync`](Self::sync)s to the clock when it starts or is off by more than two
+    /// frames, e.g. after a locate. In between it runs on like with `due`, so jitter of the
+    /// clock doesn't skip or repeat cues.
+    pub fn chase(&mut self, position: Duration, now: Instant) -> Vec<A> {
+        let position = match self.length {
+            Some(length) => Duration::from_nanos((position.as_nanos() % length.as_nanos()) as u64),
+            None => position,
+        };
+        if self.start.is_none() || self.position(now).abs_diff(position) > CHASE_TOLERANCE {
+            self.sync(position, now);
+        }
+        self.due(now)
+    }
+
+    /// The time into the show at `now`, zero before it started
+    #[must_use]
+    pub fn position(&self, now: Instant) -> Duration {
+        self.start
+            .map_or(Duration::ZERO, |start| now.saturating_duration_since(start))
+    }
+
+    /// The actions due in the frame shown at `now`, in order. The show starts on the first
+    /// call, unless it was [`sync`](Self::sync)ed before.
+    pub fn due(&mut self, now: Instant) -> Vec<A> {
+        let start = *self.start.get_or_insert(now);
+        let mut elapsed = now.saturating_duration_since(start);
+        let mut due = Vec::new();
+        if let Some(length) = self.length {
+            while elapsed >= length {
+                due.extend(
+                    self.timed[self.next..]
+                        .iter()
+                        .map(|(_, action)| action.clone()),
+                );
+                self.next = 0;
+                elapsed -= length;
+                self.start = Some(now - elapsed);
+            }
+        }
+        while let Some((_, action)) = self.timed.get(self.next).filter(|(t, _)| *t <= elapsed) {
+            due.push(action.clone());
+            self.next += 1;
+        }
+        for event in std::mem::take(&mut self.triggered) {
+            due.extend(
+                self.events
+                    .iter()
+                    .filter(|(name, _)| *name == event)
+                    .map(|(_, action)| action.clone()),
+            );
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut cues = CueList::new()
+            .at(ms(100), "second")
+            .at(ms(0), "first")
+            .at(ms(100), "third")
+            .on("beat", "beat")
+            .looping(ms(200));
+        assert_eq!(cues.due(start), ["first"]);
+        assert!(cues.due(start + ms(99)).is_empty());
+        cues.trigger("beat");
+        cues.trigger("unknown");
+        assert_eq!(cues.due(start + ms(100)), ["second", "third", "beat"]);
+        // a frame after the loop fires the start of the next round
+        assert_eq!(cues.due(start + ms(216)), ["first"]);
+        assert_eq!(cues.position(start + ms(216)), ms(16));
+
+        cues.sync(ms(100), start + ms(300));
+        assert_eq!(cues.due(start + ms(300)), ["second", "third"]);
+        assert!(cues.due(start + ms(350)).is_empty());
+
+        // small differences to the clock are ignored, jumps followed
+        assert_eq!(cues.chase(ms(195), start + ms(400)), ["first"]);
+        assert!(cues.chase(ms(150), start + ms(410)).is_empty());
+        assert!(cues.chase(ms(20), start + ms(420)).is_empty());
+        assert_eq!(cues.chase(ms(100), start + ms(500)), ["second", "third"]);
+    }
+}
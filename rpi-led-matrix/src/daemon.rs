@@ -0,0 +1,65 @@
+use std::io::Write;
+
+/// The number of threads of this process, or `None` if `/proc` isn't available.
+pub(crate) fn thread_count() -> Option<usize> {
+    std::fs::read_dir("/proc/self/task")
+        .ok()
+        .map(Iterator::count)
+}
+
+/// Checks that forking is safe: only the forking thread survives in the child, so any other
+/// thread, and with it e.g. locks it holds, would silently disappear.
+pub(crate) fn check_fork() -> Result<(), &'static str> {
+    match thread_count() {
+        Some(count) if count > 1 => Err("Can't daemonize with other threads running"),
+        _ => {
+            // buffered output would be written twice, by both parent and child
+            let _ = std::io::stdout().flush();
+            let _ = std::io::stderr().flush();
+            Ok(())
+        }
+    }
+}
+
+/// Moves the process into the background, as the C++ library does with
+/// [`set_daemon`](crate::LedRuntimeOptions::set_daemon), but before the matrix is created.
+///
+/// The process forks, the parent exits and the child continues in a new session, detached
+/// from the terminal, with the working directory at `/` and stdin, stdout and stderr pointing
+/// to `/dev/null`. Buffered output is flushed first, so it isn't written twice.
+///
+/// Call this first thing in `main`, before spawning threads: forking only keeps the calling
+/// thread, so it refuses to run while others are alive. Unlike the daemon option, this lets you
+/// daemonize before setting up logging, sockets or other state the matrix process needs.
+///
+/// ```no_run
+/// use rpi_led_matrix::{daemonize, LedMatrix};
+/// daemonize().unwrap();
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// ```
+///
+/// # Errors
+/// If other threads are running, or the fork fails.
+pub fn daemonize() -> Result<(), &'static str> {
+    check_fork()?;
+    if unsafe { libc::daemon(0, 0) } == 0 {
+        Ok(())
+    } else {
+        Err("Couldn't daemonize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_with_threads() {
+        let (done, wait) = std::sync::mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || wait.recv());
+        assert!(thread_count().unwrap() >= 2);
+        assert!(daemonize().is_err());
+        drop(done);
+        let _ = thread.join();
+    }
+}
@@ -0,0 +1,302 @@
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, LedColor, LedMatrix};
+
+/// The port DDP is sent to
+pub const DDP_PORT: u16 = 4048;
+
+/// Version 1 in the top bits of the flags
+const VERSION_1: u8 = 0x40;
+const FLAG_TIMECODE: u8 = 0x10;
+const FLAG_REPLY: u8 = 0x04;
+const FLAG_QUERY: u8 = 0x02;
+const FLAG_PUSH: u8 = 0x01;
+
+/// The default output device
+const ID_DISPLAY: u8 = 1;
+/// Status queries, answered with JSON
+const ID_STATUS: u8 = 251;
+/// All devices
+const ID_ALL: u8 = 255;
+
+/// Pixels of the RGBW data type, in the type bits
+const TYPE_RGBW: u8 = 3;
+/// Largest packet read from the socket, senders keep them under 1500 bytes
+const MAX_PACKET: usize = 2048;
+
+/// The color of pixels without data
+const BLACK: LedColor = LedColor {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+/// A packet of a DDP source
+#[derive(Debug, PartialEq, Eq)]
+struct Packet<'a> {
+    flags: u8,
+    sequence: u8,
+    kind: u8,
+    id: u8,
+    offset: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    fn parse(packet: &'a [u8]) -> Option<Self> {
+        let header = packet.get(..10)?;
+        let flags = header[0];
+        if flags & 0xc0 != VERSION_1 {
+            return None;
+        }
+        let start = if flags & FLAG_TIMECODE == 0 { 10 } else { 14 };
+        let length = usize::from(u16::from_be_bytes([header[8], header[9]]));
+        Some(Self {
+            flags,
+            sequence: header[1] & 15,
+            kind: header[2],
+            id: header[3],
+            offset: u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize,
+            data: packet.get(start..start + length)?,
+        })
+    }
+}
+
+/// Receives pixels over the Distributed Display Protocol (DDP), so software targeting WLED
+/// controllers like LedFx or xLights can stream to the matrix.
+///
+/// The pixels are taken row by row from the top left, three bytes each, or four for RGBW
+/// data with white mixed in. A frame is shown once its last packet with the push flag
+/// arrives. Status queries are answered, so senders can discover the matrix.
+///
+/// ```no_run
+/// use rpi_led_matrix::{Ddp, LedMatrix};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let size = matrix.canvas().canvas_size();
+/// Ddp::bind("0.0.0.0:4048", size).unwrap().run(&matrix).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Ddp {
+    socket: UdpSocket,
+    size: (i32, i32),
+    name: String,
+    timeout: Duration,
+    /// The data received, by its offset, room for all pixels as RGBW
+    data: Vec<u8>,
+    pixels: Vec<LedColor>,
+    buffer: Vec<u8>,
+}
+
+impl Ddp {
+    /// Listens for DDP on `address`, usually port [`DDP_PORT`] on all interfaces, for a
+    /// display of `size` pixels.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn bind(address: impl ToSocketAddrs, size: (i32, i32)) -> Result<Self, &'static str> {
+        let socket = UdpSocket::bind(address).map_err(|_| "Couldn't bind DDP address")?;
+        Ok(Self {
+            socket,
+            size,
+            name: String::from("LED matrix"),
+            timeout: Duration::from_millis(100),
+            data: vec![0; (size.0 * size.1).max(0) as usize * 4],
+            pixels: vec![BLACK; (size.0 * size.1).max(0) as usize],
+            buffer: vec![0; MAX_PACKET],
+        })
+    }
+
+    /// Sets the name answered to status queries, "LED matrix" by default
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Sets how long [`receive`](Self::receive) waits for a packet
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits up to the timeout for packets and handles all that arrived, returning true if a
+    /// new frame is ready to draw.
+    ///
+    /// Packets that aren't valid are ignored.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn receive(&mut self) -> Result<bool, &'static str> {
+        let mut ready = false;
+        let timeout = Some(self.timeout.max(Duration::from_millis(1)));
+        let _ = self.socket.set_read_timeout(timeout);
+        let started = Instant::now();
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((length, from)) => {
+                    let packet = std::mem::take(&mut self.buffer);
+                    ready |= self.handle(&packet[..length], from);
+                    self.buffer = packet;
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    break;
+                }
+                Err(_) => return Err("Couldn't receive DDP packet"),
+            }
+            // a frame spread over packets may still be arriving, wait for its push
+            let rest = self.timeout.saturating_sub(started.elapsed());
+            let wait = if ready {
+                Duration::from_micros(1)
+            } else {
+                rest
+            };
+            let _ = self
+                .socket
+                .set_read_timeout(Some(wait.max(Duration::from_micros(1))));
+        }
+        Ok(ready)
+    }
+
+    /// Handles a packet from `from`, returning true if a frame is ready.
+    fn handle(&mut self, data: &[u8], from: SocketAddr) -> bool {
+        let Some(packet) = Packet::parse(data) else {
+            return false;
+        };
+        if packet.flags & FLAG_REPLY != 0 {
+            return false;
+        }
+        if packet.flags & FLAG_QUERY != 0 {
+            if packet.id == ID_STATUS || packet.id == ID_ALL {
+                let _ = self.socket.send_to(&self.status(packet.sequence), from);
+            }
+            return false;
+        }
+        if packet.id != ID_DISPLAY && packet.id != ID_ALL {
+            return false;
+        }
+        let rgbw = packet.kind >> 3 & 7 == TYPE_RGBW;
+        let stride = if rgbw { 4 } else { 3 };
+        let start = packet.offset.min(self.data.len());
+        let end = start.saturating_add(packet.data.len()).min(self.data.len());
+        self.data[start..end].copy_from_slice(&packet.data[..end - start]);
+        // the data of a pixel may be split between packets, so the first pixel ending in
+        // this one can have started in the one before
+        let first = start / stride;
+        let pixels = self.data[first * stride..end].chunks_exact(stride);
+        for (pixel, bytes) in self.pixels.iter_mut().skip(first).zip(pixels) {
+            let white = if rgbw { bytes[3] } else { 0 };
+            *pixel = LedColor {
+                red: bytes[0].saturating_add(white),
+                green: bytes[1].saturating_add(white),
+                blue: bytes[2].saturating_add(white),
+            };
+        }
+        packet.flags & FLAG_PUSH != 0
+    }
+
+    /// The reply to a status query, with the JSON status WLED answers with
+    fn status(&self, sequence: u8) -> Vec<u8> {
+        let name = self.name.replace(['"', '\\'], "");
+        let json = format!(
+            r#"{{"status":{{"man":"rpi-led-matrix","mod":"{name}","ver":"{}"}}}}"#,
+            env!("CARGO_PKG_VERSION")
+        );
+        let mut packet = vec![VERSION_1 | FLAG_REPLY | FLAG_PUSH, sequence, 0, ID_STATUS];
+        packet.extend(0u32.to_be_bytes());
+        packet.extend((json.len() as u16).to_be_bytes());
+        packet.extend(json.into_bytes());
+        packet
+    }
+
+    /// Draws the received pixels.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        let width = self.size.0.max(1);
+        for (i, color) in (0..).zip(&self.pixels) {
+            canvas.set(i % width, i / width, color);
+        }
+    }
+
+    /// Drives `matrix` with the received pixels until the socket fails.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn run(mut self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        let mut canvas = matrix.offscreen_canvas();
+        loop {
+            if self.receive()? {
+                self.draw(&mut canvas);
+                canvas = matrix.swap(canvas);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Frame;
+
+    fn packet(flags: u8, kind: u8, offset: u32, data: &[u8]) -> Vec<u8> {
+        let mut packet = vec![VERSION_1 | flags, 1, kind, ID_DISPLAY];
+        packet.extend(offset.to_be_bytes());
+        packet.extend((data.len() as u16).to_be_bytes());
+        packet.extend(data);
+        packet
+    }
+
+    #[test]
+    fn receiving() {
+        let mut receiver = Ddp::bind("127.0.0.1:0", (2, 2))
+            .unwrap()
+            .name("Stage \"left\"")
+            .timeout(Duration::from_millis(200));
+        let from = SocketAddr::from(([127, 0, 0, 1], DDP_PORT));
+        // a frame in two packets, split in the middle of the second pixel
+        assert!(!receiver.handle(&packet(0, 0x0b, 0, &[1, 1, 1, 2]), from));
+        assert!(receiver.handle(&packet(FLAG_PUSH, 0x0b, 4, &[2, 2, 3, 3, 3]), from));
+        // RGBW with white mixed in, from the third pixel on
+        let rgbw = packet(FLAG_PUSH | FLAG_TIMECODE, 0x1b, 8, &[0; 0]);
+        assert!(Packet::parse(&rgbw).is_none());
+        let mut rgbw = packet(FLAG_PUSH, 0x1b, 8, &[10, 0, 0, 250]);
+        assert!(receiver.handle(&rgbw, from));
+        rgbw[3] = 2;
+        assert!(!receiver.handle(&rgbw, from));
+
+        let mut pixels = vec![BLACK; 4];
+        receiver.draw(&mut Frame::new(&mut pixels, (2, 2)));
+        let gray = |level| LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        };
+        let rgbw = LedColor {
+            red: 255,
+            green: 250,
+            blue: 250,
+        };
+        assert_eq!(pixels, [gray(1), gray(2), rgbw, BLACK]);
+
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut query = packet(FLAG_QUERY, 0, 0, &[]);
+        query[3] = ID_STATUS;
+        client
+            .send_to(&query, receiver.socket.local_addr().unwrap())
+            .unwrap();
+        assert!(!receiver.receive().unwrap());
+        let mut buffer = [0; MAX_PACKET];
+        let length = client.recv(&mut buffer).unwrap();
+        let reply = Packet::parse(&buffer[..length]).unwrap();
+        assert_eq!(
+            (reply.flags & FLAG_REPLY, reply.id),
+            (FLAG_REPLY, ID_STATUS)
+        );
+        let json = std::str::from_utf8(reply.data).unwrap();
+        assert!(json.contains(r#""mod":"Stage left""#), "{json}");
+    }
+}
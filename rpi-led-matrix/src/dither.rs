@@ -0,0 +1,78 @@
+use crate::LedColor;
+
+/// Temporal dithering: the error of quantizing a pixel is carried over to the next frame, so
+/// that levels between the ones the panel can show appear on average.
+#[derive(Clone, Debug)]
+pub(crate) struct TemporalDither {
+    /// Distance between the levels the panel can show
+    step: i16,
+    /// Error carried over per pixel and channel
+    errors: Vec<[i16; 3]>,
+}
+
+impl TemporalDither {
+    /// Creates the dithering for a panel showing `bits` levels per channel.
+    pub(crate) fn new(bits: u8) -> Self {
+        Self {
+            step: 1 << (8 - bits.clamp(1, 8)),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Quantizes the color of the pixel at `index` for this frame.
+    pub(crate) fn apply(&mut self, index: usize, color: LedColor) -> LedColor {
+        if self.step == 1 {
+            return color;
+        }
+        if index >= self.errors.len() {
+            self.errors.resize(index + 1, [0; 3]);
+        }
+        let step = self.step;
+        let errors = &mut self.errors[index];
+        let mut quantize = |channel: usize, value: u8| {
+            let wanted = i16::from(value) + errors[channel];
+            let shown = (wanted.div_euclid(step) * step).clamp(0, 255 / step * step);
+            // full intensity can't be reached by steps, show it as is
+            let shown = if value == 255 { 255 } else { shown };
+            errors[channel] = (wanted - shown).clamp(-step, step);
+            shown as u8
+        };
+        LedColor {
+            red: quantize(0, color.red),
+            green: quantize(1, color.green),
+            blue: quantize(2, color.blue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_over_frames() {
+        let mut dither = TemporalDither::new(4);
+        let color = LedColor {
+            red: 20,
+            green: 0,
+            blue: 255,
+        };
+        let frames: Vec<_> = (0..16).map(|_| dither.apply(3, color)).collect();
+        assert!(frames
+            .iter()
+            .all(|c| c.red % 16 == 0 && c.green == 0 && c.blue == 255));
+        let average = frames.iter().map(|c| u32::from(c.red)).sum::<u32>() / 16;
+        assert_eq!(average, 20);
+    }
+
+    #[test]
+    fn full_depth_is_untouched() {
+        let mut dither = TemporalDither::new(8);
+        let color = LedColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+        };
+        assert_eq!(dither.apply(0, color), color);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::LedColor;
+
+/// Channels in a DMX universe
+const UNIVERSE_CHANNELS: u16 = 512;
+
+/// Maps the DMX channels of universes received by [`ArtNet`](crate::ArtNet) or
+/// [`Sacn`](crate::Sacn) to the pixels of the matrix.
+///
+/// Every pixel takes three channels, red, green and blue, numbered in patch order row by row
+/// from the top left. By default consecutive universes hold up to 170 pixels each, like show
+/// software patches pixel matrices. [`patch`](Self::patch) maps universes one by one instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DmxLayout {
+    size: (i32, i32),
+    universe: u16,
+    channel: u16,
+    pixels_per_universe: u16,
+    serpentine: bool,
+    patches: Vec<(u16, u32, u16)>,
+}
+
+impl DmxLayout {
+    /// Creates a layout for a display of `size` pixels, starting at universe 0.
+    #[must_use]
+    pub const fn new(size: (i32, i32)) -> Self {
+        Self {
+            size,
+            universe: 0,
+            channel: 0,
+            pixels_per_universe: UNIVERSE_CHANNELS / 3,
+            serpentine: false,
+            patches: Vec::new(),
+        }
+    }
+
+    /// Sets the first universe. Art-Net calls it port-address, 15 bits made of net, sub-net
+    /// and universe, sACN universes start at 1.
+    #[must_use]
+    pub const fn universe(mut self, universe: u16) -> Self {
+        self.universe = universe;
+        self
+    }
+
+    /// Sets the DMX channel of the first pixel in every universe, from 1 to 512, 1 by default.
+    #[must_use]
+    pub const fn start_channel(mut self, channel: u16) -> Self {
+        self.channel = if channel == 0 {
+            0
+        } else if channel > UNIVERSE_CHANNELS {
+            UNIVERSE_CHANNELS - 1
+        } else {
+            channel - 1
+        };
+        self
+    }
+
+    /// Sets how many pixels are in a universe, fewer when a console patches whole fixtures
+    /// into universes. At most and by default as many as fit from the start channel.
+    #[must_use]
+    pub const fn pixels_per_universe(mut self, pixels: u16) -> Self {
+        self.pixels_per_universe = pixels;
+        self
+    }
+
+    /// Runs every other row from right to left, like pixels patched as a zigzag.
+    #[must_use]
+    pub const fn serpentine(mut self, serpentine: bool) -> Self {
+        self.serpentine = serpentine;
+        self
+    }
+
+    /// Maps `pixels` pixels of `universe` to the pixels from `first_pixel` on in patch order.
+    /// Once a universe is patched, only patched universes are received.
+    #[must_use]
+    pub fn patch(mut self, universe: u16, first_pixel: u32, pixels: u16) -> Self {
+        self.patches.retain(|&(patched, ..)| patched != universe);
+        self.patches.push((universe, first_pixel, pixels));
+        self
+    }
+
+    /// The size of the display
+    pub(crate) const fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// The number of pixels that fit a universe from the start channel
+    fn fit(&self) -> u16 {
+        ((UNIVERSE_CHANNELS - self.channel) / 3).max(1)
+    }
+
+    /// The universes the layout receives, in order
+    #[must_use]
+    pub fn universes(&self) -> Vec<u16> {
+        if !self.patches.is_empty() {
+            let mut universes: Vec<_> = self
+                .patches
+                .iter()
+                .map(|&(universe, ..)| universe)
+                .collect();
+            universes.sort_unstable();
+            return universes;
+        }
+        let pixels = (self.size.0 * self.size.1).max(0) as u32;
+        let per_universe = self.pixels_per_universe.clamp(1, self.fit());
+        let count = pixels.div_ceil(u32::from(per_universe));
+        (0..count)
+            .map_while(|index| u16::try_from(index).ok()?.checked_add(self.universe))
+            .collect()
+    }
+
+    /// The first pixel and number of pixels of `universe`
+    fn range(&self, universe: u16) -> Option<(u32, u16)> {
+        if self.patches.is_empty() {
+            let per_universe = self.pixels_per_universe.clamp(1, self.fit());
+            let index = universe.checked_sub(self.universe)?;
+            let first = u32::from(index) * u32::from(per_universe);
+            let pixels = (self.size.0 * self.size.1).max(0) as u32;
+            (first < pixels).then_some((first, per_universe))
+        } else {
+            let &(_, first, pixels) = self
+                .patches
+                .iter()
+                .find(|&&(patched, ..)| patched == universe)?;
+            Some((first, pixels.min(self.fit())))
+        }
+    }
+
+    /// The position of the pixel numbered `pixel` in patch order.
+    fn position(&self, pixel: i32) -> (i32, i32) {
+        let width = self.size.0.max(1);
+        let (x, y) = (pixel % width, pixel / width);
+        if self.serpentine && y % 2 == 1 {
+            (width - 1 - x, y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Copies the `channels` of `universe` to `pixels`, row by row, returning true if it's a
+    /// universe of the layout.
+    pub(crate) fn apply(&self, universe: u16, channels: &[u8], pixels: &mut [LedColor]) -> bool {
+        let Some((first, count)) = self.range(universe) else {
+            return false;
+        };
+        let (width, height) = self.size;
+        let start = usize::from(self.channel);
+        for pixel in 0..count {
+            let offset = start + usize::from(pixel) * 3;
+            let Some(rgb) = channels.get(offset..offset + 3) else {
+                break;
+            };
+            let number = first + u32::from(pixel);
+            if number >= (width * height).max(0) as u32 {
+                break;
+            }
+            let (x, y) = self.position(number as i32);
+            pixels[(y * width + x) as usize] = LedColor {
+                red: rgb[0],
+                green: rgb[1],
+                blue: rgb[2],
+            };
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping() {
+        let layout = DmxLayout::new((3, 2))
+            .universe(15)
+            .start_channel(4)
+            .pixels_per_universe(4)
+            .serpentine(true);
+        assert_eq!(layout.universes(), [15, 16]);
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let gray = |level| LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        };
+        let mut pixels = vec![black; 6];
+        let channels = [[0; 3], [1; 3], [2; 3], [3; 3], [4; 3]].concat();
+        assert!(layout.apply(15, &channels, &mut pixels));
+        assert!(layout.apply(16, &[9; 12], &mut pixels));
+        assert!(!layout.apply(17, &[9; 12], &mut pixels));
+        assert_eq!(
+            pixels,
+            [gray(1), gray(2), gray(3), gray(9), gray(9), gray(4)]
+        );
+
+        let patched = DmxLayout::new((3, 2)).patch(7, 3, 2).patch(2, 0, 1);
+        assert_eq!(patched.universes(), [2, 7]);
+        assert!(!patched.apply(0, &[5; 6], &mut pixels));
+        assert!(patched.apply(7, &[5; 9], &mut pixels));
+        assert_eq!(pixels[3..], [gray(5), gray(5), gray(4)]);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::canvas::TextRenderer;
+use crate::{Canvas, Frame, LedCanvas, LedColor, LedFont, SubCanvas, TextDrawOptions};
+
+/// Text drawing with the fonts of this crate, on every [`Canvas`].
+///
+/// Widgets showing text take any canvas with this trait, so they draw onto a [`LedCanvas`],
+/// a [`SubCanvas`], a [`Frame`], a [`View`](crate::View) of any of them or a canvas of your
+/// own the same way.
+///
+/// ```
+/// use rpi_led_matrix::{LedColor, LedFont, LedMatrix, TextCanvas, TextDrawOptions};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let font = LedFont::new(Path::new("/usr/share/fonts/misc/5x7.bdf")).unwrap();
+/// // a label on top of every frame shown
+/// matrix.add_pre_swap(move |frame| {
+///     let red = LedColor { red: 255, green: 0, blue: 0 };
+///     frame.draw_text(&font, "REC", &TextDrawOptions::new().position(1, 7).color(&red));
+/// });
+/// ```
+pub trait TextCanvas: Canvas {
+    /// Renders text with the given font, returning the distance the pen advanced, as
+    /// [`LedCanvas::draw_text`] does.
+    fn draw_text(&mut self, font: &LedFont, text: &str, options: &TextDrawOptions) -> i32;
+}
+
+impl<C: Canvas + ?Sized> TextCanvas for C {
+    fn draw_text(&mut self, font: &LedFont, text: &str, options: &TextDrawOptions) -> i32 {
+        TextRenderer {
+            canvas: self,
+            glyphs: None,
+        }
+        .draw_text(font, text, options)
+    }
+}
+
+impl Canvas for LedCanvas {
+    fn canvas_size(&self) -> (i32, i32) {
+        Self::canvas_size(self)
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        Self::get(self, x, y)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        Self::set(self, x, y, color);
+    }
+
+    fn set_iter(&mut self, pixels: impl IntoIterator<Item = (i32, i32, LedColor)>) {
+        Self::set_iter(self, pixels);
+    }
+
+    fn shade(&mut self, shader: impl FnMut(i32, i32) -> LedColor) {
+        Self::shade(self, shader);
+    }
+
+    fn clear(&mut self) {
+        Self::clear(self);
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        Self::fill(self, color);
+    }
+
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        Self::draw_line_aa(self, x0, y0, x1, y1, color);
+    }
+
+    fn draw_circle_aa(&mut self, x: f32, y: f32, radius: f32, color: &LedColor) {
+        Self::draw_circle_aa(self, x, y, radius, color);
+    }
+}
+
+impl Canvas for SubCanvas<'_> {
+    fn canvas_size(&self) -> (i32, i32) {
+        LedCanvas::canvas_size(self)
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        LedCanvas::get(self, x, y)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        LedCanvas::set(self, x, y, color);
+    }
+
+    fn set_iter(&mut self, pixels: impl IntoIterator<Item = (i32, i32, LedColor)>) {
+        LedCanvas::set_iter(self, pixels);
+    }
+
+    fn shade(&mut self, shader: impl FnMut(i32, i32) -> LedColor) {
+        LedCanvas::shade(self, shader);
+    }
+
+    fn clear(&mut self) {
+        LedCanvas::clear(self);
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        LedCanvas::fill(self, color);
+    }
+
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        LedCanvas::draw_line_aa(self, x0, y0, x1, y1, color);
+    }
+
+    fn draw_circle_aa(&mut self, x: f32, y: f32, radius: f32, color: &LedColor) {
+        LedCanvas::draw_circle_aa(self, x, y, radius, color);
+    }
+}
+
+impl Canvas for Frame<'_> {
+    fn canvas_size(&self) -> (i32, i32) {
+        self.size()
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        Self::get(self, x, y)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        Self::set(self, x, y, *color);
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        self.pixels_mut().fill(*color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame() {
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let mut pixels = vec![white; 16];
+        let mut frame = Frame::new(&mut pixels, (4, 4));
+        Canvas::clear(&mut frame);
+        Canvas::draw_line(&mut frame, 0, 0, 3, 3, &white);
+        frame.fill_polygon(&[(0, 3), (1, 3), (1, 2)], &white);
+        assert_eq!(Canvas::canvas_size(&frame), (4, 4));
+        let lit: Vec<_> = (0..16).filter(|&i| pixels[i] == white).collect();
+        assert_eq!(lit, [0, 5, 9, 10, 12, 13, 15]);
+        assert_eq!(pixels[1], black);
+    }
+
+    #[test]
+    fn text() {
+        let font = LedFont::new_from_buffer(crate::font::test::TINY_BDF.as_bytes()).unwrap();
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        let options = TextDrawOptions::new()
+            .position(1, 6)
+            .color(&white)
+            .outline(&red, 1);
+        let mut canvas = LedCanvas::headless(8, 8);
+        assert_eq!(canvas.draw_text(&font, "AA", &options), 8);
+        // the same text drawn into a view of a frame, one pixel further right
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let mut pixels = vec![black; 9 * 8];
+        let mut frame = Frame::new(&mut pixels, (9, 8));
+        assert_eq!(frame.view(1, 0, 8, 8).draw_text(&font, "AA", &options), 8);
+        for (y, row) in pixels.chunks_exact(9).enumerate() {
+            for (x, pixel) in (0..).zip(row.iter().skip(1)) {
+                assert_eq!(canvas.get(x, y as i32).as_ref(), Some(pixel));
+            }
+        }
+        assert!(pixels.contains(&red));
+    }
+}
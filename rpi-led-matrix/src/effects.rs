@@ -0,0 +1,36 @@
+//! Animated full-canvas effects for demos, burn-in tests and screensavers.
+//!
+//! The effects of [`rpi_led_matrix_core::effects`], drawing on any [`Canvas`](crate::Canvas).
+//! Every effect keeps its own state and draws the next frame over the whole canvas, or the
+//! active viewport, with `step`. They also implement [`Scene`], so they can be put into the
+//! rotation of a [`SceneManager`](crate::SceneManager).
+//!
+//! ```
+//! use rpi_led_matrix::effects::Fire;
+//! use rpi_led_matrix::LedMatrix;
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let mut fire = Fire::new();
+//! for _ in 0..100 {
+//!     fire.step(&mut canvas);
+//!     canvas = matrix.swap(canvas);
+//! }
+//! ```
+use std::time::Duration;
+
+use crate::{LedCanvas, Scene};
+
+#[doc(inline)]
+pub use rpi_led_matrix_core::effects::{ColorWheel, Fire, MatrixRain, Plasma, Starfield};
+
+macro_rules! scene {
+    ($($effect:ty),*) => {$(
+        impl Scene for $effect {
+            fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+                self.step(canvas);
+            }
+        }
+    )*};
+}
+
+scene!(Plasma, Fire, MatrixRain, Starfield, ColorWheel);
@@ -0,0 +1,357 @@
+//! A server for the [Flaschen-Taschen] protocol, so the matrix can be driven by its clients
+//! and tools like `send-image` and `send-video`.
+//!
+//! Every UDP packet is a binary PPM image (`P6`), drawn with its top left corner at an offset
+//! onto one of 16 layers. The offset and layer are given as `x y z` either in a `#FT: x y z`
+//! comment in the header or in a footer after the pixel data, and are 0 if left out. Layers
+//! are stacked in order, with black as transparent on every layer but the background layer 0.
+//!
+//! [Flaschen-Taschen]: https://github.com/hzeller/flaschen-taschen/blob/master/doc/protocols.md
+use std::io::ErrorKind;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, LedColor, LedMatrix};
+
+/// The port Flaschen-Taschen clients send to by default
+pub const FLASCHEN_TASCHEN_PORT: u16 = 1337;
+
+/// Number of layers, like the reference server
+const LAYERS: usize = 16;
+
+/// Largest packet read from the socket
+const MAX_PACKET: usize = 65536;
+
+/// The color of unset pixels, transparent above the background
+const BLACK: LedColor = LedColor {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+/// An image received in a packet, with where to draw it
+#[derive(Debug, PartialEq, Eq)]
+struct Packet<'a> {
+    size: (i32, i32),
+    offset: (i32, i32),
+    layer: usize,
+    rgb: &'a [u8],
+}
+
+/// Splits PPM data into whitespace separated tokens, collecting the offset of `#FT:` comments.
+struct Tokens<'a> {
+    data: &'a [u8],
+    position: usize,
+    offset: Option<[i32; 3]>,
+}
+
+impl<'a> Tokens<'a> {
+    fn token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            while self.data.get(self.position)?.is_ascii_whitespace() {
+                self.position += 1;
+            }
+            let rest = &self.data[self.position..];
+            let end = if rest[0] == b'#' {
+                rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len())
+            } else {
+                rest.iter()
+                    .position(u8::is_ascii_whitespace)
+                    .unwrap_or(rest.len())
+            };
+            self.position += end;
+            let token = &rest[..end];
+            match token.strip_prefix(b"#FT:") {
+                Some(comment) => self.offset = numbers(comment).or(self.offset),
+                None if token[0] == b'#' => {}
+                None => return Some(token),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<i32, &'static str> {
+        let token = self.token().ok_or("Truncated PPM header")?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or("Invalid PPM header")
+    }
+}
+
+/// Parses up to three whitespace separated numbers, missing ones as 0.
+fn numbers(text: &[u8]) -> Option<[i32; 3]> {
+    let text = std::str::from_utf8(text).ok()?;
+    let mut numbers = [0; 3];
+    let mut count = 0;
+    for (number, token) in numbers.iter_mut().zip(text.split_whitespace()) {
+        *number = token.parse().ok()?;
+        count += 1;
+    }
+    (count > 0).then_some(numbers)
+}
+
+impl<'a> Packet<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, &'static str> {
+        let mut tokens = Tokens {
+            data,
+            position: 0,
+            offset: None,
+        };
+        if tokens.token() != Some(b"P6") {
+            return Err("Not a binary PPM image");
+        }
+        let (width, height) = (tokens.number()?, tokens.number()?);
+        if tokens.number()? != 255 || width <= 0 || height <= 0 {
+            return Err("Unsupported PPM image");
+        }
+        // a single whitespace separates the header from the pixels
+        let start = tokens.position + 1;
+        let end = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .and_then(|length| length.checked_add(start))
+            .ok_or("Truncated PPM image")?;
+        let rgb = data.get(start..end).ok_or("Truncated PPM image")?;
+        let offset = numbers(&data[end..]).or(tokens.offset).unwrap_or_default();
+        Ok(Self {
+            size: (width, height),
+            offset: (offset[0], offset[1]),
+            layer: offset[2].clamp(0, LAYERS as i32 - 1) as usize,
+            rgb,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Layer {
+    pixels: Vec<LedColor>,
+    updated: Instant,
+}
+
+/// Receives Flaschen-Taschen packets, making the matrix a drop-in display for existing
+/// clients, see the [module documentation](crate::flaschen_taschen).
+///
+/// ```no_run
+/// use rpi_led_matrix::flaschen_taschen::FlaschenTaschen;
+/// use rpi_led_matrix::LedMatrix;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let size = matrix.canvas().canvas_size();
+/// FlaschenTaschen::bind("0.0.0.0:1337", size).unwrap().run(&matrix).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FlaschenTaschen {
+    socket: UdpSocket,
+    size: (i32, i32),
+    timeout: Duration,
+    layer_timeout: Option<Duration>,
+    layers: Vec<Option<Layer>>,
+    buffer: Vec<u8>,
+}
+
+impl FlaschenTaschen {
+    /// Listens for packets on `address`, for a display of `size` pixels.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn bind(address: impl ToSocketAddrs, size: (i32, i32)) -> Result<Self, &'static str> {
+        let socket =
+            UdpSocket::bind(address).map_err(|_| "Couldn't bind Flaschen-Taschen address")?;
+        Ok(Self {
+            socket,
+            size,
+            timeout: Duration::from_millis(100),
+            layer_timeout: Some(Duration::from_secs(15)),
+            layers: (0..LAYERS).map(|_| None).collect(),
+            buffer: vec![0; MAX_PACKET],
+        })
+    }
+
+    /// Sets how long [`receive`](Self::receive) waits for a packet
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets after how long without packets a layer above the background is cleared, 15 seconds
+    /// by default, so overlays of clients that quit don't stay forever.
+    #[must_use]
+    pub const fn layer_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.layer_timeout = timeout;
+        self
+    }
+
+    /// Waits up to the timeout for packets and applies all that arrived, returning true if
+    /// the display changed.
+    ///
+    /// Packets that aren't valid are ignored.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn receive(&mut self) -> Result<bool, &'static str> {
+        let now = Instant::now();
+        let mut changed = self.expire(now);
+        let timeout = Some(self.timeout.max(Duration::from_millis(1)));
+        let _ = self.socket.set_read_timeout(timeout);
+        loop {
+            match self.socket.recv(&mut self.buffer) {
+                Ok(length) => {
+                    let packet = std::mem::take(&mut self.buffer);
+                    changed |= self.apply(&packet[..length], now).is_ok();
+                    self.buffer = packet;
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    break;
+                }
+                Err(_) => return Err("Couldn't receive Flaschen-Taschen packet"),
+            }
+            // take the packets already waiting, without waiting for more
+            let _ = self.socket.set_read_timeout(Some(Duration::from_micros(1)));
+        }
+        Ok(changed)
+    }
+
+    /// Clears the layers above the background that timed out, returning true if any did.
+    fn expire(&mut self, now: Instant) -> bool {
+        let Some(timeout) = self.layer_timeout else {
+            return false;
+        };
+        let mut expired = false;
+        for slot in self.layers.iter_mut().skip(1) {
+            if slot
+                .as_ref()
+                .is_some_and(|layer| now.saturating_duration_since(layer.updated) > timeout)
+            {
+                *slot = None;
+                expired = true;
+            }
+        }
+        expired
+    }
+
+    /// Draws the image of a packet onto its layer.
+    fn apply(&mut self, data: &[u8], now: Instant) -> Result<(), &'static str> {
+        let packet = Packet::parse(data)?;
+        // the far corner has to be a coordinate too, so no pixel's is out of range
+        let right = packet.offset.0.checked_add(packet.size.0);
+        let bottom = packet.offset.1.checked_add(packet.size.1);
+        if right.is_none() || bottom.is_none() {
+            return Err("Flaschen-Taschen image out of range");
+        }
+        let (width, height) = self.size;
+        let layer = self.layers[packet.layer].get_or_insert_with(|| Layer {
+            pixels: vec![BLACK; (width * height).max(0) as usize],
+            updated: now,
+        });
+        layer.updated = now;
+        for (i, rgb) in (0..).zip(packet.rgb.chunks_exact(3)) {
+            let x = packet.offset.0 + i % packet.size.0;
+            let y = packet.offset.1 + i / packet.size.0;
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                layer.pixels[(y * width + x) as usize] = LedColor {
+                    red: rgb[0],
+                    green: rgb[1],
+                    blue: rgb[2],
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the layers in order, with black as transparent above the background.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        let width = self.size.0.max(1);
+        let layers = self.layers.iter().enumerate();
+        for (index, layer) in layers.filter_map(|(index, layer)| Some((index, layer.as_ref()?))) {
+            for (i, color) in (0..).zip(&layer.pixels) {
+                if index == 0 || *color != BLACK {
+                    canvas.set(i % width, i / width, color);
+                }
+            }
+        }
+    }
+
+    /// Drives `matrix` with the received packets until the socket fails.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn run(mut self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        let mut canvas = matrix.offscreen_canvas();
+        loop {
+            if self.receive()? {
+                canvas.clear();
+                self.draw(&mut canvas);
+                canvas = matrix.swap(canvas);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Frame;
+
+    #[test]
+    fn layers() {
+        let packet = b"P6\n2 1\n#FT: 1 0 3\n255\n\x01\x02\x03\0\0\0";
+        let parsed = Packet::parse(packet).unwrap();
+        assert_eq!(
+            (parsed.size, parsed.offset, parsed.layer),
+            ((2, 1), (1, 0), 3)
+        );
+        assert_eq!(parsed.rgb, [1, 2, 3, 0, 0, 0]);
+        let footer = b"P6 1 1 255\n\xff\0\0\n2 1 20\n";
+        let parsed = Packet::parse(footer).unwrap();
+        assert_eq!((parsed.offset, parsed.layer), ((2, 1), 15));
+        assert!(Packet::parse(b"P6 2 2 255\n\0\0\0").is_err());
+        assert!(Packet::parse(b"P3 1 1 255\n0 0 0").is_err());
+        assert!(Packet::parse(b"P6 2147483647 2147483647 255\n\0\0\0").is_err());
+
+        let mut server = FlaschenTaschen::bind("127.0.0.1:0", (3, 2))
+            .unwrap()
+            .timeout(Duration::from_millis(200));
+        let address = server.socket.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut background = b"P6 3 2 255\n".to_vec();
+        background.extend([9; 18]);
+        sender.send_to(&background, address).unwrap();
+        sender.send_to(packet, address).unwrap();
+        sender.send_to(footer, address).unwrap();
+        let mut received = false;
+        for _ in 0..10 {
+            received |= server.receive().unwrap();
+            if server.layers[15].is_some() {
+                break;
+            }
+        }
+        assert!(received);
+
+        // a pixel past the largest x is dropped with its packet
+        let far = b"P6 2 1 255\n\xff\0\0\xff\0\0\n2147483647 0 0\n";
+        assert!(server.apply(far, Instant::now()).is_err());
+
+        let mut pixels = vec![BLACK; 6];
+        server.draw(&mut Frame::new(&mut pixels, (3, 2)));
+        let gray = LedColor {
+            red: 9,
+            green: 9,
+            blue: 9,
+        };
+        let layered = LedColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+        };
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        assert_eq!(pixels, [gray, layered, gray, gray, gray, red]);
+
+        server.layer_timeout = Some(Duration::ZERO);
+        assert!(server.expire(Instant::now() + Duration::from_millis(1)));
+        assert!(server.layers[0].is_some() && server.layers[3].is_none());
+    }
+}
@@ -1,53 +1,143 @@
-use std::ffi::CString;
+use std::collections::HashMap;
 use std::path::Path;
 
-use crate::ffi;
+use crate::unicode;
+
+/// Codepoint drawn in place of characters missing from a font.
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
 
 /// The Rust handle for [`LedFont`].
+///
+/// Fonts are parsed from the [BDF] format and rendered on the Rust side, so text follows
+/// the same transforms as every other draw call on a [`LedCanvas`](crate::LedCanvas).
+///
+/// [BDF]: https://en.wikipedia.org/wiki/Glyph_Bitmap_Distribution_Format
+#[derive(Clone, Debug)]
 pub struct LedFont {
-    pub(crate) handle: *mut ffi::CLedFont,
+    height: i32,
+    baseline: i32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+/// A single parsed BDF glyph.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Glyph {
+    /// Horizontal advance after drawing this glyph
+    pub(crate) device_width: i32,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) x_offset: i32,
+    pub(crate) y_offset: i32,
+    /// Row-major bitmap, each row padded to full bytes
+    bitmap: Vec<u8>,
+}
+
+impl Glyph {
+    const fn bytes_per_row(&self) -> usize {
+        (self.width as usize).div_ceil(8)
+    }
+
+    /// Returns true if the pixel at the given glyph-local coordinate is set.
+    pub(crate) fn pixel(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return false;
+        }
+        let byte = self.bitmap[y as usize * self.bytes_per_row() + x as usize / 8];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Iterates over the set pixels, relative to the pen position on the baseline.
+    pub(crate) fn pixels(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let top = -self.height - self.y_offset;
+        (0..self.height).flat_map(move |y| {
+            (0..self.width)
+                .filter(move |&x| self.pixel(x, y))
+                .map(move |x| (x + self.x_offset, y + top))
+        })
+    }
 }
 
 impl LedFont {
     /// Creates a new [`LedFont`] instance with the given bdf filepath, if it exists.
     ///
     /// # Errors
-    /// - If the given `bdf_file` path fails to convert to a string. This can
-    ///   occur when there is a null character mid way in the string.
-    /// - If the C++ library returns us a null pointer when loading the font.
+    /// - If the given `bdf_file` can't be read.
+    /// - If the file isn't a valid bdf font.
     pub fn new(bdf_file: &Path) -> Result<Self, &'static str> {
-        let string = match bdf_file.to_str() {
-            Some(s) => s,
-            None => return Err("Couldn't convert path to str"),
-        };
-        let string = if let Ok(string) = CString::new(string) {
-            string
-        } else {
-            return Err("Failed to convert path to CString");
-        };
-
-        let handle = unsafe { ffi::load_font(string.as_ptr()) };
-
-        if handle.is_null() {
-            Err("Couldn't load font")
-        } else {
-            Ok(Self { handle })
-        }
+        let buffer = std::fs::read(bdf_file).map_err(|_| "Couldn't read font file")?;
+        Self::new_from_buffer(&buffer)
     }
 
     /// Creates a new [`LedFont`] instance from a buffer containing a bdf font.
     ///
     /// # Errors
-    /// - If the C++ library returns us a null pointer when loading the font.
+    /// - If the buffer isn't a valid bdf font.
     pub fn new_from_buffer(bdf_buffer: &[u8]) -> Result<Self, &'static str> {
-        let handle = unsafe {
-            ffi::load_font_from_buffer(bdf_buffer.as_ptr(), bdf_buffer.len())
+        let text = std::str::from_utf8(bdf_buffer).map_err(|_| "Couldn't load font")?;
+        let mut font = Self {
+            height: -1,
+            baseline: 0,
+            glyphs: HashMap::new(),
         };
+        let mut glyph: Option<(Option<char>, Glyph)> = None;
+        let mut row: Option<usize> = None;
 
-        if handle.is_null() {
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let keyword = fields.next().unwrap_or_default();
+            let numbers: Vec<i32> = fields.filter_map(|f| f.parse().ok()).collect();
+
+            match (keyword, glyph.as_mut()) {
+                ("FONTBOUNDINGBOX", None) if numbers.len() == 4 => {
+                    font.height = numbers[1];
+                    font.baseline = numbers[1] + numbers[3];
+                }
+                ("STARTCHAR", None) => glyph = Some((None, Glyph::default())),
+                ("ENCODING", Some((codepoint, _))) => {
+                    *codepoint = numbers
+                        .first()
+                        .and_then(|&c| u32::try_from(c).ok())
+                        .and_then(char::from_u32);
+                }
+                ("DWIDTH", Some((_, current))) if !numbers.is_empty() => {
+                    current.device_width = numbers[0];
+                }
+                ("BBX", Some((_, current))) if numbers.len() == 4 => {
+                    current.width = numbers[0].max(0);
+                    current.height = numbers[1].max(0);
+                    current.x_offset = numbers[2];
+                    current.y_offset = numbers[3];
+                    current.bitmap = vec![0; current.bytes_per_row() * current.height as usize];
+                }
+                ("BITMAP", Some(_)) => row = Some(0),
+                ("ENDCHAR", Some(_)) => {
+                    if let Some((Some(codepoint), current)) = glyph.take() {
+                        font.glyphs.insert(codepoint, current);
+                    }
+                    row = None;
+                }
+                (hex, Some((_, current))) if row.is_some() => {
+                    let index = row.unwrap_or_default();
+                    let bytes_per_row = current.bytes_per_row();
+                    if index < current.height as usize {
+                        let start = index * bytes_per_row;
+                        for (i, chunk) in hex.as_bytes().chunks(2).take(bytes_per_row).enumerate() {
+                            current.bitmap[start + i] = std::str::from_utf8(chunk)
+                                .ok()
+                                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                                .unwrap_or_default();
+                        }
+                    }
+                    row = Some(index + 1);
+                }
+                _ => {}
+            }
+        }
+
+        if font.height < 0 || font.glyphs.is_empty() {
             Err("Couldn't load font")
         } else {
-            Ok(Self { handle })
+            Ok(font)
         }
     }
 
@@ -56,33 +146,722 @@ impl LedFont {
     /// # Errors
     /// - If the font has not been loaded.
     pub fn height(&self) -> Result<i32, &'static str> {
-        let height = unsafe { ffi::height_font(self.handle) };
-
-        if height == -1 {
+        if self.height == -1 {
             Err("Font is not loaded")
         } else {
-            Ok(height)
+            Ok(self.height)
         }
     }
 
     /// Return baseline. Pixels from the topline to the baseline.
-    pub fn baseline(&self) -> i32 {
-        return unsafe { ffi::baseline_font(self.handle) };
+    #[must_use]
+    pub const fn baseline(&self) -> i32 {
+        self.baseline
+    }
+
+    /// Pixels from the baseline to the bottom of the line, below the [`baseline`](Self::baseline).
+    #[must_use]
+    pub const fn descent(&self) -> i32 {
+        self.height - self.baseline
+    }
+
+    /// Measures the width and height of a single line of `text` without drawing it.
+    ///
+    /// The width is the distance the pen advances, as returned by
+    /// [`LedCanvas::draw_text`](crate::LedCanvas::draw_text).
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedFont;
+    /// use std::path::Path;
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/10x20.bdf")).unwrap();
+    /// assert_eq!(font.measure("Hello", 0), (50, 20));
+    /// ```
+    #[must_use]
+    pub fn measure(&self, text: &str, kerning_offset: i32) -> (i32, i32) {
+        (self.advance(text, kerning_offset), self.height)
+    }
+
+    /// Measures `text` as laid out by [`TextLayout::Wrapped`](crate::TextLayout::Wrapped),
+    /// returning the width of the widest line, the total height and the number of lines.
+    ///
+    /// The height spans from the top of the first line to the bottom of the last one, with
+    /// `leading` pixels between lines.
+    #[must_use]
+    pub fn measure_wrapped(
+        &self,
+        text: &str,
+        line_width: i32,
+        kerning_offset: i32,
+        leading: i32,
+    ) -> (i32, i32, usize) {
+        let lines = self.wrap(text, line_width, kerning_offset);
+        let width = lines
+            .iter()
+            .map(|line| self.advance(line, kerning_offset))
+            .max()
+            .unwrap_or_default();
+        let count = lines.len() as i32;
+        let height = (count * (self.height + leading) - leading).max(0);
+        (width, height, lines.len())
+    }
+
+    /// Horizontal distance the pen advances after drawing `c`.
+    ///
+    /// Characters missing from the font advance as far as the replacement character, or not
+    /// at all if the font has none.
+    #[must_use]
+    pub fn char_width(&self, c: char) -> i32 {
+        self.glyph(c).map_or(0, |g| g.device_width)
+    }
+
+    /// Iterates over the characters of `text` with their advance widths, including
+    /// `kerning_offset`, as drawn by [`LedCanvas::draw_text`](crate::LedCanvas::draw_text).
+    ///
+    /// Combining marks don't advance, as they're drawn on the character before them. With
+    /// the `unicode` feature, right-to-left text is returned shaped and in display order.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedFont;
+    /// use std::path::Path;
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/10x20.bdf")).unwrap();
+    /// // truncate to 40 pixels
+    /// let mut width = 0;
+    /// let fitting: String = font
+    ///     .advances("Hello world", 0)
+    ///     .take_while(|(_, advance)| {
+    ///         width += advance;
+    ///         width <= 40
+    ///     })
+    ///     .map(|(c, _)| c)
+    ///     .collect();
+    /// assert_eq!(fitting, "Hell");
+    /// ```
+    pub fn advances<'a>(
+        &'a self,
+        text: &'a str,
+        kerning_offset: i32,
+    ) -> impl Iterator<Item = (char, i32)> + 'a {
+        unicode::display_clusters(text)
+            .into_iter()
+            .flat_map(move |cluster| {
+                let width = self.char_width(cluster[0]) + kerning_offset;
+                (0..)
+                    .zip(cluster)
+                    .map(move |(i, c)| (c, if i == 0 { width } else { 0 }))
+            })
+    }
+
+    /// Horizontal advance of `text`, adding `kerning_offset` after every character.
+    pub(crate) fn advance(&self, text: &str, kerning_offset: i32) -> i32 {
+        self.advances(text, kerning_offset)
+            .map(|(_, width)| width)
+            .sum()
+    }
+
+    /// Splits `text` into lines no wider than `line_width`, minimizing the sum of squared gaps
+    /// at the end of all but the last line. Words wider than a line get a line of their own.
+    ///
+    /// Lines also break between CJK characters, which aren't separated by spaces, but not
+    /// before closing punctuation.
+    pub(crate) fn wrap(&self, text: &str, line_width: i32, kerning_offset: i32) -> Vec<String> {
+        // every word with whether it's separated from the one before by a space
+        let words: Vec<(&str, bool)> = text
+            .split_whitespace()
+            .flat_map(|word| {
+                split_wide(word)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, part)| (part, i == 0))
+            })
+            .collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let widths: Vec<i64> = words
+            .iter()
+            .map(|(w, _)| i64::from(self.advance(w, kerning_offset)))
+            .collect();
+        let space = i64::from(self.advance(" ", kerning_offset));
+        let line_width = i64::from(line_width);
+
+        // cost[i]: minimal cost of laying out words[i..]
+        // next[i]: index of the first word of the line after the one starting at words[i]
+        let count = words.len();
+        let mut cost = vec![0_i64; count + 1];
+        let mut next = vec![count; count + 1];
+        for i in (0..count).rev() {
+            cost[i] = i64::MAX;
+            let mut width = 0;
+            for j in i..count {
+                if j > i && words[j].1 {
+                    width += space;
+                }
+                width += widths[j];
+                if width > line_width && j > i {
+                    break;
+                }
+                let gap = (line_width - width).max(0);
+                let line_cost = if j + 1 == count { 0 } else { gap * gap };
+                let total = line_cost.saturating_add(cost[j + 1]);
+                if total < cost[i] {
+                    cost[i] = total;
+                    next[i] = j + 1;
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut i = 0;
+        while i < count {
+            let mut line = String::new();
+            for (j, (word, spaced)) in words.iter().enumerate().take(next[i]).skip(i) {
+                if j > i && *spaced {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+            lines.push(line);
+            i = next[i];
+        }
+        lines
+    }
+
+    /// Returns true if the font has a glyph of its own for `c`.
+    #[must_use]
+    pub fn contains(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    /// Creates a copy of the font holding only the given characters and the replacement
+    /// character.
+    ///
+    /// Subsetting large fonts, e.g. CJK fonts of tens of megabytes, to the characters actually
+    /// shown and [saving](Self::save) the result cuts memory use and load time.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedFont;
+    /// use std::path::Path;
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/10x20.bdf")).unwrap();
+    /// let digits = font.subset("0123456789:".chars());
+    /// digits.save(Path::new("/tmp/clock-digits.bdf")).unwrap();
+    /// ```
+    #[must_use]
+    pub fn subset(&self, chars: impl IntoIterator<Item = char>) -> Self {
+        let glyphs = chars
+            .into_iter()
+            .chain([REPLACEMENT_CHARACTER])
+            .filter_map(|c| Some((c, self.glyphs.get(&c)?.clone())))
+            .collect();
+        Self {
+            height: self.height,
+            baseline: self.baseline,
+            glyphs,
+        }
+    }
+
+    /// Serializes the font into the BDF format, with the glyphs ordered by codepoint.
+    #[must_use]
+    pub fn to_bdf(&self) -> String {
+        let mut glyphs: Vec<_> = self.glyphs.iter().collect();
+        glyphs.sort_unstable_by_key(|(&c, _)| c);
+        let width = glyphs
+            .iter()
+            .map(|(_, g)| g.width)
+            .max()
+            .unwrap_or_default();
+        let mut bdf = format!(
+            "STARTFONT 2.1\nFONT rpi-led-matrix\nSIZE {height} 75 75\n\
+             FONTBOUNDINGBOX {width} {height} 0 {descent}\nCHARS {count}\n",
+            height = self.height,
+            descent = self.baseline - self.height,
+            count = glyphs.len(),
+        );
+        for (&c, glyph) in glyphs {
+            let code = u32::from(c);
+            bdf += &format!(
+                "STARTCHAR U+{code:04X}\nENCODING {code}\nDWIDTH {} 0\nBBX {} {} {} {}\nBITMAP\n",
+                glyph.device_width, glyph.width, glyph.height, glyph.x_offset, glyph.y_offset,
+            );
+            for row in glyph.bitmap.chunks(glyph.bytes_per_row().max(1)) {
+                bdf.extend(row.iter().map(|byte| format!("{byte:02X}")));
+                bdf.push('\n');
+            }
+            bdf += "ENDCHAR\n";
+        }
+        bdf + "ENDFONT\n"
+    }
+
+    /// Writes the font to a BDF file, see [`to_bdf`](Self::to_bdf).
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    pub fn save(&self, bdf_file: &Path) -> Result<(), &'static str> {
+        std::fs::write(bdf_file, self.to_bdf()).map_err(|_| "Couldn't write font file")
+    }
+
+    /// Splits `text` into lines as drawn by [`TextLayout::WrappedWords`], e.g. to count them.
+    ///
+    /// Lines are filled greedily and only break at whitespace. Hyphenated words are split
+    /// where the most characters fit, keeping at least two characters on either side. Words
+    /// that are still too wide get a line of their own.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedFont;
+    /// use std::path::Path;
+    /// let font = LedFont::new(Path::new("/usr/share/fonts/misc/6x13.bdf")).unwrap();
+    /// let lines = font.wrap_words("Extraordinary claims", 60, 0, true);
+    /// assert_eq!(lines, ["Extraordi-", "nary", "claims"]);
+    /// ```
+    ///
+    /// [`TextLayout::WrappedWords`]: crate::TextLayout::WrappedWords
+    #[must_use]
+    pub fn wrap_words(
+        &self,
+        text: &str,
+        line_width: i32,
+        kerning_offset: i32,
+        hyphenate: bool,
+    ) -> Vec<String> {
+        let fits = |line: &str| self.advance(line, kerning_offset) <= line_width;
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for mut word in text.split_whitespace() {
+            loop {
+                let start = if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("{line} ")
+                };
+                if fits(&format!("{start}{word}")) {
+                    line = start + word;
+                    break;
+                }
+                let split = hyphenate
+                    .then(|| {
+                        let splits: Vec<usize> = word.char_indices().map(|(i, _)| i).collect();
+                        let count = splits.len();
+                        splits
+                            .into_iter()
+                            .take(count.saturating_sub(1))
+                            .skip(2)
+                            .rev()
+                            .find(|&i| fits(&format!("{start}{}-", &word[..i])))
+                    })
+                    .flatten();
+                if let Some(i) = split {
+                    lines.push(format!("{start}{}-", &word[..i]));
+                    line.clear();
+                    word = &word[i..];
+                } else if line.is_empty() {
+                    line = word.to_string();
+                    break;
+                } else {
+                    lines.push(std::mem::take(&mut line));
+                }
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Looks up the glyph for the given character, falling back to the replacement character.
+    pub(crate) fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&REPLACEMENT_CHARACTER))
     }
 }
 
-impl Drop for LedFont {
-    fn drop(&mut self) {
-        unsafe { ffi::delete_font(self.handle) }
+/// Returns true for characters usually drawn twice as wide as Latin ones, e.g. CJK ideographs,
+/// kana and hangul.
+fn is_wide(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{20000}'..='\u{3FFFD}'
+    )
+}
+
+/// Returns true for punctuation that must not start a line in CJK text.
+fn is_closing(c: char) -> bool {
+    "、。，．：；？！）」』】〉》〕ー…・ゝゞヽヾぁぃぅぇぉっゃゅょァィゥェォッャュョ".contains(c)
+}
+
+/// Splits a word at every wide character, keeping closing punctuation with the character
+/// before it.
+fn split_wide(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut previous_wide = false;
+    for (i, c) in word.char_indices() {
+        let wide = is_wide(c);
+        if i > start && (wide || previous_wide) && !is_closing(c) {
+            parts.push(&word[start..i]);
+            start = i;
+        }
+        previous_wide = wide;
+    }
+    parts.push(&word[start..]);
+    parts
+}
+
+/// A chain of fonts tried in order for every character.
+///
+/// Text mixing e.g. ASCII, accented Latin and symbols often needs more than one BDF font.
+/// Each character is drawn from the first font that has a glyph for it. Characters missing
+/// from all fonts fall back to the replacement character of the first font that has one.
+///
+/// The stack is drawn and measured as a single [`LedFont`]. Glyphs of all fonts share the
+/// baseline, and lines are tall enough for the highest ascent and deepest descent of all
+/// fonts, e.g. a taller CJK fallback.
+///
+/// ```
+/// use rpi_led_matrix::{FontStack, LedFont};
+/// use std::path::Path;
+/// let latin = LedFont::new(Path::new("/usr/share/fonts/misc/6x13.bdf")).unwrap();
+/// let symbols = LedFont::new(Path::new("/usr/share/fonts/misc/9x18.bdf")).unwrap();
+/// let stack = FontStack::new(latin).fallback(symbols);
+/// assert!(stack.font_for('é').contains('é'));
+/// let font: LedFont = stack.into();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FontStack {
+    fonts: Vec<LedFont>,
+}
+
+impl FontStack {
+    /// Creates a stack of just the primary font.
+    #[must_use]
+    pub fn new(primary: LedFont) -> Self {
+        Self {
+            fonts: vec![primary],
+        }
+    }
+
+    /// Adds a font tried after all fonts added before
+    #[must_use]
+    pub fn fallback(mut self, font: LedFont) -> Self {
+        self.fonts.push(font);
+        self
+    }
+
+    /// The font `c` is drawn from: the first one containing it, or the primary font.
+    #[must_use]
+    pub fn font_for(&self, c: char) -> &LedFont {
+        self.fonts
+            .iter()
+            .find(|font| font.contains(c))
+            .unwrap_or(&self.fonts[0])
+    }
+
+    /// Merges the fonts into a single [`LedFont`] for drawing and measuring.
+    #[must_use]
+    pub fn to_font(&self) -> LedFont {
+        let ascent = self.fonts.iter().map(|f| f.baseline).max();
+        let descent = self.fonts.iter().map(|f| f.height - f.baseline).max();
+        let mut glyphs = HashMap::new();
+        for font in &self.fonts {
+            for (&c, glyph) in &font.glyphs {
+                glyphs.entry(c).or_insert_with(|| glyph.clone());
+            }
+        }
+        let ascent = ascent.unwrap_or_default();
+        LedFont {
+            height: ascent + descent.unwrap_or_default(),
+            baseline: ascent,
+            glyphs,
+        }
+    }
+}
+
+impl From<FontStack> for LedFont {
+    fn from(stack: FontStack) -> Self {
+        stack.to_font()
+    }
+}
+
+#[cfg(feature = "ttf")]
+impl LedFont {
+    /// Characters rasterized by [`from_ttf`](Self::from_ttf): Basic Latin and Latin-1
+    const LATIN: [std::ops::RangeInclusive<char>; 2] = [' '..='~', '\u{a0}'..='\u{ff}'];
+
+    /// Loads a TrueType or OpenType font, rasterized at `size` pixels per em.
+    ///
+    /// Glyphs are rasterized once into a cache when loading, for the Basic Latin and Latin-1
+    /// characters. Use [`from_ttf_chars`](Self::from_ttf_chars) for other characters. Pixels
+    /// at least half covered by the outline are set, giving the same crisp look as BDF fonts.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedFont;
+    /// let data = std::fs::read("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf").unwrap();
+    /// let font = LedFont::from_ttf(&data, 12.0).unwrap();
+    /// assert_eq!(font.height(), Ok(15));
+    /// ```
+    ///
+    /// # Errors
+    /// - If the data isn't a valid font.
+    /// - If the font has no horizontal layout.
+    pub fn from_ttf(data: &[u8], size: f32) -> Result<Self, &'static str> {
+        Self::from_ttf_chars(data, size, Self::LATIN.into_iter().flatten())
+    }
+
+    /// Loads a TrueType or OpenType font, rasterizing only the given characters at `size`
+    /// pixels per em, e.g. the characters of the texts to show in a font with many glyphs.
+    ///
+    /// Characters missing from the font are drawn as its replacement glyph.
+    ///
+    /// # Errors
+    /// - If the data isn't a valid font.
+    /// - If the font has no horizontal layout.
+    pub fn from_ttf_chars(
+        data: &[u8],
+        size: f32,
+        chars: impl IntoIterator<Item = char>,
+    ) -> Result<Self, &'static str> {
+        let outlines = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+            .map_err(|_| "Couldn't load font")?;
+        let metrics = outlines
+            .horizontal_line_metrics(size)
+            .ok_or("Font has no horizontal metrics")?;
+        let ascent = metrics.ascent.ceil() as i32;
+        let mut font = Self {
+            height: ascent - metrics.descent.floor() as i32,
+            baseline: ascent,
+            glyphs: HashMap::new(),
+        };
+        for c in chars {
+            // index 0 is the font's own replacement glyph, which is added once below
+            let index = outlines.lookup_glyph_index(c);
+            if index != 0 {
+                font.glyphs
+                    .insert(c, Glyph::rasterize(&outlines, index, size));
+            }
+        }
+        let replacement = outlines.lookup_glyph_index(REPLACEMENT_CHARACTER);
+        font.glyphs.insert(
+            REPLACEMENT_CHARACTER,
+            Glyph::rasterize(&outlines, replacement, size),
+        );
+        Ok(font)
+    }
+}
+
+#[cfg(feature = "ttf")]
+impl Glyph {
+    /// Rasterizes a glyph of an outline font, setting pixels covered at least by half.
+    fn rasterize(font: &fontdue::Font, index: u16, size: f32) -> Self {
+        let (metrics, coverage) = font.rasterize_indexed(index, size);
+        let mut glyph = Self {
+            device_width: metrics.advance_width.round() as i32,
+            width: metrics.width as i32,
+            height: metrics.height as i32,
+            x_offset: metrics.xmin,
+            y_offset: metrics.ymin,
+            bitmap: Vec::new(),
+        };
+        let bytes_per_row = glyph.bytes_per_row();
+        glyph.bitmap = vec![0; bytes_per_row * metrics.height];
+        if metrics.width > 0 {
+            for (y, row) in coverage.chunks(metrics.width).enumerate() {
+                for (x, _) in row.iter().enumerate().filter(|(_, &c)| c >= 128) {
+                    glyph.bitmap[y * bytes_per_row + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        glyph
     }
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
     use crate::{LedColor, LedMatrix, TextDrawOptions};
     use std::{thread, time};
 
+    pub(crate) const TINY_BDF: &str = "STARTFONT 2.1
+FONT tiny
+SIZE 5 75 75
+FONTBOUNDINGBOX 4 6 0 -1
+CHARS 3
+STARTCHAR space
+ENCODING 32
+DWIDTH 4 0
+BBX 1 1 0 0
+BITMAP
+00
+ENDCHAR
+STARTCHAR A
+ENCODING 65
+DWIDTH 4 0
+BBX 3 5 0 0
+BITMAP
+40
+A0
+E0
+A0
+A0
+ENDCHAR
+STARTCHAR replacement
+ENCODING 65533
+DWIDTH 4 0
+BBX 3 3 0 1
+BITMAP
+E0
+E0
+E0
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn parse_bdf() {
+        let font = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        assert_eq!(font.height(), Ok(6));
+        assert_eq!(font.baseline(), 5);
+        assert_eq!(font.descent(), 1);
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!(glyph.device_width, 4);
+        assert!(glyph.pixel(1, 0));
+        assert!(!glyph.pixel(0, 0));
+        assert_eq!(glyph.pixels().count(), 10);
+        assert_eq!(glyph.pixels().next(), Some((1, -5)));
+        assert_eq!(font.glyph('B').unwrap().pixels().count(), 9);
+    }
+
+    #[test]
+    fn wrap() {
+        let font = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        assert_eq!(font.wrap("A  A\nA", 12, 0), vec!["A A", "A"]);
+        assert_eq!(font.wrap("AAAA A", 12, 0), vec!["AAAA", "A"]);
+        assert_eq!(font.wrap("A A A", 12, 1), vec!["A", "A", "A"]);
+        assert!(font.wrap(" ", 12, 0).is_empty());
+        // CJK lines break between characters, but not before closing punctuation
+        assert_eq!(
+            font.wrap("日本語のテキスト", 12, 0),
+            ["日本語", "のテキ", "スト"]
+        );
+        assert_eq!(font.wrap("日本語。テキ", 12, 0), ["日本", "語。テ", "キ"]);
+        assert_eq!(font.wrap("A 日本", 12, 0), ["A 日", "本"]);
+        assert_eq!(split_wide("ok日本。x"), ["ok", "日", "本。", "x"]);
+    }
+
+    #[test]
+    fn wrap_words() {
+        let font = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        // every character is 4 wide
+        assert_eq!(
+            font.wrap_words("A AAAA A", 16, 0, false),
+            ["A", "AAAA", "A"]
+        );
+        assert_eq!(
+            font.wrap_words("A AAAAAA", 16, 0, true),
+            ["A", "AAA-", "AAA"]
+        );
+        assert_eq!(font.wrap_words("A AAAAAA", 24, 0, true), ["A AAA-", "AAA"]);
+        assert_eq!(
+            font.wrap_words("AAAAAAAAA", 16, 0, true),
+            ["AAA-", "AAA-", "AAA"]
+        );
+        // at least two characters on either side of a hyphen
+        assert_eq!(font.wrap_words("AA AAA", 16, 0, true), ["AA", "AAA"]);
+        assert_eq!(font.wrap_words("AAAAAA", 8, 0, true), ["AAAAAA"]);
+        assert!(font.wrap_words(" ", 16, 0, true).is_empty());
+    }
+
+    #[test]
+    fn measure() {
+        let font = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        assert_eq!(font.measure("AA", 0), (8, 6));
+        assert_eq!(font.measure("AA", 1), (10, 6));
+        assert_eq!(font.measure("", 0), (0, 6));
+        assert_eq!(font.char_width('A'), 4);
+        assert_eq!(font.char_width('B'), 4);
+        let advances: Vec<_> = font.advances("A A", 1).collect();
+        assert_eq!(advances, [('A', 5), (' ', 5), ('A', 5)]);
+        assert_eq!(font.measure_wrapped("A A AAA", 12, 0, 2), (12, 14, 2));
+        assert_eq!(font.measure_wrapped(" ", 12, 0, 2), (0, 0, 0));
+    }
+
+    #[cfg(feature = "ttf")]
+    #[test]
+    fn load_ttf() {
+        assert!(LedFont::from_ttf(TINY_BDF.as_bytes(), 12.0).is_err());
+        let Ok(data) = std::fs::read("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf") else {
+            return;
+        };
+        let font = LedFont::from_ttf_chars(&data, 12.0, "Hi".chars()).unwrap();
+        assert_eq!((font.height(), font.baseline()), (Ok(15), 12));
+        let glyph = font.glyph('H').unwrap();
+        assert_eq!(glyph.y_offset, 0);
+        assert_eq!(glyph.pixels().map(|(_, y)| y).min(), Some(-glyph.height));
+        assert!(glyph.pixels().count() > 10);
+        // missing glyphs fall back to the replacement glyph
+        assert_eq!(
+            font.glyph('x').map(|g| g.width),
+            font.glyph('\u{FFFD}').map(|g| g.width)
+        );
+    }
+
+    #[test]
+    fn font_stack() {
+        let primary = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        // a fallback drawing 'B' with the glyph of 'A'
+        let bdf = TINY_BDF.replace("ENCODING 65\n", "ENCODING 66\n");
+        let fallback = LedFont::new_from_buffer(bdf.as_bytes()).unwrap();
+        assert!(!primary.contains('B'));
+        let stack = FontStack::new(primary).fallback(fallback);
+        assert!(stack.font_for('B').contains('B'));
+        assert!(stack.font_for('A').contains('A'));
+        assert!(!stack.font_for('C').contains('B'));
+
+        let font = stack.to_font();
+        assert_eq!((font.height(), font.baseline()), (Ok(6), 5));
+        assert_eq!(font.glyph('A').unwrap().pixels().count(), 10);
+        assert_eq!(font.glyph('B').unwrap().pixels().count(), 10);
+        assert_eq!(font.glyph('C').unwrap().pixels().count(), 9);
+
+        // lines fit the deeper descent of a fallback
+        let bdf = TINY_BDF.replace("FONTBOUNDINGBOX 4 6 0 -1", "FONTBOUNDINGBOX 4 8 0 -3");
+        let deep = LedFont::new_from_buffer(bdf.as_bytes()).unwrap();
+        let font = stack.fallback(deep).to_font();
+        assert_eq!((font.height(), font.baseline()), (Ok(8), 5));
+    }
+
+    #[test]
+    fn subset_and_write() {
+        let font = LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap();
+        let subset = font.subset("AB".chars());
+        assert!(subset.contains('A') && subset.contains('\u{FFFD}'));
+        assert!(!subset.contains(' ') && !subset.contains('B'));
+
+        let written = LedFont::new_from_buffer(font.to_bdf().as_bytes()).unwrap();
+        assert_eq!(
+            (written.height(), written.baseline()),
+            (font.height(), font.baseline())
+        );
+        for c in [' ', 'A', '\u{FFFD}'] {
+            let (original, copy) = (font.glyph(c).unwrap(), written.glyph(c).unwrap());
+            assert_eq!(copy.device_width, original.device_width);
+            assert_eq!(
+                copy.pixels().collect::<Vec<_>>(),
+                original.pixels().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_invalid_bdf() {
+        assert!(LedFont::new_from_buffer(b"not a font").is_err());
+    }
+
     #[test]
     #[serial_test::serial]
     fn draw_text() {
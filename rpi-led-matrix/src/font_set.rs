@@ -0,0 +1,112 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::LedFont;
+
+/// Several sizes of a font family, loaded once and picked by the height available.
+///
+/// The fonts are kept in [`Arc`]s, so the set can be shared between widgets, e.g. in an
+/// `Arc<FontSet>`, and the picked font can be handed on to a
+/// [`Theme`](crate::widgets::Theme) without loading the file again.
+///
+/// ```
+/// use rpi_led_matrix::FontSet;
+/// let fonts = FontSet::load(
+///     ["5x7", "6x13", "9x18", "10x20"].map(|size| format!("/usr/share/fonts/misc/{size}.bdf")),
+/// )
+/// .unwrap();
+/// let title = fonts.best_for_height(16).unwrap();
+/// assert_eq!(title.height(), Ok(13));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FontSet {
+    /// Sorted by height, shortest first
+    fonts: Vec<Arc<LedFont>>,
+}
+
+impl FontSet {
+    /// Creates an empty set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { fonts: Vec::new() }
+    }
+
+    /// Loads the BDF fonts at the given paths into a set.
+    ///
+    /// # Errors
+    /// If any of the fonts can't be loaded, see [`LedFont::new`].
+    pub fn load<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, &'static str> {
+        paths.into_iter().try_fold(Self::new(), |set, path| {
+            Ok(set.with_font(Arc::new(LedFont::new(path.as_ref())?)))
+        })
+    }
+
+    /// Adds a font. Fonts of equal height are kept in the order they were added.
+    #[must_use]
+    pub fn with_font(mut self, font: Arc<LedFont>) -> Self {
+        let height = font.height().unwrap_or_default();
+        let index = self
+            .fonts
+            .partition_point(|f| f.height().unwrap_or_default() <= height);
+        self.fonts.insert(index, font);
+        self
+    }
+
+    /// The tallest font no taller than `height` pixels, or the shortest font if none fits.
+    ///
+    /// Returns `None` only if the set is empty.
+    #[must_use]
+    pub fn best_for_height(&self, height: i32) -> Option<&Arc<LedFont>> {
+        let fitting = self
+            .fonts
+            .partition_point(|f| f.height().unwrap_or_default() <= height);
+        self.fonts.get(fitting.max(1) - 1)
+    }
+
+    /// The heights of the fonts in the set, shortest first
+    pub fn heights(&self) -> impl Iterator<Item = i32> + '_ {
+        self.fonts.iter().map(|f| f.height().unwrap_or_default())
+    }
+
+    /// The number of fonts in the set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Returns true if the set holds no fonts.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::test::TINY_BDF;
+
+    fn font(height: i32) -> Arc<LedFont> {
+        let bdf = TINY_BDF.replace(
+            "FONTBOUNDINGBOX 4 6 0 -1",
+            &format!("FONTBOUNDINGBOX 4 {height} 0 -1"),
+        );
+        Arc::new(LedFont::new_from_buffer(bdf.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn best_for_height() {
+        assert!(FontSet::new().best_for_height(10).is_none());
+        let set = FontSet::new()
+            .with_font(font(13))
+            .with_font(font(6))
+            .with_font(font(20));
+        assert_eq!(set.heights().collect::<Vec<_>>(), [6, 13, 20]);
+        let best = |height| set.best_for_height(height).unwrap().height();
+        assert_eq!(best(16), Ok(13));
+        assert_eq!(best(13), Ok(13));
+        assert_eq!(best(100), Ok(20));
+        assert_eq!(best(4), Ok(6));
+        assert!(FontSet::load(["/nonexistent.bdf"]).is_err());
+    }
+}
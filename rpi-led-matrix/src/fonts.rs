@@ -0,0 +1,86 @@
+//! BDF fonts embedded into the binary, so programs don't depend on font files being installed.
+//!
+//! The fonts are parsed on first use. They are cut down to ASCII and Latin-1 to keep the crate
+//! small; load the complete fonts shipped with the C++ library with [`LedFont::new`] for other
+//! scripts, or embed the characters a program needs with [`LedFont::subset`].
+//!
+//! ```
+//! use rpi_led_matrix::{fonts, LedMatrix, TextDrawOptions};
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let options = TextDrawOptions::new().position(0, fonts::FONT_5X8.baseline());
+//! canvas.draw_text(&fonts::FONT_5X8, "Hello", &options);
+//! ```
+use std::sync::LazyLock;
+
+use crate::LedFont;
+
+macro_rules! bundled_font {
+    ($(#[$doc:meta])* $name:ident, $file:literal) => {
+        $(#[$doc])*
+        pub static $name: LazyLock<LedFont> = LazyLock::new(|| {
+            LedFont::new_from_buffer(include_bytes!(concat!("../fonts/", $file)))
+                .expect("Bundled fonts are valid")
+        });
+    };
+}
+
+bundled_font!(
+    /// 4 by 6 pixels, the smallest legible font
+    FONT_4X6,
+    "4x6.bdf"
+);
+bundled_font!(
+    /// 5 by 7 pixels
+    FONT_5X7,
+    "5x7.bdf"
+);
+bundled_font!(
+    /// 5 by 8 pixels
+    FONT_5X8,
+    "5x8.bdf"
+);
+bundled_font!(
+    /// 6 by 10 pixels
+    FONT_6X10,
+    "6x10.bdf"
+);
+bundled_font!(
+    /// 7 by 13 pixels
+    FONT_7X13,
+    "7x13.bdf"
+);
+bundled_font!(
+    /// 9 by 18 pixels
+    FONT_9X18,
+    "9x18.bdf"
+);
+bundled_font!(
+    /// 10 by 20 pixels
+    FONT_10X20,
+    "10x20.bdf"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bundled() {
+        let fonts = [
+            (&FONT_4X6, 6),
+            (&FONT_5X7, 7),
+            (&FONT_5X8, 8),
+            (&FONT_6X10, 10),
+            (&FONT_7X13, 13),
+            (&FONT_9X18, 18),
+            (&FONT_10X20, 20),
+        ];
+        for (font, height) in fonts {
+            assert_eq!(font.height(), Ok(height));
+        }
+        assert_eq!(FONT_5X8.measure("Hello", 0), (25, 8));
+        assert!(FONT_10X20.contains('é') && FONT_10X20.contains('\u{FFFD}'));
+        assert!(!FONT_10X20.contains('Ж'));
+    }
+}
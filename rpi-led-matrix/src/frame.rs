@@ -0,0 +1,107 @@
+use crate::LedColor;
+
+/// A hook run on every [`swap`](crate::LedMatrix::swap), see
+/// [`add_pre_swap`](crate::LedMatrix::add_pre_swap).
+pub(crate) type PreSwapHook = Box<dyn FnMut(&mut Frame) + Send>;
+
+/// The final frame about to be shown on the panel, handed to the hooks registered with
+/// [`LedMatrix::add_pre_swap`](crate::LedMatrix::add_pre_swap).
+///
+/// Pixels are addressed in panel coordinates, after the canvas transform, and changes only
+/// affect what's shown: the canvas keeps what the application drew.
+pub struct Frame<'a> {
+    pixels: &'a mut [LedColor],
+    width: i32,
+    height: i32,
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn new(pixels: &'a mut [LedColor], (width, height): (i32, i32)) -> Self {
+        debug_assert_eq!(pixels.len(), (width * height) as usize);
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    /// The width and height of the frame in pixels.
+    #[must_use]
+    pub const fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// The color of the pixel at (`x`, `y`), or `None` outside the frame.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Sets the pixel at (`x`, `y`), ignoring pixels outside the frame.
+    pub fn set(&mut self, x: i32, y: i32, color: LedColor) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// All pixels, row by row.
+    #[must_use]
+    pub fn pixels(&self) -> &[LedColor] {
+        self.pixels
+    }
+
+    /// All pixels, row by row, for changes to the whole frame like limiting its power draw.
+    pub fn pixels_mut(&mut self) -> &mut [LedColor] {
+        self.pixels
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y))
+            .then(|| (y * self.width + x) as usize)
+    }
+}
+
+/// Runs `hooks` in order on `frame`.
+pub(crate) fn run_hooks(hooks: &mut [PreSwapHook], frame: &mut Frame) {
+    for hook in hooks {
+        hook(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks() {
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let shadow = vec![white; 6];
+        let mut hooks: Vec<PreSwapHook> = vec![
+            Box::new(|frame: &mut Frame| {
+                for pixel in frame.pixels_mut() {
+                    pixel.red /= 2;
+                }
+            }),
+            Box::new(move |frame: &mut Frame| {
+                assert_eq!(frame.get(2, 1).map(|color| color.red), Some(127));
+                assert_eq!(frame.get(3, 0), None);
+                frame.set(2, 1, black);
+                frame.set(3, 0, black);
+            }),
+        ];
+        let mut shown = shadow.clone();
+        run_hooks(&mut hooks, &mut Frame::new(&mut shown, (3, 2)));
+        assert_eq!(shown[5], black);
+        assert_eq!(shown[0].red, 127);
+        assert_eq!(shown[3].green, 255);
+    }
+}
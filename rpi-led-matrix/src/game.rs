@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+use crate::{LedCanvas, LedMatrix};
+
+/// A game driven by a [`GameLoop`].
+pub trait Game {
+    /// Polls input devices, called once per frame before any updates.
+    fn poll_input(&mut self) {}
+
+    /// Advances the game state by one fixed time step of `step`.
+    fn update(&mut self, step: Duration);
+
+    /// Draws the current state onto the canvas.
+    ///
+    /// `alpha` is how far the time is between the last update and the next one, from 0 to 1,
+    /// to interpolate movement between updates if wanted.
+    fn render(&mut self, canvas: &mut LedCanvas, alpha: f32);
+
+    /// Returns true once the game is over, ending [`GameLoop::run`].
+    fn is_over(&self) -> bool {
+        false
+    }
+}
+
+/// Accumulates frame times into whole fixed time steps.
+#[derive(Clone, Debug)]
+struct Accumulator {
+    step: Duration,
+    max_updates: u32,
+    pending: Duration,
+}
+
+impl Accumulator {
+    /// Adds the time `elapsed` since the last frame, returning the number of updates to run and
+    /// the fraction of a step left over.
+    ///
+    /// After a long stall only `max_updates` are run and the rest of the time is dropped, so
+    /// the game slows down instead of spiraling into ever longer frames.
+    fn advance(&mut self, elapsed: Duration) -> (u32, f32) {
+        if self.step.is_zero() {
+            return (1, 0.0);
+        }
+        self.pending += elapsed;
+        let mut updates = 0;
+        while self.pending >= self.step {
+            if updates == self.max_updates {
+                self.pending = Duration::ZERO;
+                break;
+            }
+            self.pending -= self.step;
+            updates += 1;
+        }
+        (
+            updates,
+            self.pending.as_secs_f32() / self.step.as_secs_f32(),
+        )
+    }
+}
+
+/// Runs a [`Game`] with a fixed time step for updates, rendering a frame per swap.
+///
+/// Game logic runs at a fixed rate regardless of how fast frames are drawn, which keeps
+/// physics and timing predictable. Frames are swapped on vsync, so rendering follows the
+/// refresh of the panel, optionally limited to a frame rate.
+///
+/// ```no_run
+/// use rpi_led_matrix::{Game, GameLoop, LedCanvas, LedColor, LedMatrix};
+/// use std::time::Duration;
+///
+/// struct Bounce {
+///     x: f32,
+///     speed: f32,
+/// }
+///
+/// impl Game for Bounce {
+///     fn update(&mut self, step: Duration) {
+///         self.x += self.speed * step.as_secs_f32();
+///         if !(0.0..63.0).contains(&self.x) {
+///             self.speed = -self.speed;
+///         }
+///     }
+///
+///     fn render(&mut self, canvas: &mut LedCanvas, _alpha: f32) {
+///         canvas.clear();
+///         canvas.set(self.x as i32, 16, &LedColor { red: 255, green: 255, blue: 255 });
+///     }
+/// }
+///
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// GameLoop::new(60).run(&matrix, &mut Bounce { x: 0.0, speed: 20.0 });
+/// ```
+#[derive(Clone, Debug)]
+pub struct GameLoop {
+    step: Duration,
+    max_updates: u32,
+    frame_time: Option<Duration>,
+}
+
+impl GameLoop {
+    /// Creates a loop running `updates_per_second` updates.
+    #[must_use]
+    pub fn new(updates_per_second: u32) -> Self {
+        Self {
+            step: Duration::from_secs(1) / updates_per_second.max(1),
+            max_updates: 5,
+            frame_time: None,
+        }
+    }
+
+    /// Sets the most updates run to catch up before a frame, 5 by default
+    #[must_use]
+    pub const fn max_updates(mut self, updates: u32) -> Self {
+        self.max_updates = updates;
+        self
+    }
+
+    /// Limits rendering to `frames_per_second`, or only to the panel refresh with `None`
+    #[must_use]
+    pub fn frame_rate(mut self, frames_per_second: Option<u32>) -> Self {
+        self.frame_time = frames_per_second.map(|fps| Duration::from_secs(1) / fps.max(1));
+        self
+    }
+
+    /// Runs `game` on `matrix` until it's over.
+    pub fn run(&self, matrix: &LedMatrix, game: &mut impl Game) {
+        let mut canvas = matrix.offscreen_canvas();
+        let mut clock = Accumulator {
+            step: self.step,
+            max_updates: self.max_updates,
+            pending: Duration::ZERO,
+        };
+        let mut last = Instant::now();
+        while !game.is_over() {
+            game.poll_input();
+            let now = Instant::now();
+            let (updates, alpha) = clock.advance(now - last);
+            last = now;
+            for _ in 0..updates {
+                game.update(self.step);
+                if game.is_over() {
+                    return;
+                }
+            }
+            game.render(&mut canvas, alpha);
+            canvas = matrix.swap(canvas);
+            if let Some(frame_time) = self.frame_time {
+                std::thread::sleep(frame_time.saturating_sub(now.elapsed()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_steps() {
+        let mut clock = Accumulator {
+            step: Duration::from_millis(10),
+            max_updates: 3,
+            pending: Duration::ZERO,
+        };
+        assert_eq!(clock.advance(Duration::from_millis(5)), (0, 0.5));
+        assert_eq!(clock.advance(Duration::from_millis(10)), (1, 0.5));
+        assert_eq!(clock.advance(Duration::from_millis(25)), (3, 0.0));
+        // a stall only runs the maximum and drops the rest
+        assert_eq!(clock.advance(Duration::from_secs(1)), (3, 0.0));
+        assert_eq!(GameLoop::new(50).step, Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,377 @@
+//! HPACK header compression of HTTP/2, as specified in RFC 7541.
+//!
+//! Headers are decoded with the static and dynamic tables and Huffman coded strings. Headers
+//! are encoded as plain literals without indexing, which every decoder accepts.
+use std::collections::VecDeque;
+
+/// The table size the peer may use, the default as the server doesn't announce another
+pub(super) const TABLE_SIZE: usize = 4096;
+
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// The Huffman code length of every byte and of the end of string symbol, from RFC 7541
+/// Appendix B. The code is canonical, so the codes follow from the lengths.
+const HUFFMAN_LENGTHS: [u8; 257] = [
+    13, 23, 28, 28, 28, 28, 28, 28, 28, 24, 30, 28, 28, 30, 28, 28, 28, 28, 28, 28, 28, 28, 30, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 6, 10, 10, 12, 13, 6, 8, 11, 10, 10, 8, 11, 8, 6, 6, 6, 5, 5,
+    5, 6, 6, 6, 6, 6, 6, 6, 7, 8, 15, 6, 12, 10, 13, 6, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+    7, 7, 7, 7, 7, 7, 7, 7, 8, 7, 8, 13, 19, 13, 14, 6, 15, 5, 6, 5, 6, 5, 6, 6, 6, 5, 7, 7, 6, 6,
+    6, 5, 6, 7, 6, 5, 5, 6, 7, 7, 7, 7, 7, 15, 11, 14, 13, 28, 20, 22, 20, 20, 22, 22, 22, 23, 22,
+    23, 23, 23, 23, 23, 24, 23, 24, 24, 22, 23, 24, 23, 23, 23, 23, 21, 22, 23, 22, 23, 23, 24, 22,
+    21, 20, 22, 22, 23, 23, 21, 23, 22, 22, 24, 21, 22, 23, 23, 21, 21, 22, 21, 23, 22, 23, 23, 20,
+    22, 22, 22, 23, 22, 22, 23, 26, 26, 20, 19, 22, 23, 22, 25, 26, 26, 26, 27, 27, 26, 24, 25, 19,
+    21, 26, 27, 27, 26, 27, 24, 21, 21, 26, 26, 28, 27, 27, 27, 20, 24, 20, 21, 22, 21, 21, 23, 22,
+    22, 25, 25, 24, 24, 26, 23, 26, 27, 26, 26, 27, 27, 27, 27, 27, 28, 27, 27, 27, 27, 27, 26, 30,
+];
+const END_OF_STRING: u16 = 256;
+const LONGEST_CODE: usize = 30;
+
+/// The symbols ordered by code length, then value, and the number of codes of every length
+const HUFFMAN: ([u16; 257], [u16; LONGEST_CODE + 1]) = huffman_order();
+
+const fn huffman_order() -> ([u16; 257], [u16; LONGEST_CODE + 1]) {
+    let mut symbols = [0; 257];
+    let mut counts = [0; LONGEST_CODE + 1];
+    let mut ordered = 0;
+    let mut length = 1;
+    while length <= LONGEST_CODE {
+        let mut symbol = 0;
+        while symbol < symbols.len() {
+            if HUFFMAN_LENGTHS[symbol] as usize == length {
+                symbols[ordered] = symbol as u16;
+                ordered += 1;
+                counts[length] += 1;
+            }
+            symbol += 1;
+        }
+        length += 1;
+    }
+    (symbols, counts)
+}
+
+const MALFORMED: &str = "Malformed header block";
+
+/// Decodes header blocks, keeping the dynamic table of a connection between them.
+#[derive(Debug)]
+pub(super) struct Decoder {
+    /// Newest entry first
+    table: VecDeque<(String, String)>,
+    size: usize,
+    max_size: usize,
+}
+
+impl Decoder {
+    pub(super) const fn new() -> Self {
+        Self {
+            table: VecDeque::new(),
+            size: 0,
+            max_size: TABLE_SIZE,
+        }
+    }
+
+    /// Decodes a complete header block into names and values.
+    pub(super) fn decode(&mut self, block: &[u8]) -> Result<Vec<(String, String)>, &'static str> {
+        let mut headers = Vec::new();
+        let mut position = 0;
+        while let Some(&first) = block.get(position) {
+            if first & 0x80 != 0 {
+                let index = integer(block, &mut position, 7)?;
+                headers.push(self.entry(index)?);
+            } else if first & 0xe0 == 0x20 {
+                let size = integer(block, &mut position, 5)?;
+                if size > TABLE_SIZE {
+                    return Err("Header table size too large");
+                }
+                self.max_size = size;
+                self.evict(0);
+            } else {
+                // with incremental indexing, or without indexing or never indexed
+                let indexed = first & 0xc0 == 0x40;
+                let index = integer(block, &mut position, if indexed { 6 } else { 4 })?;
+                let name = if index == 0 {
+                    string(block, &mut position)?
+                } else {
+                    self.entry(index)?.0
+                };
+                let value = string(block, &mut position)?;
+                if indexed {
+                    self.insert(name.clone(), value.clone());
+                }
+                headers.push((name, value));
+            }
+        }
+        Ok(headers)
+    }
+
+    fn entry(&self, index: usize) -> Result<(String, String), &'static str> {
+        let (name, value) = match index.checked_sub(1) {
+            Some(index) if index < STATIC_TABLE.len() => STATIC_TABLE[index],
+            Some(index) => {
+                let (name, value) = self
+                    .table
+                    .get(index - STATIC_TABLE.len())
+                    .ok_or("Header index out of range")?;
+                (name.as_str(), value.as_str())
+            }
+            None => return Err(MALFORMED),
+        };
+        Ok((name.to_string(), value.to_string()))
+    }
+
+    fn insert(&mut self, name: String, value: String) {
+        let size = name.len() + value.len() + 32;
+        self.evict(size);
+        if size <= self.max_size {
+            self.size += size;
+            self.table.push_front((name, value));
+        }
+    }
+
+    /// Evicts the oldest entries until `space` more bytes fit.
+    fn evict(&mut self, space: usize) {
+        while self.size + space > self.max_size {
+            let Some((name, value)) = self.table.pop_back() else {
+                self.size = 0;
+                return;
+            };
+            self.size -= name.len() + value.len() + 32;
+        }
+    }
+}
+
+/// Decodes an integer with a prefix of `bits` in its first byte.
+fn integer(block: &[u8], position: &mut usize, bits: u32) -> Result<usize, &'static str> {
+    let max = (1 << bits) - 1;
+    let mut value = usize::from(*block.get(*position).ok_or(MALFORMED)?) & max;
+    *position += 1;
+    if value < max {
+        return Ok(value);
+    }
+    for shift in (0..28).step_by(7) {
+        let byte = *block.get(*position).ok_or(MALFORMED)?;
+        *position += 1;
+        value += usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(MALFORMED)
+}
+
+fn string(block: &[u8], position: &mut usize) -> Result<String, &'static str> {
+    let huffman = block.get(*position).ok_or(MALFORMED)? & 0x80 != 0;
+    let length = integer(block, position, 7)?;
+    let end = position.checked_add(length).ok_or(MALFORMED)?;
+    let bytes = block.get(*position..end).ok_or(MALFORMED)?;
+    *position = end;
+    let bytes = if huffman {
+        decode_huffman(bytes)?
+    } else {
+        bytes.to_vec()
+    };
+    String::from_utf8(bytes).or(Err("Header isn't UTF-8"))
+}
+
+fn decode_huffman(bytes: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (symbols, counts) = &HUFFMAN;
+    let mut decoded = Vec::with_capacity(bytes.len() * 8 / 5);
+    // the code read so far, the first code of its length and the index of that code's symbol
+    let (mut code, mut length, mut first, mut index) = (0_u32, 0, 0_u32, 0);
+    for bit in bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1))
+    {
+        code = code << 1 | u32::from(bit);
+        length += 1;
+        if length > LONGEST_CODE {
+            return Err("Malformed Huffman code");
+        }
+        let count = u32::from(counts[length]);
+        if code - first < count {
+            match symbols[index + (code - first) as usize] {
+                END_OF_STRING => return Err("Malformed Huffman code"),
+                symbol => decoded.push(symbol as u8),
+            }
+            (code, length, first, index) = (0, 0, 0, 0);
+        } else {
+            index += count as usize;
+            first = (first + count) << 1;
+        }
+    }
+    // the last byte is padded with the start of the end of string code, all ones
+    if length >= 8 || code != (1 << length) - 1 {
+        return Err("Malformed Huffman code");
+    }
+    Ok(decoded)
+}
+
+fn put_integer(block: &mut Vec<u8>, flags: u8, bits: u32, value: usize) {
+    let max = (1 << bits) - 1;
+    if value < max {
+        block.push(flags | value as u8);
+        return;
+    }
+    block.push(flags | max as u8);
+    let mut rest = value - max;
+    while rest >= 0x80 {
+        block.push(rest as u8 | 0x80);
+        rest >>= 7;
+    }
+    block.push(rest as u8);
+}
+
+/// Encodes headers as literals without indexing.
+pub(super) fn encode(headers: &[(&str, &str)]) -> Vec<u8> {
+    let mut block = Vec::new();
+    for (name, value) in headers {
+        block.push(0);
+        for string in [name, value] {
+            put_integer(&mut block, 0, 7, string.len());
+            block.extend_from_slice(string.as_bytes());
+        }
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding() {
+        // the requests of RFC 7541, C.4, with Huffman coding
+        let mut decoder = Decoder::new();
+        let mut first = vec![0x82, 0x86, 0x84, 0x41, 0x8c];
+        first.extend([
+            0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab, 0x90, 0xf4, 0xff,
+        ]);
+        let headers = decoder.decode(&first).unwrap();
+        assert_eq!(
+            headers[3],
+            (":authority".to_string(), "www.example.com".to_string())
+        );
+        let second = [
+            0x82, 0x86, 0x84, 0xbe, 0x58, 0x86, 0xa8, 0xeb, 0x10, 0x64, 0x9c, 0xbf,
+        ];
+        let headers = decoder.decode(&second).unwrap();
+        assert_eq!(headers[3].1, "www.example.com");
+        assert_eq!(
+            headers[4],
+            ("cache-control".to_string(), "no-cache".to_string())
+        );
+        assert_eq!(decoder.size, 57 + 53);
+
+        let long = "x".repeat(200);
+        let encoded = encode(&[("grpc-message", &long), (":status", "200")]);
+        assert_eq!(
+            Decoder::new().decode(&encoded).unwrap(),
+            [
+                ("grpc-message".to_string(), long),
+                (":status".to_string(), "200".to_string())
+            ]
+        );
+        assert!(decoder.decode(&[0x41, 0x81, 0x00]).is_err());
+        assert!(decoder.decode(&[0xff, 0xff]).is_err());
+    }
+
+    #[test]
+    fn huffman() {
+        // the canonical codes of all symbols, checked against a few of RFC 7541 Appendix B
+        let (symbols, counts) = &HUFFMAN;
+        let mut codes = [(0_u32, 0); 257];
+        let (mut code, mut index) = (0, 0);
+        for (length, &count) in counts.iter().enumerate() {
+            for &symbol in &symbols[index..index + usize::from(count)] {
+                codes[usize::from(symbol)] = (code, length);
+                code += 1;
+            }
+            index += usize::from(count);
+            code <<= 1;
+        }
+        assert_eq!(codes[usize::from(b'0')], (0x0, 5));
+        assert_eq!(codes[usize::from(b'\\')], (0x7fff0, 19));
+        assert_eq!(codes[0xff], (0x3ff_ffee, 26));
+        assert_eq!(codes[usize::from(END_OF_STRING)], (0x3fff_ffff, 30));
+
+        let all: Vec<u8> = (0..=255).chain((0..=255).rev()).collect();
+        let mut encoded = Vec::new();
+        let (mut bits, mut pending) = (0_u64, 0);
+        for &byte in &all {
+            let (code, length) = codes[usize::from(byte)];
+            bits = bits << length | u64::from(code);
+            pending += length;
+            while pending >= 8 {
+                pending -= 8;
+                encoded.push((bits >> pending) as u8);
+            }
+        }
+        if pending > 0 {
+            encoded.push((bits << (8 - pending)) as u8 | (0xff >> pending));
+        }
+        assert_eq!(decode_huffman(&encoded).unwrap(), all);
+        // the end of string code itself isn't allowed
+        assert!(decode_huffman(&[0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+//! Unary gRPC calls over HTTP/2 without TLS, the transport of insecure gRPC channels.
+//!
+//! Streams are answered in the order their requests complete, one at a time, which is all a
+//! panel taking one drawing command after another needs. Replies are sent as the flow control
+//! windows of the client allow, while its next frames are read.
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use super::hpack::{self, Decoder};
+use super::Status;
+
+pub(super) const PREFACE: &[u8; 24] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+pub(super) const DATA: u8 = 0;
+pub(super) const HEADERS: u8 = 1;
+pub(super) const RST_STREAM: u8 = 3;
+pub(super) const SETTINGS: u8 = 4;
+const PING: u8 = 6;
+const GOAWAY: u8 = 7;
+pub(super) const WINDOW_UPDATE: u8 = 8;
+const CONTINUATION: u8 = 9;
+
+pub(super) const END_STREAM: u8 = 0x1;
+const ACK: u8 = 0x1;
+pub(super) const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+const PRIORITY: u8 = 0x20;
+
+const MAX_CONCURRENT_STREAMS: u16 = 0x3;
+const INITIAL_WINDOW_SIZE: u16 = 0x4;
+const MAX_FRAME_SIZE_SETTING: u16 = 0x5;
+
+const PROTOCOL: u32 = 0x1;
+const FLOW_CONTROL: u32 = 0x3;
+const FRAME_SIZE: u32 = 0x6;
+const REFUSED_STREAM: u32 = 0x7;
+const COMPRESSION: u32 = 0x9;
+
+/// The largest frame peers may send without the server announcing more
+const MAX_FRAME_SIZE: usize = 16384;
+/// The largest request message accepted, enough for an RGB image of 1024 by 1024 pixels. It
+/// also bounds the request data buffered by a connection over all its streams.
+const MAX_MESSAGE_SIZE: usize = 4 << 20;
+/// The streams a client may have open at once, announced in the settings of the server
+const MAX_STREAMS: usize = 16;
+/// The largest header block accepted, with its `CONTINUATION` frames
+const MAX_HEADER_BLOCK: usize = 64 << 10;
+/// How long a client may stay silent, or not read the replies, before it's disconnected
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// The flow control window of the connection and of streams before any settings
+const DEFAULT_WINDOW: i64 = 65535;
+const MAX_WINDOW: i64 = 0x7fff_ffff;
+
+const PROTOCOL_ERROR: &str = "HTTP/2 protocol error";
+const FLOW_CONTROL_ERROR: &str = "HTTP/2 flow control window overflowed";
+
+/// A frame of the connection
+#[derive(Debug)]
+pub(super) struct Frame {
+    pub(super) kind: u8,
+    pub(super) flags: u8,
+    pub(super) stream: u32,
+    pub(super) payload: Vec<u8>,
+}
+
+/// Reads the next frame, `None` once the peer closed the connection.
+pub(super) fn read_frame(stream: &mut impl Read) -> Result<Option<Frame>, &'static str> {
+    let mut header = [0; 9];
+    match stream.read(&mut header[..1]) {
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        Err(_) => return Err("Couldn't read HTTP/2 frame"),
+    }
+    stream
+        .read_exact(&mut header[1..])
+        .map_err(|_| "Couldn't read HTTP/2 frame")?;
+    let length = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err("HTTP/2 frame too large");
+    }
+    let mut payload = vec![0; length];
+    stream
+        .read_exact(&mut payload)
+        .map_err(|_| "Couldn't read HTTP/2 frame")?;
+    Ok(Some(Frame {
+        kind: header[3],
+        flags: header[4],
+        stream: u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff,
+        payload,
+    }))
+}
+
+pub(super) fn write_frame(
+    stream: &mut impl Write,
+    kind: u8,
+    flags: u8,
+    id: u32,
+    payload: &[u8],
+) -> Result<(), &'static str> {
+    let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+    frame.extend([kind, flags]);
+    frame.extend(id.to_be_bytes());
+    frame.extend(payload);
+    stream
+        .write_all(&frame)
+        .map_err(|_| "Couldn't write HTTP/2 frame")
+}
+
+/// The payload of a `DATA` or `HEADERS` frame, without padding and priority.
+fn content(frame: &Frame) -> Result<&[u8], &'static str> {
+    let mut payload = frame.payload.as_slice();
+    if frame.flags & PADDED != 0 {
+        let (&padding, rest) = payload.split_first().ok_or(PROTOCOL_ERROR)?;
+        let end = rest
+            .len()
+            .checked_sub(usize::from(padding))
+            .ok_or(PROTOCOL_ERROR)?;
+        payload = &rest[..end];
+    }
+    if frame.kind == HEADERS && frame.flags & PRIORITY != 0 {
+        payload = payload.get(5..).ok_or(PROTOCOL_ERROR)?;
+    }
+    Ok(payload)
+}
+
+/// A request being received
+#[derive(Debug, Default)]
+struct Request {
+    path: String,
+    body: Vec<u8>,
+}
+
+/// A reply waiting for the flow control windows of the client to open
+#[derive(Debug)]
+struct Reply {
+    id: u32,
+    data: Vec<u8>,
+    sent: usize,
+}
+
+/// A connection answering calls with a handler taking the path and the request message.
+struct Connection<H> {
+    stream: TcpStream,
+    decoder: Decoder,
+    requests: HashMap<u32, Request>,
+    /// A header block continued in the next frames: its stream, whether it ends the stream,
+    /// and the fragments so far
+    block: Option<(u32, bool, Vec<u8>)>,
+    replies: Vec<Reply>,
+    /// The windows of the open streams for sending
+    windows: HashMap<u32, i64>,
+    /// The window of the connection for sending
+    window: i64,
+    /// The window of new streams, from the client's settings
+    initial_window: i64,
+    /// The largest frame the client accepts
+    max_frame: usize,
+    /// The highest stream opened by the client
+    last_stream: u32,
+    /// The error code sent in `GOAWAY` when the connection fails
+    error: u32,
+    handler: H,
+}
+
+impl<H: FnMut(&str, &[u8]) -> Result<Vec<u8>, Status>> Connection<H> {
+    fn headers(&mut self, id: u32, end_stream: bool, block: &[u8]) -> Result<(), &'static str> {
+        // the block has to be decoded even for unknown streams to keep the table in sync
+        let headers = self.decoder.decode(block).inspect_err(|_| {
+            self.error = COMPRESSION;
+        })?;
+        // clients open odd streams
+        if id & 1 == 0 {
+            return Err(PROTOCOL_ERROR);
+        }
+        if !self.requests.contains_key(&id) {
+            if id <= self.last_stream {
+                // trailers of a stream already answered or reset
+                return Ok(());
+            }
+            self.last_stream = id;
+            if self.requests.len() + self.replies.len() >= MAX_STREAMS {
+                let code = REFUSED_STREAM.to_be_bytes();
+                return write_frame(&mut self.stream, RST_STREAM, 0, id, &code);
+            }
+            self.windows.insert(id, self.initial_window);
+        }
+        let request = self.requests.entry(id).or_default();
+        if let Some((_, path)) = headers.into_iter().find(|(name, _)| name == ":path") {
+            request.path = path;
+        }
+        if end_stream {
+            self.respond(id)?;
+        }
+        Ok(())
+    }
+
+    fn data(&mut self, frame: &Frame) -> Result<(), &'static str> {
+        if frame.stream == 0 {
+            return Err(PROTOCOL_ERROR);
+        }
+        let data = content(frame)?;
+        // padding counts against the window too
+        if !frame.payload.is_empty() {
+            let increment = (frame.payload.len() as u32).to_be_bytes();
+            write_frame(&mut self.stream, WINDOW_UPDATE, 0, 0, &increment)?;
+            if frame.flags & END_STREAM == 0 {
+                write_frame(&mut self.stream, WINDOW_UPDATE, 0, frame.stream, &increment)?;
+            }
+        }
+        let buffered: usize = self
+            .requests
+            .values()
+            .map(|request| request.body.len())
+            .sum();
+        let Some(request) = self.requests.get_mut(&frame.stream) else {
+            return Ok(());
+        };
+        if buffered + data.len() > MAX_MESSAGE_SIZE {
+            self.requests.remove(&frame.stream);
+            self.status(frame.stream, Status::RESOURCE_EXHAUSTED)?;
+            self.windows.remove(&frame.stream);
+            // refuse the rest of the stream
+            return write_frame(&mut self.stream, RST_STREAM, 0, frame.stream, &[0, 0, 0, 0]);
+        }
+        request.body.extend_from_slice(data);
+        if frame.flags & END_STREAM != 0 {
+            self.respond(frame.stream)?;
+        }
+        Ok(())
+    }
+
+    /// Answers the complete request on stream `id`.
+    fn respond(&mut self, id: u32) -> Result<(), &'static str> {
+        let Some(request) = self.requests.remove(&id) else {
+            return Ok(());
+        };
+        let reply = match request.body.split_first_chunk::<5>() {
+            Some(([0, length @ ..], message))
+                if u32::from_be_bytes(*length) as usize == message.len() =>
+            {
+                (self.handler)(&request.path, message)
+            }
+            Some(([1, ..], _)) => Err(Status::COMPRESSED),
+            _ => Err(Status::MALFORMED),
+        };
+        let message = match reply {
+            Ok(message) => message,
+            Err(status) => {
+                self.windows.remove(&id);
+                return self.status(id, status);
+            }
+        };
+        let headers = hpack::encode(&[(":status", "200"), ("content-type", "application/grpc")]);
+        write_frame(&mut self.stream, HEADERS, END_HEADERS, id, &headers)?;
+        let mut data = vec![0];
+        data.extend((message.len() as u32).to_be_bytes());
+        data.extend(message);
+        self.replies.push(Reply { id, data, sent: 0 });
+        self.flush()
+    }
+
+    /// Sends as much of the waiting replies as the windows allow, ending the streams of those
+    /// sent completely.
+    fn flush(&mut self) -> Result<(), &'static str> {
+        let mut index = 0;
+        while let Some(reply) = self.replies.get_mut(index) {
+            let window = self.windows.entry(reply.id).or_insert(0);
+            while reply.sent < reply.data.len() {
+                let open = self.window.min(*window).max(0) as usize;
+                let length = (reply.data.len() - reply.sent)
+                    .min(self.max_frame)
+                    .min(open);
+                if length == 0 {
+                    break;
+                }
+                let chunk = &reply.data[reply.sent..][..length];
+                write_frame(&mut self.stream, DATA, 0, reply.id, chunk)?;
+                reply.sent += length;
+                self.window -= length as i64;
+                *window -= length as i64;
+            }
+            if reply.sent < reply.data.len() {
+                index += 1;
+                continue;
+            }
+            let id = self.replies.remove(index).id;
+            self.windows.remove(&id);
+            let trailers = hpack::encode(&[("grpc-status", "0")]);
+            write_frame(
+                &mut self.stream,
+                HEADERS,
+                END_HEADERS | END_STREAM,
+                id,
+                &trailers,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Takes the settings of the client.
+    fn settings(&mut self, payload: &[u8]) -> Result<(), &'static str> {
+        let settings = payload.chunks_exact(6);
+        if !settings.remainder().is_empty() {
+            self.error = FRAME_SIZE;
+            return Err(PROTOCOL_ERROR);
+        }
+        for setting in settings {
+            let value = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+            match u16::from_be_bytes([setting[0], setting[1]]) {
+                INITIAL_WINDOW_SIZE => {
+                    let value = i64::from(value);
+                    if value > MAX_WINDOW {
+                        self.error = FLOW_CONTROL;
+                        return Err(FLOW_CONTROL_ERROR);
+                    }
+                    // open streams change by the difference
+                    for window in self.windows.values_mut() {
+                        *window += value - self.initial_window;
+                    }
+                    self.initial_window = value;
+                }
+                MAX_FRAME_SIZE_SETTING => {
+                    if !(16384..1 << 24).contains(&value) {
+                        return Err(PROTOCOL_ERROR);
+                    }
+                    self.max_frame = value as usize;
+                }
+                _ => {}
+            }
+        }
+        write_frame(&mut self.stream, SETTINGS, ACK, 0, &[])?;
+        self.flush()
+    }
+
+    fn window_update(&mut self, frame: &Frame) -> Result<(), &'static str> {
+        let Ok(increment) = <[u8; 4]>::try_from(frame.payload.as_slice()) else {
+            self.error = FRAME_SIZE;
+            return Err(PROTOCOL_ERROR);
+        };
+        let increment = i64::from(u32::from_be_bytes(increment) & 0x7fff_ffff);
+        if frame.stream == 0 {
+            if increment == 0 {
+                return Err(PROTOCOL_ERROR);
+            }
+            self.window += increment;
+            if self.window > MAX_WINDOW {
+                self.error = FLOW_CONTROL;
+                return Err(FLOW_CONTROL_ERROR);
+            }
+        } else if let Some(window) = self.windows.get_mut(&frame.stream) {
+            *window += increment;
+        }
+        self.flush()
+    }
+
+    /// Ends stream `id` with an error, as a response of trailers only.
+    fn status(&mut self, id: u32, status: Status) -> Result<(), &'static str> {
+        let code = status.code.to_string();
+        let headers = hpack::encode(&[
+            (":status", "200"),
+            ("content-type", "application/grpc"),
+            ("grpc-status", &code),
+            ("grpc-message", status.message),
+        ]);
+        write_frame(
+            &mut self.stream,
+            HEADERS,
+            END_HEADERS | END_STREAM,
+            id,
+            &headers,
+        )
+    }
+
+    fn run(&mut self) -> Result<(), &'static str> {
+        let mut preface = [0; PREFACE.len()];
+        self.stream
+            .read_exact(&mut preface)
+            .map_err(|_| "Couldn't read HTTP/2 preface")?;
+        if &preface != PREFACE {
+            return Err("Not an HTTP/2 connection");
+        }
+        let mut settings = MAX_CONCURRENT_STREAMS.to_be_bytes().to_vec();
+        settings.extend((MAX_STREAMS as u32).to_be_bytes());
+        write_frame(&mut self.stream, SETTINGS, 0, 0, &settings)?;
+        while let Some(frame) = read_frame(&mut self.stream)? {
+            if let Some((id, end_stream, mut block)) = self.block.take() {
+                if frame.kind != CONTINUATION || frame.stream != id {
+                    return Err(PROTOCOL_ERROR);
+                }
+                if block.len() + frame.payload.len() > MAX_HEADER_BLOCK {
+                    return Err("HTTP/2 header block too large");
+                }
+                block.extend_from_slice(&frame.payload);
+                if frame.flags & END_HEADERS == 0 {
+                    self.block = Some((id, end_stream, block));
+                } else {
+                    self.headers(id, end_stream, &block)?;
+                }
+                continue;
+            }
+            match frame.kind {
+                DATA => self.data(&frame)?,
+                HEADERS => {
+                    let end_stream = frame.flags & END_STREAM != 0;
+                    let block = content(&frame)?;
+                    if frame.flags & END_HEADERS == 0 {
+                        self.block = Some((frame.stream, end_stream, block.to_vec()));
+                    } else {
+                        self.headers(frame.stream, end_stream, block)?;
+                    }
+                }
+                RST_STREAM => {
+                    self.requests.remove(&frame.stream);
+                    self.replies.retain(|reply| reply.id != frame.stream);
+                    self.windows.remove(&frame.stream);
+                }
+                SETTINGS if frame.flags & ACK == 0 => self.settings(&frame.payload)?,
+                PING if frame.flags & ACK == 0 => {
+                    write_frame(&mut self.stream, PING, ACK, 0, &frame.payload)?;
+                }
+                WINDOW_UPDATE => self.window_update(&frame)?,
+                GOAWAY => break,
+                CONTINUATION => return Err(PROTOCOL_ERROR),
+                // priorities don't matter to answering one stream at a time
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Answers the calls of a client until it disconnects, with `handler` taking the path of
+/// the method and the request message and returning the reply message.
+pub(super) fn serve(
+    stream: TcpStream,
+    handler: impl FnMut(&str, &[u8]) -> Result<Vec<u8>, Status>,
+) -> Result<(), &'static str> {
+    let _ = stream.set_nodelay(true);
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let _ = stream.set_write_timeout(Some(TIMEOUT));
+    let mut connection = Connection {
+        stream,
+        decoder: Decoder::new(),
+        requests: HashMap::new(),
+        block: None,
+        replies: Vec::new(),
+        windows: HashMap::new(),
+        window: DEFAULT_WINDOW,
+        initial_window: DEFAULT_WINDOW,
+        max_frame: MAX_FRAME_SIZE,
+        last_stream: 0,
+        error: PROTOCOL,
+        handler,
+    };
+    let result = connection.run();
+    if result.is_err() {
+        // tell the client why, in case the connection itself still works
+        let mut payload = connection.last_stream.to_be_bytes().to_vec();
+        payload.extend(connection.error.to_be_bytes());
+        let _ = write_frame(&mut connection.stream, GOAWAY, 0, 0, &payload);
+    }
+    result
+}
@@ -0,0 +1,439 @@
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+
+use crate::http_server;
+use crate::{LedCanvas, LedColor, LedFont, LedMatrix, TextDrawOptions};
+
+mod hpack;
+mod http2;
+mod proto;
+
+use proto::Value;
+
+/// The service definition, for generating clients
+pub const DISPLAY_PROTO: &str = include_str!("../../proto/display.proto");
+
+/// The clients answered at once, further connections are closed right away
+const MAX_CLIENTS: usize = 8;
+
+/// A gRPC status ending a call that failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Status {
+    code: u32,
+    message: &'static str,
+}
+
+impl Status {
+    const MALFORMED: Self = Self::invalid("Malformed request");
+    const COMPRESSED: Self = Self {
+        code: 12,
+        message: "Compressed messages aren't supported",
+    };
+    const UNIMPLEMENTED: Self = Self {
+        code: 12,
+        message: "Unknown method",
+    };
+    const NOT_FOUND: Self = Self {
+        code: 5,
+        message: "Unknown font",
+    };
+    const RESOURCE_EXHAUSTED: Self = Self {
+        code: 8,
+        message: "Request too large",
+    };
+    const UNAVAILABLE: Self = Self {
+        code: 14,
+        message: "Display stopped",
+    };
+
+    /// An invalid argument
+    const fn invalid(message: &'static str) -> Self {
+        Self { code: 3, message }
+    }
+}
+
+/// A call of the display service
+#[derive(Debug, PartialEq, Eq)]
+enum Call {
+    DrawText {
+        text: String,
+        x: i32,
+        y: i32,
+        color: LedColor,
+        font: String,
+    },
+    DrawImage {
+        rgb: Vec<u8>,
+        width: u32,
+        x: i32,
+        y: i32,
+    },
+    Fill(LedColor),
+    Swap,
+    SetBrightness(u32),
+    GetBrightness,
+}
+
+impl Call {
+    fn decode(path: &str, message: &[u8]) -> Result<Self, Status> {
+        let fields = proto::fields(message).or(Err(Status::MALFORMED))?;
+        // the last value of a field wins, like protobuf merges them
+        let field = |number| {
+            fields
+                .iter()
+                .rev()
+                .find(|&&(field, _)| field == number)
+                .map(|&(_, value)| value)
+        };
+        let varint = |number| field(number).and_then(Value::varint).unwrap_or(0);
+        let bytes = |number| field(number).and_then(Value::bytes).unwrap_or_default();
+        let string = |number| {
+            String::from_utf8(bytes(number).to_vec()).or(Err(Status::invalid("Text isn't UTF-8")))
+        };
+        let color = |number| -> Result<LedColor, Status> {
+            let fields = proto::fields(bytes(number)).or(Err(Status::MALFORMED))?;
+            let channel = |number| {
+                fields
+                    .iter()
+                    .rev()
+                    .find(|&&(field, _)| field == number)
+                    .and_then(|&(_, value)| value.varint())
+                    .map_or(0, |value| value.min(255) as u8)
+            };
+            Ok(LedColor {
+                red: channel(1),
+                green: channel(2),
+                blue: channel(3),
+            })
+        };
+        let method = path
+            .strip_prefix("/rpi_led_matrix.Display/")
+            .ok_or(Status::UNIMPLEMENTED)?;
+        Ok(match method {
+            "DrawText" => Self::DrawText {
+                text: string(1)?,
+                x: varint(2) as i32,
+                y: varint(3) as i32,
+                color: color(4)?,
+                font: string(5)?,
+            },
+            "DrawImage" => Self::DrawImage {
+                rgb: bytes(1).to_vec(),
+                width: varint(2) as u32,
+                x: varint(3) as i32,
+                y: varint(4) as i32,
+            },
+            "Fill" => Self::Fill(color(1)?),
+            "Swap" => Self::Swap,
+            "SetBrightness" => Self::SetBrightness(varint(1) as u32),
+            "GetBrightness" => Self::GetBrightness,
+            _ => return Err(Status::UNIMPLEMENTED),
+        })
+    }
+}
+
+/// Serves a gRPC display service, so applications in any language can drive the panel over
+/// the network through a typed API.
+///
+/// The service in [`DISPLAY_PROTO`], `proto/display.proto` in the crate, draws text, RGB
+/// images and fills on the offscreen canvas, swaps it onto the panel and sets the
+/// brightness. Clients connect with an insecure channel, as the server speaks HTTP/2 without
+/// TLS, and calls are applied in the order they arrive from up to 8 clients at once. Clients
+/// silent for 30 seconds are disconnected, channels reconnect on their next call.
+///
+/// ```no_run
+/// use rpi_led_matrix::{GrpcServer, LedFont, LedMatrix};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let font = LedFont::new(Path::new("/usr/share/fonts/misc/6x10.bdf")).unwrap();
+/// GrpcServer::bind("0.0.0.0:50051")
+///     .unwrap()
+///     .font("6x10", font)
+///     .run(&matrix)
+///     .unwrap();
+/// ```
+///
+/// Generated clients then draw, e.g. in Python:
+///
+/// ```python
+/// channel = grpc.insecure_channel("matrix.local:50051")
+/// display = display_pb2_grpc.DisplayStub(channel)
+/// display.Fill(display_pb2.FillRequest(color=display_pb2.Color(blue=64)))
+/// red = display_pb2.Color(red=255)
+/// display.DrawText(display_pb2.DrawTextRequest(text="Hi", x=2, y=10, color=red))
+/// display.Swap(display_pb2.Empty())
+/// ```
+pub struct GrpcServer {
+    listener: TcpListener,
+    fonts: Vec<(String, LedFont)>,
+}
+
+impl GrpcServer {
+    /// Listens for clients on `address`, port 50051 is the usual one for gRPC.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, &'static str> {
+        let listener = TcpListener::bind(address).map_err(|_| "Couldn't bind gRPC address")?;
+        Ok(Self {
+            listener,
+            fonts: Vec::new(),
+        })
+    }
+
+    /// Adds a font clients draw text with by `name`. The first one is used when clients don't
+    /// name one.
+    #[must_use]
+    pub fn font(mut self, name: &str, font: LedFont) -> Self {
+        self.fonts.push((name.to_string(), font));
+        self
+    }
+
+    /// The address listened on, e.g. to find the port when binding port 0.
+    ///
+    /// # Errors
+    /// If the socket can't tell.
+    pub fn local_addr(&self) -> Result<SocketAddr, &'static str> {
+        self.listener
+            .local_addr()
+            .map_err(|_| "Couldn't get gRPC address")
+    }
+
+    /// Answers calls, drawing on `matrix`, until the listening socket fails.
+    ///
+    /// # Errors
+    /// If accepting clients fails for another reason than a client giving up.
+    pub fn run(self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        // `None` once no more clients can be accepted
+        let (calls, received) = mpsc::channel::<Option<(Call, Sender<Result<_, Status>>)>>();
+        let Self { listener, fonts } = self;
+        std::thread::spawn(move || {
+            let client = calls.clone();
+            http_server::serve(&listener, MAX_CLIENTS, move |stream| {
+                let _ = http2::serve(stream, |path, message| {
+                    let call = Call::decode(path, message)?;
+                    let (reply, answer) = mpsc::channel();
+                    client
+                        .send(Some((call, reply)))
+                        .or(Err(Status::UNAVAILABLE))?;
+                    answer.recv().unwrap_or(Err(Status::UNAVAILABLE))
+                });
+            });
+            let _ = calls.send(None);
+        });
+        let mut canvas = matrix.offscreen_canvas();
+        while let Ok(Some((call, reply))) = received.recv() {
+            let result = match call {
+                Call::Swap => {
+                    canvas = matrix.swap(canvas);
+                    Ok(Vec::new())
+                }
+                call => apply(&fonts, call, matrix, &mut canvas),
+            };
+            let _ = reply.send(result);
+        }
+        Err("gRPC listener failed")
+    }
+}
+
+/// Applies a call other than a swap, returning the reply message.
+fn apply(
+    fonts: &[(String, LedFont)],
+    call: Call,
+    matrix: &LedMatrix,
+    canvas: &mut LedCanvas,
+) -> Result<Vec<u8>, Status> {
+    match call {
+        Call::DrawText {
+            text,
+            x,
+            y,
+            color,
+            font,
+        } => {
+            let (_, font) = fonts
+                .iter()
+                .find(|(name, _)| font.is_empty() || *name == font)
+                .ok_or(Status::NOT_FOUND)?;
+            let options = TextDrawOptions::new().position(x, y).color(&color);
+            canvas.draw_text(font, &text, &options);
+        }
+        Call::DrawImage { rgb, width, x, y } => canvas.set_iter(image_pixels(&rgb, width, x, y)?),
+        Call::Fill(color) => canvas.fill(&color),
+        Call::SetBrightness(percent) => {
+            let percent = u8::try_from(percent).unwrap_or(0);
+            matrix.set_brightness(percent).map_err(Status::invalid)?;
+            return Ok(brightness(matrix));
+        }
+        Call::GetBrightness => return Ok(brightness(matrix)),
+        Call::Swap => {}
+    }
+    Ok(Vec::new())
+}
+
+/// The `Brightness` reply
+/// The pixels of an RGB image `width` pixels wide with its top left corner at (`x`, `y`).
+///
+/// Pixels past the range of coordinates are dropped, they're off every canvas anyway.
+fn image_pixels(
+    rgb: &[u8],
+    width: u32,
+    x: i32,
+    y: i32,
+) -> Result<impl Iterator<Item = (i32, i32, LedColor)> + '_, Status> {
+    let row = usize::try_from(width)
+        .ok()
+        .and_then(|width| width.checked_mul(3));
+    let (Some(row), Ok(width)) = (row.filter(|&row| row > 0), i32::try_from(width)) else {
+        return Err(Status::invalid("Image width out of range"));
+    };
+    if !rgb.len().is_multiple_of(row) {
+        return Err(Status::invalid("Image width doesn't match its pixels"));
+    }
+    Ok((0..)
+        .zip(rgb.chunks_exact(3))
+        .filter_map(move |(i, pixel)| {
+            let color = LedColor {
+                red: pixel[0],
+                green: pixel[1],
+                blue: pixel[2],
+            };
+            Some((x.checked_add(i % width)?, y.checked_add(i / width)?, color))
+        }))
+}
+
+fn brightness(matrix: &LedMatrix) -> Vec<u8> {
+    let mut message = Vec::new();
+    proto::put_varint(&mut message, 1, u64::from(matrix.brightness()));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http2::{read_frame, write_frame, DATA, END_HEADERS, END_STREAM, HEADERS, SETTINGS};
+    use http2::{RST_STREAM, WINDOW_UPDATE};
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    #[test]
+    fn calls() {
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        let fill = Call::decode("/rpi_led_matrix.Display/Fill", &[0x0a, 3, 0x08, 0xff, 0x01]);
+        assert_eq!(fill, Ok(Call::Fill(red)));
+        // -2 as int32, and a fixed64 field to skip
+        let mut image = vec![0x0a, 3, 1, 2, 3, 0x10, 1, 0x18];
+        image.extend([0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+        image.extend([0x31, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            Call::decode("/rpi_led_matrix.Display/DrawImage", &image),
+            Ok(Call::DrawImage {
+                rgb: vec![1, 2, 3],
+                width: 1,
+                x: -2,
+                y: 0
+            })
+        );
+        assert_eq!(
+            Call::decode("/rpi_led_matrix.Display/Fill", &[0x0a, 3, 0x08]),
+            Err(Status::MALFORMED)
+        );
+        // the second pixel would lie past the largest x
+        let pixels: Vec<_> = image_pixels(&[1, 2, 3, 4, 5, 6], 2, i32::MAX, 0)
+            .unwrap()
+            .collect();
+        assert_eq!(pixels.len(), 1);
+        assert!(image_pixels(&[1, 2, 3], u32::MAX, 0, 0).is_err());
+
+        // the transport with the calls answered like a panel at 40% brightness would
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            http2::serve(stream, |path, message| match Call::decode(path, message)? {
+                Call::SetBrightness(0 | 101..) => Err(Status::invalid("Brightness out of range")),
+                Call::SetBrightness(_) | Call::GetBrightness => Ok(vec![0x08, 40]),
+                Call::DrawText { font, .. } if font != "4x6" => Err(Status::NOT_FOUND),
+                _ => Ok(Vec::new()),
+            })
+        });
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.write_all(http2::PREFACE).unwrap();
+        // windows of 4 bytes, so replies have to wait for the client
+        write_frame(&mut stream, SETTINGS, 0, 0, &[0, 4, 0, 0, 0, 4]).unwrap();
+        let mut decoder = hpack::Decoder::new();
+        let path = |method: &str| format!("/rpi_led_matrix.Display/{method}");
+        let headers = |path: &str| {
+            hpack::encode(&[
+                (":method", "POST"),
+                (":path", path),
+                ("content-type", "application/grpc"),
+            ])
+        };
+        let mut call = |id: u32, method: &str, message: &[u8]| {
+            let headers = headers(&path(method));
+            write_frame(&mut stream, HEADERS, END_HEADERS, id, &headers).unwrap();
+            let mut data = vec![0];
+            data.extend((message.len() as u32).to_be_bytes());
+            data.extend(message);
+            write_frame(&mut stream, DATA, END_STREAM, id, &data).unwrap();
+            let mut reply = Vec::new();
+            loop {
+                let frame = read_frame(&mut stream).unwrap().unwrap();
+                if frame.stream != id {
+                    continue;
+                }
+                if frame.kind == DATA {
+                    assert!(frame.payload.len() <= 4);
+                    let increment = (frame.payload.len() as u32).to_be_bytes();
+                    write_frame(&mut stream, WINDOW_UPDATE, 0, id, &increment).unwrap();
+                    reply.extend(frame.payload);
+                } else if frame.kind == HEADERS {
+                    let headers = decoder.decode(&frame.payload).unwrap();
+                    if frame.flags & END_STREAM != 0 {
+                        let (_, status) = headers
+                            .into_iter()
+                            .find(|(name, _)| name == "grpc-status")
+                            .unwrap();
+                        return (status, reply);
+                    }
+                }
+            }
+        };
+        let empty = vec![0; 5];
+        assert_eq!(
+            call(1, "Fill", &[0x0a, 3, 0x08, 0xff, 0x01]),
+            ("0".into(), empty.clone())
+        );
+        assert_eq!(call(3, "DrawImage", &image), ("0".into(), empty.clone()));
+        assert_eq!(call(5, "Swap", &[]), ("0".into(), empty));
+        assert_eq!(call(7, "DrawText", &[0x0a, 2, b'H', b'i']).0, "5");
+        assert_eq!(call(9, "SetBrightness", &[0x08, 101]).0, "3");
+        let brightness = vec![0, 0, 0, 0, 2, 0x08, 40];
+        assert_eq!(call(11, "GetBrightness", &[]), ("0".into(), brightness));
+        assert_eq!(call(13, "Explode", &[]).0, "12");
+
+        // streams beyond the 16 announced are refused
+        for id in (15..47).step_by(2) {
+            let headers = headers(&path("Fill"));
+            write_frame(&mut stream, HEADERS, END_HEADERS, id, &headers).unwrap();
+        }
+        let headers = headers(&path("Fill"));
+        write_frame(&mut stream, HEADERS, END_HEADERS, 47, &headers).unwrap();
+        let refused = loop {
+            let frame = read_frame(&mut stream).unwrap().unwrap();
+            if frame.kind == RST_STREAM {
+                break frame;
+            }
+        };
+        assert_eq!((refused.stream, refused.payload), (47, vec![0, 0, 0, 7]));
+    }
+}
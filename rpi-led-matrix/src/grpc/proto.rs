@@ -0,0 +1,85 @@
+//! The protobuf wire format, as far as the messages of `proto/display.proto` need it.
+
+/// The value of a field, fixed width ones are skipped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Value<'a> {
+    pub(super) const fn varint(self) -> Option<u64> {
+        match self {
+            Self::Varint(value) => Some(value),
+            Self::Bytes(_) => None,
+        }
+    }
+
+    pub(super) const fn bytes(self) -> Option<&'a [u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            Self::Varint(_) => None,
+        }
+    }
+}
+
+const MALFORMED: &str = "Malformed protobuf message";
+
+fn varint(message: &[u8], position: &mut usize) -> Result<u64, &'static str> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *message.get(*position).ok_or(MALFORMED)?;
+        *position += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(MALFORMED)
+}
+
+/// The fields of `message` with their numbers, in the order sent.
+pub(super) fn fields(message: &[u8]) -> Result<Vec<(u64, Value<'_>)>, &'static str> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while position < message.len() {
+        let key = varint(message, &mut position)?;
+        let value = match key & 7 {
+            0 => Value::Varint(varint(message, &mut position)?),
+            2 => {
+                let length = usize::try_from(varint(message, &mut position)?).or(Err(MALFORMED))?;
+                let end = position.checked_add(length).ok_or(MALFORMED)?;
+                let bytes = message.get(position..end).ok_or(MALFORMED)?;
+                position = end;
+                Value::Bytes(bytes)
+            }
+            wire_type => {
+                position += match wire_type {
+                    1 => 8,
+                    5 => 4,
+                    _ => return Err(MALFORMED),
+                };
+                if position > message.len() {
+                    return Err(MALFORMED);
+                }
+                continue;
+            }
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(fields)
+}
+
+/// Appends a varint field, left out at its default of 0 as proto3 does.
+pub(super) fn put_varint(message: &mut Vec<u8>, field: u64, value: u64) {
+    if value == 0 {
+        return;
+    }
+    for mut byte in [field << 3, value] {
+        while byte >= 0x80 {
+            message.push(byte as u8 | 0x80);
+            byte >>= 7;
+        }
+        message.push(byte as u8);
+    }
+}
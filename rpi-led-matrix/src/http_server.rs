@@ -0,0 +1,140 @@
+//! The little of HTTP/1.1 the preview and metrics servers need: one request per connection,
+//! read up to a size limit, and a bounded number of connections answered at once, which the
+//! gRPC server and the control socket of apps share.
+use std::io::ErrorKind;
+#[cfg(any(feature = "preview", feature = "metrics"))]
+use std::io::{self, BufRead, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Bytes of the request line and headers read, the rest of a longer head is left unread
+#[cfg(any(feature = "preview", feature = "metrics"))]
+const MAX_HEAD: u64 = 8 << 10;
+
+/// The path and headers of a request
+#[cfg(any(feature = "preview", feature = "metrics"))]
+pub(crate) struct Request {
+    pub(crate) path: String,
+    /// Names in lower case, with their values, only looked at by the preview
+    #[cfg_attr(not(feature = "preview"), allow(dead_code))]
+    headers: Vec<(String, String)>,
+}
+
+#[cfg(any(feature = "preview", feature = "metrics"))]
+impl Request {
+    /// Reads the request line and the headers up to the empty line ending them.
+    pub(crate) fn read(reader: &mut impl BufRead) -> io::Result<Self> {
+        let mut head = reader.take(MAX_HEAD);
+        let mut line = String::new();
+        head.read_line(&mut line)?;
+        let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            if head.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        Ok(Self { path, headers })
+    }
+
+    /// The value of the header `name`, given in lower case
+    #[cfg_attr(not(feature = "preview"), allow(dead_code))]
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Answers with `status`, e.g. `200 OK`, and the body, closing the connection after.
+#[cfg(any(feature = "preview", feature = "metrics"))]
+pub(crate) fn respond(
+    stream: &mut impl Write,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Hands every connection to `handle` on a thread of its own, as long as fewer than
+/// `max_clients` are open. Further connections are closed right away.
+///
+/// Returns when accepting fails for another reason than a client giving up.
+pub(crate) fn serve(
+    listener: &TcpListener,
+    max_clients: usize,
+    handle: impl Fn(TcpStream) + Clone + Send + 'static,
+) {
+    let clients = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    ErrorKind::ConnectionAborted
+                        | ErrorKind::ConnectionReset
+                        | ErrorKind::Interrupted
+                ) =>
+            {
+                continue
+            }
+            Err(_) => return,
+        };
+        if clients.fetch_add(1, Ordering::SeqCst) >= max_clients {
+            clients.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+        let (handle, clients) = (handle.clone(), Arc::clone(&clients));
+        std::thread::spawn(move || {
+            handle(stream);
+            clients.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+#[cfg(all(test, any(feature = "preview", feature = "metrics")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request() {
+        let mut head: &[u8] =
+            b"GET /stream HTTP/1.1\r\nHost: panel\r\nSec-WebSocket-Key: abc\r\n\r\nrest";
+        let request = Request::read(&mut head).unwrap();
+        assert_eq!(request.path, "/stream");
+        assert_eq!(request.header("sec-websocket-key"), Some("abc"));
+        assert_eq!(request.header("upgrade"), None);
+        assert_eq!(head, b"rest");
+
+        let mut answer = Vec::new();
+        respond(&mut answer, "404 Not Found", "text/plain", "").unwrap();
+        assert!(answer.starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+        assert!(answer.ends_with(b"Content-Length: 0\r\nConnection: close\r\n\r\n"));
+
+        // a client past the limit is closed without an answer
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            serve(&listener, 1, |mut stream| {
+                let _ = stream.read(&mut [0]);
+            });
+        });
+        let mut first = TcpStream::connect(address).unwrap();
+        let mut second = TcpStream::connect(address).unwrap();
+        assert_eq!(second.read(&mut [0]).unwrap(), 0);
+        first.write_all(b"x").unwrap();
+    }
+}
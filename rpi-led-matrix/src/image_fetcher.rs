@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+use crate::LedImage;
+
+/// Downloads images over HTTP, e.g. webcam snapshots or rendered charts for a dashboard.
+///
+/// Downloaded images are cached by their `ETag`, so polling an unchanged image only costs a
+/// `304 Not Modified` response. Images larger than the size limit are refused.
+///
+/// ```no_run
+/// use rpi_led_matrix::ImageFetcher;
+/// use std::time::Duration;
+/// let mut fetcher = ImageFetcher::new()
+///     .timeout(Duration::from_secs(3))
+///     .max_size(512 * 1024);
+/// let image = fetcher.fetch("http://camera.local/snapshot.jpg").unwrap();
+/// ```
+pub struct ImageFetcher {
+    timeout: Duration,
+    max_size: u64,
+    cache: HashMap<String, (String, LedImage)>,
+}
+
+impl ImageFetcher {
+    /// Creates a fetcher with a timeout of 10 seconds and a size limit of 4 MiB.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_size: 4 * 1024 * 1024,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Sets the timeout for a whole request
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the largest image file in bytes that is downloaded
+    #[must_use]
+    pub const fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Downloads and decodes the image at `url`, or returns the cached one if it's unchanged.
+    ///
+    /// # Errors
+    /// - If the request fails or times out.
+    /// - If the image is larger than the size limit.
+    /// - If the image can't be decoded.
+    pub fn fetch(&mut self, url: &str) -> Result<LedImage, &'static str> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(self.timeout)
+            .user_agent(concat!("rpi-led-matrix/", env!("CARGO_PKG_VERSION")))
+            .build();
+        let mut request = agent.get(url);
+        if let Some((etag, _)) = self.cache.get(url) {
+            request = request.set("If-None-Match", etag);
+        }
+        let response = request.call().map_err(|_| "Couldn't fetch image")?;
+
+        if response.status() == 304 {
+            if let Some((_, image)) = self.cache.get(url) {
+                return Ok(image.clone());
+            }
+        }
+        let too_large = response
+            .header("Content-Length")
+            .and_then(|length| length.parse::<u64>().ok())
+            .is_some_and(|length| length > self.max_size);
+        if too_large {
+            return Err("Image exceeds the size limit");
+        }
+        let etag = response.header("ETag").map(str::to_string);
+
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_size + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|_| "Couldn't read image")?;
+        if bytes.len() as u64 > self.max_size {
+            return Err("Image exceeds the size limit");
+        }
+        let image = LedImage::from_bytes(&bytes)?;
+        match etag {
+            Some(etag) => {
+                self.cache.insert(url.to_string(), (etag, image.clone()));
+            }
+            None => {
+                self.cache.remove(url);
+            }
+        }
+        Ok(image)
+    }
+}
+
+impl Default for ImageFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LedImage {
+    /// Downloads and decodes the image at `url`, using a shared [`ImageFetcher`] with the
+    /// default timeout and size limit.
+    ///
+    /// # Errors
+    /// See [`ImageFetcher::fetch`].
+    pub fn from_url(url: &str) -> Result<Self, &'static str> {
+        static FETCHER: OnceLock<Mutex<ImageFetcher>> = OnceLock::new();
+        FETCHER
+            .get_or_init(|| Mutex::new(ImageFetcher::new()))
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .fetch(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageEncoder;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut bytes)
+            .write_image(&[255, 0, 0], 1, 1, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        bytes
+    }
+
+    /// Serves `png()` with an ETag for `requests` requests, returning the base URL.
+    fn serve(requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut cached = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    cached |= line.to_lowercase().starts_with("if-none-match: \"v1\"");
+                }
+                let body = if cached { Vec::new() } else { png() };
+                let status = if cached { "304 Not Modified" } else { "200 OK" };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn etag_cache() {
+        let url = serve(2);
+        let mut fetcher = ImageFetcher::new();
+        let image = fetcher.fetch(&url).unwrap();
+        assert_eq!(image.width(), 1);
+        assert_eq!(fetcher.fetch(&url).unwrap(), image);
+    }
+
+    #[test]
+    fn size_limit() {
+        let url = serve(1);
+        let mut fetcher = ImageFetcher::new().max_size(8);
+        assert_eq!(fetcher.fetch(&url), Err("Image exceeds the size limit"));
+    }
+}
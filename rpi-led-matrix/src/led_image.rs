@@ -0,0 +1,522 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader, Rgb, RgbImage};
+
+use crate::{Canvas, LedCanvas, LedColor, Transform};
+
+/// How the orientation of an image is determined when decoding it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageOrientation {
+    /// Rotate and mirror as the EXIF orientation tag says, like photos from phones need
+    #[default]
+    Exif,
+    /// Keep the pixels as stored, ignoring any EXIF orientation
+    AsStored,
+    /// Ignore the EXIF orientation and apply the given transform instead
+    Fixed(Transform),
+}
+
+/// A decoded image, ready to be drawn onto a [`LedCanvas`].
+///
+/// ```
+/// use rpi_led_matrix::{LedImage, LedMatrix};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let image = LedImage::open(Path::new("photo.jpg"))
+///     .unwrap()
+///     .fit(canvas.canvas_size())
+///     .equalize(24);
+/// canvas.draw_image(&image, 0, 0);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedImage {
+    buffer: RgbImage,
+}
+
+impl LedImage {
+    /// Loads an image file, guessing the format from its contents.
+    ///
+    /// Photos are turned upright according to their EXIF orientation.
+    ///
+    /// # Errors
+    /// If the file can't be read or decoded.
+    pub fn open(path: &Path) -> Result<Self, &'static str> {
+        Self::open_with(path, ImageOrientation::Exif)
+    }
+
+    /// Loads an image file like [`open`](Self::open), orienting it as given.
+    ///
+    /// # Errors
+    /// If the file can't be read or decoded.
+    pub fn open_with(path: &Path, orientation: ImageOrientation) -> Result<Self, &'static str> {
+        let bytes = std::fs::read(path).map_err(|_| "Couldn't read image file")?;
+        Self::from_bytes_with(&bytes, orientation)
+    }
+
+    /// Decodes an image from the contents of an image file.
+    ///
+    /// Photos are turned upright according to their EXIF orientation.
+    ///
+    /// # Errors
+    /// If the format isn't supported or the image can't be decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        Self::from_bytes_with(bytes, ImageOrientation::Exif)
+    }
+
+    /// Decodes an image like [`from_bytes`](Self::from_bytes), orienting it as given.
+    ///
+    /// # Errors
+    /// If the format isn't supported or the image can't be decoded.
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        orientation: ImageOrientation,
+    ) -> Result<Self, &'static str> {
+        let (image, transform) = decode(bytes, orientation)?;
+        let image = Self {
+            buffer: image.to_rgb8(),
+        };
+        Ok(match transform {
+            Some(transform) => image.transform(transform),
+            None => image,
+        })
+    }
+
+    /// Creates an image from its pixels, row by row.
+    ///
+    /// # Errors
+    /// If the number of pixels doesn't match the size.
+    pub fn from_pixels(width: u32, height: u32, pixels: &[LedColor]) -> Result<Self, &'static str> {
+        if pixels.len() != width as usize * height as usize {
+            return Err("Number of pixels doesn't match the image size");
+        }
+        let raw = pixels
+            .iter()
+            .flat_map(|c| [c.red, c.green, c.blue])
+            .collect();
+        let buffer = RgbImage::from_raw(width, height, raw).ok_or("Invalid image size")?;
+        Ok(Self { buffer })
+    }
+
+    /// Width of the image in pixels
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.buffer.width()
+    }
+
+    /// Height of the image in pixels
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.buffer.height()
+    }
+
+    /// Writes the image to a file, in the format of its extension like `.png`.
+    ///
+    /// # Errors
+    /// If the format isn't supported or the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        self.buffer
+            .save(path)
+            .map_err(|_| "Couldn't write image file")
+    }
+
+    /// The color of the pixel at the given coordinate, if it is within the image.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<LedColor> {
+        self.buffer
+            .get_pixel_checked(x, y)
+            .map(|&Rgb([red, green, blue])| LedColor { red, green, blue })
+    }
+
+    /// Rotates and mirrors the image, the same way a [`Transform`] maps a canvas onto the panel.
+    #[must_use]
+    pub fn transform(&self, transform: Transform) -> Self {
+        let size = (self.width() as i32, self.height() as i32);
+        // the size of the result is what the transform considers the device
+        let (width, height) = transform.logical_size(size);
+        let mut buffer = RgbImage::new(width as u32, height as u32);
+        for (x, y, pixel) in self.buffer.enumerate_pixels() {
+            let (x, y) = transform.apply(x as i32, y as i32, (width, height));
+            buffer.put_pixel(x as u32, y as u32, *pixel);
+        }
+        Self { buffer }
+    }
+
+    /// Scales the image to exactly the given size.
+    #[must_use]
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        Self {
+            buffer: image::imageops::resize(&self.buffer, width, height, FilterType::Triangle),
+        }
+    }
+
+    /// Scales the image to the largest size fitting into `size`, keeping its aspect ratio.
+    ///
+    /// Takes the size as returned by [`LedCanvas::canvas_size`].
+    #[must_use]
+    pub fn fit(&self, size: (i32, i32)) -> Self {
+        let (max_width, max_height) = (size.0.max(1) as u64, size.1.max(1) as u64);
+        let (width, height) = (u64::from(self.width()), u64::from(self.height()));
+        // compare max_width / width against max_height / height without rounding
+        let (width, height) = if max_width * height <= max_height * width {
+            (max_width, (height * max_width / width.max(1)).max(1))
+        } else {
+            ((width * max_height / height.max(1)).max(1), max_height)
+        };
+        self.resize(width as u32, height as u32)
+    }
+
+    /// Perceived brightness of a pixel, 0-255
+    fn luma(pixel: &Rgb<u8>) -> u32 {
+        let [red, green, blue] = pixel.0.map(u32::from);
+        (299 * red + 587 * green + 114 * blue) / 1000
+    }
+
+    /// Scales every pixel so its brightness becomes `map[brightness]`, keeping its hue.
+    fn remap_luma(&self, map: &[u8; 256]) -> Self {
+        let mut buffer = self.buffer.clone();
+        for pixel in buffer.pixels_mut() {
+            let luma = Self::luma(pixel);
+            if luma == 0 {
+                continue;
+            }
+            let target = u32::from(map[luma as usize]);
+            *pixel = Rgb(pixel
+                .0
+                .map(|c| (u32::from(c) * target / luma).min(255) as u8));
+        }
+        Self { buffer }
+    }
+
+    /// Stretches the brightness so the darkest and brightest percent of pixels become black
+    /// and white.
+    #[must_use]
+    pub fn auto_levels(&self) -> Self {
+        let mut lumas: Vec<u32> = self.buffer.pixels().map(Self::luma).collect();
+        if lumas.is_empty() {
+            return self.clone();
+        }
+        lumas.sort_unstable();
+        let low = lumas[lumas.len() / 100];
+        let high = lumas[lumas.len() - 1 - lumas.len() / 100];
+        if high <= low {
+            return self.clone();
+        }
+        let mut map = [0; 256];
+        for (luma, target) in map.iter_mut().enumerate() {
+            *target = ((luma as u32).saturating_sub(low) * 255 / (high - low)).min(255) as u8;
+        }
+        self.remap_luma(&map)
+    }
+
+    /// Equalizes the brightness histogram, so dark photos stay visible on a dim panel.
+    ///
+    /// LED panels can't show levels below a certain threshold, which rises as brightness or
+    /// PWM bits are reduced. Everything that isn't black is lifted to at least `min_level`,
+    /// the lowest level the panel still shows in its configuration.
+    #[must_use]
+    pub fn equalize(&self, min_level: u8) -> Self {
+        let mut histogram = [0_u64; 256];
+        for pixel in self.buffer.pixels() {
+            histogram[Self::luma(pixel) as usize] += 1;
+        }
+        // black stays black, so it doesn't take part in the distribution
+        let total: u64 = histogram[1..].iter().sum();
+        if total == 0 {
+            return self.clone();
+        }
+        let min_level = u64::from(min_level);
+        let mut map = [0; 256];
+        let mut cumulative = 0;
+        for luma in 1..256 {
+            cumulative += histogram[luma];
+            map[luma] = (min_level + cumulative * (255 - min_level) / total) as u8;
+        }
+        self.remap_luma(&map)
+    }
+
+    /// Reduces every color channel to `bits` bits with an ordered (Bayer) dither, so
+    /// gradients stay smooth on panels running with fewer PWM bits.
+    ///
+    /// Unlike [`LedMatrix::set_dithering`](crate::LedMatrix::set_dithering), the pattern is
+    /// fixed in place and doesn't flicker, which suits still images and screenshots.
+    #[must_use]
+    pub fn dither(&self, bits: u8) -> Self {
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        if bits == 0 || bits >= 8 {
+            return self.clone();
+        }
+        let levels = f32::from((1_u8 << bits) - 1);
+        let mut buffer = self.buffer.clone();
+        for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+            let threshold = (f32::from(BAYER[y as usize % 4][x as usize % 4]) + 0.5) / 16.0;
+            for channel in &mut pixel.0 {
+                let scaled = f32::from(*channel) / 255.0 * levels;
+                let level = scaled.floor() + f32::from(u8::from(scaled.fract() > threshold));
+                *channel = (level * 255.0 / levels).round() as u8;
+            }
+        }
+        Self { buffer }
+    }
+
+    /// Draws the image onto any canvas with its top left corner at the given coordinate.
+    pub fn draw(&self, canvas: &mut impl Canvas, x: i32, y: i32) {
+        canvas.set_iter(self.buffer.enumerate_pixels().map(
+            |(px, py, &Rgb([red, green, blue]))| {
+                (x + px as i32, y + py as i32, LedColor { red, green, blue })
+            },
+        ));
+    }
+}
+
+/// Decodes an image file, applying its EXIF orientation if asked to, or returning the fixed
+/// transform still to be applied.
+pub(crate) fn decode(
+    bytes: &[u8],
+    orientation: ImageOrientation,
+) -> Result<(DynamicImage, Option<Transform>), &'static str> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| "Couldn't read image")?
+        .into_decoder()
+        .map_err(|_| "Unsupported image format")?;
+    let exif = decoder.orientation().ok();
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|_| "Couldn't decode image")?;
+    match orientation {
+        ImageOrientation::Exif => {
+            if let Some(exif) = exif {
+                image.apply_orientation(exif);
+            }
+            Ok((image, None))
+        }
+        ImageOrientation::AsStored => Ok((image, None)),
+        ImageOrientation::Fixed(transform) => Ok((image, Some(transform))),
+    }
+}
+
+impl LedCanvas {
+    /// Draws an image with its top left corner at the given coordinate.
+    pub fn draw_image(&mut self, image: &LedImage, x: i32, y: i32) {
+        image.draw(self, x, y);
+    }
+
+    /// The pixels drawn onto the canvas as an image, as laid out on the panel, i.e. without
+    /// the current [`Transform`] undone.
+    #[must_use]
+    pub fn to_image(&self) -> LedImage {
+        let (width, height) = self.device_size;
+        LedImage::from_pixels(width as u32, height as u32, &self.shadow)
+            .expect("the shadow copy matches the canvas size")
+    }
+
+    /// Writes the pixels drawn onto the canvas to a PNG file, see [`to_image`](Self::to_image).
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    pub fn to_png(&self, path: &Path) -> Result<(), &'static str> {
+        self.to_image().save(path)
+    }
+
+    /// Compares the canvas to the image at `path`, returning the coordinates of the pixels that
+    /// differ.
+    ///
+    /// # Errors
+    /// If the image can't be read, or its size differs from the canvas.
+    pub fn diff_png(&self, path: &Path) -> Result<Vec<(i32, i32)>, &'static str> {
+        let expected = LedImage::open_with(path, ImageOrientation::AsStored)?;
+        let actual = self.to_image();
+        if (expected.width(), expected.height()) != (actual.width(), actual.height()) {
+            return Err("Image size differs from the canvas");
+        }
+        Ok(actual
+            .buffer
+            .enumerate_pixels()
+            .filter(|&(x, y, pixel)| expected.buffer.get_pixel(x, y) != pixel)
+            .map(|(x, y, _)| (x as i32, y as i32))
+            .collect())
+    }
+
+    /// Asserts that the canvas looks like the golden image at `path`, for tests of drawing
+    /// code on a [headless](Self::headless) canvas.
+    ///
+    /// A missing golden image is written from the canvas instead, as is every golden image
+    /// while the environment variable `UPDATE_GOLDEN` is set, so they are created and updated
+    /// by running the tests.
+    ///
+    /// ```no_run
+    /// use rpi_led_matrix::{LedCanvas, LedColor};
+    /// use std::path::Path;
+    /// let mut canvas = LedCanvas::headless(16, 16);
+    /// canvas.draw_circle(8, 8, 6, &LedColor { red: 0, green: 255, blue: 0 });
+    /// canvas.assert_png(Path::new("tests/golden/circle.png"));
+    /// ```
+    ///
+    /// # Panics
+    /// If the canvas differs from the golden image, or it can't be read or written.
+    #[track_caller]
+    pub fn assert_png(&self, path: &Path) {
+        if !path.exists() || std::env::var_os("UPDATE_GOLDEN").is_some() {
+            if let Some(directory) = path.parent() {
+                let _ = std::fs::create_dir_all(directory);
+            }
+            if let Err(error) = self.to_png(path) {
+                panic!("{error}: {}", path.display());
+            }
+            return;
+        }
+        match self.diff_png(path) {
+            Ok(differences) if differences.is_empty() => {}
+            Ok(differences) => panic!(
+                "{} pixels differ from {}, the first at {:?}",
+                differences.len(),
+                path.display(),
+                differences[0]
+            ),
+            Err(error) => panic!("{error}: {}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(level: u8) -> LedColor {
+        LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        }
+    }
+
+    fn dark_gradient() -> LedImage {
+        let pixels: Vec<_> = (0..16).map(|level| gray(level * 2)).collect();
+        LedImage::from_pixels(4, 4, &pixels).unwrap()
+    }
+
+    #[test]
+    fn pixels() {
+        let image = dark_gradient();
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.get(1, 1), Some(gray(10)));
+        assert_eq!(image.get(4, 0), None);
+        assert!(LedImage::from_pixels(2, 2, &[gray(0)]).is_err());
+        assert!(LedImage::from_bytes(b"not an image").is_err());
+    }
+
+    /// A 2x1 PNG of a red and a green pixel, tagged with the given EXIF orientation
+    fn tagged_png(orientation: u16) -> Vec<u8> {
+        use image::{ImageEncoder, ImageFormat};
+        let mut exif = vec![
+            b'M', b'M', 0, 42, 0, 0, 0, 8, 0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1,
+        ];
+        exif.extend_from_slice(&orientation.to_be_bytes());
+        exif.extend_from_slice(&[0; 6]);
+        let mut bytes = Vec::new();
+        let mut encoder = image::codecs::png::PngEncoder::new(&mut bytes);
+        encoder.set_exif_metadata(exif).unwrap();
+        encoder
+            .write_image(
+                &[255, 0, 0, 0, 255, 0],
+                2,
+                1,
+                image::ExtendedColorType::Rgb8,
+            )
+            .unwrap();
+        assert_eq!(image::guess_format(&bytes).unwrap(), ImageFormat::Png);
+        bytes
+    }
+
+    #[test]
+    fn orientation() {
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        // 6: rotated 90° clockwise
+        let image = LedImage::from_bytes(&tagged_png(6)).unwrap();
+        assert_eq!((image.width(), image.height()), (1, 2));
+        assert_eq!(image.get(0, 0), Some(red));
+
+        let image = LedImage::from_bytes_with(&tagged_png(6), ImageOrientation::AsStored).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+
+        let flip = Transform::new().mirror_horizontal(true);
+        let image =
+            LedImage::from_bytes_with(&tagged_png(6), ImageOrientation::Fixed(flip)).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.get(1, 0), Some(red));
+    }
+
+    #[test]
+    fn transform() {
+        let image = dark_gradient();
+        let rotated = image.transform(Transform::new().rotation(crate::Rotation::Rotate90));
+        // the bottom left corner moves to the top left
+        assert_eq!(rotated.get(0, 0), image.get(0, 3));
+        assert_eq!(rotated.transform(Transform::new()), rotated);
+    }
+
+    #[test]
+    fn fit() {
+        let image = LedImage::from_pixels(8, 4, &[gray(0); 32]).unwrap();
+        let fitted = image.fit((64, 64));
+        assert_eq!((fitted.width(), fitted.height()), (64, 32));
+        let fitted = image.fit((16, 4));
+        assert_eq!((fitted.width(), fitted.height()), (8, 4));
+    }
+
+    #[test]
+    fn auto_levels() {
+        let image = dark_gradient().auto_levels();
+        assert_eq!(image.get(0, 0), Some(gray(0)));
+        assert_eq!(image.get(3, 3), Some(gray(255)));
+    }
+
+    #[test]
+    fn dither() {
+        let image = LedImage::from_pixels(4, 4, &[gray(128); 16])
+            .unwrap()
+            .dither(1);
+        let lit = (0..16)
+            .filter(|i| image.get(i % 4, i / 4) == Some(gray(255)))
+            .count();
+        let dark = (0..16)
+            .filter(|i| image.get(i % 4, i / 4) == Some(gray(0)))
+            .count();
+        assert_eq!((lit, dark), (8, 8));
+        assert_eq!(dark_gradient().dither(8), dark_gradient());
+    }
+
+    #[test]
+    fn equalize() {
+        let image = dark_gradient().equalize(32);
+        assert_eq!(image.get(0, 0), Some(gray(0)));
+        assert!(image.get(1, 0).unwrap().red >= 32);
+        assert_eq!(image.get(3, 3), Some(gray(255)));
+        let levels: Vec<_> = (0..16)
+            .map(|i| image.get(i % 4, i / 4).unwrap().red)
+            .collect();
+        assert!(levels.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn golden() {
+        let mut canvas = LedCanvas::headless(4, 3);
+        canvas.fill(&gray(10));
+        canvas.draw_line(0, 0, 3, 0, &gray(200));
+        canvas.set_transform(Transform::new().rotation(crate::Rotation::Rotate90));
+        assert_eq!(canvas.canvas_size(), (3, 4));
+        let path = std::env::temp_dir().join(format!("golden-{}.png", std::process::id()));
+        canvas.assert_png(&path);
+        canvas.assert_png(&path);
+        assert_eq!(LedImage::open(&path).unwrap().get(3, 0), Some(gray(200)));
+        canvas.set(0, 1, &gray(0));
+        assert_eq!(canvas.diff_png(&path), Ok(vec![(2, 0)]));
+        std::fs::remove_file(&path).unwrap();
+        assert!(canvas.diff_png(&path).is_err());
+    }
+}
@@ -16,6 +16,10 @@
 //! }
 //! ```
 //!
+//! Colors, geometry and the [`Canvas`] trait come from
+//! [`rpi-led-matrix-core`](rpi_led_matrix_core), which is `no_std`, so effects and widgets
+//! drawing on any [`Canvas`] also run on microcontroller LED projects.
+//!
 //! # Features
 //!
 //! ## `embeddedgraphics` (default)
@@ -29,40 +33,390 @@
 //! Pulls in [`clap`], enabling the [`args`](self::args) module which adds LED matrix arguments for
 //! configuration to your [`clap::App`].
 //!
+//! ## `online-widgets`
+//!
+//! Enables [`widgets`] that fetch their content over the network, like
+//! [`weather_alerts`](widgets::weather_alerts).
+//!
+//! ## `clock`
+//!
+//! Pulls in [`chrono`] for the local time, enabling the [`clock`](widgets::clock) widget.
+//!
+//! ## `image`
+//!
+//! Pulls in [`image`](::image) to decode photos and other images into a [`LedImage`].
+//!
+//! ## `http`
+//!
+//! Enables the `image` feature and pulls in [`ureq`] to download images with an
+//! [`ImageFetcher`] or [`LedImage::from_url`].
+//!
+//! ## `ttf`
+//!
+//! Pulls in [`fontdue`] to load TrueType and OpenType fonts via [`LedFont::from_ttf`].
+//!
+//! ## `home-assistant`
+//!
+//! Pulls in [`serde_json`] and [`ureq`], enabling the
+//! [`home_assistant`](widgets::home_assistant) widget showing the state of an entity.
+//!
+//! ## `ambient`
+//!
+//! Pulls in [`ureq`] to sync Philips Hue and WLED lights with the panel via [`AmbientSync`].
+//!
+//! ## `bundled-fonts`
+//!
+//! Embeds a selection of the BDF fonts shipped with the C++ library, cut down to ASCII and
+//! Latin-1, available in [`fonts`].
+//!
+//! ## `unicode`
+//!
+//! Pulls in [`unicode-segmentation`](unicode_segmentation) and [`unicode-bidi`](unicode_bidi)
+//! to draw text by grapheme clusters, shape Arabic letters and lay out right-to-left text,
+//! e.g. Hebrew and Arabic, in display order. Without it, only common combining marks are
+//! placed on the character before them.
+//!
+//! ## `qrcode`
+//!
+//! Pulls in [`qrcode`] to draw QR codes, e.g. for WiFi credentials or URLs, with
+//! [`LedCanvas::draw_qr`].
+//!
+//! ## `audio`
+//!
+//! Pulls in [`cpal`] to capture a microphone or the system's audio into a [`Spectrum`] with an
+//! [`AudioInput`], e.g. for visualizers.
+//!
+//! ## `video`
+//!
+//! Enables [`Video`] playing video files and streams, decoded by the `ffmpeg` command line
+//! tool, which has to be installed.
+//!
+//! ## `svg`
+//!
+//! Pulls in [`resvg`] to rasterize SVG icons and logos at the resolution of the panel with
+//! [`Surface::from_svg`].
+//!
+//! ## `aseprite`
+//!
+//! Pulls in [`flate2`] to load sprites made with Aseprite, with their frames, durations and
+//! tags, as an [`Animation`].
+//!
+//! ## `recorder`
+//!
+//! Implies `image` and pulls in [`png`] to record what the panel shows and export it as an
+//! animated GIF or PNG with a [`Recorder`].
+//!
+//! ## `midi`
+//!
+//! Pulls in [`alsa`] to map notes and controllers of a MIDI controller, received through an
+//! ALSA sequencer port, to commands and values of a [`MatrixApp`] with [`MidiRoutes`].
+//!
+//! ## `osc`
+//!
+//! Adds an Open Sound Control server to [`MatrixApp`], mapping messages of VJ and lighting
+//! tools to commands and values with [`OscRoutes`].
+//!
+//! ## `preview`
+//!
+//! Adds a [`PreviewServer`] streaming every swapped frame over a WebSocket to a viewer in the
+//! browser, to watch panels mounted out of sight.
+//!
+//! ## `artnet`
+//!
+//! Adds an [`ArtNet`] receiver mapping DMX universes to pixels, so lighting consoles can
+//! drive the matrix as a fixture.
+//!
+//! ## `sacn`
+//!
+//! Adds a [`Sacn`] receiver for E1.31 streaming ACN, so show software like xLights can drive
+//! the matrix with multicast universes mapped to pixels by a [`DmxLayout`].
+//!
+//! ## `ddp`
+//!
+//! Adds a [`Ddp`] receiver for the Distributed Display Protocol, so software streaming to WLED
+//! controllers like LedFx can drive the matrix.
+//!
+//! ## `metrics`
+//!
+//! Adds [`Metrics`], counting swapped and dropped frames and timing renders, exported in the
+//! text format of Prometheus over HTTP for monitoring installations.
+//!
+//! ## `grpc`
+//!
+//! Adds a [`GrpcServer`] serving a gRPC display service, so applications in any language can
+//! draw text and images, fill, swap and set the brightness over the network.
+//!
 //! ## `c-stubs`
 //!
 //! Passthrough argument to [`rpi-led-matrix-sys`](rpi_led_matrix_sys). See their documentation for more info.
 //!
+//! ## `simulator-terminal`
+//!
+//! Passthrough argument to [`rpi-led-matrix-sys`](rpi_led_matrix_sys). [`LedMatrix::new`]
+//! then shows the panel in the terminal, for developing without a Raspberry Pi. See their
+//! documentation for more info.
+//!
+//! ## `simulator-window`
+//!
+//! Passthrough argument to [`rpi-led-matrix-sys`](rpi_led_matrix_sys). [`LedMatrix::new`]
+//! then opens a window showing the panel, for developing without a Raspberry Pi. See their
+//! documentation for more info.
+//!
 //! ## `stdcpp-static-link`
 //!
 //! Passthrough argument to [`rpi-led-matrix-sys`](rpi_led_matrix_sys). See their documentation for more info.
 extern crate libc;
 
+#[deny(missing_docs)]
+mod aging;
+#[cfg(feature = "ambient")]
+#[deny(missing_docs)]
+mod ambient;
+#[deny(missing_docs)]
+mod app;
 #[cfg(feature = "args")]
 #[deny(missing_docs)]
 pub mod args;
+#[cfg(feature = "artnet")]
+#[deny(missing_docs)]
+mod artnet;
+#[cfg(feature = "aseprite")]
+#[deny(missing_docs)]
+mod aseprite;
+#[deny(missing_docs)]
+mod assets;
+#[cfg(feature = "audio")]
+#[deny(missing_docs)]
+mod audio;
 #[deny(missing_docs)]
 mod canvas;
 #[deny(missing_docs)]
+pub mod compat;
+#[deny(missing_docs)]
+mod cue;
+#[deny(missing_docs)]
+mod daemon;
+#[cfg(feature = "ddp")]
+#[deny(missing_docs)]
+mod ddp;
+#[deny(missing_docs)]
+mod dither;
+#[cfg(any(feature = "artnet", feature = "sacn"))]
+#[deny(missing_docs)]
+mod dmx;
+#[deny(missing_docs)]
+mod draw;
+#[deny(missing_docs)]
+pub mod effects;
+#[deny(missing_docs)]
+pub mod flaschen_taschen;
+#[deny(missing_docs)]
 mod font;
 #[deny(missing_docs)]
-mod led_color;
+mod font_set;
+#[cfg(feature = "bundled-fonts")]
+#[deny(missing_docs)]
+pub mod fonts;
+#[deny(missing_docs)]
+mod frame;
+#[deny(missing_docs)]
+mod game;
+#[cfg(feature = "grpc")]
+#[deny(missing_docs)]
+mod grpc;
+#[deny(missing_docs)]
+mod http_server;
+#[cfg(feature = "http")]
+#[deny(missing_docs)]
+mod image_fetcher;
+#[cfg(feature = "image")]
+#[deny(missing_docs)]
+mod led_image;
 #[deny(missing_docs)]
 mod matrix;
 #[deny(missing_docs)]
+mod message_queue;
+#[cfg(feature = "metrics")]
+#[deny(missing_docs)]
+mod metrics;
+#[cfg(feature = "midi")]
+#[deny(missing_docs)]
+mod midi;
+#[deny(missing_docs)]
+mod mirror;
+#[deny(missing_docs)]
 mod options;
+#[cfg(feature = "osc")]
+#[deny(missing_docs)]
+mod osc;
+#[deny(missing_docs)]
+mod palette;
+#[deny(missing_docs)]
+mod patch;
+#[cfg(feature = "preview")]
+#[deny(missing_docs)]
+mod preview;
+#[cfg(feature = "qrcode")]
+#[deny(missing_docs)]
+mod qr;
+#[deny(missing_docs)]
+mod race;
+#[cfg(feature = "recorder")]
+#[deny(missing_docs)]
+mod recorder;
+#[deny(missing_docs)]
+mod remote;
+#[cfg(feature = "sacn")]
+#[deny(missing_docs)]
+mod sacn;
+#[deny(missing_docs)]
+mod scene;
+#[deny(missing_docs)]
+mod solar;
+#[deny(missing_docs)]
+mod spectrum;
+#[deny(missing_docs)]
+mod sprite;
+#[deny(missing_docs)]
+mod stats_log;
+#[deny(missing_docs)]
+mod surface;
+#[cfg(feature = "svg")]
+#[deny(missing_docs)]
+mod svg;
+#[deny(missing_docs)]
+mod text_effect;
+#[deny(missing_docs)]
+mod timecode;
+#[deny(missing_docs)]
+mod transition;
+#[deny(missing_docs)]
+mod unicode;
+#[cfg(feature = "video")]
+#[deny(missing_docs)]
+mod video;
+#[deny(missing_docs)]
+mod watermark;
+#[deny(missing_docs)]
+pub mod widgets;
+#[deny(missing_docs)]
+pub mod wizard;
 
 // import all of the C FFI functions
 pub(crate) use rpi_led_matrix_sys as ffi;
+// the drawing shared with the hardware independent core
+use rpi_led_matrix_core::raster;
+
+// re-export the core to the root
+#[doc(inline)]
+pub use rpi_led_matrix_core::{
+    Canvas, CoordinateSpace, LedColor, LedColorA, Plot, Rect, Rotation, Transform, View,
+};
 
 // re-export objects to the root
 #[doc(inline)]
-pub use canvas::{LedCanvas, TextDrawOptions, TextLayout};
+pub use aging::{Compensation, WearTracker};
+#[cfg(feature = "ambient")]
+#[doc(inline)]
+pub use ambient::{AmbientLight, AmbientSync};
+#[doc(inline)]
+pub use app::{AppCommand, AppControl, MatrixApp, MatrixAppBuilder};
+#[cfg(feature = "artnet")]
+#[doc(inline)]
+pub use artnet::{ArtNet, ARTNET_PORT};
+#[cfg(feature = "aseprite")]
+#[doc(inline)]
+pub use aseprite::{Animation, PlayDirection, Tag};
 #[doc(inline)]
-pub use font::LedFont;
+pub use assets::{AssetLoader, Variants};
+#[cfg(feature = "audio")]
 #[doc(inline)]
-pub use led_color::LedColor;
+pub use audio::AudioInput;
+#[doc(inline)]
+pub use canvas::{Align, BoundsMode, LedCanvas, SubCanvas, TextDrawOptions, TextLayout};
+#[doc(inline)]
+pub use cue::{CueAction, CueList};
+#[doc(inline)]
+pub use daemon::daemonize;
+#[cfg(feature = "ddp")]
+#[doc(inline)]
+pub use ddp::{Ddp, DDP_PORT};
+#[cfg(any(feature = "artnet", feature = "sacn"))]
+#[doc(inline)]
+pub use dmx::DmxLayout;
+#[doc(inline)]
+pub use draw::TextCanvas;
+#[doc(inline)]
+pub use font::{FontStack, LedFont};
+#[doc(inline)]
+pub use font_set::FontSet;
+#[doc(inline)]
+pub use frame::Frame;
+#[doc(inline)]
+pub use game::{Game, GameLoop};
+#[cfg(feature = "grpc")]
+#[doc(inline)]
+pub use grpc::{GrpcServer, DISPLAY_PROTO};
+#[cfg(feature = "http")]
+#[doc(inline)]
+pub use image_fetcher::ImageFetcher;
+#[cfg(feature = "image")]
+#[doc(inline)]
+pub use led_image::{ImageOrientation, LedImage};
 #[doc(inline)]
 pub use matrix::LedMatrix;
 #[doc(inline)]
-pub use options::{LedMatrixOptions, LedRuntimeOptions};
+pub use message_queue::{Message, MessageQueue};
+#[cfg(feature = "metrics")]
+#[doc(inline)]
+pub use metrics::Metrics;
+#[cfg(feature = "midi")]
+#[doc(inline)]
+pub use midi::{MidiEvent, MidiInput, MidiRoutes};
+#[doc(inline)]
+pub use mirror::{MirrorEncoder, MirrorReceiver};
+#[doc(inline)]
+pub use options::{Board, LedMatrixOptions, LedRuntimeOptions};
+#[cfg(feature = "osc")]
+#[doc(inline)]
+pub use osc::{OscArg, OscMessage, OscRoutes};
+#[doc(inline)]
+pub use palette::{Levels, Palette};
+#[doc(inline)]
+pub use patch::Patch;
+#[cfg(feature = "preview")]
+#[doc(inline)]
+pub use preview::PreviewServer;
+#[doc(inline)]
+pub use race::{races_detected, set_race_detection, RaceDetection};
+#[cfg(feature = "recorder")]
+#[doc(inline)]
+pub use recorder::Recorder;
+#[doc(inline)]
+pub use remote::{RemoteCanvas, RemoteMatrix};
+#[cfg(feature = "sacn")]
+#[doc(inline)]
+pub use sacn::{Sacn, SACN_PORT};
+#[doc(inline)]
+pub use scene::{Hours, Priority, Scene, SceneId, SceneManager};
+#[doc(inline)]
+pub use solar::{Daylight, SolarClock};
+#[doc(inline)]
+pub use spectrum::Spectrum;
+#[doc(inline)]
+pub use sprite::Sprite;
+#[doc(inline)]
+pub use stats_log::StatsLog;
+#[doc(inline)]
+pub use surface::Surface;
+#[doc(inline)]
+pub use text_effect::TextEffect;
+#[doc(inline)]
+pub use timecode::{FrameRate, LtcDecoder, MtcDecoder, Timecode, TimecodeClock};
+#[doc(inline)]
+pub use transition::{Direction, Transition, TransitionKind};
+#[cfg(feature = "video")]
+#[doc(inline)]
+pub use video::{Video, VideoFrames};
+#[doc(inline)]
+pub use watermark::{Corner, Watermark};
@@ -1,5 +1,11 @@
+use crate::dither::TemporalDither;
 use crate::ffi;
-use crate::{LedCanvas, LedMatrixOptions, LedRuntimeOptions};
+use crate::frame::{self, Frame, PreSwapHook};
+use crate::race;
+use crate::{
+    Compensation, LedCanvas, LedColor, LedMatrixOptions, LedRuntimeOptions, Sprite, Transition,
+    Watermark,
+};
 #[cfg(feature = "embeddedgraphics")]
 use embedded_graphics_core::{
     draw_target::DrawTarget,
@@ -8,6 +14,10 @@ use embedded_graphics_core::{
     primitives::Rectangle,
     Pixel,
 };
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
 
 /// The Rust handle for the RGB matrix.
 ///
@@ -18,6 +28,22 @@ use embedded_graphics_core::{
 pub struct LedMatrix {
     handle: *mut ffi::CLedMatrix,
     _options: LedMatrixOptions,
+    rt_options: LedRuntimeOptions,
+    /// Pixels of the canvases currently not handed out, by canvas handle
+    shadows: Mutex<HashMap<usize, Vec<LedColor>>>,
+    /// Canvas handles whose panel buffer doesn't match their shadow, e.g. still showing a
+    /// watermark, so their next swap rewrites every pixel
+    altered: Mutex<HashSet<usize>>,
+    dither: Mutex<Option<TemporalDither>>,
+    compensation: Mutex<Option<Compensation>>,
+    pre_swap: Mutex<Vec<PreSwapHook>>,
+    transition: Mutex<Option<Transition>>,
+    watermark: Mutex<Option<Watermark>>,
+    sprite: Mutex<Option<Sprite>>,
+    /// Handle of the canvas last swapped onto the panel
+    displayed: AtomicUsize,
+    /// The frame on the panel, with its size
+    front: Mutex<(Vec<LedColor>, (i32, i32))>,
 }
 
 impl LedMatrix {
@@ -32,13 +58,17 @@ impl LedMatrix {
     ///
     /// # Errors
     /// If the underlying C++ library returns a null pointer when trying to create
-    /// the [`CLedMatrix`](ffi::CLedMatrix).
+    /// the [`CLedMatrix`](ffi::CLedMatrix), or if the [daemon](LedRuntimeOptions::set_daemon)
+    /// option is set while other threads are running.
     pub fn new(
         options: Option<LedMatrixOptions>,
         rt_options: Option<LedRuntimeOptions>,
     ) -> Result<Self, &'static str> {
         let mut options = options.unwrap_or_default();
         let mut rt_options = rt_options.unwrap_or_default();
+        if rt_options.daemon() {
+            crate::daemon::check_fork()?;
+        }
 
         let handle = unsafe {
             ffi::led_matrix_create_from_options_and_rt_options(
@@ -53,6 +83,17 @@ impl LedMatrix {
             Ok(Self {
                 handle,
                 _options: options,
+                rt_options,
+                shadows: Mutex::new(HashMap::new()),
+                altered: Mutex::new(HashSet::new()),
+                dither: Mutex::new(None),
+                compensation: Mutex::new(None),
+                pre_swap: Mutex::new(Vec::new()),
+                transition: Mutex::new(None),
+                watermark: Mutex::new(None),
+                sprite: Mutex::new(None),
+                displayed: AtomicUsize::new(0),
+                front: Mutex::new((Vec::new(), (0, 0))),
             })
         }
     }
@@ -62,7 +103,45 @@ impl LedMatrix {
     pub fn canvas(&self) -> LedCanvas {
         let handle = unsafe { ffi::led_matrix_get_canvas(self.handle) };
 
-        LedCanvas { handle }
+        let mut canvas = LedCanvas::new(handle);
+        if let Some(shadow) = self.shadows().get(&(handle as usize)) {
+            canvas.shadow.clone_from(shadow);
+        }
+        canvas
+    }
+
+    /// A snapshot of the runtime options the matrix was created with, e.g. to report the
+    /// effective GPIO slowdown, daemon mode and privilege settings for diagnostics.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedMatrix, LedRuntimeOptions};
+    /// let mut rt_options = LedRuntimeOptions::new();
+    /// rt_options.set_gpio_slowdown(2);
+    /// let matrix = LedMatrix::new(None, Some(rt_options)).unwrap();
+    /// assert_eq!(matrix.runtime_options().gpio_slowdown(), 2);
+    /// ```
+    #[must_use]
+    pub fn runtime_options(&self) -> LedRuntimeOptions {
+        self.rt_options.clone()
+    }
+
+    /// Retrieves the current panel brightness in percent.
+    #[must_use]
+    pub fn brightness(&self) -> u8 {
+        unsafe { ffi::led_matrix_get_brightness(self.handle) }
+    }
+
+    /// Changes the panel brightness in percent while running.
+    ///
+    /// # Errors
+    /// If the given `brightness` is not in the range \[1,100\].
+    pub fn set_brightness(&self, brightness: u8) -> Result<(), &'static str> {
+        if (1..=100).contains(&brightness) {
+            unsafe { ffi::led_matrix_set_brightness(self.handle, brightness) };
+            Ok(())
+        } else {
+            Err("Brightness can only have value between 1 and 100 inclusive")
+        }
     }
 
     /// Retrieves the offscreen canvas. Used in conjunction with [swap](LedMatrix.swap).
@@ -70,11 +149,14 @@ impl LedMatrix {
     pub fn offscreen_canvas(&self) -> LedCanvas {
         let handle = unsafe { ffi::led_matrix_create_offscreen_canvas(self.handle) };
 
-        LedCanvas { handle }
+        LedCanvas::new(handle)
     }
 
     /// Cleanly swaps the canvas on v-sync, returning the off-screen canvas for updating.
     ///
+    /// Drawing state such as the canvas' [`Transform`](crate::Transform) carries over to the
+    /// returned canvas.
+    ///
     /// ```
     /// use rpi_led_matrix::{LedMatrix, LedColor};
     /// let matrix = LedMatrix::new(None, None).unwrap();
@@ -87,16 +169,357 @@ impl LedMatrix {
     /// }
     /// ```
     #[must_use]
-    #[allow(clippy::needless_pass_by_value)]
-    pub fn swap(&self, canvas: LedCanvas) -> LedCanvas {
+    pub fn swap(&self, mut canvas: LedCanvas) -> LedCanvas {
+        let mut hooks = self
+            .pre_swap
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut dither = self
+            .dither
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let map = self
+            .compensation
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // the compensation of the panel, then dithering
+        let compensation = map
+            .as_ref()
+            .filter(|compensation| compensation.size() == canvas.device_size);
+        let adjusted = dither.is_some() || compensation.is_some();
+        let mut finish = |index, color| {
+            let color = compensation.map_or(color, |map| map.apply(index, color));
+            dither
+                .as_mut()
+                .map_or(color, |dither| dither.apply(index, color))
+        };
+        let mut transition = self
+            .transition
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let watermark = self
+            .watermark
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = Instant::now();
+        let visible = watermark
+            .as_ref()
+            .filter(|watermark| watermark.is_visible(now));
+        let sprite = self
+            .sprite
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+            .filter(Sprite::is_visible);
+        let mut altered = self
+            .altered
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut composited = None;
+        let overlays = visible.is_some() || sprite.is_some();
+        if !hooks.is_empty() || transition.is_some() || overlays {
+            let mut shown = canvas.shadow.clone();
+            let mut frame = Frame::new(&mut shown, canvas.device_size);
+            if let Some(running) = transition.as_mut() {
+                running.apply(&mut frame);
+                if running.is_finished() {
+                    *transition = None;
+                }
+            }
+            if let Some(watermark) = visible {
+                watermark.apply(&mut frame, now);
+            }
+            frame::run_hooks(&mut hooks, &mut frame);
+            if let Some(sprite) = &sprite {
+                sprite.apply(&mut frame);
+            }
+            canvas.present(|index, _| finish(index, shown[index]));
+            composited = Some(shown);
+        } else if adjusted {
+            canvas.present(finish);
+        } else if altered.remove(&(canvas.handle as usize)) {
+            // the overlays of an earlier frame are still in the buffer
+            canvas.present(|_, color| color);
+        }
+        if composited.is_some() || adjusted {
+            altered.insert(canvas.handle as usize);
+        }
+        drop((hooks, dither, map, transition, watermark, altered));
+        let writing = race::writing(canvas.handle);
         let handle = unsafe { ffi::led_matrix_swap_on_vsync(self.handle, canvas.handle) };
+        drop(writing);
+
+        let mut shadows = self.shadows();
+        let shadow = shadows
+            .remove(&(handle as usize))
+            .unwrap_or_else(|| LedCanvas::blank_shadow(canvas.device_size));
+        let previous = std::mem::replace(&mut canvas.shadow, shadow);
+        let mut front = self
+            .front
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(shown) = composited {
+            front.0 = shown;
+        } else {
+            front.0.clear();
+            front.0.extend_from_slice(&previous);
+        }
+        front.1 = canvas.device_size;
+        drop(front);
+        shadows.insert(canvas.handle as usize, previous);
+        self.displayed
+            .store(canvas.handle as usize, Ordering::Relaxed);
+        canvas.handle = handle;
+        canvas
+    }
+
+    /// Returns a copy of the frame currently on the panel, row by row in panel coordinates,
+    /// with its width and height, or `None` before the first [`swap`](Self::swap).
+    ///
+    /// The C++ library can't read pixels back, so this is the last frame passed to the panel
+    /// by a swap: the content of the canvas with a [transition](Self::start_transition),
+    /// [watermark](Self::set_watermark), [pre-swap hooks](Self::add_pre_swap) and
+    /// [sprite](Self::set_sprite) applied, though before compensation and dithering. As it only
+    /// takes a lock, it can be called from any thread, e.g. to show the panel on a monitoring
+    /// page while another thread draws.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedMatrix};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// canvas.fill(&LedColor { red: 0, green: 0, blue: 255 });
+    /// canvas = matrix.swap(canvas);
+    /// let (pixels, (width, height)) = matrix.capture().unwrap();
+    /// assert_eq!(pixels.len(), (width * height) as usize);
+    /// ```
+    #[must_use]
+    pub fn capture(&self) -> Option<(Vec<LedColor>, (i32, i32))> {
+        let front = self
+            .front
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        (!front.0.is_empty()).then(|| front.clone())
+    }
+
+    /// Returns the frame currently on the panel as an image, see [`capture`](Self::capture).
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn capture_image(&self) -> Option<crate::LedImage> {
+        let (pixels, (width, height)) = self.capture()?;
+        crate::LedImage::from_pixels(width as u32, height as u32, &pixels).ok()
+    }
+
+    /// Enables temporal dithering on [`swap`](Self::swap) for a panel showing `bits` levels per
+    /// color channel, or disables it with `None`.
+    ///
+    /// Panels running with reduced PWM bits or at low brightness band visibly on gradients.
+    /// With dithering, the error of every pixel is carried over to the next frame, so that in
+    /// between levels show on average. Every swap then rewrites the whole canvas, so this works
+    /// best when swapping continuously, even if nothing changed.
+    pub fn set_dithering(&self, bits: Option<u8>) {
+        *self
+            .dither
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = bits.map(TemporalDither::new);
+    }
+
+    /// Evens out the brightness of aged panels on [`swap`](Self::swap) with a per-pixel
+    /// [`Compensation`] map, or stops with `None`.
+    ///
+    /// It applies to the final frame right before [dithering](Self::set_dithering), and only
+    /// while its size matches the canvas.
+    pub fn set_compensation(&self, compensation: Option<Compensation>) {
+        *self
+            .compensation
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = compensation;
+    }
+
+    /// Registers `hook` to run on every [`swap`](Self::swap) with the final frame, for
+    /// last-moment changes like a watermark, a power limiter or a recorder.
+    ///
+    /// Hooks run in the order they were added, before [dithering](Self::set_dithering). They
+    /// change a copy of the canvas, so what the application drew stays as it is and e.g. a
+    /// dimming hook doesn't compound over frames.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedMatrix};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// // a red dot in the top right corner while recording
+    /// matrix.add_pre_swap(|frame| {
+    ///     let (width, _) = frame.size();
+    ///     frame.set(width - 1, 0, LedColor { red: 255, green: 0, blue: 0 });
+    /// });
+    /// ```
+    pub fn add_pre_swap(&self, hook: impl FnMut(&mut Frame) + Send + 'static) {
+        self.pre_swap
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(Box::new(hook));
+    }
+
+    /// Removes all hooks added with [`add_pre_swap`](Self::add_pre_swap).
+    pub fn clear_pre_swap(&self) {
+        self.pre_swap
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Plays `transition` from the content currently shown to whatever the next swaps show.
+    ///
+    /// Every [`swap`](Self::swap) advances the transition by a frame, before running the
+    /// [pre-swap hooks](Self::add_pre_swap), until it's finished. Starting a transition replaces
+    /// one still running. See [`Transition`] for an example.
+    pub fn start_transition(&self, mut transition: Transition) {
+        let displayed = self.displayed.load(Ordering::Relaxed);
+        if let Some(shown) = self.shadows().get(&displayed) {
+            transition.set_from(shown.clone());
+        }
+        *self
+            .transition
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) =
+            (!transition.is_finished()).then_some(transition);
+    }
 
-        LedCanvas { handle }
+    /// Sets the watermark blended over every frame on [`swap`](Self::swap), after a running
+    /// [transition](Self::start_transition) and before the [pre-swap hooks](Self::add_pre_swap),
+    /// or removes it with `None`.
+    pub fn set_watermark(&self, watermark: Option<Watermark>) {
+        *self
+            .watermark
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = watermark;
+    }
+
+    /// Sets the sprite drawn on top of every frame on [`swap`](Self::swap), after the
+    /// [pre-swap hooks](Self::add_pre_swap), or removes it with `None`. See [`Sprite`] for an
+    /// example.
+    pub fn set_sprite(&self, sprite: Option<Sprite>) {
+        *self
+            .sprite
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = sprite;
+    }
+
+    /// Blanks the panel and stops the C++ library's refresh thread, waiting up to `timeout`
+    /// for it to exit.
+    ///
+    /// Dropping the matrix does the same, but without blanking and blocking for as long as the
+    /// thread takes. Use this for a clean stop, e.g. when systemd stops the service, or before
+    /// creating a new matrix with different options in the same process. Canvases retrieved
+    /// from the matrix must not be used afterwards.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedMatrix;
+    /// use std::time::Duration;
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// matrix.shutdown(Duration::from_secs(1)).unwrap();
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the refresh thread didn't exit within `timeout`. It's left to finish in the
+    /// background, so creating a new matrix isn't safe then.
+    pub fn shutdown(mut self, timeout: Duration) -> Result<(), &'static str> {
+        // keep Drop from deleting the matrix again
+        let handle = std::mem::replace(&mut self.handle, std::ptr::null_mut()) as usize;
+        let (done, stopped) = mpsc::channel();
+        std::thread::spawn(move || {
+            let handle = handle as *mut ffi::CLedMatrix;
+            unsafe {
+                ffi::led_canvas_clear(ffi::led_matrix_get_canvas(handle));
+                ffi::led_matrix_delete(handle);
+            }
+            let _ = done.send(());
+        });
+        stopped
+            .recv_timeout(timeout)
+            .map_err(|_| "Refresh thread didn't stop in time")
+    }
+
+    /// Shuts the matrix down and creates a new one with other options, e.g. more chained
+    /// panels or another pixel mapper, without restarting the process.
+    ///
+    /// The content of `canvas` is moved onto the new matrix as far as it fits, aligned at the
+    /// top left corner, and shown right away. The returned canvas keeps the drawing state of
+    /// `canvas`, such as its [`Transform`](crate::Transform), just like after a
+    /// [`swap`](Self::swap). Other canvases of the old matrix must not be used anymore.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedColor, LedMatrix, LedMatrixOptions};
+    /// use std::time::Duration;
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// canvas.fill(&LedColor { red: 0, green: 0, blue: 128 });
+    /// let mut options = LedMatrixOptions::new();
+    /// options.set_chain_length(2);
+    /// let (matrix, canvas) = matrix
+    ///     .reconfigure(canvas, Some(options), None, Duration::from_secs(1))
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the old matrix doesn't [`shutdown`](Self::shutdown) in time, or the new one can't be
+    /// created, see [`new`](Self::new).
+    pub fn reconfigure(
+        self,
+        mut canvas: LedCanvas,
+        options: Option<LedMatrixOptions>,
+        rt_options: Option<LedRuntimeOptions>,
+        timeout: Duration,
+    ) -> Result<(Self, LedCanvas), &'static str> {
+        let content = std::mem::take(&mut canvas.shadow);
+        let content_size = canvas.device_size;
+        self.shutdown(timeout)?;
+        let matrix = Self::new(options, rt_options)?;
+        let fresh = matrix.offscreen_canvas();
+        canvas.handle = fresh.handle;
+        canvas.device_size = fresh.device_size;
+        canvas.shadow = fresh.shadow;
+        copy_overlap(
+            &content,
+            content_size,
+            &mut canvas.shadow,
+            canvas.device_size,
+        );
+        canvas.present(|_, color| color);
+        // show the content, and keep it on the canvas handed back for further drawing
+        let shown = canvas.shadow.clone();
+        let mut canvas = matrix.swap(canvas);
+        canvas.shadow = shown;
+        canvas.present(|_, color| color);
+        Ok((matrix, canvas))
+    }
+
+    fn shadows(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Vec<LedColor>>> {
+        self.shadows
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Copies the pixels of `from` that lie within `to`, both row by row with the given sizes.
+fn copy_overlap(
+    from: &[LedColor],
+    from_size: (i32, i32),
+    to: &mut [LedColor],
+    to_size: (i32, i32),
+) {
+    let width = from_size.0.min(to_size.0).max(0) as usize;
+    for y in 0..from_size.1.min(to_size.1).max(0) as usize {
+        let source = y * from_size.0 as usize;
+        let target = y * to_size.0 as usize;
+        to[target..target + width].copy_from_slice(&from[source..source + width]);
     }
 }
 
 impl Drop for LedMatrix {
     fn drop(&mut self) {
+        if self.handle.is_null() {
+            return;
+        }
         unsafe {
             ffi::led_matrix_delete(self.handle);
         }
@@ -153,4 +576,93 @@ mod tests {
     fn matrix_create() {
         let _matrix = led_matrix();
     }
+
+    #[test]
+    fn overlap() {
+        let color = |red| LedColor {
+            red,
+            green: 0,
+            blue: 0,
+        };
+        let from: Vec<_> = (0..6).map(color).collect();
+        // 3x2 onto 2x3
+        let mut to = vec![color(9); 6];
+        copy_overlap(&from, (3, 2), &mut to, (2, 3));
+        let reds: Vec<_> = to.iter().map(|c| c.red).collect();
+        assert_eq!(reds, [0, 1, 3, 4, 9, 9]);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn reconfigure() {
+        let matrix = led_matrix();
+        let mut canvas = matrix.offscreen_canvas();
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        canvas.set(3, 4, &red);
+        let mut options = LedMatrixOptions::new();
+        options.set_chain_length(1);
+        let (_matrix, canvas) = matrix
+            .reconfigure(canvas, Some(options), None, Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(canvas.canvas_size(), (32, 32));
+        assert_eq!(canvas.get(3, 4), Some(red));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn capture() {
+        let matrix = led_matrix();
+        assert!(matrix.capture().is_none());
+        let mut canvas = matrix.offscreen_canvas();
+        let blue = LedColor {
+            red: 0,
+            green: 0,
+            blue: 255,
+        };
+        canvas.fill(&blue);
+        matrix.add_pre_swap(|frame| frame.pixels_mut()[0].red = 255);
+        let _canvas = matrix.swap(canvas);
+        let (pixels, size) = matrix.capture().unwrap();
+        assert_eq!(size, (64, 32));
+        assert_eq!(pixels[1], blue);
+        assert_eq!(pixels[0].red, 255);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    #[cfg(any(feature = "simulator-terminal", feature = "simulator-window"))]
+    fn overlay_removed() {
+        let matrix = led_matrix();
+        let mut logo = crate::Surface::new(2, 2);
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        logo.set(0, 0, crate::LedColorA::new(white, 255));
+        matrix.set_watermark(Some(Watermark::surface(logo)));
+        let mut canvas = matrix.offscreen_canvas();
+        canvas.clear();
+        let marked = canvas.handle;
+        canvas = matrix.swap(canvas);
+        matrix.set_watermark(None);
+        canvas = matrix.swap(canvas);
+        // the buffer showing the watermark is back, with nothing drawn on it
+        assert_eq!(canvas.handle, marked);
+        let _canvas = matrix.swap(canvas);
+        let pixels = unsafe { ffi::simulator::pixels(marked) };
+        assert!(pixels.iter().all(|&pixel| pixel == [0; 3]));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn shutdown() {
+        led_matrix().shutdown(Duration::from_secs(2)).unwrap();
+        // the hardware is free again for a new matrix
+        let _matrix = led_matrix();
+    }
 }
@@ -0,0 +1,277 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::Priority;
+
+/// A text message accepted by a [`MessageQueue`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    /// who sent the message, e.g. a user name or IP address
+    pub sender: String,
+    /// the (filtered) text of the message
+    pub text: String,
+    /// messages of a higher priority are handed out first
+    pub priority: Priority,
+    /// when the message was accepted
+    pub received: Instant,
+}
+
+type Filter = Box<dyn Fn(&str) -> Option<String> + Send>;
+
+/// Queue for incoming text messages, e.g. for a public "send a message to the sign" feature.
+///
+/// Messages are handed out by priority, then in the order they arrived. The queue rejects
+/// duplicates, senders exceeding their rate limit and anything the filter hook refuses.
+///
+/// ```
+/// use rpi_led_matrix::{MessageQueue, Priority};
+/// use std::time::{Duration, Instant};
+/// let mut queue = MessageQueue::new()
+///     .rate_limit(3, Duration::from_secs(60))
+///     .filter(|text| (!text.contains("spam")).then(|| text.to_uppercase()));
+/// queue.push("alice", "hello world", Priority::Normal, Instant::now()).unwrap();
+/// assert!(queue.push("bob", "spam", Priority::Normal, Instant::now()).is_err());
+/// assert_eq!(queue.pop(Instant::now()).unwrap().text, "HELLO WORLD");
+/// ```
+pub struct MessageQueue {
+    queue: VecDeque<Message>,
+    capacity: usize,
+    dedup_window: Duration,
+    /// normalized text of recently accepted messages, for deduplication
+    recent: VecDeque<(String, Instant)>,
+    rate_limit: Option<(usize, Duration)>,
+    senders: HashMap<String, VecDeque<Instant>>,
+    filter: Option<Filter>,
+}
+
+impl MessageQueue {
+    /// Creates a queue holding up to 64 messages, rejecting duplicates within 5 minutes and
+    /// without rate limit or filter.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            capacity: 64,
+            dedup_window: Duration::from_secs(5 * 60),
+            recent: VecDeque::new(),
+            rate_limit: None,
+            senders: HashMap::new(),
+            filter: None,
+        }
+    }
+
+    /// Sets how many messages may wait in the queue at once
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets for how long a message rejects repetitions of the same text, from any sender.
+    ///
+    /// Texts are compared ignoring case and whitespace. A zero window disables deduplication.
+    #[must_use]
+    pub fn dedup_window(mut self, window: Duration) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Allows each sender at most `count` messages per `period`
+    #[must_use]
+    pub fn rate_limit(mut self, count: usize, period: Duration) -> Self {
+        self.rate_limit = Some((count, period));
+        self
+    }
+
+    /// Sets a hook run on every incoming text, e.g. a profanity filter.
+    ///
+    /// Returning `None` rejects the message, otherwise the returned text is queued instead.
+    #[must_use]
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    fn normalize(text: &str) -> String {
+        text.split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Forgets everything older than the dedup window and rate limit period.
+    fn expire(&mut self, now: Instant) {
+        let window = self.dedup_window;
+        while let Some((_, received)) = self.recent.front() {
+            if now.saturating_duration_since(*received) < window {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        if let Some((_, period)) = self.rate_limit {
+            self.senders.retain(|_, times| {
+                while let Some(time) = times.front() {
+                    if now.saturating_duration_since(*time) < period {
+                        break;
+                    }
+                    times.pop_front();
+                }
+                !times.is_empty()
+            });
+        }
+    }
+
+    /// Offers a message to the queue.
+    ///
+    /// # Errors
+    /// - If the text is empty or the filter rejects it.
+    /// - If the sender exceeded their rate limit.
+    /// - If the same text was accepted within the dedup window.
+    /// - If the queue is full.
+    pub fn push(
+        &mut self,
+        sender: &str,
+        text: &str,
+        priority: Priority,
+        now: Instant,
+    ) -> Result<(), &'static str> {
+        self.expire(now);
+
+        let text = match &self.filter {
+            Some(filter) => filter(text).ok_or("Message rejected by filter")?,
+            None => text.to_string(),
+        };
+        let normalized = Self::normalize(&text);
+        if normalized.is_empty() {
+            return Err("Message is empty");
+        }
+        if let Some((count, _)) = self.rate_limit {
+            if self.senders.get(sender).map_or(0, VecDeque::len) >= count {
+                return Err("Sender exceeded the rate limit");
+            }
+        }
+        if !self.dedup_window.is_zero() && self.recent.iter().any(|(t, _)| *t == normalized) {
+            return Err("Duplicate message");
+        }
+        if self.queue.len() >= self.capacity {
+            return Err("Message queue is full");
+        }
+
+        if self.rate_limit.is_some() {
+            self.senders
+                .entry(sender.to_string())
+                .or_default()
+                .push_back(now);
+        }
+        if !self.dedup_window.is_zero() {
+            self.recent.push_back((normalized, now));
+        }
+        let index = self
+            .queue
+            .iter()
+            .position(|m| m.priority < priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(
+            index,
+            Message {
+                sender: sender.to_string(),
+                text,
+                priority,
+                received: now,
+            },
+        );
+        Ok(())
+    }
+
+    /// Takes the next message out of the queue.
+    pub fn pop(&mut self, now: Instant) -> Option<Message> {
+        self.expire(now);
+        self.queue.pop_front()
+    }
+
+    /// Returns the next message without taking it out of the queue.
+    #[must_use]
+    pub fn peek(&self) -> Option<&Message> {
+        self.queue.front()
+    }
+
+    /// Number of messages waiting
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no messages are waiting
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl Default for MessageQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordering() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new();
+        queue.push("a", "first", Priority::Normal, now).unwrap();
+        queue.push("b", "second", Priority::Normal, now).unwrap();
+        queue.push("c", "urgent", Priority::Alert, now).unwrap();
+        let texts: Vec<_> = std::iter::from_fn(|| queue.pop(now))
+            .map(|m| m.text)
+            .collect();
+        assert_eq!(texts, ["urgent", "first", "second"]);
+    }
+
+    #[test]
+    fn dedup() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new().dedup_window(Duration::from_secs(10));
+        queue
+            .push("a", "Hello  World", Priority::Normal, now)
+            .unwrap();
+        assert!(queue.pop(now).is_some());
+        assert!(queue
+            .push("b", "hello world", Priority::Normal, now)
+            .is_err());
+        let later = now + Duration::from_secs(10);
+        assert!(queue
+            .push("b", "hello world", Priority::Normal, later)
+            .is_ok());
+    }
+
+    #[test]
+    fn rate_limit() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new().rate_limit(2, Duration::from_secs(60));
+        queue.push("a", "one", Priority::Normal, now).unwrap();
+        queue.push("a", "two", Priority::Normal, now).unwrap();
+        assert!(queue.push("a", "three", Priority::Normal, now).is_err());
+        assert!(queue.push("b", "three", Priority::Normal, now).is_ok());
+        let later = now + Duration::from_secs(60);
+        assert!(queue.push("a", "four", Priority::Normal, later).is_ok());
+    }
+
+    #[test]
+    fn filter_and_capacity() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new()
+            .capacity(1)
+            .filter(|text| Some(text.replace("darn", "****")));
+        queue.push("a", "darn it", Priority::Normal, now).unwrap();
+        assert_eq!(queue.peek().unwrap().text, "**** it");
+        assert!(queue.push("a", "another", Priority::Normal, now).is_err());
+        assert!(queue.push("a", "   ", Priority::Normal, now).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt::Write as _;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::http_server::{self, Request};
+use crate::stats_log::soc_temperature;
+use crate::LedMatrix;
+
+/// Upper bounds of the render time histogram buckets, in seconds
+const BUCKETS: [f64; 10] = [0.001, 0.002, 0.005, 0.01, 0.02, 0.033, 0.05, 0.1, 0.2, 0.5];
+
+/// The scrapes answered at once, further connections are closed right away
+const MAX_SCRAPERS: usize = 4;
+
+#[derive(Debug)]
+struct State {
+    frame_time: Duration,
+    swapped: u64,
+    dropped: u64,
+    last_swap: Option<Instant>,
+    window_start: Option<Instant>,
+    window_frames: u32,
+    fps: f32,
+    /// Renders per bucket, not cumulative
+    buckets: [u64; BUCKETS.len()],
+    renders: u64,
+    render_time: Duration,
+}
+
+impl State {
+    fn swapped(&mut self, now: Instant) {
+        self.swapped += 1;
+        if let Some(last) = self.last_swap.replace(now) {
+            let missed = now.saturating_duration_since(last).as_secs_f32()
+                / self.frame_time.as_secs_f32().max(1e-6);
+            // half a frame late already shows as a stutter
+            self.dropped += u64::from(((missed + 0.5) as u32).saturating_sub(1));
+        }
+        let start = *self.window_start.get_or_insert(now);
+        self.window_frames += 1;
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed >= Duration::from_secs(1) {
+            // the first swap of the window only starts it
+            self.fps = (self.window_frames - 1) as f32 / elapsed.as_secs_f32();
+            self.window_start = Some(now);
+            self.window_frames = 1;
+        }
+    }
+}
+
+/// Runtime metrics of a matrix for monitoring long-running installations, in the text format
+/// of Prometheus.
+///
+/// Counts the frames swapped and the frames dropped for missing their deadline, the frames per
+/// second achieved over the last second, a histogram of the time spent rendering a frame and
+/// the SoC temperature where the system reports it. [`attach`](Self::attach) it to a matrix
+/// and feed it the render times with [`record_render`](Self::record_render), or pass it to
+/// [`MatrixAppBuilder::metrics`](crate::MatrixAppBuilder::metrics), which does both.
+///
+/// Clones share the metrics, so one can be served while another is fed.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, Metrics};
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let metrics = Metrics::new().frame_rate(60);
+/// metrics.attach(&matrix);
+/// metrics.serve("0.0.0.0:9100").unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// loop {
+///     let start = Instant::now();
+///     canvas.clear();
+///     metrics.record_render(start.elapsed());
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Creates metrics counting nothing yet, expecting 30 frames per second
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                frame_time: Duration::from_nanos(1_000_000_000 / 30),
+                swapped: 0,
+                dropped: 0,
+                last_swap: None,
+                window_start: None,
+                window_frames: 0,
+                fps: 0.0,
+                buckets: [0; BUCKETS.len()],
+                renders: 0,
+                render_time: Duration::ZERO,
+            })),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets the frames per second the matrix is driven at, 30 by default, to count the
+    /// frames that took longer as dropped.
+    #[must_use]
+    pub fn frame_rate(self, frames_per_second: u32) -> Self {
+        self.state().frame_time = Duration::from_secs(1) / frames_per_second.max(1);
+        self
+    }
+
+    /// Counts every frame swapped on `matrix`, with a [pre-swap hook](LedMatrix::add_pre_swap).
+    pub fn attach(&self, matrix: &LedMatrix) {
+        let metrics = self.clone();
+        matrix.add_pre_swap(move |_| metrics.state().swapped(Instant::now()));
+    }
+
+    /// Adds the time spent rendering a frame to the histogram.
+    pub fn record_render(&self, duration: Duration) {
+        let mut state = self.state();
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = BUCKETS.iter().position(|&bound| seconds <= bound) {
+            state.buckets[bucket] += 1;
+        }
+        state.renders += 1;
+        state.render_time += duration;
+    }
+
+    /// The number of frames swapped
+    #[must_use]
+    pub fn frames_swapped(&self) -> u64 {
+        self.state().swapped
+    }
+
+    /// The number of frames dropped for missing their deadline by half a frame or more
+    #[must_use]
+    pub fn frames_dropped(&self) -> u64 {
+        self.state().dropped
+    }
+
+    /// The frames swapped per second, measured over the last full second
+    #[must_use]
+    pub fn fps(&self) -> f32 {
+        self.state().fps
+    }
+
+    /// The metrics in the text exposition format of Prometheus.
+    #[must_use]
+    pub fn encode(&self) -> String {
+        let state = self.state();
+        let mut text = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: &str| {
+            let _ = write!(
+                text,
+                "# HELP rpi_led_matrix_{name} {help}\n\
+                 # TYPE rpi_led_matrix_{name} {kind}\n{samples}"
+            );
+        };
+        family(
+            "frames_swapped_total",
+            "counter",
+            "Frames swapped onto the panel.",
+            &format!("rpi_led_matrix_frames_swapped_total {}\n", state.swapped),
+        );
+        family(
+            "frames_dropped_total",
+            "counter",
+            "Frames that missed their deadline.",
+            &format!("rpi_led_matrix_frames_dropped_total {}\n", state.dropped),
+        );
+        family(
+            "fps",
+            "gauge",
+            "Frames swapped per second.",
+            &format!("rpi_led_matrix_fps {:.2}\n", state.fps),
+        );
+        let mut histogram = String::new();
+        let mut count = 0;
+        for (bound, renders) in BUCKETS.iter().zip(state.buckets) {
+            count += renders;
+            let _ = writeln!(
+                histogram,
+                "rpi_led_matrix_render_seconds_bucket{{le=\"{bound}\"}} {count}"
+            );
+        }
+        let _ = write!(
+            histogram,
+            "rpi_led_matrix_render_seconds_bucket{{le=\"+Inf\"}} {renders}\n\
+             rpi_led_matrix_render_seconds_sum {}\n\
+             rpi_led_matrix_render_seconds_count {renders}\n",
+            state.render_time.as_secs_f64(),
+            renders = state.renders,
+        );
+        family(
+            "render_seconds",
+            "histogram",
+            "Time spent rendering a frame.",
+            &histogram,
+        );
+        if let Some(celsius) = soc_temperature() {
+            family(
+                "temperature_celsius",
+                "gauge",
+                "SoC temperature.",
+                &format!("rpi_led_matrix_temperature_celsius {celsius:.1}\n"),
+            );
+        }
+        text
+    }
+
+    /// Answers `http://<address>/metrics` with the metrics from a thread of its own, for
+    /// Prometheus to scrape. Returns the address listened on, e.g. to find the port when
+    /// binding port 0.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn serve(&self, address: impl ToSocketAddrs) -> Result<SocketAddr, &'static str> {
+        let listener = TcpListener::bind(address).map_err(|_| "Couldn't bind metrics address")?;
+        let address = listener
+            .local_addr()
+            .map_err(|_| "Couldn't bind metrics address")?;
+        let metrics = self.clone();
+        std::thread::spawn(move || {
+            http_server::serve(&listener, MAX_SCRAPERS, move |stream| {
+                let _ = metrics.answer(stream);
+            });
+        });
+        Ok(address)
+    }
+
+    /// Answers a scrape request.
+    fn answer(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let request = Request::read(&mut BufReader::new(stream.try_clone()?))?;
+        let path = request.path.as_str();
+        if path == "/metrics" || path.starts_with("/metrics?") {
+            let body = self.encode();
+            http_server::respond(&mut stream, "200 OK", "text/plain; version=0.0.4", &body)
+        } else {
+            http_server::respond(&mut stream, "404 Not Found", "text/plain", "")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn metrics() {
+        let metrics = Metrics::new().frame_rate(10);
+        let start = Instant::now();
+        // a second of frames with the fourth two frames late
+        for frame in [0, 1, 2, 5, 6, 7, 8, 9, 10, 11] {
+            metrics
+                .state()
+                .swapped(start + Duration::from_millis(frame * 100));
+        }
+        metrics.record_render(Duration::from_millis(3));
+        metrics.record_render(Duration::from_millis(40));
+        metrics.record_render(Duration::from_secs(1));
+        assert_eq!(metrics.frames_swapped(), 10);
+        assert_eq!(metrics.frames_dropped(), 2);
+        assert!((metrics.fps() - 8.0).abs() < 0.01);
+
+        let address = metrics.clone().serve("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let lines: Vec<_> = body.lines().collect();
+        let sample = |name: &str| {
+            let index = lines
+                .iter()
+                .position(|line| line.starts_with(name))
+                .unwrap();
+            (lines[index - 1], lines[index])
+        };
+        assert_eq!(
+            sample("rpi_led_matrix_frames_swapped_total"),
+            (
+                "# TYPE rpi_led_matrix_frames_swapped_total counter",
+                "rpi_led_matrix_frames_swapped_total 10"
+            )
+        );
+        assert_eq!(sample("rpi_led_matrix_fps").1, "rpi_led_matrix_fps 8.00");
+        assert!(lines.contains(&"rpi_led_matrix_render_seconds_bucket{le=\"0.002\"} 0"));
+        assert!(lines.contains(&"rpi_led_matrix_render_seconds_bucket{le=\"0.005\"} 1"));
+        assert!(lines.contains(&"rpi_led_matrix_render_seconds_bucket{le=\"0.5\"} 2"));
+        assert!(lines.contains(&"rpi_led_matrix_render_seconds_bucket{le=\"+Inf\"} 3"));
+        assert!(lines.contains(&"rpi_led_matrix_render_seconds_count 3"));
+    }
+}
@@ -0,0 +1,394 @@
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use alsa::seq::{EvCtrl, EvNote, EventType, PortCap, PortType};
+use alsa::{Direction, Seq};
+
+use crate::{AppCommand, AppControl, MtcDecoder, SceneId, Timecode, TimecodeClock};
+
+/// A MIDI message received by a [`MidiInput`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiEvent {
+    /// A key or pad was pressed with a velocity from 1 to 127
+    NoteOn {
+        /// The channel from 0 to 15
+        channel: u8,
+        /// The note number from 0 to 127
+        note: u8,
+        /// How hard it was pressed
+        velocity: u8,
+    },
+    /// A key or pad was released
+    NoteOff {
+        /// The channel from 0 to 15
+        channel: u8,
+        /// The note number from 0 to 127
+        note: u8,
+    },
+    /// A knob, fader or button changed its value from 0 to 127
+    Control {
+        /// The channel from 0 to 15
+        channel: u8,
+        /// The controller number from 0 to 127
+        controller: u8,
+        /// The new value
+        value: u8,
+    },
+    /// A complete MIDI timecode arrived
+    Timecode(Timecode),
+}
+
+impl MidiEvent {
+    const fn channel(self) -> Option<u8> {
+        match self {
+            Self::NoteOn { channel, .. }
+            | Self::NoteOff { channel, .. }
+            | Self::Control { channel, .. } => Some(channel),
+            Self::Timecode(_) => None,
+        }
+    }
+}
+
+type MidiHook = Box<dyn FnMut(&MidiEvent) -> Option<AppCommand> + Send>;
+
+enum Action {
+    NextScene,
+    Scene(SceneId),
+    Cue(String),
+    Brightness,
+    Param(Arc<Mutex<f32>>),
+}
+
+/// Maps notes and controllers of a MIDI controller to [`AppCommand`]s and shared values, for
+/// live control of a [`MatrixApp`](crate::MatrixApp).
+///
+/// Notes trigger on press, controllers map their value from 0 to 127 onto the brightness or
+/// a parameter from 0 to 1. Controllers sent by buttons, with a value of 127 on press and 0
+/// on release, trigger scenes and cues like notes.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedCanvas, MatrixApp, MidiRoutes, SceneManager};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+/// let mut scenes = SceneManager::new();
+/// let speed = Arc::new(Mutex::new(0.5));
+/// let shared = Arc::clone(&speed);
+/// let plasma = scenes.add(Duration::from_secs(30), move |canvas: &mut LedCanvas, _| {
+///     let _ = *shared.lock().unwrap();
+/// });
+/// let routes = MidiRoutes::new()
+///     .scene_note(36, plasma)
+///     .cue_note(37, "flash")
+///     .brightness_control(7)
+///     .param_control(1, &speed);
+/// MatrixApp::builder()
+///     .scenes(scenes)
+///     .midi("LED matrix", routes)
+///     .build()
+///     .unwrap()
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct MidiRoutes {
+    channel: Option<u8>,
+    notes: Vec<(u8, Action)>,
+    controls: Vec<(u8, Action)>,
+    hooks: Vec<MidiHook>,
+    clock: Option<Arc<Mutex<TimecodeClock>>>,
+}
+
+impl MidiRoutes {
+    /// Creates a mapping without routes, listening on all channels.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only listens on `channel`, from 0 to 15.
+    #[must_use]
+    pub const fn channel(mut self, channel: u8) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Moves on to the next scene when `note` is pressed.
+    #[must_use]
+    pub fn next_scene_note(mut self, note: u8) -> Self {
+        self.notes.push((note, Action::NextScene));
+        self
+    }
+
+    /// Switches to the scene `id` when `note` is pressed.
+    #[must_use]
+    pub fn scene_note(mut self, note: u8, id: SceneId) -> Self {
+        self.notes.push((note, Action::Scene(id)));
+        self
+    }
+
+    /// Triggers the [cue](crate::CueList) `event` when `note` is pressed.
+    #[must_use]
+    pub fn cue_note(mut self, note: u8, event: &str) -> Self {
+        self.notes.push((note, Action::Cue(event.to_string())));
+        self
+    }
+
+    /// Switches to the scene `id` when the button `controller` is pressed.
+    #[must_use]
+    pub fn scene_control(mut self, controller: u8, id: SceneId) -> Self {
+        self.controls.push((controller, Action::Scene(id)));
+        self
+    }
+
+    /// Triggers the [cue](crate::CueList) `event` when the button `controller` is pressed.
+    #[must_use]
+    pub fn cue_control(mut self, controller: u8, event: &str) -> Self {
+        self.controls
+            .push((controller, Action::Cue(event.to_string())));
+        self
+    }
+
+    /// Sets the brightness with `controller`.
+    #[must_use]
+    pub fn brightness_control(mut self, controller: u8) -> Self {
+        self.controls.push((controller, Action::Brightness));
+        self
+    }
+
+    /// Stores the value of `controller` in `value`, from 0 to 1.
+    #[must_use]
+    pub fn param_control(mut self, controller: u8, value: &Arc<Mutex<f32>>) -> Self {
+        self.controls
+            .push((controller, Action::Param(Arc::clone(value))));
+        self
+    }
+
+    /// Makes `clock` chase the MIDI timecode received, to drive the cue list of the app with
+    /// [`MatrixAppBuilder::timecode`](crate::MatrixAppBuilder::timecode).
+    #[must_use]
+    pub fn timecode(mut self, clock: &Arc<Mutex<TimecodeClock>>) -> Self {
+        self.clock = Some(Arc::clone(clock));
+        self
+    }
+
+    /// Calls `hook` with every event on the channel, sending the command it returns.
+    #[must_use]
+    pub fn on(
+        mut self,
+        hook: impl FnMut(&MidiEvent) -> Option<AppCommand> + Send + 'static,
+    ) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Handles `event`, returning the commands to send.
+    fn handle(&mut self, event: &MidiEvent) -> Vec<AppCommand> {
+        let channel = event.channel();
+        if self
+            .channel
+            .is_some_and(|wanted| channel.is_some_and(|channel| channel != wanted))
+        {
+            return Vec::new();
+        }
+        let (routes, number, value) = match *event {
+            MidiEvent::Timecode(timecode) => {
+                if let Some(clock) = &self.clock {
+                    clock
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .update(timecode, Instant::now());
+                }
+                (&self.notes, u8::MAX, 0)
+            }
+            MidiEvent::NoteOn { note, velocity, .. } => (&self.notes, note, velocity),
+            MidiEvent::NoteOff { .. } => (&self.notes, u8::MAX, 0),
+            MidiEvent::Control {
+                controller, value, ..
+            } => (&self.controls, controller, value),
+        };
+        let mut commands: Vec<_> = routes
+            .iter()
+            .filter(|(route, _)| *route == number)
+            .filter_map(|(_, action)| match action {
+                Action::NextScene if value > 0 => Some(AppCommand::NextScene),
+                Action::Scene(id) if value > 0 => Some(AppCommand::Scene(*id)),
+                Action::Cue(event) if value > 0 => Some(AppCommand::Cue(event.clone())),
+                Action::Brightness => {
+                    let percent = (u16::from(value.min(127)) * 100 + 63) / 127;
+                    Some(AppCommand::Brightness(percent as u8))
+                }
+                Action::Param(param) => {
+                    *param
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner) =
+                        f32::from(value.min(127)) / 127.0;
+                    None
+                }
+                _ => None,
+            })
+            .collect();
+        commands.extend(self.hooks.iter_mut().filter_map(|hook| hook(event)));
+        commands
+    }
+}
+
+/// Receives MIDI events through an ALSA sequencer port, which controllers and programs like
+/// `aconnect` or a DAW can connect to.
+pub struct MidiInput {
+    seq: Seq,
+    mtc: MtcDecoder,
+}
+
+impl MidiInput {
+    /// Opens a sequencer client named `name` with a port other clients can send to.
+    ///
+    /// # Errors
+    /// If there's no ALSA sequencer, or the port can't be created.
+    pub fn open(name: &str) -> Result<Self, &'static str> {
+        let name = CString::new(name).map_err(|_| "Invalid MIDI client name")?;
+        let seq = Seq::open(None, Some(Direction::Capture), false)
+            .map_err(|_| "Couldn't open ALSA sequencer")?;
+        seq.set_client_name(&name)
+            .map_err(|_| "Couldn't name MIDI client")?;
+        seq.create_simple_port(
+            &name,
+            PortCap::WRITE | PortCap::SUBS_WRITE,
+            PortType::MIDI_GENERIC | PortType::APPLICATION,
+        )
+        .map_err(|_| "Couldn't create MIDI port")?;
+        Ok(Self {
+            seq,
+            mtc: MtcDecoder::new(),
+        })
+    }
+
+    /// Waits for the next note, controller or complete timecode, skipping other events.
+    ///
+    /// # Errors
+    /// If reading from the sequencer fails.
+    pub fn receive(&mut self) -> Result<MidiEvent, &'static str> {
+        let mut input = self.seq.input();
+        loop {
+            let event = input
+                .event_input()
+                .map_err(|_| "Couldn't read MIDI event")?;
+            let midi = match event.get_type() {
+                EventType::Noteon => event.get_data::<EvNote>().map(|note| {
+                    // a note on without velocity is a note off in running status
+                    if note.velocity == 0 {
+                        MidiEvent::NoteOff {
+                            channel: note.channel,
+                            note: note.note,
+                        }
+                    } else {
+                        MidiEvent::NoteOn {
+                            channel: note.channel,
+                            note: note.note,
+                            velocity: note.velocity,
+                        }
+                    }
+                }),
+                EventType::Noteoff => event.get_data::<EvNote>().map(|note| MidiEvent::NoteOff {
+                    channel: note.channel,
+                    note: note.note,
+                }),
+                EventType::Controller => {
+                    event
+                        .get_data::<EvCtrl>()
+                        .map(|control| MidiEvent::Control {
+                            channel: control.channel,
+                            controller: control.param.min(127) as u8,
+                            value: control.value.clamp(0, 127) as u8,
+                        })
+                }
+                EventType::Qframe => event
+                    .get_data::<EvCtrl>()
+                    .and_then(|data| self.mtc.quarter_frame(data.value as u8))
+                    .map(MidiEvent::Timecode),
+                EventType::Sysex => event
+                    .get_ext()
+                    .and_then(|message| self.mtc.full_frame(message))
+                    .map(MidiEvent::Timecode),
+                _ => None,
+            };
+            if let Some(midi) = midi {
+                return Ok(midi);
+            }
+        }
+    }
+
+    /// Receives events and sends the commands of `routes` to `control`, until reading fails.
+    pub fn serve(mut self, mut routes: MidiRoutes, control: &AppControl) {
+        while let Ok(event) = self.receive() {
+            for command in routes.handle(&event) {
+                control.send(command);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LedCanvas, SceneManager};
+    use std::time::Duration;
+
+    #[test]
+    fn routing() {
+        let mut scenes = SceneManager::new();
+        let id = scenes.add(Duration::from_secs(1), |_: &mut LedCanvas, _| {});
+        let speed = Arc::new(Mutex::new(0.0));
+        let clock = Arc::new(Mutex::new(TimecodeClock::new()));
+        let mut routes = MidiRoutes::new()
+            .channel(9)
+            .scene_note(36, id)
+            .cue_control(20, "flash")
+            .brightness_control(7)
+            .param_control(1, &speed)
+            .timecode(&clock)
+            .on(|event| matches!(event, MidiEvent::NoteOff { .. }).then_some(AppCommand::Quit));
+        let note = |note, velocity| MidiEvent::NoteOn {
+            channel: 9,
+            note,
+            velocity,
+        };
+        let control = |controller, value| MidiEvent::Control {
+            channel: 9,
+            controller,
+            value,
+        };
+        assert!(
+            matches!(routes.handle(&note(36, 100))[..], [AppCommand::Scene(scene)] if scene == id)
+        );
+        assert!(routes.handle(&note(37, 100)).is_empty());
+        let off = MidiEvent::NoteOff {
+            channel: 9,
+            note: 36,
+        };
+        assert!(matches!(routes.handle(&off)[..], [AppCommand::Quit]));
+        let cue = routes.handle(&control(20, 127));
+        assert!(matches!(&cue[..], [AppCommand::Cue(event)] if event == "flash"));
+        assert!(routes.handle(&control(20, 0)).is_empty());
+        assert!(matches!(
+            routes.handle(&control(7, 127))[..],
+            [AppCommand::Brightness(100)]
+        ));
+        assert!(matches!(
+            routes.handle(&control(7, 64))[..],
+            [AppCommand::Brightness(50)]
+        ));
+        assert!(routes.handle(&control(1, 127)).is_empty());
+        assert_eq!(*speed.lock().unwrap(), 1.0);
+        let other = MidiEvent::Control {
+            channel: 0,
+            controller: 1,
+            value: 0,
+        };
+        assert!(routes.handle(&other).is_empty());
+        assert_eq!(*speed.lock().unwrap(), 1.0);
+        let timecode = Timecode::new(1, 2, 3, 4, crate::FrameRate::Fps30);
+        assert!(routes.handle(&MidiEvent::Timecode(timecode)).is_empty());
+        assert_eq!(clock.lock().unwrap().timecode(), Some(timecode));
+    }
+}
@@ -0,0 +1,518 @@
+//! A small protocol for mirroring a screen region from a desktop to the matrix.
+//!
+//! Every message starts with a 14 byte header: the magic `LEDM`, the protocol version, the
+//! message kind and the frame width and height as `u16` plus the payload length as `u32`, all
+//! big endian. A key frame carries all pixels as RGB. A delta frame carries runs of changed
+//! pixels, each a `u32` index of its first pixel and a `u16` pixel count followed by the RGB
+//! data, and is applied on top of the previous frame.
+//!
+//! A receiver [announcing](MirrorReceiver::announce) its size greets every sender with a hello
+//! message without payload, carrying the size to draw at. Frames of more than 512×512 pixels
+//! are refused.
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, LedColor};
+
+const MAGIC: &[u8; 4] = b"LEDM";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 14;
+const KEY_FRAME: u8 = 0;
+const DELTA_FRAME: u8 = 1;
+const HELLO: u8 = 2;
+/// Bytes a run adds besides its pixels, so runs separated by fewer unchanged pixels are merged
+const RUN_OVERHEAD: usize = 6;
+/// Pixels of the largest frame received, bounding the memory a sender can make us allocate
+const MAX_PIXELS: usize = 512 * 512;
+
+/// Encodes frames for a [`MirrorReceiver`], sending only what changed since the last frame.
+///
+/// ```no_run
+/// use rpi_led_matrix::MirrorEncoder;
+/// use std::net::TcpStream;
+/// let mut stream = TcpStream::connect("matrix.local:7070").unwrap();
+/// let mut encoder = MirrorEncoder::new(64, 32);
+/// let screenshot = vec![0; 1920 * 1080 * 3];
+/// encoder.send(&mut stream, &screenshot, (1920, 1080)).unwrap();
+/// ```
+pub struct MirrorEncoder {
+    width: u16,
+    height: u16,
+    key_interval: u32,
+    since_key: u32,
+    previous: Option<Vec<u8>>,
+}
+
+impl MirrorEncoder {
+    /// Creates an encoder for frames of the size of the matrix, sending a key frame every 300
+    /// frames so a receiver joining late catches up.
+    #[must_use]
+    pub const fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            key_interval: 300,
+            since_key: 0,
+            previous: None,
+        }
+    }
+
+    /// Sets after how many frames a full key frame is sent again
+    #[must_use]
+    pub const fn key_interval(mut self, frames: u32) -> Self {
+        self.key_interval = frames;
+        self
+    }
+
+    /// Encodes an RGB frame of `size`, downsampling it to the matrix size first.
+    ///
+    /// # Errors
+    /// If `rgb` doesn't hold 3 bytes for every pixel of `size`.
+    pub fn encode(&mut self, rgb: &[u8], size: (u32, u32)) -> Result<Vec<u8>, &'static str> {
+        if rgb.len() != size.0 as usize * size.1 as usize * 3 {
+            return Err("Frame size doesn't match the pixel data");
+        }
+        let target = (u32::from(self.width), u32::from(self.height));
+        let frame = if size == target {
+            rgb.to_vec()
+        } else {
+            downsample(rgb, size, target)
+        };
+
+        let delta = match &self.previous {
+            Some(previous) if self.since_key < self.key_interval => {
+                Some(Self::runs(previous, &frame))
+            }
+            _ => None,
+        };
+        let message = match delta {
+            Some(runs) if runs.len() < frame.len() => {
+                self.since_key += 1;
+                self.message(DELTA_FRAME, &runs)
+            }
+            _ => {
+                self.since_key = 0;
+                self.message(KEY_FRAME, &frame)
+            }
+        };
+        self.previous = Some(frame);
+        Ok(message)
+    }
+
+    /// Encodes a frame and writes it to `stream`, see [`encode`](Self::encode).
+    ///
+    /// # Errors
+    /// If the frame doesn't match its size or the stream fails.
+    pub fn send(
+        &mut self,
+        stream: &mut impl Write,
+        rgb: &[u8],
+        size: (u32, u32),
+    ) -> Result<(), &'static str> {
+        let message = self.encode(rgb, size)?;
+        stream
+            .write_all(&message)
+            .map_err(|_| "Couldn't send mirror frame")
+    }
+
+    /// Encodes the runs of pixels that differ between two frames.
+    fn runs(previous: &[u8], frame: &[u8]) -> Vec<u8> {
+        let changed: Vec<usize> = (0..frame.len() / 3)
+            .filter(|&i| previous[i * 3..i * 3 + 3] != frame[i * 3..i * 3 + 3])
+            .collect();
+        let mut payload = Vec::new();
+        let mut i = 0;
+        while i < changed.len() {
+            let start = changed[i];
+            let mut end = start + 1;
+            while i + 1 < changed.len()
+                && changed[i + 1] - end <= RUN_OVERHEAD / 3
+                && changed[i + 1] - start < usize::from(u16::MAX)
+            {
+                i += 1;
+                end = changed[i] + 1;
+            }
+            payload.extend_from_slice(&(start as u32).to_be_bytes());
+            payload.extend_from_slice(&((end - start) as u16).to_be_bytes());
+            payload.extend_from_slice(&frame[start * 3..end * 3]);
+            i += 1;
+        }
+        payload
+    }
+
+    fn message(&self, kind: u8, payload: &[u8]) -> Vec<u8> {
+        let mut message = Vec::with_capacity(HEADER_SIZE + payload.len());
+        message.extend_from_slice(MAGIC);
+        message.extend_from_slice(&[VERSION, kind]);
+        message.extend_from_slice(&self.width.to_be_bytes());
+        message.extend_from_slice(&self.height.to_be_bytes());
+        message.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        message.extend_from_slice(payload);
+        message
+    }
+}
+
+/// Reads the hello of a receiver [announcing](MirrorReceiver::announce) its size.
+pub(crate) fn read_hello(stream: &mut impl Read) -> Result<(u16, u16), &'static str> {
+    let mut header = [0; HEADER_SIZE];
+    stream
+        .read_exact(&mut header)
+        .map_err(|_| "Couldn't read mirror hello")?;
+    if &header[..4] != MAGIC || header[4] != VERSION || header[5] != HELLO {
+        return Err("Not a mirror hello");
+    }
+    Ok((
+        u16::from_be_bytes([header[6], header[7]]),
+        u16::from_be_bytes([header[8], header[9]]),
+    ))
+}
+
+/// Scales an RGB frame down by averaging the source pixels covering every target pixel.
+fn downsample(rgb: &[u8], (width, height): (u32, u32), target: (u32, u32)) -> Vec<u8> {
+    // source pixels from start to end cover target pixel t, at least one of them
+    let span = |t: u32, source: u32, target: u32| {
+        let start = t * source / target;
+        (
+            start,
+            ((t + 1) * source / target).max(start + 1).min(source),
+        )
+    };
+    let mut frame = Vec::with_capacity(target.0 as usize * target.1 as usize * 3);
+    for ty in 0..target.1 {
+        let (top, bottom) = span(ty, height, target.1);
+        for tx in 0..target.0 {
+            let (left, right) = span(tx, width, target.0);
+            let mut sum = [0_u64; 3];
+            let mut count = 0;
+            for y in top..bottom {
+                for x in left..right {
+                    let index = (y as usize * width as usize + x as usize) * 3;
+                    for (s, &channel) in sum.iter_mut().zip(&rgb[index..index + 3]) {
+                        *s += u64::from(channel);
+                    }
+                    count += 1;
+                }
+            }
+            frame.extend(sum.map(|s| (s / count.max(1)) as u8));
+        }
+    }
+    frame
+}
+
+/// Receives frames sent by a [`MirrorEncoder`] over TCP, from one sender at a time.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, MirrorReceiver};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut receiver = MirrorReceiver::bind("0.0.0.0:7070").unwrap();
+/// loop {
+///     if receiver.receive().unwrap() {
+///         receiver.draw(&mut canvas);
+///         canvas = matrix.swap(canvas);
+///     }
+/// }
+/// ```
+pub struct MirrorReceiver {
+    listener: TcpListener,
+    stream: Option<TcpStream>,
+    timeout: Duration,
+    idle_timeout: Duration,
+    last_message: Instant,
+    announce: Option<(u16, u16)>,
+    size: (u16, u16),
+    frame: Vec<u8>,
+}
+
+impl MirrorReceiver {
+    /// Listens for a sender on `address`.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, &'static str> {
+        let listener = TcpListener::bind(address).map_err(|_| "Couldn't bind mirror address")?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|_| "Couldn't configure mirror socket")?;
+        Ok(Self {
+            listener,
+            stream: None,
+            timeout: Duration::from_millis(100),
+            idle_timeout: Duration::from_secs(10),
+            last_message: Instant::now(),
+            announce: None,
+            size: (0, 0),
+            frame: Vec::new(),
+        })
+    }
+
+    /// Sets how long [`receive`](Self::receive) waits for a frame
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how long a sender may stay silent before it's disconnected, 10 seconds by default,
+    /// so another one can connect
+    #[must_use]
+    pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Greets every sender with the size to draw at, usually the canvas size, as a
+    /// [`RemoteMatrix`](crate::RemoteMatrix) expects.
+    #[must_use]
+    pub const fn announce(mut self, width: u16, height: u16) -> Self {
+        self.announce = Some((width, height));
+        self
+    }
+
+    /// The address listened on, e.g. to find the port when binding port 0.
+    ///
+    /// # Errors
+    /// If the socket can't tell.
+    pub fn local_addr(&self) -> Result<SocketAddr, &'static str> {
+        self.listener
+            .local_addr()
+            .map_err(|_| "Couldn't get mirror address")
+    }
+
+    /// Waits up to the timeout for the next frame, returning true if one arrived.
+    ///
+    /// A new sender is accepted whenever none is connected. A sender sending invalid data
+    /// or nothing for longer than the [idle timeout](Self::idle_timeout) is disconnected.
+    ///
+    /// # Errors
+    /// If the listening socket fails.
+    pub fn receive(&mut self) -> Result<bool, &'static str> {
+        let Some(stream) = &mut self.stream else {
+            match self.listener.accept() {
+                Ok((mut stream, _)) => {
+                    stream
+                        .set_nonblocking(false)
+                        .map_err(|_| "Couldn't configure mirror socket")?;
+                    if let Some((width, height)) = self.announce {
+                        let hello = MirrorEncoder::new(width, height).message(HELLO, &[]);
+                        if stream.write_all(&hello).is_err() {
+                            return Ok(false);
+                        }
+                    }
+                    self.stream = Some(stream);
+                    self.last_message = Instant::now();
+                    return self.receive();
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(self.timeout);
+                    return Ok(false);
+                }
+                Err(_) => return Err("Couldn't accept mirror sender"),
+            }
+        };
+        // wait for the start of a message, then give the rest of it more time to arrive
+        let _ = stream.set_read_timeout(Some(self.timeout.max(Duration::from_millis(1))));
+        match stream.peek(&mut [0]) {
+            Ok(0) => {
+                self.stream = None;
+                return Ok(false);
+            }
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                if self.last_message.elapsed() > self.idle_timeout {
+                    self.stream = None;
+                }
+                return Ok(false);
+            }
+            Err(_) => {
+                self.stream = None;
+                return Ok(false);
+            }
+        }
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+        let message = Self::read_message(stream);
+        match message.and_then(|message| self.apply(&message)) {
+            Ok(()) => {
+                self.last_message = Instant::now();
+                Ok(true)
+            }
+            Err(_) => {
+                self.stream = None;
+                Ok(false)
+            }
+        }
+    }
+
+    fn read_message(stream: &mut impl Read) -> Result<Vec<u8>, &'static str> {
+        let mut header = [0; HEADER_SIZE];
+        stream
+            .read_exact(&mut header)
+            .map_err(|_| "Couldn't read mirror message")?;
+        let width = usize::from(u16::from_be_bytes([header[6], header[7]]));
+        let height = usize::from(u16::from_be_bytes([header[8], header[9]]));
+        let length = u32::from_be_bytes([header[10], header[11], header[12], header[13]]) as usize;
+        // deltas merge runs, so they never need more than twice the bytes of a key frame
+        let limit = Self::frame_len(width, height).map(|frame| frame * 2 + RUN_OVERHEAD);
+        if limit.is_none_or(|limit| length > limit) {
+            return Err("Mirror message too large");
+        }
+        let mut message = header.to_vec();
+        message.resize(HEADER_SIZE + length, 0);
+        stream
+            .read_exact(&mut message[HEADER_SIZE..])
+            .map_err(|_| "Couldn't read mirror message")?;
+        Ok(message)
+    }
+
+    /// Applies an encoded message to the current frame.
+    fn apply(&mut self, message: &[u8]) -> Result<(), &'static str> {
+        if message.len() < HEADER_SIZE || &message[..4] != MAGIC || message[4] != VERSION {
+            return Err("Not a mirror message");
+        }
+        let size = (
+            u16::from_be_bytes([message[6], message[7]]),
+            u16::from_be_bytes([message[8], message[9]]),
+        );
+        let payload = &message[HEADER_SIZE..];
+        let frame_len = Self::frame_len(usize::from(size.0), usize::from(size.1))
+            .ok_or("Mirror frame too large")?;
+        match message[5] {
+            KEY_FRAME if payload.len() == frame_len => {
+                self.size = size;
+                self.frame = payload.to_vec();
+            }
+            DELTA_FRAME if size == self.size => {
+                let mut frame = self.frame.clone();
+                let mut rest = payload;
+                while !rest.is_empty() {
+                    if rest.len() < RUN_OVERHEAD {
+                        return Err("Truncated mirror delta");
+                    }
+                    let start = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+                    let count = usize::from(u16::from_be_bytes([rest[4], rest[5]]));
+                    let bytes = rest
+                        .get(RUN_OVERHEAD..RUN_OVERHEAD + count * 3)
+                        .ok_or("Truncated mirror delta")?;
+                    let range = start
+                        .checked_add(count)
+                        .and_then(|end| Some(start.checked_mul(3)?..end.checked_mul(3)?))
+                        .and_then(|range| frame.get_mut(range))
+                        .ok_or("Mirror delta out of bounds")?;
+                    range.copy_from_slice(bytes);
+                    rest = &rest[RUN_OVERHEAD + count * 3..];
+                }
+                self.frame = frame;
+            }
+            _ => return Err("Invalid mirror frame"),
+        }
+        Ok(())
+    }
+
+    /// Bytes of a key frame of the given size, `None` if it's larger than allowed
+    fn frame_len(width: usize, height: usize) -> Option<usize> {
+        let pixels = width.checked_mul(height)?;
+        (pixels <= MAX_PIXELS).then_some(pixels * 3)
+    }
+
+    /// Width and height of the last received frame, zero before the first one
+    #[must_use]
+    pub const fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Draws the last received frame, centered on the canvas.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        let (width, height) = canvas.canvas_size();
+        let x0 = (width - i32::from(self.size.0)) / 2;
+        let y0 = (height - i32::from(self.size.1)) / 2;
+        for (i, pixel) in (0..).zip(self.frame.chunks_exact(3)) {
+            let (x, y) = (i % i32::from(self.size.0), i / i32::from(self.size.0));
+            let color = LedColor {
+                red: pixel[0],
+                green: pixel[1],
+                blue: pixel[2],
+            };
+            canvas.set(x0 + x, y0 + y, &color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receiver() -> MirrorReceiver {
+        MirrorReceiver::bind("127.0.0.1:0").unwrap()
+    }
+
+    #[test]
+    fn key_and_delta_frames() {
+        let mut encoder = MirrorEncoder::new(4, 2);
+        let mut receiver = receiver();
+        let mut frame = vec![0_u8; 4 * 2 * 3];
+        let key = encoder.encode(&frame, (4, 2)).unwrap();
+        assert_eq!((key[5], key.len()), (KEY_FRAME, HEADER_SIZE + 24));
+        receiver.apply(&key).unwrap();
+
+        frame[3] = 255;
+        frame[21..24].copy_from_slice(&[1, 2, 3]);
+        let delta = encoder.encode(&frame, (4, 2)).unwrap();
+        // two runs, too far apart to merge
+        assert_eq!((delta[5], delta.len()), (DELTA_FRAME, HEADER_SIZE + 2 * 9));
+        receiver.apply(&delta).unwrap();
+        assert_eq!(receiver.frame, frame);
+        assert_eq!(receiver.size(), (4, 2));
+
+        assert!(receiver.apply(&delta[..delta.len() - 1]).is_err());
+        assert!(receiver.apply(b"not a mirror message").is_err());
+
+        // a run starting far past the frame
+        let mut far = delta[..HEADER_SIZE].to_vec();
+        far.extend_from_slice(&u32::MAX.to_be_bytes());
+        far.extend_from_slice(&[0, 1, 1, 2, 3]);
+        assert!(receiver.apply(&far).is_err());
+        assert_eq!(receiver.frame, frame);
+    }
+
+    #[test]
+    fn too_large() {
+        let huge = MirrorEncoder::new(u16::MAX, u16::MAX).message(KEY_FRAME, &[]);
+        let mut header = huge[..HEADER_SIZE].to_vec();
+        header[10..14].copy_from_slice(&u32::MAX.to_be_bytes());
+        let error = MirrorReceiver::read_message(&mut header.as_slice());
+        assert_eq!(error, Err("Mirror message too large"));
+        assert!(receiver().apply(&huge).is_err());
+    }
+
+    #[test]
+    fn downsampling() {
+        let rgb = [10, 0, 0, 30, 0, 0, 0, 0, 0, 0, 0, 40];
+        assert_eq!(downsample(&rgb, (2, 2), (1, 1)), [10, 0, 10]);
+        let mut encoder = MirrorEncoder::new(1, 1);
+        assert!(encoder.encode(&rgb, (3, 2)).is_err());
+        assert_eq!(
+            encoder.encode(&rgb, (2, 2)).unwrap()[HEADER_SIZE..],
+            [10, 0, 10]
+        );
+    }
+
+    #[test]
+    fn over_tcp() {
+        let mut receiver = receiver().timeout(Duration::from_millis(20));
+        let address = receiver.listener.local_addr().unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut encoder = MirrorEncoder::new(2, 1);
+        encoder
+            .send(&mut stream, &[1, 2, 3, 4, 5, 6], (2, 1))
+            .unwrap();
+        let received = (0..50).any(|_| receiver.receive().unwrap());
+        assert!(received);
+        assert_eq!(receiver.frame, [1, 2, 3, 4, 5, 6]);
+
+        // a silent sender makes room for the next one
+        receiver = receiver.idle_timeout(Duration::ZERO);
+        assert!(!receiver.receive().unwrap());
+        assert!(receiver.stream.is_none());
+    }
+}
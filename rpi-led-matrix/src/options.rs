@@ -1,5 +1,5 @@
 use libc::c_int;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 
 use crate::ffi;
 
@@ -13,7 +13,117 @@ pub struct LedRuntimeOptions(pub(crate) ffi::CLedRuntimeOptions);
 
 type LedMatrixOptionsResult = Result<(), &'static str>;
 
+/// The board connecting the Raspberry Pi to the panels, see
+/// [`LedMatrixOptions::set_board`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Board {
+    /// The boards following the pinout of the C++ library, like the active-3 board, with up to
+    /// three parallel chains
+    Regular,
+    /// The Adafruit RGB Matrix HAT or Bonnet, driving a single chain. `pwm_mod` is set when
+    /// GPIO4 and GPIO18 are bridged, which allows hardware pulsing for less flicker
+    AdafruitBonnet {
+        /// GPIO4 and GPIO18 are bridged
+        pwm_mod: bool,
+    },
+}
+
+impl Board {
+    /// The name of the GPIO mapping of the board
+    #[must_use]
+    pub const fn hardware_mapping(self) -> &'static str {
+        match self {
+            Self::Regular => "regular",
+            Self::AdafruitBonnet { pwm_mod: false } => "adafruit-hat",
+            Self::AdafruitBonnet { pwm_mod: true } => "adafruit-hat-pwm",
+        }
+    }
+
+    /// The most chains the board drives in parallel
+    #[must_use]
+    pub const fn max_parallel(self) -> u32 {
+        match self {
+            Self::Regular => 3,
+            Self::AdafruitBonnet { .. } => 1,
+        }
+    }
+}
+
 impl LedMatrixOptions {
+    /// Names of the GPIO mappings known to the C++ library, see
+    /// [`set_hardware_mapping`](Self::set_hardware_mapping).
+    pub const HARDWARE_MAPPINGS: &'static [&'static str] = &[
+        "regular",
+        "adafruit-hat",
+        "adafruit-hat-pwm",
+        "regular-pi1",
+        "classic",
+        "classic-pi1",
+        "compute-module",
+    ];
+
+    /// Names of the multiplexing types, indexed by their value for
+    /// [`set_multiplexing`](Self::set_multiplexing).
+    pub const MULTIPLEXINGS: &'static [&'static str] = &[
+        "direct",
+        "Stripe",
+        "Checkered",
+        "Spiral",
+        "ZStripe",
+        "ZnMirrorZStripe",
+        "coreman",
+        "Kaler2Scan",
+        "ZStripeUneven",
+        "P10-128x4-Z",
+        "QiangLiQ8",
+        "InversedZStripe",
+        "P10Outdoor1R1G1-1",
+        "P10Outdoor1R1G1-2",
+        "P10Outdoor1R1G1-3",
+        "P10CoremanMapper",
+        "P8Outdoor1R1G1",
+    ];
+
+    /// Descriptions of the row address types, indexed by their value for
+    /// [`set_row_addr_type`](Self::set_row_addr_type).
+    pub const ROW_ADDRESS_TYPES: &'static [&'static str] = &[
+        "default",
+        "AB-addressed panels",
+        "direct row select",
+        "ABC-addressed panels",
+        "ABC Shift + DE direct",
+    ];
+
+    /// Names of the pixel mappers, see [`set_pixel_mapper_config`](Self::set_pixel_mapper_config).
+    pub const PIXEL_MAPPERS: &'static [&'static str] =
+        &["Mirror", "Rotate", "U-mapper", "V-mapper"];
+
+    /// Names of the special panel types, see [`set_panel_type`](Self::set_panel_type).
+    pub const PANEL_TYPES: &'static [&'static str] = &["FM6126A", "FM6127"];
+
+    /// Looks up the value of a multiplexing type by its name, ignoring case.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedMatrixOptions;
+    /// assert_eq!(LedMatrixOptions::multiplexing_by_name("checkered"), Some(2));
+    /// assert_eq!(LedMatrixOptions::multiplexing_by_name("Lattice"), None);
+    /// ```
+    #[must_use]
+    pub fn multiplexing_by_name(name: &str) -> Option<u32> {
+        Self::MULTIPLEXINGS
+            .iter()
+            .position(|known| known.eq_ignore_ascii_case(name))
+            .map(|index| index as u32)
+    }
+
+    /// Returns true if the C++ library knows the GPIO mapping `name`, ignoring case as it does.
+    #[must_use]
+    pub fn is_hardware_mapping(name: &str) -> bool {
+        Self::HARDWARE_MAPPINGS
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(name))
+    }
+
     /// Creates a new `LedMatrixOptions` struct with the default parameters.
     ///
     /// ```
@@ -51,6 +161,59 @@ impl LedMatrixOptions {
         })
     }
 
+    /// Sets up the options for `board`: its GPIO mapping and, for the Adafruit HAT and Bonnet,
+    /// hardware pulsing, which only works with the PWM mod.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{Board, LedMatrixOptions};
+    /// let mut options = LedMatrixOptions::new();
+    /// options.set_board(Board::AdafruitBonnet { pwm_mod: true });
+    /// assert_eq!(options.board(), Some(Board::AdafruitBonnet { pwm_mod: true }));
+    /// assert!(options.check_board().is_ok());
+    /// ```
+    pub fn set_board(&mut self, board: Board) {
+        self.set_hardware_mapping(board.hardware_mapping());
+        if let Board::AdafruitBonnet { pwm_mod } = board {
+            self.set_hardware_pulsing(pwm_mod);
+        }
+    }
+
+    /// The board the GPIO mapping is for, or `None` for other mappings.
+    #[must_use]
+    pub fn board(&self) -> Option<Board> {
+        let mapping = unsafe { CStr::from_ptr(self.0.hardware_mapping) }.to_string_lossy();
+        [
+            Board::Regular,
+            Board::AdafruitBonnet { pwm_mod: false },
+            Board::AdafruitBonnet { pwm_mod: true },
+        ]
+        .into_iter()
+        .find(|board| board.hardware_mapping().eq_ignore_ascii_case(&mapping))
+    }
+
+    /// Checks the options against what the board of the GPIO mapping supports, so mistakes
+    /// show up as an error instead of a blank or flickering panel.
+    ///
+    /// # Errors
+    /// If more chains run in parallel than the board drives, or hardware pulsing is enabled
+    /// on an Adafruit HAT or Bonnet without the PWM mod.
+    pub fn check_board(&self) -> LedMatrixOptionsResult {
+        let Some(board) = self.board() else {
+            return Ok(());
+        };
+        if self.0.parallel as u32 > board.max_parallel() {
+            return Err(match board {
+                Board::Regular => "The regular mapping drives at most 3 parallel chains",
+                Board::AdafruitBonnet { .. } => "The Adafruit HAT and Bonnet drive a single chain",
+            });
+        }
+        let pulsing = self.0.disable_hardware_pulsing == 0;
+        if pulsing && board == (Board::AdafruitBonnet { pwm_mod: false }) {
+            return Err("Hardware pulsing on the Adafruit HAT and Bonnet needs the PWM mod");
+        }
+        Ok(())
+    }
+
     /// Sets the type of GPIO mapping used (e.g., "adafruit-hat-pwm").
     ///
     /// # Panics
@@ -285,6 +448,11 @@ impl LedRuntimeOptions {
     }
 
     /// If True, make the process run in the background as daemon.
+    ///
+    /// The C++ library forks when the matrix is created, keeping only the calling thread, so
+    /// [`LedMatrix::new`](crate::LedMatrix::new) fails if other threads are running. Buffered
+    /// output is flushed before. To daemonize at a time of your choosing, e.g. before setting up
+    /// logging, use [`daemonize`](crate::daemonize) instead.
     pub fn set_daemon(&mut self, daemon: bool) {
         self.0.daemon = if daemon { 1 } else { 0 };
     }
@@ -298,6 +466,41 @@ impl LedRuntimeOptions {
     pub fn set_do_gpio_init(&mut self, do_gpio_init: bool) {
         self.0.do_gpio_init = do_gpio_init;
     }
+
+    /// The GPIO slowdown, see [`set_gpio_slowdown`](Self::set_gpio_slowdown).
+    #[must_use]
+    pub const fn gpio_slowdown(&self) -> u32 {
+        self.0.gpio_slowdown as u32
+    }
+
+    /// Returns true if the process runs in the background as daemon.
+    #[must_use]
+    pub const fn daemon(&self) -> bool {
+        self.0.daemon != 0
+    }
+
+    /// Returns true if privileges are dropped from 'root' after initializing the hardware.
+    #[must_use]
+    pub const fn drop_privileges(&self) -> bool {
+        self.0.drop_privileges != 0
+    }
+
+    /// Returns true if the GPIO pins are initialized.
+    #[must_use]
+    pub const fn do_gpio_init(&self) -> bool {
+        self.0.do_gpio_init
+    }
+}
+
+impl Clone for LedRuntimeOptions {
+    fn clone(&self) -> Self {
+        Self(ffi::CLedRuntimeOptions {
+            gpio_slowdown: self.0.gpio_slowdown,
+            daemon: self.0.daemon,
+            drop_privileges: self.0.drop_privileges,
+            do_gpio_init: self.0.do_gpio_init,
+        })
+    }
 }
 
 impl Default for LedRuntimeOptions {
@@ -305,3 +508,46 @@ impl Default for LedRuntimeOptions {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_options() {
+        let mut rt_options = LedRuntimeOptions::new();
+        assert_eq!(rt_options.gpio_slowdown(), 1);
+        assert!(!rt_options.daemon());
+        assert!(rt_options.drop_privileges());
+        rt_options.set_gpio_slowdown(4);
+        rt_options.set_daemon(true);
+        rt_options.set_drop_privileges(false);
+        rt_options.set_do_gpio_init(false);
+        let snapshot = rt_options.clone();
+        assert_eq!(snapshot.gpio_slowdown(), 4);
+        assert!(snapshot.daemon());
+        assert!(!snapshot.drop_privileges());
+        assert!(!snapshot.do_gpio_init());
+    }
+
+    #[test]
+    fn board() {
+        let mut options = LedMatrixOptions::new();
+        assert_eq!(options.board(), Some(Board::Regular));
+        options.set_board(Board::AdafruitBonnet { pwm_mod: false });
+        assert_eq!(
+            options.board(),
+            Some(Board::AdafruitBonnet { pwm_mod: false })
+        );
+        assert!(options.check_board().is_ok());
+        options.set_hardware_pulsing(true);
+        assert!(options.check_board().is_err());
+        options.set_board(Board::AdafruitBonnet { pwm_mod: true });
+        assert!(options.check_board().is_ok());
+        options.set_parallel(2);
+        assert!(options.check_board().is_err());
+        options.set_hardware_mapping("classic");
+        assert_eq!(options.board(), None);
+        assert!(options.check_board().is_ok());
+    }
+}
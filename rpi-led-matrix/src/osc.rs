@@ -0,0 +1,455 @@
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+
+use crate::{AppCommand, AppControl, SceneId};
+
+/// Largest packet read from the socket, more than fits a UDP datagram on most networks
+const MAX_PACKET: usize = 65536;
+
+/// An argument of an [`OscMessage`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    /// A 32 bit integer, type tag `i`
+    Int(i32),
+    /// A 64 bit integer, type tag `h`
+    Long(i64),
+    /// A 32 bit float, type tag `f`
+    Float(f32),
+    /// A 64 bit float, type tag `d`
+    Double(f64),
+    /// A string, type tags `s` and `S`
+    String(String),
+    /// Raw bytes, type tag `b`
+    Blob(Vec<u8>),
+    /// A boolean, type tags `T` and `F`
+    Bool(bool),
+    /// No value, type tag `N`
+    Nil,
+}
+
+impl OscArg {
+    /// The argument as a number, with booleans as 0 and 1
+    #[must_use]
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Self::Int(value) => Some(value as f32),
+            Self::Long(value) => Some(value as f32),
+            Self::Float(value) => Some(value),
+            Self::Double(value) => Some(value as f32),
+            Self::Bool(value) => Some(f32::from(u8::from(value))),
+            _ => None,
+        }
+    }
+
+    /// The argument as a string, if it is one
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A message of the Open Sound Control protocol: an address like `/led/brightness` and its
+/// arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    /// The address the message is sent to
+    pub address: String,
+    /// The arguments, in order
+    pub args: Vec<OscArg>,
+}
+
+/// Reads OSC packets, keeping track of the position
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], &'static str> {
+        if count > self.bytes.len() {
+            return Err("Truncated OSC packet");
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    /// Reads a null terminated string, padded to four bytes.
+    fn string(&mut self) -> Result<String, &'static str> {
+        let length = self
+            .bytes
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or("Unterminated OSC string")?;
+        let string =
+            std::str::from_utf8(&self.bytes[..length]).map_err(|_| "Invalid OSC string")?;
+        self.take((length + 4) & !3)?;
+        Ok(string.to_string())
+    }
+
+    /// Reads a size followed by that many bytes, padded to four bytes.
+    fn blob(&mut self) -> Result<&'a [u8], &'static str> {
+        let size = usize::try_from(i32::from_be_bytes(self.array()?))
+            .map_err(|_| "Invalid OSC blob size")?;
+        let blob = self.take(size)?;
+        self.take((4 - size % 4) % 4)?;
+        Ok(blob)
+    }
+}
+
+impl OscMessage {
+    /// Creates a message to `address` with `args`.
+    #[must_use]
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        Self {
+            address: address.to_string(),
+            args,
+        }
+    }
+
+    /// Decodes an OSC packet, a message or a bundle of messages. The time tags of bundles are
+    /// ignored, their messages are returned right away in order.
+    ///
+    /// # Errors
+    /// If the packet isn't valid OSC, or has arguments of unsupported types.
+    pub fn decode(packet: &[u8]) -> Result<Vec<Self>, &'static str> {
+        let mut messages = Vec::new();
+        Self::decode_into(packet, &mut messages)?;
+        Ok(messages)
+    }
+
+    fn decode_into(packet: &[u8], messages: &mut Vec<Self>) -> Result<(), &'static str> {
+        let mut reader = Reader { bytes: packet };
+        let address = reader.string()?;
+        if address == "#bundle" {
+            reader.take(8)?;
+            while !reader.bytes.is_empty() {
+                Self::decode_into(reader.blob()?, messages)?;
+            }
+            return Ok(());
+        }
+        if !address.starts_with('/') {
+            return Err("Invalid OSC address");
+        }
+        // old implementations may leave out the type tags of messages without arguments
+        let tags = if reader.bytes.is_empty() {
+            String::from(",")
+        } else {
+            reader.string()?
+        };
+        let tags = tags.strip_prefix(',').ok_or("Missing OSC type tags")?;
+        let mut args = Vec::with_capacity(tags.len());
+        for tag in tags.chars() {
+            let arg = match tag {
+                'i' => OscArg::Int(i32::from_be_bytes(reader.array()?)),
+                'h' => OscArg::Long(i64::from_be_bytes(reader.array()?)),
+                'f' => OscArg::Float(f32::from_be_bytes(reader.array()?)),
+                'd' => OscArg::Double(f64::from_be_bytes(reader.array()?)),
+                's' | 'S' => OscArg::String(reader.string()?),
+                'b' => OscArg::Blob(reader.blob()?.to_vec()),
+                'T' => OscArg::Bool(true),
+                'F' => OscArg::Bool(false),
+                'N' => OscArg::Nil,
+                _ => return Err("Unsupported OSC argument type"),
+            };
+            args.push(arg);
+        }
+        messages.push(Self { address, args });
+        Ok(())
+    }
+
+    /// Encodes the message as an OSC packet, e.g. to send feedback to a controller.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        fn string(packet: &mut Vec<u8>, string: &str) {
+            packet.extend_from_slice(string.as_bytes());
+            packet.resize((packet.len() + 4) & !3, 0);
+        }
+        let mut packet = Vec::new();
+        string(&mut packet, &self.address);
+        let tags: String = self
+            .args
+            .iter()
+            .map(|arg| match arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Long(_) => 'h',
+                OscArg::Float(_) => 'f',
+                OscArg::Double(_) => 'd',
+                OscArg::String(_) => 's',
+                OscArg::Blob(_) => 'b',
+                OscArg::Bool(true) => 'T',
+                OscArg::Bool(false) => 'F',
+                OscArg::Nil => 'N',
+            })
+            .collect();
+        string(&mut packet, &format!(",{tags}"));
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Long(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Float(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Double(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::String(value) => string(&mut packet, value),
+                OscArg::Blob(value) => {
+                    packet.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                    packet.extend_from_slice(value);
+                    packet.resize((packet.len() + 3) & !3, 0);
+                }
+                OscArg::Bool(_) | OscArg::Nil => {}
+            }
+        }
+        packet
+    }
+}
+
+type OscHook = Box<dyn FnMut(&OscMessage) -> Option<AppCommand> + Send>;
+
+enum Route {
+    Brightness,
+    NextScene,
+    Scenes(Vec<SceneId>),
+    Cue,
+    Text(Arc<Mutex<String>>),
+    Param(Arc<Mutex<f32>>),
+    Custom(OscHook),
+}
+
+/// Maps OSC addresses to [`AppCommand`]s and shared values, so VJ and lighting tools can
+/// control a [`MatrixApp`](crate::MatrixApp).
+///
+/// Buttons of controllers like TouchOSC send a value on press and release, and often encode
+/// the choice in the address, so routes taking an index or a name also accept it as the last
+/// part of the address, like `/scene/2`, and ignore messages with a value of 0.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedCanvas, MatrixApp, OscRoutes};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+/// let text = Arc::new(Mutex::new(String::from("Hello")));
+/// let speed = Arc::new(Mutex::new(1.0));
+/// let routes = OscRoutes::new()
+///     .brightness("/led/brightness")
+///     .text("/led/text", &text)
+///     .param("/led/speed", &speed);
+/// MatrixApp::builder()
+///     .osc("0.0.0.0:9000", routes)
+///     .scene(Duration::from_secs(10), move |canvas: &mut LedCanvas, elapsed: Duration| {
+///         let _ = (text.lock().unwrap().as_str(), *speed.lock().unwrap());
+///     })
+///     .build()
+///     .unwrap()
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct OscRoutes {
+    routes: Vec<(String, Route)>,
+}
+
+impl OscRoutes {
+    /// Creates a router without routes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn route(mut self, address: &str, route: Route) -> Self {
+        self.routes
+            .push((address.trim_end_matches('/').to_string(), route));
+        self
+    }
+
+    /// Sets the brightness from `address`, as a float from 0 to 1 or an integer percentage.
+    #[must_use]
+    pub fn brightness(self, address: &str) -> Self {
+        self.route(address, Route::Brightness)
+    }
+
+    /// Moves on to the next scene on a message to `address`.
+    #[must_use]
+    pub fn next_scene(self, address: &str) -> Self {
+        self.route(address, Route::NextScene)
+    }
+
+    /// Switches to one of `scenes` by its index, sent to `address` or as in `/scene/1`.
+    #[must_use]
+    pub fn scenes(self, address: &str, scenes: Vec<SceneId>) -> Self {
+        self.route(address, Route::Scenes(scenes))
+    }
+
+    /// Triggers the [cue](crate::CueList) event named by a string sent to `address`, or as in
+    /// `/cue/drop`.
+    #[must_use]
+    pub fn cue(self, address: &str) -> Self {
+        self.route(address, Route::Cue)
+    }
+
+    /// Stores a string sent to `address` in `text`, e.g. for a scene showing it.
+    #[must_use]
+    pub fn text(self, address: &str, text: &Arc<Mutex<String>>) -> Self {
+        self.route(address, Route::Text(Arc::clone(text)))
+    }
+
+    /// Stores a number sent to `address` in `value`, e.g. the speed of an effect.
+    #[must_use]
+    pub fn param(self, address: &str, value: &Arc<Mutex<f32>>) -> Self {
+        self.route(address, Route::Param(Arc::clone(value)))
+    }
+
+    /// Calls `hook` with every message to `address`, sending the command it returns.
+    #[must_use]
+    pub fn on(
+        self,
+        address: &str,
+        hook: impl FnMut(&OscMessage) -> Option<AppCommand> + Send + 'static,
+    ) -> Self {
+        self.route(address, Route::Custom(Box::new(hook)))
+    }
+
+    /// Handles `message` with the first matching route, returning the command to send.
+    fn handle(&mut self, message: &OscMessage) -> Option<AppCommand> {
+        let first = message.args.first();
+        for (address, route) in &mut self.routes {
+            let suffix = match message.address.strip_prefix(address.as_str()) {
+                Some("") => None,
+                Some(suffix) => match suffix.strip_prefix('/') {
+                    Some(suffix) if !suffix.contains('/') => Some(suffix),
+                    _ => continue,
+                },
+                None => continue,
+            };
+            // a button in the address is released
+            let released = suffix.is_some() && first.and_then(OscArg::as_f32) == Some(0.0);
+            return match (route, suffix) {
+                (Route::Brightness, None) => {
+                    let percent = match first? {
+                        OscArg::Float(value) => value * 100.0,
+                        OscArg::Double(value) => *value as f32 * 100.0,
+                        arg => arg.as_f32()?,
+                    };
+                    Some(AppCommand::Brightness(
+                        percent.round().clamp(0.0, 100.0) as u8
+                    ))
+                }
+                (Route::NextScene, None) => Some(AppCommand::NextScene),
+                (Route::Scenes(scenes), _) if !released => {
+                    let index = match suffix {
+                        Some(suffix) => suffix.parse().ok()?,
+                        None => first?.as_f32()? as usize,
+                    };
+                    scenes.get(index).copied().map(AppCommand::Scene)
+                }
+                (Route::Cue, _) if !released => {
+                    let event = suffix.or_else(|| first?.as_str())?;
+                    Some(AppCommand::Cue(event.to_string()))
+                }
+                (Route::Text(text), None) => {
+                    let value = first?.as_str()?;
+                    *text
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner) = value.to_string();
+                    None
+                }
+                (Route::Param(param), None) => {
+                    let value = first?.as_f32()?;
+                    *param
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner) = value;
+                    None
+                }
+                (Route::Custom(hook), None) => hook(message),
+                _ => continue,
+            };
+        }
+        None
+    }
+
+    /// Receives OSC packets on `socket` and sends the commands of their routes to `control`,
+    /// until the socket fails. Packets that aren't valid OSC are ignored.
+    pub fn serve(mut self, socket: &UdpSocket, control: &AppControl) {
+        let mut buffer = vec![0; MAX_PACKET];
+        while let Ok(length) = socket.recv(&mut buffer) {
+            let Ok(messages) = OscMessage::decode(&buffer[..length]) else {
+                continue;
+            };
+            for message in &messages {
+                if let Some(command) = self.handle(message) {
+                    control.send(command);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LedCanvas, SceneManager};
+    use std::time::Duration;
+
+    #[test]
+    fn routing() {
+        let message = OscMessage::new(
+            "/led/text",
+            vec![
+                OscArg::String(String::from("Hello")),
+                OscArg::Int(-3),
+                OscArg::Float(0.5),
+                OscArg::Blob(vec![1, 2, 3, 4, 5]),
+                OscArg::Bool(true),
+            ],
+        );
+        let packet = message.encode();
+        assert_eq!(packet.len() % 4, 0);
+        assert_eq!(OscMessage::decode(&packet), Ok(vec![message.clone()]));
+        let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+        let brightness = OscMessage::new("/led/brightness", vec![OscArg::Float(0.42)]);
+        for packet in [packet, brightness.encode()] {
+            bundle.extend_from_slice(&(packet.len() as i32).to_be_bytes());
+            bundle.extend_from_slice(&packet);
+        }
+        let messages = OscMessage::decode(&bundle).unwrap();
+        assert_eq!(messages, [message, brightness]);
+        assert!(OscMessage::decode(b"/led\0\0\0\0,x\0\0").is_err());
+
+        let mut scenes = SceneManager::new();
+        let ids = vec![
+            scenes.add(Duration::from_secs(1), |_: &mut LedCanvas, _| {}),
+            scenes.add(Duration::from_secs(1), |_: &mut LedCanvas, _| {}),
+        ];
+        let text = Arc::new(Mutex::new(String::new()));
+        let mut routes = OscRoutes::new()
+            .brightness("/led/brightness")
+            .text("/led/text", &text)
+            .scenes("/scene/", ids.clone())
+            .cue("/cue");
+        for message in &messages {
+            let command = routes.handle(message);
+            assert_eq!(
+                matches!(command, Some(AppCommand::Brightness(42))),
+                command.is_some()
+            );
+        }
+        assert_eq!(*text.lock().unwrap(), "Hello");
+        let send =
+            |routes: &mut OscRoutes, address, args| routes.handle(&OscMessage::new(address, args));
+        let scene = send(&mut routes, "/scene", vec![OscArg::Int(1)]);
+        assert!(matches!(scene, Some(AppCommand::Scene(id)) if id == ids[1]));
+        let scene = send(&mut routes, "/scene/0", vec![OscArg::Float(1.0)]);
+        assert!(matches!(scene, Some(AppCommand::Scene(id)) if id == ids[0]));
+        assert!(send(&mut routes, "/scene/0", vec![OscArg::Float(0.0)]).is_none());
+        assert!(send(&mut routes, "/scene/7", vec![]).is_none());
+        let cue = send(&mut routes, "/cue/drop", vec![]);
+        assert!(matches!(cue, Some(AppCommand::Cue(event)) if event == "drop"));
+        assert!(send(&mut routes, "/cue/drop/now", vec![]).is_none());
+        assert!(send(&mut routes, "/led/brightnesses", vec![OscArg::Int(5)]).is_none());
+    }
+}
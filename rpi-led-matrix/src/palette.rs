@@ -0,0 +1,259 @@
+use crate::{unicode, LedCanvas, LedColor, LedFont};
+
+/// Maps levels from 0 to 255 onto colors, blending evenly spaced colors like a gradient.
+///
+/// All 256 colors are computed up front, so mapping a level is a single lookup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<LedColor>,
+}
+
+impl Palette {
+    /// Creates a palette blending `stops` evenly from level 0 to 255, or all black without
+    /// any colors.
+    #[must_use]
+    pub fn new(stops: &[LedColor]) -> Self {
+        let colors = (0..=255u8)
+            .map(|level| match stops {
+                [] => LedColor {
+                    red: 0,
+                    green: 0,
+                    blue: 0,
+                },
+                [color] => *color,
+                stops => {
+                    let position = f32::from(level) / 255.0 * (stops.len() - 1) as f32;
+                    let index = (position as usize).min(stops.len() - 2);
+                    stops[index].lerp(&stops[index + 1], position - index as f32)
+                }
+            })
+            .collect();
+        Self { colors }
+    }
+
+    /// Black to white
+    #[must_use]
+    pub fn gray() -> Self {
+        Self::new(&[
+            LedColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+            },
+            LedColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+        ])
+    }
+
+    /// Black through red and yellow to white, like glowing metal
+    #[must_use]
+    pub fn heat() -> Self {
+        let [black, red, yellow, white] = [(0, 0, 0), (255, 0, 0), (255, 255, 0), (255, 255, 255)]
+            .map(|(red, green, blue)| LedColor { red, green, blue });
+        Self::new(&[black, red, yellow, white])
+    }
+
+    /// The color of `level`
+    #[must_use]
+    pub fn get(&self, level: u8) -> LedColor {
+        self.colors[usize::from(level)]
+    }
+
+    /// The color at `fraction` of the way from the first to the last color, clamped to
+    /// \[0,1\].
+    #[must_use]
+    pub fn at(&self, fraction: f32) -> LedColor {
+        self.get((fraction.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+}
+
+/// A grayscale buffer of levels from 0 to 255, mapped through a [`Palette`] when it's drawn.
+///
+/// Draw intensities into it instead of colors, like text, heatmaps or audio levels, and pick
+/// the colors once at the end. Fading and blurring the levels gives trails and glows that stay
+/// within the palette.
+///
+/// ```
+/// use rpi_led_matrix::{LedFont, LedMatrix, Levels, Palette};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let font = LedFont::new(Path::new("/usr/share/fonts/misc/6x10.bdf")).unwrap();
+/// let mut levels = Levels::new(64, 32);
+/// levels.draw_text(&font, "HOT", 2, 20, 255);
+/// levels.blur(1);
+/// canvas.draw_levels(&levels, 0, 0, &Palette::heat());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Levels {
+    width: i32,
+    height: i32,
+    /// Row-major, one entry per pixel
+    levels: Vec<u8>,
+}
+
+impl Levels {
+    /// Creates a buffer of the given size at level 0.
+    #[must_use]
+    pub fn new(width: i32, height: i32) -> Self {
+        let (width, height) = (width.max(0), height.max(0));
+        Self {
+            width,
+            height,
+            levels: vec![0; (width * height) as usize],
+        }
+    }
+
+    /// The width and height in pixels
+    #[must_use]
+    pub const fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// All levels, row by row
+    #[must_use]
+    pub fn levels(&self) -> &[u8] {
+        &self.levels
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y))
+            .then(|| (y * self.width + x) as usize)
+    }
+
+    /// The level at (`x`, `y`), or `None` outside the buffer.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<u8> {
+        self.index(x, y).map(|index| self.levels[index])
+    }
+
+    /// Sets the level at (`x`, `y`).
+    pub fn set(&mut self, x: i32, y: i32, level: u8) {
+        if let Some(index) = self.index(x, y) {
+            self.levels[index] = level;
+        }
+    }
+
+    /// Raises the level at (`x`, `y`) to at least `level`, so overlapping content doesn't
+    /// darken what's already there.
+    pub fn brighten(&mut self, x: i32, y: i32, level: u8) {
+        if let Some(index) = self.index(x, y) {
+            self.levels[index] = self.levels[index].max(level);
+        }
+    }
+
+    /// Sets all levels to `level`.
+    pub fn fill(&mut self, level: u8) {
+        self.levels.fill(level);
+    }
+
+    /// Sets all levels to 0.
+    pub fn clear(&mut self) {
+        self.fill(0);
+    }
+
+    /// Lowers all levels by `amount`, e.g. once per frame for trails.
+    pub fn fade(&mut self, amount: u8) {
+        for level in &mut self.levels {
+            *level = level.saturating_sub(amount);
+        }
+    }
+
+    /// Averages every level with its neighbours up to `radius` pixels away, softening edges.
+    pub fn blur(&mut self, radius: i32) {
+        if radius <= 0 {
+            return;
+        }
+        // a box blur, horizontally and then vertically
+        for (step, length, lines) in [
+            (1, self.width, self.height),
+            (self.width, self.height, self.width),
+        ] {
+            let source = self.levels.clone();
+            let start = |line: i32| if step == 1 { line * self.width } else { line };
+            for line in 0..lines {
+                for i in 0..length {
+                    let window = (i - radius).max(0)..=(i + radius).min(length - 1);
+                    let count = window.clone().count() as u32;
+                    let sum: u32 = window
+                        .map(|j| u32::from(source[(start(line) + j * step) as usize]))
+                        .sum();
+                    self.levels[(start(line) + i * step) as usize] = (sum / count) as u8;
+                }
+            }
+        }
+    }
+
+    /// Draws text at `level` with the pen starting on the baseline at (`x`, `y`), returning
+    /// the distance it advanced.
+    pub fn draw_text(&mut self, font: &LedFont, text: &str, x: i32, y: i32, level: u8) -> i32 {
+        let mut pen = x;
+        for cluster in unicode::display_clusters(text) {
+            // combining marks are drawn on top of the character before them
+            for c in &cluster {
+                for (gx, gy) in font.glyph(*c).into_iter().flat_map(|glyph| glyph.pixels()) {
+                    self.brighten(pen + gx, y + gy, level);
+                }
+            }
+            pen += font.char_width(cluster[0]);
+        }
+        pen - x
+    }
+}
+
+impl LedCanvas {
+    /// Draws `levels` with the top left corner at (`x`, `y`), mapping every level through
+    /// `palette`.
+    ///
+    /// Pixels at level 0 are left as they are, so the levels can be drawn over a background.
+    /// Fill the canvas with the palette's first color to map every pixel.
+    pub fn draw_levels(&mut self, levels: &Levels, x: i32, y: i32, palette: &Palette) {
+        let width = levels.width.max(1);
+        let pixels = (0..)
+            .zip(&levels.levels)
+            .filter(|(_, &level)| level > 0)
+            .map(|(index, &level)| (x + index % width, y + index / width, palette.get(level)));
+        self.set_iter(pixels.collect::<Vec<_>>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping() {
+        let heat = Palette::heat();
+        assert_eq!(heat.get(0).red, 0);
+        assert_eq!(
+            heat.get(85),
+            LedColor {
+                red: 255,
+                green: 0,
+                blue: 0
+            }
+        );
+        assert_eq!(heat.get(170).green, 255);
+        assert_eq!(heat.at(2.0), heat.get(255));
+        assert_eq!(Palette::gray().at(0.5).blue, 128);
+        assert_eq!(Palette::new(&[]).get(200).red, 0);
+    }
+
+    #[test]
+    fn levels() {
+        let mut levels = Levels::new(5, 3);
+        levels.set(2, 1, 90);
+        levels.brighten(2, 1, 30);
+        assert_eq!(levels.get(2, 1), Some(90));
+        levels.blur(1);
+        // spread over a 3 by 3 box, or less at the edges
+        assert_eq!(levels.get(1, 0), Some(15));
+        assert_eq!(levels.get(4, 1), Some(0));
+        levels.fade(5);
+        assert_eq!(levels.get(2, 1), Some(5));
+        assert_eq!(levels.get(5, 1), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{LedCanvas, LedColor, Rect};
+
+/// The pixels of a region of a canvas, taken with [`LedCanvas::snapshot`] to put them back
+/// later with [`LedCanvas::restore`].
+///
+/// ```
+/// use rpi_led_matrix::{LedColor, LedMatrix, Rect};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let popup = Rect::new(8, 8, 48, 16);
+/// let below = canvas.snapshot(popup);
+/// canvas.set_clip(popup);
+/// canvas.fill(&LedColor { red: 0, green: 0, blue: 128 });
+/// canvas.clear_clip();
+/// // the popup is closed
+/// canvas.restore(&below);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch {
+    rect: Rect,
+    /// Row-major, `None` for pixels outside of the canvas
+    pixels: Vec<Option<LedColor>>,
+}
+
+impl Patch {
+    /// The region the pixels were taken from
+    #[must_use]
+    pub const fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The pixel at (`x`, `y`) in canvas coordinates, or `None` outside the region or canvas.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        let rect = self.rect;
+        rect.contains(x, y)
+            .then(|| self.pixels[((y - rect.y) * rect.width + x - rect.x) as usize])
+            .flatten()
+    }
+}
+
+impl LedCanvas {
+    /// Copies the pixels of `rect`, e.g. before drawing a menu or popup over them.
+    ///
+    /// Like [`get`](Self::get), the clip rectangle doesn't affect what's copied.
+    #[must_use]
+    pub fn snapshot(&self, rect: Rect) -> Patch {
+        let rect = Rect::new(rect.x, rect.y, rect.width.max(0), rect.height.max(0));
+        let pixels = (rect.y..rect.y + rect.height)
+            .flat_map(|y| (rect.x..rect.x + rect.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get(x, y))
+            .collect();
+        Patch { rect, pixels }
+    }
+
+    /// Puts the pixels of `patch` back where they were taken from, within the clip rectangle.
+    pub fn restore(&mut self, patch: &Patch) {
+        let rect = patch.rect;
+        let pixels: Vec<_> = (0..)
+            .zip(&patch.pixels)
+            .filter_map(|(index, color)| {
+                color.map(|color| {
+                    (
+                        rect.x + index % rect.width,
+                        rect.y + index / rect.width,
+                        color,
+                    )
+                })
+            })
+            .collect();
+        self.set_iter(pixels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LedColor, LedMatrix, Rect};
+
+    #[test]
+    #[serial_test::serial]
+    fn snapshot() {
+        let matrix = LedMatrix::new(None, None).unwrap();
+        let mut canvas = matrix.offscreen_canvas();
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        let blue = LedColor {
+            red: 0,
+            green: 0,
+            blue: 255,
+        };
+        canvas.fill(&red);
+        let patch = canvas.snapshot(Rect::new(-2, 0, 4, 4));
+        assert_eq!(patch.get(0, 0), Some(red));
+        assert_eq!(patch.get(-1, 0), None);
+        canvas.fill(&blue);
+        canvas.restore(&patch);
+        assert_eq!(canvas.get(1, 3), Some(red));
+        assert_eq!(canvas.get(2, 0), Some(blue));
+    }
+}
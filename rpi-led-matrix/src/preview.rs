@@ -0,0 +1,393 @@
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+
+use crate::http_server::{self, Request};
+use crate::{LedColor, LedMatrix};
+
+/// Shortest time between two frames sent to a viewer
+const MIN_FRAME_TIME: Duration = Duration::from_millis(33);
+
+/// The viewers served at once, further connections are closed right away
+const MAX_VIEWERS: usize = 8;
+
+/// How long a viewer may stay silent before it's pinged, and again before it's dropped
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for messages of the viewer between frames
+const POLL_TIME: Duration = Duration::from_millis(1);
+
+/// How long to wait for a new frame before looking for messages of the viewer again
+const FRAME_WAIT: Duration = Duration::from_millis(250);
+
+/// Opcodes of the WebSocket frames used, see RFC 6455
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+/// Appended to the key of a WebSocket handshake before hashing, see RFC 6455
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The page showing the stream, drawing every message onto a canvas scaled up without
+/// smoothing
+const VIEWER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width">
+<title>LED matrix</title>
+<style>
+body { margin: 0; background: #111; display: flex; height: 100vh; }
+canvas { margin: auto; max-width: 100%; max-height: 100%; image-rendering: pixelated; }
+</style>
+</head>
+<body>
+<canvas id="panel" width="64" height="32"></canvas>
+<script>
+const canvas = document.getElementById("panel");
+const context = canvas.getContext("2d");
+function connect() {
+  const socket = new WebSocket(`ws://${location.host}/stream`);
+  socket.binaryType = "arraybuffer";
+  socket.onmessage = (event) => {
+    const view = new DataView(event.data);
+    const width = view.getUint16(0), height = view.getUint16(2);
+    if (canvas.width !== width || canvas.height !== height) {
+      canvas.width = width;
+      canvas.height = height;
+      canvas.style.width = `${width * 10}px`;
+    }
+    const rgb = new Uint8Array(event.data, 4);
+    const image = context.createImageData(width, height);
+    for (let i = 0; i < width * height; i++) {
+      image.data.set(rgb.subarray(i * 3, i * 3 + 3), i * 4);
+      image.data[i * 4 + 3] = 255;
+    }
+    context.putImageData(image, 0, 0);
+  };
+  socket.onclose = () => setTimeout(connect, 1000);
+}
+connect();
+</script>
+</body>
+</html>
+"#;
+
+/// The latest frame, numbered so viewers can wait for the next one
+#[derive(Default)]
+struct Latest {
+    number: u64,
+    message: Arc<Vec<u8>>,
+    /// The viewers streaming frames, no frames are kept without any
+    viewers: usize,
+}
+
+/// Counts a viewer as streaming until dropped.
+struct Viewer<'a>(&'a Shared);
+
+impl<'a> Viewer<'a> {
+    fn join(shared: &'a Shared) -> Self {
+        lock(shared).viewers += 1;
+        Self(shared)
+    }
+}
+
+impl Drop for Viewer<'_> {
+    fn drop(&mut self) {
+        lock(self.0).viewers -= 1;
+    }
+}
+
+type Shared = Arc<(Mutex<Latest>, Condvar)>;
+
+/// Streams what the panel shows to browsers, for watching a panel mounted out of sight.
+///
+/// The server answers `http://<address>/` with a viewer page, which receives the frames over
+/// a WebSocket at `/stream`. Every message is a frame, its width and height as big endian
+/// `u16` followed by the pixels as RGB, row by row, so other tools can read the stream too.
+///
+/// Frames are taken from every [`swap`](LedMatrix::swap) as a
+/// [pre-swap hook](LedMatrix::add_pre_swap), and sent at most 30 times a second. Viewers that
+/// can't keep up skip frames, and no frames are taken while nobody watches. Up to 8 viewers are
+/// served at once, viewers that stop answering pings or reading frames are dropped. The server
+/// runs until the process ends.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, PreviewServer};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let preview = PreviewServer::start(&matrix, "0.0.0.0:8080").unwrap();
+/// println!("Watch at http://{}/", preview.local_addr());
+/// ```
+pub struct PreviewServer {
+    address: SocketAddr,
+}
+
+impl PreviewServer {
+    /// Listens for viewers on `address` and streams the frames swapped on `matrix`.
+    ///
+    /// # Errors
+    /// If the address can't be bound.
+    pub fn start(matrix: &LedMatrix, address: impl ToSocketAddrs) -> Result<Self, &'static str> {
+        let listener = TcpListener::bind(address).map_err(|_| "Couldn't bind preview address")?;
+        let address = listener
+            .local_addr()
+            .map_err(|_| "Couldn't bind preview address")?;
+        let shared = Shared::default();
+        let frames = Arc::clone(&shared);
+        matrix.add_pre_swap(move |frame| publish(&frames, frame.pixels(), frame.size()));
+        std::thread::spawn(move || {
+            http_server::serve(&listener, MAX_VIEWERS, move |stream| {
+                let _ = handle(stream, &shared);
+            });
+        });
+        Ok(Self { address })
+    }
+
+    /// The address the server listens on, e.g. to find the port when binding port 0
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+/// Locks the latest frame, even if a viewer panicked holding it.
+fn lock(shared: &Shared) -> std::sync::MutexGuard<'_, Latest> {
+    shared
+        .0
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Makes `pixels` the latest frame and wakes up the viewers, if there are any.
+fn publish(shared: &Shared, pixels: &[LedColor], (width, height): (i32, i32)) {
+    if lock(shared).viewers == 0 {
+        return;
+    }
+    let mut message = Vec::with_capacity(4 + pixels.len() * 3);
+    message.extend_from_slice(&(width as u16).to_be_bytes());
+    message.extend_from_slice(&(height as u16).to_be_bytes());
+    message.extend(pixels.iter().flat_map(|c| [c.red, c.green, c.blue]));
+    let mut latest = lock(shared);
+    latest.number += 1;
+    latest.message = Arc::new(message);
+    shared.1.notify_all();
+}
+
+/// Answers a request, streaming frames if it asks for a WebSocket.
+fn handle(mut stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = Request::read(&mut reader)?;
+    match (request.path.as_str(), request.header("sec-websocket-key")) {
+        ("/stream", Some(key)) => {
+            let _viewer = Viewer::join(shared);
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key)
+            )?;
+            // frames the viewer sent right after its request
+            let received = reader.buffer().to_vec();
+            stream_frames(&mut stream, received, shared)
+        }
+        ("/", None) => {
+            http_server::respond(&mut stream, "200 OK", "text/html; charset=utf-8", VIEWER)
+        }
+        _ => http_server::respond(&mut stream, "404 Not Found", "text/plain", ""),
+    }
+}
+
+/// Sends every new frame as a binary WebSocket message, answering the pings of the viewer,
+/// until it closes the connection or stops answering pings.
+fn stream_frames(stream: &mut TcpStream, mut received: Vec<u8>, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(POLL_TIME))?;
+    let mut sent = 0;
+    let (mut heard, mut pinged) = (Instant::now(), false);
+    let mut buffer = [0; 256];
+    loop {
+        let start = Instant::now();
+        match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => {
+                received.extend_from_slice(&buffer[..read]);
+                (heard, pinged) = (start, false);
+            }
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(error) => return Err(error),
+        }
+        while let Some((opcode, payload)) = take_frame(&mut received)? {
+            match opcode {
+                CLOSE => {
+                    // echo the status code, if any
+                    stream.write_all(&header(CLOSE, payload.len().min(2)))?;
+                    return stream.write_all(&payload[..payload.len().min(2)]);
+                }
+                PING => {
+                    stream.write_all(&header(PONG, payload.len()))?;
+                    stream.write_all(&payload)?;
+                }
+                // pongs and messages of the viewer
+                _ => {}
+            }
+        }
+        if heard.elapsed() > 2 * TIMEOUT {
+            return Err(ErrorKind::TimedOut.into());
+        }
+        if heard.elapsed() > TIMEOUT && !pinged {
+            stream.write_all(&header(PING, 0))?;
+            pinged = true;
+        }
+
+        let message = {
+            let (latest, _) = shared
+                .1
+                .wait_timeout_while(lock(shared), FRAME_WAIT, |latest| latest.number == sent)
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            (latest.number != sent).then(|| {
+                sent = latest.number;
+                Arc::clone(&latest.message)
+            })
+        };
+        if let Some(message) = message {
+            stream.write_all(&header(BINARY, message.len()))?;
+            stream.write_all(&message)?;
+            std::thread::sleep(MIN_FRAME_TIME.saturating_sub(start.elapsed()));
+        }
+    }
+}
+
+/// Takes the first complete frame sent by the viewer off `received`, unmasked.
+///
+/// Viewers only send control frames and short messages, a frame longer than that, or one
+/// without a mask, ends the connection.
+fn take_frame(received: &mut Vec<u8>) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let [first, second, ..] = received[..] else {
+        return Ok(None);
+    };
+    let length = usize::from(second & 0x7f);
+    if second & 0x80 == 0 || length > 125 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "Unexpected WebSocket frame",
+        ));
+    }
+    let Some(masked) = received.get(2..6 + length) else {
+        return Ok(None);
+    };
+    let (mask, payload) = masked.split_at(4);
+    let payload = (payload.iter().zip(mask.iter().cycle()))
+        .map(|(byte, key)| byte ^ key)
+        .collect();
+    received.drain(..6 + length);
+    Ok(Some((first & 0x0f, payload)))
+}
+
+/// The header of an unmasked, final WebSocket frame with a payload of `length` bytes
+fn header(opcode: u8, length: usize) -> Vec<u8> {
+    let mut header = vec![0x80 | opcode];
+    match length {
+        0..=125 => header.push(length as u8),
+        126..=0xffff => {
+            header.push(126);
+            header.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        _ => {
+            header.push(127);
+            header.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    header
+}
+
+/// The `Sec-WebSocket-Accept` answer to the key of a handshake
+fn accept_key(key: &str) -> String {
+    let hash = digest(
+        &SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{key}{WEBSOCKET_GUID}").as_bytes(),
+    );
+    STANDARD.encode(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+
+    #[test]
+    fn streaming() {
+        // the example handshake of RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(header(BINARY, 300), [0x82, 126, 1, 44]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let shared = Shared::default();
+        let server = Arc::clone(&shared);
+        // nobody watches yet
+        publish(&shared, &[], (0, 0));
+        assert_eq!(lock(&shared).number, 0);
+        std::thread::spawn(move || {
+            http_server::serve(&listener, MAX_VIEWERS, move |stream| {
+                let _ = handle(stream, &server);
+            });
+        });
+
+        let mut page = String::new();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        stream.read_to_string(&mut page).unwrap();
+        assert!(page.starts_with("HTTP/1.1 200 OK") && page.ends_with(VIEWER));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(
+                b"GET /stream HTTP/1.1\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            )
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut response = String::new();
+        while response != "\r\n" {
+            response.clear();
+            reader.read_line(&mut response).unwrap();
+            if response.starts_with("HTTP") {
+                assert!(response.contains("101"));
+            }
+        }
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        publish(&shared, &[red, red], (2, 1));
+        let mut message = [0; 12];
+        reader.read_exact(&mut message).unwrap();
+        assert_eq!(message, [0x82, 10, 0, 2, 0, 1, 255, 0, 0, 255, 0, 0]);
+
+        // a ping is answered, and a close echoed before the connection ends
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(&[0x89, 0x82, 1, 2, 3, 4, b'h' ^ 1, b'i' ^ 2])
+            .unwrap();
+        let mut pong = [0; 4];
+        stream.read_exact(&mut pong).unwrap();
+        assert_eq!(pong, [0x8a, 2, b'h', b'i']);
+        stream
+            .write_all(&[0x88, 0x82, 0, 0, 0, 0, 0x03, 0xe8])
+            .unwrap();
+        let mut close = Vec::new();
+        stream.read_to_end(&mut close).unwrap();
+        assert_eq!(close, [0x88, 2, 0x03, 0xe8]);
+        assert!(take_frame(&mut vec![0x82, 1, 0]).is_err());
+    }
+}
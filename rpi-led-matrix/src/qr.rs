@@ -0,0 +1,124 @@
+use qrcode::{Color, QrCode};
+
+use crate::{LedCanvas, LedColor, Rect};
+
+/// Modules of light border around a code that readers rely on to find it.
+const QUIET_ZONE: i32 = 4;
+
+/// Encodes `data` into a code, with the width and height in modules excluding the quiet zone.
+fn encode(data: &str) -> Result<(QrCode, i32), &'static str> {
+    let code = QrCode::new(data.as_bytes()).map_err(|_| "Couldn't encode QR code")?;
+    let width = code.width() as i32;
+    Ok((code, width))
+}
+
+/// The largest module size and quiet zone to fit a code of `modules` in a square of `side`
+/// pixels, shrinking the quiet zone down to a single module before the modules themselves.
+fn fit(modules: i32, side: i32) -> Option<(i32, i32)> {
+    (1..=QUIET_ZONE).rev().find_map(|quiet| {
+        let module_size = side / (modules + 2 * quiet);
+        (module_size > 0).then_some((module_size, quiet))
+    })
+}
+
+impl LedCanvas {
+    /// Draws a QR code of `data` with its top left corner at (`x`, `y`), returning the side
+    /// length in pixels.
+    ///
+    /// Light modules are lit white and dark modules turned off, which readers expect. The code
+    /// is surrounded by the standard quiet zone of four light modules, which counts towards its
+    /// side length. Modules are at least a pixel wide.
+    ///
+    /// ```
+    /// use rpi_led_matrix::LedMatrix;
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// let side = canvas.draw_qr("WIFI:T:WPA;S:guests;P:correct horse;;", 0, 0, 1).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If `data` is too long to fit into a QR code.
+    pub fn draw_qr(
+        &mut self,
+        data: &str,
+        x: i32,
+        y: i32,
+        module_size: i32,
+    ) -> Result<i32, &'static str> {
+        let (code, modules) = encode(data)?;
+        let module_size = module_size.max(1);
+        Ok(self.draw_qr_code(&code, modules, x, y, module_size, QUIET_ZONE))
+    }
+
+    /// Draws the largest QR code of `data` fitting `region`, centered within it, and returns
+    /// the area it took up.
+    ///
+    /// On small panels the quiet zone is narrowed, down to a single module, before the modules
+    /// get smaller, see [`draw_qr`](Self::draw_qr).
+    ///
+    /// # Errors
+    /// If `data` is too long to fit into a QR code, or the code doesn't fit `region` even with
+    /// single pixel modules.
+    pub fn draw_qr_fit(&mut self, data: &str, region: Rect) -> Result<Rect, &'static str> {
+        let (code, modules) = encode(data)?;
+        let (module_size, quiet) =
+            fit(modules, region.width.min(region.height)).ok_or("QR code doesn't fit region")?;
+        let side = (modules + 2 * quiet) * module_size;
+        let x = region.x + (region.width - side) / 2;
+        let y = region.y + (region.height - side) / 2;
+        self.draw_qr_code(&code, modules, x, y, module_size, quiet);
+        Ok(Rect::new(x, y, side, side))
+    }
+
+    fn draw_qr_code(
+        &mut self,
+        code: &QrCode,
+        modules: i32,
+        x: i32,
+        y: i32,
+        module_size: i32,
+        quiet: i32,
+    ) -> i32 {
+        let colors = code.to_colors();
+        let side = modules + 2 * quiet;
+        let module = |mx: i32, my: i32| {
+            let inside =
+                (quiet..quiet + modules).contains(&mx) && (quiet..quiet + modules).contains(&my);
+            let dark =
+                inside && colors[((my - quiet) * modules + mx - quiet) as usize] == Color::Dark;
+            let level = if dark { 0 } else { 255 };
+            LedColor {
+                red: level,
+                green: level,
+                blue: level,
+            }
+        };
+        let pixels = side * module_size;
+        self.set_iter((0..pixels).flat_map(|py| {
+            (0..pixels).map(move |px| {
+                let color = module(px / module_size, py / module_size);
+                (x + px, y + py, color)
+            })
+        }));
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_region() {
+        let (code, modules) = encode("HELLO").unwrap();
+        assert_eq!(modules, 21);
+        assert_eq!(code.to_colors().len(), 21 * 21);
+        // 29 modules with the full quiet zone
+        assert_eq!(fit(21, 64), Some((2, 4)));
+        assert_eq!(fit(21, 32), Some((1, 4)));
+        // narrower quiet zones on small panels
+        assert_eq!(fit(21, 25), Some((1, 2)));
+        assert_eq!(fit(21, 22), None);
+        assert!(encode(&"x".repeat(3000)).is_err());
+    }
+}
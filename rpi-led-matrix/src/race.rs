@@ -0,0 +1,139 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+use crate::ffi::CLedCanvas;
+
+static MODE: AtomicU8 = AtomicU8::new(RaceDetection::Off as u8);
+static RACES: AtomicUsize = AtomicUsize::new(0);
+/// The canvas handles being written to, with the thread writing
+static WRITERS: Mutex<Vec<(usize, ThreadId)>> = Mutex::new(Vec::new());
+
+/// What happens when threads write to the same canvas at once, see [`set_race_detection`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RaceDetection {
+    /// Writes aren't checked, the default
+    #[default]
+    Off,
+    /// Races are counted in [`races_detected`] and printed to stderr, with a backtrace if
+    /// `RUST_BACKTRACE` is set
+    Report,
+    /// The thread running into a race panics
+    Panic,
+}
+
+/// Checks writes to canvases for data races, to find them in debug builds before they
+/// corrupt frames.
+///
+/// [`LedCanvas`](crate::LedCanvas) is `Send` and `Sync`, but every
+/// [`LedMatrix::canvas`](crate::LedMatrix::canvas) call returns a new handle to the same
+/// canvas of the C library, which isn't safe to write from several threads. Once enabled,
+/// pixels sent to the panel, clears, fills and swaps are tracked per canvas, and a thread
+/// writing to a canvas another thread is writing to is reported. Tracking takes a lock per
+/// write, so leave it off in production.
+///
+/// ```
+/// use rpi_led_matrix::{set_race_detection, RaceDetection};
+/// if cfg!(debug_assertions) {
+///     set_race_detection(RaceDetection::Panic);
+/// }
+/// ```
+pub fn set_race_detection(mode: RaceDetection) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// The number of races found since [`set_race_detection`] enabled reporting
+#[must_use]
+pub fn races_detected() -> usize {
+    RACES.load(Ordering::Relaxed)
+}
+
+/// Marks a write to a canvas as running until dropped
+#[derive(Debug)]
+pub(crate) struct Writing {
+    handle: usize,
+}
+
+/// Starts a write to the canvas `handle`, reporting it if another thread is writing to it.
+pub(crate) fn writing(handle: *mut CLedCanvas) -> Option<Writing> {
+    let mode = match MODE.load(Ordering::Relaxed) {
+        m if m == RaceDetection::Report as u8 => RaceDetection::Report,
+        m if m == RaceDetection::Panic as u8 => RaceDetection::Panic,
+        _ => return None,
+    };
+    if handle.is_null() {
+        return None;
+    }
+    let handle = handle as usize;
+    let current = thread::current().id();
+    let mut writers = WRITERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let other = writers
+        .iter()
+        .find(|&&(written, id)| written == handle && id != current)
+        .map(|&(_, id)| id);
+    writers.push((handle, current));
+    drop(writers);
+    let guard = Writing { handle };
+    if let Some(other) = other {
+        RACES.fetch_add(1, Ordering::Relaxed);
+        let message = format!(
+            "rpi-led-matrix: {current:?} wrote to canvas {handle:#x} while {other:?} was \
+             writing to it"
+        );
+        if mode == RaceDetection::Panic {
+            panic!("{message}");
+        }
+        let backtrace = Backtrace::capture();
+        if backtrace.status() == BacktraceStatus::Captured {
+            eprintln!("{message}\n{backtrace}");
+        } else {
+            eprintln!("{message}");
+        }
+    }
+    Some(guard)
+}
+
+impl Drop for Writing {
+    fn drop(&mut self) {
+        let current = thread::current().id();
+        let mut writers = WRITERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(index) = writers
+            .iter()
+            .position(|&(handle, id)| handle == self.handle && id == current)
+        {
+            writers.swap_remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection() {
+        let write = |handle: usize| thread::spawn(move || writing(handle as _).is_some());
+        let handle = 0x5eed_0000;
+        assert!(writing(handle as _).is_none());
+        set_race_detection(RaceDetection::Report);
+        let before = races_detected();
+        let first = writing(handle as _);
+        assert!(first.is_some());
+        // the same thread writing again, and another thread writing elsewhere, aren't races
+        drop(writing(handle as _));
+        assert!(write(handle + 1).join().unwrap());
+        assert_eq!(races_detected(), before);
+
+        assert!(write(handle).join().unwrap());
+        assert_eq!(races_detected(), before + 1);
+        drop(first);
+        assert!(write(handle).join().unwrap());
+        assert_eq!(races_detected(), before + 1);
+        set_race_detection(RaceDetection::Off);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, RgbaImage};
+
+use crate::{Frame, LedColor, LedMatrix};
+
+/// Frames kept by default, about two minutes at 30 frames per second
+const DEFAULT_LIMIT: usize = 3600;
+
+/// How long a frame was shown, with its pixels as RGB bytes
+type TimedFrame = (Duration, Vec<u8>);
+
+#[derive(Debug)]
+struct Recording {
+    active: bool,
+    /// Frames with the time they were shown, changes only
+    frames: VecDeque<(Instant, Vec<LedColor>)>,
+    size: (i32, i32),
+    /// When the last frame stopped showing, `None` while it's still on the panel
+    end: Option<Instant>,
+    limit: usize,
+}
+
+/// Records what the panel shows and exports it as an animated GIF or PNG with the real frame
+/// timing, e.g. for documentation or to see what a remote display is doing.
+///
+/// The recorder taps every [`swap`](LedMatrix::swap) as a [pre-swap hook](LedMatrix::add_pre_swap),
+/// so it records the final frames with any overlays. Frames that don't change anything are
+/// merged into the frame before, so a static panel takes no memory over time. Only the latest
+/// [`limit`](Self::set_limit) frames are kept.
+///
+/// Recording can be started and stopped at any time from any thread through clones of the
+/// recorder.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, Recorder};
+/// use std::path::Path;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let recorder = Recorder::attach(&matrix);
+/// recorder.start();
+/// // draw and swap for a while
+/// recorder.stop();
+/// recorder.save(Path::new("display.gif")).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl Recorder {
+    /// Creates a stopped recorder taking the frames swapped on `matrix`.
+    #[must_use]
+    pub fn attach(matrix: &LedMatrix) -> Self {
+        let recorder = Self::new();
+        let tap = recorder.clone();
+        matrix.add_pre_swap(move |frame| tap.record(frame, Instant::now()));
+        recorder
+    }
+
+    fn new() -> Self {
+        let recording = Recording {
+            active: false,
+            frames: VecDeque::new(),
+            size: (0, 0),
+            end: None,
+            limit: DEFAULT_LIMIT,
+        };
+        Self {
+            recording: Arc::new(Mutex::new(recording)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Recording> {
+        self.recording
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Starts recording, discarding a previous recording.
+    pub fn start(&self) {
+        let mut recording = self.lock();
+        recording.frames.clear();
+        recording.end = None;
+        recording.active = true;
+    }
+
+    /// Stops recording, keeping the frames for export.
+    pub fn stop(&self) {
+        let mut recording = self.lock();
+        if recording.active {
+            recording.active = false;
+            recording.end = Some(Instant::now());
+        }
+    }
+
+    /// Starts recording if it's stopped and stops it otherwise, returning whether it's
+    /// recording now.
+    pub fn toggle(&self) -> bool {
+        if self.is_recording() {
+            self.stop();
+            false
+        } else {
+            self.start();
+            true
+        }
+    }
+
+    /// Whether frames are being recorded
+    #[must_use]
+    pub fn is_recording(&self) -> bool {
+        self.lock().active
+    }
+
+    /// The number of distinct frames recorded
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.lock().frames.len()
+    }
+
+    /// Sets how many frames are kept, 3600 by default. The oldest frames are dropped.
+    pub fn set_limit(&self, frames: usize) {
+        let mut recording = self.lock();
+        recording.limit = frames.max(1);
+        let excess = recording.frames.len().saturating_sub(recording.limit);
+        recording.frames.drain(..excess);
+    }
+
+    fn record(&self, frame: &Frame, now: Instant) {
+        let mut recording = self.lock();
+        if !recording.active {
+            return;
+        }
+        if recording.size != frame.size() {
+            recording.frames.clear();
+            recording.size = frame.size();
+        }
+        let unchanged = recording
+            .frames
+            .back()
+            .is_some_and(|(_, pixels)| pixels == frame.pixels());
+        if !unchanged {
+            recording.frames.push_back((now, frame.pixels().to_vec()));
+            if recording.frames.len() > recording.limit {
+                recording.frames.pop_front();
+            }
+        }
+    }
+
+    /// The recorded frames with how long each was shown, and their width and height
+    fn frames(&self) -> Result<(Vec<TimedFrame>, (u32, u32)), &'static str> {
+        let recording = self.lock();
+        if recording.frames.is_empty() {
+            return Err("Nothing recorded");
+        }
+        let end = recording.end.unwrap_or_else(Instant::now);
+        let ends = recording.frames.iter().skip(1).map(|(shown, _)| *shown);
+        let frames = recording
+            .frames
+            .iter()
+            .zip(ends.chain([end]))
+            .map(|((shown, pixels), next)| {
+                let bytes = pixels
+                    .iter()
+                    .flat_map(|c| [c.red, c.green, c.blue])
+                    .collect();
+                (next.saturating_duration_since(*shown), bytes)
+            })
+            .collect();
+        let (width, height) = recording.size;
+        Ok((frames, (width as u32, height as u32)))
+    }
+
+    /// Writes the recording as an animated GIF, looping forever.
+    ///
+    /// GIF stores durations in hundredths of a second, so frames are rounded to that.
+    ///
+    /// # Errors
+    /// If nothing was recorded or the GIF can't be written.
+    pub fn write_gif(&self, writer: impl Write) -> Result<(), &'static str> {
+        let (frames, (width, height)) = self.frames()?;
+        let mut encoder = GifEncoder::new(writer);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|_| "Couldn't write GIF")?;
+        let frames = frames.into_iter().map(|(duration, bytes)| {
+            let rgba = bytes
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect();
+            let buffer = RgbaImage::from_raw(width, height, rgba).unwrap_or_default();
+            let delay = Delay::from_saturating_duration(duration);
+            image::Frame::from_parts(buffer, 0, 0, delay)
+        });
+        encoder
+            .encode_frames(frames)
+            .map_err(|_| "Couldn't write GIF")
+    }
+
+    /// Writes the recording as an animated PNG, looping forever.
+    ///
+    /// APNG keeps the colors exactly and the durations to the millisecond, up to a minute per
+    /// frame.
+    ///
+    /// # Errors
+    /// If nothing was recorded or the PNG can't be written.
+    pub fn write_apng(&self, writer: impl Write) -> Result<(), &'static str> {
+        let (frames, (width, height)) = self.frames()?;
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frames.len() as u32, 0)
+            .map_err(|_| "Couldn't write PNG")?;
+        let mut writer = encoder.write_header().map_err(|_| "Couldn't write PNG")?;
+        for (duration, bytes) in frames {
+            let milliseconds = duration.as_millis().min(u128::from(u16::MAX)) as u16;
+            writer
+                .set_frame_delay(milliseconds, 1000)
+                .and_then(|()| writer.write_image_data(&bytes))
+                .map_err(|_| "Couldn't write PNG")?;
+        }
+        writer.finish().map_err(|_| "Couldn't write PNG")
+    }
+
+    /// Saves the recording to `path`, as a GIF if it ends in `.gif` and as an animated PNG
+    /// otherwise.
+    ///
+    /// # Errors
+    /// If nothing was recorded or the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        let file = File::create(path).map_err(|_| "Couldn't create recording file")?;
+        let gif = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+        let mut writer = BufWriter::new(file);
+        if gif {
+            self.write_gif(&mut writer)?;
+        } else {
+            self.write_apng(&mut writer)?;
+        }
+        writer.flush().map_err(|_| "Couldn't write recording file")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording() {
+        let recorder = Recorder::new();
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let mut pixels = vec![black; 6];
+        recorder.record(&Frame::new(&mut pixels, (3, 2)), start);
+        assert_eq!(recorder.frame_count(), 0);
+        assert!(recorder.toggle());
+        recorder.record(&Frame::new(&mut pixels, (3, 2)), start);
+        recorder.record(&Frame::new(&mut pixels, (3, 2)), start + ms(40));
+        pixels[4].red = 255;
+        recorder.record(&Frame::new(&mut pixels, (3, 2)), start + ms(100));
+        assert!(!recorder.toggle());
+        assert_eq!(recorder.frame_count(), 2);
+        let (frames, size) = recorder.frames().unwrap();
+        assert_eq!(size, (3, 2));
+        assert_eq!(frames[0].0, ms(100));
+        assert_eq!(frames[1].1[12..15], [255, 0, 0]);
+
+        let mut apng = Vec::new();
+        recorder.write_apng(&mut apng).unwrap();
+        let decoder = png::Decoder::new(apng.as_slice()).read_info().unwrap();
+        let control = decoder.info().animation_control.unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 0));
+        let mut gif = Vec::new();
+        recorder.write_gif(&mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+
+        recorder.set_limit(1);
+        assert_eq!(recorder.frame_count(), 1);
+        recorder.start();
+        assert_eq!(recorder.write_gif(Vec::new()), Err("Nothing recorded"));
+    }
+}
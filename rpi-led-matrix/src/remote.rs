@@ -0,0 +1,163 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::mirror::read_hello;
+use crate::{Canvas, LedColor, MirrorEncoder};
+
+const BLACK: LedColor = LedColor {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+/// A matrix driven over TCP, so the application can run on a faster machine while the Pi only
+/// shows the frames.
+///
+/// Frames are drawn on a [`RemoteCanvas`] and sent to a [`MirrorReceiver`](crate::MirrorReceiver)
+/// [announcing](crate::MirrorReceiver::announce) its size, as the `remote-server` example does,
+/// with only the pixels that changed since the last frame.
+///
+/// ```no_run
+/// use rpi_led_matrix::{effects::Plasma, Canvas, RemoteMatrix};
+/// let mut matrix = RemoteMatrix::connect("matrix.local:7071").unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut plasma = Plasma::new();
+/// loop {
+///     plasma.step(&mut canvas);
+///     canvas = matrix.swap(canvas).unwrap();
+///     std::thread::sleep(std::time::Duration::from_millis(16));
+/// }
+/// ```
+pub struct RemoteMatrix {
+    stream: TcpStream,
+    encoder: MirrorEncoder,
+    size: (i32, i32),
+    rgb: Vec<u8>,
+}
+
+impl RemoteMatrix {
+    /// Connects to the server at `address`, waiting up to 5 seconds for it to announce its
+    /// size.
+    ///
+    /// # Errors
+    /// If the server can't be reached or doesn't announce its size.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, &'static str> {
+        let mut stream = TcpStream::connect(address).map_err(|_| "Couldn't connect to matrix")?;
+        let _ = stream.set_nodelay(true);
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+        let (width, height) = read_hello(&mut stream)?;
+        Ok(Self {
+            stream,
+            encoder: MirrorEncoder::new(width, height),
+            size: (i32::from(width), i32::from(height)),
+            rgb: Vec::new(),
+        })
+    }
+
+    /// The width and height of the remote canvas in pixels
+    #[must_use]
+    pub const fn canvas_size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// A black canvas of the remote size to draw a frame on.
+    #[must_use]
+    pub fn offscreen_canvas(&self) -> RemoteCanvas {
+        RemoteCanvas {
+            pixels: vec![BLACK; (self.size.0 * self.size.1) as usize],
+            size: self.size,
+        }
+    }
+
+    /// Sends the frame drawn on `canvas` to be shown, and returns the canvas, still holding
+    /// the frame, to draw the next one on.
+    ///
+    /// # Errors
+    /// If the connection fails.
+    pub fn swap(&mut self, canvas: RemoteCanvas) -> Result<RemoteCanvas, &'static str> {
+        self.rgb.clear();
+        self.rgb.extend(
+            canvas
+                .pixels
+                .iter()
+                .flat_map(|pixel| [pixel.red, pixel.green, pixel.blue]),
+        );
+        let size = (self.size.0 as u32, self.size.1 as u32);
+        self.encoder.send(&mut self.stream, &self.rgb, size)?;
+        Ok(canvas)
+    }
+}
+
+/// A frame of a [`RemoteMatrix`], drawn on through [`Canvas`].
+#[derive(Clone, Debug)]
+pub struct RemoteCanvas {
+    pixels: Vec<LedColor>,
+    size: (i32, i32),
+}
+
+impl RemoteCanvas {
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        ((0..self.size.0).contains(&x) && (0..self.size.1).contains(&y))
+            .then(|| (y * self.size.0 + x) as usize)
+    }
+}
+
+impl Canvas for RemoteCanvas {
+    fn canvas_size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = *color;
+        }
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        self.pixels.fill(*color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frame, MirrorReceiver};
+
+    #[test]
+    fn remote_frames() {
+        let mut receiver = MirrorReceiver::bind("127.0.0.1:0")
+            .unwrap()
+            .announce(4, 2)
+            .timeout(Duration::from_millis(20));
+        let address = receiver.local_addr().unwrap();
+        let red = LedColor {
+            red: 255,
+            green: 0,
+            blue: 0,
+        };
+        let client = std::thread::spawn(move || {
+            let mut matrix = RemoteMatrix::connect(address).unwrap();
+            assert_eq!(matrix.canvas_size(), (4, 2));
+            let mut canvas = matrix.offscreen_canvas();
+            canvas.draw_line(0, 1, 3, 1, &red);
+            canvas.set(9, 9, &red);
+            assert_eq!(canvas.get(3, 1), Some(red));
+            let mut canvas = matrix.swap(canvas).unwrap();
+            canvas.set(0, 0, &red);
+            matrix.swap(canvas).unwrap();
+        });
+        let mut frames = 0;
+        while frames < 2 {
+            frames += usize::from(receiver.receive().unwrap());
+        }
+        client.join().unwrap();
+
+        let mut pixels = vec![BLACK; 8];
+        receiver.draw(&mut Frame::new(&mut pixels, (4, 2)));
+        assert_eq!(pixels, [red, BLACK, BLACK, BLACK, red, red, red, red]);
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{Canvas, DmxLayout, LedColor, LedMatrix};
+
+/// The port sACN is sent to
+pub const SACN_PORT: u16 = 5568;
+
+/// The ACN packet identifier starting the root layer, after preamble and postamble size
+const ACN_PID: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_DATA: u32 = 0x04;
+const VECTOR_ROOT_EXTENDED: u32 = 0x08;
+const VECTOR_FRAMING_DATA: u32 = 0x02;
+const VECTOR_FRAMING_SYNC: u32 = 0x01;
+
+/// Length of the headers of a data packet, before the channels
+const DATA_HEADER: usize = 126;
+/// Option bit of data meant for visualizers, not for output
+const OPTION_PREVIEW: u8 = 0x80;
+/// Option bit of a source that stops sending the universe
+const OPTION_TERMINATED: u8 = 0x40;
+/// How long a source counts as sending after its last packet, as in the spec
+const SOURCE_TIMEOUT: Duration = Duration::from_millis(2500);
+/// Largest packet read from the socket, a data packet is at most 638 bytes
+const MAX_PACKET: usize = 2048;
+
+/// The color of pixels without data
+const BLACK: LedColor = LedColor {
+    red: 0,
+    green: 0,
+    blue: 0,
+};
+
+/// A packet of an sACN source
+#[derive(Debug, PartialEq, Eq)]
+enum Packet<'a> {
+    Data {
+        source: [u8; 16],
+        priority: u8,
+        sync: u16,
+        sequence: u8,
+        options: u8,
+        universe: u16,
+        channels: &'a [u8],
+    },
+    Sync {
+        address: u16,
+    },
+}
+
+impl<'a> Packet<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let be16 = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?));
+        let be32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+        if data.get(4..16)? != ACN_PID {
+            return None;
+        }
+        match (be32(18)?, be32(40)?) {
+            (VECTOR_ROOT_DATA, VECTOR_FRAMING_DATA) => {
+                // only DMX data with the start code 0
+                if data.get(125) != Some(&0) {
+                    return None;
+                }
+                let count = usize::from(be16(123)?).saturating_sub(1);
+                let channels = &data[DATA_HEADER..];
+                Some(Self::Data {
+                    source: data[22..38].try_into().ok()?,
+                    priority: data[108],
+                    sync: be16(109)?,
+                    sequence: data[111],
+                    options: data[112],
+                    universe: be16(113)?,
+                    channels: &channels[..count.min(channels.len())],
+                })
+            }
+            (VECTOR_ROOT_EXTENDED, VECTOR_FRAMING_SYNC) => Some(Self::Sync { address: be16(45)? }),
+            _ => None,
+        }
+    }
+}
+
+/// The source a universe is taken from
+#[derive(Debug)]
+struct Source {
+    id: [u8; 16],
+    priority: u8,
+    sequence: u8,
+    seen: Instant,
+}
+
+/// Receives DMX universes over E1.31 streaming ACN (sACN), so show software like xLights or
+/// Falcon controllers' players can drive the matrix.
+///
+/// [`bind`](Self::bind) subscribes to the multicast group of every universe of the layout,
+/// unicast to the port works as well. A universe follows the source with the highest
+/// priority, ignoring lower ones until it stops sending. Sources using synchronization are
+/// shown once their sync packet arrives, to show whole frames without tearing.
+///
+/// ```no_run
+/// use rpi_led_matrix::{DmxLayout, LedMatrix, Sacn};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let layout = DmxLayout::new(matrix.canvas().canvas_size()).universe(1);
+/// Sacn::bind(layout).unwrap().run(&matrix).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Sacn {
+    socket: UdpSocket,
+    layout: DmxLayout,
+    timeout: Duration,
+    pixels: Vec<LedColor>,
+    sources: HashMap<u16, Source>,
+    /// The sync addresses of data that arrived since their last sync packet
+    pending: Vec<u16>,
+    buffer: Vec<u8>,
+}
+
+impl Sacn {
+    /// Listens for sACN on all interfaces, subscribed to the universes of `layout`.
+    ///
+    /// # Errors
+    /// If the port can't be bound or the multicast groups can't be joined.
+    pub fn bind(layout: DmxLayout) -> Result<Self, &'static str> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, SACN_PORT))
+            .map_err(|_| "Couldn't bind sACN port")?;
+        for universe in layout.universes() {
+            let [high, low] = universe.to_be_bytes();
+            socket
+                .join_multicast_v4(&Ipv4Addr::new(239, 255, high, low), &Ipv4Addr::UNSPECIFIED)
+                .map_err(|_| "Couldn't join sACN multicast group")?;
+        }
+        Ok(Self::new(socket, layout))
+    }
+
+    fn new(socket: UdpSocket, layout: DmxLayout) -> Self {
+        let (width, height) = layout.size();
+        Self {
+            socket,
+            layout,
+            timeout: Duration::from_millis(100),
+            pixels: vec![BLACK; (width * height).max(0) as usize],
+            sources: HashMap::new(),
+            pending: Vec::new(),
+            buffer: vec![0; MAX_PACKET],
+        }
+    }
+
+    /// Sets how long [`receive`](Self::receive) waits for a packet
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits up to the timeout for packets and handles all that arrived, returning true if a
+    /// new frame is ready to draw.
+    ///
+    /// Packets that aren't valid are ignored.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn receive(&mut self) -> Result<bool, &'static str> {
+        let now = Instant::now();
+        let mut ready = false;
+        let timeout = Some(self.timeout.max(Duration::from_millis(1)));
+        let _ = self.socket.set_read_timeout(timeout);
+        loop {
+            match self.socket.recv(&mut self.buffer) {
+                Ok(length) => {
+                    let packet = std::mem::take(&mut self.buffer);
+                    ready |= self.handle(&packet[..length], now);
+                    self.buffer = packet;
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    break;
+                }
+                Err(_) => return Err("Couldn't receive sACN packet"),
+            }
+            // take the packets already waiting, without waiting for more
+            let _ = self.socket.set_read_timeout(Some(Duration::from_micros(1)));
+        }
+        Ok(ready)
+    }
+
+    /// Handles a packet, returning true if a frame is ready.
+    fn handle(&mut self, data: &[u8], now: Instant) -> bool {
+        match Packet::parse(data) {
+            Some(Packet::Data {
+                source,
+                priority,
+                sync,
+                sequence,
+                options,
+                universe,
+                channels,
+            }) => {
+                if options & OPTION_PREVIEW != 0 {
+                    return false;
+                }
+                let current = self.sources.get(&universe).filter(|current| {
+                    now.saturating_duration_since(current.seen) <= SOURCE_TIMEOUT
+                });
+                match current {
+                    Some(current) if current.id == source => {
+                        // packets up to 20 behind arrived out of order
+                        let behind = current.sequence.wrapping_sub(sequence);
+                        if behind < 20 {
+                            return false;
+                        }
+                    }
+                    Some(current) if current.priority >= priority => return false,
+                    _ => {}
+                }
+                if options & OPTION_TERMINATED != 0 {
+                    self.sources.remove(&universe);
+                    return false;
+                }
+                self.sources.insert(
+                    universe,
+                    Source {
+                        id: source,
+                        priority,
+                        sequence,
+                        seen: now,
+                    },
+                );
+                if !self.layout.apply(universe, channels, &mut self.pixels) {
+                    return false;
+                }
+                if sync == 0 {
+                    return true;
+                }
+                if !self.pending.contains(&sync) {
+                    self.pending.push(sync);
+                }
+                false
+            }
+            Some(Packet::Sync { address }) => {
+                let pending = self.pending.len();
+                self.pending.retain(|&waiting| waiting != address);
+                self.pending.len() < pending
+            }
+            None => false,
+        }
+    }
+
+    /// Draws the received pixels.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        let width = self.layout.size().0.max(1);
+        for (i, color) in (0..).zip(&self.pixels) {
+            canvas.set(i % width, i / width, color);
+        }
+    }
+
+    /// The address the receiver is bound to
+    ///
+    /// # Errors
+    /// If the socket has no address.
+    pub fn local_addr(&self) -> Result<SocketAddr, &'static str> {
+        self.socket
+            .local_addr()
+            .map_err(|_| "Couldn't get sACN address")
+    }
+
+    /// Drives `matrix` with the received universes until the socket fails.
+    ///
+    /// # Errors
+    /// If the socket fails.
+    pub fn run(mut self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        let mut canvas = matrix.offscreen_canvas();
+        loop {
+            if self.receive()? {
+                self.draw(&mut canvas);
+                canvas = matrix.swap(canvas);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Frame;
+
+    fn data(source: u8, priority: u8, sequence: u8, sync: u16, channels: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0, 0x10, 0, 0];
+        packet.extend(ACN_PID);
+        packet.extend([0x72, 0x6e]);
+        packet.extend(VECTOR_ROOT_DATA.to_be_bytes());
+        packet.extend([source; 16]);
+        packet.extend([0x72, 0x58]);
+        packet.extend(VECTOR_FRAMING_DATA.to_be_bytes());
+        packet.extend([b'x'; 64]);
+        packet.push(priority);
+        packet.extend(sync.to_be_bytes());
+        packet.extend([sequence, 0]);
+        packet.extend(1u16.to_be_bytes());
+        packet.extend([0x72, 0x0b, 0x02, 0xa1, 0, 0, 0, 1]);
+        packet.extend((channels.len() as u16 + 1).to_be_bytes());
+        packet.push(0);
+        packet.extend(channels);
+        packet
+    }
+
+    #[test]
+    fn receiving() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut receiver = Sacn::new(socket, DmxLayout::new((2, 1)).universe(1));
+        let now = Instant::now();
+        let pixel = |level| LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        };
+        let mut pixels = vec![BLACK; 2];
+        let mut check = |receiver: &Sacn, expected: [LedColor; 2]| {
+            receiver.draw(&mut Frame::new(&mut pixels, (2, 1)));
+            assert_eq!(pixels, expected);
+        };
+
+        assert!(receiver.handle(&data(1, 100, 10, 0, &[1; 6]), now));
+        check(&receiver, [pixel(1), pixel(1)]);
+        // a late packet of the source and a lower priority source are ignored
+        assert!(!receiver.handle(&data(1, 100, 9, 0, &[2; 6]), now));
+        assert!(!receiver.handle(&data(2, 50, 0, 0, &[3; 6]), now));
+        check(&receiver, [pixel(1), pixel(1)]);
+        // a higher priority source takes over
+        assert!(receiver.handle(&data(3, 150, 0, 0, &[4; 6]), now));
+        assert!(!receiver.handle(&data(1, 100, 11, 0, &[5; 6]), now));
+        check(&receiver, [pixel(4), pixel(4)]);
+        // until it times out
+        let later = now + SOURCE_TIMEOUT * 2;
+        assert!(receiver.handle(&data(1, 100, 12, 0, &[6; 6]), later));
+
+        // synchronized data waits for its sync packet
+        assert!(!receiver.handle(&data(1, 100, 13, 7000, &[7; 6]), later));
+        let mut sync = vec![0, 0x10, 0, 0];
+        sync.extend(ACN_PID);
+        sync.extend([0x70, 0x21]);
+        sync.extend(VECTOR_ROOT_EXTENDED.to_be_bytes());
+        sync.extend([1; 16]);
+        sync.extend([0x70, 0x0b]);
+        sync.extend(VECTOR_FRAMING_SYNC.to_be_bytes());
+        sync.push(0);
+        sync.extend(7000u16.to_be_bytes());
+        sync.extend([0, 0]);
+        assert!(receiver.handle(&sync, later));
+        assert!(!receiver.handle(&sync, later));
+        check(&receiver, [pixel(7), pixel(7)]);
+
+        // a terminated stream frees the universe for lower priorities
+        let mut terminated = data(1, 100, 14, 0, &[]);
+        terminated[112] = OPTION_TERMINATED;
+        assert!(!receiver.handle(&terminated, later));
+        assert!(receiver.handle(&data(2, 50, 0, 0, &[8; 6]), later));
+        assert!(Packet::parse(&sync[..40]).is_none());
+    }
+}
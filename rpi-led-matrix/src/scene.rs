@@ -0,0 +1,474 @@
+use std::time::{Duration, Instant};
+
+use crate::LedCanvas;
+
+/// Content that can be shown by a [`SceneManager`].
+///
+/// Implemented for all `FnMut(&mut LedCanvas, Duration)` closures.
+pub trait Scene {
+    /// Draws the scene onto the canvas.
+    ///
+    /// `elapsed` is the time the scene has been on screen so far. Time spent preempted by
+    /// higher priority content is not counted, so animations resume where they left off.
+    fn render(&mut self, canvas: &mut LedCanvas, elapsed: Duration);
+}
+
+impl<F> Scene for F
+where
+    F: FnMut(&mut LedCanvas, Duration),
+{
+    fn render(&mut self, canvas: &mut LedCanvas, elapsed: Duration) {
+        self(canvas, elapsed);
+    }
+}
+
+/// Priority of content shown by a [`SceneManager`].
+///
+/// Content of a higher priority preempts everything below it immediately.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Regular content, shown in rotation
+    #[default]
+    Normal,
+    /// Alerts, shown instead of regular content
+    Alert,
+    /// Emergencies, shown instead of everything else
+    Emergency,
+}
+
+/// Identifies a scene added to a [`SceneManager`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SceneId(u64);
+
+/// A daily span of local time, e.g. to show a scene with
+/// [`SceneManager::only_when`] during opening hours.
+///
+/// The span starts at `start` and ends right before `end`, wrapping over midnight if `end`
+/// comes first.
+///
+/// ```
+/// use rpi_led_matrix::Hours;
+/// let night = Hours::new((22, 0), (6, 30));
+/// assert!(night.contains(23, 15));
+/// assert!(night.contains(6, 29));
+/// assert!(!night.contains(12, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hours {
+    /// Minutes since midnight
+    start: u32,
+    end: u32,
+}
+
+impl Hours {
+    /// Creates the span from `start` to `end`, both as hour and minute.
+    #[must_use]
+    pub const fn new(
+        (start_hour, start_minute): (u32, u32),
+        (end_hour, end_minute): (u32, u32),
+    ) -> Self {
+        Self {
+            start: start_hour * 60 + start_minute,
+            end: end_hour * 60 + end_minute,
+        }
+    }
+
+    /// Returns true if the time of day `hour`:`minute` lies within the span.
+    #[must_use]
+    pub const fn contains(&self, hour: u32, minute: u32) -> bool {
+        let time = hour * 60 + minute;
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Returns true if the current local time lies within the span.
+    #[must_use]
+    pub fn is_now(&self) -> bool {
+        let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+        unsafe {
+            let time = libc::time(std::ptr::null_mut());
+            libc::localtime_r(&time, &mut tm);
+        }
+        self.contains(tm.tm_hour as u32, tm.tm_min as u32)
+    }
+}
+
+type Rule = Box<dyn FnMut() -> bool + Send>;
+
+struct Entry {
+    id: SceneId,
+    priority: Priority,
+    scene: Box<dyn Scene + Send>,
+    /// How long the scene is shown for before moving on, `None` until dismissed
+    duration: Option<Duration>,
+    elapsed: Duration,
+    /// Decides if a scene of the rotation may be shown
+    rule: Option<Rule>,
+    /// The outcome of the rule at the last update
+    allowed: bool,
+}
+
+impl Entry {
+    fn new(
+        id: SceneId,
+        priority: Priority,
+        scene: Box<dyn Scene + Send>,
+        duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            id,
+            priority,
+            scene,
+            duration,
+            elapsed: Duration::ZERO,
+            rule: None,
+            allowed: true,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.duration
+            .is_some_and(|duration| self.elapsed >= duration)
+    }
+}
+
+/// Rotates through scenes, letting higher priority content preempt the rotation.
+///
+/// ```
+/// use rpi_led_matrix::{LedCanvas, LedColor, LedMatrix, Priority, SceneManager};
+/// use std::time::{Duration, Instant};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut scenes = SceneManager::new();
+/// scenes.add(Duration::from_secs(10), |canvas: &mut LedCanvas, _: Duration| {
+///     canvas.fill(&LedColor { red: 0, green: 0, blue: 64 });
+/// });
+/// let alert = |canvas: &mut LedCanvas, _: Duration| {
+///     canvas.fill(&LedColor { red: 255, green: 0, blue: 0 });
+/// };
+/// scenes.preempt(Priority::Alert, Some(Duration::from_secs(5)), alert);
+/// scenes.render(&mut canvas, Instant::now());
+/// canvas = matrix.swap(canvas);
+/// ```
+#[derive(Default)]
+pub struct SceneManager {
+    rotation: Vec<Entry>,
+    current: usize,
+    interrupts: Vec<Entry>,
+    next_id: u64,
+    last_update: Option<Instant>,
+}
+
+impl SceneManager {
+    /// Creates an empty scene manager
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_id(&mut self) -> SceneId {
+        self.next_id += 1;
+        SceneId(self.next_id)
+    }
+
+    /// Adds a scene to the normal rotation, shown for `duration` each time it comes up.
+    pub fn add<S>(&mut self, duration: Duration, scene: S) -> SceneId
+    where
+        S: Scene + Send + 'static,
+    {
+        let id = self.next_id();
+        self.rotation.push(Entry::new(
+            id,
+            Priority::Normal,
+            Box::new(scene),
+            Some(duration),
+        ));
+        id
+    }
+
+    /// Shows a scene of the rotation only while `rule` returns true, e.g. during certain
+    /// [`Hours`]. Returns false if there's no such scene in the rotation.
+    ///
+    /// The rule is checked on every [`update`](Self::update). Scenes not allowed are skipped,
+    /// and a scene on screen moves on as soon as its rule turns false. If no scene of the
+    /// rotation is allowed, nothing is shown besides preempting scenes.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{Hours, LedCanvas, LedColor, SceneManager};
+    /// use std::time::Duration;
+    /// let mut scenes = SceneManager::new();
+    /// let menu = scenes.add(Duration::from_secs(10), |canvas: &mut LedCanvas, _: Duration| {
+    ///     canvas.fill(&LedColor { red: 255, green: 128, blue: 0 });
+    /// });
+    /// let lunch = Hours::new((11, 30), (14, 0));
+    /// scenes.only_when(menu, move || lunch.is_now());
+    /// ```
+    pub fn only_when<F>(&mut self, id: SceneId, rule: F) -> bool
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        match self.rotation.iter_mut().find(|e| e.id == id) {
+            Some(entry) => {
+                entry.rule = Some(Box::new(rule));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Shows a scene immediately, in front of all content of a lower priority.
+    ///
+    /// The scene is removed after `duration`, or when [`dismiss`](Self::dismiss)ed if `None`.
+    /// Among preempting scenes of the same priority the most recent one is shown. Once all
+    /// of them are gone, the rotation continues where it was interrupted.
+    pub fn preempt<S>(
+        &mut self,
+        priority: Priority,
+        duration: Option<Duration>,
+        scene: S,
+    ) -> SceneId
+    where
+        S: Scene + Send + 'static,
+    {
+        let id = self.next_id();
+        self.interrupts
+            .push(Entry::new(id, priority, Box::new(scene), duration));
+        id
+    }
+
+    /// Removes a scene, wherever it is. Returns false if there was no such scene.
+    pub fn dismiss(&mut self, id: SceneId) -> bool {
+        if let Some(index) = self.interrupts.iter().position(|e| e.id == id) {
+            self.interrupts.remove(index);
+            return true;
+        }
+        if let Some(index) = self.rotation.iter().position(|e| e.id == id) {
+            self.rotation.remove(index);
+            if index < self.current {
+                self.current -= 1;
+            }
+            if self.current >= self.rotation.len() {
+                self.current = 0;
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Continues the rotation with the scene `id` right away, from its start. Returns false if
+    /// there's no such scene in the rotation.
+    pub fn switch_to(&mut self, id: SceneId) -> bool {
+        let Some(index) = self.rotation.iter().position(|e| e.id == id) else {
+            return false;
+        };
+        if let Some(entry) = self.rotation.get_mut(self.current) {
+            entry.elapsed = Duration::ZERO;
+        }
+        self.current = index;
+        self.rotation[index].elapsed = Duration::ZERO;
+        true
+    }
+
+    /// Moves the rotation on to the next scene right away, e.g. on a button press. While
+    /// preempted, the rotation moves on once the preempting scenes are gone.
+    pub fn skip(&mut self) {
+        if let Some(entry) = self.rotation.get_mut(self.current) {
+            entry.elapsed = entry.duration.unwrap_or(Duration::MAX);
+        }
+        self.update(self.last_update.unwrap_or_else(Instant::now));
+    }
+
+    /// Index of the interrupt on screen, if any: the latest one with the highest priority.
+    fn active_interrupt(&self) -> Option<usize> {
+        self.interrupts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, e)| e.priority)
+            .map(|(index, _)| index)
+    }
+
+    fn active_mut(&mut self) -> Option<&mut Entry> {
+        match self.active_interrupt() {
+            Some(index) => self.interrupts.get_mut(index),
+            None => self.rotation.get_mut(self.current).filter(|e| e.allowed),
+        }
+    }
+
+    /// The scene currently on screen
+    #[must_use]
+    pub fn current(&self) -> Option<SceneId> {
+        match self.active_interrupt() {
+            Some(index) => Some(self.interrupts[index].id),
+            None => self
+                .rotation
+                .get(self.current)
+                .filter(|e| e.allowed)
+                .map(|e| e.id),
+        }
+    }
+
+    /// The priority of the scene currently on screen
+    #[must_use]
+    pub fn current_priority(&self) -> Option<Priority> {
+        match self.active_interrupt() {
+            Some(index) => Some(self.interrupts[index].priority),
+            None => self
+                .rotation
+                .get(self.current)
+                .filter(|e| e.allowed)
+                .map(|e| e.priority),
+        }
+    }
+
+    /// Advances the time of the scene on screen, moving on to the next one when it expired.
+    pub fn update(&mut self, now: Instant) {
+        let delta = self
+            .last_update
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_update = Some(now);
+
+        for entry in &mut self.rotation {
+            entry.allowed = entry.rule.as_mut().is_none_or(|rule| rule());
+        }
+
+        if let Some(index) = self.active_interrupt() {
+            let entry = &mut self.interrupts[index];
+            entry.elapsed += delta;
+            if entry.is_expired() {
+                self.interrupts.remove(index);
+            }
+            return;
+        }
+
+        let Some(entry) = self.rotation.get_mut(self.current) else {
+            return;
+        };
+        if entry.allowed {
+            entry.elapsed += delta;
+            if !entry.is_expired() {
+                return;
+            }
+        }
+        entry.elapsed = Duration::ZERO;
+        // the next allowed scene, which may be the same one again
+        let count = self.rotation.len();
+        if let Some(next) = (1..=count)
+            .map(|step| (self.current + step) % count)
+            .find(|&index| self.rotation[index].allowed)
+        {
+            self.current = next;
+        }
+    }
+
+    /// Updates the timing like [`update`](Self::update) and renders the scene on screen.
+    pub fn render(&mut self, canvas: &mut LedCanvas, now: Instant) {
+        self.update(now);
+        if let Some(entry) = self.active_mut() {
+            entry.scene.render(canvas, entry.elapsed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop(_: &mut LedCanvas, _: Duration) {}
+
+    #[test]
+    fn rotation() {
+        let start = Instant::now();
+        let mut scenes = SceneManager::new();
+        let first = scenes.add(Duration::from_secs(2), noop);
+        let second = scenes.add(Duration::from_secs(1), noop);
+
+        scenes.update(start);
+        assert_eq!(scenes.current(), Some(first));
+        scenes.update(start + Duration::from_secs(2));
+        assert_eq!(scenes.current(), Some(second));
+        scenes.update(start + Duration::from_secs(3));
+        assert_eq!(scenes.current(), Some(first));
+        scenes.skip();
+        assert_eq!(scenes.current(), Some(second));
+    }
+
+    #[test]
+    fn preemption_resumes_rotation() {
+        let start = Instant::now();
+        let mut scenes = SceneManager::new();
+        let first = scenes.add(Duration::from_secs(2), noop);
+        let second = scenes.add(Duration::from_secs(2), noop);
+
+        scenes.update(start);
+        scenes.update(start + Duration::from_secs(1));
+        let alert = scenes.preempt(Priority::Alert, Some(Duration::from_secs(5)), noop);
+        assert_eq!(scenes.current(), Some(alert));
+        assert_eq!(scenes.current_priority(), Some(Priority::Alert));
+
+        let emergency = scenes.preempt(Priority::Emergency, None, noop);
+        scenes.update(start + Duration::from_secs(10));
+        assert_eq!(scenes.current(), Some(emergency));
+        assert!(scenes.dismiss(emergency));
+        assert_eq!(scenes.current(), Some(alert));
+
+        scenes.update(start + Duration::from_secs(15));
+        assert_eq!(scenes.current(), Some(first));
+        scenes.update(start + Duration::from_secs(16));
+        assert_eq!(scenes.current(), Some(second));
+    }
+
+    #[test]
+    fn rules() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let start = Instant::now();
+        let mut scenes = SceneManager::new();
+        let first = scenes.add(Duration::from_secs(2), noop);
+        let second = scenes.add(Duration::from_secs(2), noop);
+        let third = scenes.add(Duration::from_secs(2), noop);
+        let open = Arc::new(AtomicBool::new(false));
+        let rule = Arc::clone(&open);
+        assert!(scenes.only_when(second, move || rule.load(Ordering::Relaxed)));
+        assert!(!scenes.only_when(SceneId(99), || true));
+
+        scenes.update(start);
+        scenes.update(start + Duration::from_secs(2));
+        assert_eq!(scenes.current(), Some(third));
+        open.store(true, Ordering::Relaxed);
+        scenes.update(start + Duration::from_secs(4));
+        scenes.update(start + Duration::from_secs(6));
+        assert_eq!(scenes.current(), Some(second));
+        // moves on as soon as the rule turns false
+        open.store(false, Ordering::Relaxed);
+        scenes.update(start + Duration::from_secs(7));
+        assert_eq!(scenes.current(), Some(third));
+
+        scenes.only_when(first, || false);
+        scenes.only_when(third, || false);
+        scenes.update(start + Duration::from_secs(8));
+        assert_eq!(scenes.current(), None);
+        let alert = scenes.preempt(Priority::Alert, None, noop);
+        assert_eq!(scenes.current(), Some(alert));
+
+        let night = Hours::new((22, 0), (6, 30));
+        assert!(night.contains(0, 0) && !night.contains(6, 30) && night.contains(22, 0));
+        assert!(Hours::new((9, 0), (17, 0)).contains(12, 0));
+        assert!(!Hours::new((9, 0), (17, 0)).contains(17, 0));
+    }
+
+    #[test]
+    fn dismiss_from_rotation() {
+        let mut scenes = SceneManager::new();
+        let first = scenes.add(Duration::from_secs(1), noop);
+        let second = scenes.add(Duration::from_secs(1), noop);
+        assert!(scenes.dismiss(first));
+        assert!(!scenes.dismiss(first));
+        assert_eq!(scenes.current(), Some(second));
+        assert!(scenes.dismiss(second));
+        assert_eq!(scenes.current(), None);
+    }
+}
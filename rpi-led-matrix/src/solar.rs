@@ -0,0 +1,235 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds in a day
+const DAY: f64 = 86_400.0;
+/// Days from the unix epoch to the J2000 epoch (2000-01-01 12:00 UTC)
+const J2000: f64 = 10_957.5;
+/// Altitude of the sun's center at sunrise and sunset, accounting for refraction and its radius
+const HORIZON: f64 = -0.833;
+/// Obliquity of the ecliptic
+const OBLIQUITY: f64 = 23.4397;
+
+/// Whether the sun is up, as reported by [`SolarClock`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Daylight {
+    /// The sun is above the horizon
+    Day,
+    /// The sun is below the horizon
+    Night,
+}
+
+impl Daylight {
+    /// Picks the value matching this daylight, e.g. a brightness or theme.
+    pub fn pick<T>(self, day: T, night: T) -> T {
+        match self {
+            Self::Day => day,
+            Self::Night => night,
+        }
+    }
+}
+
+type Hook = Box<dyn FnMut(Daylight) + Send>;
+
+/// Computes sunrise and sunset for a location, switching between day and night modes.
+///
+/// Useful for signs that are visible outdoors: bright during the day, dimmed at night.
+///
+/// ```
+/// use rpi_led_matrix::{LedMatrix, SolarClock};
+/// use std::time::SystemTime;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// // Berlin
+/// let mut solar = SolarClock::new(52.52, 13.40)
+///     .unwrap()
+///     .on_change(|daylight| println!("switched to {daylight:?}"));
+/// if let Some(daylight) = solar.update(SystemTime::now()) {
+///     matrix.set_brightness(daylight.pick(100, 20)).unwrap();
+/// }
+/// ```
+pub struct SolarClock {
+    latitude: f64,
+    longitude: f64,
+    current: Option<Daylight>,
+    hooks: Vec<Hook>,
+}
+
+impl SolarClock {
+    /// Creates a solar clock for the given location in degrees, north and east being positive.
+    ///
+    /// # Errors
+    /// If the latitude isn't in \[-90,90\] or the longitude isn't in \[-180,180\].
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, &'static str> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err("Latitude can only have value between -90 and 90 inclusive");
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err("Longitude can only have value between -180 and 180 inclusive");
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            current: None,
+            hooks: Vec::new(),
+        })
+    }
+
+    /// Adds a hook called with the new daylight whenever [`update`](Self::update) switches.
+    #[must_use]
+    pub fn on_change<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(Daylight) + Send + 'static,
+    {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Computes sunrise and sunset of the (local solar) day containing `time`.
+    ///
+    /// Returns `None` if the sun doesn't rise or set that day, as in polar regions.
+    #[must_use]
+    pub fn sun_times(&self, time: SystemTime) -> Option<(SystemTime, SystemTime)> {
+        let (transit, hour_angle) = self.solar_day(time);
+        let hour_angle = hour_angle?;
+        let to_time = |days: f64| {
+            let secs = (days + J2000) * DAY;
+            if secs >= 0.0 {
+                UNIX_EPOCH + Duration::from_secs_f64(secs)
+            } else {
+                UNIX_EPOCH - Duration::from_secs_f64(-secs)
+            }
+        };
+        Some((
+            to_time(transit - hour_angle / 360.0),
+            to_time(transit + hour_angle / 360.0),
+        ))
+    }
+
+    /// Whether the sun is up at the given time.
+    #[must_use]
+    pub fn daylight(&self, now: SystemTime) -> Daylight {
+        let (_, hour_angle) = self.solar_day(now);
+        match hour_angle {
+            Some(_) => match self.sun_times(now) {
+                Some((sunrise, sunset)) if sunrise <= now && now < sunset => Daylight::Day,
+                _ => Daylight::Night,
+            },
+            None if self.declination(now).signum() == self.latitude.signum() => Daylight::Day,
+            None => Daylight::Night,
+        }
+    }
+
+    /// Checks the daylight, returning and announcing it to the hooks if it changed.
+    ///
+    /// The first call always reports the current daylight.
+    pub fn update(&mut self, now: SystemTime) -> Option<Daylight> {
+        let daylight = self.daylight(now);
+        if self.current == Some(daylight) {
+            return None;
+        }
+        self.current = Some(daylight);
+        for hook in &mut self.hooks {
+            hook(daylight);
+        }
+        Some(daylight)
+    }
+
+    /// Days since J2000 of the day containing `time` at this longitude, not yet corrected
+    /// for the equation of time.
+    fn mean_noon(&self, time: SystemTime) -> f64 {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        let day = (secs / DAY + self.longitude / 360.0).floor();
+        day - J2000 + 0.5 - self.longitude / 360.0
+    }
+
+    /// Mean anomaly and ecliptic longitude of the sun, in radians.
+    fn ecliptic(noon: f64) -> (f64, f64) {
+        let anomaly = (357.5291 + 0.985_600_28 * noon)
+            .rem_euclid(360.0)
+            .to_radians();
+        let center =
+            1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+        let longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        (anomaly, longitude)
+    }
+
+    fn declination(&self, time: SystemTime) -> f64 {
+        let (_, longitude) = Self::ecliptic(self.mean_noon(time));
+        (longitude.sin() * OBLIQUITY.to_radians().sin()).asin()
+    }
+
+    /// Solar noon in days since J2000 and the hour angle of sunrise/sunset in degrees.
+    fn solar_day(&self, time: SystemTime) -> (f64, Option<f64>) {
+        let noon = self.mean_noon(time);
+        let (anomaly, longitude) = Self::ecliptic(noon);
+        let transit = noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * longitude).sin();
+        let declination = self.declination(time);
+        let latitude = self.latitude.to_radians();
+        let cos_hour_angle = (HORIZON.to_radians().sin() - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos());
+        let hour_angle = (-1.0..=1.0)
+            .contains(&cos_hour_angle)
+            .then(|| cos_hour_angle.acos().to_degrees());
+        (transit, hour_angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// 2024-06-21 00:00 UTC
+    const SOLSTICE: u64 = 1_718_928_000;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn assert_close(time: SystemTime, expected: u64) {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(secs.abs_diff(expected) < 5 * 60, "{secs} != {expected}");
+    }
+
+    #[test]
+    fn london_solstice() {
+        let london = SolarClock::new(51.5074, -0.1278).unwrap();
+        let (sunrise, sunset) = london.sun_times(at(SOLSTICE + 12 * 3600)).unwrap();
+        // 03:43 and 20:21 UTC
+        assert_close(sunrise, SOLSTICE + 3 * 3600 + 43 * 60);
+        assert_close(sunset, SOLSTICE + 20 * 3600 + 21 * 60);
+        assert_eq!(london.daylight(at(SOLSTICE + 12 * 3600)), Daylight::Day);
+        assert_eq!(london.daylight(at(SOLSTICE + 2 * 3600)), Daylight::Night);
+        assert_eq!(london.daylight(at(SOLSTICE + 22 * 3600)), Daylight::Night);
+    }
+
+    #[test]
+    fn polar() {
+        let tromso = SolarClock::new(69.65, 18.96).unwrap();
+        assert!(tromso.sun_times(at(SOLSTICE)).is_none());
+        assert_eq!(tromso.daylight(at(SOLSTICE)), Daylight::Day);
+        let mcmurdo = SolarClock::new(-77.85, 166.67).unwrap();
+        assert_eq!(mcmurdo.daylight(at(SOLSTICE)), Daylight::Night);
+        assert!(SolarClock::new(91.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn hooks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let mut london = SolarClock::new(51.5074, -0.1278)
+            .unwrap()
+            .on_change(move |daylight| hook_seen.lock().unwrap().push(daylight));
+        assert_eq!(london.update(at(SOLSTICE + 12 * 3600)), Some(Daylight::Day));
+        assert_eq!(london.update(at(SOLSTICE + 13 * 3600)), None);
+        assert_eq!(
+            london.update(at(SOLSTICE + 22 * 3600)),
+            Some(Daylight::Night)
+        );
+        assert_eq!(*seen.lock().unwrap(), [Daylight::Day, Daylight::Night]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+/// Frequency spectrum of the latest samples of a signal, e.g. audio for a visualizer.
+///
+/// Samples are pushed as they arrive, and the spectrum of the last window of samples is
+/// computed with an FFT on demand. [`bands`](Self::bands) groups it into logarithmically spaced
+/// frequency bands from 0 to 1, ready to draw with a
+/// [`BarGraph`](crate::widgets::charts::BarGraph). With the `audio` feature an
+/// [`AudioInput`](crate::AudioInput) feeds it from a microphone or the system's audio.
+///
+/// ```
+/// use rpi_led_matrix::Spectrum;
+/// let mut spectrum = Spectrum::new(1024, 44_100);
+/// let tone: Vec<f32> = (0..1024)
+///     .map(|i| (i as f32 * 440.0 / 44_100.0 * std::f32::consts::TAU).sin())
+///     .collect();
+/// spectrum.push(&tone);
+/// let bands = spectrum.bands(16);
+/// assert_eq!(bands.len(), 16);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Spectrum {
+    sample_rate: u32,
+    frequencies: (f32, f32),
+    floor: f32,
+    /// The last window of samples, oldest first
+    samples: VecDeque<f32>,
+}
+
+impl Spectrum {
+    /// Creates a spectrum over windows of `size` samples, rounded up to a power of two, of a
+    /// signal sampled at `sample_rate`.
+    ///
+    /// Larger windows resolve lower frequencies but react slower, 1024 or 2048 samples suit
+    /// audio at 44.1 kHz. Bands span 40 Hz to 16 kHz and levels 60 dB by default.
+    #[must_use]
+    pub fn new(size: usize, sample_rate: u32) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            frequencies: (40.0, 16_000.0),
+            floor: -60.0,
+            samples: vec![0.0; size.max(2).next_power_of_two()].into(),
+        }
+    }
+
+    /// Sets the frequencies in Hz where the lowest band starts and the highest band ends
+    #[must_use]
+    pub const fn frequency_range(mut self, min: f32, max: f32) -> Self {
+        self.frequencies = (min, max);
+        self
+    }
+
+    /// Sets the level in dB below a full scale sine that's shown as an empty band, -60 by
+    /// default
+    #[must_use]
+    pub const fn floor(mut self, decibels: f32) -> Self {
+        self.floor = decibels;
+        self
+    }
+
+    /// The number of samples in a window
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Adds samples from -1 to 1, dropping the oldest ones beyond the window.
+    pub fn push(&mut self, samples: &[f32]) {
+        let size = self.samples.len();
+        let samples = &samples[samples.len().saturating_sub(size)..];
+        self.samples.drain(..samples.len());
+        self.samples.extend(samples);
+    }
+
+    /// The amplitude of every frequency bin up to half the sample rate, 1 for a full scale
+    /// sine. Bin `k` is centered on `k * sample_rate / size` Hz.
+    #[must_use]
+    pub fn magnitudes(&self) -> Vec<f32> {
+        let size = self.samples.len();
+        // a Hann window keeps bins from leaking far into their neighbours
+        let mut bins: Vec<(f32, f32)> = (0..size)
+            .zip(&self.samples)
+            .map(|(i, sample)| {
+                let window = 0.5 - 0.5 * (2.0 * PI * i as f32 / (size - 1) as f32).cos();
+                (sample * window, 0.0)
+            })
+            .collect();
+        fft(&mut bins);
+        // the window halves the amplitude, and half of it is mirrored above the sample rate
+        bins[..size / 2]
+            .iter()
+            .map(|(re, im)| (re * re + im * im).sqrt() * 4.0 / size as f32)
+            .collect()
+    }
+
+    /// Groups the spectrum into `count` logarithmically spaced bands, with the level of the
+    /// loudest frequency in every band from 0 at the [floor](Self::floor) to 1 at full scale.
+    #[must_use]
+    pub fn bands(&self, count: usize) -> Vec<f32> {
+        let magnitudes = self.magnitudes();
+        let resolution = self.sample_rate as f32 / self.samples.len() as f32;
+        let (min, max) = (self.frequencies.0.max(resolution), self.frequencies.1);
+        let edge = |band: usize| {
+            let frequency = min * (max / min).powf(band as f32 / count as f32);
+            ((frequency / resolution).round() as usize).min(magnitudes.len())
+        };
+        (0..count)
+            .map(|band| {
+                let start = edge(band).min(magnitudes.len() - 1);
+                let end = edge(band + 1).max(start + 1);
+                let loudest = magnitudes[start..end]
+                    .iter()
+                    .fold(0.0_f32, |a, &b| a.max(b));
+                let decibels = 20.0 * loudest.max(f32::MIN_POSITIVE).log10();
+                ((decibels - self.floor) / -self.floor).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+}
+
+/// Transforms `data` into its spectrum in place with a radix-2 FFT. The length must be a
+/// power of two.
+fn fft(data: &mut [(f32, f32)]) {
+    let size = data.len();
+    let bits = size.trailing_zeros();
+    for i in 0..size {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= size {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..size).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (re, im) = data[start + k + length / 2];
+                let odd = (re * cos - im * sin, re * sin + im * cos);
+                let even = data[start + k];
+                data[start + k] = (even.0 + odd.0, even.1 + odd.1);
+                data[start + k + length / 2] = (even.0 - odd.0, even.1 - odd.1);
+            }
+        }
+        length *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32, amplitude: f32, sample_rate: u32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn peaks() {
+        let mut spectrum = Spectrum::new(1000, 8000);
+        assert_eq!(spectrum.size(), 1024);
+        assert!(spectrum.bands(4).iter().all(|&band| band == 0.0));
+
+        spectrum.push(&tone(1000.0, 0.5, 8000, 3000));
+        let magnitudes = spectrum.magnitudes();
+        assert_eq!(magnitudes.len(), 512);
+        // 1 kHz is exactly bin 128
+        assert!((magnitudes[128] - 0.5).abs() < 0.01);
+        assert!(magnitudes[100] < 0.001);
+
+        let bands = spectrum.frequency_range(40.0, 4000.0).bands(8);
+        // half scale is 6 dB down
+        assert!((bands[5] - 0.9).abs() < 0.01);
+        assert!(bands[0] < 0.1 && bands[7] < 0.1);
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::{Frame, Surface};
+
+/// A pointer or indicator kept on top of everything on the panel, set with
+/// [`LedMatrix::set_sprite`](crate::LedMatrix::set_sprite).
+///
+/// The sprite is blended over the final frame on every swap, after the watermark and the
+/// pre-swap hooks, at its latest position. Moving it only updates that position, so the
+/// content below doesn't have to be redrawn, and clones of a sprite share it, so it can be
+/// moved from an input thread while another thread draws. Positions are in panel coordinates,
+/// regardless of the canvas transform.
+///
+/// ```
+/// use rpi_led_matrix::{LedColor, LedColorA, LedMatrix, Sprite, Surface};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut arrow = Surface::new(3, 3);
+/// let white = LedColorA::from(LedColor { red: 255, green: 255, blue: 255 });
+/// for (x, y) in [(0, 0), (1, 0), (0, 1), (2, 2)] {
+///     arrow.set(x, y, white);
+/// }
+/// let pointer = Sprite::new(arrow);
+/// matrix.set_sprite(Some(pointer.clone()));
+/// pointer.move_to(20, 10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    surface: Arc<Surface>,
+    hotspot: (i32, i32),
+    /// The position, x in the upper and y in the lower half
+    position: Arc<AtomicU64>,
+    visible: Arc<AtomicBool>,
+}
+
+impl Sprite {
+    /// Creates a visible sprite of `surface`, with its top left corner at the top left corner
+    /// of the panel.
+    #[must_use]
+    pub fn new(surface: Surface) -> Self {
+        Self {
+            surface: Arc::new(surface),
+            hotspot: (0, 0),
+            position: Arc::new(AtomicU64::new(0)),
+            visible: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Sets the pixel of the surface that's placed at the position, e.g. the tip of an arrow
+    /// or the center of a crosshair
+    #[must_use]
+    pub const fn hotspot(mut self, x: i32, y: i32) -> Self {
+        self.hotspot = (x, y);
+        self
+    }
+
+    /// Moves the sprite to (`x`, `y`), shown from the next swap on.
+    pub fn move_to(&self, x: i32, y: i32) {
+        let packed = (u64::from(x as u32) << 32) | u64::from(y as u32);
+        self.position.store(packed, Ordering::Relaxed);
+    }
+
+    /// The position the hotspot is at
+    #[must_use]
+    pub fn position(&self) -> (i32, i32) {
+        let packed = self.position.load(Ordering::Relaxed);
+        ((packed >> 32) as u32 as i32, packed as u32 as i32)
+    }
+
+    /// Shows or hides the sprite, keeping its position.
+    pub fn set_visible(&self, visible: bool) {
+        self.visible.store(visible, Ordering::Relaxed);
+    }
+
+    /// Returns true unless the sprite is hidden
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    /// Blends the sprite over `frame` at its position.
+    pub(crate) fn apply(&self, frame: &mut Frame) {
+        let (x, y) = self.position();
+        frame.draw_surface(&self.surface, x - self.hotspot.0, y - self.hotspot.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LedColor, LedColorA};
+
+    #[test]
+    fn moving() {
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let mut dot = Surface::new(3, 3);
+        dot.set(1, 1, white.into());
+        dot.set(0, 0, LedColorA::new(white, 0));
+        let sprite = Sprite::new(dot).hotspot(1, 1);
+        let handle = sprite.clone();
+        handle.move_to(-3, 2);
+        assert_eq!(sprite.position(), (-3, 2));
+        handle.move_to(0, 1);
+
+        let black = LedColor {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let mut pixels = vec![black; 4];
+        sprite.apply(&mut Frame::new(&mut pixels, (2, 2)));
+        assert_eq!(pixels, [black, black, white, black]);
+        handle.set_visible(false);
+        assert!(!sprite.is_visible());
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::LedMatrix;
+
+/// The socket of the native journald protocol
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+/// The SoC temperature in millidegrees Celsius on a Raspberry Pi
+const THERMAL_ZONE: &str = "/sys/class/thermal/thermal_zone0/temp";
+
+/// Where a [`StatsLog`] writes to
+#[derive(Clone, Debug)]
+enum Sink {
+    File {
+        path: PathBuf,
+        max_size: u64,
+        keep: u32,
+    },
+    Journald,
+}
+
+/// The statistics of one interval
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    fps: f32,
+    slowest: Duration,
+    dropped: u32,
+    brightness: u8,
+    temperature: Option<f32>,
+}
+
+impl Entry {
+    fn fields(&self) -> [(&'static str, String); 5] {
+        let temperature = self
+            .temperature
+            .map_or_else(|| String::from("-"), |celsius| format!("{celsius:.1}"));
+        [
+            ("FPS", format!("{:.1}", self.fps)),
+            ("SLOWEST_MS", self.slowest.as_millis().to_string()),
+            ("DROPPED", self.dropped.to_string()),
+            ("BRIGHTNESS", self.brightness.to_string()),
+            ("TEMPERATURE", temperature),
+        ]
+    }
+}
+
+/// The SoC temperature in degrees Celsius, if the system reports it
+pub(crate) fn soc_temperature() -> Option<f32> {
+    fs::read_to_string(THERMAL_ZONE)
+        .ok()
+        .and_then(|millis| millis.trim().parse::<f32>().ok())
+        .map(|millis| millis / 1000.0)
+}
+
+/// Formats `time` as an RFC 3339 timestamp in UTC, like `2024-05-01T12:30:00Z`.
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, rest) = (secs / 86400, secs % 86400);
+    // the civil date of a day count, after Howard Hinnant
+    let days = days as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+/// Periodically logs runtime statistics of the matrix, for post-mortem analysis of flicker
+/// and stutter at installations.
+///
+/// Every interval, a minute by default, it logs the frames per second, the slowest frame,
+/// the frames dropped for missing their deadline, the brightness and the SoC temperature.
+/// Feed it every frame with [`record`](Self::record), or pass it to
+/// [`MatrixAppBuilder::stats_log`](crate::MatrixAppBuilder::stats_log).
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, StatsLog};
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut stats = StatsLog::file("/var/log/led-matrix/stats.log").rotate(256 * 1024, 4);
+/// let mut canvas = matrix.offscreen_canvas();
+/// loop {
+///     canvas = matrix.swap(canvas);
+///     stats.record(&matrix, Instant::now()).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct StatsLog {
+    sink: Sink,
+    interval: Duration,
+    frame_time: Duration,
+    started: Option<Instant>,
+    last: Option<Instant>,
+    frames: u32,
+    dropped: u32,
+    slowest: Duration,
+}
+
+impl StatsLog {
+    fn new(sink: Sink) -> Self {
+        Self {
+            sink,
+            interval: Duration::from_secs(60),
+            frame_time: Duration::from_nanos(1_000_000_000 / 30),
+            started: None,
+            last: None,
+            frames: 0,
+            dropped: 0,
+            slowest: Duration::ZERO,
+        }
+    }
+
+    /// Logs a line per interval to the file at `path`, rotated at 1 MiB keeping 4 old files.
+    #[must_use]
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::new(Sink::File {
+            path: path.into(),
+            max_size: 1 << 20,
+            keep: 4,
+        })
+    }
+
+    /// Logs an entry per interval to the systemd journal, with the statistics as fields like
+    /// `FPS` and `TEMPERATURE` to query with `journalctl`.
+    #[must_use]
+    pub fn journald() -> Self {
+        Self::new(Sink::Journald)
+    }
+
+    /// Sets how often the statistics are logged
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the frames per second the matrix is driven at, 30 by default, to count the
+    /// frames that took longer as dropped.
+    #[must_use]
+    pub const fn frame_rate(mut self, frames_per_second: u32) -> Self {
+        let frames_per_second = if frames_per_second == 0 {
+            1
+        } else {
+            frames_per_second
+        };
+        self.frame_time = Duration::from_nanos(1_000_000_000 / frames_per_second as u64);
+        self
+    }
+
+    /// Starts a new log file once it would grow past `max_size` bytes, renaming the old ones
+    /// to `path.1`, `path.2` and so on, and keeping at most `keep` of them.
+    ///
+    /// Only applies to logs to a [`file`](Self::file).
+    #[must_use]
+    pub fn rotate(mut self, max_size: u64, keep: u32) -> Self {
+        if let Sink::File {
+            max_size: size,
+            keep: kept,
+            ..
+        } = &mut self.sink
+        {
+            *size = max_size;
+            *kept = keep;
+        }
+        self
+    }
+
+    /// Counts a frame of `matrix` swapped at `now`, logging the statistics once the interval
+    /// passed.
+    ///
+    /// # Errors
+    /// If the statistics can't be written.
+    pub fn record(&mut self, matrix: &LedMatrix, now: Instant) -> Result<(), &'static str> {
+        self.frame(now, || matrix.brightness())
+    }
+
+    fn frame(&mut self, now: Instant, brightness: impl FnOnce() -> u8) -> Result<(), &'static str> {
+        let started = *self.started.get_or_insert(now);
+        if let Some(last) = self.last.replace(now) {
+            let frame_time = now.saturating_duration_since(last);
+            self.slowest = self.slowest.max(frame_time);
+            let missed = frame_time.as_secs_f32() / self.frame_time.as_secs_f32().max(1e-6);
+            // half a frame late already shows as a stutter
+            self.dropped += ((missed + 0.5) as u32).saturating_sub(1);
+            self.frames += 1;
+        }
+        let elapsed = now.saturating_duration_since(started);
+        if elapsed < self.interval || self.frames == 0 {
+            return Ok(());
+        }
+        let entry = Entry {
+            fps: self.frames as f32 / elapsed.as_secs_f32(),
+            slowest: self.slowest,
+            dropped: self.dropped,
+            brightness: brightness(),
+            temperature: soc_temperature(),
+        };
+        self.started = Some(now);
+        self.frames = 0;
+        self.dropped = 0;
+        self.slowest = Duration::ZERO;
+        self.write(&entry, SystemTime::now())
+    }
+
+    fn write(&self, entry: &Entry, time: SystemTime) -> Result<(), &'static str> {
+        let fields = entry.fields();
+        match &self.sink {
+            Sink::File {
+                path,
+                max_size,
+                keep,
+            } => {
+                let mut line = timestamp(time);
+                for (name, value) in &fields {
+                    let _ = write!(line, " {}={value}", name.to_lowercase());
+                }
+                line.push('\n');
+                let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+                if size > 0 && size + line.len() as u64 > *max_size {
+                    let numbered = |n: u32| {
+                        let mut name = path.clone().into_os_string();
+                        name.push(format!(".{n}"));
+                        PathBuf::from(name)
+                    };
+                    let _ = fs::remove_file(numbered(*keep));
+                    for n in (1..*keep).rev() {
+                        let _ = fs::rename(numbered(n), numbered(n + 1));
+                    }
+                    let rotated = if *keep == 0 {
+                        fs::remove_file(path)
+                    } else {
+                        fs::rename(path, numbered(1))
+                    };
+                    rotated.map_err(|_| "Couldn't rotate stats log")?;
+                }
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+                    .map_err(|_| "Couldn't write stats log")
+            }
+            Sink::Journald => {
+                let mut message = format!(
+                    "MESSAGE=Matrix at {} fps, {} frames dropped\n",
+                    fields[0].1, fields[2].1
+                );
+                message.push_str("PRIORITY=6\nSYSLOG_IDENTIFIER=rpi-led-matrix\n");
+                for (name, value) in &fields {
+                    let _ = writeln!(message, "{name}={value}");
+                }
+                let socket = UnixDatagram::unbound().map_err(|_| "Couldn't open journal socket")?;
+                socket
+                    .send_to(message.as_bytes(), JOURNAL_SOCKET)
+                    .map(|_| ())
+                    .map_err(|_| "Couldn't write to the journal")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logging() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(timestamp(leap_day), "2024-02-29T12:34:56Z");
+
+        let directory = std::env::temp_dir().join(format!("led-stats-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("stats.log");
+        let mut stats = StatsLog::file(&path)
+            .interval(Duration::from_secs(1))
+            .frame_rate(10)
+            .rotate(150, 1);
+        let start = Instant::now();
+        // a second of frames with the fourth two frames late, then the next interval
+        for frame in [0, 1, 2, 5, 6, 7, 8, 9, 10, 11, 12] {
+            stats
+                .frame(start + Duration::from_millis(frame * 100), || 80)
+                .unwrap();
+        }
+        let log = fs::read_to_string(&path).unwrap();
+        let line = log.lines().next().unwrap();
+        assert!(line.contains(" fps=8.0 slowest_ms=300 dropped=2 brightness=80 temperature="));
+
+        for _ in 0..3 {
+            let entry = Entry {
+                fps: 30.0,
+                slowest: Duration::from_millis(40),
+                dropped: 0,
+                brightness: 100,
+                temperature: Some(51.2),
+            };
+            stats.write(&entry, SystemTime::now()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(
+            fs::read_to_string(path.with_extension("log.1"))
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
+        assert!(!path.with_extension("log.2").exists());
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
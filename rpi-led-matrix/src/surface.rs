@@ -0,0 +1,259 @@
+#[cfg(feature = "image")]
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use crate::{led_image, ImageOrientation, Transform};
+use crate::{Frame, LedCanvas, LedColorA};
+
+/// A buffer of [`LedColorA`] pixels, keeping transparency until it's drawn onto a canvas.
+///
+/// Surfaces can be composited onto each other, and are flattened when drawn onto a
+/// [`LedCanvas`] or a [`Frame`] in a [pre-swap hook](crate::LedMatrix::add_pre_swap). With the
+/// `image` feature, images with an alpha channel like PNGs load into a surface as they are.
+///
+/// ```
+/// use rpi_led_matrix::{LedColor, LedColorA, LedMatrix, Surface};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let mut glow = Surface::new(8, 8);
+/// for (x, y) in [(3, 3), (4, 3), (3, 4), (4, 4)] {
+///     glow.set(x, y, LedColorA { red: 255, green: 200, blue: 0, alpha: 128 });
+/// }
+/// canvas.fill(&LedColor { red: 0, green: 0, blue: 64 });
+/// canvas.draw_surface(&glow, 10, 10);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Surface {
+    width: i32,
+    height: i32,
+    /// Row-major, one entry per pixel
+    pixels: Vec<LedColorA>,
+}
+
+impl Surface {
+    /// Creates a transparent surface of the given size.
+    #[must_use]
+    pub fn new(width: i32, height: i32) -> Self {
+        let (width, height) = (width.max(0), height.max(0));
+        Self {
+            width,
+            height,
+            pixels: vec![LedColorA::TRANSPARENT; (width * height) as usize],
+        }
+    }
+
+    /// Creates a surface from its pixels, row by row.
+    ///
+    /// # Errors
+    /// If the number of pixels doesn't match the size.
+    pub fn from_pixels(
+        width: i32,
+        height: i32,
+        pixels: &[LedColorA],
+    ) -> Result<Self, &'static str> {
+        if width < 0 || height < 0 || pixels.len() != (width * height) as usize {
+            return Err("Number of pixels doesn't match the surface size");
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        })
+    }
+
+    /// Loads an image file with its alpha channel, see [`LedImage::open`](crate::LedImage::open).
+    ///
+    /// # Errors
+    /// If the file can't be read or decoded.
+    #[cfg(feature = "image")]
+    pub fn open(path: &Path) -> Result<Self, &'static str> {
+        let bytes = std::fs::read(path).map_err(|_| "Couldn't read image file")?;
+        Self::from_bytes_with(&bytes, ImageOrientation::Exif)
+    }
+
+    /// Decodes an image with its alpha channel, oriented as given.
+    ///
+    /// # Errors
+    /// If the format isn't supported or the image can't be decoded.
+    #[cfg(feature = "image")]
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        orientation: ImageOrientation,
+    ) -> Result<Self, &'static str> {
+        let (image, transform) = led_image::decode(bytes, orientation)?;
+        let image = image.to_rgba8();
+        let surface = Self {
+            width: image.width() as i32,
+            height: image.height() as i32,
+            pixels: image
+                .pixels()
+                .map(|pixel| {
+                    let [red, green, blue, alpha] = pixel.0;
+                    LedColorA {
+                        red,
+                        green,
+                        blue,
+                        alpha,
+                    }
+                })
+                .collect(),
+        };
+        Ok(match transform {
+            Some(transform) => surface.transform(transform),
+            None => surface,
+        })
+    }
+
+    /// Rotates and mirrors the surface, the same way a [`Transform`] maps a canvas onto the
+    /// panel.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn transform(&self, transform: Transform) -> Self {
+        let (width, height) = transform.logical_size((self.width, self.height));
+        let mut surface = Self::new(width, height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (tx, ty) = transform.apply(x, y, (width, height));
+                surface.set(tx, ty, self.pixels[(y * self.width + x) as usize]);
+            }
+        }
+        surface
+    }
+
+    /// The width and height in pixels
+    #[must_use]
+    pub const fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// All pixels, row by row
+    #[must_use]
+    pub fn pixels(&self) -> &[LedColorA] {
+        &self.pixels
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y))
+            .then(|| (y * self.width + x) as usize)
+    }
+
+    /// The pixel at (`x`, `y`), or `None` outside the surface.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<LedColorA> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Replaces the pixel at (`x`, `y`), including its alpha.
+    pub fn set(&mut self, x: i32, y: i32, color: LedColorA) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Draws `color` over the pixel at (`x`, `y`), blending by its alpha.
+    pub fn blend(&mut self, x: i32, y: i32, color: LedColorA) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color.blend(&self.pixels[index]);
+        }
+    }
+
+    /// Replaces all pixels with `color`.
+    pub fn fill(&mut self, color: LedColorA) {
+        self.pixels.fill(color);
+    }
+
+    /// Makes the surface transparent.
+    pub fn clear(&mut self) {
+        self.fill(LedColorA::TRANSPARENT);
+    }
+
+    /// Composites `other` over this surface, with its top left corner at (`x`, `y`).
+    pub fn draw_surface(&mut self, other: &Self, x: i32, y: i32) {
+        for (index, color) in other.pixels.iter().enumerate() {
+            let index = index as i32;
+            self.blend(x + index % other.width, y + index / other.width, *color);
+        }
+    }
+
+    /// Iterates over the pixels that aren't fully transparent, with their coordinates.
+    fn visible(&self) -> impl Iterator<Item = (i32, i32, LedColorA)> + '_ {
+        (0..)
+            .zip(&self.pixels)
+            .filter(|(_, color)| color.alpha > 0)
+            .map(|(index, color)| (index % self.width, index / self.width, *color))
+    }
+}
+
+impl LedCanvas {
+    /// Draws `surface` with its top left corner at (`x`, `y`), blending it over what's on the
+    /// canvas by its alpha.
+    pub fn draw_surface(&mut self, surface: &Surface, x: i32, y: i32) {
+        let pixels: Vec<_> = surface
+            .visible()
+            .filter_map(|(sx, sy, color)| {
+                let below = self.get(x + sx, y + sy)?;
+                Some((x + sx, y + sy, color.flatten(&below)))
+            })
+            .collect();
+        self.set_iter(pixels);
+    }
+}
+
+impl Frame<'_> {
+    /// Draws `surface` with its top left corner at (`x`, `y`), blending it over the frame by
+    /// its alpha.
+    pub fn draw_surface(&mut self, surface: &Surface, x: i32, y: i32) {
+        for (sx, sy, color) in surface.visible() {
+            if let Some(below) = self.get(x + sx, y + sy) {
+                self.set(x + sx, y + sy, color.flatten(&below));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LedColor;
+
+    const WHITE: LedColor = LedColor {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+    const BLACK: LedColor = LedColor {
+        red: 0,
+        green: 0,
+        blue: 0,
+    };
+
+    #[test]
+    fn compositing() {
+        let half_white = LedColorA::new(WHITE, 128);
+        assert_eq!(LedColorA::from(BLACK).alpha, 255);
+        assert_eq!(half_white.flatten(&BLACK).red, 128);
+        // half over half covers three quarters, keeping the color
+        let blended = half_white.blend(&half_white);
+        assert_eq!((blended.red, blended.alpha), (255, 192));
+        assert_eq!(half_white.blend(&LedColorA::TRANSPARENT), half_white);
+        assert_eq!(
+            LedColorA::TRANSPARENT.blend(&LedColorA::TRANSPARENT),
+            LedColorA::TRANSPARENT
+        );
+
+        let mut surface = Surface::new(3, 2);
+        let mut spot = Surface::new(2, 2);
+        spot.fill(half_white);
+        spot.set(0, 0, LedColorA::TRANSPARENT);
+        surface.draw_surface(&spot, 2, 0);
+        assert_eq!(surface.get(2, 0), Some(LedColorA::TRANSPARENT));
+        assert_eq!(surface.get(2, 1), Some(half_white));
+        assert_eq!(surface.get(3, 1), None);
+
+        let mut pixels = vec![BLACK; 6];
+        Frame::new(&mut pixels, (3, 2)).draw_surface(&surface, 0, 0);
+        assert_eq!(pixels[5].red, 128);
+        assert_eq!(pixels[2], BLACK);
+        assert!(Surface::from_pixels(2, 2, &[half_white; 3]).is_err());
+    }
+}
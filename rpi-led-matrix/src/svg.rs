@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use resvg::{tiny_skia, usvg};
+
+use crate::{LedColorA, Surface};
+
+impl Surface {
+    /// Rasterizes an SVG image to fit `width` by `height` pixels, keeping its aspect ratio
+    /// and centering it, with transparent pixels around it.
+    ///
+    /// Vector icons and logos are rendered right at the resolution of the panel this way,
+    /// with anti-aliased edges. Text in the image isn't rendered, convert it to paths first.
+    ///
+    /// ```
+    /// use rpi_led_matrix::{LedMatrix, Surface};
+    /// let matrix = LedMatrix::new(None, None).unwrap();
+    /// let mut canvas = matrix.offscreen_canvas();
+    /// let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+    ///     <circle cx="5" cy="5" r="4" fill="orange"/>
+    /// </svg>"#;
+    /// let icon = Surface::from_svg(svg, 16, 16).unwrap();
+    /// canvas.draw_surface(&icon, 0, 0);
+    /// ```
+    ///
+    /// # Errors
+    /// If the image can't be parsed, or the size is empty.
+    pub fn from_svg(data: &[u8], width: i32, height: i32) -> Result<Self, &'static str> {
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default())
+            .map_err(|_| "Couldn't parse SVG image")?;
+        let mut pixmap = tiny_skia::Pixmap::new(width.max(0) as u32, height.max(0) as u32)
+            .ok_or("SVG image size is empty")?;
+        let size = tree.size();
+        let scale = (width as f32 / size.width()).min(height as f32 / size.height());
+        let offset = (
+            (width as f32 - size.width() * scale) / 2.0,
+            (height as f32 - size.height() * scale) / 2.0,
+        );
+        let transform = tiny_skia::Transform::from_row(scale, 0.0, 0.0, scale, offset.0, offset.1);
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+        let pixels: Vec<LedColorA> = pixmap
+            .pixels()
+            .iter()
+            .map(|pixel| {
+                let color = pixel.demultiply();
+                LedColorA {
+                    red: color.red(),
+                    green: color.green(),
+                    blue: color.blue(),
+                    alpha: color.alpha(),
+                }
+            })
+            .collect();
+        Self::from_pixels(width, height, &pixels)
+    }
+
+    /// Reads an SVG file and rasterizes it, see [`from_svg`](Self::from_svg).
+    ///
+    /// # Errors
+    /// If the file can't be read or parsed, or the size is empty.
+    pub fn open_svg(path: &Path, width: i32, height: i32) -> Result<Self, &'static str> {
+        let data = std::fs::read(path).map_err(|_| "Couldn't read SVG file")?;
+        Self::from_svg(&data, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterize() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+            <rect width="10" height="10" fill="red"/>
+        </svg>"#;
+        let surface = Surface::from_svg(svg, 8, 8).unwrap();
+        assert_eq!(surface.size(), (8, 8));
+        // scaled to 8 by 4, centered vertically
+        assert_eq!(surface.get(0, 1).map(|pixel| pixel.alpha), Some(0));
+        let red = surface.get(1, 3).unwrap();
+        assert_eq!((red.red, red.blue, red.alpha), (255, 0, 255));
+        assert_eq!(surface.get(5, 3).map(|pixel| pixel.alpha), Some(0));
+        assert!(Surface::from_svg(b"<svg", 8, 8).is_err());
+        assert!(Surface::from_svg(svg, 0, 8).is_err());
+    }
+}
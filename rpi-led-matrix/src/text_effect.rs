@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use crate::{LedCanvas, LedFont, TextDrawOptions};
+
+/// An animation of text over time, applied to the [`TextDrawOptions`] it's drawn with.
+///
+/// Effects are driven by the time since the text first appeared, e.g. the `elapsed` time
+/// passed to a [`Scene`](crate::Scene), and can be combined: fades multiply the brightness and
+/// the shortest typewriter reveal wins.
+///
+/// ```
+/// use rpi_led_matrix::{LedFont, LedMatrix, TextDrawOptions, TextEffect};
+/// use std::path::Path;
+/// use std::time::{Duration, Instant};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let font = LedFont::new(Path::new("/usr/share/fonts/misc/6x13.bdf")).unwrap();
+/// let effects = [
+///     TextEffect::Typewriter { chars_per_second: 10.0 },
+///     TextEffect::FadeOut { start: Duration::from_secs(5), duration: Duration::from_secs(1) },
+/// ];
+/// let start = Instant::now();
+/// while start.elapsed() < Duration::from_secs(6) {
+///     canvas.clear();
+///     let options = TextDrawOptions::new().position(1, 20);
+///     canvas.draw_text_animated(&font, "Hello!", options, &effects, start.elapsed());
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextEffect {
+    /// Reveals the text one character after the other
+    Typewriter {
+        /// How many characters are revealed per second
+        chars_per_second: f32,
+    },
+    /// Shows and hides the text in turns, starting shown
+    Blink {
+        /// How long the text is shown
+        on: Duration,
+        /// How long the text is hidden
+        off: Duration,
+    },
+    /// Fades the text in from black
+    FadeIn {
+        /// How long fading takes
+        duration: Duration,
+    },
+    /// Fades the text out to black
+    FadeOut {
+        /// When fading starts
+        start: Duration,
+        /// How long fading takes
+        duration: Duration,
+    },
+}
+
+impl TextEffect {
+    /// Applies the effect at `elapsed` to the options.
+    #[must_use]
+    pub fn apply<'a>(
+        &self,
+        options: TextDrawOptions<'a>,
+        elapsed: Duration,
+    ) -> TextDrawOptions<'a> {
+        let brightness = options.current_brightness();
+        match *self {
+            Self::Typewriter { chars_per_second } => {
+                let count = (elapsed.as_secs_f32() * chars_per_second.max(0.0)) as usize;
+                let reveal = options.current_reveal().min(count);
+                options.reveal(reveal)
+            }
+            Self::Blink { on, off } => {
+                let period = (on + off).as_nanos().max(1);
+                let shown = elapsed.as_nanos() % period < on.as_nanos();
+                options.brightness(if shown { brightness } else { 0.0 })
+            }
+            Self::FadeIn { duration } => {
+                options.brightness(brightness * progress(elapsed, duration))
+            }
+            Self::FadeOut { start, duration } => {
+                let faded = progress(elapsed.saturating_sub(start), duration);
+                options.brightness(brightness * (1.0 - faded))
+            }
+        }
+    }
+
+    /// Returns true if the effect won't change anymore after `elapsed` for `text`.
+    ///
+    /// Blinking never finishes.
+    #[must_use]
+    pub fn is_finished(&self, text: &str, elapsed: Duration) -> bool {
+        match *self {
+            Self::Typewriter { chars_per_second } => {
+                elapsed.as_secs_f32() * chars_per_second >= text.chars().count() as f32
+            }
+            Self::Blink { .. } => false,
+            Self::FadeIn { duration } => elapsed >= duration,
+            Self::FadeOut { start, duration } => elapsed >= start + duration,
+        }
+    }
+}
+
+/// How far `elapsed` is into `duration`, from 0 to 1
+fn progress(elapsed: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        1.0
+    } else {
+        (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+}
+
+impl LedCanvas {
+    /// Renders text with all `effects` applied at `elapsed`, see [`draw_text`](Self::draw_text).
+    pub fn draw_text_animated(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        options: TextDrawOptions,
+        effects: &[TextEffect],
+        elapsed: Duration,
+    ) -> i32 {
+        let options = effects
+            .iter()
+            .fold(options, |options, effect| effect.apply(options, elapsed));
+        self.draw_text(font, text, &options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(effects: &[TextEffect], millis: u64) -> (f32, usize) {
+        let elapsed = Duration::from_millis(millis);
+        let options = effects
+            .iter()
+            .fold(TextDrawOptions::new(), |options, effect| {
+                effect.apply(options, elapsed)
+            });
+        (options.current_brightness(), options.current_reveal())
+    }
+
+    #[test]
+    fn effects() {
+        let typewriter = TextEffect::Typewriter {
+            chars_per_second: 10.0,
+        };
+        assert_eq!(applied(&[typewriter], 250).1, 2);
+        assert!(!typewriter.is_finished("Hello", Duration::from_millis(400)));
+        assert!(typewriter.is_finished("Hello", Duration::from_millis(500)));
+
+        let blink = TextEffect::Blink {
+            on: Duration::from_millis(300),
+            off: Duration::from_millis(200),
+        };
+        assert_eq!(applied(&[blink], 100).0, 1.0);
+        assert_eq!(applied(&[blink], 400).0, 0.0);
+        assert_eq!(applied(&[blink], 600).0, 1.0);
+
+        let fade_in = TextEffect::FadeIn {
+            duration: Duration::from_secs(1),
+        };
+        let fade_out = TextEffect::FadeOut {
+            start: Duration::from_secs(1),
+            duration: Duration::from_secs(2),
+        };
+        assert_eq!(applied(&[fade_in, fade_out], 500).0, 0.5);
+        assert_eq!(applied(&[fade_in, fade_out], 2000).0, 0.5);
+        assert_eq!(applied(&[fade_in, fade_out], 4000).0, 0.0);
+        assert!(fade_out.is_finished("", Duration::from_secs(3)));
+    }
+}
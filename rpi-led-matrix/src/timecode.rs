@@ -0,0 +1,519 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The frame rate of a [`Timecode`], as in SMPTE timecode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FrameRate {
+    /// 24 frames per second, film
+    Fps24,
+    /// 25 frames per second, PAL video
+    #[default]
+    Fps25,
+    /// 29.97 frames per second with dropped frame numbers, NTSC video
+    Fps30Drop,
+    /// 30 frames per second
+    Fps30,
+}
+
+impl FrameRate {
+    /// The frame numbers per second
+    #[must_use]
+    pub const fn frames(self) -> u32 {
+        match self {
+            Self::Fps24 => 24,
+            Self::Fps25 => 25,
+            Self::Fps30Drop | Self::Fps30 => 30,
+        }
+    }
+
+    /// How long a frame lasts
+    #[must_use]
+    pub fn frame_duration(self) -> Duration {
+        match self {
+            Self::Fps30Drop => Duration::from_secs(1001) / 30000,
+            rate => Duration::from_secs(1) / rate.frames(),
+        }
+    }
+}
+
+/// A SMPTE timecode position, like `01:00:12:05`, received from show control systems.
+///
+/// ```
+/// use rpi_led_matrix::{FrameRate, Timecode};
+/// use std::time::Duration;
+/// let timecode = Timecode::new(0, 1, 30, 12, FrameRate::Fps25);
+/// assert_eq!(timecode.to_string(), "00:01:30:12");
+/// assert_eq!(timecode.position(), Duration::from_millis(90_480));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timecode {
+    /// Hours, from 0 to 23
+    pub hours: u8,
+    /// Minutes, from 0 to 59
+    pub minutes: u8,
+    /// Seconds, from 0 to 59
+    pub seconds: u8,
+    /// Frames, from 0 to one less than the frame rate
+    pub frames: u8,
+    /// The frame rate
+    pub rate: FrameRate,
+}
+
+/// Frames in ten minutes of drop frame timecode, which drops two frame numbers in every
+/// minute but every tenth
+const DROP_TEN_MINUTES: u32 = 17982;
+/// Frames in a minute of drop frame timecode that drops frame numbers
+const DROP_MINUTE: u32 = 1798;
+
+impl Timecode {
+    /// Creates the timecode `hours:minutes:seconds:frames` at `rate`.
+    #[must_use]
+    pub const fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, rate: FrameRate) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            rate,
+        }
+    }
+
+    /// The number of frames since `00:00:00:00`
+    #[must_use]
+    pub fn frame_count(&self) -> u32 {
+        let fps = self.rate.frames();
+        let minutes = u32::from(self.hours) * 60 + u32::from(self.minutes);
+        let count = (minutes * 60 + u32::from(self.seconds)) * fps + u32::from(self.frames);
+        match self.rate {
+            FrameRate::Fps30Drop => count.saturating_sub(2 * (minutes - minutes / 10)),
+            _ => count,
+        }
+    }
+
+    /// The timecode `count` frames after `00:00:00:00`, wrapping after 24 hours.
+    #[must_use]
+    pub fn from_frame_count(count: u32, rate: FrameRate) -> Self {
+        let fps = rate.frames();
+        let mut count = count;
+        if rate == FrameRate::Fps30Drop {
+            count %= DROP_TEN_MINUTES * 6 * 24;
+            let (tens, rest) = (count / DROP_TEN_MINUTES, count % DROP_TEN_MINUTES);
+            count += 18 * tens + 2 * (rest.saturating_sub(2) / DROP_MINUTE);
+        }
+        let seconds = count / fps;
+        Self {
+            hours: (seconds / 3600 % 24) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: (seconds % 60) as u8,
+            frames: (count % fps) as u8,
+            rate,
+        }
+    }
+
+    /// The timecode `frames` frames later
+    #[must_use]
+    pub fn add_frames(&self, frames: u32) -> Self {
+        Self::from_frame_count(self.frame_count() + frames, self.rate)
+    }
+
+    /// The time since `00:00:00:00`
+    #[must_use]
+    pub fn position(&self) -> Duration {
+        self.rate.frame_duration() * self.frame_count()
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.rate == FrameRate::Fps30Drop {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+/// The frame rate of the rate bits of MIDI timecode
+const fn mtc_rate(bits: u8) -> FrameRate {
+    match bits & 3 {
+        0 => FrameRate::Fps24,
+        1 => FrameRate::Fps25,
+        2 => FrameRate::Fps30Drop,
+        _ => FrameRate::Fps30,
+    }
+}
+
+/// Decodes MIDI timecode (MTC) from quarter frame and full frame messages.
+///
+/// A quarter frame message carries a quarter of a timecode, so a complete one arrives every
+/// two frames. Its timecode is two frames old once complete, which the decoder makes up for.
+#[derive(Clone, Debug, Default)]
+pub struct MtcDecoder {
+    pieces: [u8; 8],
+    /// The next quarter frame piece expected, counting forward from 0
+    next: u8,
+    status: Option<u8>,
+    sysex: Vec<u8>,
+}
+
+impl MtcDecoder {
+    /// Creates a decoder waiting for the first complete timecode.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles the data byte of a quarter frame message (`F1 <data>`), returning the timecode
+    /// once all eight pieces arrived in order.
+    pub fn quarter_frame(&mut self, data: u8) -> Option<Timecode> {
+        let piece = data >> 4 & 7;
+        if piece != self.next && piece != 0 {
+            self.next = 0;
+            return None;
+        }
+        self.pieces[usize::from(piece)] = data & 15;
+        self.next = piece + 1;
+        if piece != 7 {
+            return None;
+        }
+        self.next = 0;
+        let [frames, frames_high, seconds, seconds_high, minutes, minutes_high, hours, rest] =
+            self.pieces;
+        let timecode = Timecode {
+            hours: hours | (rest & 1) << 4,
+            minutes: minutes | minutes_high << 4,
+            seconds: seconds | seconds_high << 4,
+            frames: frames | frames_high << 4,
+            rate: mtc_rate(rest >> 1),
+        };
+        Some(timecode.add_frames(2))
+    }
+
+    /// Handles a full frame message (`F0 7F <device> 01 01 hh mm ss ff F7`), sent when the
+    /// source locates to a new position.
+    pub fn full_frame(&mut self, message: &[u8]) -> Option<Timecode> {
+        let [0xf0, 0x7f, _, 0x01, 0x01, hours, minutes, seconds, frames, 0xf7] = *message else {
+            return None;
+        };
+        self.next = 0;
+        Some(Timecode {
+            hours: hours & 31,
+            minutes,
+            seconds,
+            frames,
+            rate: mtc_rate(hours >> 5),
+        })
+    }
+
+    /// Handles a byte of a raw MIDI stream, e.g. read from a raw MIDI device, returning a
+    /// timecode once one is complete.
+    pub fn push(&mut self, byte: u8) -> Option<Timecode> {
+        match byte {
+            // real time messages may come in between the bytes of any message
+            0xf8.. => None,
+            0xf0 => {
+                self.sysex = vec![byte];
+                self.status = Some(byte);
+                None
+            }
+            0xf7 if self.status == Some(0xf0) => {
+                self.sysex.push(byte);
+                self.status = None;
+                let sysex = std::mem::take(&mut self.sysex);
+                self.full_frame(&sysex)
+            }
+            0x80.. => {
+                self.status = Some(byte);
+                None
+            }
+            _ => match self.status {
+                Some(0xf0) => {
+                    self.sysex.push(byte);
+                    None
+                }
+                Some(0xf1) => {
+                    self.status = None;
+                    self.quarter_frame(byte)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// The sync word ending every LTC frame, in the order it's received
+const LTC_SYNC: u16 = 0b0011_1111_1111_1101;
+
+/// Decodes linear timecode (LTC) from audio samples, like a timecode track played by a show
+/// control system into a sound card.
+///
+/// The bit rate is learned from the signal, so any sample rate well above 10 kHz works and
+/// the speed may vary a little. Frames are recognized by their sync word, backwards playback
+/// isn't supported.
+#[derive(Clone, Debug)]
+pub struct LtcDecoder {
+    sample_rate: u32,
+    /// Whether the signal is above the zero line, with hysteresis
+    high: bool,
+    /// Samples since the last zero crossing
+    since_crossing: u32,
+    /// Estimated samples per bit
+    bit_length: f32,
+    /// Whether the first half of a one bit was seen
+    half_bit: bool,
+    /// The last 80 bits received, the latest in the lowest bit
+    bits: u128,
+}
+
+/// Level the signal has to cross to count as a transition, against noise around zero
+const LTC_HYSTERESIS: f32 = 0.02;
+
+impl LtcDecoder {
+    /// Creates a decoder for audio sampled at `sample_rate` samples per second.
+    #[must_use]
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            high: false,
+            since_crossing: 0,
+            // 25 frames of 80 bits per second, until the signal tells otherwise
+            bit_length: sample_rate as f32 / 2000.0,
+            half_bit: false,
+            bits: 0,
+        }
+    }
+
+    /// Handles mono `samples` from -1 to 1, returning the latest timecode completed in them.
+    /// The timecode is that of the frame starting after the samples.
+    pub fn push(&mut self, samples: &[f32]) -> Option<Timecode> {
+        let mut latest = None;
+        for &sample in samples {
+            self.since_crossing += 1;
+            let crossed = if self.high {
+                sample < -LTC_HYSTERESIS
+            } else {
+                sample > LTC_HYSTERESIS
+            };
+            if crossed {
+                self.high = !self.high;
+                let interval = self.since_crossing as f32;
+                self.since_crossing = 0;
+                latest = self.transition(interval).or(latest);
+            }
+        }
+        latest
+    }
+
+    /// Handles a transition `interval` samples after the one before.
+    fn transition(&mut self, interval: f32) -> Option<Timecode> {
+        // biphase mark code: a zero is a whole bit without transition, a one has another one
+        // in the middle
+        if interval > self.bit_length * 0.75 {
+            if interval > self.bit_length * 1.5 {
+                // a gap in the signal, the next transition starts a bit
+                self.half_bit = false;
+                return None;
+            }
+            self.bit_length = self.bit_length * 0.9 + interval * 0.1;
+            self.half_bit = false;
+            self.bit(false)
+        } else {
+            self.bit_length = self.bit_length * 0.9 + interval * 2.0 * 0.1;
+            self.half_bit = !self.half_bit;
+            if self.half_bit {
+                None
+            } else {
+                self.bit(true)
+            }
+        }
+    }
+
+    fn bit(&mut self, one: bool) -> Option<Timecode> {
+        self.bits = (self.bits << 1 | u128::from(one)) & ((1 << 80) - 1);
+        if self.bits as u16 != LTC_SYNC {
+            return None;
+        }
+        // bits are sent with the lowest bit of each field first
+        let field = |start: u32, length: u32| {
+            (0..length).fold(0u8, |value, bit| {
+                let received = (self.bits >> (79 - start - bit)) & 1;
+                value | (received as u8) << bit
+            })
+        };
+        let fps = self.sample_rate as f32 / self.bit_length / 80.0;
+        let rate = if field(10, 1) == 1 {
+            FrameRate::Fps30Drop
+        } else if fps < 24.5 {
+            FrameRate::Fps24
+        } else if fps < 27.5 {
+            FrameRate::Fps25
+        } else {
+            FrameRate::Fps30
+        };
+        let timecode = Timecode {
+            hours: field(48, 4) + field(56, 2) * 10,
+            minutes: field(32, 4) + field(40, 3) * 10,
+            seconds: field(16, 4) + field(24, 3) * 10,
+            frames: field(0, 4) + field(8, 2) * 10,
+            rate,
+        };
+        Some(timecode.add_frames(1))
+    }
+}
+
+/// Follows an external timecode, running on between the timecodes received.
+///
+/// A source that stops sending is taken as stopped once the freewheel time passed, so shows
+/// pause with the show control system.
+///
+/// ```
+/// use rpi_led_matrix::{FrameRate, Timecode, TimecodeClock};
+/// use std::time::{Duration, Instant};
+/// let mut clock = TimecodeClock::new();
+/// let now = Instant::now();
+/// clock.update(Timecode::new(0, 0, 10, 0, FrameRate::Fps25), now);
+/// let later = now + Duration::from_millis(100);
+/// assert_eq!(clock.position(later), Some(Duration::from_millis(10_100)));
+/// assert_eq!(clock.position(now + Duration::from_secs(1)), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimecodeClock {
+    last: Option<(Timecode, Instant)>,
+    freewheel: Duration,
+}
+
+impl Default for TimecodeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimecodeClock {
+    /// Creates a clock that hasn't received a timecode, freewheeling for half a second.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            freewheel: Duration::from_millis(500),
+        }
+    }
+
+    /// Sets how long the clock runs on without receiving timecode
+    #[must_use]
+    pub const fn freewheel(mut self, freewheel: Duration) -> Self {
+        self.freewheel = freewheel;
+        self
+    }
+
+    /// Takes `timecode`, received at `now`.
+    pub fn update(&mut self, timecode: Timecode, now: Instant) {
+        self.last = Some((timecode, now));
+    }
+
+    /// The last timecode received
+    #[must_use]
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.last.map(|(timecode, _)| timecode)
+    }
+
+    /// The position of the source at `now`, or `None` while it's stopped.
+    #[must_use]
+    pub fn position(&self, now: Instant) -> Option<Duration> {
+        let (timecode, received) = self.last?;
+        let since = now.saturating_duration_since(received);
+        (since <= self.freewheel).then(|| timecode.position() + since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes `timecode` as a frame of LTC, `samples_per_bit` samples per bit, continuing
+    /// from `level`.
+    fn ltc(timecode: Timecode, samples_per_bit: usize, level: &mut f32) -> Vec<f32> {
+        let mut fields = [0u8; 10];
+        let bcd = |value: u8| (value % 10, value / 10);
+        let (frames, frame_tens) = bcd(timecode.frames);
+        let (seconds, second_tens) = bcd(timecode.seconds);
+        let (minutes, minute_tens) = bcd(timecode.minutes);
+        let (hours, hour_tens) = bcd(timecode.hours);
+        fields[0] = frames;
+        fields[1] = frame_tens | u8::from(timecode.rate == FrameRate::Fps30Drop) << 2;
+        fields[2] = seconds;
+        fields[3] = second_tens;
+        fields[4] = minutes;
+        fields[5] = minute_tens;
+        fields[6] = hours;
+        fields[7] = hour_tens;
+        fields[8] = 0xfc;
+        fields[9] = 0xbf;
+        let mut samples = Vec::new();
+        for bit in 0..80 {
+            let one = fields[bit / 8] >> (bit % 8) & 1 == 1;
+            *level = -*level;
+            for i in 0..samples_per_bit {
+                if one && i == samples_per_bit / 2 {
+                    *level = -*level;
+                }
+                samples.push(*level);
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn decoding() {
+        let drop = Timecode::new(0, 10, 0, 2, FrameRate::Fps30Drop);
+        assert_eq!(
+            Timecode::from_frame_count(drop.frame_count(), drop.rate),
+            drop
+        );
+        let minute = Timecode::new(0, 1, 0, 2, FrameRate::Fps30Drop);
+        assert_eq!(minute.frame_count(), 1800);
+        assert_eq!(
+            Timecode::new(0, 0, 59, 29, FrameRate::Fps30Drop).add_frames(1),
+            minute
+        );
+        assert_eq!(minute.to_string(), "00:01:00;02");
+
+        let mut mtc = MtcDecoder::new();
+        let timecode = Timecode::new(1, 2, 3, 4, FrameRate::Fps25);
+        let mut decoded = None;
+        // quarter frames of 01:02:03:04 at 25 fps, with a clock tick in between
+        for data in [0x04, 0x10, 0x23, 0x30, 0x42, 0x50, 0x61, 0x72] {
+            assert_eq!(decoded, None);
+            decoded = mtc.push(0xf1).or(mtc.push(0xf8)).or(mtc.push(data));
+        }
+        assert_eq!(decoded, Some(timecode.add_frames(2)));
+        let full = [0xf0, 0x7f, 0x7f, 0x01, 0x01, 0x21, 2, 3, 4, 0xf7];
+        let decoded = full
+            .iter()
+            .fold(None, |decoded, &byte| mtc.push(byte).or(decoded));
+        assert_eq!(decoded, Some(timecode));
+
+        // 48 kHz at 25 fps, starting with a gap the decoder locks on after
+        let mut ltc_decoder = LtcDecoder::new(48_000);
+        let mut level = 0.5;
+        let mut samples = vec![level; 100];
+        for frame in 0..3 {
+            samples.extend(ltc(timecode.add_frames(frame), 24, &mut level));
+        }
+        // the transition starting the next frame ends the last bit
+        samples.push(-level);
+        assert_eq!(ltc_decoder.push(&samples), Some(timecode.add_frames(3)));
+        let mut samples = ltc(
+            Timecode::new(23, 59, 59, 29, FrameRate::Fps30Drop),
+            20,
+            &mut level,
+        );
+        samples.push(-level);
+        let decoded = ltc_decoder.push(&samples).unwrap();
+        assert_eq!(decoded.to_string(), "00:00:00;00");
+    }
+}
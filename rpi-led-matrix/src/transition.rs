@@ -0,0 +1,210 @@
+use crate::{Frame, LedColor};
+
+/// The direction new content moves in during a [`Transition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// New content enters from the right edge
+    Left,
+    /// New content enters from the left edge
+    Right,
+    /// New content enters from the bottom edge
+    Up,
+    /// New content enters from the top edge
+    Down,
+}
+
+/// How a [`Transition`] moves from the old content to the new.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// The new content pushes the old one out of the panel
+    Slide(Direction),
+    /// The new content is uncovered over the old one, which stays in place
+    Wipe(Direction),
+    /// The old content blends into the new one
+    Fade,
+    /// Pixels switch to the new content one by one, in a fixed random order
+    Dissolve,
+}
+
+/// An animated change from the content last shown to new content, played over a number of
+/// swaps by [`LedMatrix::start_transition`](crate::LedMatrix::start_transition).
+///
+/// ```
+/// use rpi_led_matrix::{Direction, LedColor, LedMatrix, Transition, TransitionKind};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// canvas.fill(&LedColor { red: 255, green: 0, blue: 0 });
+/// canvas = matrix.swap(canvas);
+/// // the next 30 swaps slide the blue frame in over the red one
+/// matrix.start_transition(Transition::new(TransitionKind::Slide(Direction::Left), 30));
+/// for _ in 0..30 {
+///     canvas.fill(&LedColor { red: 0, green: 0, blue: 255 });
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Transition {
+    kind: TransitionKind,
+    frames: u32,
+    frame: u32,
+    /// The old content, in panel coordinates
+    from: Vec<LedColor>,
+}
+
+impl Transition {
+    /// Creates a transition taking `frames` swaps, the last of which shows only new content.
+    #[must_use]
+    pub const fn new(kind: TransitionKind, frames: u32) -> Self {
+        Self {
+            kind,
+            frames,
+            frame: 0,
+            from: Vec::new(),
+        }
+    }
+
+    /// Returns true once all frames are played.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    /// Sets the old content the transition starts from.
+    pub(crate) fn set_from(&mut self, from: Vec<LedColor>) {
+        self.from = from;
+    }
+
+    /// Blends the old content into `frame`, which holds the new one, and advances by a frame.
+    pub(crate) fn apply(&mut self, frame: &mut Frame) {
+        if self.is_finished() {
+            return;
+        }
+        self.frame += 1;
+        let t = self.frame as f32 / self.frames as f32;
+        let (width, height) = frame.size();
+        if self.from.len() != frame.pixels().len() {
+            // the panel changed size, there's nothing to start from
+            return;
+        }
+        let to = frame.pixels().to_vec();
+        let from = &self.from;
+        let pixels = frame.pixels_mut();
+        match self.kind {
+            TransitionKind::Fade => {
+                for (pixel, old) in pixels.iter_mut().zip(from) {
+                    *pixel = old.lerp(pixel, t);
+                }
+            }
+            TransitionKind::Dissolve => {
+                for (index, (pixel, old)) in pixels.iter_mut().zip(from).enumerate() {
+                    if dissolve_order(index) >= t {
+                        *pixel = *old;
+                    }
+                }
+            }
+            TransitionKind::Slide(direction) | TransitionKind::Wipe(direction) => {
+                let slide = matches!(self.kind, TransitionKind::Slide(_));
+                let (extent, along) = match direction {
+                    Direction::Left | Direction::Right => (width, true),
+                    Direction::Up | Direction::Down => (height, false),
+                };
+                let offset = (extent as f32 * t).round() as i32;
+                for y in 0..height {
+                    for x in 0..width {
+                        let position = if along { x } else { y };
+                        // distance from the edge the new content enters at
+                        let entering = match direction {
+                            Direction::Left | Direction::Up => extent - 1 - position,
+                            Direction::Right | Direction::Down => position,
+                        };
+                        let shift = |shift: i32| match direction {
+                            Direction::Left => (x + shift, y),
+                            Direction::Right => (x - shift, y),
+                            Direction::Up => (x, y + shift),
+                            Direction::Down => (x, y - shift),
+                        };
+                        let index = (y * width + x) as usize;
+                        pixels[index] = if entering < offset {
+                            if slide {
+                                let (sx, sy) = shift(offset - extent);
+                                to[(sy * width + sx) as usize]
+                            } else {
+                                to[index]
+                            }
+                        } else if slide {
+                            let (sx, sy) = shift(offset);
+                            from[(sy * width + sx) as usize]
+                        } else {
+                            from[index]
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// When the pixel at `index` switches during a dissolve, spread evenly over 0 to 1.
+fn dissolve_order(index: usize) -> f32 {
+    // Knuth's multiplicative hash scatters neighbouring pixels
+    let hash = (index as u32).wrapping_mul(2_654_435_761);
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn gray(level: u8) -> LedColor {
+        LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        }
+    }
+
+    /// Runs `frames` frames of `kind` from a gradient to black, returning the red channel of
+    /// the last frame.
+    fn run(kind: TransitionKind, frames: u32, played: u32) -> Vec<u8> {
+        let mut transition = Transition::new(kind, frames);
+        transition.set_from((1..=8).map(|level| gray(level * 10)).collect());
+        let mut pixels = Vec::new();
+        for _ in 0..played {
+            pixels = vec![gray(0); 8];
+            transition.apply(&mut Frame::new(&mut pixels, (4, 2)));
+        }
+        pixels.iter().map(|color| color.red).collect()
+    }
+
+    #[test]
+    fn effects() {
+        assert_eq!(
+            run(TransitionKind::Fade, 2, 1),
+            [5, 10, 15, 20, 25, 30, 35, 40]
+        );
+        // the old content moves out to the left, the new enters from the right
+        assert_eq!(
+            run(TransitionKind::Slide(Direction::Left), 4, 1),
+            [20, 30, 40, 0, 60, 70, 80, 0]
+        );
+        assert_eq!(
+            run(TransitionKind::Slide(Direction::Down), 2, 1),
+            [0, 0, 0, 0, 10, 20, 30, 40]
+        );
+        assert_eq!(
+            run(TransitionKind::Wipe(Direction::Right), 4, 1),
+            [0, 20, 30, 40, 0, 60, 70, 80]
+        );
+        let dissolved = run(TransitionKind::Dissolve, 2, 1);
+        assert!(dissolved.contains(&0) && dissolved.iter().any(|&red| red > 0));
+        // the last frame only shows the new content
+        for kind in [
+            TransitionKind::Fade,
+            TransitionKind::Dissolve,
+            TransitionKind::Slide(Direction::Up),
+            TransitionKind::Wipe(Direction::Left),
+        ] {
+            assert_eq!(run(kind, 3, 3), [0; 8]);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Prepares lines of text for drawing, glyph by glyph.
+//!
+//! Combining marks are grouped with the character they're placed on. With the `unicode`
+//! feature, lines are split into extended grapheme clusters, Arabic letters are shaped into
+//! their joined forms and right-to-left text is reordered for display.
+
+/// Splits a line into clusters drawn at the same pen position, in display order.
+///
+/// The first character of every cluster advances the pen, the following ones are marks drawn
+/// on top of it.
+pub(crate) fn display_clusters(text: &str) -> Vec<Vec<char>> {
+    #[cfg(feature = "unicode")]
+    {
+        let info = unicode_bidi::BidiInfo::new(text, None);
+        if info.has_rtl() {
+            let shaped = shape_arabic(text);
+            let info = unicode_bidi::BidiInfo::new(&shaped, None);
+            let mut display = Vec::new();
+            for paragraph in &info.paragraphs {
+                let (levels, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+                for run in runs {
+                    let clusters = clusters(&shaped[run.clone()]);
+                    if levels[run.start].is_rtl() {
+                        display.extend(clusters.into_iter().rev());
+                    } else {
+                        display.extend(clusters);
+                    }
+                }
+            }
+            return display;
+        }
+    }
+    clusters(text)
+}
+
+/// Splits text into grapheme clusters in logical order.
+#[cfg(feature = "unicode")]
+fn clusters(text: &str) -> Vec<Vec<char>> {
+    use unicode_segmentation::UnicodeSegmentation;
+    text.graphemes(true)
+        .map(|cluster| cluster.chars().collect())
+        .collect()
+}
+
+/// Splits text before every character that isn't a combining mark, in logical order.
+#[cfg(not(feature = "unicode"))]
+fn clusters(text: &str) -> Vec<Vec<char>> {
+    let mut clusters: Vec<Vec<char>> = Vec::new();
+    for c in text.chars() {
+        match clusters.last_mut() {
+            Some(cluster) if is_combining(c) => cluster.push(c),
+            _ => clusters.push(vec![c]),
+        }
+    }
+    clusters
+}
+
+/// Returns true for the common combining marks of Latin, Greek, Cyrillic, Hebrew and Arabic.
+#[cfg(not(feature = "unicode"))]
+fn is_combining(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{0591}'..='\u{05BD}'
+            | '\u{05BF}'
+            | '\u{05C1}'..='\u{05C2}'
+            | '\u{05C4}'..='\u{05C5}'
+            | '\u{05C7}'
+            | '\u{064B}'..='\u{065F}'
+            | '\u{0670}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+/// How an Arabic letter connects to its neighbours.
+#[cfg(feature = "unicode")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// Only connects to the letter before it, e.g. alef
+    Right,
+    /// Connects to the letters on both sides, e.g. beh
+    Dual,
+    /// Connects on both sides without changing its own form, the tatweel
+    Causing,
+}
+
+/// Joining type and first presentation form (isolated, final, initial, medial) of a letter.
+#[cfg(feature = "unicode")]
+fn arabic_forms(c: char) -> Option<(Joining, u32)> {
+    if c == '\u{0640}' {
+        return Some((Joining::Causing, 0x0640));
+    }
+    let right = |c: char| {
+        matches!(c, '\u{0622}'..='\u{0625}' | '\u{0627}' | '\u{0629}')
+            || matches!(c, '\u{062F}'..='\u{0632}' | '\u{0648}' | '\u{0649}')
+    };
+    let letters = ('\u{0621}'..='\u{063A}').chain('\u{0641}'..='\u{064A}');
+    // the presentation forms follow the letters in order, with as many forms as they have
+    let mut form = 0xFE80;
+    for letter in letters {
+        let (joining, count) = match letter {
+            '\u{0621}' => (None, 1),
+            l if right(l) => (Some(Joining::Right), 2),
+            _ => (Some(Joining::Dual), 4),
+        };
+        if letter == c {
+            return joining.map(|joining| (joining, form));
+        }
+        form += count;
+    }
+    None
+}
+
+/// Replaces Arabic letters by the presentation form matching their neighbours, including the
+/// mandatory lam-alef ligatures.
+#[cfg(feature = "unicode")]
+fn shape_arabic(text: &str) -> String {
+    use unicode_bidi::BidiClass;
+    // marks between letters don't break their connection
+    let transparent = |c: char| unicode_bidi::bidi_class(c) == BidiClass::NSM;
+    let chars: Vec<char> = text.chars().collect();
+    let neighbour = |range: &mut dyn Iterator<Item = usize>| {
+        range
+            .map(|i| chars[i])
+            .find(|&c| !transparent(c))
+            .and_then(arabic_forms)
+    };
+    let mut shaped = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let Some((joining, first)) = arabic_forms(c) else {
+            shaped.push(c);
+            i += 1;
+            continue;
+        };
+        let joins_before =
+            neighbour(&mut (0..i).rev()).is_some_and(|(before, _)| before != Joining::Right);
+        let next = (i + 1..chars.len()).find(|&j| !transparent(chars[j]));
+        let ligature = match (c, next.map(|j| chars[j])) {
+            ('\u{0644}', Some('\u{0622}')) => Some(0xFEF5),
+            ('\u{0644}', Some('\u{0623}')) => Some(0xFEF7),
+            ('\u{0644}', Some('\u{0625}')) => Some(0xFEF9),
+            ('\u{0644}', Some('\u{0627}')) => Some(0xFEFB),
+            _ => None,
+        };
+        if let (Some(ligature), Some(alef)) = (ligature, next) {
+            let form = ligature + u32::from(joins_before);
+            shaped.extend(char::from_u32(form));
+            // keep marks on the lam, drop the alef merged into the ligature
+            shaped.extend(&chars[i + 1..alef]);
+            i = alef + 1;
+            continue;
+        }
+        let joins_after =
+            joining != Joining::Right && neighbour(&mut (i + 1..chars.len())).is_some();
+        let form = match (joining, joins_before, joins_after) {
+            (Joining::Causing, ..) => first,
+            (_, false, false) => first,
+            (_, true, false) => first + 1,
+            (_, false, true) => first + 2,
+            (_, true, true) => first + 3,
+        };
+        shaped.extend(char::from_u32(form));
+        i += 1;
+    }
+    shaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(text: &str) -> String {
+        display_clusters(text).into_iter().flatten().collect()
+    }
+
+    #[test]
+    fn combining_marks() {
+        assert_eq!(
+            display_clusters("e\u{301}x"),
+            [vec!['e', '\u{301}'], vec!['x']]
+        );
+        assert_eq!(display("plain"), "plain");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn right_to_left() {
+        // Hebrew is reversed, the Latin text and number keep their order
+        assert_eq!(display("abc אבג"), "abc גבא");
+        assert_eq!(display("אב 12"), "12 בא");
+        // points stay on their letter
+        assert_eq!(
+            display_clusters("\u{5D0}\u{5B8}\u{5D1}"),
+            [vec!['\u{5D1}'], vec!['\u{5D0}', '\u{5B8}']]
+        );
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn arabic_shaping() {
+        // beh beh: initial and final form, displayed right to left
+        assert_eq!(shape_arabic("\u{628}\u{628}"), "\u{FE91}\u{FE90}");
+        assert_eq!(display("\u{628}\u{628}"), "\u{FE90}\u{FE91}");
+        // beh alef beh: alef doesn't connect to the beh after it
+        assert_eq!(
+            shape_arabic("\u{628}\u{627}\u{628}"),
+            "\u{FE91}\u{FE8E}\u{FE8F}"
+        );
+        // medial seen, the hamza never joins
+        assert_eq!(
+            shape_arabic("\u{628}\u{633}\u{628} \u{621}"),
+            "\u{FE91}\u{FEB4}\u{FE90} \u{621}"
+        );
+        // lam-alef ligature, isolated and final
+        assert_eq!(shape_arabic("\u{644}\u{627}"), "\u{FEFB}");
+        assert_eq!(shape_arabic("\u{628}\u{644}\u{627}"), "\u{FE91}\u{FEFC}");
+    }
+}
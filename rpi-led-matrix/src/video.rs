@@ -0,0 +1,177 @@
+use std::io::Read;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::{LedColor, LedMatrix};
+
+/// A video file or stream, decoded by the `ffmpeg` command line tool and scaled to the
+/// matrix with letterboxing.
+///
+/// Anything `ffmpeg` can read works as a source, like files, HTTP or RTSP streams and
+/// webcams. Frames are decoded at a fixed frame rate, dropping or repeating frames of the
+/// source as needed, so playback stays in time with the source.
+///
+/// ```no_run
+/// use rpi_led_matrix::{LedMatrix, Video};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// Video::new("/home/pi/fireplace.mp4")
+///     .frame_rate(25)
+///     .looping(true)
+///     .play(&matrix)
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Video {
+    source: String,
+    frame_rate: u32,
+    looping: bool,
+}
+
+impl Video {
+    /// Creates a video of a file path or URL, played at 30 frames per second once.
+    #[must_use]
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            frame_rate: 30,
+            looping: false,
+        }
+    }
+
+    /// Sets the frames per second the video is decoded and played at
+    #[must_use]
+    pub const fn frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.frame_rate = frames_per_second;
+        self
+    }
+
+    /// Sets whether a file starts over at its end
+    #[must_use]
+    pub const fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// The time every frame is shown
+    fn frame_time(&self) -> Duration {
+        Duration::from_secs(1) / self.frame_rate.max(1)
+    }
+
+    /// Arguments for `ffmpeg` writing frames of `width` by `height` as raw RGB to stdout.
+    fn arguments(&self, (width, height): (i32, i32)) -> Vec<String> {
+        let filter = format!(
+            "fps={},scale={width}:{height}:force_original_aspect_ratio=decrease,\
+             pad={width}:{height}:(ow-iw)/2:(oh-ih)/2",
+            self.frame_rate.max(1)
+        );
+        let mut arguments = vec!["-loglevel", "error", "-nostdin"];
+        if self.looping {
+            arguments.extend(["-stream_loop", "-1"]);
+        }
+        arguments.extend(["-i", &self.source, "-vf", &filter]);
+        arguments.extend(["-f", "rawvideo", "-pix_fmt", "rgb24", "-"]);
+        arguments.into_iter().map(String::from).collect()
+    }
+
+    /// Starts decoding frames of `width` by `height` pixels.
+    ///
+    /// # Errors
+    /// If `ffmpeg` can't be started.
+    pub fn frames(&self, size: (i32, i32)) -> Result<VideoFrames, &'static str> {
+        let mut child = Command::new("ffmpeg")
+            .args(self.arguments(size))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|_| "Couldn't start ffmpeg")?;
+        let stdout = child.stdout.take().ok_or("Couldn't read from ffmpeg")?;
+        Ok(VideoFrames {
+            child,
+            stdout,
+            size,
+        })
+    }
+
+    /// Plays the video on `matrix` until it ends, scaled to the size of its canvas.
+    ///
+    /// Frames are swapped in at the frame rate. When drawing falls behind by more than a
+    /// frame, frames are dropped to catch up.
+    ///
+    /// # Errors
+    /// If `ffmpeg` can't be started.
+    pub fn play(&self, matrix: &LedMatrix) -> Result<(), &'static str> {
+        let mut canvas = matrix.offscreen_canvas();
+        let (width, _) = canvas.canvas_size();
+        let frame_time = self.frame_time();
+        let start = Instant::now();
+        for (index, frame) in (0..).zip(self.frames(canvas.canvas_size())?) {
+            let due = start + frame_time * index;
+            if Instant::now() > due + frame_time {
+                continue;
+            }
+            canvas.shade(|x, y| frame[(y * width + x) as usize]);
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            canvas = matrix.swap(canvas);
+        }
+        Ok(())
+    }
+}
+
+/// Decoded frames of a [`Video`], row by row, ending with the video.
+///
+/// Decoding stops when this is dropped.
+pub struct VideoFrames {
+    child: Child,
+    stdout: ChildStdout,
+    size: (i32, i32),
+}
+
+impl Iterator for VideoFrames {
+    type Item = Vec<LedColor>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (width, height) = self.size;
+        let mut bytes = vec![0; (width.max(0) * height.max(0) * 3) as usize];
+        self.stdout.read_exact(&mut bytes).ok()?;
+        Some(colors(&bytes))
+    }
+}
+
+impl Drop for VideoFrames {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Colors of raw RGB bytes
+fn colors(bytes: &[u8]) -> Vec<LedColor> {
+    bytes
+        .chunks_exact(3)
+        .map(|rgb| LedColor {
+            red: rgb[0],
+            green: rgb[1],
+            blue: rgb[2],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments() {
+        let video = Video::new("clip.mp4").frame_rate(0).looping(true);
+        let arguments = video.arguments((64, 32));
+        assert_eq!(arguments[3..5], ["-stream_loop", "-1"]);
+        assert_eq!(arguments[6], "clip.mp4");
+        assert_eq!(
+            arguments[8],
+            "fps=1,scale=64:32:force_original_aspect_ratio=decrease,pad=64:32:(ow-iw)/2:(oh-ih)/2"
+        );
+        assert_eq!(arguments.last().map(String::as_str), Some("-"));
+        assert_eq!(video.frame_time(), Duration::from_secs(1));
+        assert_eq!(colors(&[1, 2, 3, 4, 5, 6, 7])[1].blue, 6);
+    }
+}
@@ -0,0 +1,232 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "image")]
+use crate::LedImage;
+use crate::{unicode, widgets::Icon, Frame, LedColor, LedColorA, LedFont, Surface};
+
+/// A corner of the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+    /// The top left corner
+    TopLeft,
+    /// The top right corner
+    TopRight,
+    /// The bottom left corner
+    BottomLeft,
+    /// The bottom right corner
+    #[default]
+    BottomRight,
+}
+
+/// A small logo or text kept in a corner of the panel on top of all content, set with
+/// [`LedMatrix::set_watermark`](crate::LedMatrix::set_watermark).
+///
+/// The watermark is blended over the final frame on every swap, so application code doesn't
+/// have to draw it, and scenes or transitions can't cover it. It's placed in panel
+/// coordinates, regardless of the canvas transform.
+///
+/// ```
+/// use rpi_led_matrix::{Corner, LedColor, LedFont, LedMatrix, Watermark};
+/// use std::path::Path;
+/// use std::time::Duration;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let font = LedFont::new(Path::new("/usr/share/fonts/misc/4x6.bdf")).unwrap();
+/// let gray = LedColor { red: 128, green: 128, blue: 128 };
+/// // shown for ten seconds every minute
+/// let watermark = Watermark::text("ACME", &font, gray)
+///     .corner(Corner::TopRight)
+///     .margin(1)
+///     .opacity(0.5)
+///     .schedule(Duration::from_secs(60), Duration::from_secs(10));
+/// matrix.set_watermark(Some(watermark));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Watermark {
+    surface: Surface,
+    corner: Corner,
+    margin: i32,
+    opacity: f32,
+    /// Period and the time shown at its start
+    schedule: Option<(Duration, Duration)>,
+    start: Instant,
+}
+
+impl Watermark {
+    /// Creates a watermark of a surface, keeping its transparency, e.g. a logo loaded from a
+    /// PNG file.
+    #[must_use]
+    pub fn surface(surface: Surface) -> Self {
+        Self {
+            surface,
+            corner: Corner::default(),
+            margin: 0,
+            opacity: 1.0,
+            schedule: None,
+            start: Instant::now(),
+        }
+    }
+
+    /// Creates a watermark of a single line of `text`.
+    #[must_use]
+    pub fn text(text: &str, font: &LedFont, color: LedColor) -> Self {
+        let (width, height) = font.measure(text, 0);
+        let mut surface = Surface::new(width, height);
+        let mut pen = 0;
+        for cluster in unicode::display_clusters(text) {
+            // combining marks are drawn on top of the character before them
+            for c in &cluster {
+                for (x, y) in font.glyph(*c).into_iter().flat_map(|glyph| glyph.pixels()) {
+                    surface.set(pen + x, font.baseline() + y, color.into());
+                }
+            }
+            pen += font.char_width(cluster[0]);
+        }
+        Self::surface(surface)
+    }
+
+    /// Creates a watermark of an icon.
+    #[must_use]
+    pub fn icon(icon: &Icon, color: LedColor) -> Self {
+        let (width, height) = icon.size();
+        let mut surface = Surface::new(width, height);
+        for (x, y) in icon.pixels() {
+            surface.set(x, y, color.into());
+        }
+        Self::surface(surface)
+    }
+
+    /// Creates a watermark of an image, with black pixels left transparent.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn image(image: &LedImage) -> Self {
+        let (width, height) = (image.width(), image.height());
+        let pixels: Vec<_> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                image
+                    .get(x, y)
+                    .filter(|color| (color.red, color.green, color.blue) != (0, 0, 0))
+                    .map_or(LedColorA::TRANSPARENT, LedColorA::from)
+            })
+            .collect();
+        Self::surface(
+            Surface::from_pixels(width as i32, height as i32, &pixels)
+                .expect("One pixel per image pixel"),
+        )
+    }
+
+    /// Sets the corner of the panel, the bottom right one by default
+    #[must_use]
+    pub const fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Sets the distance from the edges of the panel in pixels
+    #[must_use]
+    pub const fn margin(mut self, margin: i32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets how much the watermark covers the content below it, from 0 to 1
+    #[must_use]
+    pub const fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Shows the watermark only for `shown` at the start of every `period`, counting from
+    /// its creation, instead of all the time.
+    #[must_use]
+    pub const fn schedule(mut self, period: Duration, shown: Duration) -> Self {
+        self.schedule = Some((period, shown));
+        self
+    }
+
+    /// Returns true if the watermark is shown at `now`, according to its schedule.
+    #[must_use]
+    pub fn is_visible(&self, now: Instant) -> bool {
+        self.schedule.is_none_or(|(period, shown)| {
+            let elapsed = now.saturating_duration_since(self.start);
+            period.is_zero()
+                || Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64) < shown
+        })
+    }
+
+    /// Blends the watermark into `frame`, if it's visible at `now`.
+    pub(crate) fn apply(&self, frame: &mut Frame, now: Instant) {
+        if !self.is_visible(now) {
+            return;
+        }
+        let (frame_width, frame_height) = frame.size();
+        let (width, height) = self.surface.size();
+        let left = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => self.margin,
+            Corner::TopRight | Corner::BottomRight => frame_width - self.margin - width,
+        };
+        let top = match self.corner {
+            Corner::TopLeft | Corner::TopRight => self.margin,
+            Corner::BottomLeft | Corner::BottomRight => frame_height - self.margin - height,
+        };
+        if self.opacity >= 1.0 {
+            frame.draw_surface(&self.surface, left, top);
+        } else {
+            let mut faded = self.surface.clone();
+            let opacity = self.opacity.max(0.0);
+            for y in 0..height {
+                for x in 0..width {
+                    if let Some(color) = faded.get(x, y) {
+                        let alpha = (f32::from(color.alpha) * opacity).round() as u8;
+                        faded.set(x, y, LedColorA { alpha, ..color });
+                    }
+                }
+            }
+            frame.draw_surface(&faded, left, top);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn gray(level: u8) -> LedColor {
+        LedColor {
+            red: level,
+            green: level,
+            blue: level,
+        }
+    }
+
+    #[test]
+    fn blending() {
+        let icon = Icon::from_rows(&["#.", "##"]).unwrap();
+        let watermark = Watermark::icon(&icon, gray(200))
+            .corner(Corner::BottomRight)
+            .margin(1)
+            .opacity(0.5);
+        let mut pixels = vec![gray(100); 16];
+        watermark.apply(&mut Frame::new(&mut pixels, (4, 4)), Instant::now());
+        let levels: Vec<u8> = pixels.iter().map(|color| color.red).collect();
+        #[rustfmt::skip]
+        assert_eq!(levels, [
+            100, 100, 100, 100,
+            100, 150, 100, 100,
+            100, 150, 150, 100,
+            100, 100, 100, 100,
+        ]);
+    }
+
+    #[test]
+    fn schedule() {
+        let watermark = Watermark::icon(&Icon::bulb(), gray(255))
+            .schedule(Duration::from_secs(60), Duration::from_secs(10));
+        let at = |secs| watermark.start + Duration::from_secs(secs);
+        assert!(watermark.is_visible(at(0)));
+        assert!(watermark.is_visible(at(9)));
+        assert!(!watermark.is_visible(at(30)));
+        assert!(watermark.is_visible(at(65)));
+        assert!(Watermark::icon(&Icon::bulb(), gray(255)).is_visible(at(30)));
+    }
+}
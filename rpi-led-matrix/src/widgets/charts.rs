@@ -0,0 +1,374 @@
+//! Micro charts of data series, with axes reduced to fit tiny displays.
+//!
+//! A [`Chart`] draws bar, line or area charts of plain values or of `(x, y)` series such as
+//! timestamps and measurements. The value axis is auto-ranged to [`ticks`] unless a fixed
+//! range is given. Colors come from the [`Theme`]: data in [`ACCENT`](Theme::ACCENT), axes
+//! and ticks in [`MUTED`](Theme::MUTED) and tick labels, if the theme has a font and there's
+//! room for them, in [`FOREGROUND`](Theme::FOREGROUND).
+//!
+//! A [`BarGraph`] draws live values such as audio spectrums as columns with falling peak
+//! markers, without axes.
+//!
+//! ```
+//! use rpi_led_matrix::widgets::charts::{Chart, ChartKind};
+//! use rpi_led_matrix::widgets::SharedTheme;
+//! use rpi_led_matrix::LedMatrix;
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let load = [0.2, 0.4, 1.3, 0.9, 0.7, 2.1, 1.8];
+//! Chart::new(ChartKind::Area, SharedTheme::default()).draw(&mut canvas, &load);
+//! ```
+use std::time::Duration;
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{Align, Canvas, LedColor, Rect, TextCanvas, TextDrawOptions};
+
+/// How a [`Chart`] shows its data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChartKind {
+    /// One bar per value, from zero to the value
+    #[default]
+    Bar,
+    /// Values connected by lines
+    Line,
+    /// A line with the area down to zero filled
+    Area,
+}
+
+/// Picks evenly spaced, round tick values covering `min..=max`, at most about `count` of them.
+///
+/// The ticks are multiples of 1, 2 or 5 times a power of ten. The first tick is at or below
+/// `min`, the last one at or above `max`, so they also serve as an auto-ranged axis.
+///
+/// ```
+/// use rpi_led_matrix::widgets::charts::ticks;
+/// assert_eq!(ticks(0.3, 9.2, 5), [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+/// ```
+#[must_use]
+pub fn ticks(min: f32, max: f32, count: usize) -> Vec<f32> {
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+    let (min, max) = if max - min > f32::EPSILON * min.abs().max(1.0) {
+        (min, max)
+    } else {
+        (min - 1.0, max + 1.0)
+    };
+    let rough = (max - min) / count.max(1) as f32;
+    let magnitude = 10_f32.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&step| step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+    (first..=last).map(|i| i as f32 * step).collect()
+}
+
+/// A bar, line or area chart.
+pub struct Chart {
+    kind: ChartKind,
+    theme: SharedTheme,
+    range: Option<(f32, f32)>,
+    axes: bool,
+    tick_count: usize,
+}
+
+impl Chart {
+    /// Creates a chart with axes and an auto-ranged value axis of about 3 ticks.
+    #[must_use]
+    pub const fn new(kind: ChartKind, theme: SharedTheme) -> Self {
+        Self {
+            kind,
+            theme,
+            range: None,
+            axes: true,
+            tick_count: 3,
+        }
+    }
+
+    /// Fixes the value axis to `min..=max` instead of fitting it to the data
+    #[must_use]
+    pub const fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets if the axes, ticks and tick labels are drawn
+    #[must_use]
+    pub const fn axes(mut self, enable: bool) -> Self {
+        self.axes = enable;
+        self
+    }
+
+    /// Sets about how many ticks the value axis has
+    #[must_use]
+    pub const fn tick_count(mut self, count: usize) -> Self {
+        self.tick_count = count;
+        self
+    }
+
+    /// Draws `values` evenly spread over the whole canvas.
+    pub fn draw(&self, canvas: &mut impl TextCanvas, values: &[f32]) {
+        let series: Vec<(f32, f32)> = (0..).zip(values).map(|(i, &v)| (i as f32, v)).collect();
+        self.draw_series(canvas, &series);
+    }
+
+    /// Draws a series of `(x, y)` points, e.g. timestamps and measurements, over the whole
+    /// canvas. The points have to be sorted by `x`.
+    pub fn draw_series(&self, canvas: &mut impl TextCanvas, series: &[(f32, f32)]) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        let ticks = self.value_ticks(series);
+        let (low, high) = (ticks[0], ticks[ticks.len() - 1]);
+
+        let plot = if self.axes {
+            Self::draw_axes(canvas, &theme, &ticks, (width, height))
+        } else {
+            Rect::new(0, 0, width, height)
+        };
+        if plot.is_empty() || series.is_empty() {
+            return;
+        }
+        let points = Self::layout(series, plot, (low, high));
+        let zero = Self::value_y(0.0_f32.clamp(low, high), plot, (low, high));
+        let color = theme.color(Theme::ACCENT);
+        match self.kind {
+            ChartKind::Bar => {
+                let count = points.len() as i32;
+                for (i, &(_, y)) in (0..).zip(&points) {
+                    let left = plot.x + i * plot.width / count;
+                    let right = plot.x + (i + 1) * plot.width / count - 1;
+                    // keep a gap between bars that are wide enough
+                    let right = if right - left >= 2 { right - 1 } else { right };
+                    let polygon = [(left, zero), (right, zero), (right, y), (left, y)];
+                    canvas.fill_polygon(&polygon, &color);
+                }
+            }
+            ChartKind::Line => canvas.draw_polyline(&points, 1, &color),
+            ChartKind::Area => {
+                let fill = theme.color(Theme::BACKGROUND).lerp(&color, 0.4);
+                for pair in points.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                    canvas.fill_polygon(&[(x0, zero), (x0, y0), (x1, y1), (x1, zero)], &fill);
+                }
+                canvas.draw_polyline(&points, 1, &color);
+            }
+        }
+    }
+
+    /// Ticks of the value axis, fixed by the range or fitted to the data
+    fn value_ticks(&self, series: &[(f32, f32)]) -> Vec<f32> {
+        let (min, max) = self.range.unwrap_or_else(|| {
+            series
+                .iter()
+                .fold((0.0_f32, 0.0_f32), |(min, max), &(_, y)| {
+                    (min.min(y), max.max(y))
+                })
+        });
+        let ticks = ticks(min, max, self.tick_count);
+        match self.range {
+            // a fixed range isn't widened, only ticks within it are kept
+            Some((min, max)) => {
+                let mut within: Vec<f32> = ticks
+                    .into_iter()
+                    .filter(|t| (min..=max).contains(t))
+                    .collect();
+                within.insert(0, min);
+                within.push(max);
+                within.dedup();
+                within
+            }
+            None => ticks,
+        }
+    }
+
+    /// Draws the axes with ticks and labels, returning the area left for plotting.
+    fn draw_axes(
+        canvas: &mut impl TextCanvas,
+        theme: &Theme,
+        ticks: &[f32],
+        (width, height): (i32, i32),
+    ) -> Rect {
+        let range = (ticks[0], ticks[ticks.len() - 1]);
+        let labels: Vec<String> = ticks.iter().map(|t| format!("{t}")).collect();
+        // labels only if they fit next to each other vertically and leave room to plot
+        let font = theme.font(Theme::DEFAULT_FONT).filter(|font| {
+            let label_height = font.height().unwrap_or_default();
+            label_height * ticks.len() as i32 <= height
+        });
+        let label_width = font.map_or(0, |font| {
+            labels
+                .iter()
+                .map(|l| font.measure(l, 0).0 + 1)
+                .max()
+                .unwrap_or_default()
+        });
+        let label_width = if label_width * 3 > width {
+            0
+        } else {
+            label_width
+        };
+
+        // one column for the value axis and its ticks, one row for the x axis
+        let plot = Rect::new(label_width + 2, 0, width - label_width - 2, height - 1);
+        if plot.is_empty() {
+            return plot;
+        }
+        let axis = theme.color(Theme::MUTED);
+        let foreground = theme.color(Theme::FOREGROUND);
+        canvas.draw_line(label_width + 1, 0, label_width + 1, height - 1, &axis);
+        canvas.draw_line(label_width + 1, height - 1, width - 1, height - 1, &axis);
+        for (tick, label) in ticks.iter().zip(&labels) {
+            let y = Self::value_y(*tick, plot, range);
+            canvas.set(label_width, y, &axis);
+            if let (Some(font), true) = (font, label_width > 0) {
+                let baseline = (y - font.height().unwrap_or_default() / 2 + font.baseline())
+                    .clamp(font.baseline(), height - 1);
+                let options = TextDrawOptions::new()
+                    .position(label_width - 1, baseline)
+                    .align(Align::Right)
+                    .color(&foreground);
+                canvas.draw_text(font, label, &options);
+            }
+        }
+        plot
+    }
+
+    /// Row of `value` within the plot area
+    fn value_y(value: f32, plot: Rect, (low, high): (f32, f32)) -> i32 {
+        let scale = (value - low) / (high - low);
+        plot.y + plot.height - 1 - (scale * (plot.height - 1) as f32).round() as i32
+    }
+
+    /// Maps a series onto pixel coordinates within the plot area.
+    fn layout(series: &[(f32, f32)], plot: Rect, range: (f32, f32)) -> Vec<(i32, i32)> {
+        let first = series.first().map_or(0.0, |p| p.0);
+        let last = series.last().map_or(0.0, |p| p.0);
+        let span = if last > first { last - first } else { 1.0 };
+        series
+            .iter()
+            .map(|&(x, y)| {
+                let column = ((x - first) / span * (plot.width - 1) as f32).round() as i32;
+                let y = Self::value_y(y.clamp(range.0, range.1), plot, range);
+                (plot.x + column, y)
+            })
+            .collect()
+    }
+}
+
+/// Columns of live values with peak-hold markers, e.g. for audio spectrums or metrics.
+///
+/// The [`BarGraph`](rpi_led_matrix_core::widgets::BarGraph) of the core crate in the colors of
+/// the theme: bars are drawn bottom up, centered horizontally, in the
+/// [`ACCENT`](Theme::ACCENT) color or colored by height along a gradient. The peak of every
+/// bar is held for a while and then falls, drawn as a marker row in the
+/// [`FOREGROUND`](Theme::FOREGROUND) color.
+///
+/// ```
+/// use rpi_led_matrix::widgets::charts::BarGraph;
+/// use rpi_led_matrix::widgets::SharedTheme;
+/// use rpi_led_matrix::{LedColor, LedMatrix};
+/// use std::time::Duration;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let green = LedColor { red: 0, green: 255, blue: 0 };
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// let mut spectrum = BarGraph::new(SharedTheme::default())
+///     .bar_width(3)
+///     .gap(1)
+///     .gradient(&[green, green, red]);
+/// let bands = [0.9, 0.7, 0.8, 0.4, 0.3, 0.5, 0.2, 0.1];
+/// spectrum.draw(&mut canvas, &bands, Duration::from_millis(40));
+/// ```
+pub struct BarGraph {
+    theme: SharedTheme,
+    graph: rpi_led_matrix_core::widgets::BarGraph,
+}
+
+impl BarGraph {
+    /// Creates a graph of values from 0 to 1, with bars 2 pixels wide and 1 pixel apart, and
+    /// peaks held for half a second before falling by the whole height in a second.
+    #[must_use]
+    pub const fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            graph: rpi_led_matrix_core::widgets::BarGraph::new(),
+        }
+    }
+
+    /// Sets the values of an empty and a full bar
+    #[must_use]
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.graph = self.graph.range(min, max);
+        self
+    }
+
+    /// Sets the width of the bars in pixels
+    #[must_use]
+    pub fn bar_width(mut self, width: i32) -> Self {
+        self.graph = self.graph.bar_width(width);
+        self
+    }
+
+    /// Sets the pixels between bars
+    #[must_use]
+    pub fn gap(mut self, gap: i32) -> Self {
+        self.graph = self.graph.gap(gap);
+        self
+    }
+
+    /// Colors the bars by height, blending evenly spaced colors from the bottom to the top.
+    /// Without colors, bars are drawn in the accent color
+    #[must_use]
+    pub fn gradient(mut self, colors: &[LedColor]) -> Self {
+        self.graph = self.graph.gradient(colors);
+        self
+    }
+
+    /// Sets how long peaks are held, and how fast they fall after, in heights per second.
+    /// A fall speed of 0 disables the peak markers
+    #[must_use]
+    pub fn peak_hold(mut self, hold: Duration, fall: f32) -> Self {
+        self.graph = self.graph.peak_hold(hold, fall);
+        self
+    }
+
+    /// Draws `values` as bars over the whole canvas.
+    ///
+    /// `elapsed` is the time the graph has been on screen, as passed to
+    /// [`Scene::render`](crate::Scene::render), which moves the peak markers.
+    pub fn draw(&mut self, canvas: &mut impl Canvas, values: &[f32], elapsed: Duration) {
+        let theme = self.theme.current();
+        // the theme may have changed since the last frame
+        self.graph = std::mem::take(&mut self.graph)
+            .color(theme.color(Theme::ACCENT))
+            .peak_color(theme.color(Theme::FOREGROUND));
+        self.graph.draw(canvas, values, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_values() {
+        assert_eq!(ticks(0.0, 100.0, 4), [0.0, 50.0, 100.0]);
+        assert_eq!(ticks(-3.0, 3.0, 3), [-4.0, -2.0, 0.0, 2.0, 4.0]);
+        assert_eq!(ticks(0.5, 3.5, 3), [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(ticks(5.0, 5.0, 3), [4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn fixed_range() {
+        let chart = Chart::new(ChartKind::Line, SharedTheme::default()).range(0.0, 7.0);
+        assert_eq!(chart.value_ticks(&[(0.0, 100.0)]), [0.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn layout() {
+        let plot = Rect::new(2, 0, 10, 11);
+        let points = Chart::layout(&[(0.0, 0.0), (5.0, 5.0), (10.0, 20.0)], plot, (0.0, 10.0));
+        assert_eq!(points, [(2, 10), (7, 5), (11, 0)]);
+    }
+}
@@ -0,0 +1,812 @@
+//! Clocks showing the local time, all usable as [`Scene`].
+//!
+//! [`Clock`] draws the time digitally in one of several [`ClockFace`] layouts, picking the
+//! richest one that fits the canvas unless told otherwise. Colors and fonts come from the
+//! [`Theme`]: the time is drawn with [`LARGE_FONT`](Clock::LARGE_FONT) in the
+//! [`ACCENT`](Theme::ACCENT) color, everything else with the default font.
+//!
+//! [`AnalogClock`] draws a round face with hands, and for dedicated novelty builds there's the
+//! [`BinaryClock`] and the [`WordClock`]. All clocks show the local time unless given a
+//! [`timezone`](Clock::timezone).
+//!
+//! ```
+//! use rpi_led_matrix::widgets::clock::{Clock, ClockFace, Locale};
+//! use rpi_led_matrix::widgets::{SharedTheme, Theme};
+//! use rpi_led_matrix::{LedMatrix, Scene};
+//! use std::time::Duration;
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let mut clock = Clock::new(SharedTheme::new(Theme::day())).locale(Locale::German);
+//! clock.render(&mut canvas, Duration::ZERO);
+//! ```
+use std::f32::consts::TAU;
+use std::fmt::Write;
+use std::time::Duration;
+
+use chrono::{Datelike, FixedOffset, Local, NaiveDateTime, Timelike, Utc};
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{Align, Canvas, LedCanvas, LedColor, LedFont, Scene, TextCanvas, TextDrawOptions};
+
+/// Layout of a [`Clock`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClockFace {
+    /// `HH:MM` in the large font
+    Large,
+    /// `HH:MM:SS`
+    Seconds,
+    /// The date above `HH:MM`
+    Stacked,
+    /// `HH:MM` above the ISO week number
+    WeekNumber,
+}
+
+/// Language of day and month names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// English, e.g. "Mon Oct 14"
+    #[default]
+    English,
+    /// German, e.g. "Mo 14. Okt"
+    German,
+    /// French, e.g. "lun 14 oct"
+    French,
+    /// Spanish, e.g. "lun 14 oct"
+    Spanish,
+    /// Italian, e.g. "lun 14 ott"
+    Italian,
+    /// Dutch, e.g. "ma 14 okt"
+    Dutch,
+}
+
+impl Locale {
+    /// Abbreviated names of the days, starting on Monday
+    const fn weekdays(self) -> [&'static str; 7] {
+        match self {
+            Self::English => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            Self::German => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+            Self::French => ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"],
+            Self::Spanish => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+            Self::Italian => ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+            Self::Dutch => ["ma", "di", "wo", "do", "vr", "za", "zo"],
+        }
+    }
+
+    /// Abbreviated names of the months, starting with January
+    const fn months(self) -> [&'static str; 12] {
+        match self {
+            Self::English => [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            Self::German => [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ],
+            Self::French => [
+                "jan", "fév", "mar", "avr", "mai", "jun", "jul", "aoû", "sep", "oct", "nov", "déc",
+            ],
+            Self::Spanish => [
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+            ],
+            Self::Italian => [
+                "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+            ],
+            Self::Dutch => [
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+        }
+    }
+
+    /// Formats the date in the local order, e.g. "Mon Oct 14" or "Mo 14. Okt".
+    #[must_use]
+    pub fn date(self, time: &NaiveDateTime) -> String {
+        let weekday = self.weekdays()[time.weekday().num_days_from_monday() as usize];
+        let month = self.months()[time.month0() as usize];
+        let day = time.day();
+        match self {
+            Self::English => format!("{weekday} {month} {day}"),
+            Self::German => format!("{weekday} {day}. {month}"),
+            Self::French | Self::Spanish | Self::Italian | Self::Dutch => {
+                format!("{weekday} {day} {month}")
+            }
+        }
+    }
+
+    /// Formats the ISO week number, e.g. "Week 42" or "KW 42".
+    #[must_use]
+    pub fn week(self, time: &NaiveDateTime) -> String {
+        let week = time.iso_week().week();
+        match self {
+            Self::English => format!("Week {week}"),
+            Self::German => format!("KW {week}"),
+            Self::French => format!("Sem. {week}"),
+            Self::Spanish => format!("Sem. {week}"),
+            Self::Italian => format!("Sett. {week}"),
+            Self::Dutch => format!("Week {week}"),
+        }
+    }
+}
+
+/// The current time at `offset` from UTC, or the local time without one.
+fn now(offset: Option<FixedOffset>) -> NaiveDateTime {
+    offset.map_or_else(
+        || Local::now().naive_local(),
+        |offset| Utc::now().with_timezone(&offset).naive_local(),
+    )
+}
+
+/// A digital clock, usable as [`Scene`] showing the local time.
+pub struct Clock {
+    theme: SharedTheme,
+    face: Option<ClockFace>,
+    locale: Locale,
+    format: Option<String>,
+    offset: Option<FixedOffset>,
+}
+
+impl Clock {
+    /// Name of the theme font used for the time, falling back to the default font
+    pub const LARGE_FONT: &'static str = "large";
+
+    /// Creates a clock in English choosing its face by the size of the canvas.
+    #[must_use]
+    pub fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            face: None,
+            locale: Locale::default(),
+            format: None,
+            offset: None,
+        }
+    }
+
+    /// Always uses the given face instead of choosing one by size
+    #[must_use]
+    pub const fn face(mut self, face: ClockFace) -> Self {
+        self.face = Some(face);
+        self
+    }
+
+    /// Sets the language of day and month names
+    #[must_use]
+    pub const fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Formats the time with a [`chrono` format string](chrono::format::strftime), e.g.
+    /// `"%l:%M %p"` for a 12-hour clock, instead of `HH:MM` and `HH:MM:SS`
+    #[must_use]
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// Shows the time at a fixed offset from UTC instead of the local time.
+    ///
+    /// The offset doesn't follow daylight saving time, the shown time is an hour off while a
+    /// zone observing it is on summer time unless the offset is changed with it. Leave it
+    /// unset to follow the system's local time zone, which includes its DST rules.
+    #[must_use]
+    pub const fn timezone(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The lines of text shown by `face` at `time`, each with the theme font it's drawn in.
+    ///
+    /// An invalid [`format`](Self::format) string shows the time as empty text.
+    #[must_use]
+    pub fn lines(&self, face: ClockFace, time: &NaiveDateTime) -> Vec<(&'static str, String)> {
+        let formatted = |format: &str| {
+            let mut text = String::new();
+            if write!(text, "{}", time.format(format)).is_err() {
+                text.clear();
+            }
+            text
+        };
+        let hh_mm = self.format.as_deref().map_or_else(
+            || format!("{:02}:{:02}", time.hour(), time.minute()),
+            formatted,
+        );
+        match face {
+            ClockFace::Large => vec![(Self::LARGE_FONT, hh_mm)],
+            ClockFace::Seconds => {
+                let text = self
+                    .format
+                    .as_deref()
+                    .map_or_else(|| format!("{hh_mm}:{:02}", time.second()), formatted);
+                vec![(Theme::DEFAULT_FONT, text)]
+            }
+            ClockFace::Stacked => vec![
+                (Theme::DEFAULT_FONT, self.locale.date(time)),
+                (Self::LARGE_FONT, hh_mm),
+            ],
+            ClockFace::WeekNumber => vec![
+                (Self::LARGE_FONT, hh_mm),
+                (Theme::DEFAULT_FONT, self.locale.week(time)),
+            ],
+        }
+    }
+
+    /// Picks the configured face, or else the richest one fitting into `size` with the fonts of
+    /// `theme`: [`Stacked`](ClockFace::Stacked), then [`Seconds`](ClockFace::Seconds), then
+    /// [`Large`](ClockFace::Large).
+    #[must_use]
+    pub fn choose_face(&self, theme: &Theme, size: (i32, i32), time: &NaiveDateTime) -> ClockFace {
+        if let Some(face) = self.face {
+            return face;
+        }
+        [ClockFace::Stacked, ClockFace::Seconds]
+            .into_iter()
+            .find(|&face| {
+                let (width, height) = Self::measure(theme, &self.lines(face, time));
+                width <= size.0 && height <= size.1
+            })
+            .unwrap_or(ClockFace::Large)
+    }
+
+    /// Size of the given lines when drawn with the fonts of `theme`
+    fn measure(theme: &Theme, lines: &[(&str, String)]) -> (i32, i32) {
+        let (width, height) = lines.iter().fold((0, 0), |(width, height), (font, text)| {
+            theme.font(font).map_or((width, height), |font| {
+                (
+                    width.max(font.advance(text, 0)),
+                    height + font.height().unwrap_or_default() + theme.line_spacing(),
+                )
+            })
+        });
+        (width, (height - theme.line_spacing()).max(0))
+    }
+
+    /// Draws the clock showing `time`, centered on the canvas.
+    pub fn render_at(&self, canvas: &mut impl TextCanvas, time: &NaiveDateTime) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+
+        let face = self.choose_face(&theme, (width, height), time);
+        let lines = self.lines(face, time);
+        let (_, text_height) = Self::measure(&theme, &lines);
+        let mut top = (height - text_height) / 2;
+        for (font_name, text) in &lines {
+            let Some(font) = theme.font(font_name) else {
+                continue;
+            };
+            let color = if *font_name == Self::LARGE_FONT {
+                theme.color(Theme::ACCENT)
+            } else {
+                theme.color(Theme::FOREGROUND)
+            };
+            draw_centered(canvas, font, text, top, &color);
+            top += font.height().unwrap_or_default() + theme.line_spacing();
+        }
+    }
+}
+
+impl Scene for Clock {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        self.render_at(canvas, &now(self.offset));
+    }
+}
+
+/// An analog clock with hour ticks and hands, as large as fits the canvas.
+///
+/// The rim is drawn in the [`MUTED`](Theme::MUTED) color, the ticks and the hour and minute
+/// hands in the [`FOREGROUND`](Theme::FOREGROUND) color and the second hand in the
+/// [`ACCENT`](Theme::ACCENT) color.
+///
+/// ```
+/// use chrono::FixedOffset;
+/// use rpi_led_matrix::widgets::clock::AnalogClock;
+/// use rpi_led_matrix::widgets::{SharedTheme, Theme};
+/// use rpi_led_matrix::{LedMatrix, Scene};
+/// use std::time::Duration;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+/// let mut clock = AnalogClock::new(SharedTheme::new(Theme::night())).timezone(tokyo);
+/// clock.render(&mut canvas, Duration::ZERO);
+/// ```
+pub struct AnalogClock {
+    theme: SharedTheme,
+    seconds: bool,
+    offset: Option<FixedOffset>,
+}
+
+impl AnalogClock {
+    /// Creates an analog clock with a second hand, showing the local time.
+    #[must_use]
+    pub const fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            seconds: true,
+            offset: None,
+        }
+    }
+
+    /// Sets if the second hand is shown
+    #[must_use]
+    pub const fn seconds(mut self, enable: bool) -> Self {
+        self.seconds = enable;
+        self
+    }
+
+    /// Shows the time at a fixed offset from UTC instead of the local time.
+    ///
+    /// The offset doesn't follow daylight saving time, the shown time is an hour off while a
+    /// zone observing it is on summer time unless the offset is changed with it. Leave it
+    /// unset to follow the system's local time zone, which includes its DST rules.
+    #[must_use]
+    pub const fn timezone(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The positions of the hour, minute and second hands at `time`, as fractions of a turn
+    /// clockwise from twelve o'clock.
+    #[must_use]
+    pub fn hands(time: &NaiveDateTime) -> [f32; 3] {
+        let seconds = time.second() as f32 / 60.0;
+        let minutes = (time.minute() as f32 + seconds) / 60.0;
+        let hours = ((time.hour() % 12) as f32 + minutes) / 12.0;
+        [hours, minutes, seconds]
+    }
+
+    /// The point `length` pixels from `center` in the direction of `turn`.
+    fn point(center: (f32, f32), length: f32, turn: f32) -> (i32, i32) {
+        let (sin, cos) = (turn * TAU).sin_cos();
+        (
+            (center.0 + length * sin).round() as i32,
+            (center.1 - length * cos).round() as i32,
+        )
+    }
+
+    /// Draws the clock showing `time`, centered on the canvas.
+    pub fn render_at(&self, canvas: &mut impl Canvas, time: &NaiveDateTime) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+        let radius = (width.min(height) - 1) / 2;
+        if radius < 2 {
+            return;
+        }
+        let (cx, cy) = ((width - 1) / 2, (height - 1) / 2);
+        let center = (cx as f32, cy as f32);
+        let radius_f = radius as f32;
+        let foreground = theme.color(Theme::FOREGROUND);
+        canvas.draw_circle(cx, cy, radius as u32, &theme.color(Theme::MUTED));
+        for hour in 0..12 {
+            let turn = hour as f32 / 12.0;
+            let tick = if hour % 3 == 0 { 0.2 } else { 0.1 };
+            let (x0, y0) = Self::point(center, radius_f * (1.0 - tick), turn);
+            let (x1, y1) = Self::point(center, radius_f, turn);
+            canvas.draw_line(x0, y0, x1, y1, &foreground);
+        }
+        let [hours, minutes, seconds] = Self::hands(time);
+        let mut hands = vec![(hours, 0.5, foreground), (minutes, 0.8, foreground)];
+        if self.seconds {
+            hands.push((seconds, 0.9, theme.color(Theme::ACCENT)));
+        }
+        for (turn, length, color) in hands {
+            let (x, y) = Self::point(center, radius_f * length, turn);
+            canvas.draw_line(cx, cy, x, y, &color);
+        }
+    }
+}
+
+impl Scene for AnalogClock {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        self.render_at(canvas, &now(self.offset));
+    }
+}
+
+/// A binary clock: one column per digit of `HH:MM:SS`, lighting the bits of each digit
+/// from 8 at the top to 1 at the bottom.
+///
+/// Set bits are drawn in the [`ACCENT`](Theme::ACCENT) color, unset ones in the
+/// [`MUTED`](Theme::MUTED) color, with [`padding`](Theme::padding) between the cells.
+pub struct BinaryClock {
+    theme: SharedTheme,
+    seconds: bool,
+    offset: Option<FixedOffset>,
+}
+
+impl BinaryClock {
+    /// Creates a binary clock showing hours, minutes and seconds.
+    #[must_use]
+    pub const fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            seconds: true,
+            offset: None,
+        }
+    }
+
+    /// Sets if the seconds columns are shown
+    #[must_use]
+    pub const fn seconds(mut self, enable: bool) -> Self {
+        self.seconds = enable;
+        self
+    }
+
+    /// Shows the time at a fixed offset from UTC instead of the local time.
+    ///
+    /// The offset doesn't follow daylight saving time, the shown time is an hour off while a
+    /// zone observing it is on summer time unless the offset is changed with it. Leave it
+    /// unset to follow the system's local time zone, which includes its DST rules.
+    #[must_use]
+    pub const fn timezone(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The digits shown at `time`, one per column.
+    #[must_use]
+    pub fn digits(&self, time: &NaiveDateTime) -> Vec<u32> {
+        let mut units = vec![time.hour(), time.minute()];
+        if self.seconds {
+            units.push(time.second());
+        }
+        units.into_iter().flat_map(|u| [u / 10, u % 10]).collect()
+    }
+
+    /// Draws the clock showing `time`, centered on the canvas.
+    pub fn render_at(&self, canvas: &mut impl Canvas, time: &NaiveDateTime) {
+        const BITS: i32 = 4;
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+
+        let digits = self.digits(time);
+        let columns = digits.len() as i32;
+        let padding = theme.padding();
+        let cell = ((width + padding) / columns).min((height + padding) / BITS) - padding;
+        if cell <= 0 {
+            return;
+        }
+        let left = (width - columns * (cell + padding) + padding) / 2;
+        let top = (height - BITS * (cell + padding) + padding) / 2;
+        let (on, off) = (theme.color(Theme::ACCENT), theme.color(Theme::MUTED));
+        for (column, digit) in (0..).zip(digits) {
+            for bit in 0..BITS {
+                let color = if digit & (1 << (BITS - 1 - bit)) == 0 {
+                    &off
+                } else {
+                    &on
+                };
+                let x = left + column * (cell + padding);
+                let y = top + bit * (cell + padding);
+                canvas.view(x, y, cell, cell).fill(color);
+            }
+        }
+    }
+}
+
+impl Scene for BinaryClock {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        self.render_at(canvas, &now(self.offset));
+    }
+}
+
+/// Words to light on a [`WordClock`], split into the part before the hour and the hour part.
+pub type Phrase = (Vec<&'static str>, Vec<&'static str>);
+
+/// Letter grid of a [`WordClock`] and the phrasing of times on it.
+///
+/// Words of a phrase are looked up in reading order, each after the previous one and within a
+/// single row. Hour words are looked up no earlier than the hour row, so the same word can
+/// appear both as minutes and as hour, as "FIVE" does in "FIVE PAST FIVE".
+#[derive(Clone, Debug)]
+pub struct WordLayout {
+    rows: Vec<Vec<char>>,
+    hour_row: usize,
+    phrase: fn(u32, u32) -> Phrase,
+}
+
+impl WordLayout {
+    /// Creates a layout from its rows of letters, the row the hour words start in and a
+    /// function phrasing hour (0-23) and minute.
+    #[must_use]
+    pub fn new(rows: &[&str], hour_row: usize, phrase: fn(u32, u32) -> Phrase) -> Self {
+        Self {
+            rows: rows.iter().map(|row| row.chars().collect()).collect(),
+            hour_row,
+            phrase,
+        }
+    }
+
+    /// The classic 11x10 English layout, in five minute steps
+    #[must_use]
+    pub fn english() -> Self {
+        Self::new(
+            &[
+                "ITLISASAMPM",
+                "ACQUARTERDC",
+                "TWENTYFIVEX",
+                "HALFSTENFTO",
+                "PASTERUNINE",
+                "ONESIXTHREE",
+                "FOURFIVETWO",
+                "EIGHTELEVEN",
+                "SEVENTWELVE",
+                "TENSEOCLOCK",
+            ],
+            4,
+            Self::english_phrase,
+        )
+    }
+
+    fn english_phrase(hour: u32, minute: u32) -> Phrase {
+        const HOURS: [&str; 12] = [
+            "TWELVE", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+            "TEN", "ELEVEN",
+        ];
+        let minute = minute / 5 * 5;
+        let mut before = vec!["IT", "IS"];
+        before.extend_from_slice(match minute {
+            5 => &["FIVE", "PAST"][..],
+            10 => &["TEN", "PAST"],
+            15 => &["A", "QUARTER", "PAST"],
+            20 => &["TWENTY", "PAST"],
+            25 => &["TWENTY", "FIVE", "PAST"],
+            30 => &["HALF", "PAST"],
+            35 => &["TWENTY", "FIVE", "TO"],
+            40 => &["TWENTY", "TO"],
+            45 => &["A", "QUARTER", "TO"],
+            50 => &["TEN", "TO"],
+            55 => &["FIVE", "TO"],
+            _ => &[],
+        });
+        let hour = if minute > 30 { hour + 1 } else { hour };
+        let mut hours = vec![HOURS[hour as usize % 12]];
+        if minute == 0 {
+            hours.push("OCLOCK");
+        }
+        (before, hours)
+    }
+
+    /// Number of columns and rows of the grid
+    #[must_use]
+    pub fn size(&self) -> (usize, usize) {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or_default();
+        (columns, self.rows.len())
+    }
+
+    /// Finds `word` in reading order at or after the position `from`, returning where it starts.
+    fn find(&self, word: &str, from: (usize, usize)) -> Option<(usize, usize)> {
+        let word: Vec<char> = word.chars().collect();
+        (from.1..self.rows.len()).find_map(|row| {
+            let start = if row == from.1 { from.0 } else { 0 };
+            let letters = self.rows[row].get(start..)?;
+            letters
+                .windows(word.len())
+                .position(|w| w == word.as_slice())
+                .map(|column| (start + column, row))
+        })
+    }
+
+    /// The positions (column, row) of all letters lit at the given hour (0-23) and minute.
+    ///
+    /// # Errors
+    /// If the layout lacks a word of the phrase.
+    pub fn lit(&self, hour: u32, minute: u32) -> Result<Vec<(usize, usize)>, &'static str> {
+        let (before, hours) = (self.phrase)(hour, minute);
+        let mut lit = Vec::new();
+        let mut position = (0, 0);
+        for (index, word) in before.iter().chain(&hours).enumerate() {
+            if index == before.len() && position.1 < self.hour_row {
+                position = (0, self.hour_row);
+            }
+            let (column, row) = self
+                .find(word, position)
+                .ok_or("Word missing in word clock layout")?;
+            let length = word.chars().count();
+            lit.extend((column..column + length).map(|c| (c, row)));
+            position = (column + length, row);
+        }
+        Ok(lit)
+    }
+}
+
+/// A word clock spelling out the time on a grid of letters.
+///
+/// Needs a theme font to draw the letters with: lit letters are drawn in the
+/// [`ACCENT`](Theme::ACCENT) color, the others in the [`MUTED`](Theme::MUTED) color.
+pub struct WordClock {
+    theme: SharedTheme,
+    layout: WordLayout,
+    offset: Option<FixedOffset>,
+}
+
+impl WordClock {
+    /// Creates a word clock with the given letter layout.
+    #[must_use]
+    pub const fn new(theme: SharedTheme, layout: WordLayout) -> Self {
+        Self {
+            theme,
+            layout,
+            offset: None,
+        }
+    }
+
+    /// Shows the time at a fixed offset from UTC instead of the local time.
+    ///
+    /// The offset doesn't follow daylight saving time, the shown time is an hour off while a
+    /// zone observing it is on summer time unless the offset is changed with it. Leave it
+    /// unset to follow the system's local time zone, which includes its DST rules.
+    #[must_use]
+    pub const fn timezone(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Draws the clock showing `time`, centered on the canvas.
+    pub fn render_at(&self, canvas: &mut impl TextCanvas, time: &NaiveDateTime) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+        let Some(font) = theme.font(Theme::DEFAULT_FONT) else {
+            return;
+        };
+        let lit = self
+            .layout
+            .lit(time.hour(), time.minute())
+            .unwrap_or_default();
+
+        let (columns, rows) = self.layout.size();
+        let cell_width = width / columns.max(1) as i32;
+        let cell_height = height / rows.max(1) as i32;
+        let left = (width - cell_width * columns as i32) / 2;
+        let top = (height - cell_height * rows as i32) / 2;
+        let (on, off) = (theme.color(Theme::ACCENT), theme.color(Theme::MUTED));
+        for (row, letters) in self.layout.rows.iter().enumerate() {
+            for (column, letter) in letters.iter().enumerate() {
+                let color = if lit.contains(&(column, row)) {
+                    &on
+                } else {
+                    &off
+                };
+                let letter = letter.to_string();
+                let x = left + column as i32 * cell_width;
+                let y = top + row as i32 * cell_height;
+                let options = TextDrawOptions::new()
+                    .position(
+                        x + (cell_width - font.advance(&letter, 0)) / 2,
+                        y + (cell_height - font.height().unwrap_or_default()) / 2 + font.baseline(),
+                    )
+                    .color(color);
+                canvas.draw_text(font, &letter, &options);
+            }
+        }
+    }
+}
+
+impl Scene for WordClock {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        self.render_at(canvas, &now(self.offset));
+    }
+}
+
+/// Draws a line of text horizontally centered, with its top at `top`.
+fn draw_centered(
+    canvas: &mut impl TextCanvas,
+    font: &LedFont,
+    text: &str,
+    top: i32,
+    color: &LedColor,
+) {
+    let (width, _) = canvas.canvas_size();
+    let options = TextDrawOptions::new()
+        .position(width / 2, top + font.baseline())
+        .align(Align::Center)
+        .color(color);
+    canvas.draw_text(font, text, &options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::test::TINY_BDF;
+    use chrono::NaiveDate;
+    use std::sync::Arc;
+
+    fn time() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 10, 14)
+            .unwrap()
+            .and_hms_opt(9, 5, 7)
+            .unwrap()
+    }
+
+    #[test]
+    fn localized() {
+        let time = time();
+        assert_eq!(Locale::English.date(&time), "Mon Oct 14");
+        assert_eq!(Locale::German.date(&time), "Mo 14. Okt");
+        assert_eq!(Locale::French.date(&time), "lun 14 oct");
+        assert_eq!(Locale::German.week(&time), "KW 42");
+    }
+
+    #[test]
+    fn faces() {
+        let clock = Clock::new(SharedTheme::default());
+        let text = |face| -> Vec<String> {
+            clock
+                .lines(face, &time())
+                .into_iter()
+                .map(|(_, t)| t)
+                .collect()
+        };
+        assert_eq!(text(ClockFace::Large), ["09:05"]);
+        assert_eq!(text(ClockFace::Seconds), ["09:05:07"]);
+        assert_eq!(text(ClockFace::Stacked), ["Mon Oct 14", "09:05"]);
+        assert_eq!(text(ClockFace::WeekNumber), ["09:05", "Week 42"]);
+
+        let clock = Clock::new(SharedTheme::default()).format("%l:%M %p");
+        let lines = clock.lines(ClockFace::Stacked, &time());
+        assert_eq!(lines[1].1, " 9:05 AM");
+        let clock = Clock::new(SharedTheme::default()).format("%Q");
+        assert_eq!(clock.lines(ClockFace::Seconds, &time())[0].1, "");
+    }
+
+    #[test]
+    fn analog() {
+        let [hours, minutes, seconds] = AnalogClock::hands(&time());
+        // 9:05:07 is a bit past three quarters of a turn on the hour hand
+        assert!((hours - (9.0 + (5.0 + 7.0 / 60.0) / 60.0) / 12.0).abs() < 1e-6);
+        assert!((minutes - (5.0 + 7.0 / 60.0) / 60.0).abs() < 1e-6);
+        assert!((seconds - 7.0 / 60.0).abs() < 1e-6);
+        // twelve, three and six o'clock
+        assert_eq!(AnalogClock::point((10.0, 10.0), 5.0, 0.0), (10, 5));
+        assert_eq!(AnalogClock::point((10.0, 10.0), 5.0, 0.25), (15, 10));
+        assert_eq!(AnalogClock::point((10.0, 10.0), 5.0, 0.5), (10, 15));
+    }
+
+    #[test]
+    fn binary() {
+        let clock = BinaryClock::new(SharedTheme::default());
+        assert_eq!(clock.digits(&time()), [0, 9, 0, 5, 0, 7]);
+        assert_eq!(clock.seconds(false).digits(&time()), [0, 9, 0, 5]);
+    }
+
+    #[test]
+    fn words() {
+        let layout = WordLayout::english();
+        assert_eq!(layout.size(), (11, 10));
+        let text = |hour, minute| -> String {
+            let lit = layout.lit(hour, minute).unwrap();
+            lit.iter().map(|&(c, r)| layout.rows[r][c]).collect()
+        };
+        assert_eq!(text(9, 7), "ITISFIVEPASTNINE");
+        assert_eq!(text(17, 0), "ITISFIVEOCLOCK");
+        assert_eq!(text(4, 35), "ITISTWENTYFIVETOFIVE");
+        assert_eq!(text(23, 45), "ITISAQUARTERTOTWELVE");
+        // the hour "FIVE" is in the hour rows, not the minutes
+        assert!(layout.lit(17, 0).unwrap().iter().all(|&(_, r)| r != 2));
+
+        let broken = WordLayout::new(&["ITIS"], 0, WordLayout::english_phrase);
+        assert!(broken.lit(9, 0).is_err());
+    }
+
+    #[test]
+    fn auto_face() {
+        // 4 pixels per character, 6 pixels per line plus 1 pixel line spacing in between
+        let font = Arc::new(LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap());
+        let theme = Theme::day().with_font(Theme::DEFAULT_FONT, font);
+        let clock = Clock::new(SharedTheme::new(theme.clone()));
+        let time = time();
+        assert_eq!(
+            clock.choose_face(&theme, (64, 32), &time),
+            ClockFace::Stacked
+        );
+        assert_eq!(
+            clock.choose_face(&theme, (64, 8), &time),
+            ClockFace::Seconds
+        );
+        assert_eq!(clock.choose_face(&theme, (20, 8), &time), ClockFace::Large);
+        let clock = clock.face(ClockFace::WeekNumber);
+        assert_eq!(
+            clock.choose_face(&theme, (20, 8), &time),
+            ClockFace::WeekNumber
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Mirrors a dashboard rendered to an image, e.g. by the Grafana render API.
+use std::time::{Duration, Instant};
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{Canvas, ImageFetcher, LedCanvas, LedImage, Scene};
+
+/// Periodically downloads an image of a dashboard and shows it scaled to the canvas.
+///
+/// The image is fitted to the canvas keeping its aspect ratio, centered on the
+/// [`BACKGROUND`](Theme::BACKGROUND) color. If the last successful download is older than the
+/// staleness limit, a dot in the top right corner shows it in the [`WARNING`](Theme::WARNING)
+/// color, or in the [`CRITICAL`](Theme::CRITICAL) color if nothing has been downloaded yet.
+///
+/// As a [`Scene`], the image is downloaded while rendering, which blocks for up to the timeout
+/// of the [`ImageFetcher`]. To keep animations running, call [`poll`](Self::poll) from another
+/// thread instead and only [`render_at`](Self::render_at) from the render loop.
+///
+/// ```no_run
+/// use rpi_led_matrix::widgets::dashboard::DashboardMirror;
+/// use rpi_led_matrix::widgets::SharedTheme;
+/// use rpi_led_matrix::LedMatrix;
+/// use std::time::{Duration, Instant};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let url = "http://grafana.local/render/d-solo/abc/ops?panelId=2&width=256&height=128";
+/// let mut mirror = DashboardMirror::new(url, SharedTheme::default())
+///     .interval(Duration::from_secs(60))
+///     .dither(4);
+/// mirror.poll(Instant::now()).unwrap();
+/// mirror.render_at(&mut canvas, Instant::now());
+/// ```
+pub struct DashboardMirror {
+    url: String,
+    theme: SharedTheme,
+    fetcher: ImageFetcher,
+    interval: Duration,
+    stale_after: Duration,
+    dither: Option<u8>,
+    image: Option<LedImage>,
+    /// The image fitted to the last canvas size, as it's rendered far more often than fetched
+    scaled: Option<((i32, i32), LedImage)>,
+    last_attempt: Option<Instant>,
+    last_success: Option<Instant>,
+}
+
+impl DashboardMirror {
+    /// Creates a mirror of the image at `url`, downloaded every 30 seconds and stale after
+    /// 2 minutes.
+    #[must_use]
+    pub fn new(url: &str, theme: SharedTheme) -> Self {
+        Self {
+            url: url.to_string(),
+            theme,
+            fetcher: ImageFetcher::new(),
+            interval: Duration::from_secs(30),
+            stale_after: Duration::from_secs(120),
+            dither: None,
+            image: None,
+            scaled: None,
+            last_attempt: None,
+            last_success: None,
+        }
+    }
+
+    /// Sets the fetcher used for downloading, e.g. with a shorter timeout
+    #[must_use]
+    pub fn fetcher(mut self, fetcher: ImageFetcher) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Sets the time between downloads
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how old the last successful download may get before it's flagged as stale
+    #[must_use]
+    pub const fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Dithers the scaled image down to `bits` bits per channel, see [`LedImage::dither`]
+    #[must_use]
+    pub const fn dither(mut self, bits: u8) -> Self {
+        self.dither = Some(bits);
+        self
+    }
+
+    /// Downloads the image if the interval has passed since the last attempt.
+    ///
+    /// Returns true if a new image was downloaded. A failed download is retried after the
+    /// interval, keeping the previous image.
+    ///
+    /// # Errors
+    /// See [`ImageFetcher::fetch`].
+    pub fn poll(&mut self, now: Instant) -> Result<bool, &'static str> {
+        let due = self
+            .last_attempt
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval);
+        if !due {
+            return Ok(false);
+        }
+        self.last_attempt = Some(now);
+        let image = self.fetcher.fetch(&self.url)?;
+        self.last_success = Some(now);
+        if self.image.as_ref() != Some(&image) {
+            self.image = Some(image);
+            self.scaled = None;
+        }
+        Ok(true)
+    }
+
+    /// Returns true if nothing has been downloaded within the staleness limit.
+    #[must_use]
+    pub fn is_stale(&self, now: Instant) -> bool {
+        self.last_success
+            .is_none_or(|last| now.saturating_duration_since(last) > self.stale_after)
+    }
+
+    /// Draws the last downloaded image and, if it's stale, the staleness indicator.
+    pub fn render_at(&mut self, canvas: &mut impl Canvas, now: Instant) {
+        let theme = self.theme.current();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+        let size = canvas.canvas_size();
+        if let Some(image) = &self.image {
+            let outdated = self
+                .scaled
+                .as_ref()
+                .is_none_or(|(scaled, _)| *scaled != size);
+            if outdated {
+                let fitted = image.fit(size);
+                let fitted = match self.dither {
+                    Some(bits) => fitted.dither(bits),
+                    None => fitted,
+                };
+                self.scaled = Some((size, fitted));
+            }
+        }
+        if let Some((_, scaled)) = &self.scaled {
+            let x = (size.0 - scaled.width() as i32) / 2;
+            let y = (size.1 - scaled.height() as i32) / 2;
+            scaled.draw(canvas, x, y);
+        }
+        if self.is_stale(now) {
+            let color = if self.last_success.is_some() {
+                theme.color(Theme::WARNING)
+            } else {
+                theme.color(Theme::CRITICAL)
+            };
+            canvas.view(size.0 - 2, 0, 2, 2).fill(&color);
+        }
+    }
+}
+
+impl Scene for DashboardMirror {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        let now = Instant::now();
+        // a failed download shows as staleness, the previous image stays up
+        let _ = self.poll(now);
+        self.render_at(canvas, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn poll_schedule() {
+        // a port nobody listens on, so every download fails right away
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let start = Instant::now();
+        let mut mirror = DashboardMirror::new(&url, SharedTheme::default())
+            .interval(Duration::from_secs(10))
+            .stale_after(Duration::from_secs(20));
+        assert!(mirror.poll(start).is_err());
+        assert_eq!(mirror.poll(start + Duration::from_secs(5)), Ok(false));
+        assert!(mirror.poll(start + Duration::from_secs(10)).is_err());
+        assert!(mirror.is_stale(start));
+
+        mirror.last_success = Some(start);
+        assert!(!mirror.is_stale(start + Duration::from_secs(20)));
+        assert!(mirror.is_stale(start + Duration::from_secs(21)));
+    }
+}
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+
+use crate::widgets::Icon;
+use crate::{Align, LedColor, LedFont, Rect, TextCanvas, TextDrawOptions};
+
+/// An element flowed inline by a [`Flow`].
+#[derive(Clone, Debug)]
+pub enum Inline {
+    /// Text, breaking into lines at whitespace
+    Text {
+        /// The text
+        text: String,
+        /// The font the text is drawn in
+        font: Arc<LedFont>,
+        /// The color the text is drawn in
+        color: LedColor,
+    },
+    /// An icon sitting on the baseline
+    Icon {
+        /// The icon
+        icon: Icon,
+        /// The color the icon is drawn in
+        color: LedColor,
+    },
+    /// Empty space of the given width
+    Space(i32),
+    /// Ends the current line
+    Break,
+}
+
+/// Lays out text runs, icons and spacers like a paragraph, wrapping them within a region.
+///
+/// Lines break at whitespace and spacers, so e.g. an icon directly followed by text stays on
+/// one line with it. Space at the start of a line is dropped. Elements on a line share a
+/// baseline, and every line is as tall as its highest ascent and deepest descent. Lines are
+/// aligned within the width of the region and separated by the line spacing.
+///
+/// ```
+/// use rpi_led_matrix::widgets::{Flow, Icon};
+/// use rpi_led_matrix::{Align, LedColor, LedFont, LedMatrix, Rect};
+/// use std::path::Path;
+/// use std::sync::Arc;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let font = Arc::new(LedFont::new(Path::new("/usr/share/fonts/misc/5x7.bdf")).unwrap());
+/// let white = LedColor { red: 255, green: 255, blue: 255 };
+/// let orange = LedColor { red: 255, green: 128, blue: 0 };
+/// let flow = Flow::new()
+///     .icon(Icon::thermometer(), orange)
+///     .space(2)
+///     .text("21.5°C in the kitchen", &font, white)
+///     .align(Align::Center)
+///     .line_spacing(1);
+/// let (width, height) = flow.draw(&mut canvas, Rect::new(0, 0, 64, 32));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Flow {
+    items: Vec<Inline>,
+    align: Align,
+    line_spacing: i32,
+}
+
+/// An unbreakable piece of the flow: a word or an icon.
+struct Piece<'a> {
+    item: &'a Inline,
+    /// The word, for text
+    word: &'a str,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+    /// Space before the piece, where the line may break. Dropped at the start of a line.
+    glue: i32,
+}
+
+/// A laid out line, with the pieces and their horizontal offset from the line start.
+struct Line<'a> {
+    pieces: Vec<(i32, Piece<'a>)>,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+}
+
+impl Flow {
+    /// Creates an empty, left aligned flow.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an element
+    #[must_use]
+    pub fn push(mut self, item: Inline) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Appends text
+    #[must_use]
+    pub fn text(self, text: &str, font: &Arc<LedFont>, color: LedColor) -> Self {
+        self.push(Inline::Text {
+            text: text.to_string(),
+            font: Arc::clone(font),
+            color,
+        })
+    }
+
+    /// Appends an icon
+    #[must_use]
+    pub fn icon(self, icon: Icon, color: LedColor) -> Self {
+        self.push(Inline::Icon { icon, color })
+    }
+
+    /// Appends empty space `width` pixels wide
+    #[must_use]
+    pub fn space(self, width: i32) -> Self {
+        self.push(Inline::Space(width))
+    }
+
+    /// Ends the current line
+    #[must_use]
+    pub fn line_break(self) -> Self {
+        self.push(Inline::Break)
+    }
+
+    /// Sets how lines are aligned within the region
+    #[must_use]
+    pub const fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Sets the pixels between lines
+    #[must_use]
+    pub const fn line_spacing(mut self, line_spacing: i32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// Measures the width of the widest line and the total height when wrapped to `width`.
+    #[must_use]
+    pub fn measure(&self, width: i32) -> (i32, i32) {
+        self.size(&self.layout(width))
+    }
+
+    /// Draws the flow within `region`, returning the size it took up.
+    ///
+    /// Nothing is drawn outside of the region, though the returned size includes lines that
+    /// didn't fit.
+    pub fn draw(&self, canvas: &mut impl TextCanvas, region: Rect) -> (i32, i32) {
+        let lines = self.layout(region.width);
+        let mut canvas = canvas.view(region.x, region.y, region.width, region.height);
+        let mut top = 0;
+        for line in &lines {
+            let start = match self.align {
+                Align::Left => 0,
+                Align::Center => (region.width - line.width) / 2,
+                Align::Right => region.width - line.width,
+            };
+            let baseline = top + line.ascent;
+            for (offset, piece) in &line.pieces {
+                let x = start + offset;
+                match piece.item {
+                    Inline::Text { font, color, .. } => {
+                        let options = TextDrawOptions::new().position(x, baseline).color(color);
+                        canvas.draw_text(font, piece.word, &options);
+                    }
+                    Inline::Icon { icon, color } => {
+                        icon.draw(&mut canvas, x, baseline - piece.ascent, color);
+                    }
+                    // only add glue or end lines, so they never become pieces
+                    Inline::Space(_) | Inline::Break => {}
+                }
+            }
+            top += line.ascent + line.descent + self.line_spacing;
+        }
+        self.size(&lines)
+    }
+
+    /// Width of the widest line and total height of the lines
+    fn size(&self, lines: &[Line]) -> (i32, i32) {
+        let width = lines
+            .iter()
+            .map(|line| line.width)
+            .max()
+            .unwrap_or_default();
+        let heights = lines.iter().map(|line| line.ascent + line.descent);
+        let spacing = self.line_spacing * (lines.len() as i32 - 1).max(0);
+        (width, heights.sum::<i32>() + spacing)
+    }
+
+    /// Splits the elements into unbreakable pieces, with `None` for forced line breaks.
+    fn pieces(&self) -> Vec<Option<Piece<'_>>> {
+        let mut pieces = Vec::new();
+        let mut glue = 0;
+        for item in &self.items {
+            match item {
+                Inline::Text { text, font, .. } => {
+                    let space = font.measure(" ", 0).0;
+                    for (i, word) in text.split(' ').enumerate() {
+                        if i > 0 {
+                            glue += space;
+                        }
+                        if word.is_empty() {
+                            continue;
+                        }
+                        pieces.push(Some(Piece {
+                            item,
+                            word,
+                            width: font.measure(word, 0).0,
+                            ascent: font.baseline(),
+                            descent: font.descent(),
+                            glue: std::mem::take(&mut glue),
+                        }));
+                    }
+                }
+                Inline::Icon { icon, .. } => pieces.push(Some(Piece {
+                    item,
+                    word: "",
+                    width: icon.size().0,
+                    ascent: icon.size().1,
+                    descent: 0,
+                    glue: std::mem::take(&mut glue),
+                })),
+                Inline::Space(width) => glue += width,
+                Inline::Break => {
+                    pieces.push(None);
+                    glue = 0;
+                }
+            }
+        }
+        pieces
+    }
+
+    /// Breaks the pieces into lines no wider than `width`, unless a single piece is wider.
+    fn layout(&self, width: i32) -> Vec<Line<'_>> {
+        let mut lines = Vec::new();
+        let mut line: Vec<Piece> = Vec::new();
+        let mut pieces = self.pieces().into_iter().peekable();
+        while let Some(piece) = pieces.next() {
+            let Some(piece) = piece else {
+                lines.push(Self::line(std::mem::take(&mut line)));
+                continue;
+            };
+            // lines only break at glue, so pieces without any before them stay together
+            let mut run = vec![piece];
+            while let Some(Some(next)) = pieces.peek() {
+                if next.glue > 0 {
+                    break;
+                }
+                run.extend(pieces.next().flatten());
+            }
+            let run_width: i32 = run.iter().map(|p| p.glue + p.width).sum();
+            let line_width: i32 = line.iter().map(|p| p.glue + p.width).sum();
+            let leading_glue = line.first().map_or(0, |p| p.glue);
+            if !line.is_empty() && line_width - leading_glue + run_width > width {
+                lines.push(Self::line(std::mem::take(&mut line)));
+            }
+            line.extend(run);
+        }
+        if !line.is_empty() {
+            lines.push(Self::line(line));
+        }
+        lines
+    }
+
+    /// Positions the pieces of a line, dropping the glue before its first piece.
+    fn line(pieces: Vec<Piece>) -> Line {
+        let mut line = Line {
+            pieces: Vec::with_capacity(pieces.len()),
+            width: 0,
+            ascent: 0,
+            descent: 0,
+        };
+        for piece in pieces {
+            if !line.pieces.is_empty() {
+                line.width += piece.glue;
+            }
+            line.ascent = line.ascent.max(piece.ascent);
+            line.descent = line.descent.max(piece.descent);
+            let offset = line.width;
+            line.width += piece.width;
+            line.pieces.push((offset, piece));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::test::TINY_BDF;
+
+    #[test]
+    fn layout() {
+        // every character is 4 wide, the font 6 high with a baseline of 5
+        let font = Arc::new(LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap());
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        let flow = Flow::new()
+            .icon(Icon::thermometer(), white)
+            .space(2)
+            .text("AA AAA A", &font, white)
+            .line_spacing(1);
+        let lines = flow.layout(20);
+        let widths: Vec<_> = lines.iter().map(|line| line.width).collect();
+        // the icon is 3 wide, then 2 space, "AA", and "AAA" wraps with "A" fitting after it
+        assert_eq!(widths, [13, 20]);
+        assert_eq!((lines[0].ascent, lines[0].descent), (6, 1));
+        assert_eq!(flow.measure(20), (20, 7 + 1 + 6));
+        assert_eq!(flow.measure(100), (37, 7));
+
+        // forced breaks and pieces wider than the line
+        let flow = Flow::new()
+            .text("AAAAAA", &font, white)
+            .line_break()
+            .text("A", &font, white);
+        assert_eq!(flow.measure(8), (24, 12));
+        // text directly after an icon stays with it
+        let flow = Flow::new()
+            .text("AA ", &font, white)
+            .icon(Icon::power(), white)
+            .text("A", &font, white);
+        assert_eq!(flow.layout(12).len(), 2);
+        assert_eq!(flow.layout(12)[1].width, 9);
+        let flow = Flow::new()
+            .text("AA", &font, white)
+            .icon(Icon::power(), white);
+        assert_eq!(flow.layout(12).len(), 1);
+    }
+}
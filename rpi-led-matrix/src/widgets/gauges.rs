@@ -0,0 +1,360 @@
+//! Progress bars and radial gauges showing a single value within a range.
+//!
+//! Both draw over the whole canvas, use a [`viewport`](crate::LedCanvas::viewport) to place
+//! them. Colors come from the [`Theme`]: the unfilled track in [`MUTED`](Theme::MUTED), the
+//! value in [`ACCENT`](Theme::ACCENT) unless a threshold picks another theme color, and
+//! labels in [`FOREGROUND`](Theme::FOREGROUND).
+//!
+//! ```
+//! use rpi_led_matrix::widgets::gauges::{BarStyle, Gauge, ProgressBar};
+//! use rpi_led_matrix::widgets::{SharedTheme, Theme};
+//! use rpi_led_matrix::LedMatrix;
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let theme = SharedTheme::default();
+//! ProgressBar::new(theme.clone())
+//!     .style(BarStyle::Segments(10))
+//!     .draw(&mut canvas.viewport(0, 28, 64, 4), 0.42);
+//! Gauge::new(theme)
+//!     .range(0.0, 100.0)
+//!     .threshold(70.0, Theme::WARNING)
+//!     .threshold(90.0, Theme::CRITICAL)
+//!     .label(0, "%")
+//!     .draw(&mut canvas.viewport(0, 0, 28, 28), 73.0);
+//! ```
+use std::f32::consts::PI;
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{Align, Canvas, LedColor, TextCanvas, TextDrawOptions};
+
+/// Direction a [`ProgressBar`] fills in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// Filling from left to right
+    #[default]
+    Horizontal,
+    /// Filling from bottom to top
+    Vertical,
+}
+
+/// How a [`ProgressBar`] shows its value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BarStyle {
+    /// A continuous bar, the pixel at its end blended by how much of it is covered
+    #[default]
+    Smooth,
+    /// The given number of blocks with a pixel gap in between, lit from the start
+    Segments(u32),
+}
+
+/// A value range with colors taking over from a threshold on.
+#[derive(Clone, Debug)]
+struct Scale {
+    min: f32,
+    max: f32,
+    /// Theme color names by the value they apply from, sorted
+    thresholds: Vec<(f32, &'static str)>,
+}
+
+impl Scale {
+    const fn new() -> Self {
+        Self {
+            min: 0.0,
+            max: 1.0,
+            thresholds: Vec::new(),
+        }
+    }
+
+    fn add_threshold(&mut self, at: f32, color: &'static str) {
+        let index = self.thresholds.partition_point(|&(t, _)| t <= at);
+        self.thresholds.insert(index, (at, color));
+    }
+
+    /// Where `value` lies within the range, from 0 to 1
+    fn fraction(&self, value: f32) -> f32 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// The theme color name of the highest threshold reached by `value`
+    fn color(&self, value: f32) -> &'static str {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|&&(at, _)| value >= at)
+            .map_or(Theme::ACCENT, |&(_, color)| color)
+    }
+}
+
+/// A horizontal or vertical bar filled up to a value.
+pub struct ProgressBar {
+    theme: SharedTheme,
+    orientation: Orientation,
+    style: BarStyle,
+    scale: Scale,
+}
+
+impl ProgressBar {
+    /// Creates a smooth horizontal bar for values from 0 to 1.
+    #[must_use]
+    pub const fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            orientation: Orientation::Horizontal,
+            style: BarStyle::Smooth,
+            scale: Scale::new(),
+        }
+    }
+
+    /// Sets the direction the bar fills in
+    #[must_use]
+    pub const fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets how the bar shows its value
+    #[must_use]
+    pub const fn style(mut self, style: BarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the values of an empty and a full bar
+    #[must_use]
+    pub const fn range(mut self, min: f32, max: f32) -> Self {
+        self.scale.min = min;
+        self.scale.max = max;
+        self
+    }
+
+    /// Draws the filled part in the theme color `color` from the value `at` on
+    #[must_use]
+    pub fn threshold(mut self, at: f32, color: &'static str) -> Self {
+        self.scale.add_threshold(at, color);
+        self
+    }
+
+    /// The covered length in pixels of a bar `length` long for `fraction`, split into the
+    /// fully covered pixels and the coverage of the one after them.
+    fn fill(fraction: f32, length: i32) -> (i32, f32) {
+        let filled = fraction * length as f32;
+        (filled.floor() as i32, filled.fract())
+    }
+
+    /// The blocks of a segmented bar `length` long, as start and length, and how many are lit.
+    fn segments(count: u32, fraction: f32, length: i32) -> (Vec<(i32, i32)>, usize) {
+        let count = count.max(1) as i32;
+        let blocks = (0..count)
+            .map(|i| {
+                let start = i * (length + 1) / count;
+                let end = (i + 1) * (length + 1) / count - 1;
+                (start, end - start)
+            })
+            .filter(|&(_, len)| len > 0)
+            .collect();
+        (blocks, (fraction * count as f32).round() as usize)
+    }
+
+    /// Draws the bar filled up to `value` over the whole canvas.
+    pub fn draw(&self, canvas: &mut impl Canvas, value: f32) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        let length = match self.orientation {
+            Orientation::Horizontal => width,
+            Orientation::Vertical => height,
+        };
+        let track = theme.color(Theme::MUTED);
+        let color = theme.color(self.scale.color(value));
+        let fraction = self.scale.fraction(value);
+        // spans along the bar, from its start, with their colors
+        let spans: Vec<(i32, i32, LedColor)> = match self.style {
+            BarStyle::Smooth => {
+                let (full, partial) = Self::fill(fraction, length.max(1));
+                let full = full.min(length);
+                vec![
+                    (0, full, color),
+                    (full, 1, track.lerp(&color, partial)),
+                    (full + 1, length - full - 1, track),
+                ]
+            }
+            BarStyle::Segments(count) => {
+                let (blocks, lit) = Self::segments(count, fraction, length);
+                (0..)
+                    .zip(blocks)
+                    .map(|(i, (start, len))| (start, len, if i < lit { color } else { track }))
+                    .collect()
+            }
+        };
+        for (start, len, color) in spans {
+            if len <= 0 || start >= length {
+                continue;
+            }
+            match self.orientation {
+                Orientation::Horizontal => canvas.view(start, 0, len, height).fill(&color),
+                Orientation::Vertical => {
+                    canvas
+                        .view(0, height - start - len, width, len)
+                        .fill(&color);
+                }
+            }
+        }
+    }
+}
+
+/// A radial gauge: an arc open at the bottom, filled clockwise up to a value.
+pub struct Gauge {
+    theme: SharedTheme,
+    scale: Scale,
+    thickness: i32,
+    label: Option<(usize, String)>,
+}
+
+impl Gauge {
+    /// Angle in radians the arc sweeps, leaving a quarter open at the bottom
+    const SWEEP: f32 = 1.5 * PI;
+
+    /// Creates a gauge for values from 0 to 1 without label, its arc a fifth of the radius
+    /// thick.
+    #[must_use]
+    pub const fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            scale: Scale::new(),
+            thickness: 0,
+            label: None,
+        }
+    }
+
+    /// Sets the values of an empty and a full gauge
+    #[must_use]
+    pub const fn range(mut self, min: f32, max: f32) -> Self {
+        self.scale.min = min;
+        self.scale.max = max;
+        self
+    }
+
+    /// Draws the arc in the theme color `color` from the value `at` on
+    #[must_use]
+    pub fn threshold(mut self, at: f32, color: &'static str) -> Self {
+        self.scale.add_threshold(at, color);
+        self
+    }
+
+    /// Sets the thickness of the arc in pixels
+    #[must_use]
+    pub const fn thickness(mut self, thickness: i32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Shows the value in the center with `decimals` digits after the point, followed by
+    /// `unit`, using the [`DEFAULT_FONT`](Theme::DEFAULT_FONT) of the theme
+    #[must_use]
+    pub fn label(mut self, decimals: usize, unit: &str) -> Self {
+        self.label = Some((decimals, unit.to_string()));
+        self
+    }
+
+    /// Whether the pixel at (`dx`, `dy`) from the center lies on the arc, and if so whether
+    /// it's filled up to `fraction`.
+    fn classify(dx: f32, dy: f32, radius: f32, thickness: f32, fraction: f32) -> Option<bool> {
+        let distance = dx.hypot(dy);
+        if distance > radius + 0.5 || distance <= radius + 0.5 - thickness {
+            return None;
+        }
+        // clockwise from the bottom, so the open quarter is centered on it
+        let angle = (-dx).atan2(dy).rem_euclid(2.0 * PI);
+        let along = angle - (2.0 * PI - Self::SWEEP) / 2.0;
+        (0.0..=Self::SWEEP)
+            .contains(&along)
+            .then_some(along <= fraction * Self::SWEEP && fraction > 0.0)
+    }
+
+    /// Draws the gauge showing `value`, as large as fits the canvas.
+    pub fn draw(&self, canvas: &mut impl TextCanvas, value: f32) {
+        let theme = self.theme.current();
+        let (width, height) = canvas.canvas_size();
+        let radius = (width.min(height) as f32 - 1.0) / 2.0;
+        let thickness = if self.thickness > 0 {
+            self.thickness as f32
+        } else {
+            (radius / 5.0).round().max(1.0)
+        };
+        let center = ((width - 1) as f32 / 2.0, (height - 1) as f32 / 2.0);
+        let fraction = self.scale.fraction(value);
+        let (track, color) = (
+            theme.color(Theme::MUTED),
+            theme.color(self.scale.color(value)),
+        );
+        canvas.set_iter((0..height).flat_map(|y| {
+            (0..width).filter_map(move |x| {
+                let (dx, dy) = (x as f32 - center.0, y as f32 - center.1);
+                Self::classify(dx, dy, radius, thickness, fraction)
+                    .map(|filled| (x, y, if filled { color } else { track }))
+            })
+        }));
+
+        let (Some((decimals, unit)), Some(font)) = (&self.label, theme.font(Theme::DEFAULT_FONT))
+        else {
+            return;
+        };
+        let text = format!("{value:.decimals$}{unit}");
+        let foreground = theme.color(Theme::FOREGROUND);
+        let baseline = (height - font.height().unwrap_or_default()) / 2 + font.baseline();
+        let options = TextDrawOptions::new()
+            .position(width / 2, baseline)
+            .align(Align::Center)
+            .color(&foreground);
+        canvas.draw_text(font, &text, &options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale() {
+        let mut scale = Scale::new();
+        scale.min = 10.0;
+        scale.max = 20.0;
+        scale.add_threshold(18.0, Theme::CRITICAL);
+        scale.add_threshold(15.0, Theme::WARNING);
+        assert_eq!(scale.fraction(12.5), 0.25);
+        assert_eq!(scale.fraction(30.0), 1.0);
+        assert_eq!(scale.color(12.0), Theme::ACCENT);
+        assert_eq!(scale.color(15.0), Theme::WARNING);
+        assert_eq!(scale.color(19.0), Theme::CRITICAL);
+    }
+
+    #[test]
+    fn bar() {
+        assert_eq!(ProgressBar::fill(0.5, 10), (5, 0.0));
+        assert_eq!(ProgressBar::fill(0.25, 10), (2, 0.5));
+        // 4 blocks of 3 pixels with gaps fill 15 pixels
+        let (blocks, lit) = ProgressBar::segments(4, 0.5, 15);
+        assert_eq!(blocks, [(0, 3), (4, 3), (8, 3), (12, 3)]);
+        assert_eq!(lit, 2);
+        assert_eq!(ProgressBar::segments(4, 0.6, 15).1, 2);
+        assert_eq!(ProgressBar::segments(4, 1.0, 15).1, 4);
+    }
+
+    #[test]
+    fn gauge() {
+        let pixel = |dx, dy, fraction| Gauge::classify(dx, dy, 10.0, 2.0, fraction);
+        // the bottom is open, the center empty
+        assert_eq!(pixel(0.0, 10.0, 1.0), None);
+        assert_eq!(pixel(0.0, 0.0, 1.0), None);
+        // left, top and right are a sixth, half and five sixths along the arc
+        assert_eq!(pixel(-10.0, 0.0, 0.3), Some(true));
+        assert_eq!(pixel(0.0, -10.0, 0.3), Some(false));
+        assert_eq!(pixel(0.0, -9.0, 0.6), Some(true));
+        assert_eq!(pixel(10.0, 0.0, 0.6), Some(false));
+        assert_eq!(pixel(10.0, 0.0, 0.0), Some(false));
+        assert_eq!(pixel(0.0, -8.0, 1.0), None);
+    }
+}
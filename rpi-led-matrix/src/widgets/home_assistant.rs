@@ -0,0 +1,318 @@
+//! Shows the state of a [Home Assistant](https://www.home-assistant.io/) entity.
+//!
+//! The state is read via the [REST API](https://developers.home-assistant.io/docs/api/rest/),
+//! authenticated with a long-lived access token created in the Home Assistant user profile.
+use std::time::{Duration, Instant};
+
+use crate::widgets::{Icon, SharedTheme, Theme};
+use crate::{LedCanvas, Scene, TextCanvas, TextDrawOptions};
+
+/// The state of an entity as reported by Home Assistant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityState {
+    /// The entity ID, e.g. `sensor.kitchen_temperature`
+    pub entity_id: String,
+    /// The state, e.g. `on`, `open` or `21.5`
+    pub state: String,
+    /// The display name, falling back to the entity ID
+    pub name: String,
+    /// The unit of the state, e.g. `°C`
+    pub unit: Option<String>,
+    /// When the state last changed, in ISO 8601
+    pub last_changed: String,
+}
+
+impl EntityState {
+    /// Parses the JSON state object returned by `GET /api/states/<entity_id>`.
+    ///
+    /// # Errors
+    /// If the JSON is invalid or lacks the entity ID or state.
+    pub fn parse(json: &str) -> Result<Self, &'static str> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|_| "Couldn't parse entity state")?;
+        let text = |value: &serde_json::Value| value.as_str().map(str::to_string);
+        let entity_id = text(&value["entity_id"]).ok_or("Entity state lacks the entity ID")?;
+        let attributes = &value["attributes"];
+        Ok(Self {
+            state: text(&value["state"]).ok_or("Entity state lacks the state")?,
+            name: text(&attributes["friendly_name"]).unwrap_or_else(|| entity_id.clone()),
+            unit: text(&attributes["unit_of_measurement"]),
+            last_changed: text(&value["last_changed"]).unwrap_or_default(),
+            entity_id,
+        })
+    }
+
+    /// The state followed by its unit, if any
+    #[must_use]
+    pub fn display(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} {unit}", self.state),
+            None => self.state.clone(),
+        }
+    }
+}
+
+/// How a state is shown: a [`Theme`] color name and an optional icon.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateStyle {
+    /// Name of the theme color of the icon and state
+    pub color: String,
+    /// Icon left of the state
+    pub icon: Option<Icon>,
+}
+
+/// Shows the state of a Home Assistant entity with an icon and a color depending on it.
+///
+/// The name of the entity is shown in the [`MUTED`](Theme::MUTED) color above the state if the
+/// canvas is tall enough for two lines of the theme's [`DEFAULT_FONT`](Theme::DEFAULT_FONT).
+///
+/// The state is polled, by default every 5 seconds. As a [`Scene`], polling happens while
+/// rendering and blocks for up to the timeout. The canvas only needs to be redrawn when
+/// [`poll`](Self::poll) reports a changed state.
+///
+/// ```no_run
+/// use rpi_led_matrix::widgets::home_assistant::EntityWidget;
+/// use rpi_led_matrix::widgets::{Icon, SharedTheme, Theme};
+/// use rpi_led_matrix::LedMatrix;
+/// use std::time::Instant;
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let url = "http://homeassistant.local:8123";
+/// let mut door = EntityWidget::new(url, "TOKEN", "binary_sensor.front_door")
+///     .theme(SharedTheme::default())
+///     .style("on", Theme::WARNING, Some(Icon::door()))
+///     .style("off", Theme::MUTED, Some(Icon::door()));
+/// if door.poll(Instant::now()).unwrap() {
+///     door.draw(&mut canvas);
+///     canvas = matrix.swap(canvas);
+/// }
+/// ```
+pub struct EntityWidget {
+    url: String,
+    token: String,
+    theme: SharedTheme,
+    styles: Vec<(String, StateStyle)>,
+    default_style: StateStyle,
+    timeout: Duration,
+    interval: Duration,
+    last_poll: Option<Instant>,
+    state: Option<EntityState>,
+}
+
+impl EntityWidget {
+    /// Creates a widget for `entity_id` of the Home Assistant instance at `base_url`.
+    ///
+    /// States without a [`style`](Self::style) are shown in the
+    /// [`FOREGROUND`](Theme::FOREGROUND) color without an icon.
+    #[must_use]
+    pub fn new(base_url: &str, token: &str, entity_id: &str) -> Self {
+        Self {
+            url: format!("{}/api/states/{entity_id}", base_url.trim_end_matches('/')),
+            token: token.to_string(),
+            theme: SharedTheme::default(),
+            styles: Vec::new(),
+            default_style: StateStyle {
+                color: Theme::FOREGROUND.to_string(),
+                icon: None,
+            },
+            timeout: Duration::from_secs(5),
+            interval: Duration::from_secs(5),
+            last_poll: None,
+            state: None,
+        }
+    }
+
+    /// Sets the theme providing the colors and font
+    #[must_use]
+    pub fn theme(mut self, theme: SharedTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Shows `state` in the theme color called `color`, with an optional icon.
+    #[must_use]
+    pub fn style(mut self, state: &str, color: &str, icon: Option<Icon>) -> Self {
+        let style = StateStyle {
+            color: color.to_string(),
+            icon,
+        };
+        self.styles.push((state.to_string(), style));
+        self
+    }
+
+    /// Sets how states without a [`style`](Self::style) of their own are shown
+    #[must_use]
+    pub fn default_style(mut self, color: &str, icon: Option<Icon>) -> Self {
+        self.default_style = StateStyle {
+            color: color.to_string(),
+            icon,
+        };
+        self
+    }
+
+    /// Sets the timeout for fetching the state
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the time between polls
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Fetches the current state of the entity.
+    ///
+    /// # Errors
+    /// If the state can't be fetched or parsed, e.g. because the token is wrong.
+    pub fn fetch(&self) -> Result<EntityState, &'static str> {
+        let body = ureq::AgentBuilder::new()
+            .timeout(self.timeout)
+            .user_agent(concat!("rpi-led-matrix/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .get(&self.url)
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .call()
+            .map_err(|_| "Couldn't fetch entity state")?
+            .into_string()
+            .map_err(|_| "Couldn't read entity state")?;
+        EntityState::parse(&body)
+    }
+
+    /// Fetches the state if the interval has passed since the last poll, returning true if
+    /// it changed.
+    ///
+    /// # Errors
+    /// If the state can't be fetched or parsed. The previous state is kept in that case.
+    pub fn poll(&mut self, now: Instant) -> Result<bool, &'static str> {
+        let due = self
+            .last_poll
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval);
+        if !due {
+            return Ok(false);
+        }
+        self.last_poll = Some(now);
+        let state = self.fetch()?;
+        Ok(self.update(state))
+    }
+
+    /// Replaces the state, e.g. with one received from another source, returning true if
+    /// it changed.
+    pub fn update(&mut self, state: EntityState) -> bool {
+        let changed = self.state.as_ref() != Some(&state);
+        self.state = Some(state);
+        changed
+    }
+
+    /// The last known state
+    #[must_use]
+    pub const fn state(&self) -> Option<&EntityState> {
+        self.state.as_ref()
+    }
+
+    /// The style the given state is shown in
+    #[must_use]
+    pub fn style_of(&self, state: &str) -> &StateStyle {
+        self.styles
+            .iter()
+            .find(|(s, _)| s == state)
+            .map_or(&self.default_style, |(_, style)| style)
+    }
+
+    /// Draws the last known state, or just the background if there is none yet.
+    pub fn draw(&self, canvas: &mut impl TextCanvas) {
+        let theme = self.theme.current();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+        let Some(state) = &self.state else {
+            return;
+        };
+        let style = self.style_of(&state.state);
+        let color = theme.color(&style.color);
+        let (width, height) = canvas.canvas_size();
+        let padding = theme.padding();
+
+        let mut x = padding;
+        if let Some(icon) = &style.icon {
+            let (icon_width, icon_height) = icon.size();
+            icon.draw(canvas, x, (height - icon_height) / 2, &color);
+            x += icon_width + padding.max(1);
+        }
+        let Some(font) = theme.font(Theme::DEFAULT_FONT) else {
+            return;
+        };
+        let line_height = font.height().unwrap_or_default();
+        let two_lines = 2 * line_height + theme.line_spacing() <= height - 2 * padding;
+        let mut baseline = (height - line_height) / 2 + font.baseline();
+        if two_lines {
+            let top = (height - 2 * line_height - theme.line_spacing()) / 2;
+            let muted = theme.color(Theme::MUTED);
+            let options = TextDrawOptions::new()
+                .position(x, top + font.baseline())
+                .color(&muted);
+            canvas.draw_text(font, &state.name, &options);
+            baseline = top + line_height + theme.line_spacing() + font.baseline();
+        }
+        let text = state.display();
+        // keep the state readable when it doesn't fit next to the icon
+        let x = if x + font.measure(&text, 0).0 > width {
+            padding
+        } else {
+            x
+        };
+        let options = TextDrawOptions::new().position(x, baseline).color(&color);
+        canvas.draw_text(font, &text, &options);
+    }
+}
+
+impl Scene for EntityWidget {
+    fn render(&mut self, canvas: &mut LedCanvas, _elapsed: Duration) {
+        // a failed poll keeps showing the last known state
+        let _ = self.poll(Instant::now());
+        self.draw(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KITCHEN: &str = r#"{
+        "entity_id": "sensor.kitchen_temperature",
+        "state": "21.5",
+        "attributes": {"unit_of_measurement": "°C", "friendly_name": "Kitchen"},
+        "last_changed": "2024-01-01T12:00:00+00:00"
+    }"#;
+
+    #[test]
+    fn parse_state() {
+        let state = EntityState::parse(KITCHEN).unwrap();
+        assert_eq!(state.name, "Kitchen");
+        assert_eq!(state.display(), "21.5 °C");
+        let bare = EntityState::parse(r#"{"entity_id": "light.desk", "state": "on"}"#).unwrap();
+        assert_eq!(
+            (bare.name.as_str(), bare.display()),
+            ("light.desk", "on".to_string())
+        );
+        assert!(EntityState::parse(r#"{"state": "on"}"#).is_err());
+        assert!(EntityState::parse("<html>").is_err());
+    }
+
+    #[test]
+    fn styles_and_changes() {
+        let mut widget = EntityWidget::new("http://ha.local/", "token", "light.desk").style(
+            "on",
+            Theme::ACCENT,
+            Some(Icon::bulb()),
+        );
+        assert_eq!(widget.url, "http://ha.local/api/states/light.desk");
+        assert_eq!(widget.style_of("on").color, Theme::ACCENT);
+        assert_eq!(widget.style_of("off").color, Theme::FOREGROUND);
+
+        let state = EntityState::parse(KITCHEN).unwrap();
+        assert!(widget.update(state.clone()));
+        assert!(!widget.update(state));
+    }
+}
@@ -0,0 +1,9 @@
+use crate::widgets::Icon;
+use crate::{LedCanvas, LedColor};
+
+impl LedCanvas {
+    /// Draws an icon with its top left corner at the given coordinate.
+    pub fn draw_icon(&mut self, icon: &Icon, x: i32, y: i32, color: &LedColor) {
+        icon.draw(self, x, y, color);
+    }
+}
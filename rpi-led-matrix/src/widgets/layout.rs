@@ -0,0 +1,388 @@
+//! Rows, columns and grids assigning regions of the canvas to widgets.
+//!
+//! Instead of calculating the offsets of every part of a dashboard by hand, nest layouts and
+//! let them hand each [`Widget`] a [`SubCanvas`] of its region:
+//!
+//! ```
+//! use rpi_led_matrix::widgets::layout::{Column, Row, Size, Widget};
+//! use rpi_led_matrix::{LedColor, LedMatrix, SubCanvas};
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let fill = |color: LedColor| move |canvas: &mut SubCanvas| canvas.fill(&color);
+//! let mut dashboard = Column::new()
+//!     .child(Size::Fixed(8), fill(LedColor { red: 0, green: 0, blue: 255 }))
+//!     .child(
+//!         Size::Flex(1),
+//!         Row::new()
+//!             .gap(1)
+//!             .child(Size::Flex(2), fill(LedColor { red: 255, green: 0, blue: 0 }))
+//!             .child(Size::Flex(1), fill(LedColor { red: 0, green: 255, blue: 0 })),
+//!     );
+//! dashboard.draw(&mut canvas);
+//! ```
+use crate::{LedCanvas, Rect, SubCanvas};
+
+/// Something drawn into a region handed out by a layout.
+///
+/// Implemented for closures taking a [`SubCanvas`], and for the layouts themselves, so they
+/// nest.
+pub trait Widget {
+    /// Draws into `canvas`, the region assigned to the widget, in local coordinates.
+    fn render(&mut self, canvas: &mut SubCanvas);
+
+    /// Draws the widget over the whole of `canvas`, or the active viewport.
+    fn draw(&mut self, canvas: &mut LedCanvas)
+    where
+        Self: Sized,
+    {
+        let (width, height) = canvas.canvas_size();
+        self.render(&mut canvas.viewport(0, 0, width, height));
+    }
+}
+
+impl<F: FnMut(&mut SubCanvas)> Widget for F {
+    fn render(&mut self, canvas: &mut SubCanvas) {
+        self(canvas);
+    }
+}
+
+/// How much space a child of a layout takes along the layout's direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Size {
+    /// Exactly this many pixels
+    Fixed(i32),
+    /// A share of the space left over by the fixed children, by weight
+    Flex(u32),
+}
+
+/// Splits `total` pixels into spans of `sizes`, separated by `gap`, as (offset, length).
+///
+/// Fixed sizes are handed out first. The rest goes to the flexible sizes by weight, with the
+/// pixels lost to rounding going to the first ones. Spans running past `total` are clipped by
+/// the viewport later.
+fn split(total: i32, gap: i32, sizes: &[Size]) -> Vec<(i32, i32)> {
+    let gaps = gap * (sizes.len() as i32 - 1).max(0);
+    let fixed: i32 = sizes
+        .iter()
+        .map(|size| match size {
+            Size::Fixed(length) => (*length).max(0),
+            Size::Flex(_) => 0,
+        })
+        .sum();
+    let weights: u32 = sizes
+        .iter()
+        .map(|size| match size {
+            Size::Fixed(_) => 0,
+            Size::Flex(weight) => *weight,
+        })
+        .sum();
+    let free = (total - gaps - fixed).max(0);
+    let mut remainder = free
+        - sizes.iter().fold(0, |sum, size| match size {
+            Size::Flex(weight) if weights > 0 => sum + free * *weight as i32 / weights as i32,
+            _ => sum,
+        });
+    let mut offset = 0;
+    sizes
+        .iter()
+        .map(|size| {
+            let length = match size {
+                Size::Fixed(length) => (*length).max(0),
+                Size::Flex(0) => 0,
+                Size::Flex(weight) => {
+                    let extra = i32::from(remainder > 0);
+                    remainder -= extra;
+                    free * *weight as i32 / weights as i32 + extra
+                }
+            };
+            let span = (offset, length);
+            offset += length + gap;
+            span
+        })
+        .collect()
+}
+
+/// Children laid out one after the other along one direction.
+struct Linear<'a> {
+    children: Vec<(Size, Box<dyn Widget + 'a>)>,
+    gap: i32,
+}
+
+impl<'a> Linear<'a> {
+    const fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            gap: 0,
+        }
+    }
+
+    fn sizes(&self) -> Vec<Size> {
+        self.children.iter().map(|(size, _)| *size).collect()
+    }
+}
+
+/// Lays out children side by side, from left to right, each as high as the row.
+///
+/// See the [module documentation](self) for an example.
+pub struct Row<'a>(Linear<'a>);
+
+impl<'a> Row<'a> {
+    /// Creates an empty row without gaps.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Linear::new())
+    }
+
+    /// Sets the empty space between children in pixels
+    #[must_use]
+    pub const fn gap(mut self, gap: i32) -> Self {
+        self.0.gap = gap;
+        self
+    }
+
+    /// Appends `widget` taking `size` of the width
+    #[must_use]
+    pub fn child(mut self, size: Size, widget: impl Widget + 'a) -> Self {
+        self.0.children.push((size, Box::new(widget)));
+        self
+    }
+
+    /// The regions of the children within `area`.
+    #[must_use]
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        split(area.width, self.0.gap, &self.0.sizes())
+            .into_iter()
+            .map(|(x, width)| Rect::new(area.x + x, area.y, width, area.height))
+            .collect()
+    }
+}
+
+impl Default for Row<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Row<'_> {
+    fn render(&mut self, canvas: &mut SubCanvas) {
+        let (width, height) = canvas.canvas_size();
+        let regions = self.layout(Rect::new(0, 0, width, height));
+        render_children(canvas, &regions, self.0.children.iter_mut().map(|(_, w)| w));
+    }
+}
+
+/// Lays out children on top of each other, from top to bottom, each as wide as the column.
+///
+/// See the [module documentation](self) for an example.
+pub struct Column<'a>(Linear<'a>);
+
+impl<'a> Column<'a> {
+    /// Creates an empty column without gaps.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Linear::new())
+    }
+
+    /// Sets the empty space between children in pixels
+    #[must_use]
+    pub const fn gap(mut self, gap: i32) -> Self {
+        self.0.gap = gap;
+        self
+    }
+
+    /// Appends `widget` taking `size` of the height
+    #[must_use]
+    pub fn child(mut self, size: Size, widget: impl Widget + 'a) -> Self {
+        self.0.children.push((size, Box::new(widget)));
+        self
+    }
+
+    /// The regions of the children within `area`.
+    #[must_use]
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        split(area.height, self.0.gap, &self.0.sizes())
+            .into_iter()
+            .map(|(y, height)| Rect::new(area.x, area.y + y, area.width, height))
+            .collect()
+    }
+}
+
+impl Default for Column<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Column<'_> {
+    fn render(&mut self, canvas: &mut SubCanvas) {
+        let (width, height) = canvas.canvas_size();
+        let regions = self.layout(Rect::new(0, 0, width, height));
+        render_children(canvas, &regions, self.0.children.iter_mut().map(|(_, w)| w));
+    }
+}
+
+/// Lays out children in cells of a grid of column widths and row heights.
+///
+/// Cells may span several columns and rows, and cells without a widget stay empty.
+///
+/// ```
+/// use rpi_led_matrix::widgets::layout::{Grid, Size, Widget};
+/// use rpi_led_matrix::{LedColor, LedMatrix, SubCanvas};
+/// let matrix = LedMatrix::new(None, None).unwrap();
+/// let mut canvas = matrix.offscreen_canvas();
+/// let red = LedColor { red: 255, green: 0, blue: 0 };
+/// let mut grid = Grid::new(&[Size::Flex(1); 2], &[Size::Fixed(8), Size::Flex(1)])
+///     .gap(1)
+///     .span(0, 0, 2, 1, |canvas: &mut SubCanvas| canvas.fill(&red))
+///     .cell(1, 1, |canvas: &mut SubCanvas| canvas.draw_line(0, 0, 7, 7, &red));
+/// grid.draw(&mut canvas);
+/// ```
+pub struct Grid<'a> {
+    columns: Vec<Size>,
+    rows: Vec<Size>,
+    gap: i32,
+    /// Children with their first column and row, and the number of columns and rows spanned
+    cells: Vec<([usize; 4], Box<dyn Widget + 'a>)>,
+}
+
+impl<'a> Grid<'a> {
+    /// Creates an empty grid with the given column widths and row heights, without gaps.
+    #[must_use]
+    pub fn new(columns: &[Size], rows: &[Size]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: rows.to_vec(),
+            gap: 0,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Sets the empty space between columns and rows in pixels
+    #[must_use]
+    pub const fn gap(mut self, gap: i32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Places `widget` in the cell at `column` and `row`
+    #[must_use]
+    pub fn cell(self, column: usize, row: usize, widget: impl Widget + 'a) -> Self {
+        self.span(column, row, 1, 1, widget)
+    }
+
+    /// Places `widget` over `columns` columns and `rows` rows, starting at `column` and `row`,
+    /// including the gaps between them. Spans are cut off at the end of the grid.
+    #[must_use]
+    pub fn span(
+        mut self,
+        column: usize,
+        row: usize,
+        columns: usize,
+        rows: usize,
+        widget: impl Widget + 'a,
+    ) -> Self {
+        self.cells
+            .push(([column, row, columns, rows], Box::new(widget)));
+        self
+    }
+
+    /// The regions of the children within `area`, in the order they were added. Children
+    /// placed outside of the grid get an empty region.
+    #[must_use]
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let columns = split(area.width, self.gap, &self.columns);
+        let rows = split(area.height, self.gap, &self.rows);
+        let extent = |spans: &[(i32, i32)], first: usize, count: usize| {
+            let last = (first + count.max(1)).min(spans.len()).checked_sub(1)?;
+            let (start, _) = *spans.get(first)?;
+            let (end, length) = spans[last];
+            Some((start, end + length - start))
+        };
+        self.cells
+            .iter()
+            .map(|([column, row, columns_spanned, rows_spanned], _)| {
+                match (
+                    extent(&columns, *column, *columns_spanned),
+                    extent(&rows, *row, *rows_spanned),
+                ) {
+                    (Some((x, width)), Some((y, height))) => {
+                        Rect::new(area.x + x, area.y + y, width, height)
+                    }
+                    _ => Rect::default(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Widget for Grid<'_> {
+    fn render(&mut self, canvas: &mut SubCanvas) {
+        let (width, height) = canvas.canvas_size();
+        let regions = self.layout(Rect::new(0, 0, width, height));
+        render_children(canvas, &regions, self.cells.iter_mut().map(|(_, w)| w));
+    }
+}
+
+/// Renders every child into a viewport of its region, skipping empty ones.
+fn render_children<'w, 'a: 'w>(
+    canvas: &mut SubCanvas,
+    regions: &[Rect],
+    children: impl Iterator<Item = &'w mut Box<dyn Widget + 'a>>,
+) {
+    for (region, child) in regions.iter().zip(children) {
+        if !region.is_empty() {
+            child.render(&mut canvas.viewport(region.x, region.y, region.width, region.height));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitting() {
+        use Size::{Fixed, Flex};
+        assert_eq!(split(64, 0, &[Fixed(10), Flex(1)]), [(0, 10), (10, 54)]);
+        // rounding leftovers go to the first flexible children
+        assert_eq!(
+            split(32, 1, &[Flex(1), Flex(1), Flex(1)]),
+            [(0, 10), (11, 10), (22, 10)]
+        );
+        assert_eq!(
+            split(11, 0, &[Flex(1), Fixed(1), Flex(2)]),
+            [(0, 4), (4, 1), (5, 6)]
+        );
+        // fixed sizes beyond the total leave nothing to flexible ones
+        assert_eq!(split(8, 0, &[Fixed(10), Flex(1)]), [(0, 10), (10, 0)]);
+        assert!(split(8, 2, &[]).is_empty());
+    }
+
+    #[test]
+    fn grid() {
+        let nothing = |_: &mut SubCanvas| {};
+        let grid = Grid::new(&[Size::Flex(1); 2], &[Size::Fixed(8), Size::Flex(1)])
+            .gap(2)
+            .span(0, 0, 2, 1, nothing)
+            .cell(1, 1, nothing)
+            .span(1, 1, 5, 5, nothing)
+            .cell(2, 0, nothing);
+        assert_eq!(
+            grid.layout(Rect::new(0, 0, 64, 32)),
+            [
+                Rect::new(0, 0, 64, 8),
+                Rect::new(33, 10, 31, 22),
+                Rect::new(33, 10, 31, 22),
+                Rect::default(),
+            ]
+        );
+        let row = Row::new()
+            .gap(1)
+            .child(Size::Fixed(8), nothing)
+            .child(Size::Flex(1), nothing);
+        assert_eq!(
+            row.layout(Rect::new(4, 4, 20, 8)),
+            [Rect::new(4, 4, 8, 8), Rect::new(13, 4, 11, 8)]
+        );
+    }
+}
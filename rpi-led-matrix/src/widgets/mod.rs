@@ -0,0 +1,34 @@
+//! Ready-made content for common matrix applications.
+#[deny(missing_docs)]
+pub mod charts;
+#[cfg(feature = "clock")]
+#[deny(missing_docs)]
+pub mod clock;
+#[cfg(feature = "http")]
+#[deny(missing_docs)]
+pub mod dashboard;
+#[deny(missing_docs)]
+mod flow;
+#[deny(missing_docs)]
+pub mod gauges;
+#[cfg(feature = "home-assistant")]
+#[deny(missing_docs)]
+pub mod home_assistant;
+#[deny(missing_docs)]
+mod icon;
+#[deny(missing_docs)]
+pub mod layout;
+#[deny(missing_docs)]
+mod theme;
+#[deny(missing_docs)]
+pub mod ticker;
+#[cfg(feature = "online-widgets")]
+#[deny(missing_docs)]
+pub mod weather_alerts;
+
+#[doc(inline)]
+pub use flow::{Flow, Inline};
+#[doc(inline)]
+pub use rpi_led_matrix_core::widgets::{Icon, SevenSegment};
+#[doc(inline)]
+pub use theme::{SharedTheme, Theme};
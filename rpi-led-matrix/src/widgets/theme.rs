@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{LedColor, LedFont};
+
+/// Named colors, fonts and spacing shared by widgets, so whole dashboards restyle at once.
+///
+/// Colors and fonts are looked up by name. The well-known names are provided as constants,
+/// widgets may use any other name as long as the theme defines it. Unknown colors fall back
+/// to [`FOREGROUND`](Self::FOREGROUND), unknown fonts to [`DEFAULT_FONT`](Self::DEFAULT_FONT).
+///
+/// ```
+/// use rpi_led_matrix::widgets::Theme;
+/// use rpi_led_matrix::LedColor;
+/// let theme = Theme::night().with_color("clock", LedColor { red: 64, green: 0, blue: 0 });
+/// assert_eq!(theme.color("clock"), LedColor { red: 64, green: 0, blue: 0 });
+/// assert_eq!(theme.color("unknown"), theme.color(Theme::FOREGROUND));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Theme {
+    name: String,
+    colors: HashMap<String, LedColor>,
+    fonts: HashMap<String, Arc<LedFont>>,
+    padding: i32,
+    line_spacing: i32,
+}
+
+impl Theme {
+    /// Color of regular text and lines
+    pub const FOREGROUND: &'static str = "foreground";
+    /// Color content is drawn on
+    pub const BACKGROUND: &'static str = "background";
+    /// Color of highlights, e.g. headings or the current value
+    pub const ACCENT: &'static str = "accent";
+    /// Color of secondary content, e.g. labels or units
+    pub const MUTED: &'static str = "muted";
+    /// Color of warnings
+    pub const WARNING: &'static str = "warning";
+    /// Color of errors and critical values
+    pub const CRITICAL: &'static str = "critical";
+    /// Font used when no other font is asked for
+    pub const DEFAULT_FONT: &'static str = "default";
+
+    /// Creates an empty theme, drawing white on black with no fonts and no spacing.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            colors: HashMap::new(),
+            fonts: HashMap::new(),
+            padding: 0,
+            line_spacing: 0,
+        }
+    }
+
+    fn with_palette(name: &str, palette: [(&str, (u8, u8, u8)); 6]) -> Self {
+        palette
+            .into_iter()
+            .fold(Self::new(name), |theme, (color, (red, green, blue))| {
+                theme.with_color(color, LedColor { red, green, blue })
+            })
+            .with_padding(1)
+            .with_line_spacing(1)
+    }
+
+    /// Bright, saturated colors for daylight
+    #[must_use]
+    pub fn day() -> Self {
+        Self::with_palette(
+            "day",
+            [
+                (Self::FOREGROUND, (255, 255, 255)),
+                (Self::BACKGROUND, (0, 0, 0)),
+                (Self::ACCENT, (0, 160, 255)),
+                (Self::MUTED, (128, 128, 128)),
+                (Self::WARNING, (255, 160, 0)),
+                (Self::CRITICAL, (255, 0, 0)),
+            ],
+        )
+    }
+
+    /// Dim, warm colors that don't glare in the dark
+    #[must_use]
+    pub fn night() -> Self {
+        Self::with_palette(
+            "night",
+            [
+                (Self::FOREGROUND, (96, 32, 0)),
+                (Self::BACKGROUND, (0, 0, 0)),
+                (Self::ACCENT, (128, 48, 0)),
+                (Self::MUTED, (40, 12, 0)),
+                (Self::WARNING, (128, 64, 0)),
+                (Self::CRITICAL, (160, 0, 0)),
+            ],
+        )
+    }
+
+    /// Full intensity primaries only, for maximum legibility
+    #[must_use]
+    pub fn high_contrast() -> Self {
+        Self::with_palette(
+            "high-contrast",
+            [
+                (Self::FOREGROUND, (255, 255, 255)),
+                (Self::BACKGROUND, (0, 0, 0)),
+                (Self::ACCENT, (255, 255, 0)),
+                (Self::MUTED, (255, 255, 255)),
+                (Self::WARNING, (255, 255, 0)),
+                (Self::CRITICAL, (255, 0, 0)),
+            ],
+        )
+    }
+
+    /// Sets the color called `name`
+    #[must_use]
+    pub fn with_color(mut self, name: &str, color: LedColor) -> Self {
+        self.colors.insert(name.to_string(), color);
+        self
+    }
+
+    /// Sets the font called `name`
+    #[must_use]
+    pub fn with_font(mut self, name: &str, font: Arc<LedFont>) -> Self {
+        self.fonts.insert(name.to_string(), font);
+        self
+    }
+
+    /// Sets the space in pixels between the edge of a widget and its content
+    #[must_use]
+    pub const fn with_padding(mut self, padding: i32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the space in pixels between lines of text
+    #[must_use]
+    pub const fn with_line_spacing(mut self, line_spacing: i32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// The name of the theme
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Looks up a color, falling back to the foreground color, then white.
+    #[must_use]
+    pub fn color(&self, name: &str) -> LedColor {
+        self.colors
+            .get(name)
+            .or_else(|| self.colors.get(Self::FOREGROUND))
+            .copied()
+            .unwrap_or(LedColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+            })
+    }
+
+    /// Looks up a font, falling back to the default font.
+    #[must_use]
+    pub fn font(&self, name: &str) -> Option<&Arc<LedFont>> {
+        self.fonts
+            .get(name)
+            .or_else(|| self.fonts.get(Self::DEFAULT_FONT))
+    }
+
+    /// The space in pixels between the edge of a widget and its content
+    #[must_use]
+    pub const fn padding(&self) -> i32 {
+        self.padding
+    }
+
+    /// The space in pixels between lines of text
+    #[must_use]
+    pub const fn line_spacing(&self) -> i32 {
+        self.line_spacing
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::day()
+    }
+}
+
+/// A [`Theme`] handed to several widgets, which can be switched for all of them at runtime.
+///
+/// Clones share the same theme. Widgets look it up on every frame, so a switch shows on the
+/// next frame, e.g. when a [`SolarClock`](crate::SolarClock) reports nightfall:
+///
+/// ```
+/// use rpi_led_matrix::widgets::{SharedTheme, Theme};
+/// use rpi_led_matrix::SolarClock;
+/// let theme = SharedTheme::new(Theme::day());
+/// let switch = theme.clone();
+/// let solar = SolarClock::new(52.52, 13.40)
+///     .unwrap()
+///     .on_change(move |daylight| switch.set(daylight.pick(Theme::day(), Theme::night())));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SharedTheme(Arc<RwLock<Arc<Theme>>>);
+
+impl SharedTheme {
+    /// Shares the given theme
+    #[must_use]
+    pub fn new(theme: Theme) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(theme))))
+    }
+
+    /// The theme currently in use
+    #[must_use]
+    pub fn current(&self) -> Arc<Theme> {
+        Arc::clone(&self.0.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Switches all holders of this shared theme over to `theme`.
+    pub fn set(&self, theme: Theme) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::test::TINY_BDF;
+
+    #[test]
+    fn lookup() {
+        let font = Arc::new(LedFont::new_from_buffer(TINY_BDF.as_bytes()).unwrap());
+        let theme = Theme::new("test").with_font(Theme::DEFAULT_FONT, Arc::clone(&font));
+        let white = LedColor {
+            red: 255,
+            green: 255,
+            blue: 255,
+        };
+        assert_eq!(theme.color(Theme::ACCENT), white);
+        assert!(theme.font("title").is_some());
+        assert!(Theme::new("empty").font(Theme::DEFAULT_FONT).is_none());
+        assert_ne!(
+            Theme::night().color(Theme::FOREGROUND),
+            Theme::day().color(Theme::FOREGROUND)
+        );
+    }
+
+    #[test]
+    fn shared() {
+        let theme = SharedTheme::new(Theme::day());
+        let widget = theme.clone();
+        theme.set(Theme::night());
+        assert_eq!(widget.current().name(), "night");
+    }
+}
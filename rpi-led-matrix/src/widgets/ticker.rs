@@ -0,0 +1,379 @@
+//! A scrolling ticker of items from a data source, e.g. stock quotes, news or departures.
+//!
+//! A [`Ticker`] either polls a [`TickerSource`] itself while rendering, or is fed from
+//! another thread or an async task through a [`TickerHandle`].
+//!
+//! ```
+//! use rpi_led_matrix::widgets::ticker::{Ticker, TickerItem};
+//! use rpi_led_matrix::widgets::SharedTheme;
+//! use rpi_led_matrix::{LedColor, LedMatrix, Scene};
+//! use std::time::Duration;
+//! let matrix = LedMatrix::new(None, None).unwrap();
+//! let mut canvas = matrix.offscreen_canvas();
+//! let red = LedColor { red: 255, green: 0, blue: 0 };
+//! let quotes = move || {
+//!     Ok(vec![TickerItem::new("ACME 12.34"), TickerItem::new("XYZ -1.2%").color(red)])
+//! };
+//! let mut ticker = Ticker::new(SharedTheme::default())
+//!     .source(quotes)
+//!     .interval(Duration::from_secs(300))
+//!     .speed(24.0);
+//! ticker.render(&mut canvas, Duration::ZERO);
+//! ```
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{Canvas, LedCanvas, LedColor, LedFont, Scene, TextCanvas, TextDrawOptions};
+
+/// A piece of text shown by a [`Ticker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TickerItem {
+    /// The text
+    pub text: String,
+    /// The color of the text, or `None` for the [`FOREGROUND`](Theme::FOREGROUND) color
+    pub color: Option<LedColor>,
+}
+
+impl TickerItem {
+    /// Creates an item drawn in the foreground color.
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            color: None,
+        }
+    }
+
+    /// Sets the color of the text
+    #[must_use]
+    pub const fn color(mut self, color: LedColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// Supplies the items of a [`Ticker`] when it polls.
+///
+/// Implemented for all `FnMut() -> Result<Vec<TickerItem>, &'static str>` closures.
+pub trait TickerSource {
+    /// Fetches the current items.
+    ///
+    /// # Errors
+    /// If the items can't be fetched. The ticker keeps showing the previous ones.
+    fn fetch(&mut self) -> Result<Vec<TickerItem>, &'static str>;
+}
+
+impl<F> TickerSource for F
+where
+    F: FnMut() -> Result<Vec<TickerItem>, &'static str>,
+{
+    fn fetch(&mut self) -> Result<Vec<TickerItem>, &'static str> {
+        self()
+    }
+}
+
+/// Hands new items to a [`Ticker`] from another thread or an async task.
+#[derive(Clone, Debug, Default)]
+pub struct TickerHandle {
+    pending: Arc<Mutex<Option<Vec<TickerItem>>>>,
+}
+
+impl TickerHandle {
+    /// Replaces the items of the ticker, counting as a successful update.
+    pub fn set_items(&self, items: Vec<TickerItem>) {
+        *self.pending.lock().unwrap_or_else(PoisonError::into_inner) = Some(items);
+    }
+
+    fn take(&self) -> Option<Vec<TickerItem>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+/// Scrolls items from right to left in an endless loop, vertically centered.
+///
+/// The items are drawn with the [`DEFAULT_FONT`](Theme::DEFAULT_FONT) of the theme, one after
+/// another with a separator in the [`MUTED`](Theme::MUTED) color in between. New items replace
+/// the shown ones only once the current loop has scrolled by, so the text never jumps. If the
+/// last update is older than the staleness limit, a dot in the top right corner shows it in
+/// the [`WARNING`](Theme::WARNING) color, or in the [`CRITICAL`](Theme::CRITICAL) color if
+/// there was no update yet.
+///
+/// As a [`Scene`], the source is polled while rendering, which blocks while it fetches. To keep
+/// scrolling smoothly, fetch from another thread instead and pass the items on through a
+/// [`handle`](Self::handle).
+pub struct Ticker {
+    theme: SharedTheme,
+    source: Option<Box<dyn TickerSource + Send>>,
+    handle: TickerHandle,
+    separator: String,
+    speed: f32,
+    interval: Duration,
+    stale_after: Duration,
+    items: Vec<TickerItem>,
+    /// Items waiting for the current loop to end
+    pending: Option<Vec<TickerItem>>,
+    /// Pixels the current loop has scrolled by
+    position: f32,
+    last_elapsed: Duration,
+    last_attempt: Option<Instant>,
+    last_success: Option<Instant>,
+}
+
+impl Ticker {
+    /// Creates an empty ticker scrolling 20 pixels per second, polling every minute and stale
+    /// after 5 minutes.
+    #[must_use]
+    pub fn new(theme: SharedTheme) -> Self {
+        Self {
+            theme,
+            source: None,
+            handle: TickerHandle::default(),
+            separator: " | ".to_string(),
+            speed: 20.0,
+            interval: Duration::from_secs(60),
+            stale_after: Duration::from_secs(300),
+            items: Vec::new(),
+            pending: None,
+            position: 0.0,
+            last_elapsed: Duration::ZERO,
+            last_attempt: None,
+            last_success: None,
+        }
+    }
+
+    /// Sets the source polled for items
+    #[must_use]
+    pub fn source(mut self, source: impl TickerSource + Send + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Sets the text between items
+    #[must_use]
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Sets the scroll speed in pixels per second
+    #[must_use]
+    pub const fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the time between polls of the source
+    #[must_use]
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how old the last update may get before it's flagged as stale
+    #[must_use]
+    pub const fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// A handle to update the items from elsewhere, e.g. another thread.
+    #[must_use]
+    pub fn handle(&self) -> TickerHandle {
+        self.handle.clone()
+    }
+
+    /// The items currently shown
+    #[must_use]
+    pub fn items(&self) -> &[TickerItem] {
+        &self.items
+    }
+
+    /// Takes items handed over through the [`handle`](Self::handle), and polls the source if
+    /// the interval has passed since the last attempt.
+    ///
+    /// Returns true if new items arrived. They're shown once the current loop has scrolled by,
+    /// or right away if nothing is shown yet.
+    ///
+    /// # Errors
+    /// If the source fails, see [`TickerSource::fetch`].
+    pub fn poll(&mut self, now: Instant) -> Result<bool, &'static str> {
+        let due = self
+            .last_attempt
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval);
+        let mut items = None;
+        if let Some(source) = self.source.as_mut().filter(|_| due) {
+            self.last_attempt = Some(now);
+            items = Some(source.fetch()?);
+        }
+        // taken only after a successful fetch, so a failing source doesn't drop them
+        let Some(items) = self.handle.take().or(items) else {
+            return Ok(false);
+        };
+        self.last_success = Some(now);
+        if self.items.is_empty() {
+            self.items = items;
+            self.position = 0.0;
+        } else {
+            self.pending = Some(items);
+        }
+        Ok(true)
+    }
+
+    /// Returns true if there was no update within the staleness limit.
+    #[must_use]
+    pub fn is_stale(&self, now: Instant) -> bool {
+        self.last_success
+            .is_none_or(|last| now.saturating_duration_since(last) > self.stale_after)
+    }
+
+    /// The pieces of text of one loop with their colors, separators included.
+    fn segments(&self, theme: &Theme) -> Vec<(&str, LedColor)> {
+        let foreground = theme.color(Theme::FOREGROUND);
+        self.items
+            .iter()
+            .flat_map(|item| {
+                [
+                    (item.text.as_str(), item.color.unwrap_or(foreground)),
+                    (self.separator.as_str(), theme.color(Theme::MUTED)),
+                ]
+            })
+            .collect()
+    }
+
+    /// Scrolls by the time passed since the last frame, swapping in pending items at the end
+    /// of a loop `width` pixels wide.
+    fn advance(&mut self, elapsed: Duration, width: i32) {
+        // a scene shown again starts counting from zero
+        let delta = elapsed.checked_sub(self.last_elapsed).unwrap_or(elapsed);
+        self.last_elapsed = elapsed;
+        self.position += delta.as_secs_f32() * self.speed;
+        if width > 0 && self.position >= width as f32 {
+            self.position %= width as f32;
+            if let Some(items) = self.pending.take() {
+                self.items = items;
+                self.position = 0.0;
+            }
+        }
+    }
+
+    /// Scrolls by the time passed since the last frame and draws the items, with the
+    /// staleness indicator if there was no recent update.
+    ///
+    /// `elapsed` is the time the ticker has been on screen, as passed to [`Scene::render`].
+    pub fn render_at(&mut self, canvas: &mut impl TextCanvas, elapsed: Duration, now: Instant) {
+        let theme = self.theme.current();
+        canvas.fill(&theme.color(Theme::BACKGROUND));
+        let (width, height) = canvas.canvas_size();
+        if let Some(font) = theme.font(Theme::DEFAULT_FONT) {
+            let loop_width = Self::width(font, &self.segments(&theme));
+            self.advance(elapsed, loop_width);
+            let segments = self.segments(&theme);
+            let baseline = (height - font.height().unwrap_or_default()) / 2 + font.baseline();
+            // repeat the loop until the canvas is covered, so it wraps around seamlessly
+            let mut x = -(self.position as i32);
+            while loop_width > 0 && x < width {
+                for (text, color) in &segments {
+                    let options = TextDrawOptions::new().position(x, baseline).color(color);
+                    canvas.draw_text(font, text, &options);
+                    x += font.advance(text, 0);
+                }
+            }
+        }
+        if self.is_stale(now) {
+            let color = if self.last_success.is_some() {
+                theme.color(Theme::WARNING)
+            } else {
+                theme.color(Theme::CRITICAL)
+            };
+            canvas.view(width - 2, 0, 2, 2).fill(&color);
+        }
+    }
+
+    fn width(font: &LedFont, segments: &[(&str, LedColor)]) -> i32 {
+        segments.iter().map(|(text, _)| font.advance(text, 0)).sum()
+    }
+}
+
+impl Scene for Ticker {
+    fn render(&mut self, canvas: &mut LedCanvas, elapsed: Duration) {
+        let now = Instant::now();
+        // a failed poll shows as staleness, the previous items keep scrolling
+        let _ = self.poll(now);
+        self.render_at(canvas, elapsed, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(ticker: &Ticker) -> Vec<&str> {
+        ticker
+            .items()
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn polling() {
+        let mut count = 0;
+        let mut ticker = Ticker::new(SharedTheme::default())
+            .source(move || {
+                count += 1;
+                match count {
+                    2 => Err("offline"),
+                    _ => Ok(vec![TickerItem::new(&format!("fetch {count}"))]),
+                }
+            })
+            .interval(Duration::from_secs(10))
+            .stale_after(Duration::from_secs(15));
+        let start = Instant::now();
+        assert!(ticker.is_stale(start));
+        assert_eq!(ticker.poll(start), Ok(true));
+        assert_eq!(texts(&ticker), ["fetch 1"]);
+        assert_eq!(ticker.poll(start + Duration::from_secs(5)), Ok(false));
+        assert!(ticker.poll(start + Duration::from_secs(10)).is_err());
+        assert!(ticker.is_stale(start + Duration::from_secs(16)));
+
+        // new items wait for the end of the loop
+        assert_eq!(ticker.poll(start + Duration::from_secs(20)), Ok(true));
+        assert_eq!(texts(&ticker), ["fetch 1"]);
+        ticker.advance(Duration::from_secs(1), 30);
+        assert_eq!(texts(&ticker), ["fetch 1"]);
+        assert_eq!(ticker.position, 20.0);
+        ticker.advance(Duration::from_secs(2), 30);
+        assert_eq!(texts(&ticker), ["fetch 3"]);
+        assert_eq!(ticker.position, 0.0);
+
+        // items from a handle arrive without a source
+        let mut ticker = Ticker::new(SharedTheme::default());
+        let handle = ticker.handle();
+        std::thread::spawn(move || handle.set_items(vec![TickerItem::new("pushed")]))
+            .join()
+            .unwrap();
+        assert_eq!(ticker.poll(start), Ok(true));
+        assert_eq!(texts(&ticker), ["pushed"]);
+        assert!(!ticker.is_stale(start));
+    }
+
+    #[test]
+    fn looping() {
+        let mut ticker = Ticker::new(SharedTheme::default()).speed(10.0);
+        ticker.handle().set_items(vec![TickerItem::new("A")]);
+        ticker.poll(Instant::now()).unwrap();
+        let theme = Theme::day();
+        let segments = ticker.segments(&theme);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1], (" | ", theme.color(Theme::MUTED)));
+        // wraps around within the loop, and starts over when shown again
+        ticker.advance(Duration::from_millis(2500), 20);
+        assert_eq!(ticker.position, 5.0);
+        ticker.advance(Duration::from_millis(500), 20);
+        assert_eq!(ticker.position, 10.0);
+    }
+}
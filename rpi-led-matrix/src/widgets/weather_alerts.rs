@@ -0,0 +1,413 @@
+//! Severe weather alerts from [CAP] feeds, such as the ones published by the [NWS].
+//!
+//! [`WeatherAlerts`] polls a feed, picks the alerts for the configured area and shows them
+//! through the preemption path of a [`SceneManager`], colored by severity.
+//!
+//! ```no_run
+//! use rpi_led_matrix::widgets::weather_alerts::WeatherAlerts;
+//! use rpi_led_matrix::{LedFont, SceneManager};
+//! use std::path::Path;
+//! use std::sync::Arc;
+//! let font = Arc::new(LedFont::new(Path::new("6x10.bdf")).unwrap());
+//! let mut alerts = WeatherAlerts::new("https://api.weather.gov/alerts/active.atom?area=KS", font)
+//!     .area("KSC091");
+//! let mut scenes = SceneManager::new();
+//! alerts.poll(&mut scenes).unwrap();
+//! ```
+//!
+//! [CAP]: https://docs.oasis-open.org/emergency/cap/v1.2/CAP-v1.2.html
+//! [NWS]: https://www.weather.gov/documentation/services-web-alerts
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::widgets::{SharedTheme, Theme};
+use crate::{
+    LedCanvas, LedColor, LedFont, Priority, SceneId, SceneManager, TextCanvas, TextDrawOptions,
+};
+
+/// Horizontal scroll speed of the alert text, in pixels per second
+const SCROLL_SPEED: u128 = 20;
+
+/// The CAP severity of an alert, ordered from least to most severe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Severity unknown
+    #[default]
+    Unknown,
+    /// Minimal to no known threat to life or property
+    Minor,
+    /// Possible threat to life or property
+    Moderate,
+    /// Significant threat to life or property
+    Severe,
+    /// Extraordinary threat to life or property
+    Extreme,
+}
+
+impl Severity {
+    fn parse(value: &str) -> Self {
+        match value.trim() {
+            "Minor" => Self::Minor,
+            "Moderate" => Self::Moderate,
+            "Severe" => Self::Severe,
+            "Extreme" => Self::Extreme,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The color alerts of this severity are drawn in
+    #[must_use]
+    pub const fn color(self) -> LedColor {
+        match self {
+            Self::Unknown => LedColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+            Self::Minor => LedColor {
+                red: 0,
+                green: 128,
+                blue: 255,
+            },
+            Self::Moderate => LedColor {
+                red: 255,
+                green: 255,
+                blue: 0,
+            },
+            Self::Severe => LedColor {
+                red: 255,
+                green: 128,
+                blue: 0,
+            },
+            Self::Extreme => LedColor {
+                red: 255,
+                green: 0,
+                blue: 0,
+            },
+        }
+    }
+
+    /// The scene priority alerts of this severity are shown with
+    #[must_use]
+    pub fn priority(self) -> Priority {
+        if self == Self::Extreme {
+            Priority::Emergency
+        } else {
+            Priority::Alert
+        }
+    }
+}
+
+/// A single alert parsed from a CAP document or an ATOM feed of CAP entries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapAlert {
+    /// unique identifier of the alert
+    pub id: String,
+    /// the type of event, e.g. "Tornado Warning"
+    pub event: String,
+    /// short human readable summary
+    pub headline: String,
+    /// how severe the event is
+    pub severity: Severity,
+    /// human readable description of the affected area
+    pub area_desc: String,
+    /// codes of the affected areas, e.g. UGC or SAME codes
+    pub geocodes: Vec<String>,
+}
+
+impl CapAlert {
+    /// Parses all alerts from a CAP `<alert>` document or an ATOM feed with CAP `<entry>`s.
+    ///
+    /// # Errors
+    /// If the document isn't well-formed XML.
+    pub fn parse(xml: &str) -> Result<Vec<Self>, &'static str> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let mut alerts = Vec::new();
+        let mut current: Option<Self> = None;
+        // set once the first <info> block of a CAP alert ends, ignoring further languages
+        let mut info_done = false;
+        let mut element = String::new();
+
+        loop {
+            match reader
+                .read_event()
+                .map_err(|_| "Couldn't parse alert feed")?
+            {
+                Event::Start(start) => {
+                    element = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                    if element == "alert" || element == "entry" {
+                        current = Some(Self::default());
+                        info_done = false;
+                    }
+                }
+                Event::End(end) => {
+                    let name = end.local_name();
+                    let name = name.as_ref();
+                    if name == b"info" {
+                        info_done = true;
+                    } else if name == b"alert" || name == b"entry" {
+                        alerts.extend(current.take());
+                    }
+                    element.clear();
+                }
+                Event::Text(text) => {
+                    let Some(alert) = current.as_mut() else {
+                        continue;
+                    };
+                    let text = text.unescape().map_err(|_| "Couldn't parse alert feed")?;
+                    match element.as_str() {
+                        "identifier" | "id" if alert.id.is_empty() => alert.id = text.into(),
+                        _ if info_done => {}
+                        "event" => alert.event = text.into(),
+                        "headline" | "title" if alert.headline.is_empty() => {
+                            alert.headline = text.into();
+                        }
+                        "severity" => alert.severity = Severity::parse(&text),
+                        "areaDesc" => alert.area_desc = text.into(),
+                        "value" => alert.geocodes.push(text.into()),
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(alerts)
+    }
+
+    /// Returns true if the alert covers the given geocode or area name.
+    #[must_use]
+    pub fn covers(&self, area: &str) -> bool {
+        self.geocodes.iter().any(|code| code == area)
+            || self.area_desc.to_lowercase().contains(&area.to_lowercase())
+    }
+}
+
+/// Polls a CAP feed and preempts a [`SceneManager`] with the alerts for the configured area.
+pub struct WeatherAlerts {
+    url: String,
+    areas: Vec<String>,
+    min_severity: Severity,
+    timeout: Duration,
+    font: Arc<LedFont>,
+    theme: SharedTheme,
+    shown: HashMap<String, SceneId>,
+}
+
+impl WeatherAlerts {
+    /// Creates the widget for the feed at `url`, drawing alert text with `font`.
+    ///
+    /// By default alerts of all areas with a severity of at least [`Severity::Severe`] are shown.
+    #[must_use]
+    pub fn new(url: &str, font: Arc<LedFont>) -> Self {
+        Self {
+            url: url.to_string(),
+            areas: Vec::new(),
+            min_severity: Severity::Severe,
+            timeout: Duration::from_secs(10),
+            font,
+            theme: SharedTheme::default(),
+            shown: HashMap::new(),
+        }
+    }
+
+    /// Restricts the alerts to an area, given as geocode (e.g. UGC "KSC091") or area name.
+    ///
+    /// Can be called multiple times to watch several areas.
+    #[must_use]
+    pub fn area(mut self, area: &str) -> Self {
+        self.areas.push(area.to_string());
+        self
+    }
+
+    /// Sets the minimum severity of alerts to show
+    #[must_use]
+    pub const fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Sets the timeout for fetching the feed
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the theme providing the background behind the alerts
+    #[must_use]
+    pub fn theme(mut self, theme: SharedTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Fetches and parses the feed.
+    ///
+    /// # Errors
+    /// If the feed can't be fetched or parsed.
+    pub fn fetch(&self) -> Result<Vec<CapAlert>, &'static str> {
+        let body = ureq::AgentBuilder::new()
+            .timeout(self.timeout)
+            .user_agent(concat!("rpi-led-matrix/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .get(&self.url)
+            .call()
+            .map_err(|_| "Couldn't fetch alert feed")?
+            .into_string()
+            .map_err(|_| "Couldn't read alert feed")?;
+        CapAlert::parse(&body)
+    }
+
+    /// Fetches the feed and updates the scenes, see [`update`](Self::update).
+    ///
+    /// # Errors
+    /// If the feed can't be fetched or parsed. The scenes are left untouched in that case.
+    pub fn poll(&mut self, scenes: &mut SceneManager) -> Result<usize, &'static str> {
+        let alerts = self.fetch()?;
+        Ok(self.update(scenes, &alerts))
+    }
+
+    /// Preempts `scenes` with every new relevant alert and dismisses the ones no longer
+    /// present, returning the number of alerts on display.
+    pub fn update(&mut self, scenes: &mut SceneManager, alerts: &[CapAlert]) -> usize {
+        let relevant: Vec<&CapAlert> = alerts
+            .iter()
+            .filter(|a| a.severity >= self.min_severity)
+            .filter(|a| self.areas.is_empty() || self.areas.iter().any(|area| a.covers(area)))
+            .collect();
+
+        self.shown.retain(|id, scene| {
+            let keep = relevant.iter().any(|a| &a.id == id);
+            if !keep {
+                scenes.dismiss(*scene);
+            }
+            keep
+        });
+        for alert in relevant {
+            if self.shown.contains_key(&alert.id) {
+                continue;
+            }
+            let font = Arc::clone(&self.font);
+            let theme = self.theme.clone();
+            let severity = alert.severity;
+            let text = format!("{}: {}", alert.event, alert.headline);
+            let scene = scenes.preempt(
+                severity.priority(),
+                None,
+                move |canvas: &mut LedCanvas, elapsed: Duration| {
+                    render_alert(canvas, &font, &theme.current(), &text, severity, elapsed);
+                },
+            );
+            self.shown.insert(alert.id.clone(), scene);
+        }
+        self.shown.len()
+    }
+}
+
+/// Draws a frame in the severity color with the alert text scrolling through it.
+fn render_alert(
+    canvas: &mut impl TextCanvas,
+    font: &LedFont,
+    theme: &Theme,
+    text: &str,
+    severity: Severity,
+    elapsed: Duration,
+) {
+    let color = severity.color();
+    let (width, height) = canvas.canvas_size();
+    canvas.fill(&theme.color(Theme::BACKGROUND));
+    canvas.draw_line(0, 0, width - 1, 0, &color);
+    canvas.draw_line(0, height - 1, width - 1, height - 1, &color);
+
+    let text_width = font.advance(text, 0);
+    let scrolled = (elapsed.as_millis() * SCROLL_SPEED / 1000) as i32 % (width + text_width).max(1);
+    let baseline = (height - font.height().unwrap_or_default()) / 2 + font.baseline();
+    let options = TextDrawOptions::new()
+        .position(width - scrolled, baseline)
+        .color(&color);
+    canvas.draw_text(font, text, &options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATOM_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:cap="urn:oasis:names:tc:emergency:cap:1.2">
+  <id>https://api.weather.gov/alerts/active.atom</id>
+  <title>Current watches, warnings, and advisories</title>
+  <entry>
+    <id>urn:oid:1</id>
+    <title>Tornado Warning issued for Johnson County</title>
+    <cap:event>Tornado Warning</cap:event>
+    <cap:severity>Extreme</cap:severity>
+    <cap:areaDesc>Johnson, KS</cap:areaDesc>
+    <cap:geocode>
+      <valueName>UGC</valueName>
+      <value>KSC091</value>
+    </cap:geocode>
+  </entry>
+  <entry>
+    <id>urn:oid:2</id>
+    <title>Frost Advisory &amp; more</title>
+    <cap:event>Frost Advisory</cap:event>
+    <cap:severity>Minor</cap:severity>
+    <cap:areaDesc>Douglas, KS</cap:areaDesc>
+  </entry>
+</feed>"#;
+
+    const CAP_ALERT: &str = r#"<alert xmlns="urn:oasis:names:tc:emergency:cap:1.2">
+  <identifier>KSTO1055887203</identifier>
+  <info>
+    <event>SEVERE THUNDERSTORM</event>
+    <severity>Severe</severity>
+    <headline>SEVERE THUNDERSTORM WARNING</headline>
+    <area><areaDesc>EXTREME NORTH SACRAMENTO COUNTY</areaDesc></area>
+  </info>
+  <info>
+    <event>ORAGE VIOLENT</event>
+  </info>
+</alert>"#;
+
+    #[test]
+    fn parse_atom_feed() {
+        let alerts = CapAlert::parse(ATOM_FEED).unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].id, "urn:oid:1");
+        assert_eq!(alerts[0].event, "Tornado Warning");
+        assert_eq!(alerts[0].severity, Severity::Extreme);
+        assert_eq!(alerts[0].geocodes, ["KSC091"]);
+        assert_eq!(alerts[1].headline, "Frost Advisory & more");
+        assert!(alerts[0].covers("KSC091"));
+        assert!(alerts[1].covers("douglas"));
+    }
+
+    #[test]
+    fn parse_cap_alert() {
+        let alerts = CapAlert::parse(CAP_ALERT).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, "KSTO1055887203");
+        assert_eq!(alerts[0].event, "SEVERE THUNDERSTORM");
+        assert_eq!(alerts[0].severity, Severity::Severe);
+        assert_eq!(alerts[0].area_desc, "EXTREME NORTH SACRAMENTO COUNTY");
+    }
+
+    #[test]
+    fn update_preempts_scenes() {
+        let font = LedFont::new_from_buffer(crate::font::test::TINY_BDF.as_bytes()).unwrap();
+        let mut widget = WeatherAlerts::new("http://localhost", Arc::new(font)).area("KSC091");
+        let mut scenes = SceneManager::new();
+        let alerts = CapAlert::parse(ATOM_FEED).unwrap();
+
+        assert_eq!(widget.update(&mut scenes, &alerts), 1);
+        assert_eq!(scenes.current_priority(), Some(Priority::Emergency));
+        assert_eq!(widget.update(&mut scenes, &alerts), 1);
+        assert_eq!(widget.update(&mut scenes, &[]), 0);
+        assert_eq!(scenes.current(), None);
+    }
+}
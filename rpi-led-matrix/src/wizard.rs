@@ -0,0 +1,467 @@
+//! Walks through the panel setup on the device and saves the settings found to a config file.
+use std::path::Path;
+
+use crate::{LedCanvas, LedColor, LedMatrix, LedMatrixOptions, LedRuntimeOptions};
+
+/// The panel settings found by the [`Wizard`], saved to and loaded from a config file.
+///
+/// The file holds one `key = value` line per setting, named like the command line arguments
+/// of the [`args`](crate::args) module, and may contain `#` comments:
+///
+/// ```text
+/// # written by the rpi-led-matrix wizard
+/// gpio-mapping = adafruit-hat
+/// rows = 32
+/// cols = 64
+/// chain = 1
+/// parallel = 1
+/// multiplexing = 0
+/// row-addr-type = 0
+/// pixel-mapper =
+/// panel-type = FM6126A
+/// slowdown-gpio = 2
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanelConfig {
+    /// The GPIO mapping, see [`LedMatrixOptions::HARDWARE_MAPPINGS`]
+    pub hardware_mapping: String,
+    /// Rows of a single panel
+    pub rows: u32,
+    /// Columns of a single panel
+    pub cols: u32,
+    /// Number of daisy-chained panels
+    pub chain_length: u32,
+    /// Number of parallel chains
+    pub parallel: u32,
+    /// The multiplexing type, indexing [`LedMatrixOptions::MULTIPLEXINGS`]
+    pub multiplexing: u32,
+    /// The row address type, indexing [`LedMatrixOptions::ROW_ADDRESS_TYPES`]
+    pub row_addr_type: u32,
+    /// Semicolon-separated pixel mappers, see [`LedMatrixOptions::PIXEL_MAPPERS`]
+    pub pixel_mapper: String,
+    /// The special panel type, if any, see [`LedMatrixOptions::PANEL_TYPES`]
+    pub panel_type: String,
+    /// The GPIO slowdown
+    pub gpio_slowdown: u32,
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self {
+        Self {
+            hardware_mapping: "regular".to_string(),
+            rows: 32,
+            cols: 32,
+            chain_length: 1,
+            parallel: 1,
+            multiplexing: 0,
+            row_addr_type: 0,
+            pixel_mapper: String::new(),
+            panel_type: String::new(),
+            gpio_slowdown: 1,
+        }
+    }
+}
+
+impl PanelConfig {
+    /// Parses the contents of a config file. Settings missing from it keep their defaults.
+    ///
+    /// # Errors
+    /// If a line isn't a `key = value` pair of a known setting, or a number doesn't parse.
+    pub fn parse(config: &str) -> Result<Self, &'static str> {
+        let mut parsed = Self::default();
+        for line in config.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or("Expected a key = value line")?;
+            let value = value.trim();
+            let number = || value.parse::<u32>().map_err(|_| "Invalid number in config");
+            match key.trim() {
+                "gpio-mapping" => parsed.hardware_mapping = value.to_string(),
+                "rows" => parsed.rows = number()?,
+                "cols" => parsed.cols = number()?,
+                "chain" => parsed.chain_length = number()?,
+                "parallel" => parsed.parallel = number()?,
+                "multiplexing" => parsed.multiplexing = number()?,
+                "row-addr-type" => parsed.row_addr_type = number()?,
+                "pixel-mapper" => parsed.pixel_mapper = value.to_string(),
+                "panel-type" => parsed.panel_type = value.to_string(),
+                "slowdown-gpio" => parsed.gpio_slowdown = number()?,
+                _ => return Err("Unknown setting in config"),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Loads a config file written by [`save`](Self::save).
+    ///
+    /// # Errors
+    /// If the file can't be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, &'static str> {
+        let config = std::fs::read_to_string(path).map_err(|_| "Couldn't read config file")?;
+        Self::parse(&config)
+    }
+
+    /// Writes the config file.
+    ///
+    /// # Errors
+    /// If the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        std::fs::write(path, self.to_string()).map_err(|_| "Couldn't write config file")
+    }
+
+    /// The settings as command line arguments understood by the [`args`](crate::args) module.
+    ///
+    /// Settings at their [defaults](Self::default) are left out, which the module falls back to
+    /// as well, so the default config has no arguments at all.
+    #[must_use]
+    pub fn to_args(&self) -> Vec<String> {
+        self.settings()
+            .into_iter()
+            .zip(Self::default().settings())
+            .filter(|(setting, default)| setting != default)
+            .flat_map(|((key, value), _)| [format!("--{key}"), value])
+            .collect()
+    }
+
+    /// The options to create a [`LedMatrix`] with these settings.
+    #[must_use]
+    pub fn options(&self) -> (LedMatrixOptions, LedRuntimeOptions) {
+        let mut options = LedMatrixOptions::new();
+        options.set_hardware_mapping(&self.hardware_mapping);
+        options.set_rows(self.rows);
+        options.set_cols(self.cols);
+        options.set_chain_length(self.chain_length);
+        options.set_parallel(self.parallel);
+        options.set_multiplexing(self.multiplexing);
+        options.set_row_addr_type(self.row_addr_type);
+        options.set_pixel_mapper_config(&self.pixel_mapper);
+        options.set_panel_type(&self.panel_type);
+        let mut rt_options = LedRuntimeOptions::new();
+        rt_options.set_gpio_slowdown(self.gpio_slowdown);
+        (options, rt_options)
+    }
+
+    fn settings(&self) -> [(&'static str, String); 10] {
+        [
+            ("gpio-mapping", self.hardware_mapping.clone()),
+            ("rows", self.rows.to_string()),
+            ("cols", self.cols.to_string()),
+            ("chain", self.chain_length.to_string()),
+            ("parallel", self.parallel.to_string()),
+            ("multiplexing", self.multiplexing.to_string()),
+            ("row-addr-type", self.row_addr_type.to_string()),
+            ("pixel-mapper", self.pixel_mapper.clone()),
+            ("panel-type", self.panel_type.clone()),
+            ("slowdown-gpio", self.gpio_slowdown.to_string()),
+        ]
+    }
+}
+
+impl std::fmt::Display for PanelConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# written by the rpi-led-matrix wizard")?;
+        for (key, value) in self.settings() {
+            writeln!(f, "{key} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The user's reaction to a candidate setting shown by the [`Wizard`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Answer {
+    /// The test pattern looks right, keep the setting and go on
+    Accept,
+    /// Try the next candidate
+    Next,
+    /// Go back to the previous candidate
+    Previous,
+    /// Stop the wizard without a result
+    Quit,
+}
+
+/// A setting the [`Wizard`] walks through, in the order they are asked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// The GPIO mapping, i.e. the HAT or wiring used
+    HardwareMapping,
+    /// Rows and columns of a single panel
+    PanelSize,
+    /// The special panel type needing initialization
+    PanelType,
+    /// The row address type
+    RowAddressType,
+    /// The multiplexing type
+    Multiplexing,
+    /// The GPIO slowdown
+    GpioSlowdown,
+}
+
+impl Step {
+    /// All steps, in the order the wizard asks them.
+    pub const ALL: [Self; 6] = [
+        Self::HardwareMapping,
+        Self::PanelSize,
+        Self::PanelType,
+        Self::RowAddressType,
+        Self::Multiplexing,
+        Self::GpioSlowdown,
+    ];
+
+    const PANEL_SIZES: [(u32, u32); 6] =
+        [(16, 32), (32, 32), (32, 64), (64, 64), (64, 128), (8, 32)];
+
+    /// The number of candidates of the setting.
+    #[must_use]
+    pub const fn candidates(self) -> usize {
+        match self {
+            Self::HardwareMapping => LedMatrixOptions::HARDWARE_MAPPINGS.len(),
+            Self::PanelSize => Self::PANEL_SIZES.len(),
+            // no special panel type comes first
+            Self::PanelType => LedMatrixOptions::PANEL_TYPES.len() + 1,
+            Self::RowAddressType => LedMatrixOptions::ROW_ADDRESS_TYPES.len(),
+            Self::Multiplexing => LedMatrixOptions::MULTIPLEXINGS.len(),
+            Self::GpioSlowdown => 5,
+        }
+    }
+
+    /// Changes `config` to the candidate at `index`.
+    pub fn apply(self, config: &mut PanelConfig, index: usize) {
+        match self {
+            Self::HardwareMapping => {
+                config.hardware_mapping = LedMatrixOptions::HARDWARE_MAPPINGS[index].to_string();
+            }
+            Self::PanelSize => (config.rows, config.cols) = Self::PANEL_SIZES[index],
+            Self::PanelType => {
+                config.panel_type = index
+                    .checked_sub(1)
+                    .map_or("", |i| LedMatrixOptions::PANEL_TYPES[i])
+                    .to_string();
+            }
+            Self::RowAddressType => config.row_addr_type = index as u32,
+            Self::Multiplexing => config.multiplexing = index as u32,
+            Self::GpioSlowdown => config.gpio_slowdown = index as u32,
+        }
+    }
+
+    /// The index of the candidate `config` is set to, or the first one if it's none of them.
+    #[must_use]
+    pub fn current(self, config: &PanelConfig) -> usize {
+        (0..self.candidates())
+            .find(|&index| {
+                let mut candidate = config.clone();
+                self.apply(&mut candidate, index);
+                candidate == *config
+            })
+            .unwrap_or_default()
+    }
+
+    /// Describes the candidate at `index`, e.g. "multiplexing: Checkered (3/17)".
+    #[must_use]
+    pub fn describe(self, index: usize) -> String {
+        let (name, value) = match self {
+            Self::HardwareMapping => (
+                "GPIO mapping",
+                LedMatrixOptions::HARDWARE_MAPPINGS[index].to_string(),
+            ),
+            Self::PanelSize => {
+                let (rows, cols) = Self::PANEL_SIZES[index];
+                ("panel size", format!("{cols}x{rows}"))
+            }
+            Self::PanelType => (
+                "panel type",
+                index
+                    .checked_sub(1)
+                    .map_or("none", |i| LedMatrixOptions::PANEL_TYPES[i])
+                    .to_string(),
+            ),
+            Self::RowAddressType => (
+                "row address type",
+                LedMatrixOptions::ROW_ADDRESS_TYPES[index].to_string(),
+            ),
+            Self::Multiplexing => (
+                "multiplexing",
+                LedMatrixOptions::MULTIPLEXINGS[index].to_string(),
+            ),
+            Self::GpioSlowdown => ("GPIO slowdown", index.to_string()),
+        };
+        format!("{name}: {value} ({}/{})", index + 1, self.candidates())
+    }
+}
+
+/// Walks through the panel setup on the device, showing a test pattern for every candidate
+/// setting until the user confirms the one that looks right.
+///
+/// How the user answers is up to the caller, e.g. reading the keyboard or polling buttons, so
+/// the wizard runs headless as well. See the `wizard` example, which writes the result to a
+/// config file for [`PanelConfig::load`].
+///
+/// ```no_run
+/// use rpi_led_matrix::wizard::{Answer, PanelConfig, Wizard};
+/// use std::path::Path;
+/// let config = Wizard::new(PanelConfig::default())
+///     .run(|prompt| {
+///         println!("{prompt} - does the pattern look right? [y/n/p/q]");
+///         let mut line = String::new();
+///         std::io::stdin().read_line(&mut line).unwrap();
+///         match line.trim() {
+///             "y" => Answer::Accept,
+///             "p" => Answer::Previous,
+///             "q" => Answer::Quit,
+///             _ => Answer::Next,
+///         }
+///     })
+///     .unwrap();
+/// config.save(Path::new("matrix.conf")).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Wizard {
+    config: PanelConfig,
+    steps: Vec<Step>,
+}
+
+impl Wizard {
+    /// Creates a wizard asking all [`Step`]s, starting from the candidates `config` is set to.
+    #[must_use]
+    pub fn new(config: PanelConfig) -> Self {
+        Self {
+            config,
+            steps: Step::ALL.to_vec(),
+        }
+    }
+
+    /// Sets the steps to ask, in order
+    #[must_use]
+    pub fn steps(mut self, steps: &[Step]) -> Self {
+        self.steps = steps.to_vec();
+        self
+    }
+
+    /// Runs the wizard, showing every candidate on the panel and passing its description to
+    /// `ask` until a candidate is accepted for every step.
+    ///
+    /// # Errors
+    /// If the user quits, or the matrix can't be created for a candidate.
+    pub fn run(mut self, mut ask: impl FnMut(&str) -> Answer) -> Result<PanelConfig, &'static str> {
+        for step in self.steps.clone() {
+            let mut index = step.current(&self.config);
+            loop {
+                step.apply(&mut self.config, index);
+                let (options, mut rt_options) = self.config.options();
+                // the matrix is created again for every candidate, which needs root
+                rt_options.set_drop_privileges(false);
+                let matrix = LedMatrix::new(Some(options), Some(rt_options))?;
+                let mut canvas = matrix.offscreen_canvas();
+                draw_test_pattern(&mut canvas);
+                let _canvas = matrix.swap(canvas);
+                let count = step.candidates();
+                match ask(&step.describe(index)) {
+                    Answer::Accept => break,
+                    Answer::Next => index = (index + 1) % count,
+                    Answer::Previous => index = (index + count - 1) % count,
+                    Answer::Quit => return Err("Wizard quit"),
+                }
+            }
+        }
+        Ok(self.config)
+    }
+}
+
+/// Draws a pattern showing whether the panel is set up right: a white border around the whole
+/// canvas, a diagonal from the top left corner, and the corners marked red (top left), green
+/// (top right), blue (bottom left) and white (bottom right).
+///
+/// With wrong settings the border breaks up, the diagonal jumps or the corners end up in the
+/// wrong place.
+pub fn draw_test_pattern(canvas: &mut LedCanvas) {
+    let (width, height) = canvas.canvas_size();
+    let (right, bottom) = (width - 1, height - 1);
+    let white = LedColor {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+    let color = |red, green, blue| LedColor { red, green, blue };
+    canvas.clear();
+    canvas.draw_polygon(&[(0, 0), (right, 0), (right, bottom), (0, bottom)], &white);
+    let side = width.min(height);
+    canvas.draw_line(0, 0, side - 1, side - 1, &color(255, 255, 0));
+    let size = (side / 4).max(1);
+    for (x, y, corner) in [
+        (1, 1, color(255, 0, 0)),
+        (right - size, 1, color(0, 255, 0)),
+        (1, bottom - size, color(0, 0, 255)),
+        (right - size, bottom - size, white),
+    ] {
+        canvas.set_iter((0..size).flat_map(|dy| (0..size).map(move |dx| (x + dx, y + dy, corner))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file() {
+        let config = PanelConfig {
+            hardware_mapping: "adafruit-hat".to_string(),
+            cols: 64,
+            panel_type: "FM6126A".to_string(),
+            gpio_slowdown: 2,
+            ..PanelConfig::default()
+        };
+        assert_eq!(PanelConfig::parse(&config.to_string()), Ok(config.clone()));
+        assert_eq!(
+            PanelConfig::parse("rows = 16 # half height\n\n"),
+            Ok(PanelConfig {
+                rows: 16,
+                ..PanelConfig::default()
+            })
+        );
+        assert!(PanelConfig::parse("rows = many").is_err());
+        assert!(PanelConfig::parse("colour = red").is_err());
+        assert_eq!(
+            config.to_args(),
+            [
+                "--gpio-mapping",
+                "adafruit-hat",
+                "--cols",
+                "64",
+                "--panel-type",
+                "FM6126A",
+                "--slowdown-gpio",
+                "2"
+            ]
+        );
+    }
+
+    #[test]
+    fn default_args() {
+        assert!(PanelConfig::default().to_args().is_empty());
+        let config = PanelConfig {
+            pixel_mapper: "Rotate:90".to_string(),
+            ..PanelConfig::default()
+        };
+        assert_eq!(config.to_args(), ["--pixel-mapper", "Rotate:90"]);
+    }
+
+    #[test]
+    fn steps() {
+        let mut config = PanelConfig::default();
+        for step in Step::ALL {
+            for index in 0..step.candidates() {
+                step.apply(&mut config, index);
+                assert_eq!(step.current(&config), index, "{}", step.describe(index));
+            }
+        }
+        assert_eq!(
+            Step::Multiplexing.describe(2),
+            "multiplexing: Checkered (3/17)"
+        );
+        assert_eq!(Step::PanelType.describe(0), "panel type: none (1/3)");
+        Step::PanelSize.apply(&mut config, 2);
+        assert_eq!((config.rows, config.cols), (32, 64));
+    }
+}